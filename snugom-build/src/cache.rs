@@ -0,0 +1,188 @@
+//! Fingerprint cache that lets unchanged files skip re-parsing between builds.
+
+use crate::scanner::EntityInfo;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Bumped whenever the cached metadata changes shape or meaning.
+const CACHE_FORMAT: u32 = 2;
+
+/// Entities discovered per file, keyed by path, with the fingerprint they were parsed from.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    format: u32,
+    generator_version: String,
+    files: BTreeMap<PathBuf, CachedFile>,
+    /// Files looked up during this run; the rest are dropped on save
+    #[serde(skip)]
+    seen: BTreeSet<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFile {
+    len: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    content_hash: u64,
+    module_path: String,
+    entities: Vec<EntityInfo>,
+}
+
+impl ScanCache {
+    /// Load the cache at `path`, starting empty when it is missing, unreadable, or was written
+    /// by a different version of the generator.
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|cache| cache.format == CACHE_FORMAT && cache.generator_version == env!("CARGO_PKG_VERSION"))
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to `path`, keeping only files looked up since it was loaded.
+    pub fn save(mut self, path: &Path) -> Result<()> {
+        let seen = std::mem::take(&mut self.seen);
+        self.files.retain(|file, _| seen.contains(file));
+        self.format = CACHE_FORMAT;
+        self.generator_version = env!("CARGO_PKG_VERSION").to_string();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let bytes = serde_json::to_vec(&self).context("Failed to serialize scan cache")?;
+        // Skip identical writes so the cache file's mtime only moves when something changed
+        if fs::read(path).is_ok_and(|existing| existing == bytes) {
+            return Ok(());
+        }
+        fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Entities of `file_path`, from the cache when its size and mtime (or failing that, its
+    /// content hash) are unchanged, otherwise from `parse`.
+    pub fn entities(
+        &mut self,
+        file_path: &Path,
+        module_path: &str,
+        parse: impl FnOnce(&str) -> Result<Vec<EntityInfo>>,
+    ) -> Result<Vec<EntityInfo>> {
+        let key = file_path.to_path_buf();
+        self.seen.insert(key.clone());
+
+        let metadata = fs::metadata(file_path).with_context(|| format!("Failed to read {}", file_path.display()))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let (len, modified_secs, modified_nanos) = (metadata.len(), modified.as_secs(), modified.subsec_nanos());
+
+        if let Some(cached) = self.files.get(&key)
+            && cached.module_path == module_path
+            && cached.len == len
+            && cached.modified_secs == modified_secs
+            && cached.modified_nanos == modified_nanos
+        {
+            return Ok(cached.entities.clone());
+        }
+
+        let content = fs::read_to_string(file_path).with_context(|| format!("Failed to read {}", file_path.display()))?;
+        let content_hash = hash_content(&content);
+        let entities = match self.files.get(&key) {
+            Some(cached) if cached.module_path == module_path && cached.content_hash == content_hash => {
+                cached.entities.clone()
+            }
+            _ => parse(&content)?,
+        };
+        self.files.insert(
+            key,
+            CachedFile {
+                len,
+                modified_secs,
+                modified_nanos,
+                content_hash,
+                module_path: module_path.to_string(),
+                entities: entities.clone(),
+            },
+        );
+        Ok(entities)
+    }
+}
+
+fn hash_content(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn entity(name: &str) -> EntityInfo {
+        EntityInfo {
+            name: name.to_string(),
+            module_path: "crate::models".to_string(),
+            sort_fields: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_reuses_entities_of_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("models.rs");
+        let cache_path = dir.path().join("cache.json");
+        fs::write(&source, "struct Guild;").unwrap();
+
+        let parses = Cell::new(0);
+        let parse = |_: &str| {
+            parses.set(parses.get() + 1);
+            Ok(vec![entity("Guild")])
+        };
+
+        let mut cache = ScanCache::load(&cache_path);
+        assert_eq!(cache.entities(&source, "crate::models", parse).unwrap()[0].name, "Guild");
+        cache.save(&cache_path).unwrap();
+        assert_eq!(parses.get(), 1);
+
+        let mut cache = ScanCache::load(&cache_path);
+        assert_eq!(cache.entities(&source, "crate::models", parse).unwrap()[0].name, "Guild");
+        assert_eq!(parses.get(), 1);
+
+        // A different module path for the same file is a miss
+        cache.entities(&source, "other::models", parse).unwrap();
+        assert_eq!(parses.get(), 2);
+
+        fs::write(&source, "struct Guild; struct Member;").unwrap();
+        cache.entities(&source, "other::models", parse).unwrap();
+        assert_eq!(parses.get(), 3);
+    }
+
+    #[test]
+    fn test_save_drops_files_not_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.rs");
+        let second = dir.path().join("second.rs");
+        let cache_path = dir.path().join("cache.json");
+        fs::write(&first, "").unwrap();
+        fs::write(&second, "").unwrap();
+
+        let mut cache = ScanCache::load(&cache_path);
+        cache.entities(&first, "crate::first", |_| Ok(Vec::new())).unwrap();
+        cache.entities(&second, "crate::second", |_| Ok(Vec::new())).unwrap();
+        cache.save(&cache_path).unwrap();
+
+        let mut cache = ScanCache::load(&cache_path);
+        cache.entities(&first, "crate::first", |_| Ok(Vec::new())).unwrap();
+        cache.save(&cache_path).unwrap();
+
+        let cache = ScanCache::load(&cache_path);
+        assert!(cache.files.contains_key(&first));
+        assert!(!cache.files.contains_key(&second));
+    }
+}
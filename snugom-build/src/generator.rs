@@ -1,17 +1,24 @@
 //! Code generator for SnugomClient.
 
-use crate::scanner::{EntityInfo, scan_directory};
+use crate::cache::ScanCache;
+use crate::scanner::{EntityInfo, PathFilter, scan_directory};
+use crate::workspace::workspace_members;
 use anyhow::{Context, Result};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
 /// Builder for configuring and running the SnugomClient generator.
 pub struct ClientGenerator {
     scan_paths: Vec<PathBuf>,
-    output_file: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    scan_workspace: bool,
+    cache: Option<PathBuf>,
+    use_cache: bool,
+    output: Output,
     crate_name: String,
     client_name: String,
 }
@@ -21,7 +28,12 @@ impl ClientGenerator {
     pub fn new() -> Self {
         Self {
             scan_paths: Vec::new(),
-            output_file: PathBuf::from("src/generated/snugom_client.rs"),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            scan_workspace: false,
+            cache: None,
+            use_cache: true,
+            output: Output::SourceTree(PathBuf::from("src/generated/snugom_client.rs")),
             crate_name: "crate".to_string(),
             client_name: "SnugomClient".to_string(),
         }
@@ -35,11 +47,74 @@ impl ClientGenerator {
         self
     }
 
+    /// Only scan files matching this glob pattern.
+    ///
+    /// Patterns are matched against paths relative to each scanned directory (e.g. `models/**`).
+    /// Can be called multiple times; a file matching any include pattern is scanned. Without
+    /// include patterns every `.rs` file is scanned.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Skip files matching this glob pattern, even when they match an include pattern.
+    ///
+    /// Can be called multiple times.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Also scan the `src/` directory of every member of the enclosing Cargo workspace.
+    ///
+    /// Entities from the crate running the build script import through `crate_name`; those from
+    /// other members import through their library name, so those members must be dependencies
+    /// of this crate and export their entities publicly. When run from a build script, a
+    /// `cargo:rerun-if-changed` line is printed for each member's `src/`.
+    pub fn scan_workspace(mut self) -> Self {
+        self.scan_workspace = true;
+        self
+    }
+
+    /// Set where the scan cache is kept.
+    ///
+    /// The cache records a fingerprint and the discovered entities of every scanned file, so
+    /// later runs only re-parse files that changed.
+    ///
+    /// Default: `snugom-build-cache.json` in `OUT_DIR` when run from a build script, otherwise
+    /// `target/snugom-build/cache.json`
+    pub fn cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache = Some(path.into());
+        self
+    }
+
+    /// Parse every file on every run instead of using the scan cache.
+    pub fn disable_cache(mut self) -> Self {
+        self.use_cache = false;
+        self
+    }
+
     /// Set the output file path for the generated code.
     ///
     /// Default: `src/generated/snugom_client.rs`
     pub fn output_file(mut self, path: impl Into<PathBuf>) -> Self {
-        self.output_file = path.into();
+        self.output = Output::SourceTree(path.into());
+        self
+    }
+
+    /// Write the generated code to `OUT_DIR/{file_name}` instead of the source tree.
+    ///
+    /// Must be run from a build script. No `mod.rs` is written; include the file from a module
+    /// of your crate instead:
+    ///
+    /// ```ignore
+    /// mod snugom_client {
+    ///     include!(concat!(env!("OUT_DIR"), "/snugom_client.rs"));
+    /// }
+    /// pub use snugom_client::SnugomClient;
+    /// ```
+    pub fn output_out_dir(mut self, file_name: impl Into<String>) -> Self {
+        self.output = Output::OutDir(file_name.into());
         self
     }
 
@@ -64,21 +139,53 @@ impl ClientGenerator {
     /// This scans all configured paths, discovers entities, and writes
     /// the generated SnugomClient to the output file.
     pub fn run(self) -> Result<()> {
+        let filter = PathFilter::new(&self.include, &self.exclude)?;
+
+        // Each scanned directory with the crate path its entities are imported through
+        let mut scan_roots: Vec<(PathBuf, String)> =
+            self.scan_paths.iter().map(|path| (path.clone(), self.crate_name.clone())).collect();
+        if self.scan_workspace {
+            let manifest_dir = match std::env::var_os("CARGO_MANIFEST_DIR") {
+                Some(dir) => PathBuf::from(dir),
+                None => std::env::current_dir()?,
+            };
+            let in_build_script = std::env::var_os("OUT_DIR").is_some();
+            for member in workspace_members(&manifest_dir, &self.crate_name)? {
+                if in_build_script {
+                    println!("cargo:rerun-if-changed={}", member.src_dir.display());
+                }
+                scan_roots.push((member.src_dir, member.crate_name));
+            }
+        }
+
         // Default to scanning "src/" if no paths specified
-        let scan_paths = if self.scan_paths.is_empty() {
-            vec![PathBuf::from("src/")]
-        } else {
-            self.scan_paths
-        };
+        if scan_roots.is_empty() {
+            scan_roots.push((PathBuf::from("src/"), self.crate_name.clone()));
+        }
+
+        let cache_path = self.use_cache.then(|| {
+            self.cache.clone().unwrap_or_else(|| match std::env::var_os("OUT_DIR") {
+                Some(out_dir) => PathBuf::from(out_dir).join("snugom-build-cache.json"),
+                None => PathBuf::from("target/snugom-build/cache.json"),
+            })
+        });
+        let mut cache = cache_path.as_deref().map(ScanCache::load).unwrap_or_default();
 
         // Discover all entities
         let mut all_entities = Vec::new();
-        for path in &scan_paths {
-            let entities =
-                scan_directory(path, &self.crate_name).with_context(|| format!("Failed to scan {}", path.display()))?;
+        for (path, crate_name) in &scan_roots {
+            let entities = scan_directory(path, crate_name, &filter, &mut cache)
+                .with_context(|| format!("Failed to scan {}", path.display()))?;
             all_entities.extend(entities);
         }
 
+        // The cache only saves work, so a failure to persist it is not fatal
+        if let Some(cache_path) = &cache_path
+            && let Err(err) = cache.save(cache_path)
+        {
+            eprintln!("snugom-build: {err:#}");
+        }
+
         // Deduplicate by name (in case same entity found multiple times)
         let mut seen = std::collections::HashSet::new();
         all_entities.retain(|e| seen.insert(e.name.clone()));
@@ -87,34 +194,41 @@ impl ClientGenerator {
         all_entities.sort_by(|a, b| a.name.cmp(&b.name));
 
         // Generate the code
-        let code = generate_client_code(&self.client_name, &all_entities)?;
+        let (output_file, included) = match &self.output {
+            Output::SourceTree(path) => (path.clone(), false),
+            Output::OutDir(file_name) => {
+                let out_dir = std::env::var_os("OUT_DIR")
+                    .context("output_out_dir requires OUT_DIR; run the generator from a build script")?;
+                (PathBuf::from(out_dir).join(file_name), true)
+            }
+        };
+        let code = generate_client_code(&self.client_name, &all_entities, included)?;
 
         // Ensure output directory exists
-        if let Some(parent) = self.output_file.parent() {
+        if let Some(parent) = output_file.parent() {
             fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {}", parent.display()))?;
         }
 
         // Only write if content has changed (avoids unnecessary recompilation)
-        let should_write = match fs::read_to_string(&self.output_file) {
+        let should_write = match fs::read_to_string(&output_file) {
             Ok(existing) => existing != code,
             Err(_) => true, // File doesn't exist, need to write
         };
 
         if should_write {
-            fs::write(&self.output_file, &code)
-                .with_context(|| format!("Failed to write {}", self.output_file.display()))?;
+            fs::write(&output_file, &code)
+                .with_context(|| format!("Failed to write {}", output_file.display()))?;
             eprintln!(
                 "snugom-build: Generated {} with {} entities",
-                self.output_file.display(),
+                output_file.display(),
                 all_entities.len()
             );
         }
 
         // Generate mod.rs to expose the client module
-        if let Some(parent) = self.output_file.parent() {
+        if let Some(parent) = output_file.parent().filter(|_| !included) {
             let mod_file = parent.join("mod.rs");
-            let file_stem = self
-                .output_file
+            let file_stem = output_file
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or("snugom_client");
@@ -139,6 +253,14 @@ impl ClientGenerator {
     }
 }
 
+/// Where the generated client is written.
+enum Output {
+    /// A file in the source tree, exposed through a sibling `mod.rs`
+    SourceTree(PathBuf),
+    /// A file name under `OUT_DIR`, pulled in with `include!`
+    OutDir(String),
+}
+
 impl Default for ClientGenerator {
     fn default() -> Self {
         Self::new()
@@ -146,11 +268,14 @@ impl Default for ClientGenerator {
 }
 
 /// Generate the SnugomClient code.
-fn generate_client_code(client_name: &str, entities: &[EntityInfo]) -> Result<String> {
+///
+/// Code destined for `include!` carries no inner attributes or module docs, which are only
+/// allowed at the top of a module file.
+fn generate_client_code(client_name: &str, entities: &[EntityInfo], included: bool) -> Result<String> {
     let client_ident = format_ident!("{}", client_name);
 
     // Group entities by module path for imports
-    let mut imports_by_module: HashMap<String, Vec<String>> = HashMap::new();
+    let mut imports_by_module: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for entity in entities {
         imports_by_module
             .entry(entity.module_path.clone())
@@ -165,6 +290,7 @@ fn generate_client_code(client_name: &str, entities: &[EntityInfo]) -> Result<St
             let module_path: syn::Path = syn::parse_str(module).unwrap();
             let name_idents: Vec<_> = names.iter().map(|n| format_ident!("{}", n)).collect();
             quote! {
+                #[allow(unused_imports)]
                 use #module_path::{#(#name_idents),*};
             }
         })
@@ -213,15 +339,24 @@ fn generate_client_code(client_name: &str, entities: &[EntityInfo]) -> Result<St
         })
         .collect();
 
+    // Generate typed sort/filter field enums
+    let field_enums: Vec<TokenStream> = entities.iter().map(generate_field_enums).collect();
+
     // Generate the full module
-    let output = quote! {
-        //! Auto-generated SnugomClient. Do not edit manually.
-        //!
-        //! Regenerate with: `cargo build`
-        //!
-        //! Generated by snugom-build.
+    let header = if included {
+        quote! {}
+    } else {
+        quote! {
+            //! Auto-generated SnugomClient. Do not edit manually.
+            //!
+            //! Regenerate with: `cargo build`
+            //!
+            //! Generated by snugom-build.
+        }
+    };
 
-        #![allow(unused_imports)]
+    let output = quote! {
+        #header
 
         use ::redis::aio::ConnectionManager;
 
@@ -303,6 +438,8 @@ fn generate_client_code(client_name: &str, entities: &[EntityInfo]) -> Result<St
 
             #(#accessors)*
         }
+
+        #(#field_enums)*
     };
 
     // Format with prettyplease for readable output
@@ -310,6 +447,108 @@ fn generate_client_code(client_name: &str, entities: &[EntityInfo]) -> Result<St
     Ok(prettyplease::unparse(&syntax_tree))
 }
 
+/// Generate `{Entity}SortField` for an entity's sortable fields, parsing from `sort_by` values
+/// and converting to the `SortField` the entity's `SearchEntity` impl accepts.
+///
+/// Filter names are left to the derive, whose `{Entity}FilterField` already covers them.
+fn generate_field_enums(entity: &EntityInfo) -> TokenStream {
+    if entity.sort_fields.is_empty() {
+        return TokenStream::new();
+    }
+    let entity_ident = format_ident!("{}", entity.name);
+    let enum_ident = format_ident!("{}SortField", entity.name);
+    let field_enum = generate_field_enum(&enum_ident, &entity.sort_fields, "Unsupported sort field");
+    let doc = format!("Sortable fields of [`{}`], as named by the `sort_by` query parameter.", entity.name);
+    let undeclared = format!("`{{}}` is not a sortable field of `{}`", entity.name);
+    quote! {
+        #[doc = #doc]
+        #field_enum
+
+        impl ::std::convert::TryFrom<#enum_ident> for ::snugom::search::SortField {
+            type Error = ::snugom::errors::RepoError;
+
+            /// Fails when the entity no longer declares the field sortable, e.g. a stale generated file.
+            fn try_from(field: #enum_ident) -> Result<Self, Self::Error> {
+                use ::snugom::search::SearchEntity;
+                <#entity_ident as SearchEntity>::allowed_sorts()
+                    .iter()
+                    .find(|sort| sort.name == field.as_str())
+                    .copied()
+                    .ok_or_else(|| ::snugom::errors::RepoError::InvalidRequest {
+                        message: format!(#undeclared, field.as_str()),
+                    })
+            }
+        }
+    }
+}
+
+/// A field-name enum with `ALL`, `as_str`, `FromStr`, `Display`, and serde support.
+fn generate_field_enum(enum_ident: &proc_macro2::Ident, names: &[String], unknown_message: &str) -> TokenStream {
+    let variants: Vec<_> = names.iter().map(|name| format_ident!("{}", to_pascal_case(name))).collect();
+    let message = format!("{unknown_message}: {{}}");
+    quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum #enum_ident {
+            #(#variants),*
+        }
+
+        impl #enum_ident {
+            /// Every field, in declaration order.
+            pub const ALL: &'static [Self] = &[#(Self::#variants),*];
+
+            /// The field name used in query parameters.
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    #(Self::#variants => #names),*
+                }
+            }
+        }
+
+        impl ::std::str::FromStr for #enum_ident {
+            type Err = ::snugom::errors::RepoError;
+
+            /// Parse a field name, ignoring ASCII case.
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|field| field.as_str().eq_ignore_ascii_case(value))
+                    .ok_or_else(|| ::snugom::errors::RepoError::InvalidRequest {
+                        message: format!(#message, value),
+                    })
+            }
+        }
+
+        impl ::std::fmt::Display for #enum_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for #enum_ident {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+                value.parse().map_err(::serde::de::Error::custom)
+            }
+        }
+    }
+}
+
+/// Convert snake_case (or any non-alphanumeric separated name) to PascalCase.
+fn to_pascal_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
 /// Convert PascalCase to snake_case.
 fn to_snake_case(name: &str) -> String {
     let mut result = String::new();
@@ -353,6 +592,14 @@ mod tests {
         assert_eq!(to_snake_case("HTTPRequest"), "h_t_t_p_request");
     }
 
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("created_at"), "CreatedAt");
+        assert_eq!(to_pascal_case("name"), "Name");
+        assert_eq!(to_pascal_case("owner-id"), "OwnerId");
+        assert_eq!(to_pascal_case("r#type"), "Type");
+    }
+
     #[test]
     fn test_pluralize() {
         assert_eq!(pluralize("guild"), "guilds");
@@ -360,4 +607,42 @@ mod tests {
         assert_eq!(pluralize("category"), "categories");
         assert_eq!(pluralize("key"), "keys");
     }
+
+    #[test]
+    fn test_generate_client_code_is_deterministic() {
+        let entities = vec![
+            EntityInfo {
+                name: "Guild".to_string(),
+                module_path: "crate::guild".to_string(),
+                sort_fields: vec!["created_at".to_string(), "name".to_string()],
+            },
+            EntityInfo {
+                name: "Member".to_string(),
+                module_path: "crate::member".to_string(),
+                sort_fields: Vec::new(),
+            },
+            EntityInfo {
+                name: "Role".to_string(),
+                module_path: "crate::guild".to_string(),
+                sort_fields: Vec::new(),
+            },
+        ];
+        let code = generate_client_code("SnugomClient", &entities, false).unwrap();
+        assert_eq!(code, generate_client_code("SnugomClient", &entities, false).unwrap());
+        let guild_import = code.find("use crate::guild::{Guild, Role};").unwrap();
+        let member_import = code.find("use crate::member::Member;").unwrap();
+        assert!(guild_import < member_import);
+        assert!(code.starts_with("//!"));
+
+        let included = generate_client_code("SnugomClient", &entities, true).unwrap();
+        assert!(!included.contains("//!"));
+        assert!(!included.contains("#!["));
+        assert!(included.contains("pub fn guilds(&self)"));
+
+        assert!(code.contains("pub enum GuildSortField {\n    CreatedAt,\n    Name,\n}"));
+        assert!(code.contains("Self::CreatedAt => \"created_at\""));
+        assert!(code.contains("TryFrom<GuildSortField> for ::snugom::search::SortField"));
+        assert!(!code.contains("FilterField"));
+        assert!(!code.contains("MemberSortField"));
+    }
 }
@@ -3,6 +3,11 @@
 //! This crate scans your source files for `#[derive(SnugomEntity)]` structs
 //! and generates a `SnugomClient` with typed accessor methods.
 //!
+//! For each entity with sortable fields it also emits a `{Entity}SortField` enum naming them.
+//! It parses from `sort_by` values (`FromStr` and serde), and converts with `TryFrom` to the
+//! `SortField` the entity's search accepts. Filter names come from the derive's own
+//! `{Entity}FilterField` under the `utoipa` feature.
+//!
 //! # Example
 //!
 //! In your `build.rs`:
@@ -18,9 +23,44 @@
 //!     println!("cargo:rerun-if-changed=src/");
 //! }
 //! ```
+//!
+//! Entities spread across a workspace can be collected into one client, optionally
+//! narrowed with glob patterns:
+//!
+//! ```ignore
+//! snugom_build::generate_client()
+//!     .scan_workspace()
+//!     .include("**/models/**")
+//!     .exclude("**/legacy/**")
+//!     .output_file("src/generated/snugom_client.rs")
+//!     .run()
+//!     .expect("Failed to generate SnugomClient");
+//! ```
+//!
+//! To keep generated code out of the source tree, write it to `OUT_DIR` and include it:
+//!
+//! ```ignore
+//! // build.rs
+//! snugom_build::generate_client()
+//!     .output_out_dir("snugom_client.rs")
+//!     .run()
+//!     .expect("Failed to generate SnugomClient");
+//!
+//! // src/lib.rs
+//! mod snugom_client {
+//!     include!(concat!(env!("OUT_DIR"), "/snugom_client.rs"));
+//! }
+//! pub use snugom_client::SnugomClient;
+//! ```
+//!
+//! Output is deterministic: entities and imports are sorted, and the file is only rewritten
+//! when its content changes. A fingerprint cache kept under `target/` (in `OUT_DIR` for build
+//! scripts) lets each run re-parse only the files that changed since the last one.
 
+mod cache;
 mod generator;
 mod scanner;
+mod workspace;
 
 pub use generator::ClientGenerator;
 
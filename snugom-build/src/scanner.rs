@@ -1,35 +1,80 @@
 //! Source file scanner for discovering SnugomEntity derives.
 
+use crate::cache::ScanCache;
 use anyhow::{Context, Result};
-use std::fs;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use syn::{Attribute, Expr, ExprLit, Lit, Meta, MetaNameValue};
 use walkdir::WalkDir;
 
 /// Information about a discovered entity.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityInfo {
     /// The struct name (e.g., "Guild")
     pub name: String,
     /// The module path where this entity is defined (e.g., "crate::guild")
     pub module_path: String,
+    /// Names accepted by `sort_by` (the serde names), in declaration order
+    pub sort_fields: Vec<String>,
+}
+
+/// Include/exclude glob patterns, matched against file paths relative to the scanned directory.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>> {
+            patterns
+                .iter()
+                .map(|pattern| Pattern::new(pattern).with_context(|| format!("Invalid glob pattern `{pattern}`")))
+                .collect()
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// A file is scanned when it matches any include pattern (or there are none) and no exclude pattern.
+    pub fn allows(&self, relative: &Path) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches_path(relative));
+        included && !self.exclude.iter().any(|pattern| pattern.matches_path(relative))
+    }
 }
 
 /// Scan a directory recursively for Rust files containing SnugomEntity derives.
-pub fn scan_directory(path: &Path, crate_name: &str) -> Result<Vec<EntityInfo>> {
+///
+/// Files whose fingerprint matches `cache` reuse their cached entities instead of being parsed.
+pub fn scan_directory(
+    path: &Path,
+    crate_name: &str,
+    filter: &PathFilter,
+    cache: &mut ScanCache,
+) -> Result<Vec<EntityInfo>> {
     let mut entities = Vec::new();
 
     for entry in WalkDir::new(path)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| {
             e.path().extension().is_some_and(|ext| ext == "rs")
                 && !e.path().to_string_lossy().contains("/generated/")
                 && !e.path().to_string_lossy().contains("/target/")
+                && filter.allows(e.path().strip_prefix(path).unwrap_or(e.path()))
         })
     {
         let file_path = entry.path();
-        if let Ok(file_entities) = scan_file(file_path, path, crate_name) {
+        let module_path = compute_module_path(file_path, path, crate_name);
+        let scanned = cache.entities(file_path, &module_path, |content| {
+            parse_entities(content, &module_path).with_context(|| format!("Failed to parse {}", file_path.display()))
+        });
+        if let Ok(file_entities) = scanned {
             entities.extend(file_entities);
         }
     }
@@ -37,22 +82,16 @@ pub fn scan_directory(path: &Path, crate_name: &str) -> Result<Vec<EntityInfo>>
     Ok(entities)
 }
 
-/// Scan a single Rust file for SnugomEntity derives.
-fn scan_file(file_path: &Path, base_path: &Path, crate_name: &str) -> Result<Vec<EntityInfo>> {
-    let content = fs::read_to_string(file_path)
-        .with_context(|| format!("Failed to read {}", file_path.display()))?;
-
-    let syntax = syn::parse_file(&content)
-        .with_context(|| format!("Failed to parse {}", file_path.display()))?;
-
-    let module_path = compute_module_path(file_path, base_path, crate_name);
+/// Parse the source of a single Rust file for SnugomEntity derives.
+fn parse_entities(content: &str, module_path: &str) -> Result<Vec<EntityInfo>> {
+    let syntax = syn::parse_file(content)?;
 
     let mut entities = Vec::new();
 
     for item in syntax.items {
         if let syn::Item::Struct(item_struct) = item
             && has_snugom_entity_derive(&item_struct.attrs)
-                && let Some(entity) = extract_entity_info(&item_struct, &module_path) {
+                && let Some(entity) = extract_entity_info(&item_struct, module_path) {
                     entities.push(entity);
                 }
     }
@@ -105,14 +144,106 @@ fn extract_entity_info(item: &syn::ItemStruct, module_path: &str) -> Option<Enti
     }
 
     // Both collection and service are required for a valid entity
-    if has_collection && has_service {
-        Some(EntityInfo {
-            name,
-            module_path: module_path.to_string(),
-        })
-    } else {
-        None
+    if !(has_collection && has_service) {
+        return None;
+    }
+
+    // Fields are sorted under the names serde writes them with
+    let rename_all = serde_name(&item.attrs, "rename_all");
+    let mut sort_fields = Vec::new();
+    for field in &item.fields {
+        let Some(ident) = &field.ident else { continue };
+        // Timestamps are always sortable
+        let sortable = field_snugom_metas(&field.attrs).iter().any(|meta| {
+            let path = meta.path();
+            path.is_ident("sortable") || path.is_ident("created_at") || path.is_ident("updated_at")
+        });
+        if sortable {
+            sort_fields.push(serde_name(&field.attrs, "rename").unwrap_or_else(|| {
+                let rust_name = ident.to_string();
+                rename_field(rust_name.trim_start_matches("r#"), rename_all.as_deref())
+            }));
+        }
     }
+
+    Some(EntityInfo {
+        name,
+        module_path: module_path.to_string(),
+        sort_fields,
+    })
+}
+
+/// The serialize-side value of `#[serde(key = "...")]` or `#[serde(key(serialize = "..."))]`.
+fn serde_name(attrs: &[Attribute], key: &str) -> Option<String> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && meta.input.peek(syn::Token![=]) {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                found = Some(lit.value());
+            } else if meta.path.is_ident(key) && meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|nested| {
+                    let lit: syn::LitStr = nested.value()?.parse()?;
+                    if nested.path.is_ident("serialize") {
+                        found = Some(lit.value());
+                    }
+                    Ok(())
+                })?;
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let _: proc_macro2::TokenStream = content.parse()?;
+            }
+            Ok(())
+        });
+    }
+    found
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field name.
+fn rename_field(field: &str, rule: Option<&str>) -> String {
+    let pascal = || -> String {
+        field
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    };
+    match rule {
+        Some("UPPERCASE") | Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("PascalCase") => pascal(),
+        Some("camelCase") => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.replace('_', "-").to_uppercase(),
+        _ => field.to_string(),
+    }
+}
+
+/// Every item of every `#[snugom(...)]` attribute on a field.
+fn field_snugom_metas(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("snugom"))
+        .filter_map(|attr| {
+            attr.parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .collect()
 }
 
 /// Compute the module path from a file path.
@@ -176,6 +307,59 @@ mod tests {
         assert_eq!(info.module_path, "crate::guild");
     }
 
+    #[test]
+    fn test_extract_sort_fields() {
+        let code = r#"
+            #[derive(SnugomEntity)]
+            #[snugom(schema = 1, service = "guild", collection = "guilds")]
+            struct Guild {
+                #[snugom(id)]
+                id: String,
+                #[snugom(created_at)]
+                created_at: DateTime<Utc>,
+                #[snugom(validate(length(min = 1)), filterable(text), sortable)]
+                name: String,
+                #[snugom(filterable(tag), alias = "owner")]
+                owner_id: String,
+                #[snugom(filterable)]
+                #[snugom(sortable)]
+                member_count: u32,
+                description: String,
+            }
+        "#;
+
+        let syntax: syn::ItemStruct = syn::parse_str(code).unwrap();
+        let info = extract_entity_info(&syntax, "crate::guild").unwrap();
+
+        assert_eq!(info.sort_fields, vec!["created_at", "name", "member_count"]);
+    }
+
+    #[test]
+    fn test_sort_fields_use_serde_names() {
+        let code = r#"
+            #[derive(SnugomEntity)]
+            #[serde(rename_all = "camelCase")]
+            #[snugom(schema = 1, service = "guild", collection = "guilds")]
+            struct Guild {
+                #[snugom(id)]
+                id: String,
+                #[snugom(filterable(text), sortable)]
+                display_name: String,
+                #[serde(rename = "lvl")]
+                #[snugom(filterable, sortable)]
+                level: u32,
+                #[serde(rename(serialize = "members", deserialize = "member_total"))]
+                #[snugom(sortable)]
+                member_count: u32,
+            }
+        "#;
+
+        let syntax: syn::ItemStruct = syn::parse_str(code).unwrap();
+        let info = extract_entity_info(&syntax, "crate::guild").unwrap();
+
+        assert_eq!(info.sort_fields, vec!["displayName", "lvl", "members"]);
+    }
+
     #[test]
     fn test_extract_entity_info_missing_attributes() {
         let code = r#"
@@ -190,4 +374,16 @@ mod tests {
         // Should return None because collection and service are missing
         assert!(extract_entity_info(&syntax, "crate::test").is_none());
     }
+
+    #[test]
+    fn test_path_filter() {
+        let filter = PathFilter::new(&["models/**".to_string()], &["**/legacy.rs".to_string()]).unwrap();
+        assert!(filter.allows(Path::new("models/guild.rs")));
+        assert!(filter.allows(Path::new("models/nested/member.rs")));
+        assert!(!filter.allows(Path::new("models/legacy.rs")));
+        assert!(!filter.allows(Path::new("handlers/guild.rs")));
+
+        assert!(PathFilter::default().allows(Path::new("anything.rs")));
+        assert!(PathFilter::new(&["[".to_string()], &[]).is_err());
+    }
 }
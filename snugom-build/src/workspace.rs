@@ -0,0 +1,150 @@
+//! Workspace discovery for scanning entities across member crates.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+/// A workspace member whose sources are scanned.
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    /// Path prefix used to import the member's entities (e.g., "crate" or "guild_models")
+    pub crate_name: String,
+    /// The member's `src/` directory
+    pub src_dir: PathBuf,
+}
+
+/// Find the workspace enclosing `manifest_dir` and list its members.
+///
+/// The member at `manifest_dir` is imported through `current_crate` (usually `crate`); every
+/// other member through its library name, which defaults to the package name with `-` replaced
+/// by `_`. Members are sorted by path so output stays deterministic.
+pub fn workspace_members(manifest_dir: &Path, current_crate: &str) -> Result<Vec<WorkspaceMember>> {
+    let manifest_dir = fs::canonicalize(manifest_dir)
+        .with_context(|| format!("Failed to resolve {}", manifest_dir.display()))?;
+    let (root, root_manifest) = find_workspace_root(&manifest_dir)?;
+    let workspace = root_manifest
+        .get("workspace")
+        .and_then(|value| value.as_table())
+        .context("Workspace manifest has no [workspace] table")?;
+
+    let excluded: Vec<PathBuf> = string_array(workspace, "exclude").map(|path| root.join(path)).collect();
+    let mut member_dirs = Vec::new();
+    if root_manifest.contains_key("package") {
+        member_dirs.push(root.clone());
+    }
+    for pattern in string_array(workspace, "members") {
+        let pattern_path = root.join(pattern);
+        let pattern_str = pattern_path.to_string_lossy();
+        for dir in glob::glob(&pattern_str).with_context(|| format!("Invalid workspace member `{pattern}`"))? {
+            let dir = dir.with_context(|| format!("Failed to expand workspace member `{pattern}`"))?;
+            if dir.join("Cargo.toml").is_file() && !excluded.iter().any(|excluded| dir.starts_with(excluded)) {
+                member_dirs.push(dir);
+            }
+        }
+    }
+    member_dirs.sort();
+    member_dirs.dedup();
+
+    let mut members = Vec::new();
+    for dir in member_dirs {
+        let dir = fs::canonicalize(&dir).with_context(|| format!("Failed to resolve {}", dir.display()))?;
+        let crate_name = if dir == manifest_dir {
+            current_crate.to_string()
+        } else {
+            library_name(&read_manifest(&dir.join("Cargo.toml"))?)
+                .with_context(|| format!("{} has no package name", dir.join("Cargo.toml").display()))?
+        };
+        let src_dir = dir.join("src");
+        if src_dir.is_dir() {
+            members.push(WorkspaceMember { crate_name, src_dir });
+        }
+    }
+    Ok(members)
+}
+
+/// Walk up from `start` to the first manifest with a `[workspace]` table.
+fn find_workspace_root(start: &Path) -> Result<(PathBuf, Table)> {
+    for dir in start.ancestors() {
+        let manifest_path = dir.join("Cargo.toml");
+        if manifest_path.is_file() {
+            let manifest = read_manifest(&manifest_path)?;
+            if manifest.contains_key("workspace") {
+                return Ok((dir.to_path_buf(), manifest));
+            }
+        }
+    }
+    bail!("No Cargo workspace found above {}", start.display())
+}
+
+fn read_manifest(path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .parse::<Table>()
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// `[lib] name`, or the package name with `-` replaced by `_`.
+fn library_name(manifest: &Table) -> Option<String> {
+    let lib_name = manifest
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .and_then(|name| name.as_str());
+    let package_name = manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str());
+    lib_name.or(package_name).map(|name| name.replace('-', "_"))
+}
+
+fn string_array<'a>(table: &'a Table, key: &str) -> impl Iterator<Item = &'a str> {
+    table
+        .get(key)
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|value| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_workspace_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"crates/*\"]\nexclude = [\"crates/scratch\"]\n",
+        );
+        write(&root.join("app/Cargo.toml"), "[package]\nname = \"app\"\n");
+        write(&root.join("app/src/main.rs"), "");
+        write(&root.join("crates/guild-models/Cargo.toml"), "[package]\nname = \"guild-models\"\n");
+        write(&root.join("crates/guild-models/src/lib.rs"), "");
+        write(
+            &root.join("crates/users/Cargo.toml"),
+            "[package]\nname = \"users\"\n\n[lib]\nname = \"user_store\"\n",
+        );
+        write(&root.join("crates/users/src/lib.rs"), "");
+        write(&root.join("crates/scratch/Cargo.toml"), "[package]\nname = \"scratch\"\n");
+        write(&root.join("crates/scratch/src/lib.rs"), "");
+
+        let members = workspace_members(&root.join("app"), "crate").unwrap();
+        let names: Vec<&str> = members.iter().map(|member| member.crate_name.as_str()).collect();
+        assert_eq!(names, vec!["crate", "guild_models", "user_store"]);
+        assert!(members[1].src_dir.ends_with("crates/guild-models/src"));
+    }
+
+    #[test]
+    fn test_workspace_members_requires_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("Cargo.toml"), "[package]\nname = \"solo\"\n");
+        assert!(workspace_members(dir.path(), "crate").is_err());
+    }
+}
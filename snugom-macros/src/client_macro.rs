@@ -7,12 +7,16 @@
 //!
 //! ```ignore
 //! #[derive(SnugomClient)]
-//! #[snugom_client(entities = [Guild, GuildMember, Role])]
+//! #[snugom_client(entities = [Guild, GuildMember, Role], shared = [billing::Invoice])]
 //! pub struct GuildClient {
 //!     conn: ConnectionManager,
 //!     prefix: String,
 //! }
 //!
+//! // `shared` lists entities owned by another client that relations declared with
+//! // `target_service` point at; every such target must be among `entities` or `shared`,
+//! // or the client fails to compile.
+//!
 //! // Generates:
 //! impl GuildClient {
 //!     pub fn new(conn: ConnectionManager, prefix: String) -> Self { ... }
@@ -20,13 +24,18 @@
 //!     pub fn guilds(&self) -> CollectionHandle<Guild> { ... }
 //!     pub fn guild_members(&self) -> CollectionHandle<GuildMember> { ... }
 //!     pub fn roles(&self) -> CollectionHandle<Role> { ... }
+//!     pub async fn ensure_indexes(&mut self) -> Result<IndexBootReport, RepoError> { ... }
+//!     pub async fn boot_indexes(&mut self) -> Result<IndexBootReport, RepoError> { ... }
+//!     pub async fn index_health(&mut self) -> Result<IndexHealthReport, RepoError> { ... }
+//!     pub async fn copy_to_prefix(&mut self, prefix: &str, options: PrefixCopyOptions)
+//!         -> Result<PrefixCopyReport, RepoError> { ... }
 //! }
 //! ```
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use syn::{
-    Data, DeriveInput, Error, Fields, Ident, Result, Token,
+    Data, DeriveInput, Error, Fields, Ident, Path, Result, Token,
     bracketed,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
@@ -92,10 +101,12 @@ impl Parse for EntityEntry {
 }
 
 /// Parsed attributes for SnugomClient derive
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct ClientAttributes {
     /// List of entity types to generate accessors for
     pub entities: Vec<EntityEntry>,
+    /// Entities of other clients that relations may target; registered, but given no accessors or indexes
+    pub shared: Vec<Path>,
     /// Field to use as connection (if not using standard field detection)
     pub conn_field: Option<Ident>,
     /// Field to use as prefix (if not using standard field detection)
@@ -116,6 +127,11 @@ impl ClientAttributes {
                 let parsed: Punctuated<EntityEntry, Token![,]> =
                     content.parse_terminated(EntityEntry::parse, Token![,])?;
                 attrs.entities = parsed.into_iter().collect();
+            } else if key == "shared" {
+                let content;
+                bracketed!(content in input);
+                let parsed: Punctuated<Path, Token![,]> = content.parse_terminated(Path::parse_mod_style, Token![,])?;
+                attrs.shared = parsed.into_iter().collect();
             } else if key == "connection" {
                 attrs.conn_field = Some(input.parse()?);
             } else if key == "prefix" {
@@ -133,7 +149,6 @@ impl ClientAttributes {
 }
 
 /// The parsed SnugomClient derive input
-#[derive(Debug)]
 pub struct ParsedClient {
     /// The struct name
     pub name: Ident,
@@ -295,42 +310,142 @@ impl ParsedClient {
                 let repo = ::snugom::Repo::new(self.#prefix_field.clone());
                 ::snugom::CollectionHandle::new(repo, self.#conn_field.clone())
             }
+
+            /// Start staging writes to send together; see [`UnitOfWork`](::snugom::UnitOfWork).
+            pub fn unit_of_work(&self) -> ::snugom::UnitOfWork {
+                ::snugom::UnitOfWork::new(self.#conn_field.clone(), self.#prefix_field.clone())
+            }
         };
 
         // Generate ensure_indexes method
         let entity_types: Vec<_> = self.attrs.entities.iter().map(|e| &e.type_name).collect();
+        let shared_types = &self.attrs.shared;
         let ensure_indexes = quote! {
             /// Ensure all Redis indexes exist for the registered entity types.
             ///
-            /// This creates FT.CREATE indexes for each entity that implements SearchEntity.
-            /// It also registers all entity descriptors in the global registry for cascade operations.
-            /// Call this at application startup to ensure all indexes are ready for queries.
-            pub async fn ensure_indexes(&mut self) -> Result<(), ::snugom::errors::RepoError> {
+            /// Creates missing indexes and adds newly declared fields to existing ones, all
+            /// entities at once, and reports what happened to each; see
+            /// [`ensure_indexes`](::snugom::search::ensure_indexes). Fails if any index couldn't
+            /// be created or altered. It also registers all entity descriptors in the global
+            /// registry for cascade operations. Call this at application startup to ensure all
+            /// indexes are ready for queries.
+            pub async fn ensure_indexes(
+                &mut self,
+            ) -> Result<::snugom::search::IndexBootReport, ::snugom::errors::RepoError> {
+                let definitions = self.registered_index_definitions();
+                ::snugom::search::ensure_indexes(&mut self.#conn_field, &definitions).await
+            }
+
+            /// [`ensure_indexes`](Self::ensure_indexes), reporting indexes that couldn't be
+            /// created or altered as `IndexBootOutcome::Failed` instead of failing.
+            pub async fn boot_indexes(
+                &mut self,
+            ) -> Result<::snugom::search::IndexBootReport, ::snugom::errors::RepoError> {
+                let definitions = self.registered_index_definitions();
+                ::snugom::search::boot_indexes(&mut self.#conn_field, &definitions).await
+            }
+
+            /// Register every entity descriptor and return the entities' index definitions.
+            fn registered_index_definitions(&self) -> Vec<::snugom::search::IndexDefinition> {
                 // First, register all entity descriptors in the global registry
                 // This is required for cascade delete/update operations to work
                 #(
                     <#entity_types as ::snugom::types::EntityMetadata>::ensure_registered();
                 )*
+                // Shared entities are indexed by the client that owns them
+                #(
+                    <#shared_types as ::snugom::types::EntityMetadata>::ensure_registered();
+                )*
 
-                // Then ensure search indexes exist
+                use ::snugom::search::SearchEntity;
+                vec![
+                    #(<#entity_types as SearchEntity>::index_definition(&self.#prefix_field),)*
+                ]
+            }
+        };
+
+        let index_health = quote! {
+            /// Compare the declared index of each registered entity type with the server.
+            ///
+            /// Reports missing indexes, schema drift, and indexing failures without changing anything.
+            pub async fn index_health(
+                &mut self,
+            ) -> Result<::snugom::search::IndexHealthReport, ::snugom::errors::RepoError> {
+                use ::snugom::search::SearchEntity;
+                let definitions = vec![
+                    #(<#entity_types as SearchEntity>::index_definition(&self.#prefix_field),)*
+                ];
+                ::snugom::search::index_health(&mut self.#conn_field, &definitions).await
+            }
+        };
+
+        let copy_to_prefix = quote! {
+            /// Copy every key under this client's prefix to `prefix` and create the registered
+            /// entities' indexes there, e.g. to clone `staging` into `perf`.
+            ///
+            /// With `options.remove_source` the keys are moved and the indexes under this client's
+            /// prefix are dropped. The client keeps its own prefix; connect another to `prefix` to
+            /// use the copy. See [`copy_prefix_with`](::snugom::copy_prefix_with).
+            pub async fn copy_to_prefix(
+                &mut self,
+                prefix: &str,
+                options: ::snugom::PrefixCopyOptions,
+            ) -> Result<::snugom::PrefixCopyReport, ::snugom::errors::RepoError> {
+                use ::snugom::search::SearchEntity;
+                let remove_source = options.remove_source;
+                let report =
+                    ::snugom::copy_prefix_with(&mut self.#conn_field, &self.#prefix_field, prefix, options, |_| {})
+                        .await?;
                 #(
                     {
-                        use ::snugom::search::SearchEntity;
-                        let definition = <#entity_types as SearchEntity>::index_definition(&self.#prefix_field);
+                        let definition = <#entity_types as SearchEntity>::index_definition(prefix);
                         ::snugom::search::ensure_index(&mut self.#conn_field, &definition).await?;
+                        if remove_source {
+                            let source = <#entity_types as SearchEntity>::index_definition(&self.#prefix_field);
+                            ::snugom::search::drop_index(&mut self.#conn_field, &source.name).await?;
+                        }
                     }
                 )*
-                Ok(())
+                Ok(report)
             }
         };
 
+        // Relations into other services must name an entity this client knows
+        let service_target_check = quote! {
+            const _: () = {
+                const KNOWN: &[(&str, &str)] = &[
+                    #((
+                        <#entity_types as ::snugom::types::SnugomModel>::SERVICE,
+                        <#entity_types as ::snugom::types::SnugomModel>::COLLECTION,
+                    ),)*
+                    #((
+                        <#shared_types as ::snugom::types::SnugomModel>::SERVICE,
+                        <#shared_types as ::snugom::types::SnugomModel>::COLLECTION,
+                    ),)*
+                ];
+                #(
+                    ::snugom::validate_service_relation_targets(
+                        stringify!(#entity_types),
+                        #entity_types::SERVICE_RELATION_TARGETS,
+                        KNOWN,
+                    );
+                )*
+            };
+        };
+
         quote! {
+            #service_target_check
+
             impl #name {
                 #constructor
 
                 #(#accessors)*
 
                 #ensure_indexes
+
+                #index_health
+
+                #copy_to_prefix
             }
         }
     }
@@ -15,6 +15,13 @@
 //! snugom_update!(client, Guild(entity_id = &id) {
 //!     name: "New Name",
 //! });
+//!
+//! // Several writes sent together
+//! snugom_run!(client, {
+//!     let guild_id = create Guild { name: "Knights" };
+//!     update Player(entity_id = &player_id) { guild_id: Some(guild_id.clone()) };
+//!     guild_id
+//! });
 //! ```
 
 use proc_macro2::TokenStream as TokenStream2;
@@ -323,3 +330,195 @@ impl ClientGetOrCreateInvocation {
         }})
     }
 }
+
+/// Parsed invocation for snugom_run! macro
+pub struct ClientRunInvocation {
+    /// The client expression
+    pub client: Expr,
+    /// The writes, in order
+    pub steps: Vec<RunStep>,
+    /// Expression evaluated after the commit, the block's result
+    pub output: Option<Expr>,
+}
+
+/// One write in a snugom_run! block
+pub enum RunStep {
+    /// `[let binding =] create Entity { ... };`
+    Create {
+        binding: Option<Ident>,
+        entity: SnugInvocation,
+    },
+    /// `update Entity(entity_id = ...) { ... };`
+    Update { update: SnugInvocation },
+    /// `delete Entity(id [, expected_version = ...]);`
+    Delete {
+        entity_type: Path,
+        entity_id: Expr,
+        expected_version: Option<Expr>,
+    },
+}
+
+impl RunStep {
+    fn parse_op(input: ParseStream, binding: Option<Ident>) -> Result<Self> {
+        let op: Ident = input.parse()?;
+        match op.to_string().as_str() {
+            "create" => {
+                let entity: SnugInvocation = input.parse()?;
+                if entity.is_update() {
+                    return Err(syn::Error::new(
+                        entity.entity_type().span(),
+                        "`create` takes `Entity { ... }`; use `update` to patch an existing entity",
+                    ));
+                }
+                Ok(RunStep::Create { binding, entity })
+            }
+            _ if binding.is_some() => Err(syn::Error::new(
+                op.span(),
+                "only `create` steps can be bound with `let`; they evaluate to the new id",
+            )),
+            "update" => {
+                let update: SnugInvocation = input.parse()?;
+                if !update.is_update() {
+                    return Err(syn::Error::new(
+                        update.entity_type().span(),
+                        "`update` requires `(entity_id = ...)`",
+                    ));
+                }
+                Ok(RunStep::Update { update })
+            }
+            "delete" => {
+                let entity_type: Path = input.parse()?;
+                let content;
+                syn::parenthesized!(content in input);
+
+                let entity_id: Expr = if content.peek(Ident) && content.peek2(Token![=]) {
+                    let _id_ident: Ident = content.parse()?;
+                    content.parse::<Token![=]>()?;
+                    content.parse()?
+                } else {
+                    content.parse()?
+                };
+
+                let mut expected_version = None;
+                if content.peek(Token![,]) {
+                    content.parse::<Token![,]>()?;
+                }
+                if !content.is_empty() {
+                    let key: Ident = content.parse()?;
+                    if key != "expected_version" {
+                        return Err(syn::Error::new(key.span(), "expected `expected_version`"));
+                    }
+                    content.parse::<Token![=]>()?;
+                    expected_version = Some(content.parse()?);
+                }
+
+                Ok(RunStep::Delete {
+                    entity_type,
+                    entity_id,
+                    expected_version,
+                })
+            }
+            other => Err(syn::Error::new(
+                op.span(),
+                format!("unknown step `{other}`; expected `create`, `update`, or `delete`"),
+            )),
+        }
+    }
+
+    fn emit(self) -> Result<TokenStream2> {
+        Ok(match self {
+            RunStep::Create { binding, entity } => {
+                let builder_tokens = entity.emit()?;
+                let staged = quote! { __snugom_work.create(#builder_tokens).await? };
+                match binding {
+                    Some(binding) => quote! { let #binding: ::std::string::String = #staged; },
+                    None => quote! { #staged; },
+                }
+            }
+            RunStep::Update { update } => {
+                let patch_tokens = update.emit()?;
+                quote! { __snugom_work.update(#patch_tokens).await?; }
+            }
+            RunStep::Delete {
+                entity_type,
+                entity_id,
+                expected_version,
+            } => {
+                let expected_version = match expected_version {
+                    Some(expected) => quote! { ::std::option::Option::Some(#expected) },
+                    None => quote! { ::std::option::Option::None },
+                };
+                quote! {{
+                    let __snugom_id: String = ::std::convert::Into::into(#entity_id);
+                    __snugom_work.delete::<#entity_type>(&__snugom_id, #expected_version)?;
+                }}
+            }
+        })
+    }
+}
+
+/// Whether the input starts a step rather than the trailing output expression
+fn peek_step(input: ParseStream) -> bool {
+    if input.peek(Token![let]) {
+        return true;
+    }
+    let fork = input.fork();
+    fork.parse::<Ident>().is_ok_and(|op| op == "create" || op == "update" || op == "delete") && fork.peek(Ident)
+}
+
+impl Parse for ClientRunInvocation {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let client: Expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+
+        let mut steps = Vec::new();
+        let mut output = None;
+        while !content.is_empty() {
+            if !peek_step(&content) {
+                output = Some(content.parse()?);
+                if !content.is_empty() {
+                    return Err(content.error("expected end of block after the result expression"));
+                }
+                break;
+            }
+            let binding = if content.peek(Token![let]) {
+                content.parse::<Token![let]>()?;
+                let binding: Ident = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Some(binding)
+            } else {
+                None
+            };
+            steps.push(RunStep::parse_op(&content, binding)?);
+            content.parse::<Token![;]>()?;
+        }
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(Self { client, steps, output })
+    }
+}
+
+impl ClientRunInvocation {
+    pub fn emit(self) -> Result<TokenStream2> {
+        let client = self.client;
+        let steps = self.steps.into_iter().map(RunStep::emit).collect::<Result<Vec<_>>>()?;
+        let output = self.output.map(|output| quote! { #output }).unwrap_or_else(|| quote! { () });
+
+        // Use `async` (not `async move`) so values are borrowed, not consumed.
+        Ok(quote! {{
+            let __snugom_client = &#client;
+            async {
+                let mut __snugom_work = __snugom_client.unit_of_work();
+                #(#steps)*
+                __snugom_work.commit().await?;
+                ::std::result::Result::<_, ::snugom::errors::RepoError>::Ok(#output)
+            }
+        }})
+    }
+}
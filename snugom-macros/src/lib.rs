@@ -16,7 +16,7 @@ mod snug_macro;
 
 use client_macro::ParsedClient;
 use client_ops_macro::{
-    ClientCreateInvocation, ClientDeleteInvocation, ClientGetOrCreateInvocation,
+    ClientCreateInvocation, ClientDeleteInvocation, ClientGetOrCreateInvocation, ClientRunInvocation,
     ClientUpdateInvocation, ClientUpsertInvocation,
 };
 use parsed::ParsedEntity;
@@ -189,3 +189,29 @@ pub fn snugom_get_or_create(input: TokenStream) -> TokenStream {
         Err(err) => err.to_compile_error().into(),
     }
 }
+
+/// Run several creates, updates, and deletes as one unit of work.
+///
+/// The writes are staged in order on the client's `unit_of_work()` and committed together when
+/// the block ends, so either all of them apply or, if one fails, none do. `let` binds the id of a created entity for later steps,
+/// and a final expression becomes the result.
+///
+/// # Example
+///
+/// ```ignore
+/// let guild_id = snugom_run!(client, {
+///     let guild_id = create Guild { name: "Knights".to_string() };
+///     create GuildMember { guild_id: guild_id.clone(), user_id: user_id.clone() };
+///     update Player(entity_id = &user_id) { guild_id: Some(guild_id.clone()) };
+///     delete Invite(&invite_id);
+///     guild_id
+/// }).await?;
+/// ```
+#[proc_macro]
+pub fn snugom_run(input: TokenStream) -> TokenStream {
+    let invocation = parse_macro_input!(input as ClientRunInvocation);
+    match invocation.emit() {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
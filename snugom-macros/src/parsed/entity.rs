@@ -13,10 +13,61 @@ pub(crate) struct ParsedEntity {
     default_sort: Option<DefaultSortSpec>,
     // Unique constraints from #[snugom(unique_together = [...])]
     unique_together: Vec<UniqueTogetherSpec>,
+    // Relations from #[snugom(has_many_through(...))]
+    through_relations: Vec<ThroughRelationSpec>,
     // Optional service/collection for auto-registration (Prisma-style client API)
     // When present, generates SnugomModel impl and inventory registration
     service: Option<String>,
     collection: Option<String>,
+    // Path (relative to the crate manifest) of a Lua snippet from #[snugom(lua_check = "...")]
+    lua_check: Option<LitStr>,
+    // Doc comment on the struct, surfaced as EntityDescriptor::description
+    description: Option<String>,
+    // Emit GraphQL connection/filter types from #[snugom(graphql)]
+    graphql: bool,
+    // Emit a `{Entity}Factory` of fake builders from #[snugom(factory)]
+    factory: bool,
+    // Set by #[snugom(storage = "hash")]; documents are stored as plain hash fields
+    hash_storage: bool,
+    // Default stemming language of the index from #[snugom(language = "...")]
+    language: Option<String>,
+    // Stored name of the field holding each document's language, from #[snugom(language_field = "...")]
+    language_field: Option<String>,
+    // Index name under the key prefix, from #[snugom(index_name = "...")], instead of `{service}:{collection}:idx`
+    index_name: Option<String>,
+    // Partial structs from #[snugom(view(Name = [field, ...]))]
+    views: Vec<ViewSpec>,
+    // Entity-level validator functions from #[snugom(validate(custom = "path"))]
+    custom_validators: Vec<(TokenStream2, LitStr)>,
+    // Entity-level validator types from #[snugom(validate(async_custom = "path"))]
+    async_validators: Vec<(TokenStream2, LitStr)>,
+    // Lifecycle callbacks from #[snugom(before_create = "path", ...)], keyed by hook name
+    hooks: Vec<(String, TokenStream2)>,
+    // Previous documents kept per entity, from #[snugom(versioned(keep = N))]
+    keep_versions: Option<usize>,
+    // Separator of this entity's keys, from #[snugom(key_separator = "/")]
+    key_separator: Option<char>,
+    // Segments after the key prefix and service, from #[snugom(key_components = ["eu"])]
+    key_components: Vec<String>,
+}
+
+/// Previous versions a bare `#[snugom(versioned)]` keeps
+const DEFAULT_KEEP_VERSIONS: usize = 10;
+
+/// Lifecycle callbacks accepted on the struct; `before_*` hooks take `&mut Self`, `after_*` take `&Self`
+const LIFECYCLE_HOOKS: &[&str] = &["before_create", "after_create", "before_update", "after_update"];
+
+/// Languages RediSearch can stem, accepted by `#[snugom(language = "...")]`
+const SEARCH_LANGUAGES: &[&str] = &[
+    "arabic", "armenian", "basque", "catalan", "chinese", "danish", "dutch", "english", "finnish", "french", "german",
+    "greek", "hindi", "hungarian", "indonesian", "irish", "italian", "lithuanian", "nepali", "norwegian", "portuguese",
+    "romanian", "russian", "serbian", "spanish", "swedish", "tamil", "turkish", "yiddish",
+];
+
+/// Specification for a generated view struct `{Entity}{name}`
+struct ViewSpec {
+    name: Ident,
+    fields: Vec<Ident>,
 }
 
 /// Specification for entity-level compound unique constraint
@@ -25,6 +76,13 @@ struct UniqueTogetherSpec {
     case_insensitive: bool,
 }
 
+/// Specification for a has-many-through relation
+struct ThroughRelationSpec {
+    alias: String,
+    through: LitStr,
+    source: String,
+}
+
 /// Specification for default sort order
 pub(crate) struct DefaultSortSpec {
     pub field: String,
@@ -34,9 +92,12 @@ pub(crate) struct DefaultSortSpec {
 pub(crate) struct ParsedRelation {
     alias: String,
     target: String,
+    target_service: Option<String>,
     kind: RelationKind,
     cascade: CascadePolicy,
     foreign_key: Option<String>,
+    counter: Option<String>,
+    model: Option<syn::Path>,
 }
 
 struct DerivedIdSpec {
@@ -44,6 +105,33 @@ struct DerivedIdSpec {
     separator: String,
 }
 
+/// Join `///` doc comment lines into a single description, or `None` when there are none.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit), ..
+                }),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).trim_end().to_string())
+        .collect();
+    let joined = lines.join("\n").trim().to_string();
+    (!joined.is_empty()).then_some(joined)
+}
+
+fn option_string_tokens(value: &Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Some(#value.to_string()) },
+        None => quote! { None },
+    }
+}
+
 impl ParsedEntity {
     pub(crate) fn from_input(input: &DeriveInput) -> Result<Self> {
         let mut version = 1u32;
@@ -52,6 +140,22 @@ impl ParsedEntity {
         let mut unique_together: Vec<UniqueTogetherSpec> = Vec::new();
         let mut service: Option<String> = None;
         let mut collection: Option<String> = None;
+        let mut lua_check: Option<LitStr> = None;
+        let mut through_relations: Vec<ThroughRelationSpec> = Vec::new();
+        let mut graphql = false;
+        let mut factory = false;
+        let mut storage: Option<LitStr> = None;
+        let mut language: Option<LitStr> = None;
+        let mut language_field: Option<LitStr> = None;
+        let mut index_name: Option<LitStr> = None;
+        let mut views: Vec<ViewSpec> = Vec::new();
+        let mut custom_validators = Vec::new();
+        let mut async_validators = Vec::new();
+        let mut hooks = Vec::new();
+        let mut keep_versions: Option<usize> = None;
+        let mut key_separator: Option<char> = None;
+        let mut key_components: Vec<String> = Vec::new();
+        let rename_all = serde_rename_all(&input.attrs)?;
 
         for attr in &input.attrs {
             if attr.path().is_ident("snugom") {
@@ -63,6 +167,21 @@ impl ParsedEntity {
                     &mut unique_together,
                     &mut service,
                     &mut collection,
+                    &mut lua_check,
+                    &mut through_relations,
+                    &mut graphql,
+                    &mut factory,
+                    &mut storage,
+                    &mut language,
+                    &mut language_field,
+                    &mut index_name,
+                    &mut views,
+                    &mut custom_validators,
+                    &mut async_validators,
+                    &mut hooks,
+                    &mut keep_versions,
+                    &mut key_separator,
+                    &mut key_components,
                 )?;
             }
         }
@@ -70,12 +189,12 @@ impl ParsedEntity {
         // service and collection can now optionally come from the derive macro
         // When present, auto-registration via inventory is enabled
 
-        let fields = match &input.data {
+        let mut fields = match &input.data {
             Data::Struct(data) => match &data.fields {
                 Fields::Named(named) => {
                     let mut parsed = Vec::new();
                     for field in &named.named {
-                        parsed.push(ParsedField::from_field(field)?);
+                        parsed.push(ParsedField::from_field(field, rename_all)?);
                     }
                     parsed
                 }
@@ -101,10 +220,46 @@ impl ParsedEntity {
             Error::new(input.ident.span(), "SnugomEntity requires a field annotated with #[snugom(id)]")
         })?;
 
+        Self::mark_counter_fields(&mut fields)?;
+        Self::resolve_slug_sources(&mut fields)?;
+
+        // Container attributes name fields by their Rust names; the runtime needs stored names
+        for spec in &mut unique_together {
+            for field in &mut spec.fields {
+                *field = Self::stored_name(&fields, field);
+            }
+        }
+        if let Some(spec) = default_sort.as_mut() {
+            spec.field = Self::stored_name(&fields, &spec.field);
+        }
+
         // Collect field-based relations and merge with container-level relations
         let field_relations = Self::collect_field_relations(&fields);
         relations.extend(field_relations);
 
+        for spec in &through_relations {
+            let through = spec.through.value();
+            if !relations.iter().any(|relation| relation.alias == through) {
+                return Err(Error::new(
+                    spec.through.span(),
+                    format!("has_many_through `{}` references unknown relation `{}`", spec.alias, through),
+                ));
+            }
+        }
+
+        let hash_storage = storage.as_ref().is_some_and(|lit| lit.value() == "hash");
+        if let Some(storage) = storage.as_ref().filter(|_| hash_storage) {
+            Self::check_hash_storage(storage, &fields, lua_check.is_some())?;
+            if keep_versions.is_some() {
+                return Err(Error::new(storage.span(), "versioned is not supported with storage = \"hash\""));
+            }
+        }
+
+        Self::check_views(&views, &fields)?;
+        let language_field = language_field
+            .map(|lit| Self::resolve_language_field(&lit, &fields))
+            .transpose()?;
+
         let derived_id = Self::detect_derived_id(&fields, &relations);
 
         Ok(Self {
@@ -119,9 +274,146 @@ impl ParsedEntity {
             unique_together,
             service,
             collection,
+            lua_check,
+            through_relations,
+            description: doc_comment(&input.attrs),
+            graphql,
+            factory,
+            hash_storage,
+            language: language.map(|lit| lit.value()),
+            language_field,
+            index_name: index_name.map(|lit| lit.value()),
+            views,
+            custom_validators,
+            async_validators,
+            hooks,
+            keep_versions,
+            key_separator,
+            key_components,
         })
     }
 
+    /// Every view must name existing fields, and view names must be distinct.
+    fn check_views(views: &[ViewSpec], fields: &[ParsedField]) -> Result<()> {
+        for (index, view) in views.iter().enumerate() {
+            if views[..index].iter().any(|other| other.name == view.name) {
+                return Err(Error::new(view.name.span(), format!("duplicate view `{}`", view.name)));
+            }
+            if view.fields.is_empty() {
+                return Err(Error::new(view.name.span(), "a view needs at least one field"));
+            }
+            for field in &view.fields {
+                match fields.iter().find(|candidate| candidate.ident == *field) {
+                    None => {
+                        return Err(Error::new(
+                            field.span(),
+                            format!("view `{}` references unknown field `{}`", view.name, field),
+                        ));
+                    }
+                    Some(candidate) if candidate.redacted => {
+                        return Err(Error::new(
+                            field.span(),
+                            format!("view `{}` cannot include redacted field `{}`", view.name, field),
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check `language_field = "..."` names a string field, returning its stored name.
+    fn resolve_language_field(lit: &LitStr, fields: &[ParsedField]) -> Result<String> {
+        let name = lit.value();
+        let field = fields.iter().find(|field| field.rust_name() == name).ok_or_else(|| {
+            Error::new(lit.span(), format!("language_field `{name}` does not name a field on this entity"))
+        })?;
+        if !matches!(field.ty.base, FieldBase::String) {
+            return Err(Error::new(lit.span(), format!("language_field `{name}` must be a string field")));
+        }
+        Ok(field.name.clone())
+    }
+
+    /// Hash storage keeps one hash field per entity field, so it only fits flat entities.
+    fn check_hash_storage(storage: &LitStr, fields: &[ParsedField], has_lua_check: bool) -> Result<()> {
+        if has_lua_check {
+            return Err(Error::new(storage.span(), "lua_check is not supported with storage = \"hash\""));
+        }
+        for field in fields {
+            if field.is_counter {
+                return Err(Error::new(
+                    field.ident.span(),
+                    "relation counters are not supported with storage = \"hash\"",
+                ));
+            }
+            if !field.nested.is_empty() {
+                return Err(Error::new(
+                    field.ident.span(),
+                    "#[snugom(flatten(...))] is not supported with storage = \"hash\"; nested values are stored as JSON strings",
+                ));
+            }
+            if matches!(field.ty.base, FieldBase::Vec) && field.relation_spec.is_none() && !field.ty.is_bytes() {
+                return Err(Error::new(
+                    field.ident.span(),
+                    format!(
+                        "storage = \"hash\" requires flat entities; `{}` is a list and cannot be stored as a hash field",
+                        field.name
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Flag fields named by a relation's `counter` so builders default them to zero
+    fn mark_counter_fields(fields: &mut [ParsedField]) -> Result<()> {
+        let counters: Vec<LitStr> = fields
+            .iter()
+            .filter_map(|field| field.relation_spec.as_ref().and_then(|spec| spec.counter.clone()))
+            .collect();
+        for counter in counters {
+            let name = counter.value();
+            let field = fields.iter_mut().find(|field| field.rust_name() == name).ok_or_else(|| {
+                Error::new(counter.span(), format!("relation counter `{name}` does not name a field on this entity"))
+            })?;
+            if !matches!(field.ty.base, FieldBase::Numeric) || field.ty.optional {
+                return Err(Error::new(
+                    counter.span(),
+                    format!("relation counter `{name}` must be a non-optional integer field"),
+                ));
+            }
+            field.is_counter = true;
+        }
+        Ok(())
+    }
+
+    /// Check each `slug(from = "...")` names a string field, and record that field's stored name.
+    fn resolve_slug_sources(fields: &mut [ParsedField]) -> Result<()> {
+        let sources: Vec<(usize, LitStr)> = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| field.slug.as_ref().map(|spec| (index, spec.from.clone())))
+            .collect();
+        for (index, from) in sources {
+            let name = from.value();
+            let source = fields.iter().find(|field| field.rust_name() == name).ok_or_else(|| {
+                Error::new(from.span(), format!("slug source `{name}` does not name a field on this entity"))
+            })?;
+            if !matches!(source.ty.base, FieldBase::String) || source.slug.is_some() {
+                return Err(Error::new(
+                    from.span(),
+                    format!("slug source `{name}` must be a String or Option<String> field that is not itself a slug"),
+                ));
+            }
+            let from_name = source.name.clone();
+            if let Some(spec) = fields[index].slug.as_mut() {
+                spec.from_name = from_name;
+            }
+        }
+        Ok(())
+    }
+
     /// Collect relations declared on fields via #[snugom(relation)]
     fn collect_field_relations(fields: &[ParsedField]) -> Vec<ParsedRelation> {
         fields
@@ -130,15 +422,26 @@ impl ParsedEntity {
                 field.relation_spec.as_ref().map(|spec| ParsedRelation {
                     alias: spec.alias.clone(),
                     target: spec.target.clone(),
+                    target_service: spec.target_service.clone(),
                     kind: spec.kind,
                     cascade: spec.cascade,
                     foreign_key: spec.foreign_key.clone(),
+                    counter: spec.counter.as_ref().map(|counter| Self::stored_name(fields, &counter.value())),
+                    model: spec.model.clone(),
                 })
             })
             .collect()
     }
 
-    #[allow(clippy::ptr_arg)]
+    /// The name a field is stored under, given its Rust name; unknown names are returned as is.
+    fn stored_name(fields: &[ParsedField], rust_name: &str) -> String {
+        fields
+            .iter()
+            .find(|field| field.rust_name() == rust_name)
+            .map_or_else(|| rust_name.to_string(), |field| field.name.clone())
+    }
+
+    #[allow(clippy::ptr_arg, clippy::too_many_arguments)]
     fn parse_container_attr(
         attr: &Attribute,
         version: &mut u32,
@@ -147,6 +450,21 @@ impl ParsedEntity {
         unique_together: &mut Vec<UniqueTogetherSpec>,
         service: &mut Option<String>,
         collection: &mut Option<String>,
+        lua_check: &mut Option<LitStr>,
+        through_relations: &mut Vec<ThroughRelationSpec>,
+        graphql: &mut bool,
+        factory: &mut bool,
+        storage: &mut Option<LitStr>,
+        language: &mut Option<LitStr>,
+        language_field: &mut Option<LitStr>,
+        index_name: &mut Option<LitStr>,
+        views: &mut Vec<ViewSpec>,
+        custom_validators: &mut Vec<(TokenStream2, LitStr)>,
+        async_validators: &mut Vec<(TokenStream2, LitStr)>,
+        hooks: &mut Vec<(String, TokenStream2)>,
+        keep_versions: &mut Option<usize>,
+        key_separator: &mut Option<char>,
+        key_components: &mut Vec<String>,
     ) -> Result<()> {
         attr.parse_nested_meta(|meta| {
             if meta.path.is_ident("service") {
@@ -164,6 +482,168 @@ impl ParsedEntity {
             } else if meta.path.is_ident("schema") {
                 let value: LitInt = meta.value()?.parse()?;
                 *version = value.base10_parse()?;
+            } else if meta.path.is_ident("lua_check") {
+                let value: LitStr = meta.value()?.parse()?;
+                if value.value().trim().is_empty() {
+                    return Err(syn::Error::new(value.span(), "lua_check requires a non-empty script path"));
+                }
+                *lua_check = Some(value);
+            } else if meta.path.is_ident("graphql") {
+                *graphql = true;
+            } else if meta.path.is_ident("factory") {
+                *factory = true;
+            } else if meta.path.is_ident("storage") {
+                let value: LitStr = meta.value()?.parse()?;
+                if !matches!(value.value().as_str(), "json" | "hash") {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        format!("unknown storage `{}`, expected `json` or `hash`", value.value()),
+                    ));
+                }
+                *storage = Some(value);
+            } else if meta.path.is_ident("language") {
+                let value: LitStr = meta.value()?.parse()?;
+                if !SEARCH_LANGUAGES.contains(&value.value().as_str()) {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        format!(
+                            "unsupported search language `{}`, expected one of: {}",
+                            value.value(),
+                            SEARCH_LANGUAGES.join(", ")
+                        ),
+                    ));
+                }
+                *language = Some(value);
+            } else if meta.path.is_ident("language_field") {
+                *language_field = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("index_name") {
+                let value: LitStr = meta.value()?.parse()?;
+                let name = value.value();
+                if name.is_empty() || name.chars().any(char::is_whitespace) {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "index_name must be non-empty and contain no whitespace",
+                    ));
+                }
+                *index_name = Some(value);
+            } else if meta.path.is_ident("key_separator") {
+                // Same rule as snugom::keys::set_key_scheme
+                let value: LitStr = meta.value()?.parse()?;
+                let separator = value.value();
+                let mut chars = separator.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(separator), None)
+                        if separator.is_ascii_punctuation()
+                            && !matches!(separator, '_' | '-' | '*' | '?' | '[' | ']' | '\\' | '"' | '\'') =>
+                    {
+                        *key_separator = Some(separator);
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "key_separator must be one ASCII punctuation character other than _ - * ? [ ] \\ \" '",
+                        ));
+                    }
+                }
+            } else if meta.path.is_ident("key_components") {
+                // Parse #[snugom(key_components = ["eu", "primary"])]
+                meta.input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in meta.input);
+                let parsed: syn::punctuated::Punctuated<LitStr, syn::Token![,]> =
+                    content.parse_terminated(<LitStr as Parse>::parse, syn::Token![,])?;
+                for lit in parsed {
+                    let component = lit.value();
+                    if component.is_empty()
+                        || !component.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "key components must be non-empty and contain only ASCII letters, digits, `_` and `-`",
+                        ));
+                    }
+                    key_components.push(component);
+                }
+            } else if meta.path.is_ident("validate") {
+                // Parse #[snugom(validate(custom = "path::to_fn", async_custom = "path::ToType"))],
+                // called with the whole entity
+                meta.parse_nested_meta(|rule| {
+                    if rule.path.is_ident("custom") {
+                        custom_validators.push(parse_validator_path(&rule)?);
+                    } else if rule.path.is_ident("async_custom") {
+                        async_validators.push(parse_validator_path(&rule)?);
+                    } else {
+                        return Err(rule.error(
+                            "only custom and async_custom validators apply to the whole entity; put other rules on fields",
+                        ));
+                    }
+                    Ok(())
+                })?;
+            } else if let Some(hook) = LIFECYCLE_HOOKS.iter().find(|hook| meta.path.is_ident(hook)) {
+                if hooks.iter().any(|(name, _)| name == hook) {
+                    return Err(meta.error(format!("{hook} is set more than once")));
+                }
+                let lit: LitStr = meta.value()?.parse()?;
+                let path = syn::parse_str::<syn::Path>(&lit.value())
+                    .map_err(|err| Error::new(lit.span(), format!("invalid {hook} path: {err}")))?;
+                hooks.push((hook.to_string(), path.to_token_stream()));
+            } else if meta.path.is_ident("versioned") {
+                // Parse #[snugom(versioned)] or #[snugom(versioned(keep = 10))]
+                let mut keep = DEFAULT_KEEP_VERSIONS;
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|option| {
+                        if !option.path.is_ident("keep") {
+                            return Err(option.error("unknown versioned option, expected `keep`"));
+                        }
+                        let value: LitInt = option.value()?.parse()?;
+                        keep = value.base10_parse()?;
+                        if keep == 0 {
+                            return Err(Error::new(value.span(), "versioned needs keep of at least 1"));
+                        }
+                        Ok(())
+                    })?;
+                }
+                *keep_versions = Some(keep);
+            } else if meta.path.is_ident("view") {
+                // Parse #[snugom(view(Summary = [id, name], Card = [id]))]
+                meta.parse_nested_meta(|nested| {
+                    let name = nested
+                        .path
+                        .get_ident()
+                        .cloned()
+                        .ok_or_else(|| nested.error("view name must be a plain identifier, e.g. Summary"))?;
+                    nested.input.parse::<syn::Token![=]>()?;
+                    let content;
+                    syn::bracketed!(content in nested.input);
+                    let fields: syn::punctuated::Punctuated<Ident, syn::Token![,]> =
+                        content.parse_terminated(Ident::parse, syn::Token![,])?;
+                    views.push(ViewSpec {
+                        name,
+                        fields: fields.into_iter().collect(),
+                    });
+                    Ok(())
+                })?;
+            } else if meta.path.is_ident("has_many_through") {
+                // Parse #[snugom(has_many_through(alias = "teams", through = "memberships", source = "team"))]
+                let mut alias: Option<String> = None;
+                let mut through: Option<LitStr> = None;
+                let mut source: Option<String> = None;
+                meta.parse_nested_meta(|nested| {
+                    if nested.path.is_ident("alias") {
+                        alias = Some(nested.value()?.parse::<LitStr>()?.value());
+                    } else if nested.path.is_ident("through") {
+                        through = Some(nested.value()?.parse::<LitStr>()?);
+                    } else if nested.path.is_ident("source") {
+                        source = Some(nested.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        return Err(nested.error("unknown has_many_through option, expected `alias`, `through`, or `source`"));
+                    }
+                    Ok(())
+                })?;
+                let (Some(alias), Some(through), Some(source)) = (alias, through, source) else {
+                    return Err(meta.error("has_many_through requires `alias`, `through`, and `source`"));
+                };
+                through_relations.push(ThroughRelationSpec { alias, through, source });
             } else if meta.path.is_ident("default_sort") {
                 let value: LitStr = meta.value()?.parse()?;
                 let raw = value.value();
@@ -218,13 +698,24 @@ impl ParsedEntity {
         let name = &self.name;
         let version = self.version;
         let id_ident = &self.id_field;
-        let id_field_lit = LitStr::new(&self.id_field.to_string(), Span::call_site());
+        let id_field_lit = LitStr::new(&Self::stored_name(&self.fields, &self.id_field.to_string()), Span::call_site());
 
         let relation_inits = self.relations.iter().map(|rel| rel.to_tokens());
         // Collect relation targets for compile-time validation
-        let relation_targets: Vec<&str> = self.relations.iter().map(|rel| rel.target.as_str()).collect();
-        let field_inits = self.fields.iter().map(|field| field.to_descriptor_tokens());
+        let relation_targets: Vec<&str> = self
+            .relations
+            .iter()
+            .filter(|rel| rel.target_service.is_none())
+            .map(|rel| rel.target.as_str())
+            .collect();
+        let service_relation_targets: Vec<TokenStream2> = self
+            .relations
+            .iter()
+            .filter_map(|rel| rel.target_service.as_ref().map(|service| (service, &rel.target)))
+            .map(|(service, target)| quote! { (#service, #target) })
+            .collect();
         let field_idents: Vec<Ident> = self.fields.iter().map(|field| field.ident.clone()).collect();
+        let field_inits = self.fields.iter().map(|field| field.to_descriptor_tokens(name, &field_idents));
 
         // Collect unique constraints from field-level #[snugom(unique)] and entity-level #[snugom(unique_together)]
         let mut unique_constraint_tokens: Vec<TokenStream2> = Vec::new();
@@ -272,6 +763,8 @@ impl ParsedEntity {
 
         let mut builder_setters: Vec<_> = self.fields.iter().map(|field| field.builder_setter_methods()).collect();
         let patch_setters: Vec<_> = self.fields.iter().map(|field| field.patch_setter_method()).collect();
+        let patch_increments: Vec<_> = self.fields.iter().filter_map(|field| field.patch_increment_method()).collect();
+        let patch_array_methods: Vec<_> = self.fields.iter().filter_map(|field| field.patch_array_methods()).collect();
         let relation_methods = quote! {
             pub fn relation(
                 mut self,
@@ -309,6 +802,38 @@ impl ParsedEntity {
                 self
             }
 
+            pub fn connect_with<E>(
+                mut self,
+                alias: impl Into<String>,
+                id: impl Into<String>,
+                edge_data: E,
+            ) -> Self
+            where
+                E: ::serde::Serialize,
+            {
+                match ::snugom::repository::RelationPlan::connect_with(alias, id, &edge_data) {
+                    Ok(plan) => self.relations.push(plan),
+                    Err(issue) => self.validation_issues.push(issue),
+                }
+                self
+            }
+
+            pub fn connect_with_mut<E>(
+                &mut self,
+                alias: impl Into<String>,
+                id: impl Into<String>,
+                edge_data: E,
+            ) -> &mut Self
+            where
+                E: ::serde::Serialize,
+            {
+                match ::snugom::repository::RelationPlan::connect_with(alias, id, &edge_data) {
+                    Ok(plan) => self.relations.push(plan),
+                    Err(issue) => self.validation_issues.push(issue),
+                }
+                self
+            }
+
             pub fn disconnect(mut self, alias: impl Into<String>, values: Vec<String>) -> Self {
                 self.relations
                     .push(::snugom::repository::RelationPlan::new(alias, Vec::new(), values));
@@ -368,6 +893,7 @@ impl ParsedEntity {
                             alias: alias_string,
                             descriptor: <B::Entity as ::snugom::types::EntityMetadata>::entity_descriptor(),
                             payload,
+                            connect_existing: false,
                         });
                     }
                     Err(err) => {
@@ -402,6 +928,7 @@ impl ParsedEntity {
                             alias: alias_string,
                             descriptor: <B::Entity as ::snugom::types::EntityMetadata>::entity_descriptor(),
                             payload,
+                            connect_existing: false,
                         });
                     }
                     Err(err) => {
@@ -410,6 +937,38 @@ impl ParsedEntity {
                 }
                 self
             }
+
+            /// Connect the entity with the builder's id, creating it first if it doesn't exist.
+            pub fn connect_or_create<B>(mut self, alias: impl Into<String>, builder: B) -> Self
+            where
+                B: ::snugom::repository::MutationPayloadBuilder,
+                <B as ::snugom::repository::MutationPayloadBuilder>::Entity: ::snugom::types::EntityMetadata,
+            {
+                self.connect_or_create_mut(alias, builder);
+                self
+            }
+
+            pub fn connect_or_create_mut<B>(&mut self, alias: impl Into<String>, builder: B) -> &mut Self
+            where
+                B: ::snugom::repository::MutationPayloadBuilder,
+                <B as ::snugom::repository::MutationPayloadBuilder>::Entity: ::snugom::types::EntityMetadata,
+            {
+                let index = self.nested_creates.len();
+                self.create_relation_mut(alias, builder);
+                if let Some(mutation) = self.nested_creates.get_mut(index) {
+                    if mutation.payload.nested.is_empty() {
+                        mutation.connect_existing = true;
+                    } else {
+                        // They would be created even when the entity turns out to exist
+                        self.validation_issues.push(::snugom::errors::ValidationIssue::new(
+                            format!("relations.{}", mutation.alias),
+                            "relation.connect_or_create_nested",
+                            "connect_or_create cannot contain nested creates",
+                        ));
+                    }
+                }
+                self
+            }
         };
         builder_setters.push(relation_methods);
         let idempotency_methods = quote! {
@@ -449,6 +1008,7 @@ impl ParsedEntity {
         let foreign_key_names: Vec<String> = self
             .relations
             .iter()
+            .filter(|relation| matches!(relation.kind, RelationKind::BelongsTo))
             .filter_map(|relation| relation.foreign_key.clone())
             .collect();
         let builder_required_checks: Vec<_> = self
@@ -471,11 +1031,64 @@ impl ParsedEntity {
             })
             .collect();
         let builder_field_names: Vec<_> = self.fields.iter().map(|field| field.ident.clone()).collect();
+        let id_prefix = self.fields.iter().find_map(|field| field.id_prefix.as_deref());
+        let id_strategy = self.fields.iter().find_map(|field| field.id_strategy);
+        let generate_id = match (id_strategy, id_prefix) {
+            (None, Some(prefix)) => quote! { ::snugom::id::generate_prefixed_id(#prefix) },
+            (None, None) => quote! { ::snugom::id::generate_entity_id() },
+            (Some(strategy), prefix) => {
+                let id = match strategy {
+                    IdStrategy::Ulid => quote! { ::snugom::id::generate_ulid() },
+                    IdStrategy::UuidV7 => quote! { ::snugom::id::generate_uuid_v7() },
+                };
+                match prefix {
+                    Some(prefix) => quote! { ::std::format!("{}_{}", #prefix, #id) },
+                    None => id,
+                }
+            }
+        };
+        let id_prefix_tokens = match id_prefix {
+            Some(prefix) => quote! { ::std::option::Option::Some(#prefix.to_string()) },
+            None => quote! { ::std::option::Option::None },
+        };
         let id_autofill = quote! {
             if self.#id_ident.is_none() {
-                self.#id_ident = Some(::snugom::id::generate_entity_id());
+                self.#id_ident = Some(#generate_id);
             }
         };
+        let slug_autofill: Vec<_> = self
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let spec = field.slug.as_ref()?;
+                let source = self.fields.iter().find(|source| source.name == spec.from_name)?;
+                let slug_ident = &field.ident;
+                let source_ident = &source.ident;
+                let pattern = if source.ty.optional { quote! { Some(Some(source)) } } else { quote! { Some(source) } };
+                Some(quote! {
+                    if self.#slug_ident.is_none()
+                        && let #pattern = &self.#source_ident
+                    {
+                        let slug = ::snugom::slug::slugify(source);
+                        if !slug.is_empty() {
+                            self.#slug_ident = Some(slug);
+                        }
+                    }
+                })
+            })
+            .collect();
+        // Supplied ids are checked here rather than in validate(), so entities stored before the
+        // prefix was added still load and update
+        let id_prefix_check = id_prefix.map(|prefix| {
+            let message = LitStr::new(&format!("id must start with `{prefix}_`"), Span::call_site());
+            quote! {
+                if let Some(id) = &self.#id_ident
+                    && !::snugom::id::has_id_prefix(id, #prefix)
+                {
+                    issues.push(::snugom::errors::ValidationIssue::new(#id_field_lit, "validation.id_prefix", #message));
+                }
+            }
+        });
         let datetime_method = {
             let body = if datetime_snippets.is_empty() {
                 quote! { ::std::vec::Vec::new() }
@@ -515,6 +1128,83 @@ impl ParsedEntity {
             quote! { ::std::option::Option::None }
         };
 
+        let through_relation_tokens: Vec<_> = self
+            .through_relations
+            .iter()
+            .map(|spec| {
+                let alias = &spec.alias;
+                let through = &spec.through;
+                let source = &spec.source;
+                quote! {
+                    ::snugom::types::ThroughRelationDescriptor {
+                        alias: #alias.to_string(),
+                        through: #through.to_string(),
+                        source: #source.to_string(),
+                    }
+                }
+            })
+            .collect();
+
+        // The snippet is embedded at compile time so edits to the file trigger a rebuild.
+        let description_tokens = option_string_tokens(&self.description);
+        let entity_validator_calls = (!self.custom_validators.is_empty()).then(|| {
+            let paths = self.custom_validators.iter().map(|(path, _)| path);
+            quote! {
+                // Like the repository, entity validators only run once the fields are valid
+                if issues.is_empty() {
+                    #(
+                        if let Err(err) = #paths(self) {
+                            issues.extend(err.issues);
+                        }
+                    )*
+                }
+            }
+        });
+        let custom_validator_tokens = self.custom_validators.iter().map(|(path, lit)| {
+            quote! {
+                ::snugom::types::CustomValidatorDescriptor {
+                    path: #lit.to_string(),
+                    check: |value: &::serde_json::Value| -> ::snugom::errors::ValidationResult<()> {
+                        let entity: #name = ::serde_json::from_value(value.clone()).map_err(|err| {
+                            ::snugom::errors::ValidationError::single("__entity", "validation.invalid_type", err.to_string())
+                        })?;
+                        #path(&entity)
+                    },
+                }
+            }
+        });
+        let hook_tokens = self.hook_tokens();
+        let keep_versions_tokens = match self.keep_versions {
+            Some(keep) => quote! { ::std::option::Option::Some(#keep) },
+            None => quote! { ::std::option::Option::None },
+        };
+        let key_separator_tokens = match self.key_separator {
+            Some(separator) => quote! { ::std::option::Option::Some(#separator) },
+            None => quote! { ::std::option::Option::None },
+        };
+        let key_components = &self.key_components;
+        let entity_ty: Type = syn::parse_quote!(#name);
+        let async_validator_tokens: Vec<TokenStream2> = self
+            .fields
+            .iter()
+            .flat_map(|field| field.async_validator_inits())
+            .chain(
+                self.async_validators
+                    .iter()
+                    .map(|(path, lit)| async_validator_tokens(None, path, lit, &entity_ty, false)),
+            )
+            .collect();
+        let lua_check_tokens = match &self.lua_check {
+            Some(path) => quote! {
+                ::std::option::Option::Some(
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #path)).to_string()
+                )
+            },
+            None => quote! { ::std::option::Option::None },
+        };
+
+        let storage_tokens = self.storage_tokens();
+
         // Generate SearchEntity implementation if there are indexed fields
         let has_indexed_fields = self.fields.iter().any(|f| f.has_index());
         let search_entity_impl = self.emit_search_entity();
@@ -536,10 +1226,23 @@ impl ParsedEntity {
                             collection: <#name as ::snugom::types::SnugomModel>::COLLECTION.to_string(),
                             version: #version,
                             id_field: Some(#id_field_lit.to_string()),
+                            id_prefix: #id_prefix_tokens,
                             relations: vec![#(#relation_inits),*],
                             fields: vec![#(#field_inits),*],
                             derived_id: #derived_id_tokens,
                             unique_constraints: vec![#(#unique_constraint_tokens),*],
+                            through_relations: vec![#(#through_relation_tokens),*],
+                            custom_validators: vec![#(#custom_validator_tokens),*],
+                            async_validators: vec![#(#async_validator_tokens),*],
+                            lua_check: #lua_check_tokens,
+                            description: #description_tokens,
+                            storage: #storage_tokens,
+                            hooks: #hook_tokens,
+                            keep_versions: #keep_versions_tokens,
+                            keys: ::snugom::keys::KeyOverrides {
+                                separator: #key_separator_tokens,
+                                components: vec![#(#key_components.to_string()),*],
+                            },
                         });
                         ::snugom::registry::register_descriptor(descriptor);
                     });
@@ -555,9 +1258,15 @@ impl ParsedEntity {
                 /// Relation targets for compile-time validation
                 pub const RELATION_TARGETS: &'static [&'static str] = &[#(#relation_targets),*];
 
+                /// `(service, collection)` of relations into other services, checked against the
+                /// entities and `shared` list of a `SnugomClient`
+                pub const SERVICE_RELATION_TARGETS: &'static [(&'static str, &'static str)] =
+                    &[#(#service_relation_targets),*];
+
                 pub fn validate(&self) -> ::snugom::errors::ValidationResult<()> {
                     let mut issues: Vec<::snugom::errors::ValidationIssue> = Vec::new();
                     #(#validation_snippets)*
+                    #entity_validator_calls
                     if issues.is_empty() {
                         Ok(())
                     } else {
@@ -582,7 +1291,9 @@ impl ParsedEntity {
 
                 pub fn build(mut self) -> ::snugom::errors::ValidationResult<#name> {
                     #id_autofill
+                    #(#slug_autofill)*
                     let mut issues: Vec<::snugom::errors::ValidationIssue> = self.validation_issues.clone();
+                    #id_prefix_check
                     #(#builder_required_checks)*
                     if !issues.is_empty() {
                         return Err(::snugom::errors::ValidationError::new(issues));
@@ -699,6 +1410,8 @@ impl ParsedEntity {
                 }
 
                 #(#patch_setters)*
+                #(#patch_increments)*
+                #(#patch_array_methods)*
 
                 pub fn connect(mut self, alias: impl Into<String>, values: Vec<String>) -> Self {
                     self.relations
@@ -712,6 +1425,33 @@ impl ParsedEntity {
                     self
                 }
 
+                pub fn connect_with<E>(mut self, alias: impl Into<String>, id: impl Into<String>, edge_data: E) -> Self
+                where
+                    E: ::serde::Serialize,
+                {
+                    match ::snugom::repository::RelationPlan::connect_with(alias, id, &edge_data) {
+                        Ok(plan) => self.relations.push(plan),
+                        Err(issue) => self.validation_issues.push(issue),
+                    }
+                    self
+                }
+
+                pub fn connect_with_mut<E>(
+                    &mut self,
+                    alias: impl Into<String>,
+                    id: impl Into<String>,
+                    edge_data: E,
+                ) -> &mut Self
+                where
+                    E: ::serde::Serialize,
+                {
+                    match ::snugom::repository::RelationPlan::connect_with(alias, id, &edge_data) {
+                        Ok(plan) => self.relations.push(plan),
+                        Err(issue) => self.validation_issues.push(issue),
+                    }
+                    self
+                }
+
                 pub fn disconnect(mut self, alias: impl Into<String>, values: Vec<String>) -> Self {
                     self.relations
                         .push(::snugom::repository::RelationPlan::new(alias, Vec::new(), values));
@@ -757,6 +1497,7 @@ impl ParsedEntity {
                                 alias: alias_string,
                                 descriptor: <B::Entity as ::snugom::types::EntityMetadata>::entity_descriptor(),
                                 payload,
+                                connect_existing: false,
                             });
                         }
                         Err(err) => {
@@ -789,6 +1530,7 @@ impl ParsedEntity {
                                 alias: alias_string,
                                 descriptor: <B::Entity as ::snugom::types::EntityMetadata>::entity_descriptor(),
                                 payload,
+                                connect_existing: false,
                             });
                         }
                         Err(err) => {
@@ -798,6 +1540,49 @@ impl ParsedEntity {
                     self
                 }
 
+                /// Make `values` the whole relation, disconnecting current members not among them.
+                pub fn set_relation(mut self, alias: impl Into<String>, values: Vec<String>) -> Self {
+                    self.relations.push(::snugom::repository::RelationPlan::set(alias, values));
+                    self
+                }
+
+                pub fn set_relation_mut(&mut self, alias: impl Into<String>, values: Vec<String>) -> &mut Self {
+                    self.relations.push(::snugom::repository::RelationPlan::set(alias, values));
+                    self
+                }
+
+                /// Connect the entity with the builder's id, creating it first if it doesn't exist.
+                pub fn connect_or_create<B>(mut self, alias: impl Into<String>, builder: B) -> Self
+                where
+                    B: ::snugom::repository::MutationPayloadBuilder,
+                    <B as ::snugom::repository::MutationPayloadBuilder>::Entity: ::snugom::types::EntityMetadata,
+                {
+                    self.connect_or_create_mut(alias, builder);
+                    self
+                }
+
+                pub fn connect_or_create_mut<B>(&mut self, alias: impl Into<String>, builder: B) -> &mut Self
+                where
+                    B: ::snugom::repository::MutationPayloadBuilder,
+                    <B as ::snugom::repository::MutationPayloadBuilder>::Entity: ::snugom::types::EntityMetadata,
+                {
+                    let index = self.nested_creates.len();
+                    self.create_relation_mut(alias, builder);
+                    if let Some(mutation) = self.nested_creates.get_mut(index) {
+                        if mutation.payload.nested.is_empty() {
+                            mutation.connect_existing = true;
+                        } else {
+                            // They would be created even when the entity turns out to exist
+                            self.validation_issues.push(::snugom::errors::ValidationIssue::new(
+                                format!("relations.{}", mutation.alias),
+                                "relation.connect_or_create_nested",
+                                "connect_or_create cannot contain nested creates",
+                            ));
+                        }
+                    }
+                    self
+                }
+
                 pub fn build_patch(mut self) -> ::snugom::errors::ValidationResult<::snugom::repository::MutationPatch> {
                     if self.entity_id.is_none() {
                         self.validation_issues.push(::snugom::errors::ValidationIssue::new(
@@ -836,6 +1621,7 @@ impl ParsedEntity {
                         nested,
                         idempotency_key,
                         idempotency_ttl,
+                        precondition: None,
                     })
                 }
             }
@@ -885,14 +1671,508 @@ impl ParsedEntity {
 
         // Generate SnugomModel impl and inventory registration
         let auto_registration = self.emit_auto_registration();
+        let graphql_types = self.emit_graphql();
+        let filter_field_schema = self.emit_filter_field_schema();
+        let codec_requirements = self.emit_codec_requirements();
+        let views = self.emit_views();
+        let factory = self.emit_factory();
+        let nested_path_checks = self.emit_nested_path_checks();
+        let relation_accessors = self.emit_relation_accessors();
 
         quote! {
             #base_impl
             #auto_registration
+            #graphql_types
+            #filter_field_schema
+            #codec_requirements
+            #views
+            #factory
+            #nested_path_checks
+            #relation_accessors
+        }
+    }
+
+    /// Generate `{Entity}Factory` for #[snugom(factory)]: validation builders pre-filled with
+    /// fakes for every required field, plus create helpers against a collection handle.
+    fn emit_factory(&self) -> TokenStream2 {
+        if !self.factory {
+            return quote! {};
+        }
+        let name = &self.name;
+        let vis = &self.vis;
+        let factory_ident = format_ident!("{}Factory", name);
+        let builder_ident = format_ident!("{}ValidationBuilder", name);
+        let doc = format!("Test data factory for [`{}`], generated by `#[snugom(factory)]`.", name);
+        let setters = self.fields.iter().filter_map(|field| field.factory_setter());
+
+        quote! {
+            #[doc = #doc]
+            #vis struct #factory_ident;
+
+            impl #factory_ident {
+                /// Next value of the sequence numbering this factory's fakes, starting at 1.
+                pub fn sequence() -> u64 {
+                    static SEQUENCE: ::snugom::factory::Sequence = ::snugom::factory::Sequence::new();
+                    SEQUENCE.next()
+                }
+
+                /// A validation builder with every required field set to a valid fake; chain
+                /// setters to override any of them.
+                pub fn builder() -> #builder_ident {
+                    let n = Self::sequence();
+                    #name::validation_builder()
+                        #(#setters)*
+                }
+
+                /// Create one entity from [`Self::builder`].
+                pub async fn create(
+                    handle: &mut ::snugom::CollectionHandle<#name>,
+                ) -> ::std::result::Result<#name, ::snugom::errors::RepoError> {
+                    handle.create_and_get(Self::builder()).await
+                }
+
+                /// Create `count` entities from [`Self::builder`].
+                pub async fn create_batch(
+                    handle: &mut ::snugom::CollectionHandle<#name>,
+                    count: usize,
+                ) -> ::std::result::Result<::std::vec::Vec<#name>, ::snugom::errors::RepoError> {
+                    Self::create_batch_with(handle, count, |_, builder| builder).await
+                }
+
+                /// Create `count` entities, passing each builder and its index through `customize`.
+                pub async fn create_batch_with<F>(
+                    handle: &mut ::snugom::CollectionHandle<#name>,
+                    count: usize,
+                    mut customize: F,
+                ) -> ::std::result::Result<::std::vec::Vec<#name>, ::snugom::errors::RepoError>
+                where
+                    F: FnMut(usize, #builder_ident) -> #builder_ident,
+                {
+                    let mut created = ::std::vec::Vec::with_capacity(count);
+                    for index in 0..count {
+                        created.push(handle.create_and_get(customize(index, Self::builder())).await?);
+                    }
+                    Ok(created)
+                }
+            }
+        }
+    }
+
+    /// Generate one `{Entity}{View}` struct per `#[snugom(view(...))]` entry, each a
+    /// `ViewModel` of the entity so search and get helpers fetch only its fields.
+    fn emit_views(&self) -> TokenStream2 {
+        let name = &self.name;
+        let vis = &self.vis;
+        let views = self.views.iter().map(|view| {
+            let view_ident = format_ident!("{}{}", name, view.name);
+            let doc = format!("`{}` view of [`{}`], generated by `#[snugom(view)]`.", view.name, name);
+            let fields: Vec<&ParsedField> = view
+                .fields
+                .iter()
+                .filter_map(|ident| self.fields.iter().find(|field| field.ident == *ident))
+                .collect();
+            let members = fields.iter().map(|field| {
+                let ident = &field.ident;
+                let ty = &field.ty.ty;
+                let doc = field.description.as_ref().map(|doc| quote! { #[doc = #doc] });
+                // Paths missing from a projection deserialize as None
+                let default = field.ty.optional.then(|| quote! { #[serde(default)] });
+                let stored = &field.name;
+                let rename = (*stored != field.rust_name()).then(|| quote! { #[serde(rename = #stored)] });
+                quote! {
+                    #doc
+                    #default
+                    #rename
+                    #vis #ident: #ty
+                }
+            });
+            let names = fields.iter().map(|field| &field.name);
+            quote! {
+                #[doc = #doc]
+                #[derive(Debug, Clone, ::serde::Serialize, ::serde::Deserialize)]
+                #vis struct #view_ident {
+                    #(#members),*
+                }
+
+                impl ::snugom::search::ViewModel<#name> for #view_ident {
+                    fn fields() -> &'static [&'static str] {
+                        &[#(#names),*]
+                    }
+                }
+            }
+        });
+        quote! { #(#views)* }
+    }
+
+    /// Generate `{Entity}Relations`, one method per has-many and many-to-many relation on
+    /// `CollectionHandle<Entity>`, so callers name the relation instead of passing its alias.
+    fn emit_relation_accessors(&self) -> TokenStream2 {
+        let name = &self.name;
+        let vis = &self.vis;
+        let accessors: Vec<(Ident, &str, &syn::Path)> = self
+            .relations
+            .iter()
+            .filter(|relation| !matches!(relation.kind, RelationKind::BelongsTo))
+            .filter_map(|relation| {
+                let method = syn::parse_str::<Ident>(&relation.alias).ok()?;
+                Some((method, relation.alias.as_str(), relation.model.as_ref()?))
+            })
+            .collect();
+        if accessors.is_empty() {
+            return TokenStream2::new();
+        }
+        let trait_ident = format_ident!("{}Relations", name);
+        let trait_doc = format!("Relation accessors for `CollectionHandle<{name}>`, generated from its relations.");
+        let signatures = accessors.iter().map(|(method, alias, model)| {
+            let doc = format!(
+                "Load the `{alias}` relation of the entity with `id`, paged by `options` and filtered or \
+                 sorted through the target's index when they ask to; see `CollectionHandle::get_related`."
+            );
+            quote! {
+                #[doc = #doc]
+                fn #method(
+                    &mut self,
+                    id: &str,
+                    options: ::snugom::types::RelationQueryOptions,
+                ) -> impl ::std::future::Future<
+                    Output = Result<::snugom::types::RelationData<Vec<#model>>, ::snugom::errors::RepoError>,
+                >;
+            }
+        });
+        let methods = accessors.iter().map(|(method, alias, model)| {
+            quote! {
+                fn #method(
+                    &mut self,
+                    id: &str,
+                    options: ::snugom::types::RelationQueryOptions,
+                ) -> impl ::std::future::Future<
+                    Output = Result<::snugom::types::RelationData<Vec<#model>>, ::snugom::errors::RepoError>,
+                > {
+                    self.get_related::<#model>(id, #alias, options)
+                }
+            }
+        });
+        quote! {
+            #[doc = #trait_doc]
+            #vis trait #trait_ident {
+                #(#signatures)*
+            }
+
+            impl #trait_ident for ::snugom::CollectionHandle<#name> {
+                #(#methods)*
+            }
+        }
+    }
+
+    /// Require snugom's `gzip`/`zstd` feature for each codec used by a compressed field.
+    fn emit_codec_requirements(&self) -> TokenStream2 {
+        let uses = |codec| self.fields.iter().any(|field| field.compression == Some(codec));
+        let gzip = uses(CompressionCodec::Gzip).then(|| quote! { ::snugom::__snugom_require_gzip! {} });
+        let zstd = uses(CompressionCodec::Zstd).then(|| quote! { ::snugom::__snugom_require_zstd! {} });
+        quote! {
+            #gzip
+            #zstd
+        }
+    }
+
+    /// Generate `{Entity}FilterField`, an OpenAPI string enum of the entity's filter names.
+    /// Expands to nothing unless snugom's `utoipa` feature is enabled.
+    fn emit_filter_field_schema(&self) -> TokenStream2 {
+        let filters: Vec<(String, FilterFieldType)> = self
+            .filter_names()
+            .into_iter()
+            .filter(|(_, field_type)| *field_type != FilterFieldType::Geo)
+            .collect();
+        if filters.is_empty() {
+            return quote! {};
+        }
+        let name = &self.name;
+        let vis = &self.vis;
+        let enum_ident = format_ident!("{}FilterField", name);
+        let enum_doc = format!(
+            "Fields accepted in `{}` search filters (`field:operator:value`).",
+            name
+        );
+        let mut variants = Vec::new();
+        let mut arms = Vec::new();
+        for (filter_name, field_type) in &filters {
+            let variant = format_ident!("{}", to_pascal_case(&filter_name.replace('.', "_")));
+            let operators = match field_type {
+                FilterFieldType::Tag => "eq",
+                FilterFieldType::Text => "eq, prefix, contains, exact, fuzzy",
+                FilterFieldType::Numeric => "eq, range",
+                FilterFieldType::Boolean => "bool",
+                FilterFieldType::Geo => unreachable!("geo filters are skipped"),
+            };
+            let doc = format!("`{}` ({})", filter_name, operators);
+            variants.push(quote! {
+                #[doc = #doc]
+                #[schema(rename = #filter_name)]
+                #variant
+            });
+            arms.push(quote! { Self::#variant => #filter_name });
+        }
+
+        quote! {
+            ::snugom::__snugom_utoipa! {
+                #[doc = #enum_doc]
+                #[allow(dead_code)]
+                #[derive(::snugom::utoipa::ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+                #vis enum #enum_ident {
+                    #(#variants),*
+                }
+
+                impl #enum_ident {
+                    /// The filter name as used in `SearchQuery::filter`.
+                    #[allow(dead_code)]
+                    pub const fn as_str(self) -> &'static str {
+                        match self {
+                            #(#arms),*
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generate `{Entity}Connection` and `{Entity}Filter` GraphQL types for #[snugom(graphql)].
+    ///
+    /// The filter input has one member per filterable field (`_min`/`_max` for numeric ones)
+    /// and lowers to the `field:op:value` strings that `SearchQuery::filter` accepts.
+    fn emit_graphql(&self) -> TokenStream2 {
+        if !self.graphql {
+            return quote! {};
+        }
+        let name = &self.name;
+        let vis = &self.vis;
+        let connection_ident = format_ident!("{}Connection", name);
+        let filter_ident = format_ident!("{}Filter", name);
+        let connection_doc = format!("A page of `{}` search results.", name);
+        let filter_doc = format!("Filters for `{}` searches; all set members must match.", name);
+
+        let mut filter_members = Vec::new();
+        let mut filter_pushes = Vec::new();
+        for (filter_name, field_type) in self.filter_names() {
+            // Nested filters (`address.city`) become `address_city` members
+            let member_name = filter_name.replace('.', "_");
+            let member = format_ident!("{}", member_name);
+            match field_type {
+                FilterFieldType::Tag => {
+                    let doc = format!("`{}` equals any of these values.", filter_name);
+                    filter_members.push(quote! {
+                        #[doc = #doc]
+                        pub #member: ::std::option::Option<::std::vec::Vec<::std::string::String>>,
+                    });
+                    filter_pushes.push(quote! {
+                        if let Some(values) = self.#member.filter(|values| !values.is_empty()) {
+                            filters.push(format!("{}:eq:{}", #filter_name, values.join("|")));
+                        }
+                    });
+                }
+                FilterFieldType::Text => {
+                    let doc = format!("`{}` contains this text.", filter_name);
+                    filter_members.push(quote! {
+                        #[doc = #doc]
+                        pub #member: ::std::option::Option<::std::string::String>,
+                    });
+                    filter_pushes.push(quote! {
+                        if let Some(value) = self.#member {
+                            filters.push(format!("{}:contains:{}", #filter_name, value));
+                        }
+                    });
+                }
+                FilterFieldType::Numeric => {
+                    let min = format_ident!("{}_min", member_name);
+                    let max = format_ident!("{}_max", member_name);
+                    let min_doc = format!("Lower bound (inclusive) for `{}`.", filter_name);
+                    let max_doc = format!("Upper bound (inclusive) for `{}`.", filter_name);
+                    filter_members.push(quote! {
+                        #[doc = #min_doc]
+                        pub #min: ::std::option::Option<f64>,
+                        #[doc = #max_doc]
+                        pub #max: ::std::option::Option<f64>,
+                    });
+                    filter_pushes.push(quote! {
+                        if self.#min.is_some() || self.#max.is_some() {
+                            let bound = |value: ::std::option::Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+                            filters.push(format!("{}:range:{},{}", #filter_name, bound(self.#min), bound(self.#max)));
+                        }
+                    });
+                }
+                FilterFieldType::Boolean => {
+                    let doc = format!("`{}` equals this value.", filter_name);
+                    filter_members.push(quote! {
+                        #[doc = #doc]
+                        pub #member: ::std::option::Option<bool>,
+                    });
+                    filter_pushes.push(quote! {
+                        if let Some(value) = self.#member {
+                            filters.push(format!("{}:bool:{}", #filter_name, value));
+                        }
+                    });
+                }
+                FilterFieldType::Geo => {}
+            }
+        }
+
+        // GraphQL rejects input objects without fields
+        let filter_types = if filter_members.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                #[doc = #filter_doc]
+                #[derive(::snugom::async_graphql::InputObject, Default, Debug, Clone)]
+                #vis struct #filter_ident {
+                    #(#filter_members)*
+                }
+
+                impl ::snugom::graphql::GraphqlFilter for #filter_ident {
+                    fn into_filters(self) -> ::std::vec::Vec<::std::string::String> {
+                        let mut filters = ::std::vec::Vec::new();
+                        #(#filter_pushes)*
+                        filters
+                    }
+                }
+            }
+        };
+
+        quote! {
+            ::snugom::__snugom_graphql! {
+                #[doc = #connection_doc]
+                #[derive(::snugom::async_graphql::SimpleObject)]
+                #vis struct #connection_ident {
+                    pub items: ::std::vec::Vec<#name>,
+                    pub total: u64,
+                    pub page: u64,
+                    pub page_size: u64,
+                    pub has_more: bool,
+                }
+
+                impl ::std::convert::From<::snugom::search::SearchResult<#name>> for #connection_ident {
+                    fn from(result: ::snugom::search::SearchResult<#name>) -> Self {
+                        Self {
+                            has_more: result.has_more(),
+                            items: result.items,
+                            total: result.total,
+                            page: result.page,
+                            page_size: result.page_size,
+                        }
+                    }
+                }
+
+                #filter_types
+            }
         }
     }
 
     /// Generate the impl SearchEntity if there are any indexed fields
+    /// Filter names and types, top-level fields first and then nested ones.
+    fn filter_names(&self) -> Vec<(String, FilterFieldType)> {
+        let top = self
+            .fields
+            .iter()
+            .filter_map(|field| field.filter_spec.as_ref().map(|spec| (field.filter_name(), spec.field_type)));
+        let nested = self
+            .fields
+            .iter()
+            .flat_map(|field| &field.nested)
+            .filter_map(|nested| nested.filter_spec.as_ref().map(|spec| (nested.filter_name(), spec.field_type)));
+        top.chain(nested).collect()
+    }
+
+    /// Reference every `#[snugom(flatten(...))]` field so a misspelled or removed one fails to compile.
+    fn emit_nested_path_checks(&self) -> TokenStream2 {
+        let checks: Vec<_> = self
+            .fields
+            .iter()
+            .flat_map(|field| field.nested.iter().map(move |nested| nested.path_check(field)))
+            .collect();
+        if checks.is_empty() {
+            return quote! {};
+        }
+        let name = &self.name;
+        quote! {
+            const _: () = {
+                #[allow(dead_code)]
+                fn __snugom_nested_paths(entity: &#name) {
+                    #(#checks)*
+                }
+            };
+        }
+    }
+
+    fn storage_tokens(&self) -> TokenStream2 {
+        if self.hash_storage {
+            quote! { ::snugom::types::StorageMode::Hash }
+        } else {
+            quote! { ::snugom::types::StorageMode::Json }
+        }
+    }
+
+    /// The `LifecycleHooks` literal, wrapping each callback so the repository can hand it the raw document.
+    ///
+    /// A document that does not deserialize as the entity skips the hook; validation reports why.
+    fn hook_tokens(&self) -> TokenStream2 {
+        let name = &self.name;
+        // Documents carry snugom's `metadata` object, which the struct doesn't know unless it
+        // has a field of that name
+        let document = if self.fields.iter().any(|field| field.name == "metadata") {
+            quote! { value.clone() }
+        } else {
+            quote! {{
+                let mut document = value.clone();
+                if let Some(fields) = document.as_object_mut() {
+                    fields.remove("metadata");
+                }
+                document
+            }}
+        };
+        let hook = |kind: &str| {
+            let Some((_, path)) = self.hooks.iter().find(|(hook, _)| hook == kind) else {
+                return quote! { ::std::option::Option::None };
+            };
+            if kind.starts_with("before") {
+                quote! {
+                    ::std::option::Option::Some(
+                        |value: &mut ::serde_json::Value| -> ::snugom::errors::ValidationResult<()> {
+                            let mut entity = ::serde_json::from_value::<#name>(#document).map_err(|err| {
+                                ::snugom::errors::ValidationError::single("__entity", "deserialization.failed", err.to_string())
+                            })?;
+                            #path(&mut entity);
+                            let updated = ::serde_json::to_value(&entity).map_err(|err| {
+                                ::snugom::errors::ValidationError::single("__entity", "serialization.failed", err.to_string())
+                            })?;
+                            if let (Some(target), ::serde_json::Value::Object(fields)) = (value.as_object_mut(), updated) {
+                                target.extend(fields);
+                            }
+                            Ok(())
+                        },
+                    )
+                }
+            } else {
+                quote! {
+                    ::std::option::Option::Some(|value: &::serde_json::Value| {
+                        if let Ok(entity) = ::serde_json::from_value::<#name>(#document) {
+                            #path(&entity);
+                        }
+                    })
+                }
+            }
+        };
+        let before_create = hook("before_create");
+        let after_create = hook("after_create");
+        let before_update = hook("before_update");
+        let after_update = hook("after_update");
+        quote! {
+            ::snugom::types::LifecycleHooks {
+                before_create: #before_create,
+                after_create: #after_create,
+                before_update: #before_update,
+                after_update: #after_update,
+            }
+        }
+    }
+
     fn emit_search_entity(&self) -> TokenStream2 {
         // Check if we have any indexed fields
         let has_indexed_fields = self.fields.iter().any(|f| f.has_index());
@@ -904,9 +2184,11 @@ impl ParsedEntity {
 
         // Generate index schema static
         let index_schema_ident = format_ident!("__SNUGOM_INDEX_SCHEMA_{}", self.name.to_string().to_uppercase());
+        let nested: Vec<_> = self.fields.iter().flat_map(|f| &f.nested).collect();
         let index_fields: Vec<_> = self.fields
             .iter()
-            .filter_map(|f| f.to_index_field_tokens())
+            .flat_map(|f| f.to_index_field_tokens())
+            .chain(nested.iter().map(|n| n.to_index_field_tokens()))
             .collect();
         let index_field_count = index_fields.len();
 
@@ -915,6 +2197,7 @@ impl ParsedEntity {
         let sort_fields: Vec<_> = self.fields
             .iter()
             .filter_map(|f| f.to_sort_field_tokens())
+            .chain(nested.iter().filter_map(|n| n.to_sort_field_tokens()))
             .collect();
         let sort_field_count = sort_fields.len();
 
@@ -923,6 +2206,7 @@ impl ParsedEntity {
             .iter()
             .filter(|f| f.is_text_searchable())
             .map(|f| f.index_field_name())
+            .chain(nested.iter().filter(|n| n.is_searchable).map(|n| n.attribute()))
             .collect();
         let text_field_count = text_fields.len();
 
@@ -930,6 +2214,7 @@ impl ParsedEntity {
         let filter_arms: Vec<_> = self.fields
             .iter()
             .filter_map(|f| f.to_filter_match_arm())
+            .chain(nested.iter().filter_map(|n| n.to_filter_match_arm()))
             .collect();
 
         // Default sort logic
@@ -967,6 +2252,26 @@ impl ParsedEntity {
             }
         };
 
+        // Unique-constraint lookups are hashes under the collection prefix too; keep them out.
+        let index_filter = if self.hash_storage {
+            quote! { Some(format!("!startswith(@__key, \"{}\")", keys.unique_prefix(collection))) }
+        } else {
+            quote! { None }
+        };
+        let storage_tokens = self.storage_tokens();
+        let language = match &self.language {
+            Some(language) => quote! { Some(#language.to_string()) },
+            None => quote! { None },
+        };
+        let language_field = match &self.language_field {
+            Some(field) => quote! { Some(#field.to_string()) },
+            None => quote! { None },
+        };
+        let index_name = match &self.index_name {
+            Some(index_name) => quote! { format!("{}{}{}", prefix, keys.layout().separator, #index_name) },
+            None => quote! { keys.index_name(collection) },
+        };
+
         quote! {
             #[allow(non_upper_case_globals)]
             static #index_schema_ident: [::snugom::search::IndexField; #index_field_count] = [
@@ -980,13 +2285,17 @@ impl ParsedEntity {
 
             impl ::snugom::search::SearchEntity for #name {
                 fn index_definition(prefix: &str) -> ::snugom::search::IndexDefinition {
-                    let service = <#name as ::snugom::types::SnugomModel>::SERVICE;
                     let collection = <#name as ::snugom::types::SnugomModel>::COLLECTION;
+                    let descriptor = <#name as ::snugom::types::EntityMetadata>::entity_descriptor();
+                    let keys = ::snugom::keys::KeyContext::for_entity(prefix, &descriptor);
                     ::snugom::search::IndexDefinition {
-                        name: format!("{}:{}:{}:idx", prefix, service, collection),
-                        prefixes: vec![format!("{}:{}:{}:", prefix, service, collection)],
-                        filter: None,
+                        name: #index_name,
+                        prefixes: vec![keys.collection_prefix(collection)],
+                        filter: #index_filter,
                         schema: &#index_schema_ident,
+                        storage: #storage_tokens,
+                        language: #language,
+                        language_field: #language_field,
                     }
                 }
 
@@ -1043,6 +2352,12 @@ impl ParsedEntity {
         let collection_lit = LitStr::new(&collection, Span::call_site());
 
         let id_field = &self.id_field;
+        // Entities without indexed fields have no SearchEntity impl
+        let index_definition_fn = if self.fields.iter().any(|field| field.has_index()) {
+            quote! { Some(<#name as ::snugom::search::SearchEntity>::index_definition) }
+        } else {
+            quote! { None }
+        };
         quote! {
             // Auto-generated SnugomModel impl
             impl ::snugom::types::SnugomModel for #name {
@@ -1062,6 +2377,7 @@ impl ParsedEntity {
                     collection_name: #collection_lit,
                     service_name: #service_lit,
                     descriptor_fn: || <#name as ::snugom::types::EntityMetadata>::entity_descriptor(),
+                    index_definition_fn: #index_definition_fn,
                 }
             }
         }
@@ -1086,14 +2402,17 @@ impl ParsedRelation {
             Some(value) => quote! { ::std::option::Option::Some(#value.to_string()) },
             None => quote! { ::std::option::Option::None },
         };
+        let counter = option_string_tokens(&self.counter);
+        let target_service = option_string_tokens(&self.target_service);
         quote! {
             ::snugom::types::RelationDescriptor {
                 alias: #alias.to_string(),
                 target: #target.to_string(),
-                target_service: None,
+                target_service: #target_service,
                 kind: #kind,
                 cascade: #cascade,
                 foreign_key: #foreign_key,
+                counter: #counter,
             }
         }
     }
@@ -1102,7 +2421,11 @@ impl ParsedRelation {
 impl ParsedEntity {
     fn detect_derived_id(fields: &[ParsedField], relations: &[ParsedRelation]) -> Option<DerivedIdSpec> {
         let id_field = fields.iter().find(|field| field.is_id)?;
-        if !matches!(id_field.ty.base, FieldBase::String) {
+        // A prefixed or time-ordered id is always generated, never derived from other fields
+        if !matches!(id_field.ty.base, FieldBase::String)
+            || id_field.id_prefix.is_some()
+            || id_field.id_strategy.is_some()
+        {
             return None;
         }
         let id_field_name = id_field.name.clone();
@@ -1126,9 +2449,9 @@ impl ParsedEntity {
         let mut candidates = fields
             .iter()
             .filter(|field| {
-                field.name.ends_with("_id")
+                field.rust_name().ends_with("_id")
                     && field.name != foreign_key
-                    && field.name != "tenant_id"
+                    && field.rust_name() != "tenant_id"
                     && field.name != id_field_name
                     && matches!(field.ty.base, FieldBase::String)
             })
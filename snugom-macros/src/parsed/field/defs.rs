@@ -5,14 +5,54 @@ pub(crate) struct ParsedField {
     validations: Vec<FieldValidation>,
     datetime_mirror: Option<String>,
     is_id: bool,
+    // Prefix from #[snugom(id(prefix = "..."))]
+    id_prefix: Option<String>,
+    // Time-ordered format from #[snugom(id(strategy = "..."))]; such ids are indexed as a sortable tag
+    id_strategy: Option<IdStrategy>,
     auto_updated: bool,
     auto_created: bool,
+    // Set by #[snugom(immutable)]; patches may not change the field once created
+    immutable: bool,
+    // Set by #[snugom(redact)]; stripped from search hits
+    redacted: bool,
+    // Tag values in sort order from #[snugom(enum_order = [...])], sorted through a numeric shadow
+    enum_order: Vec<String>,
     // Search-related fields
     index_spec: Option<IndexSpec>,
     filter_spec: Option<FilterSpec>,
     is_searchable: bool,
     // Relation inference
     relation_spec: Option<FieldRelationSpec>,
+    // Doc comment on the field, surfaced as FieldDescriptor::description
+    description: Option<String>,
+    // Set when a relation names this field as its `counter`; defaults to 0 in builders
+    is_counter: bool,
+    // Codec from #[snugom(compressed)]; compressed fields are stored as opaque blobs
+    compression: Option<CompressionCodec>,
+    // Fields of an embedded struct indexed via #[snugom(flatten(...))]
+    nested: Vec<NestedField>,
+    // Source of a generated slug from #[snugom(slug(from = "..."))]
+    slug: Option<SlugSpec>,
+    // Validator types from #[snugom(validate(async_custom = "..."))], run by the repository
+    async_validators: Vec<(TokenStream2, LitStr)>,
+}
+
+/// `#[snugom(slug(from = "title"))]` or `slug(from = "title", suffix)`
+#[derive(Clone)]
+pub(crate) struct SlugSpec {
+    /// Rust name of the field the slug is generated from
+    pub from: LitStr,
+    /// Stored name of that field, resolved once all fields are parsed
+    pub from_name: String,
+    /// Retry with `-2`, `-3`, ... when the slug is taken
+    pub suffix: bool,
+}
+
+/// Codec selected by `#[snugom(compressed)]` or `#[snugom(compressed = "...")]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CompressionCodec {
+    Gzip,
+    Zstd,
 }
 
 /// Specification for a field-based relation
@@ -22,6 +62,8 @@ pub(crate) struct FieldRelationSpec {
     pub kind: RelationKind,
     /// Target collection name (inferred from field name or Vec<T> type)
     pub target: String,
+    /// Service of the target collection, when it isn't this entity's
+    pub target_service: Option<String>,
     /// Alias for the relation (defaults to field name)
     pub alias: String,
     /// Cascade policy on delete
@@ -31,6 +73,17 @@ pub(crate) struct FieldRelationSpec {
     /// For many_to_many: the junction table name (reserved for future use)
     #[allow(dead_code)]
     pub junction: Option<String>,
+    /// Numeric field on this entity that mirrors the relation's member count
+    pub counter: Option<LitStr>,
+    /// Rust type of the target entity, which types the generated relation accessor
+    pub model: Option<syn::Path>,
+}
+
+/// Time-ordered id formats, from `#[snugom(id(strategy = "ulid"))]` or `"uuid_v7"`
+#[derive(Clone, Copy)]
+pub(crate) enum IdStrategy {
+    Ulid,
+    UuidV7,
 }
 
 /// Specification for how a field should be indexed in RediSearch
@@ -45,6 +98,8 @@ pub(crate) struct IndexSpec {
 pub(crate) struct FilterSpec {
     pub field_type: FilterFieldType,
     pub alias: Option<String>,
+    /// `filterable(tag, casefold)`: index and match a lowercase shadow copy
+    pub casefold: bool,
 }
 
 /// RediSearch index field types
@@ -87,11 +142,9 @@ enum FieldBase {
 
 #[derive(Clone)]
 struct ElementType {
-    #[allow(dead_code)]
     optional: bool,
     base: FieldBase,
     is_datetime: bool,
-    #[allow(dead_code)]
     ty: Type,
     /// The type name for Vec<T> elements (e.g., "GuildMember" for Vec<GuildMember>)
     type_name: Option<String>,
@@ -129,6 +182,10 @@ enum ValidationData {
     Email,
     Url,
     Uuid,
+    Phone,
+    IpAddr,
+    Hostname,
+    Slug,
     RequiredIf {
         expr: TokenStream2,
         expr_repr: String,
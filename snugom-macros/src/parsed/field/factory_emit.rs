@@ -0,0 +1,131 @@
+impl ParsedField {
+    /// Builder setter call filling this field with a fake for `#[snugom(factory)]`, when the
+    /// validation builder requires it. The value may use the factory sequence number `n`.
+    fn factory_setter(&self) -> Option<TokenStream2> {
+        if self.is_id || self.ty.optional || self.auto_updated || self.auto_created || self.is_counter {
+            return None;
+        }
+        if self.relation_spec.is_some() && matches!(self.ty.base, FieldBase::Vec) {
+            return None;
+        }
+        let ident = &self.ident;
+        let value = self.factory_value();
+        Some(quote! { .#ident(#value) })
+    }
+
+    fn factory_value(&self) -> TokenStream2 {
+        let rules: Vec<&ValidationData> = self
+            .validations
+            .iter()
+            .filter(|validation| matches!(validation.scope, ValidationScope::Field))
+            .map(|validation| &validation.data)
+            .collect();
+        let unique = rules.iter().any(|data| matches!(data, ValidationData::Unique { .. }));
+        let (min_len, max_len) = rules
+            .iter()
+            .find_map(|data| match data {
+                ValidationData::Length { min, max } => Some((*min, *max)),
+                _ => None,
+            })
+            .unwrap_or((None, None));
+        let name = &self.name;
+
+        if self.ty.is_datetime {
+            return quote! { ::chrono::Utc::now() };
+        }
+        match self.ty.base {
+            FieldBase::String => {
+                for data in &rules {
+                    match data {
+                        ValidationData::Enum { allowed, .. } => {
+                            if let Some(first) = allowed.first() {
+                                return quote! { #first };
+                            }
+                        }
+                        ValidationData::Email => {
+                            let template = format!("{}{{}}@example.com", name.replace('_', "."));
+                            return quote! { format!(#template, n) };
+                        }
+                        ValidationData::Url => {
+                            let template = format!("https://example.com/{}/{{}}", name);
+                            return quote! { format!(#template, n) };
+                        }
+                        ValidationData::Uuid => {
+                            return quote! { format!("00000000-0000-4000-8000-{:012x}", n) };
+                        }
+                        ValidationData::Phone => {
+                            return quote! { format!("+1555{:07}", n) };
+                        }
+                        ValidationData::IpAddr => {
+                            return quote! {
+                                ::std::net::Ipv4Addr::from(0x0a00_0000 | (n as u32 & 0x00ff_ffff)).to_string()
+                            };
+                        }
+                        ValidationData::Hostname => {
+                            let template = format!("{}-{{}}.example.com", name.to_ascii_lowercase().replace('_', "-"));
+                            return quote! { format!(#template, n) };
+                        }
+                        ValidationData::Slug => {
+                            let template = format!("{}-{{}}", name.to_ascii_lowercase().replace('_', "-"));
+                            return quote! { format!(#template, n) };
+                        }
+                        _ => {}
+                    }
+                }
+                let template = format!("{}-{{}}", name);
+                let min = factory_bound_tokens(min_len);
+                let max = factory_bound_tokens(max_len);
+                quote! { ::snugom::factory::fit_length(format!(#template, n), #min, #max) }
+            }
+            FieldBase::Numeric => {
+                let ty = &self.ty.ty;
+                let (min, max) = rules
+                    .iter()
+                    .find_map(|data| match data {
+                        ValidationData::Range { min, max, .. } => Some((min.clone(), max.clone())),
+                        _ => None,
+                    })
+                    .unwrap_or((None, None));
+                let start = min.unwrap_or_else(|| quote! { 0 });
+                let offset = unique.then(|| quote! { value += n as #ty; });
+                let clamp = max.map(|max| {
+                    quote! {
+                        if value > (#max) as #ty {
+                            value = (#max) as #ty;
+                        }
+                    }
+                });
+                quote! {{
+                    #[allow(unused_mut)]
+                    let mut value = (#start) as #ty;
+                    #offset
+                    #clamp
+                    value
+                }}
+            }
+            FieldBase::Boolean => quote! { false },
+            FieldBase::Vec => {
+                let string_elements = self
+                    .ty
+                    .element
+                    .as_ref()
+                    .is_some_and(|element| matches!(element.base, FieldBase::String) && !element.optional);
+                match min_len {
+                    Some(min) if min > 0 && string_elements => {
+                        let template = format!("{}-{{}}-{{}}", name);
+                        quote! { (0..#min).map(|index| format!(#template, n, index)).collect() }
+                    }
+                    _ => quote! { ::std::vec::Vec::new() },
+                }
+            }
+            _ => quote! { ::std::default::Default::default() },
+        }
+    }
+}
+
+fn factory_bound_tokens(value: Option<usize>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
@@ -7,3 +7,6 @@ include!("validation_impl.rs");
 include!("validation_emit.rs");
 include!("validation_helpers.rs");
 include!("filter_support.rs");
+include!("serde_names.rs");
+include!("nested.rs");
+include!("factory_emit.rs");
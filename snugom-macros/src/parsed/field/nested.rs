@@ -0,0 +1,182 @@
+/// A field of an embedded struct indexed through `#[snugom(flatten(...))]`, e.g. `address.city`.
+pub(crate) struct NestedField {
+    /// The field inside the embedded struct, checked to exist at compile time
+    ident: Ident,
+    /// The key the embedded struct stores the field under
+    key: String,
+    /// Dotted path from the document root (`address.city`), used for filter and sort names;
+    /// set once the parent's stored name is known
+    name: String,
+    index_spec: IndexSpec,
+    filter_spec: Option<FilterSpec>,
+    is_searchable: bool,
+}
+
+impl NestedField {
+    /// Parse `flatten(city(filterable(tag), sortable), zip(indexed(tag)))` on an embedded struct field.
+    ///
+    /// Nested types aren't visible to the derive, so filterable and indexed need an explicit type.
+    fn parse_list(meta: &syn::meta::ParseNestedMeta, nested: &mut Vec<NestedField>) -> Result<()> {
+        meta.parse_nested_meta(|item| {
+            let ident = item
+                .path
+                .get_ident()
+                .cloned()
+                .ok_or_else(|| item.error("expected a field of the embedded struct"))?;
+            let mut key = syn::ext::IdentExt::unraw(&ident).to_string();
+            if !item.input.peek(syn::token::Paren) {
+                return Err(item.error(format!(
+                    "`{key}` needs index options, e.g. {key}(filterable(tag)) or {key}(indexed(numeric), sortable)"
+                )));
+            }
+
+            let mut filter_type = None;
+            let mut index_type = None;
+            let mut sortable = false;
+            let mut searchable = false;
+            let mut alias = None;
+            item.parse_nested_meta(|option| {
+                if option.path.is_ident("filterable") {
+                    filter_type = Some(Self::parse_filter_type(&option)?);
+                } else if option.path.is_ident("indexed") {
+                    index_type = Some(Self::parse_index_type(&option)?);
+                } else if option.path.is_ident("sortable") {
+                    sortable = true;
+                } else if option.path.is_ident("searchable") {
+                    searchable = true;
+                } else if option.path.is_ident("alias") {
+                    let value: LitStr = option.value()?.parse()?;
+                    alias = Some(value.value());
+                } else if option.path.is_ident("rename") {
+                    let value: LitStr = option.value()?.parse()?;
+                    key = value.value();
+                } else {
+                    return Err(option.error(
+                        "unknown flatten option, expected filterable, indexed, sortable, searchable, alias, or rename",
+                    ));
+                }
+                Ok(())
+            })?;
+
+            if searchable && filter_type == Some(FilterFieldType::Tag) {
+                return Err(item.error("searchable and filterable(tag) cannot be used together on the same field"));
+            }
+            let field_type = if searchable {
+                IndexFieldType::Text
+            } else if let Some(filter_type) = filter_type {
+                ParsedField::filter_to_index_type(filter_type)
+            } else if let Some(index_type) = index_type {
+                index_type
+            } else if sortable {
+                return Err(item.error(format!(
+                    "sortable on nested field `{key}` requires filterable(...) or indexed(...) to determine index type"
+                )));
+            } else {
+                return Err(item.error(format!("`{key}` needs filterable, indexed, or searchable")));
+            };
+            if nested.iter().any(|existing| existing.key == key) {
+                return Err(item.error(format!("nested field `{key}` is listed twice")));
+            }
+
+            nested.push(NestedField {
+                ident,
+                key,
+                name: String::new(),
+                index_spec: IndexSpec { field_type, sortable },
+                filter_spec: filter_type.map(|field_type| FilterSpec {
+                    field_type,
+                    alias,
+                    casefold: false,
+                }),
+                is_searchable: searchable,
+            });
+            Ok(())
+        })
+    }
+
+    fn parse_filter_type(meta: &syn::meta::ParseNestedMeta) -> Result<FilterFieldType> {
+        if !meta.input.peek(syn::token::Paren) {
+            return Err(meta.error(
+                "filterable on a nested field requires explicit type: filterable(tag), filterable(text), \
+                 filterable(numeric), or filterable(boolean)",
+            ));
+        }
+        let content;
+        parenthesized!(content in meta.input);
+        let type_ident: Ident = content.parse()?;
+        match type_ident.to_string().as_str() {
+            "tag" => Ok(FilterFieldType::Tag),
+            "text" => Ok(FilterFieldType::Text),
+            "numeric" => Ok(FilterFieldType::Numeric),
+            "boolean" | "bool" => Ok(FilterFieldType::Boolean),
+            other => Err(Error::new(
+                type_ident.span(),
+                format!("unknown filter type '{}', expected tag, text, numeric, or boolean", other),
+            )),
+        }
+    }
+
+    fn parse_index_type(meta: &syn::meta::ParseNestedMeta) -> Result<IndexFieldType> {
+        if !meta.input.peek(syn::token::Paren) {
+            return Err(meta.error("indexed on a nested field requires explicit type: indexed(tag), indexed(text), or indexed(numeric)"));
+        }
+        let content;
+        parenthesized!(content in meta.input);
+        let type_ident: Ident = content.parse()?;
+        match type_ident.to_string().as_str() {
+            "tag" => Ok(IndexFieldType::Tag),
+            "text" => Ok(IndexFieldType::Text),
+            "numeric" => Ok(IndexFieldType::Numeric),
+            "geo" => Ok(IndexFieldType::Geo),
+            other => Err(Error::new(
+                type_ident.span(),
+                format!("unknown index type '{}', expected tag, text, numeric, or geo", other),
+            )),
+        }
+    }
+
+    /// Place the field under its parent: `address.city`, or just `city` when the parent is
+    /// `#[serde(flatten)]` and its fields are stored at the document root.
+    fn locate(&mut self, parent: &str, serde_flatten: bool) {
+        self.name = if serde_flatten { self.key.clone() } else { format!("{parent}.{}", self.key) };
+    }
+
+    /// Index attribute name; dots would need escaping in every query
+    fn attribute(&self) -> String {
+        self.name.replace('.', "_")
+    }
+
+    /// Get the filter alias or the dotted name
+    pub(crate) fn filter_name(&self) -> String {
+        self.filter_spec
+            .as_ref()
+            .and_then(|fs| fs.alias.clone())
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    pub(crate) fn to_index_field_tokens(&self) -> TokenStream2 {
+        index_field_tokens(&format!("$.{}", self.name), &self.attribute(), &self.index_spec, false)
+    }
+
+    pub(crate) fn to_sort_field_tokens(&self) -> Option<TokenStream2> {
+        self.index_spec
+            .sortable
+            .then(|| sort_field_tokens(&self.name, &self.attribute(), &self.index_spec))
+    }
+
+    pub(crate) fn to_filter_match_arm(&self) -> Option<TokenStream2> {
+        let fs = self.filter_spec.as_ref()?;
+        Some(filter_match_arm(&self.filter_name(), &self.attribute(), fs.field_type, false))
+    }
+
+    /// A reference to the nested value through `parent` on `entity`, so a missing field fails to compile
+    fn path_check(&self, parent: &ParsedField) -> TokenStream2 {
+        let parent_ident = &parent.ident;
+        let ident = &self.ident;
+        if parent.ty.optional {
+            quote! { let _ = entity.#parent_ident.as_ref().map(|inner| &inner.#ident); }
+        } else {
+            quote! { let _ = &entity.#parent_ident.#ident; }
+        }
+    }
+}
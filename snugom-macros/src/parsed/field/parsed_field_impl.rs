@@ -1,21 +1,30 @@
 impl ParsedField {
-    fn from_field(field: &Field) -> Result<Self> {
+    fn from_field(field: &Field, rename_all: Option<RenameRule>) -> Result<Self> {
         let ident = field
             .ident
             .clone()
             .ok_or_else(|| Error::new(field.span(), "SnugomEntity requires named fields"))?;
-        let name = ident.to_string();
+        let rust_name = syn::ext::IdentExt::unraw(&ident).to_string();
 
         let ty = classify_type(&field.ty);
         let mut validations = Vec::new();
         let mut datetime_mirror = None;
         let mut is_id = false;
+        let mut id_prefix = None;
+        let mut id_strategy = None;
         let mut auto_updated = false;
         let mut auto_created = false;
+        let mut immutable = false;
+        let mut redacted = false;
+        let mut enum_order = Vec::new();
         let mut index_spec = None;
         let mut filter_spec = None;
         let mut is_searchable = false;
         let mut relation_spec = None;
+        let mut compression = None;
+        let mut nested = Vec::new();
+        let mut slug = None;
+        let mut async_validators = Vec::new();
 
         for attr in &field.attrs {
             if attr.path().is_ident("snugom") {
@@ -25,17 +34,119 @@ impl ParsedField {
                     &mut validations,
                     &mut datetime_mirror,
                     &mut is_id,
+                    &mut id_prefix,
+                    &mut id_strategy,
                     &mut auto_updated,
                     &mut auto_created,
+                    &mut immutable,
+                    &mut redacted,
+                    &mut enum_order,
                     &mut index_spec,
                     &mut filter_spec,
                     &mut is_searchable,
                     &mut relation_spec,
-                    &name,
+                    &mut compression,
+                    &mut nested,
+                    &mut slug,
+                    &mut async_validators,
+                    &rust_name,
                 )?;
             }
         }
 
+        // Documents, indexes, filters, and validation issues all use the name serde writes
+        let name = serialized_field_name(field, &rust_name, rename_all)?;
+        if name != rust_name {
+            if datetime_mirror.is_some() {
+                datetime_mirror = Some(format!("{name}_ts"));
+            }
+            if let Some(spec) = relation_spec.as_mut()
+                && spec.foreign_key.as_deref() == Some(rust_name.as_str())
+            {
+                spec.foreign_key = Some(name.clone());
+            }
+        }
+
+        // A slug is unique whether or not the field also says so
+        if slug.is_some() && !validations.iter().any(|validation| matches!(validation.data, ValidationData::Unique { .. })) {
+            validations.push(FieldValidation {
+                scope: ValidationScope::Field,
+                data: ValidationData::Unique { case_insensitive: false },
+            });
+        }
+
+        let serde_flatten = serde_flag(&field.attrs, "flatten")?;
+        for nested_field in &mut nested {
+            nested_field.locate(&name, serde_flatten);
+        }
+
+        if ty.is_bytes() && (index_spec.is_some() || filter_spec.is_some()) {
+            return Err(Error::new(
+                ident.span(),
+                "Vec<u8> byte fields cannot be indexed or filterable; they are stored as base64",
+            ));
+        }
+
+        if redacted {
+            // Search hits arrive without the field, so it has to deserialize when absent
+            if is_id || !(ty.optional || serde_has(&field.attrs, "default")?) {
+                return Err(Error::new(
+                    ident.span(),
+                    "#[snugom(redact)] fields are left out of search hits; use an Option<T> field or add \
+                     #[serde(default)], and never redact the id",
+                ));
+            }
+            if index_spec.is_some() || filter_spec.is_some() || is_searchable {
+                return Err(Error::new(
+                    ident.span(),
+                    "#[snugom(redact)] fields cannot be indexed, filterable, or searchable",
+                ));
+            }
+        }
+
+        if !enum_order.is_empty() {
+            let tag = filter_spec.as_ref().is_some_and(|fs| fs.field_type == FilterFieldType::Tag && !fs.casefold);
+            if !tag || !index_spec.as_ref().is_some_and(|idx| idx.sortable) {
+                return Err(Error::new(
+                    ident.span(),
+                    "enum_order requires a sortable tag field: #[snugom(filterable(tag), sortable, enum_order = [...])]",
+                ));
+            }
+        }
+
+        if immutable && auto_updated {
+            return Err(Error::new(
+                ident.span(),
+                "#[snugom(immutable)] conflicts with #[snugom(updated_at)], which changes on every patch",
+            ));
+        }
+
+        if compression.is_some() {
+            let indexed = index_spec.is_some()
+                || filter_spec.is_some()
+                || is_searchable
+                || datetime_mirror.is_some()
+                || is_id
+                || relation_spec.is_some()
+                || !nested.is_empty()
+                || validations
+                    .iter()
+                    .any(|validation| matches!(validation.data, ValidationData::Unique { .. }));
+            if indexed {
+                return Err(Error::new(
+                    ident.span(),
+                    "compressed fields cannot be indexed/filterable; remove indexed, filterable, searchable, sortable, \
+                     unique, datetime, id, and relation from this field or drop #[snugom(compressed)]",
+                ));
+            }
+            if !matches!(ty.base, FieldBase::String | FieldBase::Vec | FieldBase::Other) || ty.is_datetime {
+                return Err(Error::new(
+                    ident.span(),
+                    "#[snugom(compressed)] is meant for large string or JSON fields, not numbers, booleans, or datetimes",
+                ));
+            }
+        }
+
         Ok(Self {
             ident,
             name,
@@ -43,27 +154,48 @@ impl ParsedField {
             validations,
             datetime_mirror,
             is_id,
+            id_prefix,
+            id_strategy,
             auto_updated,
             auto_created,
+            immutable,
+            redacted,
+            enum_order,
             index_spec,
             filter_spec,
             is_searchable,
             relation_spec,
+            description: doc_comment(&field.attrs),
+            is_counter: false,
+            compression,
+            nested,
+            slug,
+            async_validators,
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn parse_field_attr(
         attr: &Attribute,
         ty: &TypeInfo,
         validations: &mut Vec<FieldValidation>,
         datetime_mirror: &mut Option<String>,
         is_id: &mut bool,
+        id_prefix: &mut Option<String>,
+        id_strategy: &mut Option<IdStrategy>,
         auto_updated: &mut bool,
         auto_created: &mut bool,
+        immutable: &mut bool,
+        redacted: &mut bool,
+        enum_order: &mut Vec<String>,
         index_spec: &mut Option<IndexSpec>,
         filter_spec: &mut Option<FilterSpec>,
         is_searchable: &mut bool,
         relation_spec: &mut Option<FieldRelationSpec>,
+        compression: &mut Option<CompressionCodec>,
+        nested: &mut Vec<NestedField>,
+        slug: &mut Option<SlugSpec>,
+        async_validators: &mut Vec<(TokenStream2, LitStr)>,
         field_name: &str,
     ) -> Result<()> {
         // Track if we see sortable to apply after determining index type
@@ -71,10 +203,63 @@ impl ParsedField {
         let mut filter_alias: Option<String> = None;
 
         attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("relation") {
+            if meta.path.is_ident("compressed") {
+                // compressed or compressed = "gzip" | "zstd"
+                let codec = if meta.input.peek(syn::Token![=]) {
+                    let value: LitStr = meta.value()?.parse()?;
+                    match value.value().as_str() {
+                        "gzip" => CompressionCodec::Gzip,
+                        "zstd" => CompressionCodec::Zstd,
+                        other => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                format!("unknown compression codec `{other}`, expected gzip or zstd"),
+                            ));
+                        }
+                    }
+                } else {
+                    CompressionCodec::Gzip
+                };
+                *compression = Some(codec);
+            } else if meta.path.is_ident("flatten") {
+                if !matches!(ty.base, FieldBase::Other) || ty.is_datetime {
+                    return Err(meta.error("#[snugom(flatten(...))] requires an embedded struct field (or Option thereof)"));
+                }
+                NestedField::parse_list(&meta, nested)?;
+            } else if meta.path.is_ident("slug") {
+                if ty.optional || !matches!(ty.base, FieldBase::String) {
+                    return Err(meta.error("#[snugom(slug(...))] requires a field of type String"));
+                }
+                let mut from = None;
+                let mut suffix = false;
+                meta.parse_nested_meta(|option| {
+                    if option.path.is_ident("from") {
+                        from = Some(option.value()?.parse::<LitStr>()?);
+                    } else if option.path.is_ident("suffix") {
+                        suffix = true;
+                    } else {
+                        return Err(option.error("unknown slug option, expected from or suffix"));
+                    }
+                    Ok(())
+                })?;
+                let from = from.ok_or_else(|| meta.error("slug requires a source field: slug(from = \"title\")"))?;
+                *slug = Some(SlugSpec {
+                    from,
+                    from_name: String::new(),
+                    suffix,
+                });
+            } else if meta.path.is_ident("relation") {
                 Self::parse_relation_attr(&meta, ty, relation_spec, field_name)?;
             } else if meta.path.is_ident("validate") {
-                meta.parse_nested_meta(|rule| parse_validation_rule(rule, ty, validations, field_name))?;
+                meta.parse_nested_meta(|rule| {
+                    // Async validators need a connection, so the repository runs them rather than validate()
+                    if rule.path.is_ident("async_custom") {
+                        async_validators.push(parse_validator_path(&rule)?);
+                        Ok(())
+                    } else {
+                        parse_validation_rule(rule, ty, validations, field_name)
+                    }
+                })?;
             } else if meta.path.is_ident("datetime") {
                 // Creates a numeric mirror field (field_ts) storing epoch milliseconds for sorting/filtering
                 let _ = meta.parse_nested_meta(|_item| Ok(()));
@@ -94,6 +279,34 @@ impl ParsedField {
                 if !matches!(ty.base, FieldBase::String) {
                     return Err(meta.error("#[snugom(id)] requires a field of type String"));
                 }
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|option| {
+                        if option.path.is_ident("strategy") {
+                            let value: LitStr = option.value()?.parse()?;
+                            *id_strategy = Some(match value.value().as_str() {
+                                "ulid" => IdStrategy::Ulid,
+                                "uuid_v7" => IdStrategy::UuidV7,
+                                _ => {
+                                    return Err(Error::new(value.span(), "id strategy must be \"ulid\" or \"uuid_v7\""));
+                                }
+                            });
+                            return Ok(());
+                        }
+                        if !option.path.is_ident("prefix") {
+                            return Err(option.error("unknown id option, expected prefix or strategy"));
+                        }
+                        let value: LitStr = option.value()?.parse()?;
+                        let prefix = value.value();
+                        if prefix.is_empty() || !prefix.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+                            return Err(Error::new(
+                                value.span(),
+                                "id prefix must be non-empty and ASCII alphanumeric; `_` is added as the separator",
+                            ));
+                        }
+                        *id_prefix = Some(prefix);
+                        Ok(())
+                    })?;
+                }
                 *is_id = true;
             } else if meta.path.is_ident("updated_at") {
                 if *auto_updated {
@@ -111,9 +324,30 @@ impl ParsedField {
                     return Err(meta.error("#[snugom(created_at)] requires a chrono::DateTime<Tz> field"));
                 }
                 *auto_created = true;
+            } else if meta.path.is_ident("immutable") {
+                *immutable = true;
+            } else if meta.path.is_ident("redact") {
+                *redacted = true;
+            } else if meta.path.is_ident("enum_order") {
+                let list: syn::ExprArray = meta.value()?.parse()?;
+                for element in &list.elems {
+                    let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) = element else {
+                        return Err(Error::new(element.span(), "enum_order takes string tag values, e.g. [\"low\", \"high\"]"));
+                    };
+                    if enum_order.contains(&value.value()) {
+                        return Err(Error::new(value.span(), format!("`{}` is listed twice in enum_order", value.value())));
+                    }
+                    enum_order.push(value.value());
+                }
+                if enum_order.is_empty() {
+                    return Err(meta.error("enum_order needs at least one value"));
+                }
             } else if meta.path.is_ident("sortable") {
                 saw_sortable = true;
             } else if meta.path.is_ident("searchable") {
+                if ty.is_bytes() {
+                    return Err(meta.error("Vec<u8> byte fields cannot be searchable; they are stored as base64 and are never indexed"));
+                }
                 // searchable only works on String types - full-text search doesn't apply to numbers or enums
                 if !matches!(ty.base, FieldBase::String) {
                     return Err(meta.error("searchable can only be used on String fields; use filterable for numeric or enum types"));
@@ -127,7 +361,7 @@ impl ParsedField {
                 idx.field_type = IndexFieldType::Text;
             } else if meta.path.is_ident("filterable") {
                 // Parse optional type: filterable or filterable(tag) or filterable(text) etc.
-                let filter_type = Self::parse_filter_type(&meta, ty)?;
+                let (filter_type, casefold) = Self::parse_filter_type(&meta, ty)?;
                 let index_type = Self::filter_to_index_type(filter_type);
 
                 // Set index (filterable implies indexed)
@@ -144,6 +378,7 @@ impl ParsedField {
                 *filter_spec = Some(FilterSpec {
                     field_type: filter_type,
                     alias: None, // alias parsed separately
+                    casefold,
                 });
             } else if meta.path.is_ident("indexed") {
                 // Parse optional type: indexed or indexed(tag) or indexed(text) etc.
@@ -211,6 +446,18 @@ impl ParsedField {
             }
         }
 
+        // Time-ordered ids sort chronologically as a sortable tag, so sort_by=id lists by creation
+        if id_strategy.is_some() {
+            if let Some(idx) = index_spec {
+                idx.sortable = true;
+            } else {
+                *index_spec = Some(IndexSpec {
+                    field_type: IndexFieldType::Tag,
+                    sortable: true,
+                });
+            }
+        }
+
         // Auto-configure created_at/updated_at fields with datetime mirror, sortable, and filterable
         if *auto_created || *auto_updated {
             // Auto-add datetime mirror if not already set
@@ -231,6 +478,7 @@ impl ParsedField {
                 *filter_spec = Some(FilterSpec {
                     field_type: FilterFieldType::Numeric,
                     alias: None,
+                    casefold: false,
                 });
             }
         }
@@ -252,6 +500,8 @@ impl ParsedField {
     /// - `#[snugom(relation)]` on `{entity}_id: String` → belongs_to inferred from field name
     /// - `#[snugom(relation(many_to_many = "junction"))]` → explicit many_to_many
     /// - `#[snugom(relation(cascade = "delete"))]` → set cascade policy
+    /// - `#[snugom(relation(target_service = "billing"))]` → target lives in another service
+    /// - `#[snugom(relation(model = "Post"))]` → target entity type, for the relation accessor
     fn parse_relation_attr(
         meta: &syn::meta::ParseNestedMeta,
         ty: &TypeInfo,
@@ -265,9 +515,13 @@ impl ParsedField {
         // Default cascade policy
         let mut cascade = CascadePolicy::None;
         let mut explicit_target: Option<String> = None;
+        let mut target_service: Option<String> = None;
         let mut explicit_alias: Option<String> = None;
         let mut junction: Option<String> = None;
         let mut explicit_foreign_key: Option<String> = None;
+        let mut explicit_has_many = false;
+        let mut counter: Option<LitStr> = None;
+        let mut model: Option<syn::Path> = None;
 
         // Parse optional nested attributes: relation(...) or just relation
         if meta.input.peek(syn::token::Paren) {
@@ -286,19 +540,48 @@ impl ParsedField {
                 } else if nested.path.is_ident("target") {
                     let value: LitStr = nested.value()?.parse()?;
                     explicit_target = Some(value.value());
+                } else if nested.path.is_ident("target_service") {
+                    let value: LitStr = nested.value()?.parse()?;
+                    if value.value().trim().is_empty() {
+                        return Err(syn::Error::new(value.span(), "target_service requires a service name"));
+                    }
+                    target_service = Some(value.value());
                 } else if nested.path.is_ident("alias") {
                     let value: LitStr = nested.value()?.parse()?;
                     explicit_alias = Some(value.value());
                 } else if nested.path.is_ident("foreign_key") {
                     let value: LitStr = nested.value()?.parse()?;
                     explicit_foreign_key = Some(value.value());
+                } else if nested.path.is_ident("has_many") {
+                    explicit_has_many = true;
+                } else if nested.path.is_ident("counter") {
+                    let value: LitStr = nested.value()?.parse()?;
+                    if value.value().trim().is_empty() {
+                        return Err(syn::Error::new(value.span(), "counter requires a field name"));
+                    }
+                    counter = Some(value);
+                } else if nested.path.is_ident("model") {
+                    let value: LitStr = nested.value()?.parse()?;
+                    model = Some(value.parse().map_err(|_| {
+                        syn::Error::new(value.span(), "model requires the target entity's type, e.g. \"Post\"")
+                    })?);
                 } else {
-                    return Err(nested.error("unknown relation attribute, expected cascade, many_to_many, target, alias, or foreign_key"));
+                    return Err(nested.error("unknown relation attribute, expected cascade, many_to_many, has_many, target, target_service, alias, foreign_key, counter, or model"));
                 }
                 Ok(())
             })?;
         }
 
+        if explicit_has_many && (junction.is_some() || !matches!(ty.base, FieldBase::Vec)) {
+            return Err(meta.error("has_many relation must be on a Vec<T> field without many_to_many"));
+        }
+        if counter.is_some() && !matches!(ty.base, FieldBase::Vec) {
+            return Err(meta.error("counter is only supported on has_many and many_to_many relations"));
+        }
+        if model.is_some() && !matches!(ty.base, FieldBase::Vec) {
+            return Err(meta.error("model is only supported on has_many and many_to_many relations"));
+        }
+
         // Infer relation kind and target based on field type and name
         let (kind, target, alias, foreign_key) = if let Some(ref junction_target) = junction {
             // Explicit many_to_many - must be Vec<T>
@@ -316,7 +599,9 @@ impl ParsedField {
                 .ok_or_else(|| meta.error("cannot infer target type for has_many relation; ensure Vec contains a named type"))?;
             let inferred_target = explicit_target.unwrap_or_else(|| to_snake_plural(&element_type));
             let inferred_alias = explicit_alias.unwrap_or_else(|| field_name.to_string());
-            (RelationKind::HasMany, inferred_target, inferred_alias, None)
+            // A foreign key here names the target's belongs-to field, telling apart several
+            // has-many relations to the same collection
+            (RelationKind::HasMany, inferred_target, inferred_alias, explicit_foreign_key)
         } else if matches!(ty.base, FieldBase::String) && field_name.ends_with("_id") {
             // {entity}_id: String → belongs_to
             let entity_prefix = &field_name[..field_name.len() - 3]; // Remove "_id"
@@ -334,23 +619,41 @@ impl ParsedField {
         *relation_spec = Some(FieldRelationSpec {
             kind,
             target,
+            target_service,
             alias,
             cascade,
             foreign_key,
             junction,
+            counter,
+            model,
         });
 
         Ok(())
     }
 
-    /// Parse filterable type: filterable or filterable(tag) or filterable(text) etc.
-    fn parse_filter_type(meta: &syn::meta::ParseNestedMeta, ty: &TypeInfo) -> Result<FilterFieldType> {
+    /// Parse filterable type: filterable or filterable(tag) or filterable(text) etc., plus the
+    /// `casefold` flag of `filterable(tag, casefold)`.
+    fn parse_filter_type(meta: &syn::meta::ParseNestedMeta, ty: &TypeInfo) -> Result<(FilterFieldType, bool)> {
         // Check if there are parentheses with a type
         if meta.input.peek(syn::token::Paren) {
             let content;
             parenthesized!(content in meta.input);
             let type_ident: Ident = content.parse()?;
-            match type_ident.to_string().as_str() {
+            let mut casefold = false;
+            if content.parse::<Option<Token![,]>>()?.is_some() {
+                let flag: Ident = content.parse()?;
+                if flag != "casefold" {
+                    return Err(Error::new(flag.span(), format!("unknown filterable option `{flag}`, expected `casefold`")));
+                }
+                if type_ident != "tag" {
+                    return Err(Error::new(flag.span(), "casefold is only supported with filterable(tag)"));
+                }
+                if !matches!(ty.base, FieldBase::String) {
+                    return Err(Error::new(flag.span(), "filterable(tag, casefold) can only be used on String fields"));
+                }
+                casefold = true;
+            }
+            let filter_type = match type_ident.to_string().as_str() {
                 "tag" => Ok(FilterFieldType::Tag),
                 "text" => {
                     // filterable(text) only makes sense on String types
@@ -369,10 +672,12 @@ impl ParsedField {
                     Ok(FilterFieldType::Geo)
                 }
                 other => Err(Error::new(type_ident.span(), format!("unknown filter type '{}', expected tag, text, numeric, boolean, or geo", other))),
-            }
+            }?;
+            Ok((filter_type, casefold))
         } else {
             // No explicit type - infer from Rust type
             Self::infer_filter_type(ty)
+                .map(|filter_type| (filter_type, false))
                 .ok_or_else(|| meta.error("filterable on String requires explicit type: filterable(tag) or filterable(text)"))
         }
     }
@@ -437,12 +742,15 @@ impl ParsedField {
         }
     }
 
-    fn to_descriptor_tokens(&self) -> TokenStream2 {
+    fn to_descriptor_tokens(&self, entity: &Ident, field_idents: &[Ident]) -> TokenStream2 {
         let name = &self.name;
         let optional = self.ty.optional;
         let is_id = self.is_id;
         let auto_updated = self.auto_updated;
         let auto_created = self.auto_created;
+        let immutable = self.immutable;
+        let redacted = self.redacted;
+        let enum_order = &self.enum_order;
         let datetime_mirror = match &self.datetime_mirror {
             Some(value) => {
                 let lit = LitStr::new(value, Span::call_site());
@@ -450,7 +758,10 @@ impl ParsedField {
             }
             None => quote! { None },
         };
-        let validations = self.validations.iter().map(|validation| validation.to_descriptor_tokens());
+        let validations = self
+            .validations
+            .iter()
+            .map(|validation| validation.to_descriptor_tokens(self, entity, field_idents));
         let field_type = self.field_type_tokens();
         let element_type = self.element_type_tokens();
 
@@ -463,6 +774,21 @@ impl ParsedField {
         // cannot index as TAG fields. Setting this flag tells the repository to
         // extract just the variant name (discriminant) for the indexed value.
         let normalize_enum_tag = self.needs_enum_tag_normalization();
+        let casefold_tag = self.is_casefold();
+        let slug = match &self.slug {
+            Some(spec) => {
+                let from = LitStr::new(&spec.from_name, Span::call_site());
+                let suffix = spec.suffix;
+                quote! { Some(::snugom::types::SlugDescriptor { from: #from.to_string(), suffix: #suffix }) }
+            }
+            None => quote! { None },
+        };
+        let description = option_string_tokens(&self.description);
+        let compression = match self.compression {
+            Some(CompressionCodec::Gzip) => quote! { Some(::snugom::types::Compression::Gzip) },
+            Some(CompressionCodec::Zstd) => quote! { Some(::snugom::types::Compression::Zstd) },
+            None => quote! { None },
+        };
 
         quote! {
             ::snugom::types::FieldDescriptor {
@@ -473,10 +799,17 @@ impl ParsedField {
                 datetime_mirror: #datetime_mirror,
                 auto_updated: #auto_updated,
                 auto_created: #auto_created,
+                immutable: #immutable,
+                redacted: #redacted,
+                enum_order: vec![#(#enum_order.to_string()),*],
                 field_type: #field_type,
                 element_type: #element_type,
                 is_relation_vec: #is_relation_vec,
                 normalize_enum_tag: #normalize_enum_tag,
+                casefold_tag: #casefold_tag,
+                slug: #slug,
+                description: #description,
+                compression: #compression,
             }
         }
     }
@@ -642,7 +975,7 @@ impl ParsedField {
         if self.ty.optional {
             return None;
         }
-        if self.auto_updated || self.auto_created {
+        if self.auto_updated || self.auto_created || self.is_counter {
             return None;
         }
         // Relation Vec fields (for has_many, many_to_many) are for hydration and default to empty
@@ -672,6 +1005,10 @@ impl ParsedField {
             quote! {
                 let #ident = self.#ident.take().unwrap_or_else(|| ::chrono::Utc::now());
             }
+        } else if self.is_counter {
+            quote! {
+                let #ident = self.#ident.take().unwrap_or_default();
+            }
         } else if allow_missing && matches!(self.ty.base, FieldBase::String) {
             quote! {
                 let #ident = self
@@ -841,11 +1178,92 @@ impl ParsedField {
         }
     }
 
+    /// `increment_{field}(by)` on the patch builder, for required numeric fields.
+    fn patch_increment_method(&self) -> Option<TokenStream2> {
+        let numeric = matches!(self.ty.base, FieldBase::Numeric) && !self.ty.is_datetime;
+        if !numeric || self.ty.optional || self.is_id || self.compression.is_some() {
+            return None;
+        }
+        let method = format_ident!("increment_{}", self.ident);
+        let path_lit = LitStr::new(&format!("$.{}", self.name), Span::call_site());
+        Some(quote! {
+            pub fn #method(mut self, by: f64) -> Self {
+                self.operations.push(::snugom::repository::PatchOperation {
+                    path: #path_lit.to_string(),
+                    kind: ::snugom::repository::PatchOpKind::Increment(by),
+                    mirror: ::std::option::Option::None,
+                });
+                self
+            }
+        })
+    }
+
+    /// `push_{field}(value)` / `remove_{field}(value)` on the patch builder, for plain array fields.
+    fn patch_array_methods(&self) -> Option<TokenStream2> {
+        let element = self.ty.element.as_ref()?;
+        if self.ty.is_bytes() || self.relation_spec.is_some() || self.compression.is_some() {
+            return None;
+        }
+        let field_lit = LitStr::new(&self.name, Span::call_site());
+        let path_lit = LitStr::new(&format!("$.{}", self.name), Span::call_site());
+        let methods = [
+            (format_ident!("push_{}", self.ident), quote! { ArrayAppend }),
+            (format_ident!("remove_{}", self.ident), quote! { ArrayRemove }),
+        ];
+        let tokens = methods.into_iter().map(|(method, kind)| {
+            if matches!(element.base, FieldBase::String) && !element.optional {
+                quote! {
+                    pub fn #method<S>(mut self, value: S) -> Self
+                    where
+                        S: ::std::convert::Into<String>,
+                    {
+                        self.operations.push(::snugom::repository::PatchOperation {
+                            path: #path_lit.to_string(),
+                            kind: ::snugom::repository::PatchOpKind::#kind(vec![::serde_json::Value::String(value.into())]),
+                            mirror: ::std::option::Option::None,
+                        });
+                        self
+                    }
+                }
+            } else {
+                let element_ty = &element.ty;
+                quote! {
+                    pub fn #method(mut self, value: #element_ty) -> Self {
+                        match ::serde_json::to_value(&value) {
+                            Ok(json_value) => {
+                                self.operations.push(::snugom::repository::PatchOperation {
+                                    path: #path_lit.to_string(),
+                                    kind: ::snugom::repository::PatchOpKind::#kind(vec![json_value]),
+                                    mirror: ::std::option::Option::None,
+                                });
+                            }
+                            Err(err) => {
+                                self.validation_issues.push(::snugom::errors::ValidationIssue::new(
+                                    #field_lit,
+                                    "serialization.failed",
+                                    err.to_string(),
+                                ));
+                            }
+                        }
+                        self
+                    }
+                }
+            }
+        });
+        Some(quote! { #(#tokens)* })
+    }
+
     fn field_type_tokens(&self) -> TokenStream2 {
+        if self.ty.is_bytes() {
+            return quote! { ::snugom::types::FieldType::Bytes };
+        }
         map_field_type(self.ty.base, self.ty.is_datetime)
     }
 
     fn element_type_tokens(&self) -> TokenStream2 {
+        if self.ty.is_bytes() {
+            return quote! { None };
+        }
         if let Some(element) = &self.ty.element {
             let tokens = map_field_type(element.base, element.is_datetime);
             quote! { Some(#tokens) }
@@ -856,9 +1274,14 @@ impl ParsedField {
 
     // ========== Search-related methods ==========
 
-    /// Returns true if this field has an index specification
+    /// Returns true if this field, or a nested field under it, has an index specification
     pub(crate) fn has_index(&self) -> bool {
-        self.index_spec.is_some()
+        self.index_spec.is_some() || !self.nested.is_empty()
+    }
+
+    /// The field's Rust name, which container attributes like `default_sort` refer to
+    pub(crate) fn rust_name(&self) -> String {
+        syn::ext::IdentExt::unraw(&self.ident).to_string()
     }
 
     /// Returns true if this field is included in full-text search
@@ -866,11 +1289,19 @@ impl ParsedField {
         self.is_searchable
     }
 
-    /// Get the index field name (uses datetime mirror if applicable)
+    /// Get the index field name (uses the datetime mirror or casefold shadow if applicable)
     pub(crate) fn index_field_name(&self) -> String {
+        if self.is_casefold() {
+            return format!("__{}_lc", self.name);
+        }
         self.datetime_mirror.clone().unwrap_or_else(|| self.name.clone())
     }
 
+    /// Whether the field is `filterable(tag, casefold)`
+    fn is_casefold(&self) -> bool {
+        self.filter_spec.as_ref().is_some_and(|fs| fs.casefold)
+    }
+
     /// Get the filter alias or the field name
     pub(crate) fn filter_name(&self) -> String {
         self.filter_spec
@@ -879,131 +1310,60 @@ impl ParsedField {
             .unwrap_or_else(|| self.name.clone())
     }
 
-    /// Generate the IndexField tokens for this field
-    pub(crate) fn to_index_field_tokens(&self) -> Option<TokenStream2> {
-        let idx = self.index_spec.as_ref()?;
+    /// Generate the IndexField tokens for this field, followed by the numeric order shadow of an
+    /// `enum_order` field, which takes over sorting from the tag. Optional filterable fields are
+    /// indexed with INDEXMISSING so queries can find documents without them.
+    pub(crate) fn to_index_field_tokens(&self) -> Vec<TokenStream2> {
+        let Some(idx) = self.index_spec.as_ref() else {
+            return Vec::new();
+        };
         // For fields needing enum tag normalization, index the shadow field instead
-        let (path, field_name) = if self.needs_enum_tag_normalization() {
-            let shadow_name = format!("__{}_tag", self.name);
-            (format!("$.{}", shadow_name), shadow_name)
+        let field_name = if self.needs_enum_tag_normalization() {
+            format!("__{}_tag", self.name)
         } else {
-            (format!("$.{}", self.index_field_name()), self.index_field_name())
+            self.index_field_name()
         };
-        let field_type = match idx.field_type {
-            IndexFieldType::Tag => quote! { ::snugom::search::IndexFieldType::Tag },
-            IndexFieldType::Text => quote! { ::snugom::search::IndexFieldType::Text },
-            IndexFieldType::Numeric => quote! { ::snugom::search::IndexFieldType::Numeric },
-            IndexFieldType::Geo => quote! { ::snugom::search::IndexFieldType::Geo },
+        let index_missing = self.ty.optional && self.filter_spec.is_some();
+        if self.enum_order.is_empty() {
+            return vec![index_field_tokens(&format!("$.{}", field_name), &field_name, idx, index_missing)];
+        }
+        let tag = IndexSpec { sortable: false, ..idx.clone() };
+        let order = IndexSpec {
+            field_type: IndexFieldType::Numeric,
+            sortable: true,
         };
-        let sortable = idx.sortable;
-
-        Some(quote! {
-            ::snugom::search::IndexField {
-                path: #path,
-                field_name: #field_name,
-                field_type: #field_type,
-                sortable: #sortable,
-            }
-        })
+        let order_name = self.order_shadow_name();
+        vec![
+            index_field_tokens(&format!("$.{}", field_name), &field_name, &tag, index_missing),
+            index_field_tokens(&format!("$.{}", order_name), &order_name, &order, false),
+        ]
     }
 
     /// Generate the SortField tokens for this field (if sortable)
     pub(crate) fn to_sort_field_tokens(&self) -> Option<TokenStream2> {
         let idx = self.index_spec.as_ref()?;
-        if !idx.sortable {
-            return None;
+        if !self.enum_order.is_empty() {
+            // Ascending by default: the order the values are listed in
+            return Some(sort_field_tokens(&self.name, &self.order_shadow_name(), idx));
         }
-        let name = &self.name;
-        let path = self.index_field_name();
-        let default_order = match idx.field_type {
-            IndexFieldType::Numeric => quote! { ::snugom::search::SortOrder::Desc },
-            _ => quote! { ::snugom::search::SortOrder::Asc },
-        };
+        idx.sortable.then(|| sort_field_tokens(&self.name, &self.index_field_name(), idx))
+    }
 
-        Some(quote! {
-            ::snugom::search::SortField {
-                name: #name,
-                path: #path,
-                default_order: #default_order,
-            }
-        })
+    /// Numeric shadow holding an `enum_order` field's position in the list
+    fn order_shadow_name(&self) -> String {
+        format!("__{}_ord", self.name)
     }
 
     /// Generate the filter match arm for this field
     pub(crate) fn to_filter_match_arm(&self) -> Option<TokenStream2> {
         let fs = self.filter_spec.as_ref()?;
-        let filter_name = self.filter_name();
         // For fields needing enum tag normalization, query the shadow field instead
         let query_field = if self.needs_enum_tag_normalization() {
             format!("__{}_tag", self.name)
         } else {
             self.index_field_name()
         };
-
-        let arm = match fs.field_type {
-            FilterFieldType::Tag => quote! {
-                #filter_name => {
-                    if descriptor.operator != ::snugom::search::FilterOperator::Eq {
-                        return Err(::snugom::errors::RepoError::InvalidRequest {
-                            message: format!("{} filter only supports eq operator", #filter_name),
-                        });
-                    }
-                    if descriptor.values.is_empty() {
-                        return Err(::snugom::errors::RepoError::InvalidRequest {
-                            message: format!("{} filter requires at least one value", #filter_name),
-                        });
-                    }
-                    Ok(::snugom::search::FilterCondition::TagEquals {
-                        field: #query_field.to_string(),
-                        values: descriptor.values,
-                    })
-                }
-            },
-            FilterFieldType::Numeric => quote! {
-                #filter_name => {
-                    ::snugom::filters::normalizers::build_numeric_filter(descriptor, #query_field)
-                }
-            },
-            FilterFieldType::Text => quote! {
-                #filter_name => {
-                    ::snugom::filters::normalizers::build_text_filter(descriptor, #query_field)
-                }
-            },
-            FilterFieldType::Boolean => quote! {
-                #filter_name => {
-                    if descriptor.operator != ::snugom::search::FilterOperator::Eq {
-                        return Err(::snugom::errors::RepoError::InvalidRequest {
-                            message: format!("{} filter only supports eq operator", #filter_name),
-                        });
-                    }
-                    let value = descriptor.values.get(0).ok_or_else(|| {
-                        ::snugom::errors::RepoError::InvalidRequest {
-                            message: format!("{} filter requires a value", #filter_name),
-                        }
-                    })?;
-                    let bool_value = match value.trim() {
-                        "true" => true,
-                        "false" => false,
-                        _ => return Err(::snugom::errors::RepoError::InvalidRequest {
-                            message: format!("Invalid boolean value for {}: {}", #filter_name, value),
-                        }),
-                    };
-                    Ok(::snugom::search::FilterCondition::BooleanEquals {
-                        field: #query_field.to_string(),
-                        value: bool_value,
-                    })
-                }
-            },
-            FilterFieldType::Geo => quote! {
-                #filter_name => {
-                    Err(::snugom::errors::RepoError::InvalidRequest {
-                        message: format!("Geo filter for {} not yet implemented", #filter_name),
-                    })
-                }
-            },
-        };
-
-        Some(arm)
+        Some(filter_match_arm(&self.filter_name(), &query_field, fs.field_type, fs.casefold))
     }
 
     /// Returns the unique constraint info if this field has a #[snugom(unique)] validation
@@ -1015,6 +1375,125 @@ impl ParsedField {
         }
         None
     }
+
+    /// `AsyncValidatorDescriptor`s for this field's `validate(async_custom = "...")` types
+    pub(crate) fn async_validator_inits(&self) -> Vec<TokenStream2> {
+        let (value_ty, skip_null) = match &self.ty.option_inner {
+            Some(inner) if self.ty.optional => (inner, true),
+            _ => (&self.ty.ty, false),
+        };
+        self.async_validators
+            .iter()
+            .map(|(path, lit)| async_validator_tokens(Some(&self.name), path, lit, value_ty, skip_null))
+            .collect()
+    }
+}
+
+/// `IndexField` tokens for an attribute `field_name` read from JSON `path`
+fn index_field_tokens(path: &str, field_name: &str, idx: &IndexSpec, index_missing: bool) -> TokenStream2 {
+    let field_type = match idx.field_type {
+        IndexFieldType::Tag => quote! { ::snugom::search::IndexFieldType::Tag },
+        IndexFieldType::Text => quote! { ::snugom::search::IndexFieldType::Text },
+        IndexFieldType::Numeric => quote! { ::snugom::search::IndexFieldType::Numeric },
+        IndexFieldType::Geo => quote! { ::snugom::search::IndexFieldType::Geo },
+    };
+    let sortable = idx.sortable;
+    quote! {
+        ::snugom::search::IndexField {
+            path: #path,
+            field_name: #field_name,
+            field_type: #field_type,
+            sortable: #sortable,
+            index_missing: #index_missing,
+        }
+    }
+}
+
+/// `SortField` tokens for the sort `name`, ordering by the index attribute `path`
+fn sort_field_tokens(name: &str, path: &str, idx: &IndexSpec) -> TokenStream2 {
+    let default_order = match idx.field_type {
+        IndexFieldType::Numeric => quote! { ::snugom::search::SortOrder::Desc },
+        _ => quote! { ::snugom::search::SortOrder::Asc },
+    };
+    quote! {
+        ::snugom::search::SortField {
+            name: #name,
+            path: #path,
+            default_order: #default_order,
+        }
+    }
+}
+
+/// The `map_filter` arm accepting `filter_name` and querying the index attribute `query_field`.
+/// With `casefold`, tag values are lowercased to match the shadow.
+fn filter_match_arm(filter_name: &str, query_field: &str, field_type: FilterFieldType, casefold: bool) -> TokenStream2 {
+    let tag_values = if casefold {
+        quote! { descriptor.values.iter().map(|value| value.to_lowercase()).collect() }
+    } else {
+        quote! { descriptor.values }
+    };
+    match field_type {
+        FilterFieldType::Tag => quote! {
+            #filter_name => {
+                if descriptor.operator != ::snugom::search::FilterOperator::Eq {
+                    return Err(::snugom::errors::RepoError::InvalidRequest {
+                        message: format!("{} filter only supports eq operator", #filter_name),
+                    });
+                }
+                if descriptor.values.is_empty() {
+                    return Err(::snugom::errors::RepoError::InvalidRequest {
+                        message: format!("{} filter requires at least one value", #filter_name),
+                    });
+                }
+                Ok(::snugom::search::FilterCondition::TagEquals {
+                    field: #query_field.to_string(),
+                    values: #tag_values,
+                })
+            }
+        },
+        FilterFieldType::Numeric => quote! {
+            #filter_name => {
+                ::snugom::filters::normalizers::build_numeric_filter(descriptor, #query_field)
+            }
+        },
+        FilterFieldType::Text => quote! {
+            #filter_name => {
+                ::snugom::filters::normalizers::build_text_filter(descriptor, #query_field)
+            }
+        },
+        FilterFieldType::Boolean => quote! {
+            #filter_name => {
+                if descriptor.operator != ::snugom::search::FilterOperator::Eq {
+                    return Err(::snugom::errors::RepoError::InvalidRequest {
+                        message: format!("{} filter only supports eq operator", #filter_name),
+                    });
+                }
+                let value = descriptor.values.get(0).ok_or_else(|| {
+                    ::snugom::errors::RepoError::InvalidRequest {
+                        message: format!("{} filter requires a value", #filter_name),
+                    }
+                })?;
+                let bool_value = match value.trim() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(::snugom::errors::RepoError::InvalidRequest {
+                        message: format!("Invalid boolean value for {}: {}", #filter_name, value),
+                    }),
+                };
+                Ok(::snugom::search::FilterCondition::BooleanEquals {
+                    field: #query_field.to_string(),
+                    value: bool_value,
+                })
+            }
+        },
+        FilterFieldType::Geo => quote! {
+            #filter_name => {
+                Err(::snugom::errors::RepoError::InvalidRequest {
+                    message: format!("Geo filter for {} not yet implemented", #filter_name),
+                })
+            }
+        },
+    }
 }
 
 fn map_field_type(base: FieldBase, is_datetime: bool) -> TokenStream2 {
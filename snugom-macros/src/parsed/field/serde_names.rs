@@ -0,0 +1,152 @@
+/// Case convention from `#[serde(rename_all = "...")]`, applied to field names the way serde does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(value: &LitStr) -> Result<Self> {
+        Ok(match value.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            other => {
+                return Err(Error::new(value.span(), format!("unknown serde rename_all rule `{other}`")));
+            }
+        })
+    }
+
+    /// The serialized name of a snake_case Rust field.
+    pub(crate) fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => {
+                let pascal = Self::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// The serialize-side value of a serde `rename`/`rename_all` item: `key = "..."` or
+/// `key(serialize = "...", deserialize = "...")`. Returns `None` when only `deserialize` is given.
+fn serde_serialize_name(meta: &ParseNestedMeta) -> Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut serialize = None;
+    meta.parse_nested_meta(|nested| {
+        let value: LitStr = nested.value()?.parse()?;
+        if nested.path.is_ident("serialize") {
+            serialize = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(serialize)
+}
+
+/// Skip a serde item this derive doesn't read, whatever its shape.
+fn skip_serde_item(meta: &ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in meta.input);
+        content.parse::<TokenStream2>()?;
+    }
+    Ok(())
+}
+
+/// Read `name` (`rename` or `rename_all`) from the `#[serde(...)]` attributes.
+fn serde_attr(attrs: &[Attribute], name: &str) -> Result<Option<LitStr>> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                if let Some(value) = serde_serialize_name(&meta)? {
+                    found = Some(value);
+                }
+                Ok(())
+            } else {
+                skip_serde_item(&meta)
+            }
+        })?;
+    }
+    Ok(found)
+}
+
+/// Whether the `#[serde(...)]` attributes set the bare flag `name`, e.g. `flatten`.
+fn serde_flag(attrs: &[Attribute], name: &str) -> Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) && !meta.input.peek(Token![=]) && !meta.input.peek(syn::token::Paren) {
+                found = true;
+                Ok(())
+            } else {
+                skip_serde_item(&meta)
+            }
+        })?;
+    }
+    Ok(found)
+}
+
+/// Whether the `#[serde(...)]` attributes set `name` in any form, e.g. `default` or `default = "path"`.
+fn serde_has(attrs: &[Attribute], name: &str) -> Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            found |= meta.path.is_ident(name);
+            skip_serde_item(&meta)
+        })?;
+    }
+    Ok(found)
+}
+
+/// The entity's `#[serde(rename_all = "...")]` rule, if any.
+pub(crate) fn serde_rename_all(attrs: &[Attribute]) -> Result<Option<RenameRule>> {
+    serde_attr(attrs, "rename_all")?.as_ref().map(RenameRule::parse).transpose()
+}
+
+/// The name serde writes a field under: its `#[serde(rename)]`, else its Rust name with the
+/// entity's `rename_all` rule applied.
+fn serialized_field_name(field: &Field, rust_name: &str, rename_all: Option<RenameRule>) -> Result<String> {
+    if let Some(rename) = serde_attr(&field.attrs, "rename")? {
+        return Ok(rename.value());
+    }
+    Ok(rename_all.map_or_else(|| rust_name.to_string(), |rule| rule.apply_to_field(rust_name)))
+}
@@ -17,7 +17,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #field_name,
                                 "validation.length",
                                 format!("length must be at least {}", #value),
-                            ));
+                            ).with_param("min", #value));
                         }
                     }
                 });
@@ -28,7 +28,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #field_name,
                                 "validation.length",
                                 format!("length must be at most {}", #value),
-                            ));
+                            ).with_param("max", #value));
                         }
                     }
                 });
@@ -65,7 +65,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #field_name,
                                 "validation.range",
                                 format!("value must be at least {}", #repr),
-                            ));
+                            ).with_bound("min", #repr));
                         }
                     }
                 } else {
@@ -79,7 +79,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #field_name,
                                 "validation.range",
                                 format!("value must be at most {}", #repr),
-                            ));
+                            ).with_bound("max", #repr));
                         }
                     }
                 } else {
@@ -108,12 +108,12 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                 if optional {
                     quote! {
                         if let Some(value) = self.#field_ident.as_ref() {
-                            if !::regex::Regex::new(#lit).unwrap().is_match(value.as_str()) {
+                            if !::snugom::validators::matches_regex(#lit, value.as_str()) {
                                 issues.push(::snugom::errors::ValidationIssue::new(
                                     #field_name,
                                     "validation.regex",
                                     format!("value does not match pattern {}", #lit),
-                                ));
+                                ).with_param("pattern", #lit));
                             }
                         }
                     }
@@ -121,12 +121,12 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                     quote! {
                         {
                             let value = &self.#field_ident;
-                            if !::regex::Regex::new(#lit).unwrap().is_match(value.as_str()) {
+                            if !::snugom::validators::matches_regex(#lit, value.as_str()) {
                                 issues.push(::snugom::errors::ValidationIssue::new(
                                     #field_name,
                                     "validation.regex",
                                     format!("value does not match pattern {}", #lit),
-                                ));
+                                ).with_param("pattern", #lit));
                             }
                         }
                     }
@@ -168,7 +168,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                     #field_name,
                                     "validation.enum",
                                     format!("value must be one of {:?}", &allowed_values),
-                                ));
+                                ).with_param("allowed", allowed_values.to_vec()));
                             }
                         }
                     }
@@ -186,7 +186,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                     #field_name,
                                     "validation.enum",
                                     format!("value must be one of {:?}", &allowed_values),
-                                ));
+                                ).with_param("allowed", allowed_values.to_vec()));
                             }
                         }
                     }
@@ -273,6 +273,28 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                     }
                 }
             }
+            ValidationData::Phone | ValidationData::IpAddr | ValidationData::Hostname | ValidationData::Slug => {
+                let (is_valid, code, message) = self.data.format_check();
+                let check = quote! {
+                    if !#is_valid(value.as_str()) {
+                        issues.push(::snugom::errors::ValidationIssue::new(#field_name, #code, #message));
+                    }
+                };
+                if optional {
+                    quote! {
+                        if let Some(value) = self.#field_ident.as_ref() {
+                            #check
+                        }
+                    }
+                } else {
+                    quote! {
+                        {
+                            let value = &self.#field_ident;
+                            #check
+                        }
+                    }
+                }
+            }
             ValidationData::RequiredIf { expr, .. } => {
                 if !field.ty.optional {
                     return quote! {};
@@ -365,7 +387,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #path_expr.clone(),
                                 "validation.length",
                                 format!("length must be at least {}", #value),
-                            ));
+                            ).with_param("min", #value));
                         }
                     }
                 });
@@ -376,7 +398,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #path_expr.clone(),
                                 "validation.length",
                                 format!("length must be at most {}", #value),
-                            ));
+                            ).with_param("max", #value));
                         }
                     }
                 });
@@ -400,7 +422,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #path_expr.clone(),
                                 "validation.range",
                                 format!("value must be at least {}", #repr),
-                            ));
+                            ).with_bound("min", #repr));
                         }
                     }
                 } else {
@@ -414,7 +436,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                                 #path_expr.clone(),
                                 "validation.range",
                                 format!("value must be at most {}", #repr),
-                            ));
+                            ).with_bound("max", #repr));
                         }
                     }
                 } else {
@@ -428,12 +450,12 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
             ValidationData::Regex { pattern } => {
                 let lit = LitStr::new(pattern, Span::call_site());
                 quote! {
-                    if !::regex::Regex::new(#lit).unwrap().is_match(#item_ident.as_str()) {
+                    if !::snugom::validators::matches_regex(#lit, #item_ident.as_str()) {
                         issues.push(::snugom::errors::ValidationIssue::new(
                             #path_expr.clone(),
                             "validation.regex",
                             format!("value does not match pattern {}", #lit),
-                        ));
+                        ).with_param("pattern", #lit));
                     }
                 }
             }
@@ -471,7 +493,7 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                             #path_expr.clone(),
                             "validation.enum",
                             format!("value must be one of {:?}", &allowed_values),
-                        ));
+                        ).with_param("allowed", allowed_values.to_vec()));
                     }
                 }
             }
@@ -508,6 +530,14 @@ fn emit_field_check(&self, field: &ParsedField, field_idents: &[Ident]) -> Token
                     }
                 }
             }
+            ValidationData::Phone | ValidationData::IpAddr | ValidationData::Hostname | ValidationData::Slug => {
+                let (is_valid, code, message) = self.data.format_check();
+                quote! {
+                    if !#is_valid(#item_ident.as_str()) {
+                        issues.push(::snugom::errors::ValidationIssue::new(#path_expr.clone(), #code, #message));
+                    }
+                }
+            }
             ValidationData::Custom { path, .. } => {
                 quote! {
                     if let Err(err) = #path(#item_ident) {
@@ -558,7 +588,13 @@ fn parse_validation_rule(
         .ok_or_else(|| Error::new(rule.path.span(), format!("unsupported validator on `{}`", field_name)))?;
     let ident_str = ident.to_string();
     match ident_str.as_str() {
-        "length" => {
+        "length" | "size" => {
+            if ident_str == "size" && !ty.is_bytes() {
+                return Err(Error::new(
+                    rule.path.span(),
+                    "size(...) applies to Vec<u8> byte fields; use length(...) for strings and lists",
+                ));
+            }
             ensure_length_supported(ty.base, rule.path.span())?;
             let mut min = None;
             let mut max = None;
@@ -673,6 +709,19 @@ fn parse_validation_rule(
                 data: ValidationData::Uuid,
             });
         }
+        "phone" | "ip_addr" | "hostname" | "slug" => {
+            ensure_string_supported(ty.base, rule.path.span(), ident_str.as_str())?;
+            let data = match ident_str.as_str() {
+                "phone" => ValidationData::Phone,
+                "ip_addr" => ValidationData::IpAddr,
+                "hostname" => ValidationData::Hostname,
+                _ => ValidationData::Slug,
+            };
+            validations.push(FieldValidation {
+                scope: ValidationScope::Field,
+                data,
+            });
+        }
         "required_if" => {
             if !ty.optional {
                 return Err(Error::new(
@@ -739,13 +788,11 @@ fn parse_validation_rule(
             });
         }
         "custom" => {
-            let lit: LitStr = rule.value()?.parse()?;
-            let path = syn::parse_str::<syn::Path>(&lit.value())
-                .map_err(|err| Error::new(lit.span(), format!("invalid custom validator path: {}", err)))?;
+            let (path, lit) = parse_validator_path(&rule)?;
             validations.push(FieldValidation {
                 scope: ValidationScope::Field,
                 data: ValidationData::Custom {
-                    path: path.to_token_stream(),
+                    path,
                     path_repr: lit.value(),
                 },
             });
@@ -859,6 +906,14 @@ fn unwrap_option(ty: &Type) -> Option<&Type> {
     }
 }
 
+impl TypeInfo {
+    /// `Vec<u8>` (or `Option<Vec<u8>>`), stored as base64 and described as `FieldType::Bytes`
+    fn is_bytes(&self) -> bool {
+        matches!(self.base, FieldBase::Vec)
+            && self.element.as_ref().is_some_and(|element| element.type_name.as_deref() == Some("u8"))
+    }
+}
+
 fn unwrap_vec(ty: &Type) -> Option<&Type> {
     match ty {
         Type::Path(path) if last_ident_str(path).as_deref() == Some("Vec") => {
@@ -909,6 +964,20 @@ fn to_snake_case(name: &str) -> String {
     result
 }
 
+/// Converts a snake_case identifier to PascalCase
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let mut chars = segment.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
 /// Simple pluralization rules
 fn pluralize(word: &str) -> String {
     if word.ends_with('s') || word.ends_with('x') || word.ends_with("ch") || word.ends_with("sh") {
@@ -981,6 +1050,35 @@ fn ensure_range_supported(base: FieldBase, span: Span) -> Result<()> {
     }
 }
 
+impl ValidationData {
+    /// Check function, issue code, and message of the `phone`, `ip_addr`, `hostname`, and `slug`
+    /// format rules.
+    fn format_check(&self) -> (TokenStream2, &'static str, &'static str) {
+        match self {
+            ValidationData::Phone => (
+                quote! { ::snugom::validators::is_valid_phone },
+                "validation.phone",
+                "value must be a valid phone number",
+            ),
+            ValidationData::IpAddr => (
+                quote! { ::snugom::validators::is_valid_ip_addr },
+                "validation.ip_addr",
+                "value must be a valid IP address",
+            ),
+            ValidationData::Hostname => (
+                quote! { ::snugom::validators::is_valid_hostname },
+                "validation.hostname",
+                "value must be a valid hostname",
+            ),
+            _ => (
+                quote! { ::snugom::validators::is_valid_slug },
+                "validation.slug",
+                "value must be a lowercase slug",
+            ),
+        }
+    }
+}
+
 fn ensure_string_supported(base: FieldBase, span: Span, validator: &str) -> Result<()> {
     match base {
         FieldBase::String => Ok(()),
@@ -136,6 +136,22 @@ fn parse_inline_validator(spec: String, element: ElementType, span: Span) -> Res
             ensure_string_supported(element.base, span, "uuid")?;
             Ok(ValidationData::Uuid)
         }
+        "phone" => {
+            ensure_string_supported(element.base, span, "phone")?;
+            Ok(ValidationData::Phone)
+        }
+        "ip_addr" => {
+            ensure_string_supported(element.base, span, "ip_addr")?;
+            Ok(ValidationData::IpAddr)
+        }
+        "hostname" => {
+            ensure_string_supported(element.base, span, "hostname")?;
+            Ok(ValidationData::Hostname)
+        }
+        "slug" => {
+            ensure_string_supported(element.base, span, "slug")?;
+            Ok(ValidationData::Slug)
+        }
         "custom" => {
             let path = args
                 .first()
@@ -257,3 +273,46 @@ fn destructure_fields(field_idents: &[Ident]) -> TokenStream2 {
         }
     }
 }
+
+/// Parse `custom = "path"` / `async_custom = "path"` into the path tokens and the literal naming it.
+fn parse_validator_path(rule: &ParseNestedMeta) -> Result<(TokenStream2, LitStr)> {
+    let lit: LitStr = rule.value()?.parse()?;
+    let path = syn::parse_str::<syn::Path>(&lit.value())
+        .map_err(|err| Error::new(lit.span(), format!("invalid custom validator path: {}", err)))?;
+    Ok((path.to_token_stream(), lit))
+}
+
+/// An `AsyncValidatorDescriptor` whose check deserializes the JSON value to `value_ty` and hands it
+/// to `<path as AsyncValidator<value_ty>>::validate`. `field` is `None` for entity-level validators.
+fn async_validator_tokens(
+    field: Option<&str>,
+    path: &TokenStream2,
+    lit: &LitStr,
+    value_ty: &Type,
+    skip_null: bool,
+) -> TokenStream2 {
+    let field_tokens = optional_string_tokens(&field.map(str::to_string));
+    let issue_field = field.unwrap_or("__entity");
+    let skip_null = skip_null.then(|| quote! { if value.is_null() { return Ok(()); } });
+    quote! {
+        ::snugom::types::AsyncValidatorDescriptor {
+            field: #field_tokens,
+            path: #lit.to_string(),
+            check: {
+                fn check<'a>(
+                    conn: &'a mut ::snugom::ConnectionManager,
+                    value: &'a ::serde_json::Value,
+                ) -> ::snugom::types::AsyncValidatorFuture<'a> {
+                    ::std::boxed::Box::pin(async move {
+                        #skip_null
+                        let typed: #value_ty = ::serde_json::from_value(value.clone()).map_err(|err| {
+                            ::snugom::errors::ValidationError::single(#issue_field, "validation.invalid_type", err.to_string())
+                        })?;
+                        <#path as ::snugom::validators::AsyncValidator<#value_ty>>::validate(conn, &typed).await
+                    })
+                }
+                check
+            },
+        }
+    }
+}
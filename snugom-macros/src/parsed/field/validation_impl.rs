@@ -1,5 +1,5 @@
 impl FieldValidation {
-    fn to_descriptor_tokens(&self) -> TokenStream2 {
+    fn to_descriptor_tokens(&self, field: &ParsedField, entity: &Ident, field_idents: &[Ident]) -> TokenStream2 {
         let scope = match self.scope {
             ValidationScope::Field => quote! { ::snugom::types::ValidationScope::Field },
             ValidationScope::EachElement => quote! { ::snugom::types::ValidationScope::EachElement },
@@ -37,24 +37,79 @@ impl FieldValidation {
             ValidationData::Email => quote! { ::snugom::types::ValidationRule::Email },
             ValidationData::Url => quote! { ::snugom::types::ValidationRule::Url },
             ValidationData::Uuid => quote! { ::snugom::types::ValidationRule::Uuid },
-            ValidationData::RequiredIf { expr_repr, .. } => {
+            ValidationData::Phone => quote! { ::snugom::types::ValidationRule::Phone },
+            ValidationData::IpAddr => quote! { ::snugom::types::ValidationRule::IpAddr },
+            ValidationData::Hostname => quote! { ::snugom::types::ValidationRule::Hostname },
+            ValidationData::Slug => quote! { ::snugom::types::ValidationRule::Slug },
+            ValidationData::RequiredIf { expr, expr_repr } => {
                 let lit = LitStr::new(expr_repr, Span::call_site());
-                quote! { ::snugom::types::ValidationRule::RequiredIf { expr: #lit.to_string() } }
+                let condition = condition_tokens(entity, field_idents, expr);
+                quote! { ::snugom::types::ValidationRule::RequiredIf { expr: #lit.to_string(), condition: #condition } }
             }
-            ValidationData::ForbiddenIf { expr_repr, .. } => {
+            ValidationData::ForbiddenIf { expr, expr_repr } => {
                 let lit = LitStr::new(expr_repr, Span::call_site());
-                quote! { ::snugom::types::ValidationRule::ForbiddenIf { expr: #lit.to_string() } }
+                let condition = condition_tokens(entity, field_idents, expr);
+                quote! { ::snugom::types::ValidationRule::ForbiddenIf { expr: #lit.to_string(), condition: #condition } }
             }
             ValidationData::Unique { case_insensitive } => quote! {
                 ::snugom::types::ValidationRule::Unique { case_insensitive: #case_insensitive }
             },
-            ValidationData::Custom { path_repr, .. } => {
+            ValidationData::Custom { path, path_repr } => {
                 let lit = LitStr::new(path_repr, Span::call_site());
-                quote! { ::snugom::types::ValidationRule::Custom { path: #lit.to_string() } }
+                let check = self.custom_check_tokens(field, path);
+                quote! { ::snugom::types::ValidationRule::Custom { path: #lit.to_string(), check: #check } }
             }
         };
         quote! {
             ::snugom::types::ValidationDescriptor { scope: #scope, rule: #rule }
         }
     }
+
+    /// A `CustomValidatorFn` that deserializes the JSON value to the argument the derived
+    /// `validate()` passes the custom function, then calls it.
+    fn custom_check_tokens(&self, field: &ParsedField, path: &TokenStream2) -> TokenStream2 {
+        let field_lit = LitStr::new(&field.name, Span::call_site());
+        let (arg_ty, skip_null) = match self.scope {
+            ValidationScope::Field => match &field.ty.option_inner {
+                Some(inner) if field.ty.optional => (inner, Some(quote! { if value.is_null() { return Ok(()); } })),
+                _ => (&field.ty.ty, None),
+            },
+            ValidationScope::EachElement => {
+                let element = field.ty.element.as_ref().expect("each validators are only accepted on Vec fields");
+                (&element.ty, None)
+            }
+        };
+        quote! {
+            |value: &::serde_json::Value| -> ::snugom::errors::ValidationResult<()> {
+                #skip_null
+                let typed: #arg_ty = ::serde_json::from_value(value.clone()).map_err(|err| {
+                    ::snugom::errors::ValidationError::single(#field_lit, "validation.invalid_type", err.to_string())
+                })?;
+                #path(&typed)
+            }
+        }
+    }
+}
+
+/// A `ConditionFn` that deserializes the document as the entity and evaluates a `required_if` /
+/// `forbidden_if` expression with the same bindings the derived `validate()` gives it.
+fn condition_tokens(entity: &Ident, field_idents: &[Ident], expr: &TokenStream2) -> TokenStream2 {
+    let bindings = destructure_fields(field_idents);
+    quote! {
+        |value: &::serde_json::Value| -> ::std::option::Option<bool> {
+            // A local trait gives the expression `self`, as in validate()
+            trait Condition {
+                fn condition(&self) -> bool;
+            }
+            impl Condition for #entity {
+                #[allow(unused_variables)]
+                fn condition(&self) -> bool {
+                    #bindings
+                    #expr
+                }
+            }
+            let entity: #entity = ::serde_json::from_value(value.clone()).ok()?;
+            ::std::option::Option::Some(entity.condition())
+        }
+    }
 }
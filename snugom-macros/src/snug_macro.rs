@@ -11,6 +11,11 @@ impl SnugInvocation {
     pub fn entity_type(&self) -> &Path {
         &self.path
     }
+
+    /// Whether this is a patch (`Entity(entity_id = ...) { ... }`) rather than a create
+    pub fn is_update(&self) -> bool {
+        self.options.is_some()
+    }
 }
 
 impl SnugInvocation {
@@ -61,6 +66,23 @@ impl SnugInvocation {
                     RelEntry::Relation(relation) => {
                         steps.extend(relation.emit()?);
                     }
+                    RelEntry::Increment(increment) => {
+                        let method = format_ident!("increment_{}", increment.name);
+                        let value = &increment.value;
+                        let sign = if increment.negate { quote! { - } } else { quote! {} };
+                        steps.push(quote! {
+                            builder = builder.#method(#sign ((#value) as f64));
+                        });
+                    }
+                    RelEntry::Array(array) => {
+                        let prefix = if array.remove { "remove" } else { "push" };
+                        let method = format_ident!("{}_{}", prefix, array.name);
+                        for value in &array.values {
+                            steps.push(quote! {
+                                builder = builder.#method(#value);
+                            });
+                        }
+                    }
                 }
             }
 
@@ -89,8 +111,26 @@ impl SnugInvocation {
                         }
                     }
                     RelEntry::Relation(relation) => {
+                        if relation.set.is_some() {
+                            return Err(Error::new(
+                                relation.alias.span(),
+                                "`set` is only supported in snugom_update!; a new entity has no relation to replace",
+                            ));
+                        }
                         steps.extend(relation.emit()?);
                     }
+                    RelEntry::Increment(increment) => {
+                        return Err(Error::new(
+                            increment.name.span(),
+                            "`+=` / `-=` increments are only supported in snugom_update!",
+                        ));
+                    }
+                    RelEntry::Array(array) => {
+                        return Err(Error::new(
+                            array.name.span(),
+                            "`push(...)` / `remove(...)` are only supported in snugom_update!",
+                        ));
+                    }
                 }
             }
 
@@ -190,11 +230,23 @@ impl UpdateOptions {
 enum RelEntry {
     Field(FieldEntry),
     Relation(RelationEntry),
+    Increment(IncrementEntry),
+    Array(ArrayEntry),
 }
 
 impl Parse for RelEntry {
     fn parse(input: ParseStream) -> Result<Self> {
         let name: Ident = input.parse()?;
+        if input.peek(Token![+=]) || input.peek(Token![-=]) {
+            let negate = input.peek(Token![-=]);
+            if negate {
+                input.parse::<Token![-=]>()?;
+            } else {
+                input.parse::<Token![+=]>()?;
+            }
+            let value: Expr = input.parse()?;
+            return Ok(RelEntry::Increment(IncrementEntry { name, value, negate }));
+        }
         let optional = if input.peek(Token![?]) {
             input.parse::<Token![?]>()?;
             true
@@ -202,6 +254,21 @@ impl Parse for RelEntry {
             false
         };
         input.parse::<Token![:]>()?;
+        if !optional
+            && input.peek(Ident)
+            && input.peek2(syn::token::Paren)
+            && input.fork().parse::<Ident>().is_ok_and(|op| op == "push" || op == "remove")
+        {
+            let op: Ident = input.parse()?;
+            let args;
+            parenthesized!(args in input);
+            let values = args.parse_terminated(Expr::parse, Token![,])?.into_iter().collect();
+            return Ok(RelEntry::Array(ArrayEntry {
+                name,
+                remove: op == "remove",
+                values,
+            }));
+        }
         if input.peek(syn::token::Bracket) {
             if optional {
                 return Err(Error::new(
@@ -209,15 +276,23 @@ impl Parse for RelEntry {
                     "relation directives do not support optional field markers",
                 ));
             }
+            let mut set: Option<Expr> = None;
             let mut connects = Vec::new();
             let mut disconnects = Vec::new();
             let mut deletes = Vec::new();
             let mut creates = Vec::new();
+            let mut connect_or_creates = Vec::new();
             let inner;
             bracketed!(inner in input);
             while !inner.is_empty() {
                 let op: Ident = inner.parse()?;
                 match op.to_string().as_str() {
+                    "set" => {
+                        if set.is_some() {
+                            return Err(Error::new(op.span(), "a relation can only be `set` once"));
+                        }
+                        set = Some(inner.parse()?);
+                    }
                     "connect" => {
                         let expr: Expr = inner.parse()?;
                         connects.push(expr);
@@ -235,6 +310,11 @@ impl Parse for RelEntry {
                         let invocation = SnugInvocation::parse_with_path(path, &inner)?;
                         creates.push(invocation);
                     }
+                    "connect_or_create" => {
+                        let path: Path = inner.parse()?;
+                        let invocation = SnugInvocation::parse_with_path(path, &inner)?;
+                        connect_or_creates.push(invocation);
+                    }
                     other => return Err(Error::new(op.span(), format!("unknown relation op `{}`", other))),
                 }
                 if inner.peek(Token![,]) {
@@ -243,10 +323,12 @@ impl Parse for RelEntry {
             }
             Ok(RelEntry::Relation(RelationEntry {
                 alias: name,
+                set,
                 connects,
                 disconnects,
                 deletes,
                 creates,
+                connect_or_creates,
             }))
         } else {
             let value: Expr = input.parse()?;
@@ -261,12 +343,28 @@ struct FieldEntry {
     optional: bool,
 }
 
+struct IncrementEntry {
+    name: Ident,
+    value: Expr,
+    negate: bool,
+}
+
+/// `field: push(a, b)` or `field: remove(a)`
+struct ArrayEntry {
+    name: Ident,
+    remove: bool,
+    values: Vec<Expr>,
+}
+
 struct RelationEntry {
     alias: Ident,
+    /// `set ids`: replace the whole relation, before the other directives apply
+    set: Option<Expr>,
     connects: Vec<Expr>,
     disconnects: Vec<Expr>,
     deletes: Vec<Expr>,
     creates: Vec<SnugInvocation>,
+    connect_or_creates: Vec<SnugInvocation>,
 }
 
 impl RelationEntry {
@@ -278,6 +376,15 @@ impl RelationEntry {
         let alias_lit = self.alias_literal();
         let mut tokens = Vec::new();
 
+        if let Some(set) = &self.set {
+            tokens.push(quote! {
+                builder = builder.set_relation(
+                    #alias_lit,
+                    ::std::iter::IntoIterator::into_iter(#set).map(::std::convert::Into::<String>::into).collect(),
+                );
+            });
+        }
+
         if !self.connects.is_empty() {
             let connects = self
                 .connects
@@ -315,6 +422,13 @@ impl RelationEntry {
             });
         }
 
+        for connect_or_create in &self.connect_or_creates {
+            let builder_tokens = connect_or_create.emit()?;
+            tokens.push(quote! {
+                builder = builder.connect_or_create(#alias_lit, #builder_tokens);
+            });
+        }
+
         Ok(tokens)
     }
 }
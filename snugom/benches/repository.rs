@@ -0,0 +1,20 @@
+//! Repository benchmarks: builder validation and Lua mutation plan construction.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use snugom::{Repo, bench};
+
+fn validate_payload(c: &mut Criterion) {
+    c.bench_function("validate_payload", |b| {
+        b.iter(|| bench::validate_payload(black_box(7)).expect("valid payload"))
+    });
+}
+
+fn build_create_plan(c: &mut Criterion) {
+    let repo = Repo::<bench::BenchOrder>::new("bench");
+    c.bench_function("build_create_plan", |b| {
+        b.iter(|| bench::build_create_plan(&repo, black_box(7)).expect("create plan"))
+    });
+}
+
+criterion_group!(benches, validate_payload, build_create_plan);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+//! Query-building benchmarks: value escaping and `SearchParams::build_query`.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use snugom::{
+    bench,
+    search::{
+        escape_for_tag_query, escape_for_text_contains, escape_for_text_exact, escape_for_text_fuzzy,
+        escape_for_text_prefix, escape_for_text_search,
+    },
+};
+
+type Escaper = fn(&str) -> String;
+
+fn escaping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("escape");
+    let escapers: [(&str, Escaper); 6] = [
+        ("tag", escape_for_tag_query),
+        ("text_prefix", escape_for_text_prefix),
+        ("text_contains", escape_for_text_contains),
+        ("text_exact", escape_for_text_exact),
+        ("text_fuzzy", escape_for_text_fuzzy),
+        ("text_search", escape_for_text_search),
+    ];
+    for (name, escape) in escapers {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for input in bench::ESCAPE_INPUTS {
+                    black_box(escape(black_box(input)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn build_query(c: &mut Criterion) {
+    let params = bench::search_params();
+    c.bench_function("build_query", |b| b.iter(|| black_box(params.build_query(black_box("@tenant:{acme}")))));
+}
+
+criterion_group!(benches, escaping, build_query);
+criterion_main!(benches);
@@ -1,31 +1,24 @@
 -- SnugOM entity delete script
 -- Arguments:
 --  ARGV[1] - JSON payload describing MutationCommand::DeleteEntity
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the entity's keys, see keys.lua
+-- Cascades carry the layout of the entities they reach, which may live under another service.
 
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(parts, part)
-    end
-    return parts
-end
-
-local function compute_child_relations(child_specs, prefix, service, entity_id)
+local function compute_child_relations(child_specs, entity_id)
     local result = {}
     for i = 1, #child_specs do
         local spec = child_specs[i]
-        local child_service = spec["target_service"]
-        if child_service == nil then
-            child_service = service
-        end
+        local child_layout = spec["target_layout"]
         local child_alias = spec["alias"]
-        local child_relation_key = table.concat({ prefix, child_service, "rel", child_alias, entity_id }, ":")
-        local nested = compute_child_relations(spec["child_relations"] or {}, prefix, child_service, entity_id)
+        local child_relation_key = layout_key(child_layout, "rel", child_alias, entity_id)
+        local nested = compute_child_relations(spec["child_relations"] or {}, entity_id)
         table.insert(result, {
             alias = child_alias,
             relation_key = child_relation_key,
+            layout = child_layout,
             target_collection = spec["target_collection"],
-            target_service = spec["target_service"],
+            target_layout = child_layout,
             cascade = spec["cascade"],
             maintain_reverse = spec["maintain_reverse"] == true,
             child_relations = nested,
@@ -34,10 +27,33 @@ local function compute_child_relations(child_specs, prefix, service, entity_id)
     return result
 end
 
-local function delete_with_relations(key, expected_version, relations, unique_constraints)
+-- Keys already removed by this script; guards against cycles in self-referential data
+local visited = {}
+
+-- Keep an entity's denormalized counter in step with one of its relation sets
+local function refresh_counter(entity_key, path, relation_key)
+    if path ~= nil and redis.call("EXISTS", entity_key) == 1 then
+        redis.call("JSON.SET", entity_key, path, redis.call("SCARD", relation_key))
+    end
+end
+
+local function delete_with_relations(key, layout, expected_version, relations, unique_constraints, parents)
+    if visited[key] then
+        return { ok = true }
+    end
+    visited[key] = true
     unique_constraints = unique_constraints or {}
+    parents = parents or {}
 
-    local stored_version_raw = redis.call("JSON.GET", key, "$.metadata.version")
+    -- Cascades can reach entities of either storage mode, so ask Redis which one this is
+    local is_hash = redis.call("TYPE", key)["ok"] == "hash"
+
+    local stored_version_raw
+    if is_hash then
+        stored_version_raw = redis.call("HGET", key, "metadata.version")
+    else
+        stored_version_raw = redis.call("JSON.GET", key, "$.metadata.version")
+    end
     local stored_version = nil
     if stored_version_raw ~= nil then
         if stored_version_raw == cjson.null or type(stored_version_raw) == "boolean" then
@@ -69,26 +85,31 @@ local function delete_with_relations(key, expected_version, relations, unique_co
         end
     end
 
-    -- Key structure: {prefix}:{service}:{collection}:{entity_id}
-    local key_parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(key_parts, part)
-    end
-
-    local prefix = key_parts[1]
-    local service = key_parts[2]
-    local collection = key_parts[3]
+    -- Key structure: {head}:{collection}:{entity_id}
+    local collection = key_tail(layout, key, 2)[1]
 
     -- Clean up unique constraint indexes before deleting the entity
     if #unique_constraints > 0 then
         -- Read the entity to get current field values
-        local entity_json = redis.call("JSON.GET", key, "$")
-        if entity_json ~= nil and entity_json ~= false then
-            local entity_data = cjson.decode(entity_json)
-            if type(entity_data) == "table" and entity_data[1] ~= nil then
-                entity_data = entity_data[1]
+        local entity_data = nil
+        if is_hash then
+            local flat = redis.call("HGETALL", key)
+            if #flat > 0 then
+                entity_data = {}
+                for i = 1, #flat, 2 do
+                    entity_data[flat[i]] = flat[i + 1]
+                end
             end
-
+        else
+            local entity_json = redis.call("JSON.GET", key, "$")
+            if entity_json ~= nil and entity_json ~= false then
+                entity_data = cjson.decode(entity_json)
+                if type(entity_data) == "table" and entity_data[1] ~= nil then
+                    entity_data = entity_data[1]
+                end
+            end
+        end
+        if entity_data ~= nil then
             for i = 1, #unique_constraints do
                 local constraint = unique_constraints[i]
                 local fields = constraint["fields"]
@@ -115,10 +136,10 @@ local function delete_with_relations(key, expected_version, relations, unique_co
                     -- Build unique index key
                     local unique_key
                     if #fields == 1 then
-                        unique_key = table.concat({ prefix, service, collection, "unique", fields[1] }, ":")
+                        unique_key = layout_key(layout, collection, "unique", fields[1])
                     else
                         local field_suffix = table.concat(fields, "_")
-                        unique_key = table.concat({ prefix, service, collection, "unique_compound", field_suffix }, ":")
+                        unique_key = layout_key(layout, collection, "unique_compound", field_suffix)
                     end
 
                     -- Remove the entry from the unique index
@@ -128,12 +149,51 @@ local function delete_with_relations(key, expected_version, relations, unique_co
         end
     end
 
+    -- Parent ids come from the belongs-to foreign keys, so read them before the document goes
+    local parent_links = {}
+    for i = 1, #parents do
+        local parent = parents[i]
+        local parent_id
+        if is_hash then
+            parent_id = redis.call("HGET", key, parent["foreign_key"])
+        else
+            local raw = redis.call("JSON.GET", key, "$." .. parent["foreign_key"])
+            if raw then
+                parent_id = cjson.decode(raw)[1]
+            end
+        end
+        if type(parent_id) == "string" and parent_id ~= "" then
+            table.insert(parent_links, { parent = parent, id = parent_id })
+        end
+    end
+
     redis.call("DEL", key)
+    -- Previous documents of versioned entities go with the entity: {head}:history:{collection}:{entity_id}
+    local entity_id = key_tail(layout, key, 2)[2]
+    redis.call("DEL", layout_key(layout, "history", collection, entity_id))
+
+    if #parent_links > 0 then
+        for i = 1, #parent_links do
+            local parent = parent_links[i].parent
+            local parent_layout = parent["parent_layout"]
+            local parent_id = parent_links[i].id
+            local forward_key = layout_key(parent_layout, "rel", parent["alias"], parent_id)
+            redis.call("SREM", forward_key, entity_id)
+            redis.call("HDEL", forward_key .. parent_layout.separator .. "edges", entity_id)
+            refresh_counter(
+                layout_key(parent_layout, parent["parent_collection"], parent_id),
+                parent["counter_path"],
+                forward_key
+            )
+        end
+    end
 
     for i = 1, #relations do
         local relation = relations[i]
         local cascade = relation["cascade"]
         local relation_key = relation["relation_key"]
+        -- Relation keys from the command share the entity's layout; derived ones carry their own
+        local relation_layout = relation["layout"] or layout
         local maintain_reverse = relation["maintain_reverse"] == true
         local relation_parts
         local alias
@@ -141,29 +201,32 @@ local function delete_with_relations(key, expected_version, relations, unique_co
         local reverse_alias
 
         if maintain_reverse then
-            -- Relation key structure: {prefix}:{service}:rel:{alias}:{left_id}
-            relation_parts = split_key(relation_key)
-            alias = relation_parts[4]
-            left_id = relation_parts[5]
+            -- Relation key structure: {head}:rel:{alias}:{left_id}
+            relation_parts = key_tail(relation_layout, relation_key, 3)
+            alias = relation_parts[2]
+            left_id = relation_parts[3]
             reverse_alias = alias .. "_reverse"
         end
 
         if cascade == "delete_dependents" then
             local members = redis.call("SMEMBERS", relation_key)
             local target_collection = relation["target_collection"]
-            local target_service = relation["target_service"]
-            if target_service == nil then
-                target_service = service
-            end
+            local target_layout = relation["target_layout"]
             local child_specs = relation["child_relations"] or {}
             if target_collection ~= nil then
                 for j = 1, #members do
                     local member_id = members[j]
-                    local child_key = table.concat({ prefix, target_service, target_collection, member_id }, ":")
-                    local child_relations_payload = compute_child_relations(child_specs, prefix, target_service, member_id)
+                    local child_key = layout_key(target_layout, target_collection, member_id)
+                    local child_relations_payload = compute_child_relations(child_specs, member_id)
                     -- Unique constraints for child entities are passed through the relation info
                     local child_unique_constraints = relation["unique_constraints"] or {}
-                    local result = delete_with_relations(child_key, nil, child_relations_payload, child_unique_constraints)
+                    local result = delete_with_relations(
+                        child_key,
+                        target_layout,
+                        nil,
+                        child_relations_payload,
+                        child_unique_constraints
+                    )
                     if result["error"] ~= nil then
                         return result
                     end
@@ -172,7 +235,7 @@ local function delete_with_relations(key, expected_version, relations, unique_co
             if maintain_reverse then
                 for j = 1, #members do
                     local member_id = members[j]
-                    local reverse_key = table.concat({ prefix, service, "rel", reverse_alias, member_id }, ":")
+                    local reverse_key = layout_key(layout, "rel", reverse_alias, member_id)
                     redis.call("SREM", reverse_key, left_id)
                     if redis.call("SCARD", reverse_key) == 0 then
                         redis.call("DEL", reverse_key)
@@ -180,12 +243,13 @@ local function delete_with_relations(key, expected_version, relations, unique_co
                 end
             end
             redis.call("DEL", relation_key)
+            redis.call("DEL", relation_key .. relation_layout.separator .. "edges")
         elseif cascade == "detach_dependents" then
             if maintain_reverse then
                 local members = redis.call("SMEMBERS", relation_key)
                 for j = 1, #members do
                     local member_id = members[j]
-                    local reverse_key = table.concat({ prefix, service, "rel", reverse_alias, member_id }, ":")
+                    local reverse_key = layout_key(layout, "rel", reverse_alias, member_id)
                     redis.call("SREM", reverse_key, left_id)
                     if redis.call("SCARD", reverse_key) == 0 then
                         redis.call("DEL", reverse_key)
@@ -193,18 +257,25 @@ local function delete_with_relations(key, expected_version, relations, unique_co
                 end
             end
             redis.call("DEL", relation_key)
+            redis.call("DEL", relation_key .. relation_layout.separator .. "edges")
         end
 
         if maintain_reverse then
-            local reverse_self_key = table.concat({ prefix, service, "rel", reverse_alias, left_id }, ":")
+            local reverse_self_key = layout_key(layout, "rel", reverse_alias, left_id)
             local parents = redis.call("SMEMBERS", reverse_self_key)
             for j = 1, #parents do
                 local parent_id = parents[j]
-                local parent_forward_key = table.concat({ prefix, service, "rel", alias, parent_id }, ":")
+                local parent_forward_key = layout_key(layout, "rel", alias, parent_id)
                 redis.call("SREM", parent_forward_key, left_id)
+                redis.call("HDEL", parent_forward_key .. layout.separator .. "edges", left_id)
                 if redis.call("SCARD", parent_forward_key) == 0 then
                     redis.call("DEL", parent_forward_key)
                 end
+                refresh_counter(
+                    layout_key(layout, collection, parent_id),
+                    relation["counter_path"],
+                    parent_forward_key
+                )
             end
             redis.call("DEL", reverse_self_key)
         end
@@ -221,9 +292,11 @@ end
 
 local result = delete_with_relations(
     deletion["key"],
+    LAYOUT,
     deletion["expected_version"],
     deletion["relations"] or {},
-    deletion["unique_constraints"] or {}
+    deletion["unique_constraints"] or {},
+    deletion["parents"] or {}
 )
 
 return cjson.encode(result)
@@ -5,18 +5,11 @@
 -- Arguments:
 --  KEYS[1] - placeholder (unused; commands rely on explicit keys)
 --  ARGV[1] - JSON payload describing GetOrCreate command
---  ARGV[2] - script version for optimistic upgrades (unused for now)
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the entity's keys, see keys.lua
 
 local cjson = cjson
 
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(parts, part)
-    end
-    return parts
-end
-
 local function encode_result(result)
     return cjson.encode(result)
 end
@@ -28,11 +21,7 @@ local function check_idempotency(key, idempotency_key)
     if not idempotency_key then
         return nil
     end
-    local parts = split_key(key)
-    if #parts < 3 then
-        return nil
-    end
-    local store_key = table.concat({ parts[1], parts[2], "idempotency", idempotency_key }, ":")
+    local store_key = layout_key(LAYOUT, "idempotency", idempotency_key)
     local existing = redis.call("GET", store_key)
     if existing then
         return existing
@@ -44,11 +33,7 @@ local function store_idempotency(key, idempotency_key, response, ttl)
     if not idempotency_key then
         return
     end
-    local parts = split_key(key)
-    if #parts < 3 then
-        return
-    end
-    local store_key = table.concat({ parts[1], parts[2], "idempotency", idempotency_key }, ":")
+    local store_key = layout_key(LAYOUT, "idempotency", idempotency_key)
     if ttl and ttl > 0 then
         redis.call("SET", store_key, response, "EX", ttl)
     else
@@ -56,20 +41,18 @@ local function store_idempotency(key, idempotency_key, response, ttl)
     end
 end
 
--- Check unique constraint (simplified version from upsert)
-local function check_unique_constraint(constraint, entity_id, prefix, service, collection)
+-- Index key and lookup value of a unique constraint, laid out as in entity_mutation.lua;
+-- nil when any of its values is null
+local function unique_index(constraint, collection)
     local fields = constraint["fields"]
     local values = constraint["values"]
     local case_insensitive = constraint["case_insensitive"] == true
 
-    -- Build lookup value
     local lookup_parts = {}
-    local has_null = false
     for i = 1, #fields do
         local v = values[i]
         if v == nil or v == cjson.null then
-            has_null = true
-            break
+            return nil, nil
         end
         if case_insensitive and type(v) == "string" then
             v = string.lower(v)
@@ -77,12 +60,22 @@ local function check_unique_constraint(constraint, entity_id, prefix, service, c
         table.insert(lookup_parts, tostring(v))
     end
 
-    if has_null then
-        return nil, nil, nil
+    local unique_key
+    if #fields == 1 then
+        unique_key = layout_key(LAYOUT, collection, "unique", fields[1])
+    else
+        unique_key = layout_key(LAYOUT, collection, "unique_compound", table.concat(fields, "_"))
     end
+    return unique_key, table.concat(lookup_parts, ":")
+end
 
-    local lookup_value = table.concat(lookup_parts, "|")
-    local unique_key = table.concat({ prefix, service, "unique", collection, table.concat(fields, "_") }, ":")
+local function check_unique_constraint(constraint, entity_id, collection)
+    local fields = constraint["fields"]
+    local values = constraint["values"]
+    local unique_key, lookup_value = unique_index(constraint, collection)
+    if unique_key == nil then
+        return nil, nil, nil
+    end
 
     -- Check if this value already exists
     local existing_id = redis.call("HGET", unique_key, lookup_value)
@@ -113,7 +106,7 @@ local function apply_datetime_mirrors(key, datetime_mirrors)
 end
 
 -- Apply relations (simplified from upsert)
-local function apply_relations(relations, prefix, service)
+local function apply_relations(relations)
     for i = 1, #relations do
         local rel = relations[i]
         local op = rel["op"]
@@ -147,10 +140,23 @@ local function main()
     end
 
     -- Parse key structure
-    local key_parts = split_key(entity_key)
-    local prefix = key_parts[1]
-    local service = key_parts[2]
-    local collection = key_parts[3]
+    local collection = key_tail(LAYOUT, entity_key, 2)[1]
+
+    -- Resolve by a unique constraint: an existing entity holding the value is the one to return
+    local lookup = get_or_create["lookup"]
+    if lookup ~= nil then
+        local unique_key, lookup_value = unique_index(lookup, collection)
+        if unique_key ~= nil then
+            local existing_id = redis.call("HGET", unique_key, lookup_value)
+            if existing_id then
+                local existing_key = layout_key(LAYOUT, collection, existing_id)
+                if redis.call("EXISTS", existing_key) == 1 then
+                    entity_key = existing_key
+                    entity_id = existing_id
+                end
+            end
+        end
+    end
 
     -- Check if the entity exists
     local exists = redis.call("EXISTS", entity_key) == 1
@@ -184,12 +190,17 @@ local function main()
             return encode_result({ error = "invalid_payload", message = "create_payload_json is required" })
         end
 
+        local check_error = run_lua_check(get_or_create["lua_check"], cjson.decode(create_payload_json), nil)
+        if check_error ~= nil then
+            return encode_result(check_error)
+        end
+
         -- Check unique constraints
         local unique_updates = {}
         for i = 1, #unique_constraints do
             local constraint = unique_constraints[i]
             local violation, unique_key, lookup_value = check_unique_constraint(
-                constraint, entity_id, prefix, service, collection
+                constraint, entity_id, collection
             )
             if violation then
                 return encode_result(violation)
@@ -220,7 +231,7 @@ local function main()
         apply_datetime_mirrors(entity_key, datetime_mirrors)
 
         -- Apply relations
-        apply_relations(relations, prefix, service)
+        apply_relations(relations)
 
         -- Re-read the created entity to return it
         local created_json = redis.call("JSON.GET", entity_key, "$")
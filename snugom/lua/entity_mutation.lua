@@ -2,15 +2,8 @@
 -- Arguments:
 --  KEYS[1] - placeholder (unused; commands rely on explicit keys)
 --  ARGV[1] - JSON payload describing MutationCommand::UpsertEntity
---  ARGV[2] - script version for optimistic upgrades (unused for now)
-
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(parts, part)
-    end
-    return parts
-end
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the entity's keys, see keys.lua
 
 local payload = cjson.decode(ARGV[1])
 local mutation = payload["upsert_entity"]
@@ -23,26 +16,28 @@ local expected_version = mutation["expected_version"]
 local idempotency_key = mutation["idempotency_key"]
 local datetime_mirrors = mutation["datetime_mirrors"] or {}
 local relations = mutation["relations"] or {}
+-- Present only for hash-stored entities: alternating field/value strings for HSET
+local hash_fields = mutation["hash_fields"]
+local is_hash = hash_fields ~= nil and hash_fields ~= cjson.null
 
--- Key structure: {prefix}:{service}:{collection}:{entity_id}
-local key_parts = {}
-for part in string.gmatch(key, "([^:]+)") do
-    table.insert(key_parts, part)
-end
+-- Key structure: {head}:{collection}:{entity_id}
+local key_parts = key_tail(LAYOUT, key, 2)
 
-local prefix = key_parts[1]
-local service = key_parts[2]
--- key_parts[3] is collection, key_parts[4] is entity_id
+-- Replays return the stored response flagged with `replayed`, so callers can tell it from a fresh write.
+-- Stored responses are always non-empty JSON objects.
+local function mark_replay(stored)
+    return '{"replayed":true,' .. string.sub(stored, 2)
+end
 
 local idempotency_store_key = nil
 local IDEMPOTENCY_TTL_SECONDS = 900
 local idempotency_ttl = nil
 
 if idempotency_key ~= nil then
-    idempotency_store_key = table.concat({ prefix, service, "idempotency", idempotency_key }, ":")
+    idempotency_store_key = layout_key(LAYOUT, "idempotency", idempotency_key)
     local existing = redis.call("GET", idempotency_store_key)
     if existing then
-        return existing
+        return mark_replay(existing)
     end
     idempotency_ttl = mutation["idempotency_ttl"]
     if idempotency_ttl ~= nil then
@@ -56,7 +51,17 @@ if idempotency_key ~= nil then
     end
 end
 
-local stored_version_raw = redis.call("JSON.GET", key, "$.metadata.version")
+-- Strict creates refuse to overwrite an existing document
+if mutation["if_absent"] == true and redis.call("EXISTS", key) == 1 then
+    return cjson.encode({ error = "already_exists", entity_id = mutation["entity_id"] })
+end
+
+local stored_version_raw
+if is_hash then
+    stored_version_raw = redis.call("HGET", key, "metadata.version")
+else
+    stored_version_raw = redis.call("JSON.GET", key, "$.metadata.version")
+end
 local stored_version = nil
 if stored_version_raw ~= nil then
     if stored_version_raw == cjson.null or type(stored_version_raw) == "boolean" then
@@ -94,13 +99,22 @@ if payload_json == nil then
     return cjson.encode({ error = "invalid_payload", message = "payload_json is required" })
 end
 if entity_id == nil or entity_id == cjson.null then
-    entity_id = key_parts[#key_parts]
+    entity_id = key_parts[2]
+end
+
+local lua_check = mutation["lua_check"]
+if lua_check ~= nil and lua_check ~= cjson.null then
+    local _, previous_doc = read_document(key)
+    local check_error = run_lua_check(lua_check, cjson.decode(payload_json), previous_doc)
+    if check_error ~= nil then
+        return cjson.encode(check_error)
+    end
 end
 
 -- Unique constraint enforcement
 -- Structure: unique_constraints is an array of {fields: ["name"], case_insensitive: bool, values: ["value"]}
 local unique_constraints = mutation["unique_constraints"] or {}
-local collection = key_parts[3]
+local collection = key_parts[1]
 
 -- Track unique keys we need to update (old values to remove, new values to add)
 local unique_updates = {}
@@ -133,12 +147,12 @@ for i = 1, #unique_constraints do
         -- Build unique index key
         local unique_key
         if #fields == 1 then
-            -- Single field: {prefix}:{service}:{collection}:unique:{field}
-            unique_key = table.concat({ prefix, service, collection, "unique", fields[1] }, ":")
+            -- Single field: {head}:{collection}:unique:{field}
+            unique_key = layout_key(LAYOUT, collection, "unique", fields[1])
         else
-            -- Compound: {prefix}:{service}:{collection}:unique_compound:{field1}_{field2}
+            -- Compound: {head}:{collection}:unique_compound:{field1}_{field2}
             local field_suffix = table.concat(fields, "_")
-            unique_key = table.concat({ prefix, service, collection, "unique_compound", field_suffix }, ":")
+            unique_key = layout_key(LAYOUT, collection, "unique_compound", field_suffix)
         end
 
         -- Check if value already taken by DIFFERENT entity
@@ -161,21 +175,39 @@ for i = 1, #unique_constraints do
     end
 end
 
-redis.call("JSON.SET", key, "$", payload_json)
+if is_hash then
+    -- Replace the whole document, like JSON.SET on the root path
+    redis.call("DEL", key)
+    if #hash_fields > 0 then
+        redis.call("HSET", key, unpack(hash_fields))
+    end
+else
+    redis.call("JSON.SET", key, "$", payload_json)
+end
 
 -- Now reserve all unique values atomically
 for i = 1, #unique_updates do
     local update = unique_updates[i]
     redis.call("HSET", update.unique_key, update.lookup_value, update.entity_id)
 end
-redis.call("JSON.SET", key, "$.metadata.version", next_version)
+if is_hash then
+    redis.call("HSET", key, "metadata.version", next_version)
+else
+    redis.call("JSON.SET", key, "$.metadata.version", next_version)
+end
 
 redis.call("PERSIST", key)
 
 for i = 1, #datetime_mirrors do
     local mirror = datetime_mirrors[i]
     if mirror["mirror_field"] ~= nil then
-        if mirror["value"] == cjson.null or mirror["value"] == nil then
+        if is_hash then
+            if mirror["value"] == cjson.null or mirror["value"] == nil then
+                redis.call("HDEL", key, mirror["mirror_field"])
+            else
+                redis.call("HSET", key, mirror["mirror_field"], cjson.encode(mirror["value"]))
+            end
+        elseif mirror["value"] == cjson.null or mirror["value"] == nil then
             redis.call("JSON.DEL", key, "$." .. mirror["mirror_field"])
         else
             redis.call(
@@ -194,30 +226,53 @@ for i = 1, #relations do
     local add = relation["add"] or {}
     local remove = relation["remove"] or {}
     local maintain_reverse = relation["maintain_reverse"] == true
+
+    -- Replacing the set removes the current members that are not being added
+    if relation["replace"] == true then
+        local keep = {}
+        for j = 1, #add do
+            keep[add[j]] = true
+        end
+        local current = redis.call("SMEMBERS", relation_key)
+        for j = 1, #current do
+            if not keep[current[j]] then
+                table.insert(remove, current[j])
+            end
+        end
+    end
+
     local relation_parts
-    local rel_prefix
-    local rel_service
     local alias
     local left_id
     local reverse_alias
 
     if maintain_reverse then
-        -- Relation key structure: {prefix}:{service}:rel:{alias}:{left_id}
-        relation_parts = split_key(relation_key)
-        rel_prefix = relation_parts[1]
-        rel_service = relation_parts[2]
-        -- relation_parts[3] is "rel"
-        alias = relation_parts[4]
-        left_id = relation_parts[5]
+        -- Relation key structure: {head}:rel:{alias}:{left_id}
+        relation_parts = key_tail(LAYOUT, relation_key, 3)
+        -- relation_parts[1] is "rel"
+        alias = relation_parts[2]
+        left_id = relation_parts[3]
         reverse_alias = alias .. "_reverse"
     end
 
+    -- Edge payloads live in a hash alongside the relation set, keyed by member id
+    local edges = relation["edges"] or {}
+    if #edges > 0 then
+        local edges_key = relation_key .. SEP .. "edges"
+        for j = 1, #edges do
+            redis.call("HSET", edges_key, edges[j]["member_id"], edges[j]["data_json"])
+        end
+    end
+    if #remove > 0 then
+        redis.call("HDEL", relation_key .. SEP .. "edges", unpack(remove))
+    end
+
     if #add > 0 then
         redis.call("SADD", relation_key, unpack(add))
         if maintain_reverse then
             for j = 1, #add do
                 local member_id = add[j]
-                local reverse_key = table.concat({ rel_prefix, rel_service, "rel", reverse_alias, member_id }, ":")
+                local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
                 redis.call("SADD", reverse_key, left_id)
             end
         end
@@ -228,7 +283,7 @@ for i = 1, #relations do
         if maintain_reverse then
             for j = 1, #remove do
                 local member_id = remove[j]
-                local reverse_key = table.concat({ rel_prefix, rel_service, "rel", reverse_alias, member_id }, ":")
+                local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
                 redis.call("SREM", reverse_key, left_id)
                 if redis.call("SCARD", reverse_key) == 0 then
                     redis.call("DEL", reverse_key)
@@ -236,6 +291,12 @@ for i = 1, #relations do
             end
         end
     end
+
+    -- Keep the parent's denormalized counter in step with the relation set
+    local counter = relation["counter"]
+    if counter ~= nil and redis.call("EXISTS", counter["entity_key"]) == 1 then
+        redis.call("JSON.SET", counter["entity_key"], counter["path"], redis.call("SCARD", relation_key))
+    end
 end
 
 local response = {
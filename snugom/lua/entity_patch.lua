@@ -1,3 +1,8 @@
+-- Arguments:
+--  ARGV[1] - JSON payload describing MutationCommand::PatchEntity
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the entity's keys, see keys.lua
+
 local cjson = cjson
 
 local function decode_payload(arg)
@@ -25,7 +30,10 @@ local function normalize_version(value)
     return value
 end
 
-local function load_current_version(key)
+local function load_current_version(key, is_hash)
+    if is_hash then
+        return normalize_version(redis.call('HGET', key, 'metadata.version') or cjson.null), nil
+    end
     local raw = redis.call('JSON.GET', key, '$.metadata.version')
     if not raw then
         return nil
@@ -41,25 +49,17 @@ local function encode_result(result)
     return cjson.encode(result)
 end
 
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, '([^:]+)') do
-        table.insert(parts, part)
-    end
-    return parts
-end
-
 local function ensure_idempotency(key, idempotency_key, response)
     if not idempotency_key then
         return nil
     end
 
-    local parts = split_key(key)
+    local collection = key_tail(LAYOUT, key, 2)[1]
     local global_store_key = nil
-    if #parts >= 3 then
-        global_store_key = table.concat({ parts[1], parts[2], parts[3], 'idempotency', idempotency_key }, ':')
+    if collection ~= '' then
+        global_store_key = layout_key(LAYOUT, collection, 'idempotency', idempotency_key)
     end
-    local entity_store_key = key .. ':idempotency:' .. idempotency_key
+    local entity_store_key = key .. SEP .. 'idempotency' .. SEP .. idempotency_key
 
     if response == nil then
         local existing = redis.call('GET', entity_store_key)
@@ -91,6 +91,102 @@ local function ensure_idempotency(key, idempotency_key, response)
     return nil
 end
 
+-- Hash-stored entities keep one field per top-level path and only support assign/delete.
+local function apply_hash_operation(key, op)
+    local field = string.gsub(op['path'], '^%$%.', '')
+    local op_type = op['type']
+    if op_type == 'assign' then
+        local hash_value = op['hash_value']
+        if hash_value == nil then
+            redis.call('HDEL', key, field)
+        else
+            redis.call('HSET', key, field, hash_value)
+        end
+    elseif op_type == 'delete' then
+        redis.call('HDEL', key, field)
+    elseif op_type == 'increment' then
+        redis.call('HINCRBYFLOAT', key, field, tostring(op['value']))
+    else
+        return { error = 'invalid_payload', message = op_type .. ' is not supported for hash storage' }
+    end
+end
+
+-- Deep equality for decoded JSON values, used to match array elements on removal
+local function json_equal(a, b)
+    if type(a) ~= type(b) then
+        return false
+    end
+    if type(a) ~= 'table' then
+        return a == b
+    end
+    for k, v in pairs(a) do
+        if not json_equal(v, b[k]) then
+            return false
+        end
+    end
+    for k in pairs(b) do
+        if a[k] == nil then
+            return false
+        end
+    end
+    return true
+end
+
+local function array_append(key, path, elements_json)
+    local kind = redis.call('JSON.TYPE', key, path)
+    if type(kind) == 'table' then
+        kind = kind[1]
+    end
+    if kind == nil or kind == false or kind == 'null' then
+        redis.call('JSON.SET', key, path, '[' .. table.concat(elements_json, ',') .. ']')
+    else
+        redis.call('JSON.ARRAPPEND', key, path, unpack(elements_json))
+    end
+end
+
+-- Remove every element equal to one of `elements_json`, highest index first so positions stay valid
+local function array_remove(key, path, elements_json)
+    local raw = redis.call('JSON.GET', key, path)
+    if not raw then
+        return
+    end
+    local current = cjson.decode(raw)[1]
+    if type(current) ~= 'table' then
+        return
+    end
+    local targets = {}
+    for i, element in ipairs(elements_json) do
+        targets[i] = cjson.decode(element)
+    end
+    for index = #current, 1, -1 do
+        for _, target in ipairs(targets) do
+            if json_equal(current[index], target) then
+                redis.call('JSON.DEL', key, path .. '[' .. (index - 1) .. ']')
+                break
+            end
+        end
+    end
+end
+
+-- The first array an append or removal left outside its field's `length` bounds, as an error
+local function array_length_error(operations, doc)
+    for _, op in ipairs(operations) do
+        local bounds = op['length']
+        if bounds ~= nil then
+            local field = string.sub(op['path'], 3)
+            local array = doc[field]
+            local length = 0
+            if type(array) == 'table' then
+                length = #array
+            end
+            if (bounds['min'] ~= nil and length < bounds['min']) or (bounds['max'] ~= nil and length > bounds['max']) then
+                return { error = 'length_violation', field = field, length = length, min = bounds['min'], max = bounds['max'] }
+            end
+        end
+    end
+    return nil
+end
+
 local function apply_operation(key, op)
     local path = op['path']
     local op_type = op['type']
@@ -117,24 +213,136 @@ local function apply_operation(key, op)
             number_value = tonumber(number_value)
         end
         redis.call('JSON.NUMINCRBY', key, path, number_value)
+    elseif op_type == 'array_append' or op_type == 'array_remove' then
+        local elements_json = op['elements_json']
+        if elements_json == nil then
+            return { error = 'invalid_payload', message = 'elements_json is required for ' .. op_type }
+        end
+        if op_type == 'array_append' then
+            array_append(key, path, elements_json)
+        else
+            array_remove(key, path, elements_json)
+        end
     else
         error('unknown patch operation type: ' .. tostring(op_type))
     end
 end
 
-local function apply_mirror(key, mirror)
+local function apply_mirror(key, mirror, is_hash)
     if not mirror then
         return
     end
     local mirror_field = mirror['mirror_field']
     local value_json = mirror['mirror_value_json']
-    if value_json == nil or value_json == 'null' then
+    if is_hash then
+        if value_json == nil or value_json == 'null' then
+            redis.call('HDEL', key, mirror_field)
+        else
+            redis.call('HSET', key, mirror_field, value_json)
+        end
+    elseif value_json == nil or value_json == 'null' then
         redis.call('JSON.DEL', key, '$.' .. mirror_field)
     else
         redis.call('JSON.SET', key, '$.' .. mirror_field, value_json)
     end
 end
 
+-- Values at `path` in `doc`; arrays contribute each element, null and missing nothing.
+local function precondition_values(doc, path)
+    local value = doc
+    for _, segment in ipairs(path) do
+        if type(value) ~= 'table' then
+            return {}
+        end
+        value = value[segment]
+    end
+    if value == nil or value == cjson.null then
+        return {}
+    end
+    if type(value) == 'table' and (#value > 0 or next(value) == nil) then
+        return value
+    end
+    return { value }
+end
+
+local function precondition_texts(doc, path)
+    local texts = {}
+    for _, value in ipairs(precondition_values(doc, path)) do
+        if type(value) == 'string' then
+            table.insert(texts, string.lower(value))
+        else
+            table.insert(texts, tostring(value))
+        end
+    end
+    return texts
+end
+
+-- Mirrors `Precondition::matches`: evaluates a resolved filter against the stored document.
+local function precondition_matches(condition, doc)
+    local kind = condition['kind']
+    if kind == 'and' then
+        for _, inner in ipairs(condition['conditions']) do
+            if not precondition_matches(inner, doc) then
+                return false
+            end
+        end
+        return true
+    elseif kind == 'or' then
+        if #condition['conditions'] == 0 then
+            return true
+        end
+        for _, inner in ipairs(condition['conditions']) do
+            if precondition_matches(inner, doc) then
+                return true
+            end
+        end
+        return false
+    elseif kind == 'not' then
+        return not precondition_matches(condition['condition'], doc)
+    elseif kind == 'missing' then
+        return #precondition_values(doc, condition['path']) == 0
+    elseif kind == 'numeric' then
+        local min = condition['min']
+        local max = condition['max']
+        local above = function(number)
+            return min == nil or number > min or (number == min and not condition['min_exclusive'])
+        end
+        local below = function(number)
+            return max == nil or number < max or (number == max and not condition['max_exclusive'])
+        end
+        for _, value in ipairs(precondition_values(doc, condition['path'])) do
+            local number = tonumber(value)
+            if number ~= nil and above(number) and below(number) then
+                return true
+            end
+        end
+        return false
+    end
+
+    local expected = {}
+    if kind == 'tag' then
+        for _, value in ipairs(condition['values']) do
+            table.insert(expected, string.lower(value))
+        end
+    elseif kind == 'boolean' then
+        expected = { tostring(condition['value']) }
+    else
+        expected = { string.lower(condition['value']) }
+    end
+    for _, stored in ipairs(precondition_texts(doc, condition['path'])) do
+        for _, value in ipairs(expected) do
+            if kind == 'prefix' and string.sub(stored, 1, #value) == value then
+                return true
+            elseif kind == 'contains' and string.find(stored, value, 1, true) ~= nil then
+                return true
+            elseif (kind == 'tag' or kind == 'boolean' or kind == 'exact') and stored == value then
+                return true
+            end
+        end
+    end
+    return false
+end
+
 local function main()
     local payload, error_payload = decode_payload(ARGV[1])
     if error_payload then
@@ -153,6 +361,7 @@ local function main()
     local relations = patch['relations'] or {}
     local entity_id = patch['entity_id']
     local unique_constraints = patch['unique_constraints'] or {}
+    local is_hash = patch['hash'] == true
 
     local exists = redis.call('EXISTS', key)
     if exists == 0 then
@@ -162,7 +371,7 @@ local function main()
         })
     end
 
-    if #operations == 0 and #relations == 0 then
+    if #operations == 0 and #relations == 0 and patch['touch'] ~= true then
         return encode_result({ ok = true, version = nil, entity_id = nil })
     end
 
@@ -171,7 +380,17 @@ local function main()
         return replay
     end
 
-    local current_version, err = load_current_version(key)
+    -- Immutable fields are rejected client-side too; this guards payloads built by hand
+    local immutable_paths = patch['immutable_paths'] or {}
+    for _, op in ipairs(operations) do
+        for _, path in ipairs(immutable_paths) do
+            if op['path'] == path then
+                return encode_result({ error = 'immutable_field', field = string.sub(path, 3) })
+            end
+        end
+    end
+
+    local current_version, err = load_current_version(key, is_hash)
     if err then
         return encode_result({ error = 'version_read_failed', message = err })
     end
@@ -199,17 +418,40 @@ local function main()
         end
     end
 
+    local precondition = patch['precondition']
+    if precondition ~= nil then
+        local current_doc
+        if is_hash then
+            current_doc = {}
+            local flat = redis.call('HGETALL', key)
+            for i = 1, #flat, 2 do
+                current_doc[flat[i]] = flat[i + 1]
+            end
+        else
+            current_doc = select(2, read_document(key))
+        end
+        if not precondition_matches(precondition, current_doc or {}) then
+            return encode_result({ error = 'precondition_failed', entity_id = entity_id })
+        end
+    end
+
     -- Handle unique constraint enforcement for patch operations
-    local key_parts = split_key(key)
-    local prefix = key_parts[1]
-    local service = key_parts[2]
-    local collection = key_parts[3]
+    local collection = key_tail(LAYOUT, key, 2)[1]
     local unique_updates = {}
 
     if #unique_constraints > 0 then
         -- Read current entity to get existing values for unique fields
-        local entity_json = redis.call('JSON.GET', key, '$')
+        local entity_json = nil
         local entity_data = nil
+        if is_hash then
+            local flat = redis.call('HGETALL', key)
+            entity_data = {}
+            for i = 1, #flat, 2 do
+                entity_data[flat[i]] = flat[i + 1]
+            end
+        else
+            entity_json = redis.call('JSON.GET', key, '$')
+        end
         if entity_json ~= nil and entity_json ~= false then
             local decoded = cjson.decode(entity_json)
             if type(decoded) == 'table' and decoded[1] ~= nil then
@@ -279,10 +521,10 @@ local function main()
                     -- Build unique index key
                     local unique_key
                     if #fields == 1 then
-                        unique_key = table.concat({ prefix, service, collection, 'unique', fields[1] }, ':')
+                        unique_key = layout_key(LAYOUT, collection, 'unique', fields[1])
                     else
                         local field_suffix = table.concat(fields, '_')
-                        unique_key = table.concat({ prefix, service, collection, 'unique_compound', field_suffix }, ':')
+                        unique_key = layout_key(LAYOUT, collection, 'unique_compound', field_suffix)
                     end
 
                     -- Check if new value conflicts with OTHER entity
@@ -308,17 +550,52 @@ local function main()
         end
     end
 
+    local lua_check = patch['lua_check']
+    local history = patch['history']
+    local snapshot_json = nil
+    local snapshot_doc = nil
+    local length_checked = false
     for _, op in ipairs(operations) do
-        local op_result = apply_operation(key, op)
+        if op['length'] ~= nil then
+            length_checked = true
+        end
+    end
+    if (lua_check ~= nil and lua_check ~= cjson.null) or (history ~= nil and #operations > 0) or length_checked then
+        snapshot_json, snapshot_doc = read_document(key)
+    end
+
+    for _, op in ipairs(operations) do
+        local op_result
+        if is_hash then
+            op_result = apply_hash_operation(key, op)
+        else
+            op_result = apply_operation(key, op)
+        end
         if op_result ~= nil and op_result['error'] ~= nil then
             return encode_result(op_result)
         end
         local mirror = op['mirror']
         if mirror then
-            apply_mirror(key, mirror)
+            apply_mirror(key, mirror, is_hash)
         end
     end
 
+    if snapshot_json ~= nil then
+        local _, candidate_doc = read_document(key)
+        local check_error = array_length_error(operations, candidate_doc)
+            or run_lua_check(lua_check, candidate_doc, snapshot_doc)
+        if check_error ~= nil then
+            redis.call('JSON.SET', key, '$', snapshot_json)
+            return encode_result(check_error)
+        end
+    end
+
+    -- Versioned entities keep the document as it was before this patch, newest first
+    if history ~= nil and snapshot_json ~= nil then
+        redis.call('LPUSH', history['key'], snapshot_json)
+        redis.call('LTRIM', history['key'], 0, history['keep'] - 1)
+    end
+
     for i = 1, #relations do
         local relation = relations[i]
         local relation_key = relation['relation_key']
@@ -326,30 +603,52 @@ local function main()
         local remove = relation['remove'] or {}
         local maintain_reverse = relation['maintain_reverse'] == true
 
+        -- Replacing the set removes the current members that are not being added
+        if relation['replace'] == true then
+            local keep = {}
+            for j = 1, #add do
+                keep[add[j]] = true
+            end
+            local current = redis.call('SMEMBERS', relation_key)
+            for j = 1, #current do
+                if not keep[current[j]] then
+                    table.insert(remove, current[j])
+                end
+            end
+        end
+
         local relation_parts
-        local rel_prefix
-        local rel_service
         local alias
         local left_id
         local reverse_alias
 
         if maintain_reverse then
-            -- Relation key structure: {prefix}:{service}:rel:{alias}:{left_id}
-            relation_parts = split_key(relation_key)
-            rel_prefix = relation_parts[1]
-            rel_service = relation_parts[2]
-            -- relation_parts[3] is "rel"
-            alias = relation_parts[4]
-            left_id = relation_parts[5]
+            -- Relation key structure: {head}:rel:{alias}:{left_id}
+            relation_parts = key_tail(LAYOUT, relation_key, 3)
+            -- relation_parts[1] is "rel"
+            alias = relation_parts[2]
+            left_id = relation_parts[3]
             reverse_alias = alias .. '_reverse'
         end
 
+        -- Edge payloads live in a hash alongside the relation set, keyed by member id
+        local edges = relation['edges'] or {}
+        if #edges > 0 then
+            local edges_key = relation_key .. SEP .. 'edges'
+            for j = 1, #edges do
+                redis.call('HSET', edges_key, edges[j]['member_id'], edges[j]['data_json'])
+            end
+        end
+        if #remove > 0 then
+            redis.call('HDEL', relation_key .. SEP .. 'edges', unpack(remove))
+        end
+
         if #add > 0 then
             redis.call('SADD', relation_key, unpack(add))
             if maintain_reverse then
                 for j = 1, #add do
                     local member_id = add[j]
-                    local reverse_key = table.concat({ rel_prefix, rel_service, 'rel', reverse_alias, member_id }, ':')
+                    local reverse_key = layout_key(LAYOUT, 'rel', reverse_alias, member_id)
                     redis.call('SADD', reverse_key, left_id)
                 end
             end
@@ -360,7 +659,7 @@ local function main()
             if maintain_reverse then
                 for j = 1, #remove do
                     local member_id = remove[j]
-                    local reverse_key = table.concat({ rel_prefix, rel_service, 'rel', reverse_alias, member_id }, ':')
+                    local reverse_key = layout_key(LAYOUT, 'rel', reverse_alias, member_id)
                     redis.call('SREM', reverse_key, left_id)
                     if redis.call('SCARD', reverse_key) == 0 then
                         redis.call('DEL', reverse_key)
@@ -368,6 +667,12 @@ local function main()
                 end
             end
         end
+
+        -- Keep the parent's denormalized counter in step with the relation set
+        local counter = relation['counter']
+        if counter ~= nil and redis.call('EXISTS', counter['entity_key']) == 1 then
+            redis.call('JSON.SET', counter['entity_key'], counter['path'], redis.call('SCARD', relation_key))
+        end
     end
 
     local next_version
@@ -381,7 +686,11 @@ local function main()
         end
     end
 
-    redis.call('JSON.SET', key, '$.metadata.version', next_version)
+    if is_hash then
+        redis.call('HSET', key, 'metadata.version', next_version)
+    else
+        redis.call('JSON.SET', key, '$.metadata.version', next_version)
+    end
     redis.call('PERSIST', key)
 
     -- Update unique indexes after successful patch
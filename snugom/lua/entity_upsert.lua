@@ -5,18 +5,11 @@
 -- Arguments:
 --  KEYS[1] - placeholder (unused; commands rely on explicit keys)
 --  ARGV[1] - JSON payload describing Upsert command
---  ARGV[2] - script version for optimistic upgrades (unused for now)
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the entity's keys, see keys.lua
 
 local cjson = cjson
 
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(parts, part)
-    end
-    return parts
-end
-
 local function normalize_version(value)
     if value == cjson.null then
         return nil
@@ -53,15 +46,17 @@ end
 -- Idempotency handling
 local IDEMPOTENCY_TTL_SECONDS = 900
 
+-- Replays return the stored response flagged with `replayed`, so callers can tell it from a fresh write.
+-- Stored responses are always non-empty JSON objects.
+local function mark_replay(stored)
+    return '{"replayed":true,' .. string.sub(stored, 2)
+end
+
 local function check_idempotency(key, idempotency_key)
     if not idempotency_key then
         return nil
     end
-    local parts = split_key(key)
-    if #parts < 3 then
-        return nil
-    end
-    local store_key = table.concat({ parts[1], parts[2], "idempotency", idempotency_key }, ":")
+    local store_key = layout_key(LAYOUT, "idempotency", idempotency_key)
     local existing = redis.call("GET", store_key)
     if existing then
         return existing
@@ -73,11 +68,7 @@ local function store_idempotency(key, idempotency_key, response, ttl)
     if not idempotency_key then
         return
     end
-    local parts = split_key(key)
-    if #parts < 3 then
-        return
-    end
-    local store_key = table.concat({ parts[1], parts[2], "idempotency", idempotency_key }, ":")
+    local store_key = layout_key(LAYOUT, "idempotency", idempotency_key)
     if ttl and ttl > 0 then
         redis.call("SET", store_key, response, "EX", ttl)
     else
@@ -85,6 +76,63 @@ local function store_idempotency(key, idempotency_key, response, ttl)
     end
 end
 
+-- Deep equality for decoded JSON values, used to match array elements on removal
+local function json_equal(a, b)
+    if type(a) ~= type(b) then
+        return false
+    end
+    if type(a) ~= "table" then
+        return a == b
+    end
+    for k, v in pairs(a) do
+        if not json_equal(v, b[k]) then
+            return false
+        end
+    end
+    for k in pairs(b) do
+        if a[k] == nil then
+            return false
+        end
+    end
+    return true
+end
+
+local function array_append(key, path, elements_json)
+    local kind = redis.call("JSON.TYPE", key, path)
+    if type(kind) == "table" then
+        kind = kind[1]
+    end
+    if kind == nil or kind == false or kind == "null" then
+        redis.call("JSON.SET", key, path, "[" .. table.concat(elements_json, ",") .. "]")
+    else
+        redis.call("JSON.ARRAPPEND", key, path, unpack(elements_json))
+    end
+end
+
+-- Remove every element equal to one of `elements_json`, highest index first so positions stay valid
+local function array_remove(key, path, elements_json)
+    local raw = redis.call("JSON.GET", key, path)
+    if not raw then
+        return
+    end
+    local current = cjson.decode(raw)[1]
+    if type(current) ~= "table" then
+        return
+    end
+    local targets = {}
+    for i, element in ipairs(elements_json) do
+        targets[i] = cjson.decode(element)
+    end
+    for index = #current, 1, -1 do
+        for _, target in ipairs(targets) do
+            if json_equal(current[index], target) then
+                redis.call("JSON.DEL", key, path .. "[" .. (index - 1) .. "]")
+                break
+            end
+        end
+    end
+end
+
 -- Apply a single patch operation
 local function apply_operation(key, op)
     local path = op["path"]
@@ -112,6 +160,16 @@ local function apply_operation(key, op)
             number_value = tonumber(number_value)
         end
         redis.call("JSON.NUMINCRBY", key, path, number_value)
+    elseif op_type == "array_append" or op_type == "array_remove" then
+        local elements_json = op["elements_json"]
+        if elements_json == nil then
+            return { error = "invalid_payload", message = "elements_json is required for " .. op_type }
+        end
+        if op_type == "array_append" then
+            array_append(key, path, elements_json)
+        else
+            array_remove(key, path, elements_json)
+        end
     else
         return { error = "unknown_operation", message = "unknown patch operation type: " .. tostring(op_type) }
     end
@@ -133,7 +191,7 @@ local function apply_mirror(key, mirror)
 end
 
 -- Apply relation mutations
-local function apply_relations(relations, prefix, service)
+local function apply_relations(relations)
     for i = 1, #relations do
         local relation = relations[i]
         local relation_key = relation["relation_key"]
@@ -141,28 +199,51 @@ local function apply_relations(relations, prefix, service)
         local remove = relation["remove"] or {}
         local maintain_reverse = relation["maintain_reverse"] == true
 
+        -- Replacing the set removes the current members that are not being added
+        if relation["replace"] == true then
+            local keep = {}
+            for j = 1, #add do
+                keep[add[j]] = true
+            end
+            local current = redis.call("SMEMBERS", relation_key)
+            for j = 1, #current do
+                if not keep[current[j]] then
+                    table.insert(remove, current[j])
+                end
+            end
+        end
+
         local relation_parts
-        local rel_prefix
-        local rel_service
         local alias
         local left_id
         local reverse_alias
 
         if maintain_reverse then
-            relation_parts = split_key(relation_key)
-            rel_prefix = relation_parts[1]
-            rel_service = relation_parts[2]
-            alias = relation_parts[4]
-            left_id = relation_parts[5]
+            -- Relation key structure: {head}:rel:{alias}:{left_id}
+            relation_parts = key_tail(LAYOUT, relation_key, 3)
+            alias = relation_parts[2]
+            left_id = relation_parts[3]
             reverse_alias = alias .. "_reverse"
         end
 
+        -- Edge payloads live in a hash alongside the relation set, keyed by member id
+        local edges = relation["edges"] or {}
+        if #edges > 0 then
+            local edges_key = relation_key .. SEP .. "edges"
+            for j = 1, #edges do
+                redis.call("HSET", edges_key, edges[j]["member_id"], edges[j]["data_json"])
+            end
+        end
+        if #remove > 0 then
+            redis.call("HDEL", relation_key .. SEP .. "edges", unpack(remove))
+        end
+
         if #add > 0 then
             redis.call("SADD", relation_key, unpack(add))
             if maintain_reverse then
                 for j = 1, #add do
                     local member_id = add[j]
-                    local reverse_key = table.concat({ rel_prefix, rel_service, "rel", reverse_alias, member_id }, ":")
+                    local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
                     redis.call("SADD", reverse_key, left_id)
                 end
             end
@@ -173,7 +254,7 @@ local function apply_relations(relations, prefix, service)
             if maintain_reverse then
                 for j = 1, #remove do
                     local member_id = remove[j]
-                    local reverse_key = table.concat({ rel_prefix, rel_service, "rel", reverse_alias, member_id }, ":")
+                    local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
                     redis.call("SREM", reverse_key, left_id)
                     if redis.call("SCARD", reverse_key) == 0 then
                         redis.call("DEL", reverse_key)
@@ -181,6 +262,12 @@ local function apply_relations(relations, prefix, service)
                 end
             end
         end
+
+        -- Keep the parent's denormalized counter in step with the relation set
+        local counter = relation["counter"]
+        if counter ~= nil and redis.call("EXISTS", counter["entity_key"]) == 1 then
+            redis.call("JSON.SET", counter["entity_key"], counter["path"], redis.call("SCARD", relation_key))
+        end
     end
 end
 
@@ -199,7 +286,7 @@ local function apply_datetime_mirrors(key, datetime_mirrors)
 end
 
 -- Check unique constraint and return violation if found
-local function check_unique_constraint(constraint, entity_id, prefix, service, collection)
+local function check_unique_constraint(constraint, entity_id, collection)
     local fields = constraint["fields"]
     local case_insensitive = constraint["case_insensitive"] == true
     local values = constraint["values"]
@@ -225,10 +312,10 @@ local function check_unique_constraint(constraint, entity_id, prefix, service, c
     local lookup_value = table.concat(lookup_parts, ":")
     local unique_key
     if #fields == 1 then
-        unique_key = table.concat({ prefix, service, collection, "unique", fields[1] }, ":")
+        unique_key = layout_key(LAYOUT, collection, "unique", fields[1])
     else
         local field_suffix = table.concat(fields, "_")
-        unique_key = table.concat({ prefix, service, collection, "unique_compound", field_suffix }, ":")
+        unique_key = layout_key(LAYOUT, collection, "unique_compound", field_suffix)
     end
 
     local existing_id = redis.call("HGET", unique_key, lookup_value)
@@ -266,14 +353,11 @@ local function main()
     -- Check idempotency first (use update key for consistency)
     local cached = check_idempotency(update_key, idempotency_key)
     if cached then
-        return cached
+        return mark_replay(cached)
     end
 
     -- Parse key structure from update_key
-    local key_parts = split_key(update_key)
-    local prefix = key_parts[1]
-    local service = key_parts[2]
-    local collection = key_parts[3]
+    local collection = key_tail(LAYOUT, update_key, 2)[1]
 
     -- Check if the entity to UPDATE exists
     local exists = redis.call("EXISTS", update_key) == 1
@@ -358,10 +442,10 @@ local function main()
                     if old_lookup_value ~= new_lookup_value then
                         local unique_key
                         if #fields == 1 then
-                            unique_key = table.concat({ prefix, service, collection, "unique", fields[1] }, ":")
+                            unique_key = layout_key(LAYOUT, collection, "unique", fields[1])
                         else
                             local field_suffix = table.concat(fields, "_")
-                            unique_key = table.concat({ prefix, service, collection, "unique_compound", field_suffix }, ":")
+                            unique_key = layout_key(LAYOUT, collection, "unique_compound", field_suffix)
                         end
 
                         local existing_id = redis.call("HGET", unique_key, new_lookup_value)
@@ -385,6 +469,14 @@ local function main()
             end
         end
 
+        local lua_check = upsert["lua_check"]
+        local history = upsert["history"]
+        local snapshot_json = nil
+        local snapshot_doc = nil
+        if (lua_check ~= nil and lua_check ~= cjson.null) or (history ~= nil and #update_operations > 0) then
+            snapshot_json, snapshot_doc = read_document(update_key)
+        end
+
         -- Apply operations
         for _, op in ipairs(update_operations) do
             local op_result = apply_operation(update_key, op)
@@ -397,8 +489,23 @@ local function main()
             end
         end
 
+        if snapshot_json ~= nil then
+            local _, candidate_doc = read_document(update_key)
+            local check_error = run_lua_check(lua_check, candidate_doc, snapshot_doc)
+            if check_error ~= nil then
+                redis.call("JSON.SET", update_key, "$", snapshot_json)
+                return encode_result(check_error)
+            end
+        end
+
+        -- Versioned entities keep the document as it was before this update, newest first
+        if history ~= nil and snapshot_json ~= nil then
+            redis.call("LPUSH", history["key"], snapshot_json)
+            redis.call("LTRIM", history["key"], 0, history["keep"] - 1)
+        end
+
         -- Apply relations
-        apply_relations(update_relations, prefix, service)
+        apply_relations(update_relations)
 
         -- Increment version
         local current_version = load_current_version(update_key)
@@ -439,18 +546,20 @@ local function main()
             return encode_result({ error = "invalid_payload", message = "create_payload_json is required" })
         end
 
-        -- Parse create key structure (may differ from update key)
-        local create_key_parts = split_key(create_key)
-        local create_prefix = create_key_parts[1]
-        local create_service = create_key_parts[2]
-        local create_collection = create_key_parts[3]
+        -- The create key may name another entity, but always of the same collection
+        local create_collection = key_tail(LAYOUT, create_key, 2)[1]
+
+        local check_error = run_lua_check(upsert["lua_check"], cjson.decode(create_payload_json), nil)
+        if check_error ~= nil then
+            return encode_result(check_error)
+        end
 
         -- Check unique constraints
         local unique_updates = {}
         for i = 1, #create_unique_constraints do
             local constraint = create_unique_constraints[i]
             local violation, unique_key, lookup_value = check_unique_constraint(
-                constraint, create_entity_id, create_prefix, create_service, create_collection
+                constraint, create_entity_id, create_collection
             )
             if violation then
                 return encode_result(violation)
@@ -481,7 +590,7 @@ local function main()
         apply_datetime_mirrors(create_key, datetime_mirrors)
 
         -- Apply relations
-        apply_relations(create_relations, create_prefix, create_service)
+        apply_relations(create_relations)
 
         response = encode_result({
             ok = true,
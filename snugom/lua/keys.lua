@@ -0,0 +1,30 @@
+-- Key helpers shared by every mutation script, prepended by runtime/scripts.rs.
+-- Arguments:
+--  ARGV[3] - separator between key segments, from the command's KeyLayout
+--  ARGV[4] - head of the command's keys: the prefix and service in the KeyScheme's order, joined
+
+-- A layout is `{ separator = ..., head = ... }`, as KeyLayout serializes; LAYOUT is the command's own
+local LAYOUT = { separator = ARGV[3] or ":", head = ARGV[4] }
+local SEP = LAYOUT.separator
+
+-- The layout's head followed by the given segments
+local function layout_key(layout, ...)
+    return table.concat({ layout.head, ... }, layout.separator)
+end
+
+-- Up to `count` segments of `key` after the layout's head, e.g. { collection, entity_id } of an
+-- entity key; the last keeps any separators left in it, so ids containing one survive
+local function key_tail(layout, key, count)
+    local rest = string.sub(key, #layout.head + #layout.separator + 1)
+    local parts = {}
+    while #parts < count - 1 do
+        local at = string.find(rest, layout.separator, 1, true)
+        if at == nil then
+            break
+        end
+        table.insert(parts, string.sub(rest, 1, at - 1))
+        rest = string.sub(rest, at + #layout.separator)
+    end
+    table.insert(parts, rest)
+    return parts
+end
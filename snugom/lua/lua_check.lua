@@ -0,0 +1,44 @@
+-- Helpers shared by the scripts that evaluate an entity's `lua_check` snippet.
+-- Prepended to entity_mutation, entity_patch, entity_upsert and entity_get_or_create
+-- (see runtime/scripts.rs), so everything here is in scope for those scripts.
+
+-- Runs the entity's `lua_check` snippet against the candidate document.
+-- The snippet receives `(doc, previous)` and returns nothing/true to accept,
+-- false or a message string to reject, or `{ field = ..., message = ... }`.
+local function run_lua_check(source, doc, previous)
+    if source == nil or source == cjson.null then
+        return nil
+    end
+    local chunk, compile_err = loadstring(source, "=lua_check")
+    if not chunk then
+        return { error = "lua_check_failed", field = "__entity", message = "lua_check failed to compile: " .. tostring(compile_err) }
+    end
+    local ok, result = pcall(chunk, doc, previous)
+    if not ok then
+        return { error = "lua_check_failed", field = "__entity", message = tostring(result) }
+    end
+    if result == nil or result == true then
+        return nil
+    end
+    if type(result) == "table" then
+        return {
+            error = "lua_check_failed",
+            field = result["field"] or "__entity",
+            message = result["message"] or "rejected by lua_check",
+        }
+    end
+    if type(result) == "string" then
+        return { error = "lua_check_failed", field = "__entity", message = result }
+    end
+    return { error = "lua_check_failed", field = "__entity", message = "rejected by lua_check" }
+end
+
+-- Reads a document without the JSONPath array wrapper, returning both the raw
+-- JSON (for byte-exact restore) and the decoded table.
+local function read_document(key)
+    local raw = redis.call("JSON.GET", key, ".")
+    if not raw or raw == false then
+        return nil, nil
+    end
+    return raw, cjson.decode(raw)
+end
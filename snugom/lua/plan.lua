@@ -0,0 +1,91 @@
+-- SnugOM plan script: runs every command of a MutationPlan as one unit
+-- Arguments:
+--  ARGV[1] - JSON array with one `{ operation, payload, sha, separator, head }` step per command
+-- runtime/scripts.rs appends each mutation script after this file as `STEPS[operation]`, a
+-- function of the ARGV that script takes on its own, with its `redis.call`s routed through
+-- `journaled_call`. When a step fails, every key written by the steps before it is put back, so
+-- the plan either applies whole or not at all.
+-- Replies with the steps' replies in order, or with the reply of the step that failed.
+
+local STEPS = {}
+
+-- Commands the scripts use that write nothing; any other command writes its first key
+local READS = {
+    ["EXISTS"] = true,
+    ["GET"] = true,
+    ["HEXISTS"] = true,
+    ["HGET"] = true,
+    ["HGETALL"] = true,
+    ["JSON.GET"] = true,
+    ["JSON.TYPE"] = true,
+    ["PTTL"] = true,
+    ["SCARD"] = true,
+    ["SISMEMBER"] = true,
+    ["SMEMBERS"] = true,
+    ["TTL"] = true,
+    ["TYPE"] = true,
+}
+
+-- Commands that write every key they are given
+local MULTI_KEY_WRITES = { ["DEL"] = true, ["UNLINK"] = true }
+
+-- Each key as it was before the plan first wrote it, in the order they were written
+local journal = {}
+local journaled = {}
+
+local function remember(key)
+    if journaled[key] then
+        return
+    end
+    journaled[key] = true
+    table.insert(journal, { key = key, dump = redis.call("DUMP", key), ttl = redis.call("PTTL", key) })
+end
+
+local function journaled_call(command, ...)
+    local name = string.upper(command)
+    if MULTI_KEY_WRITES[name] then
+        for i = 1, select("#", ...) do
+            remember((select(i, ...)))
+        end
+    elseif not READS[name] then
+        remember((select(1, ...)))
+    end
+    return redis.call(command, ...)
+end
+
+-- Put every key the plan wrote back as it was, removing the ones it created
+local function roll_back()
+    for i = #journal, 1, -1 do
+        local entry = journal[i]
+        redis.call("DEL", entry.key)
+        if entry.dump then
+            local ttl = entry.ttl
+            if ttl < 0 then
+                ttl = 0
+            end
+            redis.call("RESTORE", entry.key, ttl, entry.dump)
+        end
+    end
+end
+
+local function run_plan(steps)
+    local replies = {}
+    for i = 1, #steps do
+        local step = steps[i]
+        local ok, reply = pcall(STEPS[step["operation"]], { step["payload"], step["sha"], step["separator"], step["head"] })
+        if not ok then
+            roll_back()
+            -- Errors raised by redis.call arrive as `{ err = ... }`
+            if type(reply) == "table" then
+                reply = reply["err"]
+            end
+            return redis.error_reply(tostring(reply))
+        end
+        if cjson.decode(reply)["error"] ~= nil then
+            roll_back()
+            return reply
+        end
+        table.insert(replies, reply)
+    end
+    return replies
+end
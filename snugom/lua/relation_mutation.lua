@@ -1,14 +1,8 @@
 -- SnugOM relation mutation script
 -- Arguments:
 --  ARGV[1] - JSON payload describing MutationCommand::MutateRelations
-
-local function split_key(key)
-    local parts = {}
-    for part in string.gmatch(key, "([^:]+)") do
-        table.insert(parts, part)
-    end
-    return parts
-end
+--  ARGV[2] - SHA1 of this script, as a version for optimistic upgrades (unused for now)
+--  ARGV[3], ARGV[4] - layout of the relation's keys, see keys.lua
 
 local payload = cjson.decode(ARGV[1])
 local mutation = payload["mutate_relations"]
@@ -21,30 +15,52 @@ local add = mutation["add"] or {}
 local remove = mutation["remove"] or {}
 local maintain_reverse = mutation["maintain_reverse"] == true
 
+-- Replacing the set removes the current members that are not being added
+if mutation["replace"] == true then
+    local keep = {}
+    for i = 1, #add do
+        keep[add[i]] = true
+    end
+    local current = redis.call("SMEMBERS", relation_key)
+    for i = 1, #current do
+        if not keep[current[i]] then
+            table.insert(remove, current[i])
+        end
+    end
+end
+
 local relation_parts
-local prefix
-local service
 local alias
 local left_id
 local reverse_alias
 
 if maintain_reverse then
-    -- Relation key structure: {prefix}:{service}:rel:{alias}:{left_id}
-    relation_parts = split_key(relation_key)
-    prefix = relation_parts[1]
-    service = relation_parts[2]
-    -- relation_parts[3] is "rel"
-    alias = relation_parts[4]
-    left_id = relation_parts[5]
+    -- Relation key structure: {head}:rel:{alias}:{left_id}
+    relation_parts = key_tail(LAYOUT, relation_key, 3)
+    -- relation_parts[1] is "rel"
+    alias = relation_parts[2]
+    left_id = relation_parts[3]
     reverse_alias = alias .. "_reverse"
 end
 
+-- Edge payloads live in a hash alongside the relation set, keyed by member id
+local edges = mutation["edges"] or {}
+if #edges > 0 then
+    local edges_key = relation_key .. SEP .. "edges"
+    for j = 1, #edges do
+        redis.call("HSET", edges_key, edges[j]["member_id"], edges[j]["data_json"])
+    end
+end
+if #remove > 0 then
+    redis.call("HDEL", relation_key .. SEP .. "edges", unpack(remove))
+end
+
 if #add > 0 then
     redis.call("SADD", relation_key, unpack(add))
     if maintain_reverse then
         for i = 1, #add do
             local member_id = add[i]
-            local reverse_key = table.concat({ prefix, service, "rel", reverse_alias, member_id }, ":")
+            local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
             redis.call("SADD", reverse_key, left_id)
         end
     end
@@ -55,7 +71,7 @@ if #remove > 0 then
     if maintain_reverse then
         for i = 1, #remove do
             local member_id = remove[i]
-            local reverse_key = table.concat({ prefix, service, "rel", reverse_alias, member_id }, ":")
+            local reverse_key = layout_key(LAYOUT, "rel", reverse_alias, member_id)
             redis.call("SREM", reverse_key, left_id)
             if redis.call("SCARD", reverse_key) == 0 then
                 redis.call("DEL", reverse_key)
@@ -64,4 +80,10 @@ if #remove > 0 then
     end
 end
 
+-- Keep the parent's denormalized counter in step with the relation set
+local counter = mutation["counter"]
+if counter ~= nil and redis.call("EXISTS", counter["entity_key"]) == 1 then
+    redis.call("JSON.SET", counter["entity_key"], counter["path"], redis.call("SCARD", relation_key))
+end
+
 return cjson.encode({ ok = true })
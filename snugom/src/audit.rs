@@ -0,0 +1,229 @@
+//! Opt-in audit trail of the writes made through a repository.
+//!
+//! Each entity gets its own Redis stream (see [`KeyContext::audit`](crate::keys::KeyContext::audit)),
+//! appended to after every create, update, and delete with the action, the acting principal, and
+//! the fields an update touched. Stream ids carry the time, so entries are listed by time range.
+
+use std::{borrow::Cow, ops::Bound, ops::RangeBounds};
+
+use chrono::{DateTime, Utc};
+use redis::{Value, aio::ConnectionManager, cmd, from_redis_value};
+
+use crate::errors::RepoError;
+
+/// Entries kept per entity unless [`AuditSettings::max_len`] says otherwise; older ones are
+/// trimmed approximately, as `XADD MAXLEN ~` allows.
+pub const DEFAULT_AUDIT_MAX_LEN: usize = 1_000;
+
+/// What an audited write did to the entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create" => Some(AuditAction::Create),
+            "update" => Some(AuditAction::Update),
+            "delete" => Some(AuditAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Turns auditing on for a repository, naming who its writes are made on behalf of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSettings {
+    /// Recorded with every entry, e.g. `user:123`; `None` for unattributed writes
+    pub actor: Option<String>,
+    /// Entries kept per entity
+    pub max_len: usize,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            actor: None,
+            max_len: DEFAULT_AUDIT_MAX_LEN,
+        }
+    }
+}
+
+impl AuditSettings {
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+}
+
+/// One recorded write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Stream id of the entry, usable as a cursor
+    pub id: String,
+    pub at: DateTime<Utc>,
+    pub action: AuditAction,
+    pub actor: Option<String>,
+    /// Top-level fields and relation aliases an update touched; empty for creates and deletes
+    pub fields: Vec<String>,
+}
+
+/// The audit trail of one entity, from [`Client::audit`](crate::Client::audit).
+pub struct AuditLog {
+    key: String,
+    conn: ConnectionManager,
+}
+
+impl AuditLog {
+    pub(crate) fn new(key: String, conn: ConnectionManager) -> Self {
+        Self { key, conn }
+    }
+
+    /// The stream key holding the entries.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Entries recorded within `range`, oldest first; `..` lists them all.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let last_day = client.audit::<Guild>(&id).list(Utc::now() - Duration::days(1)..).await?;
+    /// ```
+    pub async fn list(&mut self, range: impl RangeBounds<DateTime<Utc>>) -> Result<Vec<AuditEntry>, RepoError> {
+        let (start, end) = stream_bounds(&range);
+        let raw: Value = cmd("XRANGE").arg(&self.key).arg(start).arg(end).query_async(&mut self.conn).await?;
+        parse_entries(raw)
+    }
+}
+
+/// Append an entry for `action` to the stream at `key`.
+pub(crate) async fn record(
+    conn: &mut ConnectionManager,
+    key: &str,
+    settings: &AuditSettings,
+    action: AuditAction,
+    fields: &[String],
+) -> Result<(), RepoError> {
+    let mut command = cmd("XADD");
+    command.arg(key).arg("MAXLEN").arg("~").arg(settings.max_len).arg("*");
+    command.arg("action").arg(action.as_str());
+    if let Some(actor) = &settings.actor {
+        command.arg("actor").arg(actor);
+    }
+    if !fields.is_empty() {
+        command.arg("fields").arg(fields.join(","));
+    }
+    command.query_async::<Value>(conn).await?;
+    Ok(())
+}
+
+/// `XRANGE` start and end ids covering `range`; the millisecond part of a stream id is its time.
+fn stream_bounds(range: &impl RangeBounds<DateTime<Utc>>) -> (String, String) {
+    let start = match range.start_bound() {
+        Bound::Included(at) => at.timestamp_millis().to_string(),
+        Bound::Excluded(at) => (at.timestamp_millis() + 1).to_string(),
+        Bound::Unbounded => "-".to_string(),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(at) => at.timestamp_millis().to_string(),
+        Bound::Excluded(at) => format!("({}-0", at.timestamp_millis()),
+        Bound::Unbounded => "+".to_string(),
+    };
+    (start, end)
+}
+
+fn parse_entries(raw: Value) -> Result<Vec<AuditEntry>, RepoError> {
+    let Value::Array(entries) = raw else {
+        return Err(RepoError::Other {
+            message: Cow::Owned(format!("Unexpected XRANGE response: {raw:?}")),
+        });
+    };
+    Ok(entries.into_iter().filter_map(parse_entry).collect())
+}
+
+/// An `[id, [field, value, ...]]` stream entry; fields arrive as a map under RESP3.
+fn parse_entry(entry: Value) -> Option<AuditEntry> {
+    let Value::Array(parts) = entry else {
+        return None;
+    };
+    let mut parts = parts.into_iter();
+    let id: String = from_redis_value(&parts.next()?).ok()?;
+    let pairs: Vec<(Value, Value)> = match parts.next()? {
+        Value::Array(values) => {
+            let mut values = values.into_iter();
+            std::iter::from_fn(|| Some((values.next()?, values.next()?))).collect()
+        }
+        Value::Map(pairs) => pairs,
+        _ => return None,
+    };
+    let field = |name: &str| {
+        pairs
+            .iter()
+            .find(|(key, _)| from_redis_value::<String>(key).is_ok_and(|key| key == name))
+            .and_then(|(_, value)| from_redis_value::<String>(value).ok())
+    };
+    let millis = id.split('-').next()?.parse().ok()?;
+    Some(AuditEntry {
+        at: DateTime::from_timestamp_millis(millis)?,
+        action: AuditAction::parse(&field("action")?)?,
+        actor: field("actor"),
+        fields: field("fields")
+            .map(|fields| fields.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+        id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(text: &str) -> Value {
+        Value::BulkString(text.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn time_ranges_map_to_stream_ids() {
+        let at = DateTime::from_timestamp_millis(1_704_067_200_000).unwrap();
+        assert_eq!(stream_bounds(&(..)), ("-".to_string(), "+".to_string()));
+        assert_eq!(stream_bounds(&(at..)), ("1704067200000".to_string(), "+".to_string()));
+        assert_eq!(stream_bounds(&(..=at)), ("-".to_string(), "1704067200000".to_string()));
+        assert_eq!(stream_bounds(&(..at)), ("-".to_string(), "(1704067200000-0".to_string()));
+    }
+
+    #[test]
+    fn parses_stream_entries() {
+        let raw = Value::Array(vec![
+            Value::Array(vec![
+                bulk("1704067200000-0"),
+                Value::Array(vec![bulk("action"), bulk("create"), bulk("actor"), bulk("user:123")]),
+            ]),
+            Value::Array(vec![
+                bulk("1704067260000-1"),
+                Value::Map(vec![(bulk("action"), bulk("update")), (bulk("fields"), bulk("name,members"))]),
+            ]),
+            Value::Array(vec![bulk("1704067270000-0"), Value::Array(vec![bulk("action"), bulk("rename")])]),
+        ]);
+        let entries = parse_entries(raw).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, AuditAction::Create);
+        assert_eq!(entries[0].actor.as_deref(), Some("user:123"));
+        assert_eq!(entries[0].at.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert!(entries[0].fields.is_empty());
+        assert_eq!(entries[1].id, "1704067260000-1");
+        assert_eq!(entries[1].actor, None);
+        assert_eq!(entries[1].fields, ["name", "members"]);
+    }
+}
@@ -0,0 +1,192 @@
+//! axum integration, enabled by the `axum` feature.
+//!
+//! - [`SearchQueryParams`] extracts a [`SearchQuery`] from the query string, collecting
+//!   repeated `filter=` parameters in order.
+//! - [`RepoError`] and [`PaginatedResponse`] implement `IntoResponse`, so handlers can return
+//!   `Result<PaginatedResponse<T>, RepoError>` directly. Errors respond with the
+//!   `application/problem+json` body described in [`crate::http`].
+//!
+//! # Example
+//! ```ignore
+//! async fn list_guilds(
+//!     State(client): State<Snugom>,
+//!     SearchQueryParams(query): SearchQueryParams,
+//! ) -> Result<PaginatedResponse<Guild>, RepoError> {
+//!     Ok(client.guilds().find_many(query).await?.into())
+//! }
+//! ```
+
+use ::axum::{
+    Json,
+    body::Body,
+    extract::FromRequestParts,
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    errors::RepoError,
+    search::{PaginatedResponse, SearchQuery, SortOrder},
+};
+
+/// Query-string extractor for [`SearchQuery`].
+///
+/// Accepts `page`, `page_size`, `sort_by`, `sort_order` (`asc`/`desc`), `q`, and any number of
+/// `filter` (or `filter[]`) parameters, e.g. `?filter=status:eq:active&filter=count:range:1,`.
+#[derive(Debug, Clone)]
+pub struct SearchQueryParams(pub SearchQuery);
+
+/// Rejection returned when the query string cannot be parsed; responds with 400.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQueryRejection {
+    pub parameter: String,
+    pub message: String,
+}
+
+impl IntoResponse for SearchQueryRejection {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": "invalid_query",
+            "parameter": self.parameter,
+            "message": self.message,
+        });
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+    }
+}
+
+impl SearchQueryParams {
+    /// Parse a raw (percent-encoded) query string.
+    pub fn parse(query: &str) -> Result<Self, SearchQueryRejection> {
+        let mut parsed = SearchQuery::default();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "page" => parsed.page = Some(parse_number(&key, &value)?),
+                "page_size" => parsed.page_size = Some(parse_number(&key, &value)?),
+                "sort_by" => parsed.sort_by = Some(value),
+                "sort_order" => {
+                    parsed.sort_order = Some(match value.to_ascii_lowercase().as_str() {
+                        "asc" => SortOrder::Asc,
+                        "desc" => SortOrder::Desc,
+                        _ => return Err(rejection(&key, format!("expected `asc` or `desc`, got `{value}`"))),
+                    })
+                }
+                "q" => parsed.q = Some(value),
+                "filter" | "filter[]" if !value.is_empty() => parsed.filter.push(value),
+                _ => {}
+            }
+        }
+        Ok(Self(parsed))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for SearchQueryParams {
+    type Rejection = SearchQueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::parse(parts.uri.query().unwrap_or_default())
+    }
+}
+
+fn rejection(parameter: &str, message: impl Into<String>) -> SearchQueryRejection {
+    SearchQueryRejection {
+        parameter: parameter.to_string(),
+        message: message.into(),
+    }
+}
+
+fn parse_number(parameter: &str, value: &str) -> Result<u64, SearchQueryRejection> {
+    value
+        .parse()
+        .map_err(|_| rejection(parameter, format!("expected a non-negative integer, got `{value}`")))
+}
+
+impl IntoResponse for RepoError {
+    fn into_response(self) -> Response {
+        self.to_http_response().map(Body::from)
+    }
+}
+
+impl<T: Serialize> IntoResponse for PaginatedResponse<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::axum::{body::to_bytes, http::Request};
+
+    use super::*;
+    use crate::errors::ValidationError;
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.expect("body");
+        serde_json::from_slice(&bytes).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn extractor_collects_repeated_filters() {
+        let request = Request::builder()
+            .uri("/guilds?page=2&page_size=10&sort_by=name&sort_order=DESC&q=dragon%20slayers&filter=status:eq:active&filter%5B%5D=members:range:5,")
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        let SearchQueryParams(query) = SearchQueryParams::from_request_parts(&mut parts, &()).await.unwrap();
+        assert_eq!(query.page, Some(2));
+        assert_eq!(query.page_size, Some(10));
+        assert_eq!(query.sort_by.as_deref(), Some("name"));
+        assert_eq!(query.sort_order, Some(SortOrder::Desc));
+        assert_eq!(query.q.as_deref(), Some("dragon slayers"));
+        assert_eq!(query.filter, vec!["status:eq:active", "members:range:5,"]);
+    }
+
+    #[tokio::test]
+    async fn extractor_rejects_malformed_numbers() {
+        let rejection = SearchQueryParams::parse("page=first").unwrap_err();
+        assert_eq!(rejection.parameter, "page");
+        let response = rejection.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["error"], "invalid_query");
+    }
+
+    #[tokio::test]
+    async fn repo_errors_map_to_http_statuses() {
+        let validation = RepoError::Validation(ValidationError::single("name", "validation.required", "field is required"));
+        let response = validation.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()["content-type"], "application/problem+json");
+        let body = body_json(response).await;
+        assert_eq!(body["issues"][0]["field"], "name");
+
+        let not_found = RepoError::NotFound { entity_id: Some("g1".to_string()) };
+        assert_eq!(not_found.into_response().status(), StatusCode::NOT_FOUND);
+
+        let conflict = RepoError::VersionConflict { expected: Some(2), actual: Some(3) };
+        let response = conflict.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(response.headers().contains_key("retry-after"));
+
+        let internal = RepoError::Other { message: "redis script exploded".into() };
+        let body = body_json(internal.into_response()).await;
+        assert_eq!(body["detail"], "internal server error");
+    }
+
+    #[tokio::test]
+    async fn paginated_response_serializes_as_json() {
+        let page = PaginatedResponse {
+            items: vec!["a", "b"],
+            total: 3,
+            page: 1,
+            page_size: 2,
+            has_more: true,
+        };
+        let response = page.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["items"], json!(["a", "b"]));
+        assert_eq!(body["has_more"], true);
+    }
+}
@@ -0,0 +1,109 @@
+//! Representative inputs for benchmarking search and repository hot paths.
+//!
+//! The `benches/` suite drives these helpers, and downstream crates can reuse them to compare
+//! their own entities against a known baseline. Nothing here talks to Redis.
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    SnugomEntity,
+    errors::RepoError,
+    repository::{MutationPayloadBuilder, Repo},
+    runtime::MutationPlan,
+    search::{FilterCondition, SearchParams, SearchSort, SortOrder},
+};
+
+/// Order-shaped entity with text, tag, numeric, validated, and datetime fields.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "bench", collection = "orders")]
+pub struct BenchOrder {
+    #[snugom(id)]
+    pub id: String,
+    #[snugom(created_at)]
+    pub created_at: chrono::DateTime<Utc>,
+    #[snugom(validate(length(min = 3, max = 120)), filterable(text), sortable)]
+    pub title: String,
+    #[snugom(validate(email), filterable(tag))]
+    pub customer_email: String,
+    #[snugom(filterable(tag))]
+    pub status: String,
+    #[snugom(validate(range(min = 0)), filterable, sortable)]
+    pub total_cents: i64,
+    #[snugom(filterable)]
+    pub gift: bool,
+    #[snugom(validate(length(max = 16)), filterable(tag))]
+    pub tags: Vec<String>,
+    #[snugom(datetime, filterable, sortable)]
+    pub shipped_at: Option<chrono::DateTime<Utc>>,
+}
+
+/// Values that exercise every escaping branch: punctuation, whitespace, unicode, and plain text.
+pub const ESCAPE_INPUTS: &[&str] = &[
+    "plain",
+    "user@example.com",
+    "multi word phrase",
+    "path/to:thing-1.2",
+    "{braces} [brackets] (parens) |pipes|",
+    "naïve café ünïcode",
+    "$price > 100 && ~fuzzy* -negated",
+];
+
+/// A search with nested boolean groups, ranges, prefix/contains text, and a raw clause.
+pub fn search_params() -> SearchParams {
+    SearchParams::new()
+        .with_condition(FilterCondition::or([
+            FilterCondition::tag_eq("status", "paid"),
+            FilterCondition::tag_eq("status", "shipped"),
+        ]))
+        .with_condition(FilterCondition::and([
+            FilterCondition::numeric_range("total_cents", Some(1_000.0), Some(50_000.0)),
+            FilterCondition::tag_eq("customer_email", "user@example.com"),
+        ]))
+        .with_condition(FilterCondition::text_prefix("title", "wire"))
+        .with_condition(FilterCondition::text_contains("title", "less head"))
+        .with_condition(FilterCondition::bool_eq("gift", false))
+        .with_text_query("wireless headphones")
+        .with_sort(Some(SearchSort {
+            field: "total_cents".to_string(),
+            order: SortOrder::Desc,
+        }))
+        .with_page(2, 50)
+}
+
+/// A fully populated, valid builder for [`BenchOrder`]; `n` varies the field values.
+pub fn order_builder(n: usize) -> BenchOrderValidationBuilder {
+    BenchOrder::validation_builder()
+        .id(format!("order-{n}"))
+        .title(format!("Wireless headphones #{n}"))
+        .customer_email(format!("customer{n}@example.com"))
+        .status("paid".to_string())
+        .total_cents(4_999 + n as i64)
+        .gift(n.is_multiple_of(2))
+        .tags(vec!["audio".to_string(), "gift".to_string(), format!("batch-{}", n % 10)])
+        .shipped_at(Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()))
+}
+
+/// Run builder validation and payload construction for one order.
+pub fn validate_payload(n: usize) -> Result<(), RepoError> {
+    order_builder(n).into_payload()?;
+    Ok(())
+}
+
+/// Build the create plan for one order, as [`Repo::create`] would before executing it.
+pub fn build_create_plan(repo: &Repo<BenchOrder>, n: usize) -> Result<MutationPlan, RepoError> {
+    repo.plan_create(order_builder(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helpers_produce_valid_inputs() {
+        assert!(!search_params().build_query("").is_empty());
+        validate_payload(1).expect("valid payload");
+        let repo = Repo::<BenchOrder>::new("bench");
+        build_create_plan(&repo, 1).expect("create plan");
+    }
+}
@@ -0,0 +1,1060 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use snugom::keys::KeyContext;
+use snugom::repository::plan_document_create;
+use snugom::runtime::{MutationCommand, execute_pipelined};
+use snugom::search::{FilterDescriptor, IndexInfo, SEARCH_DIALECT, SearchParams, drop_index, ensure_index, index_info};
+use snugom::{PrefixCopyOptions, RepoError, copy_prefix_with, verify_prefix_copy};
+use snugom::types::{EntityDescriptor, StorageMode};
+
+use crate::commands::schema::scan_project;
+use crate::context::ProjectContext;
+use crate::examples::ExampleGroup;
+use crate::output::OutputManager;
+use crate::scanner::{discover_entities, entity_descriptor, index_definition, index_name, parse_entity_file};
+
+pub const EXAMPLES: &[ExampleGroup] = &[
+    ExampleGroup {
+        title: "Export",
+        commands: &[
+            "snugom data export --prefix app --service guild --collection guilds > guilds.jsonl",
+            "snugom data export --prefix app --service guild --collection guilds --format csv --out guilds.csv",
+            "snugom data export --prefix app --service guild --collection guilds --filter status:eq:active",
+        ],
+    },
+    ExampleGroup {
+        title: "Import",
+        commands: &[
+            "snugom data import guilds.jsonl --prefix app --service guild --collection guilds",
+            "snugom data import guilds.jsonl --prefix app --service guild --collection guilds --upsert",
+            "snugom data import guilds.jsonl --prefix app --service guild --collection guilds --skip-existing",
+        ],
+    },
+    ExampleGroup {
+        title: "Snapshot",
+        commands: &[
+            "snugom data snapshot --prefix app --service guild --out guild.snap",
+            "snugom data restore guild.snap --prefix staging",
+            "snugom data restore guild.snap --prefix app --replace",
+        ],
+    },
+    ExampleGroup {
+        title: "Copy",
+        commands: &[
+            "snugom data copy --from staging --to perf",
+            "snugom data copy --from perf --to perf-archive --move",
+        ],
+    },
+];
+
+#[derive(Subcommand)]
+pub enum DataCommands {
+    /// Export a collection's documents as JSON Lines or CSV
+    #[command(name = "export")]
+    Export(ExportArgs),
+
+    /// Import JSON Lines documents through the entity's validation and mutation path
+    #[command(name = "import")]
+    Import(ImportArgs),
+
+    /// Copy every key of a service to a file, byte for byte
+    #[command(name = "snapshot")]
+    Snapshot(SnapshotArgs),
+
+    /// Write a snapshot's keys back, under the same or another prefix
+    #[command(name = "restore")]
+    Restore(RestoreArgs),
+
+    /// Copy or move every key from one prefix to another and create its indexes
+    #[command(name = "copy")]
+    Copy(CopyArgs),
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Key prefix the application's client uses
+    #[arg(long, env = "SNUGOM_PREFIX")]
+    pub prefix: String,
+
+    /// Service the collection belongs to
+    #[arg(long)]
+    pub service: String,
+
+    /// Collection to export
+    #[arg(long)]
+    pub collection: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "jsonl")]
+    pub format: ExportFormat,
+
+    /// Only export documents matching field:operator:value (repeatable, requires the search index)
+    #[arg(long = "filter")]
+    pub filters: Vec<String>,
+
+    /// CSV columns, comma-separated (defaults to the fields of the first batch)
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// File to write to (writes to stdout if omitted)
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Documents fetched per round trip
+    #[arg(long, default_value_t = 500)]
+    pub batch_size: usize,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// JSON Lines file with one document per line
+    pub file: PathBuf,
+
+    /// Key prefix the application's client uses
+    #[arg(long, env = "SNUGOM_PREFIX")]
+    pub prefix: String,
+
+    /// Service the collection belongs to
+    #[arg(long)]
+    pub service: String,
+
+    /// Collection to import into; must belong to an entity in this project
+    #[arg(long)]
+    pub collection: String,
+
+    /// Overwrite documents whose id already exists
+    #[arg(long, conflicts_with = "skip_existing")]
+    pub upsert: bool,
+
+    /// Leave documents whose id already exists untouched
+    #[arg(long)]
+    pub skip_existing: bool,
+
+    /// Documents written per round trip
+    #[arg(long, default_value_t = 500)]
+    pub batch_size: usize,
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// Key prefix the application's client uses
+    #[arg(long, env = "SNUGOM_PREFIX")]
+    pub prefix: String,
+
+    /// Service to snapshot
+    #[arg(long)]
+    pub service: String,
+
+    /// Snapshot file to write
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Keys fetched per round trip
+    #[arg(long, default_value_t = 500)]
+    pub batch_size: usize,
+}
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// Snapshot file written by `snugom data snapshot`
+    pub file: PathBuf,
+
+    /// Key prefix to restore under; may differ from the snapshot's to clone an environment
+    #[arg(long, env = "SNUGOM_PREFIX")]
+    pub prefix: String,
+
+    /// Service to restore under (defaults to the snapshot's)
+    #[arg(long)]
+    pub service: Option<String>,
+
+    /// Overwrite keys that already exist instead of refusing to restore
+    #[arg(long)]
+    pub replace: bool,
+
+    /// Keys written per round trip
+    #[arg(long, default_value_t = 500)]
+    pub batch_size: usize,
+}
+
+#[derive(Args)]
+pub struct CopyArgs {
+    /// Prefix to copy keys from
+    #[arg(long)]
+    pub from: String,
+
+    /// Prefix to copy keys to
+    #[arg(long)]
+    pub to: String,
+
+    /// Delete the source keys and drop their indexes once copied
+    #[arg(long = "move")]
+    pub move_keys: bool,
+
+    /// Overwrite keys that already exist under the target prefix
+    #[arg(long)]
+    pub replace: bool,
+
+    /// Keys copied per round trip
+    #[arg(long, default_value_t = 500)]
+    pub batch_size: usize,
+}
+
+/// Export formats
+#[derive(Clone, Copy, Debug, ValueEnum, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON document per line
+    #[default]
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+pub async fn handle_data_commands(command: DataCommands, output: &OutputManager) -> Result<()> {
+    match command {
+        DataCommands::Export(args) => handle_export(args, output).await,
+        DataCommands::Import(args) => handle_import(args, output).await,
+        DataCommands::Snapshot(args) => handle_snapshot(args, output).await,
+        DataCommands::Restore(args) => handle_restore(args, output).await,
+        DataCommands::Copy(args) => handle_copy(args, output).await,
+    }
+}
+
+async fn handle_export(args: ExportArgs, output: &OutputManager) -> Result<()> {
+    // Writing to stdout leaves it to the documents so the export can be piped
+    let to_file = args.out.is_some();
+    if to_file {
+        output.heading(&format!("Export: {}:{}", args.service, args.collection));
+    }
+
+    let mut conn = connect(output, to_file).await?;
+
+    let keys = KeyContext::new(&args.prefix, &args.service);
+    let collection_prefix = keys.collection_prefix(&args.collection);
+    let index_name = project_index_name(&args.prefix, &args.service, &args.collection);
+    let index = if args.filters.is_empty() {
+        index_info(&mut conn, &index_name).await.ok()
+    } else {
+        Some(
+            index_info(&mut conn, &index_name)
+                .await
+                .with_context(|| format!("Filtering needs the search index {index_name}"))?,
+        )
+    };
+    let storage = index.as_ref().and_then(|index| index.storage).unwrap_or_default();
+
+    let mut source = match &index {
+        Some(index) if !args.filters.is_empty() => KeySource::Search {
+            query: build_filter_query(index, &args.filters)?,
+            index: index_name.clone(),
+            offset: 0,
+            done: false,
+        },
+        _ => KeySource::Scan {
+            pattern: format!("{collection_prefix}*"),
+            collection_prefix,
+            cursor: 0,
+            done: false,
+        },
+    };
+
+    let writer: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(File::create(path).with_context(|| format!("Failed to create {}", path.display()))?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+    let mut encoder = match args.format {
+        ExportFormat::Jsonl => Encoder::Jsonl,
+        ExportFormat::Csv => Encoder::Csv(CsvEncoder::new(args.columns.clone())),
+    };
+
+    let batch_size = args.batch_size.max(1);
+    let mut exported = 0u64;
+    while let Some(keys) = source.next_batch(&mut conn, batch_size).await? {
+        let documents = fetch_documents(&mut conn, &keys, storage).await?;
+        encoder.write_batch(&mut writer, &documents)?;
+        exported += documents.len() as u64;
+        if to_file {
+            output.progress(&format!("Exported {exported} document(s)"));
+        }
+    }
+    writer.flush().context("Failed to write export")?;
+
+    if let Some(path) = &args.out {
+        output.clear_line();
+        output.success(&format!("Exported {exported} document(s) to {}", path.display()));
+        if let Encoder::Csv(csv) = &encoder
+            && csv.truncated > 0
+        {
+            output.warning(&format!(
+                "{} document(s) had fields outside the CSV columns; pass --columns to include them",
+                csv.truncated
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Search index of the project entity mapped to `collection`, honoring its `index_name`; the
+/// default name outside a project or when no entity maps to it. Scans quietly, since an export
+/// to stdout leaves stdout to the documents.
+fn project_index_name(prefix: &str, service: &str, collection: &str) -> String {
+    let schema = ProjectContext::find().ok().and_then(|ctx| {
+        let discovered = discover_entities(&ctx.project_root).ok()?;
+        discovered
+            .iter()
+            .filter_map(|file| parse_entity_file(&file.path, &file.relative_path).ok())
+            .flatten()
+            .find(|schema| schema.collection_name() == collection)
+    });
+    match schema {
+        Some(schema) => index_name(&schema, prefix, service),
+        None => KeyContext::new(prefix, service).index_name(collection),
+    }
+}
+
+/// Import outcome counts, with the line and error of each rejected document.
+#[derive(Default)]
+struct ImportReport {
+    imported: u64,
+    skipped: u64,
+    failures: Vec<(usize, String)>,
+}
+
+/// Failures listed individually; the rest are only counted.
+const MAX_REPORTED_FAILURES: usize = 20;
+
+async fn handle_import(args: ImportArgs, output: &OutputManager) -> Result<()> {
+    output.heading(&format!("Import: {}:{}", args.service, args.collection));
+
+    let ctx = ProjectContext::find()?;
+    let (schemas, _) = scan_project(&ctx, output)?;
+    let Some(schema) = schemas.iter().find(|schema| schema.collection_name() == args.collection) else {
+        bail!("No entity in this project maps to collection `{}`", args.collection);
+    };
+    let mut descriptor = entity_descriptor(schema, &args.service);
+    if descriptor.id_field.is_none() {
+        bail!("{} has no #[snugom(id)] field", schema.entity);
+    }
+
+    let total = count_lines(&args.file)?;
+    let file = File::open(&args.file).with_context(|| format!("Failed to open {}", args.file.display()))?;
+
+    let mut conn = connect(output, true).await?;
+    let index_name = index_name(schema, &args.prefix, &args.service);
+    if let Ok(index) = index_info(&mut conn, &index_name).await {
+        descriptor.storage = index.storage.unwrap_or_default();
+    }
+
+    let batch_size = args.batch_size.max(1);
+    let mut report = ImportReport::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut processed = 0u64;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read {}", args.file.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&line) {
+            Ok(document) => batch.push((index + 1, document)),
+            Err(err) => report.failures.push((index + 1, format!("invalid JSON: {err}"))),
+        }
+        processed += 1;
+        if batch.len() >= batch_size {
+            import_batch(&mut conn, &descriptor, &args, std::mem::take(&mut batch), &mut report).await?;
+            output.progress_bar("Importing", processed, total);
+        }
+    }
+    import_batch(&mut conn, &descriptor, &args, batch, &mut report).await?;
+    output.progress_bar("Importing", processed, total);
+    output.clear_line();
+
+    output.success(&format!("Imported {} document(s) into {}", report.imported, args.collection));
+    if report.skipped > 0 {
+        output.info(&format!("Skipped {} existing document(s)", report.skipped));
+    }
+    if report.failures.is_empty() {
+        return Ok(());
+    }
+
+    for (line, error) in report.failures.iter().take(MAX_REPORTED_FAILURES) {
+        output.error(&format!("line {line}: {error}"));
+    }
+    if report.failures.len() > MAX_REPORTED_FAILURES {
+        output.info(&format!("...and {} more", report.failures.len() - MAX_REPORTED_FAILURES));
+    }
+    bail!("{} document(s) were not imported", report.failures.len())
+}
+
+/// Validate and write one batch of `(line, document)` pairs, pipelining the mutation scripts.
+async fn import_batch(
+    conn: &mut ConnectionManager,
+    descriptor: &EntityDescriptor,
+    args: &ImportArgs,
+    batch: Vec<(usize, Value)>,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let mut lines = Vec::with_capacity(batch.len());
+    let mut commands = Vec::with_capacity(batch.len());
+    for (line, document) in batch {
+        match plan_document_create(descriptor, &args.prefix, document, !args.upsert) {
+            Ok((_, plan)) => {
+                lines.push(line);
+                commands.extend(plan.commands);
+            }
+            Err(err) => report.failures.push((line, err.to_string())),
+        }
+    }
+
+    let replies = execute_pipelined(conn, &commands).await.context("Failed to write documents")?;
+    for ((line, reply), command) in lines.into_iter().zip(replies).zip(&commands) {
+        match reply {
+            Ok(_) => report.imported += 1,
+            Err(RepoError::AlreadyExists { .. }) if args.skip_existing => report.skipped += 1,
+            Err(RepoError::AlreadyExists { .. }) => {
+                let id = match command {
+                    MutationCommand::UpsertEntity(mutation) => mutation.entity_id.as_str(),
+                    _ => "",
+                };
+                report
+                    .failures
+                    .push((line, format!("`{id}` already exists; pass --upsert or --skip-existing")));
+            }
+            Err(err) => report.failures.push((line, err.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Number of non-blank lines in `path`, for the progress bar.
+fn count_lines(path: &Path) -> Result<u64> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut count = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if !line.trim().is_empty() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Version of the snapshot file layout, checked on restore.
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// First line of a snapshot file.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SnapshotHeader {
+    snugom_snapshot: u32,
+    prefix: String,
+    service: String,
+    taken_at: DateTime<Utc>,
+}
+
+/// One key of a snapshot, named relative to `{prefix}:{service}:` so it can be restored elsewhere.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SnapshotEntry {
+    key: String,
+    /// Remaining time to live in milliseconds, if the key expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl: Option<u64>,
+    /// `DUMP` payload, base64-encoded
+    dump: String,
+}
+
+async fn handle_snapshot(args: SnapshotArgs, output: &OutputManager) -> Result<()> {
+    output.heading(&format!("Snapshot: {}:{}", args.prefix, args.service));
+
+    let mut conn = connect(output, true).await?;
+    let namespace = KeyContext::new(&args.prefix, &args.service).join(&[""]);
+    let file = File::create(&args.out).with_context(|| format!("Failed to create {}", args.out.display()))?;
+    let mut writer = BufWriter::new(file);
+    let header = SnapshotHeader {
+        snugom_snapshot: SNAPSHOT_FORMAT,
+        prefix: args.prefix.clone(),
+        service: args.service.clone(),
+        taken_at: Utc::now(),
+    };
+    serde_json::to_writer(&mut writer, &header).context("Failed to write snapshot")?;
+    writer.write_all(b"\n").context("Failed to write snapshot")?;
+
+    let batch_size = args.batch_size.max(1);
+    let mut cursor = 0u64;
+    let mut written = 0u64;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{namespace}*"))
+            .arg("COUNT")
+            .arg(batch_size)
+            .query_async(&mut conn)
+            .await
+            .context("Failed to scan Redis keys")?;
+        for entry in dump_keys(&mut conn, &namespace, &keys).await? {
+            serde_json::to_writer(&mut writer, &entry).context("Failed to write snapshot")?;
+            writer.write_all(b"\n").context("Failed to write snapshot")?;
+            written += 1;
+        }
+        output.progress(&format!("Saved {written} key(s)"));
+        cursor = next_cursor;
+        if cursor == 0 {
+            break;
+        }
+    }
+    writer.flush().context("Failed to write snapshot")?;
+
+    output.clear_line();
+    output.success(&format!("Saved {written} key(s) to {}", args.out.display()));
+    output.info("Search indexes are not part of the snapshot; they index restored keys once they exist");
+    Ok(())
+}
+
+/// `DUMP` and `PTTL` of `keys`, skipping keys deleted since they were scanned.
+async fn dump_keys(conn: &mut ConnectionManager, namespace: &str, keys: &[String]) -> Result<Vec<SnapshotEntry>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("DUMP").arg(key).cmd("PTTL").arg(key);
+    }
+    let replies: Vec<(Option<Vec<u8>>, i64)> = pipe.query_async(conn).await.context("Failed to dump keys")?;
+    let entries = keys
+        .iter()
+        .zip(replies)
+        .filter_map(|(key, (dump, ttl))| {
+            Some(SnapshotEntry {
+                key: key.strip_prefix(namespace)?.to_string(),
+                // -1 is a key without expiry, -2 one that expired in between
+                ttl: u64::try_from(ttl).ok(),
+                dump: STANDARD.encode(dump?),
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+async fn handle_restore(args: RestoreArgs, output: &OutputManager) -> Result<()> {
+    let (header, _) = open_snapshot(&args.file)?;
+    let service = args.service.clone().unwrap_or_else(|| header.service.clone());
+    output.heading(&format!("Restore: {}:{}", args.prefix, service));
+    output.info(&format!(
+        "Snapshot of {}:{} taken {}",
+        header.prefix,
+        header.service,
+        header.taken_at.to_rfc3339()
+    ));
+
+    let namespace = KeyContext::new(&args.prefix, &service).join(&[""]);
+    let total = count_lines(&args.file)?.saturating_sub(1);
+    let batch_size = args.batch_size.max(1);
+    let mut conn = connect(output, true).await?;
+
+    // Checked up front so a refused restore leaves nothing half-written
+    if !args.replace {
+        let (_, entries) = open_snapshot(&args.file)?;
+        let mut existing = 0u64;
+        for batch in SnapshotBatches::new(entries, batch_size) {
+            let mut pipe = redis::pipe();
+            for entry in batch? {
+                pipe.cmd("EXISTS").arg(format!("{namespace}{}", entry.key));
+            }
+            let found: Vec<u64> = pipe.query_async(&mut conn).await.context("Failed to check existing keys")?;
+            existing += found.into_iter().sum::<u64>();
+        }
+        if existing > 0 {
+            bail!("{existing} key(s) already exist under {namespace}; pass --replace to overwrite them");
+        }
+    }
+
+    let (_, entries) = open_snapshot(&args.file)?;
+    let mut restored = 0u64;
+    for batch in SnapshotBatches::new(entries, batch_size) {
+        let mut pipe = redis::pipe();
+        for entry in batch? {
+            let payload = STANDARD
+                .decode(&entry.dump)
+                .with_context(|| format!("Corrupt dump of `{}`", entry.key))?;
+            pipe.cmd("RESTORE")
+                .arg(format!("{namespace}{}", entry.key))
+                .arg(entry.ttl.unwrap_or(0))
+                .arg(payload);
+            if args.replace {
+                pipe.arg("REPLACE");
+            }
+            pipe.ignore();
+            restored += 1;
+        }
+        pipe.query_async::<()>(&mut conn).await.context("Failed to restore keys")?;
+        output.progress_bar("Restoring", restored, total);
+    }
+    output.clear_line();
+
+    output.success(&format!("Restored {restored} key(s) under {namespace}"));
+    Ok(())
+}
+
+/// Read and check the header of a snapshot file, leaving the entry lines to be read.
+fn open_snapshot(path: &Path) -> Result<(SnapshotHeader, Lines<BufReader<File>>)> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+    let first = lines
+        .next()
+        .transpose()
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .unwrap_or_default();
+    let header = parse_snapshot_header(&first).with_context(|| format!("{} is not a snugom snapshot", path.display()))?;
+    Ok((header, lines))
+}
+
+fn parse_snapshot_header(line: &str) -> Result<SnapshotHeader> {
+    let header: SnapshotHeader = serde_json::from_str(line).context("missing snapshot header")?;
+    if header.snugom_snapshot != SNAPSHOT_FORMAT {
+        bail!("unsupported snapshot format {}", header.snugom_snapshot);
+    }
+    Ok(header)
+}
+
+/// Snapshot entries read `size` at a time; line numbers count the header as line 1.
+struct SnapshotBatches {
+    lines: std::iter::Enumerate<Lines<BufReader<File>>>,
+    size: usize,
+}
+
+impl SnapshotBatches {
+    fn new(lines: Lines<BufReader<File>>, size: usize) -> Self {
+        Self {
+            lines: lines.enumerate(),
+            size,
+        }
+    }
+}
+
+impl Iterator for SnapshotBatches {
+    type Item = Result<Vec<SnapshotEntry>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut batch = Vec::with_capacity(self.size);
+        for (index, line) in self.lines.by_ref() {
+            let entry = line.context("Failed to read snapshot").and_then(|line| {
+                serde_json::from_str(&line).with_context(|| format!("line {}: invalid entry", index + 2))
+            });
+            match entry {
+                Ok(entry) => batch.push(entry),
+                Err(err) => return Some(Err(err)),
+            }
+            if batch.len() >= self.size {
+                break;
+            }
+        }
+        (!batch.is_empty()).then_some(Ok(batch))
+    }
+}
+
+async fn handle_copy(args: CopyArgs, output: &OutputManager) -> Result<()> {
+    let (verb, done) = if args.move_keys { ("Move", "Moved") } else { ("Copy", "Copied") };
+    output.heading(&format!("{verb}: {} -> {}", args.from, args.to));
+
+    let mut conn = connect(output, true).await?;
+    let options = PrefixCopyOptions {
+        chunk_size: args.batch_size.max(1),
+        replace: args.replace,
+        remove_source: args.move_keys,
+        ..PrefixCopyOptions::default()
+    };
+    let report = copy_prefix_with(&mut conn, &args.from, &args.to, options, |progress| {
+        output.progress(&format!("{done} {} key(s)", progress.copied));
+    })
+    .await?;
+    output.clear_line();
+    output.success(&format!("{done} {} key(s) in {:.1}s", report.copied, report.elapsed.as_secs_f64()));
+
+    // Index definitions come from the project's entities; the live indexes only know the old prefix
+    match ProjectContext::find() {
+        Ok(ctx) => {
+            let (schemas, _) = scan_project(&ctx, output)?;
+            for schema in &schemas {
+                let definition = index_definition(schema, &args.to);
+                ensure_index(&mut conn, &definition)
+                    .await
+                    .with_context(|| format!("Failed to create {}", definition.name))?;
+                if args.move_keys {
+                    let source = index_definition(schema, &args.from);
+                    drop_index(&mut conn, &source.name)
+                        .await
+                        .with_context(|| format!("Failed to drop {}", source.name))?;
+                }
+            }
+            output.success(&format!("Ensured {} index(es) under {}", schemas.len(), args.to));
+        }
+        Err(_) => output.warning(&format!(
+            "Not in a snugom project, so no indexes were created; run `snugom schema reindex --prefix {}` from one",
+            args.to
+        )),
+    }
+
+    output.progress("Verifying...");
+    let verification = verify_prefix_copy(&mut conn, &args.from, &args.to).await?;
+    output.clear_line();
+    if args.move_keys {
+        if verification.source_keys > 0 {
+            bail!("{} key(s) remain under {}; they were written during the move", verification.source_keys, args.from);
+        }
+        if verification.target_keys < report.copied {
+            bail!("Expected {} key(s) under {}, found {}", report.copied, args.to, verification.target_keys);
+        }
+    } else if !verification.missing.is_empty() {
+        for name in verification.missing.iter().take(MAX_REPORTED_FAILURES) {
+            output.error(&format!("{}:{name} was not copied", args.from));
+        }
+        bail!("{} key(s) under {} have no copy under {}", verification.missing.len(), args.from, args.to);
+    }
+    output.success(&format!("Verified {} key(s) under {}", verification.target_keys, args.to));
+    Ok(())
+}
+
+pub(crate) async fn connect(output: &OutputManager, verbose: bool) -> Result<ConnectionManager> {
+    let redis_url = ProjectContext::find()
+        .and_then(|ctx| ctx.redis_url())
+        .or_else(|_| std::env::var("REDIS_URL"))
+        .context("REDIS_URL environment variable not set. Set it to connect to Redis.")?;
+
+    if verbose {
+        output.progress("Connecting to Redis...");
+    }
+    let client = redis::Client::open(redis_url.as_str())
+        .context("Failed to create Redis client")?;
+    let conn = ConnectionManager::new(client)
+        .await
+        .context("Failed to connect to Redis")?;
+    if verbose {
+        output.clear_line();
+        output.success("Connected to Redis");
+    }
+    Ok(conn)
+}
+
+/// Build a RediSearch query from `field:operator:value` filters, resolving each field against
+/// the live index's attributes.
+fn build_filter_query(index: &IndexInfo, filters: &[String]) -> Result<String> {
+    let mut conditions = Vec::new();
+    for raw in filters {
+        let descriptor = FilterDescriptor::parse(raw)?;
+        conditions.push(descriptor.map_with(|descriptor| index.filter_condition(descriptor))?);
+    }
+    Ok(SearchParams::new().with_conditions(conditions).build_query(""))
+}
+
+/// Where the keys of exported documents come from.
+enum KeySource {
+    /// Every entity key under the collection. `SCAN` may return a key twice if the keyspace is
+    /// resized mid-export.
+    Scan {
+        pattern: String,
+        collection_prefix: String,
+        cursor: u64,
+        done: bool,
+    },
+    /// Keys matching a query, paged with `LIMIT`.
+    Search {
+        index: String,
+        query: String,
+        offset: u64,
+        done: bool,
+    },
+}
+
+impl KeySource {
+    async fn next_batch(&mut self, conn: &mut ConnectionManager, batch_size: usize) -> Result<Option<Vec<String>>> {
+        match self {
+            KeySource::Scan {
+                pattern,
+                collection_prefix,
+                cursor,
+                done,
+            } => {
+                if *done {
+                    return Ok(None);
+                }
+                let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(*cursor)
+                    .arg("MATCH")
+                    .arg(pattern.as_str())
+                    .arg("COUNT")
+                    .arg(batch_size)
+                    .query_async(conn)
+                    .await
+                    .context("Failed to scan Redis keys")?;
+                *cursor = next_cursor;
+                *done = next_cursor == 0;
+                // Unique-constraint and relation bookkeeping share the prefix but have a
+                // second segment after the collection
+                let keys = keys
+                    .into_iter()
+                    .filter(|key| key.strip_prefix(collection_prefix.as_str()).is_some_and(is_entity_id))
+                    .collect();
+                Ok(Some(keys))
+            }
+            KeySource::Search {
+                index,
+                query,
+                offset,
+                done,
+            } => {
+                if *done {
+                    return Ok(None);
+                }
+                let reply: Vec<redis::Value> = redis::cmd("FT.SEARCH")
+                    .arg(index.as_str())
+                    .arg(query.as_str())
+                    .arg("NOCONTENT")
+                    .arg("LIMIT")
+                    .arg(*offset)
+                    .arg(batch_size)
+                    .arg("DIALECT")
+                    .arg(SEARCH_DIALECT)
+                    .query_async(conn)
+                    .await
+                    .context("Failed to search the collection index")?;
+                let mut reply = reply.into_iter();
+                let total: u64 = reply
+                    .next()
+                    .map(|total| redis::from_redis_value(&total))
+                    .transpose()
+                    .context("Unexpected FT.SEARCH reply")?
+                    .unwrap_or(0);
+                let keys = reply
+                    .map(|key| redis::from_redis_value::<String>(&key))
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Unexpected FT.SEARCH reply")?;
+                *offset += keys.len() as u64;
+                *done = keys.len() < batch_size || *offset >= total;
+                Ok(Some(keys))
+            }
+        }
+    }
+}
+
+/// Entity ids never contain `:`, unlike the collection's bookkeeping keys.
+fn is_entity_id(rest: &str) -> bool {
+    !rest.is_empty() && !rest.contains(':')
+}
+
+/// Fetch stored documents for `keys`, skipping keys deleted since they were listed.
+async fn fetch_documents(
+    conn: &mut ConnectionManager,
+    keys: &[String],
+    storage: StorageMode,
+) -> Result<Vec<Map<String, Value>>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut pipe = redis::pipe();
+    for key in keys {
+        match storage {
+            StorageMode::Json => pipe.cmd("JSON.GET").arg(key).arg("$"),
+            StorageMode::Hash => pipe.cmd("HGETALL").arg(key),
+        };
+    }
+
+    let documents = match storage {
+        StorageMode::Json => {
+            let replies: Vec<Option<String>> = pipe.query_async(conn).await.context("Failed to fetch documents")?;
+            replies
+                .into_iter()
+                .flatten()
+                .filter_map(|json| match serde_json::from_str::<Vec<Value>>(&json) {
+                    Ok(values) => values.into_iter().next(),
+                    Err(_) => None,
+                })
+                .filter_map(|value| match value {
+                    Value::Object(document) => Some(document),
+                    _ => None,
+                })
+                .collect()
+        }
+        StorageMode::Hash => {
+            let replies: Vec<HashMap<String, String>> =
+                pipe.query_async(conn).await.context("Failed to fetch documents")?;
+            replies
+                .into_iter()
+                .filter(|fields| !fields.is_empty())
+                .map(|fields| {
+                    let mut fields: Vec<_> = fields.into_iter().collect();
+                    fields.sort();
+                    fields.into_iter().map(|(name, value)| (name, Value::String(value))).collect()
+                })
+                .collect()
+        }
+    };
+    Ok(documents)
+}
+
+enum Encoder {
+    Jsonl,
+    Csv(CsvEncoder),
+}
+
+impl Encoder {
+    fn write_batch(&mut self, writer: &mut impl Write, documents: &[Map<String, Value>]) -> Result<()> {
+        match self {
+            Encoder::Jsonl => {
+                for document in documents {
+                    serde_json::to_writer(&mut *writer, document).context("Failed to write export")?;
+                    writer.write_all(b"\n").context("Failed to write export")?;
+                }
+                Ok(())
+            }
+            Encoder::Csv(csv) => csv.write_batch(writer, documents),
+        }
+    }
+}
+
+/// Writes CSV rows under a header fixed by `--columns` or by the first batch's fields.
+struct CsvEncoder {
+    columns: Vec<String>,
+    header_written: bool,
+    /// Documents with fields that no column holds
+    truncated: u64,
+}
+
+impl CsvEncoder {
+    fn new(columns: Vec<String>) -> Self {
+        Self {
+            columns,
+            header_written: false,
+            truncated: 0,
+        }
+    }
+
+    fn write_batch(&mut self, writer: &mut impl Write, documents: &[Map<String, Value>]) -> Result<()> {
+        if !self.header_written {
+            if documents.is_empty() && self.columns.is_empty() {
+                return Ok(());
+            }
+            if self.columns.is_empty() {
+                let fields: BTreeSet<&String> = documents.iter().flat_map(|document| document.keys()).collect();
+                self.columns = fields.into_iter().cloned().collect();
+            }
+            let header: Vec<String> = self.columns.iter().map(|column| csv_field(column)).collect();
+            writeln!(writer, "{}", header.join(",")).context("Failed to write export")?;
+            self.header_written = true;
+        }
+
+        for document in documents {
+            if document.keys().any(|field| !self.columns.contains(field)) {
+                self.truncated += 1;
+            }
+            let row: Vec<String> = self
+                .columns
+                .iter()
+                .map(|column| csv_field(&csv_value(document.get(column))))
+                .collect();
+            writeln!(writer, "{}", row.join(",")).context("Failed to write export")?;
+        }
+        Ok(())
+    }
+}
+
+/// Scalars are written as-is, nulls and missing fields as empty cells, and arrays and objects
+/// as JSON.
+fn csv_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Bool(flag)) => flag.to_string(),
+        Some(Value::Number(number)) => number.to_string(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Quote a cell when it contains a delimiter, quote, or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_is_entity_id() {
+        assert!(is_entity_id("abc123"));
+        assert!(!is_entity_id(""));
+        assert!(!is_entity_id("unique:email:a@b.c"));
+        assert!(!is_entity_id("rev_rel:guild:g1"));
+    }
+
+    #[test]
+    fn test_snapshot_header() {
+        let entry = SnapshotEntry {
+            key: "guilds:g1".to_string(),
+            ttl: None,
+            dump: STANDARD.encode(b"payload"),
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        assert_eq!(line, r#"{"key":"guilds:g1","dump":"cGF5bG9hZA=="}"#);
+        assert_eq!(serde_json::from_str::<SnapshotEntry>(&line).unwrap(), entry);
+
+        let header = parse_snapshot_header(
+            r#"{"snugom_snapshot":1,"prefix":"app","service":"guild","taken_at":"2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!((header.prefix.as_str(), header.service.as_str()), ("app", "guild"));
+        assert!(parse_snapshot_header(r#"{"id":"g1","name":"Rust"}"#).is_err());
+        assert!(
+            parse_snapshot_header(
+                r#"{"snugom_snapshot":2,"prefix":"app","service":"guild","taken_at":"2024-01-01T00:00:00Z"}"#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_csv_encoder() {
+        let mut encoder = CsvEncoder::new(Vec::new());
+        let mut out = Vec::new();
+        encoder
+            .write_batch(
+                &mut out,
+                &[
+                    document(json!({"name": "Rust, \"the\" guild", "members": 3, "tags": ["a", "b"]})),
+                    document(json!({"name": "Ferris", "members": null})),
+                ],
+            )
+            .unwrap();
+        encoder
+            .write_batch(&mut out, &[document(json!({"name": "Late", "motto": "new field"}))])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "members,name,tags\n3,\"Rust, \"\"the\"\" guild\",\"[\"\"a\"\",\"\"b\"\"]\"\n,Ferris,\n,Late,\n"
+        );
+        assert_eq!(encoder.truncated, 1);
+    }
+}
@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use chrono::Utc;
 use clap::Subcommand;
 
+use crate::commands::schema::scan_project;
 use crate::context::ProjectContext;
 use crate::differ::{diff_schemas, load_latest_snapshots, EntityDiff, MigrationComplexity};
 use crate::examples::ExampleGroup;
+use crate::executor::{ResumeMode, DEFAULT_BATCH_SIZE, DEFAULT_PARALLELISM};
 use crate::generator::{generate_migration_file, update_migrations_mod, update_source_schema_version};
 use crate::output::OutputManager;
 use crate::scanner::{discover_entities, parse_entity_file};
@@ -22,11 +26,15 @@ pub const EXAMPLES: &[ExampleGroup] = &[
         commands: &[
             "snugom migrate deploy                # Run all pending migrations",
             "snugom migrate deploy --dry-run      # Preview what would be migrated",
+            "snugom migrate deploy --lock-timeout 300   # Wait up to 5 minutes for another deploy",
+            "snugom migrate deploy --batch-size 2000 --parallelism 8   # Move documents faster on a large Redis",
         ],
     },
     ExampleGroup {
         title: "Recovery",
         commands: &[
+            "snugom migrate deploy --resume             # Continue an interrupted migration",
+            "snugom migrate deploy --restart            # Start an interrupted migration over",
             "snugom migrate resolve init --applied       # Mark migration as applied",
             "snugom migrate resolve init --rolled-back   # Mark migration as rolled back",
         ],
@@ -49,6 +57,26 @@ pub enum MigrateCommands {
         /// Preview what would be migrated without making changes
         #[arg(long)]
         dry_run: bool,
+
+        /// Seconds to wait for another runner's migration lock
+        #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+        lock_timeout: u64,
+
+        /// Continue an interrupted migration from its checkpoint, failing if there is none
+        #[arg(long, conflicts_with = "restart")]
+        resume: bool,
+
+        /// Discard an interrupted migration's checkpoint and start it over
+        #[arg(long, conflicts_with = "resume")]
+        restart: bool,
+
+        /// Keys scanned per batch when moving documents to a new schema version
+        #[arg(long, value_name = "KEYS", default_value_t = DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+
+        /// Connections each batch is read and written over
+        #[arg(long, value_name = "CONNECTIONS", default_value_t = DEFAULT_PARALLELISM)]
+        parallelism: usize,
     },
 
     /// Manually mark a migration as applied or rolled back
@@ -83,8 +111,27 @@ pub async fn handle_migrate_commands(
         MigrateCommands::Create { name } => {
             handle_create(&ctx, &name, output).await?;
         }
-        MigrateCommands::Deploy { dry_run } => {
-            handle_deploy(&ctx, dry_run, output).await?;
+        MigrateCommands::Deploy {
+            dry_run,
+            lock_timeout,
+            resume,
+            restart,
+            batch_size,
+            parallelism,
+        } => {
+            let resume = match (resume, restart) {
+                (true, _) => ResumeMode::Resume,
+                (_, true) => ResumeMode::Restart,
+                _ => ResumeMode::Auto,
+            };
+            let options = DeployOptions {
+                dry_run,
+                lock_timeout: Duration::from_secs(lock_timeout),
+                resume,
+                batch_size,
+                parallelism,
+            };
+            handle_deploy(&ctx, options, output).await?;
         }
         MigrateCommands::Resolve {
             migration_name,
@@ -98,7 +145,7 @@ pub async fn handle_migrate_commands(
     Ok(())
 }
 
-async fn handle_create(ctx: &ProjectContext, name: &str, output: &OutputManager) -> Result<()> {
+pub(crate) async fn handle_create(ctx: &ProjectContext, name: &str, output: &OutputManager) -> Result<()> {
     output.heading("Generate Migration");
     output.bullet(&format!("Migration name: {name}"));
 
@@ -343,8 +390,19 @@ fn format_change(change: &crate::differ::EntityChange) -> String {
     }
 }
 
-async fn handle_deploy(ctx: &ProjectContext, dry_run: bool, output: &OutputManager) -> Result<()> {
-    use crate::executor::MigrationRunner;
+/// Flags of `snugom migrate deploy`.
+struct DeployOptions {
+    dry_run: bool,
+    lock_timeout: Duration,
+    resume: ResumeMode,
+    batch_size: usize,
+    parallelism: usize,
+}
+
+async fn handle_deploy(ctx: &ProjectContext, options: DeployOptions, output: &OutputManager) -> Result<()> {
+    use crate::executor::{MigrationLock, MigrationRunner};
+
+    let dry_run = options.dry_run;
 
     output.heading("Deploy Migrations");
 
@@ -360,16 +418,41 @@ async fn handle_deploy(ctx: &ProjectContext, dry_run: bool, output: &OutputManag
 
     output.bullet(&format!("Redis: {redis_url}"));
 
+    // Migrations name entities; their documents live under the collection each one maps to
+    let (schemas, _) = scan_project(ctx, output)?;
+    let collections = schemas
+        .iter()
+        .map(|schema| (schema.entity.clone(), schema.collection_name()))
+        .collect();
+
     // Connect to Redis
     output.progress("Connecting to Redis...");
     let mut runner = MigrationRunner::new(&redis_url, dry_run)
         .await
-        .context("Failed to connect to Redis")?;
+        .context("Failed to connect to Redis")?
+        .with_resume(options.resume)
+        .with_collections(collections)
+        .with_batching(options.batch_size, options.parallelism);
     output.clear_line();
     output.success("Connected to Redis");
 
-    // Run migrations
-    let stats = runner.run_all(&ctx.migrations_dir, output).await?;
+    // Dry runs write nothing, so they don't wait for other runners
+    let lock = if dry_run {
+        None
+    } else {
+        output.progress("Acquiring migration lock...");
+        let lock = MigrationLock::acquire(runner.context().conn().clone(), options.lock_timeout).await?;
+        output.clear_line();
+        output.success("Acquired migration lock");
+        Some(lock)
+    };
+
+    // Run migrations, releasing the lock whether or not they succeed
+    let result = runner.run_all(&ctx.migrations_dir, lock.as_ref(), output).await;
+    if let Some(lock) = lock {
+        lock.release().await?;
+    }
+    let stats = result?;
 
     // Summary
     output.heading("Summary");
@@ -438,6 +521,9 @@ async fn handle_resolve(
 
     let mut state = MigrationState::new(context.conn().clone());
 
+    // Either way the migration is settled, so an interrupted run's progress no longer applies
+    state.clear_checkpoint(migration_name).await?;
+
     // Find the migration file to get its checksum
     let migrations = MigrationRunner::discover_migrations(&ctx.migrations_dir)?;
     let migration = migrations.iter().find(|m| m.display_name == migration_name);
@@ -482,7 +568,7 @@ mod tests {
             name: "email".to_string(),
             change_type: ChangeType::Added,
             old_field: None,
-            new_field: Some(field),
+            new_field: Some(Box::new(field)),
         };
         let change = EntityChange::Field(fc);
 
@@ -496,7 +582,7 @@ mod tests {
         let fc = FieldChange {
             name: "legacy".to_string(),
             change_type: ChangeType::Removed,
-            old_field: Some(field),
+            old_field: Some(Box::new(field)),
             new_field: None,
         };
         let change = EntityChange::Field(fc);
@@ -512,8 +598,8 @@ mod tests {
         let fc = FieldChange {
             name: "count".to_string(),
             change_type: ChangeType::Modified,
-            old_field: Some(old_field),
-            new_field: Some(new_field),
+            old_field: Some(Box::new(old_field)),
+            new_field: Some(Box::new(new_field)),
         };
         let change = EntityChange::Field(fc);
 
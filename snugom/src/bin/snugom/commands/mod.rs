@@ -1,3 +1,5 @@
+pub mod data;
 pub mod init;
 pub mod migrate;
+pub mod query;
 pub mod schema;
@@ -0,0 +1,466 @@
+//! Interactive search console over the project's entities.
+
+use std::io::{self, Write};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use redis::aio::ConnectionManager;
+use serde_json::Value;
+use snugom::search::{
+    execute_index_search, index_info, IndexDefinition, IndexInfo, PaginatedResponse, SearchParams, SearchQuery,
+    SearchResult, SortField, SortOrder,
+};
+use snugom::types::EntityDescriptor;
+
+use crate::commands::data::connect;
+use crate::commands::schema::scan_project;
+use crate::context::ProjectContext;
+use crate::examples::ExampleGroup;
+use crate::output::{OutputFormat, OutputManager};
+use crate::scanner::{default_sort, entity_descriptor, index_definition, sort_fields, EntitySchema};
+
+pub const EXAMPLES: &[ExampleGroup] = &[
+    ExampleGroup {
+        title: "Console",
+        commands: &[
+            "snugom query --prefix app                  # Start the console",
+            "snugom query --prefix app --entity Guild   # Start with Guild selected",
+        ],
+    },
+    ExampleGroup {
+        title: "One-off Queries",
+        commands: &[
+            "snugom query --prefix app --entity Guild status:eq:active sort:-created_at",
+            "snugom query --prefix app --entity Guild dragon page:2 size:50 --output json",
+        ],
+    },
+];
+
+const HELP: &[(&str, &str)] = &[
+    ("field:op:value", "Filter, e.g. status:eq:active, level:range:10,50, name:prefix:dra"),
+    ("sort:[-]field", "Sort by a sortable field, descending with -"),
+    ("page:N size:N", "Page through results"),
+    ("words", "Full-text search across searchable fields"),
+    (".use <Entity>", "Switch entity"),
+    (".entities", "List entities"),
+    (".fields", "List the current entity's indexed fields"),
+    (".explain <expr>", "Show the RediSearch query without running it"),
+    (".quit", "Leave the console"),
+];
+
+/// Longest value shown in a table cell.
+const MAX_CELL_WIDTH: usize = 40;
+
+#[derive(Args)]
+pub struct QueryArgs {
+    /// Key prefix the application's client uses
+    #[arg(long, env = "SNUGOM_PREFIX")]
+    pub prefix: String,
+
+    /// Entity to query (required when passing an expression)
+    #[arg(long)]
+    pub entity: Option<String>,
+
+    /// Results per page
+    #[arg(long, default_value_t = 10)]
+    pub page_size: u64,
+
+    /// Run this expression and exit instead of starting the console
+    pub expression: Vec<String>,
+}
+
+pub async fn handle_query(args: QueryArgs, output: &OutputManager) -> Result<()> {
+    let interactive = args.expression.is_empty();
+    let ctx = ProjectContext::find()?;
+    let (schemas, _) = scan_project(&ctx, output)?;
+    if schemas.is_empty() {
+        return Ok(());
+    }
+
+    let mut session = Session {
+        conn: connect(output, interactive).await?,
+        schemas,
+        prefix: args.prefix,
+        page_size: args.page_size,
+        target: None,
+    };
+    if let Some(entity) = &args.entity {
+        session.select(entity, output).await?;
+    }
+
+    if !interactive {
+        if session.target.is_none() {
+            bail!("--entity is required to run an expression");
+        }
+        let query = parse_expression(args.expression.iter().map(String::as_str), args.page_size)?;
+        return session.run(Command::Search(query), output).await;
+    }
+
+    output.info("Type a filter expression, or .help for commands");
+    let mut line = String::new();
+    loop {
+        let prompt = session.target.as_ref().map_or("snugom", |target| target.entity.as_str());
+        print!("{prompt}> ");
+        io::stdout().flush().context("Failed to write prompt")?;
+
+        line.clear();
+        if io::stdin().read_line(&mut line).context("Failed to read input")? == 0 {
+            println!();
+            break;
+        }
+        let result = match parse_line(&line, session.page_size) {
+            Ok(Command::Quit) => break,
+            Ok(command) => session.run(command, output).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            output.error(&format!("{err:#}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// A console line.
+enum Command {
+    Empty,
+    Help,
+    Quit,
+    Entities,
+    Fields,
+    Use(String),
+    Explain(SearchQuery),
+    Search(SearchQuery),
+}
+
+fn parse_line(line: &str, page_size: u64) -> Result<Command> {
+    let line = line.trim();
+    let Some(rest) = line.strip_prefix('.') else {
+        return Ok(match line {
+            "" => Command::Empty,
+            "quit" | "exit" => Command::Quit,
+            _ => Command::Search(parse_expression(line.split_whitespace(), page_size)?),
+        });
+    };
+
+    let (name, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let arg = arg.trim();
+    Ok(match name {
+        "help" => Command::Help,
+        "quit" | "exit" => Command::Quit,
+        "entities" => Command::Entities,
+        "fields" => Command::Fields,
+        "use" if !arg.is_empty() => Command::Use(arg.to_string()),
+        "use" => bail!("Usage: .use <Entity>"),
+        "explain" => Command::Explain(parse_expression(arg.split_whitespace(), page_size)?),
+        _ => bail!("Unknown command .{name}; type .help for commands"),
+    })
+}
+
+/// Parse whitespace-separated tokens into the query parameters the HTTP layer accepts.
+///
+/// `sort:`, `page:`, `size:`, and `q:` tokens set those parameters, other `field:op:value` tokens
+/// are filters, and bare words form the full-text query.
+fn parse_expression<'a>(tokens: impl IntoIterator<Item = &'a str>, page_size: u64) -> Result<SearchQuery> {
+    let mut query = SearchQuery {
+        page_size: Some(page_size),
+        ..Default::default()
+    };
+    let mut terms = Vec::new();
+    for token in tokens {
+        match token.split_once(':') {
+            Some(("sort", field)) => {
+                let (field, order) = match field.strip_prefix('-') {
+                    Some(field) => (field, Some(SortOrder::Desc)),
+                    None => match field.strip_prefix('+') {
+                        Some(field) => (field, Some(SortOrder::Asc)),
+                        None => (field, None),
+                    },
+                };
+                if field.is_empty() {
+                    bail!("sort: needs a field name");
+                }
+                query.sort_by = Some(field.to_string());
+                query.sort_order = order;
+            }
+            Some(("page", page)) => {
+                query.page = Some(page.parse().with_context(|| format!("Invalid page `{page}`"))?);
+            }
+            Some(("size", size)) => {
+                query.page_size = Some(size.parse().with_context(|| format!("Invalid page size `{size}`"))?);
+            }
+            Some(("q", term)) => terms.push(term),
+            Some(_) => query.filter.push(token.to_string()),
+            None => terms.push(token),
+        }
+    }
+    query.q = (!terms.is_empty()).then(|| terms.join(" "));
+    Ok(query)
+}
+
+struct Session {
+    conn: ConnectionManager,
+    schemas: Vec<EntitySchema>,
+    prefix: String,
+    page_size: u64,
+    target: Option<Target>,
+}
+
+impl Session {
+    async fn run(&mut self, command: Command, output: &OutputManager) -> Result<()> {
+        match command {
+            Command::Empty | Command::Quit => {}
+            Command::Help => {
+                for (syntax, description) in HELP {
+                    output.key_value(syntax, description);
+                }
+            }
+            Command::Entities => {
+                for schema in &self.schemas {
+                    let current = self.target.as_ref().is_some_and(|target| target.entity == schema.entity);
+                    let marker = if current { " (current)" } else { "" };
+                    let ids = schema.id_prefix().map(|prefix| format!(", ids {prefix}_...")).unwrap_or_default();
+                    output.bullet(&format!("{} - {}{ids}{marker}", schema.entity, schema.collection_name()));
+                }
+            }
+            Command::Fields => {
+                let target = self.target()?;
+                for field in &target.index.fields {
+                    let sortable = if field.sortable { ", sortable" } else { "" };
+                    output.bullet(&format!("{} ({}{sortable})", query_name(&field.attribute), field.field_type));
+                }
+            }
+            Command::Use(entity) => self.select(&entity, output).await?,
+            Command::Explain(query) => {
+                let target = self.target()?;
+                let params = target.params(query)?;
+                output.key_value("Index", &target.definition.name);
+                output.key_value("Query", &params.build_query(""));
+                if let Some(sort) = &params.sort {
+                    output.key_value("Sort", &format!("{} {}", sort.field, sort.order.as_str()));
+                }
+                output.key_value("Limit", &format!("{} {}", params.offset(), params.page_size));
+            }
+            Command::Search(query) => {
+                let target = self.target.as_ref().context("No entity selected; use .use <Entity>")?;
+                let params = target.params(query)?;
+                output.verbose(&format!("FT.SEARCH {} {}", target.definition.name, params.build_query("")));
+                let result: SearchResult<Value> =
+                    execute_index_search(&mut self.conn, &target.definition, &target.descriptor, &params, "").await?;
+                print_results(output, target, result)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn select(&mut self, entity: &str, output: &OutputManager) -> Result<()> {
+        let Some(schema) = self
+            .schemas
+            .iter()
+            .find(|schema| schema.entity.eq_ignore_ascii_case(entity) || schema.collection_name() == entity)
+        else {
+            bail!("Entity '{entity}' not found; type .entities to list them");
+        };
+
+        let definition = index_definition(schema, &self.prefix);
+        let index = index_info(&mut self.conn, &definition.name).await.with_context(|| {
+            format!("{} has no search index {}; run 'snugom schema reindex'", schema.entity, definition.name)
+        })?;
+        let sorts = sort_fields(schema);
+        output.success(&format!(
+            "Using {} ({}, {} document(s))",
+            schema.entity, definition.name, index.num_docs
+        ));
+
+        self.target = Some(Target {
+            entity: schema.entity.clone(),
+            // Entities without sortable fields get the derive's empty default, dropped in `params`
+            default_sort: default_sort(schema, &sorts).unwrap_or(SortField {
+                name: "",
+                path: "",
+                default_order: SortOrder::Asc,
+            }),
+            sorts,
+            text_fields: schema
+                .fields
+                .iter()
+                .filter(|field| field.searchable)
+                .map(|field| field.stored_name().to_string())
+                .chain(
+                    schema
+                        .fields
+                        .iter()
+                        .flat_map(|field| &field.nested)
+                        .filter(|nested| nested.searchable)
+                        .map(|nested| nested.path.replace('.', "_")),
+                )
+                .collect(),
+            columns: schema.fields.iter().map(|field| field.stored_name().trim_start_matches("r#").to_string()).collect(),
+            descriptor: entity_descriptor(schema, &schema.service_name()),
+            definition,
+            index,
+        });
+        Ok(())
+    }
+
+    fn target(&self) -> Result<&Target> {
+        self.target.as_ref().context("No entity selected; use .use <Entity>")
+    }
+}
+
+/// The entity queries run against, with what the derive would generate for it.
+struct Target {
+    entity: String,
+    definition: IndexDefinition,
+    descriptor: EntityDescriptor,
+    /// Live index, which resolves filters the same way `snugom data export` does
+    index: IndexInfo,
+    sorts: Vec<SortField>,
+    default_sort: SortField,
+    text_fields: Vec<String>,
+    columns: Vec<String>,
+}
+
+impl Target {
+    fn params(&self, query: SearchQuery) -> Result<SearchParams> {
+        if query.q.is_some() && self.text_fields.is_empty() {
+            bail!("{} has no searchable fields; filter with field:op:value instead", self.entity);
+        }
+        let text_fields: Vec<&str> = self.text_fields.iter().map(String::as_str).collect();
+        let mut params = query.with_text_query(
+            &self.sorts,
+            &self.default_sort,
+            |descriptor| self.index.filter_condition(descriptor),
+            &text_fields,
+        )?;
+        if params.sort.as_ref().is_some_and(|sort| sort.field.is_empty()) {
+            params.sort = None;
+        }
+        Ok(params)
+    }
+}
+
+fn print_results(output: &OutputManager, target: &Target, result: SearchResult<Value>) -> Result<()> {
+    match output.options.output_format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&PaginatedResponse::from(result))?);
+        }
+        OutputFormat::Compact => {
+            for item in &result.items {
+                println!("{item}");
+            }
+        }
+        OutputFormat::Table => {
+            if result.items.is_empty() {
+                output.info(&format!("No matches ({} total)", result.total));
+                return Ok(());
+            }
+
+            let mut table = output.create_table();
+            output.add_table_header(&mut table, target.columns.iter().map(String::as_str).collect());
+            for item in &result.items {
+                table.add_row(target.columns.iter().map(|column| cell_text(item.get(column))));
+            }
+            println!("{table}");
+
+            let first = (result.page - 1) * result.page_size + 1;
+            let last = first + result.items.len() as u64 - 1;
+            output.info(&format!("Showing {first}-{last} of {} (page {})", result.total, result.page));
+            if result.has_more() {
+                output.info(&format!("Add page:{} for more", result.page + 1));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Strings without quotes, other values as compact JSON, cut to [`MAX_CELL_WIDTH`].
+fn cell_text(value: Option<&Value>) -> String {
+    let text = match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    };
+    if text.chars().count() <= MAX_CELL_WIDTH {
+        return text;
+    }
+    let mut cut: String = text.chars().take(MAX_CELL_WIDTH - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// The field name filters use for an index attribute: enum and casefold shadows and datetime
+/// mirrors are filtered by their source field.
+fn query_name(attribute: &str) -> &str {
+    if let Some(rest) = attribute.strip_prefix("__")
+        && let Some(field) = rest
+            .strip_suffix("_tag")
+            .or_else(|| rest.strip_suffix("_lc"))
+            .or_else(|| rest.strip_suffix("_ord"))
+    {
+        return field;
+    }
+    attribute.strip_suffix("_ts").unwrap_or(attribute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expression() {
+        let query = parse_expression(
+            "status:eq:active dragon sort:-created_at page:2 q:slayer level:range:10,50".split_whitespace(),
+            10,
+        )
+        .unwrap();
+        assert_eq!(query.filter, vec!["status:eq:active".to_string(), "level:range:10,50".to_string()]);
+        assert_eq!(query.q.as_deref(), Some("dragon slayer"));
+        assert_eq!(query.sort_by.as_deref(), Some("created_at"));
+        assert_eq!(query.sort_order, Some(SortOrder::Desc));
+        assert_eq!((query.page, query.page_size), (Some(2), Some(10)));
+
+        let query = parse_expression(["sort:name", "size:50"], 10).unwrap();
+        assert_eq!(query.sort_by.as_deref(), Some("name"));
+        assert_eq!(query.sort_order, None);
+        assert_eq!(query.page_size, Some(50));
+        assert_eq!(query.q, None);
+
+        assert!(parse_expression(["page:first"], 10).is_err());
+        assert!(parse_expression(["sort:-"], 10).is_err());
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert!(matches!(parse_line("  \n", 10).unwrap(), Command::Empty));
+        assert!(matches!(parse_line("quit\n", 10).unwrap(), Command::Quit));
+        assert!(matches!(parse_line(".use  Guild \n", 10).unwrap(), Command::Use(entity) if entity == "Guild"));
+        assert!(matches!(
+            parse_line(".explain status:eq:active", 10).unwrap(),
+            Command::Explain(query) if query.filter == ["status:eq:active"]
+        ));
+        assert!(matches!(parse_line("dragon", 10).unwrap(), Command::Search(query) if query.q.as_deref() == Some("dragon")));
+        assert!(parse_line(".use", 10).is_err());
+        assert!(parse_line(".drop", 10).is_err());
+    }
+
+    #[test]
+    fn test_cell_text() {
+        assert_eq!(cell_text(None), "");
+        assert_eq!(cell_text(Some(&Value::Null)), "");
+        assert_eq!(cell_text(Some(&serde_json::json!("Dragons"))), "Dragons");
+        assert_eq!(cell_text(Some(&serde_json::json!({"tier": 2}))), "{\"tier\":2}");
+        let long = cell_text(Some(&Value::String("x".repeat(60))));
+        assert_eq!(long.chars().count(), MAX_CELL_WIDTH);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_query_name() {
+        assert_eq!(query_name("__role_tag"), "role");
+        assert_eq!(query_name("__email_lc"), "email");
+        assert_eq!(query_name("__priority_ord"), "priority");
+        assert_eq!(query_name("created_at_ts"), "created_at");
+        assert_eq!(query_name("name"), "name");
+    }
+}
@@ -1,15 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use clap::Subcommand;
+use notify::{Event, RecursiveMode, Watcher};
 use redis::aio::ConnectionManager;
 use serde_json::Value;
+use snugom::keys::key_separator;
+use snugom::search::{index_health, index_info, rebuild_index, IndexDrift};
 
+use crate::commands::migrate::handle_create;
 use crate::context::ProjectContext;
 use crate::differ::{diff_schemas, load_latest_snapshots, ChangeType, EntityChange};
 use crate::examples::ExampleGroup;
+use crate::generator::{generate_diagram, generate_typescript, DiagramFormat};
 use crate::output::OutputManager;
-use crate::scanner::{discover_entities, parse_entity_file};
+use crate::scanner::{
+    discover_entities, index_definition, parse_entity_file, parse_enum_file, EntitySchema, EnumSchema, SCAN_DIRS,
+};
 
 pub const EXAMPLES: &[ExampleGroup] = &[
     ExampleGroup {
@@ -32,6 +42,34 @@ pub const EXAMPLES: &[ExampleGroup] = &[
             "snugom schema validate guilds --field name    # Check for duplicate values",
         ],
     },
+    ExampleGroup {
+        title: "TypeScript Types",
+        commands: &[
+            "snugom schema typescript --out web/src/types.ts    # Generate interfaces for all entities",
+        ],
+    },
+    ExampleGroup {
+        title: "Relationship Diagrams",
+        commands: &[
+            "snugom schema diagram                         # Print a Mermaid ER diagram",
+            "snugom schema diagram --format dot | dot -Tsvg -o schema.svg",
+            "snugom schema diagram --out docs/schema.mmd   # Write the diagram to a file",
+        ],
+    },
+    ExampleGroup {
+        title: "Index Rebuilds",
+        commands: &[
+            "snugom schema reindex --prefix app                 # Rebuild every entity's index",
+            "snugom schema reindex --prefix app --entity Guild  # Rebuild one entity's index",
+        ],
+    },
+    ExampleGroup {
+        title: "Watch Mode",
+        commands: &[
+            "snugom schema watch                         # Re-run the diff whenever an entity changes",
+            "snugom schema watch --generate --name wip   # Generate a migration for each change",
+        ],
+    },
 ];
 
 #[derive(Subcommand)]
@@ -64,6 +102,50 @@ pub enum SchemaCommands {
         #[arg(long)]
         case_insensitive: bool,
     },
+
+    /// Generate TypeScript interfaces from entity definitions
+    #[command(name = "typescript")]
+    Typescript {
+        /// File to write the declarations to
+        #[arg(long)]
+        out: PathBuf,
+    },
+
+    /// Render an entity relationship diagram
+    #[command(name = "diagram")]
+    Diagram {
+        /// Diagram format
+        #[arg(long, value_enum, default_value = "mermaid")]
+        format: DiagramFormat,
+
+        /// File to write the diagram to (prints to stdout if omitted)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Drop and recreate RediSearch indexes from the current entity definitions
+    #[command(name = "reindex")]
+    Reindex {
+        /// Entity to rebuild (optional, rebuilds all if omitted)
+        #[arg(long)]
+        entity: Option<String>,
+
+        /// Key prefix the application's client uses
+        #[arg(long, env = "SNUGOM_PREFIX")]
+        prefix: String,
+    },
+
+    /// Re-run the diff whenever entity source files change
+    #[command(name = "watch")]
+    Watch {
+        /// Generate a migration for each change instead of printing the diff
+        #[arg(long)]
+        generate: bool,
+
+        /// Name of generated migrations
+        #[arg(long, default_value = "schema_update", requires = "generate")]
+        name: String,
+    },
 }
 
 pub async fn handle_schema_commands(
@@ -92,6 +174,18 @@ pub async fn handle_schema_commands(
         } => {
             handle_validate(&ctx, &collection, &field, case_insensitive, output).await?;
         }
+        SchemaCommands::Typescript { out } => {
+            handle_typescript(&ctx, &out, output)?;
+        }
+        SchemaCommands::Diagram { format, out } => {
+            handle_diagram(&ctx, format, out.as_deref(), output)?;
+        }
+        SchemaCommands::Reindex { entity, prefix } => {
+            handle_reindex(&ctx, entity.as_deref(), &prefix, output).await?;
+        }
+        SchemaCommands::Watch { generate, name } => {
+            handle_watch(&ctx, generate, &name, output).await?;
+        }
     }
 
     Ok(())
@@ -241,7 +335,7 @@ async fn scan_collection_versions(
     collection: &str,
     output: &OutputManager,
 ) -> Result<CollectionStats> {
-    let pattern = format!("{collection}:*");
+    let pattern = format!("{collection}{}*", key_separator());
     let mut stats = CollectionStats {
         total: 0,
         with_version: 0,
@@ -572,6 +666,295 @@ async fn handle_validate(
     Ok(())
 }
 
+fn handle_typescript(ctx: &ProjectContext, out: &Path, output: &OutputManager) -> Result<()> {
+    output.heading("TypeScript Types");
+
+    let (schemas, enums) = scan_project(ctx, output)?;
+    if schemas.is_empty() {
+        return Ok(());
+    }
+
+    write_output_file(out, &generate_typescript(&schemas, &enums))?;
+
+    output.success(&format!(
+        "Wrote {} interface(s) and {} enum(s) to {}",
+        schemas.len(),
+        enums.len(),
+        out.display()
+    ));
+
+    Ok(())
+}
+
+fn handle_diagram(
+    ctx: &ProjectContext,
+    format: DiagramFormat,
+    out: Option<&Path>,
+    output: &OutputManager,
+) -> Result<()> {
+    let Some(out) = out else {
+        // Skip the heading and summary so the diagram can be piped
+        let (schemas, _) = scan_project(ctx, output)?;
+        if !schemas.is_empty() {
+            print!("{}", generate_diagram(&schemas, format));
+        }
+        return Ok(());
+    };
+
+    output.heading("Relationship Diagram");
+
+    let (schemas, _) = scan_project(ctx, output)?;
+    if schemas.is_empty() {
+        return Ok(());
+    }
+
+    write_output_file(out, &generate_diagram(&schemas, format))?;
+
+    let relations: usize = schemas.iter().map(|schema| schema.relations.len()).sum();
+    output.success(&format!(
+        "Wrote {} entity/entities and {relations} relation(s) to {}",
+        schemas.len(),
+        out.display()
+    ));
+
+    Ok(())
+}
+
+/// How often to poll `FT.INFO` while an index backfills.
+const REINDEX_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+async fn handle_reindex(
+    ctx: &ProjectContext,
+    entity_filter: Option<&str>,
+    prefix: &str,
+    output: &OutputManager,
+) -> Result<()> {
+    output.heading("Rebuild Search Indexes");
+
+    let (schemas, _) = scan_project(ctx, output)?;
+    let schemas: Vec<EntitySchema> = schemas
+        .into_iter()
+        .filter(|schema| entity_filter.is_none_or(|entity| schema.entity == entity))
+        .collect();
+    if schemas.is_empty() {
+        if let Some(entity) = entity_filter {
+            anyhow::bail!("Entity '{entity}' not found");
+        }
+        return Ok(());
+    }
+
+    let redis_url = ctx.redis_url().context(
+        "REDIS_URL environment variable not set. Set it to connect to Redis.",
+    )?;
+
+    output.progress("Connecting to Redis...");
+    let client = redis::Client::open(redis_url.as_str())
+        .context("Failed to create Redis client")?;
+    let mut conn = ConnectionManager::new(client)
+        .await
+        .context("Failed to connect to Redis")?;
+    output.clear_line();
+    output.success("Connected to Redis");
+
+    let mut failures = 0u64;
+    for schema in &schemas {
+        let definition = index_definition(schema, prefix);
+        output.heading(&format!("{} ({})", schema.entity, definition.name));
+
+        let report = index_health(&mut conn, std::slice::from_ref(&definition))
+            .await
+            .with_context(|| format!("Failed to inspect {}", definition.name))?;
+        let status = &report.indexes[0];
+        if status.drift.is_empty() {
+            output.info("Index matches the entity definition");
+        }
+        for drift in &status.drift {
+            output.bullet(&describe_drift(drift));
+        }
+        let before = status.info.as_ref().map(|info| info.num_docs);
+
+        rebuild_index(&mut conn, &definition)
+            .await
+            .with_context(|| format!("Failed to rebuild {}", definition.name))?;
+
+        // The new index backfills existing keys in the background
+        let after = loop {
+            let info = index_info(&mut conn, &definition.name)
+                .await
+                .with_context(|| format!("Failed to read {}", definition.name))?;
+            if !info.indexing {
+                break info;
+            }
+            let percent = info.percent_indexed.unwrap_or(0.0) * 100.0;
+            output.progress(&format!("Indexing {}... {percent:.0}%", definition.name));
+            tokio::time::sleep(REINDEX_POLL_INTERVAL).await;
+        };
+        output.clear_line();
+
+        match before {
+            Some(before) => output.success(&format!("Rebuilt: {before} -> {} document(s)", after.num_docs)),
+            None => output.success(&format!("Created: {} document(s)", after.num_docs)),
+        }
+        if after.indexing_failures > 0 {
+            failures += after.indexing_failures;
+            output.warning(&format!(
+                "{} document(s) failed to index; check their field types",
+                after.indexing_failures
+            ));
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{failures} document(s) failed to index");
+    }
+    Ok(())
+}
+
+fn describe_drift(drift: &IndexDrift) -> String {
+    match drift {
+        IndexDrift::Missing => "Index does not exist".to_string(),
+        IndexDrift::StorageMismatch { declared, actual } => {
+            format!("Storage: declared {declared:?}, found {actual:?}")
+        }
+        IndexDrift::PrefixMismatch { declared, actual } => {
+            format!("Prefixes: declared {declared:?}, found {actual:?}")
+        }
+        IndexDrift::FilterMismatch { declared, actual } => {
+            format!("Filter: declared {declared:?}, found {actual:?}")
+        }
+        IndexDrift::MissingField { field } => format!("Field '{field}' is not indexed"),
+        IndexDrift::UnexpectedField { field } => format!("Field '{field}' is indexed but no longer declared"),
+        IndexDrift::FieldMismatch { field, declared, actual } => {
+            format!("Field '{field}': declared {declared}, found {actual}")
+        }
+    }
+}
+
+/// How long to wait for a burst of file events, such as an editor's save, to settle.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+async fn handle_watch(ctx: &ProjectContext, generate: bool, name: &str, output: &OutputManager) -> Result<()> {
+    let (events, changes) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(events).context("Failed to start the file watcher")?;
+    let mut watched = Vec::new();
+    for dir in SCAN_DIRS {
+        let path = ctx.project_root.join(dir);
+        if path.exists() {
+            watcher
+                .watch(&path, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", path.display()))?;
+            watched.push(*dir);
+        }
+    }
+    if watched.is_empty() {
+        anyhow::bail!("Nothing to watch: the project has none of {}", SCAN_DIRS.join(", "));
+    }
+
+    output.info(&format!("Watching {} for entity changes (Ctrl+C to stop)", watched.join(", ")));
+    watch_check(ctx, generate, name, output).await;
+    loop {
+        let changed = tokio::task::block_in_place(|| wait_for_changes(&changes, ctx))?;
+        let changed: Vec<String> = changed
+            .iter()
+            .map(|path| path.strip_prefix(&ctx.project_root).unwrap_or(path).display().to_string())
+            .collect();
+        output.info(&format!("Changed: {}", changed.join(", ")));
+        watch_check(ctx, generate, name, output).await;
+    }
+}
+
+/// Print the pending changes, or generate a migration for them. Errors are reported rather
+/// than returned, so a half-saved file doesn't end the watch.
+async fn watch_check(ctx: &ProjectContext, generate: bool, name: &str, output: &OutputManager) {
+    let result = if generate {
+        handle_create(ctx, name, output).await
+    } else {
+        handle_diff(ctx, None, output).await
+    };
+    if let Err(err) = result {
+        output.error(&format!("{err:#}"));
+    }
+}
+
+/// Block until an entity source file changes, then collect the rest of the burst.
+fn wait_for_changes(changes: &Receiver<notify::Result<Event>>, ctx: &ProjectContext) -> Result<BTreeSet<PathBuf>> {
+    let mut changed = BTreeSet::new();
+    loop {
+        let next = if changed.is_empty() {
+            changes.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            changes.recv_timeout(WATCH_DEBOUNCE)
+        };
+        match next {
+            Ok(Ok(event)) if !event.kind.is_access() => changed.extend(
+                event
+                    .paths
+                    .into_iter()
+                    .filter(|path| is_entity_source(path, &ctx.project_root, &ctx.migrations_dir)),
+            ),
+            // Watcher errors (e.g. a removed directory) don't change the schema
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) => return Ok(changed),
+            Err(RecvTimeoutError::Disconnected) => anyhow::bail!("The file watcher stopped"),
+        }
+    }
+}
+
+/// Whether a change to `path` can change entity schemas: Rust sources outside hidden
+/// directories, except the generated migrations.
+fn is_entity_source(path: &Path, project_root: &Path, migrations_dir: &Path) -> bool {
+    if path.extension().is_none_or(|ext| ext != "rs") || path.starts_with(migrations_dir) {
+        return false;
+    }
+    let relative = path.strip_prefix(project_root).unwrap_or(path);
+    !relative
+        .components()
+        .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Parse every entity file in the project, along with the enums declared in them.
+///
+/// Files that fail to parse are skipped with a warning, and a hint is shown when no entities
+/// are found.
+pub(crate) fn scan_project(ctx: &ProjectContext, output: &OutputManager) -> Result<(Vec<EntitySchema>, Vec<EnumSchema>)> {
+    let discovered = discover_entities(&ctx.project_root)
+        .context("Failed to discover entity files")?;
+
+    let mut schemas = Vec::new();
+    let mut enums = Vec::new();
+    for file in &discovered {
+        let parsed = parse_entity_file(&file.path, &file.relative_path)
+            .and_then(|file_schemas| Ok((file_schemas, parse_enum_file(&file.path)?)));
+        match parsed {
+            Ok((file_schemas, file_enums)) => {
+                schemas.extend(file_schemas);
+                enums.extend(file_enums);
+            }
+            Err(err) => {
+                output.warning(&format!(
+                    "Failed to parse {}: {err}",
+                    file.relative_path
+                ));
+            }
+        }
+    }
+
+    if schemas.is_empty() {
+        output.warning("No SnugomEntity types found in project");
+        output.info("Make sure your entities use #[derive(SnugomEntity)]");
+    }
+
+    Ok((schemas, enums))
+}
+
+fn write_output_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
 /// Duplicate value information.
 struct DuplicateValue {
     value: String,
@@ -594,7 +977,7 @@ async fn validate_field_uniqueness(
     case_insensitive: bool,
     output: &OutputManager,
 ) -> Result<ValidationResult> {
-    let pattern = format!("{collection}:*");
+    let pattern = format!("{collection}{}*", key_separator());
     let json_path = format!("$.{field}");
 
     // Map: value -> list of document keys
@@ -689,7 +1072,7 @@ async fn validate_field_uniqueness(
     }
 
     // Sort by count descending
-    duplicates.sort_by(|a, b| b.count.cmp(&a.count));
+    duplicates.sort_by_key(|duplicate| std::cmp::Reverse(duplicate.count));
 
     Ok(ValidationResult {
         total_documents,
@@ -702,6 +1085,19 @@ async fn validate_field_uniqueness(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_entity_source() {
+        let root = Path::new("/work/app");
+        let migrations = root.join("src/migrations");
+        assert!(is_entity_source(&root.join("src/models/guild.rs"), root, &migrations));
+        assert!(is_entity_source(&root.join("tests/fixtures.rs"), root, &migrations));
+        assert!(!is_entity_source(&root.join("src/models/guild.rs.swp"), root, &migrations));
+        assert!(!is_entity_source(&root.join("src/migrations/_20241228_100000_init.rs"), root, &migrations));
+        assert!(!is_entity_source(&root.join("src/.backup/guild.rs"), root, &migrations));
+        // Hidden directories above the project don't hide it
+        assert!(is_entity_source(Path::new("/home/dev/.work/app/src/lib.rs"), Path::new("/home/dev/.work/app"), &migrations));
+    }
+
     #[test]
     fn test_to_snake_case() {
         assert_eq!(to_snake_case("User"), "user");
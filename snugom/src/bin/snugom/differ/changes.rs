@@ -84,10 +84,10 @@ pub struct FieldChange {
     pub name: String,
     /// Type of change
     pub change_type: ChangeType,
-    /// Old field info (for modified/removed)
-    pub old_field: Option<FieldInfo>,
+    /// Old field info (for modified/removed), boxed to keep `EntityChange` small
+    pub old_field: Option<Box<FieldInfo>>,
     /// New field info (for added/modified)
-    pub new_field: Option<FieldInfo>,
+    pub new_field: Option<Box<FieldInfo>>,
 }
 
 #[allow(dead_code)]
@@ -98,7 +98,7 @@ impl FieldChange {
             name: field.name.clone(),
             change_type: ChangeType::Added,
             old_field: None,
-            new_field: Some(field),
+            new_field: Some(Box::new(field)),
         }
     }
 
@@ -107,7 +107,7 @@ impl FieldChange {
         Self {
             name: field.name.clone(),
             change_type: ChangeType::Removed,
-            old_field: Some(field),
+            old_field: Some(Box::new(field)),
             new_field: None,
         }
     }
@@ -117,8 +117,8 @@ impl FieldChange {
         Self {
             name: new.name.clone(),
             change_type: ChangeType::Modified,
-            old_field: Some(old),
-            new_field: Some(new),
+            old_field: Some(Box::new(old)),
+            new_field: Some(Box::new(new)),
         }
     }
 
@@ -621,6 +621,14 @@ mod tests {
             relations: Vec::new(),
             unique_constraints: Vec::new(),
             indexes: Vec::new(),
+            service: None,
+            hash_storage: false,
+            language: None,
+            language_field: None,
+            index_name: None,
+            key_separator: None,
+            key_components: Vec::new(),
+            default_sort: None,
             generated_at: Utc::now(),
             source_file: "test.rs".to_string(),
             source_line: 1,
@@ -927,6 +935,14 @@ mod tests {
             relations,
             unique_constraints: Vec::new(),
             indexes: Vec::new(),
+            service: None,
+            hash_storage: false,
+            language: None,
+            language_field: None,
+            index_name: None,
+            key_separator: None,
+            key_components: Vec::new(),
+            default_sort: None,
             generated_at: Utc::now(),
             source_file: "test.rs".to_string(),
             source_line: 1,
@@ -1050,6 +1066,14 @@ mod tests {
             relations: Vec::new(),
             unique_constraints,
             indexes: Vec::new(),
+            service: None,
+            hash_storage: false,
+            language: None,
+            language_field: None,
+            index_name: None,
+            key_separator: None,
+            key_components: Vec::new(),
+            default_sort: None,
             generated_at: Utc::now(),
             source_file: "test.rs".to_string(),
             source_line: 1,
@@ -1183,6 +1207,14 @@ mod tests {
             }],
             unique_constraints: vec![],
             indexes: vec![],
+            service: None,
+            hash_storage: false,
+            language: None,
+            language_field: None,
+            index_name: None,
+            key_separator: None,
+            key_components: Vec::new(),
+            default_sort: None,
             generated_at: Utc::now(),
             source_file: "test.rs".to_string(),
             source_line: 1,
@@ -1,4 +1,4 @@
-use crate::commands::{init, migrate, schema};
+use crate::commands::{data, init, migrate, query, schema};
 
 #[derive(Clone, Copy)]
 pub struct ExampleGroup {
@@ -26,5 +26,13 @@ pub fn command_examples() -> &'static [CommandExample] {
             name: "schema",
             groups: schema::EXAMPLES,
         },
+        CommandExample {
+            name: "data",
+            groups: data::EXAMPLES,
+        },
+        CommandExample {
+            name: "query",
+            groups: query::EXAMPLES,
+        },
     ]
 }
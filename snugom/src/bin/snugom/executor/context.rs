@@ -4,6 +4,8 @@ use anyhow::{Context as AnyhowContext, Result};
 use redis::AsyncCommands;
 use redis::aio::ConnectionManager;
 use serde_json::Value;
+use snugom::keys::key_separator;
+use tokio::task::JoinSet;
 
 /// Context for executing migrations.
 ///
@@ -51,7 +53,7 @@ impl MigrationContext {
         schema_version: Option<u32>,
         limit: usize,
     ) -> Result<Vec<DocumentInfo>> {
-        let pattern = format!("{}:*", collection);
+        let pattern = format!("{collection}{}*", key_separator());
         let mut documents = Vec::new();
         let mut cursor: u64 = 0;
 
@@ -114,6 +116,68 @@ impl MigrationContext {
         Ok(documents)
     }
 
+    /// Count the keys in `collection`, scanning `count` at a time.
+    pub async fn count_keys(&mut self, collection: &str, count: usize) -> Result<u64> {
+        let mut total = 0;
+        let mut cursor: u64 = 0;
+        loop {
+            let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(format!("{collection}{}*", key_separator()))
+                .arg("COUNT")
+                .arg(count)
+                .query_async(&mut self.conn)
+                .await
+                .context("Failed to scan Redis keys")?;
+            total += keys.len() as u64;
+            cursor = next_cursor;
+            if cursor == 0 {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Scan one batch of `collection`'s documents from `cursor`, returning the next cursor (0
+    /// once the scan is complete), the number of keys scanned, and each JSON document's key and
+    /// schema version. Versions are read over `parallelism` connections.
+    ///
+    /// SCAN cursors stay valid across connections, so a scan can be continued by a later run.
+    pub async fn scan_versions(
+        &mut self,
+        collection: &str,
+        cursor: u64,
+        count: usize,
+        parallelism: usize,
+    ) -> Result<(u64, usize, Vec<(String, Option<u32>)>)> {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{collection}{}*", key_separator()))
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut self.conn)
+            .await
+            .context("Failed to scan Redis keys")?;
+
+        let scanned = keys.len();
+        let mut tasks = JoinSet::new();
+        for (index, chunk) in keys.chunks(chunk_size(scanned, parallelism)).enumerate() {
+            let chunk = chunk.to_vec();
+            let mut conn = self.conn.clone();
+            tasks.spawn(async move { (index, read_versions(&mut conn, chunk).await) });
+        }
+        let mut chunks = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            chunks.push(joined.context("Schema version read task failed")?);
+        }
+        // Keep SCAN order, so the last key is the one checkpoints report
+        chunks.sort_by_key(|(index, _)| *index);
+        let versions = chunks.into_iter().flat_map(|(_, versions)| versions).collect();
+
+        Ok((next_cursor, scanned, versions))
+    }
+
     /// Update a document.
     pub async fn update_document(&mut self, key: &str, data: &Value) -> Result<()> {
         if self.dry_run {
@@ -150,6 +214,30 @@ impl MigrationContext {
         Ok(())
     }
 
+    /// Set the schema version of each of `keys`, pipelined over `parallelism` connections.
+    pub async fn update_schema_versions(&mut self, keys: &[String], new_version: u32, parallelism: usize) -> Result<()> {
+        if self.dry_run || keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut tasks = JoinSet::new();
+        for chunk in keys.chunks(chunk_size(keys.len(), parallelism)) {
+            let mut pipe = redis::pipe();
+            for key in chunk {
+                pipe.cmd("JSON.SET").arg(key).arg("$.__schema_version").arg(new_version).ignore();
+            }
+            let mut conn = self.conn.clone();
+            tasks.spawn(async move { pipe.query_async::<()>(&mut conn).await });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            joined
+                .context("Schema version update task failed")?
+                .context("Failed to update schema versions")?;
+        }
+
+        Ok(())
+    }
+
     /// Delete a document.
     #[allow(dead_code)]
     pub async fn delete_document(&mut self, key: &str) -> Result<()> {
@@ -177,11 +265,38 @@ pub struct DocumentInfo {
     pub data: Value,
 }
 
+/// Size of the chunks that split `len` items across `parallelism` tasks.
+fn chunk_size(len: usize, parallelism: usize) -> usize {
+    len.div_ceil(parallelism.max(1)).max(1)
+}
+
+/// Read the schema version of each of `keys`, skipping keys that aren't JSON documents.
+async fn read_versions(conn: &mut ConnectionManager, keys: Vec<String>) -> Vec<(String, Option<u32>)> {
+    let mut versions = Vec::with_capacity(keys.len());
+    for key in keys {
+        // Keys that aren't JSON documents fail JSON.GET and are skipped
+        let Ok(version_json) = redis::cmd("JSON.GET")
+            .arg(&key)
+            .arg("$.__schema_version")
+            .query_async::<String>(conn)
+            .await
+        else {
+            continue;
+        };
+        // JSON.GET returns an array like [1], or [] when the field is missing
+        let version = serde_json::from_str::<Vec<u32>>(&version_json)
+            .ok()
+            .and_then(|values| values.first().copied());
+        versions.push((key, version));
+    }
+    versions
+}
+
 /// Extract the document ID from a Redis key.
 #[allow(dead_code)]
 fn extract_id_from_key(key: &str) -> String {
     // Key format: collection:id
-    key.split(':').next_back().unwrap_or(key).to_string()
+    key.split(key_separator()).next_back().unwrap_or(key).to_string()
 }
 
 #[cfg(test)]
@@ -212,6 +327,15 @@ mod tests {
         assert_eq!(extract_id_from_key("users:"), "");
     }
 
+    #[test]
+    fn test_chunk_size() {
+        assert_eq!(chunk_size(500, 4), 125);
+        assert_eq!(chunk_size(10, 4), 3);
+        assert_eq!(chunk_size(3, 8), 1);
+        assert_eq!(chunk_size(0, 4), 1);
+        assert_eq!(chunk_size(7, 0), 7);
+    }
+
     #[test]
     fn test_document_info_structure() {
         let doc = DocumentInfo {
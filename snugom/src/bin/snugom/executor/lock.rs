@@ -0,0 +1,161 @@
+//! Advisory lock that keeps concurrent `migrate deploy` runs from overlapping.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use redis::aio::ConnectionManager;
+use redis::Script;
+use tokio::task::JoinHandle;
+
+/// Key holding the current lock owner.
+const MIGRATION_LOCK_KEY: &str = "_snugom:migrations:lock";
+
+/// Expiry of the lock, so a crashed runner cannot hold it forever.
+const LOCK_TTL: Duration = Duration::from_secs(30);
+
+/// How often the holder extends the expiry; a few beats fit in one TTL.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often a waiting runner retries.
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Extend the expiry only while the key still holds our token.
+const RENEW_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// Delete the key only while it still holds our token.
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// A held migration lock, kept alive by a background heartbeat until released or dropped.
+pub struct MigrationLock {
+    conn: ConnectionManager,
+    token: String,
+    lost: Arc<AtomicBool>,
+    heartbeat: JoinHandle<()>,
+}
+
+impl MigrationLock {
+    /// Take the lock, waiting up to `timeout` for another runner to release it.
+    pub async fn acquire(mut conn: ConnectionManager, timeout: Duration) -> Result<Self> {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown host".to_string());
+        let token = format!("pid {} on {host} ({})", std::process::id(), nanoid::nanoid!(8));
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let acquired: Option<String> = redis::cmd("SET")
+                .arg(MIGRATION_LOCK_KEY)
+                .arg(&token)
+                .arg("NX")
+                .arg("PX")
+                .arg(LOCK_TTL.as_millis() as u64)
+                .query_async(&mut conn)
+                .await
+                .context("Failed to acquire the migration lock")?;
+            if acquired.is_some() {
+                break;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                let holder: Option<String> = redis::cmd("GET")
+                    .arg(MIGRATION_LOCK_KEY)
+                    .query_async(&mut conn)
+                    .await
+                    .unwrap_or(None);
+                bail!(held_message(holder.as_deref(), timeout));
+            }
+            tokio::time::sleep(RETRY_INTERVAL.min(deadline - now)).await;
+        }
+
+        let lost = Arc::new(AtomicBool::new(false));
+        let heartbeat = tokio::spawn(heartbeat(conn.clone(), token.clone(), lost.clone()));
+        Ok(Self {
+            conn,
+            token,
+            lost,
+            heartbeat,
+        })
+    }
+
+    /// Fail if the lock expired or was taken over since it was acquired, e.g. because Redis was
+    /// unreachable for longer than the TTL.
+    pub fn ensure_held(&self) -> Result<()> {
+        if self.lost.load(Ordering::Relaxed) {
+            bail!("Lost the migration lock; another runner may be deploying. Re-run 'snugom migrate deploy'");
+        }
+        Ok(())
+    }
+
+    /// Release the lock so other runners can proceed without waiting for it to expire.
+    pub async fn release(mut self) -> Result<()> {
+        self.heartbeat.abort();
+        let _: i64 = Script::new(RELEASE_SCRIPT)
+            .key(MIGRATION_LOCK_KEY)
+            .arg(&self.token)
+            .invoke_async(&mut self.conn)
+            .await
+            .context("Failed to release the migration lock")?;
+        Ok(())
+    }
+}
+
+impl Drop for MigrationLock {
+    fn drop(&mut self) {
+        // An unreleased lock expires after its TTL
+        self.heartbeat.abort();
+    }
+}
+
+async fn heartbeat(mut conn: ConnectionManager, token: String, lost: Arc<AtomicBool>) {
+    let script = Script::new(RENEW_SCRIPT);
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        let renewed: redis::RedisResult<i64> = script
+            .key(MIGRATION_LOCK_KEY)
+            .arg(&token)
+            .arg(LOCK_TTL.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await;
+        // Errors are retried on the next beat, which still lands inside the TTL
+        if let Ok(0) = renewed {
+            lost.store(true, Ordering::Relaxed);
+            return;
+        }
+    }
+}
+
+fn held_message(holder: Option<&str>, timeout: Duration) -> String {
+    let holder = holder.map(|holder| format!(" ({holder})")).unwrap_or_default();
+    format!(
+        "Another migration runner{holder} holds the migration lock; gave up after {}s. \
+         Wait for it to finish, or raise --lock-timeout. A crashed runner's lock expires after {}s",
+        timeout.as_secs(),
+        LOCK_TTL.as_secs()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_held_message() {
+        let message = held_message(Some("pid 42 on web-2 (x1)"), Duration::from_secs(60));
+        assert!(message.starts_with("Another migration runner (pid 42 on web-2 (x1)) holds the migration lock"));
+        assert!(message.contains("gave up after 60s"));
+
+        let message = held_message(None, Duration::ZERO);
+        assert!(message.starts_with("Another migration runner holds the migration lock; gave up after 0s"));
+    }
+}
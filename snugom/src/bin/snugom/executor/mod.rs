@@ -4,13 +4,16 @@
 //! - `MigrationContext` - Redis connection and document access
 //! - `MigrationRunner` - Executes pending migrations
 //! - `MigrationState` - Tracks applied migrations in Redis
+//! - `MigrationLock` - Keeps concurrent deploys from overlapping
 
 mod context;
+mod lock;
 mod runner;
 pub mod state;
 
 pub use context::MigrationContext;
+pub use lock::MigrationLock;
 #[allow(unused_imports)]
-pub use runner::{MigrationRunner, MigrationStats};
+pub use runner::{MigrationRunner, MigrationStats, ResumeMode, DEFAULT_BATCH_SIZE, DEFAULT_PARALLELISM};
 #[allow(unused_imports)]
 pub use state::{AppliedMigration, MigrationState};
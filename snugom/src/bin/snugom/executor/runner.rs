@@ -1,13 +1,22 @@
 //! Migration runner for executing pending migrations.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
+use std::collections::HashMap;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use super::context::MigrationContext;
-use super::state::{calculate_checksum, AppliedMigration, MigrationState};
+use super::lock::MigrationLock;
+use super::state::{calculate_checksum, AppliedMigration, MigrationCheckpoint, MigrationState};
 use crate::output::OutputManager;
+use crate::utils::format_duration;
+
+/// Keys scanned per round trip during a document pass, unless `--batch-size` says otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Connections a document pass reads and writes over, unless `--parallelism` says otherwise.
+pub const DEFAULT_PARALLELISM: usize = 4;
 
 /// Statistics from a migration run.
 #[derive(Debug, Clone, Default)]
@@ -34,6 +43,29 @@ pub struct MigrationInfo {
     pub path: std::path::PathBuf,
     /// File checksum
     pub checksum: String,
+    /// Migration type from the header (e.g., "AUTO", "STUB")
+    pub migration_type: Option<String>,
+    /// Entities the migration moves to a new schema version
+    pub targets: Vec<MigrationTarget>,
+}
+
+/// An entity and the schema version a migration moves its documents to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationTarget {
+    pub entity: String,
+    pub version: u32,
+}
+
+/// What to do with checkpoints left by an interrupted deploy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResumeMode {
+    /// Resume from a checkpoint when there is one
+    #[default]
+    Auto,
+    /// Fail unless the first pending migration has a checkpoint
+    Resume,
+    /// Discard checkpoints and start each migration over
+    Restart,
 }
 
 /// Migration runner.
@@ -41,6 +73,11 @@ pub struct MigrationRunner {
     ctx: MigrationContext,
     state: MigrationState,
     dry_run: bool,
+    resume: ResumeMode,
+    /// Collection of each entity in the project, for document passes
+    collections: HashMap<String, String>,
+    batch_size: usize,
+    parallelism: usize,
 }
 
 impl MigrationRunner {
@@ -58,9 +95,32 @@ impl MigrationRunner {
             ctx,
             state,
             dry_run,
+            resume: ResumeMode::Auto,
+            collections: HashMap::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            parallelism: DEFAULT_PARALLELISM,
         })
     }
 
+    /// Set how checkpoints of interrupted migrations are handled.
+    pub fn with_resume(mut self, resume: ResumeMode) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Set the collection of each entity, keyed by entity name.
+    pub fn with_collections(mut self, collections: HashMap<String, String>) -> Self {
+        self.collections = collections;
+        self
+    }
+
+    /// Set the keys scanned per batch and the connections each batch is spread over.
+    pub fn with_batching(mut self, batch_size: usize, parallelism: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
     /// Discover migration files from the migrations directory.
     pub fn discover_migrations(migrations_dir: &Path) -> Result<Vec<MigrationInfo>> {
         let mut migrations = Vec::new();
@@ -93,6 +153,7 @@ impl MigrationRunner {
                 let content = std::fs::read_to_string(&path)
                     .with_context(|| format!("Failed to read migration: {}", path.display()))?;
                 let checksum = calculate_checksum(&content);
+                let (targets, migration_type) = parse_header(&content);
 
                 // Create display name (remove leading underscore if present)
                 let display_name = module_name.strip_prefix('_').unwrap_or(module_name);
@@ -102,6 +163,8 @@ impl MigrationRunner {
                     display_name: display_name.to_string(),
                     path: path.clone(),
                     checksum,
+                    migration_type,
+                    targets,
                 });
             }
         }
@@ -112,10 +175,11 @@ impl MigrationRunner {
         Ok(migrations)
     }
 
-    /// Run all pending migrations.
+    /// Run all pending migrations, checking before each one that `lock` is still held.
     pub async fn run_all(
         &mut self,
         migrations_dir: &Path,
+        lock: Option<&MigrationLock>,
         output: &OutputManager,
     ) -> Result<MigrationStats> {
         let start_time = Instant::now();
@@ -161,20 +225,31 @@ impl MigrationRunner {
         }
 
         // Run each pending migration
-        for migration in pending {
+        for (position, migration) in pending.into_iter().enumerate() {
+            if let Some(lock) = lock {
+                lock.ensure_held()?;
+            }
             let migration_start = Instant::now();
 
             output.heading(&format!("Applying: {}", migration.display_name));
+            output.bullet(&format!(
+                "Migration type: {}",
+                migration.migration_type.as_deref().unwrap_or("UNKNOWN")
+            ));
 
-            // For now, we just record the migration as applied
-            // The actual document transformation would require compiling and running the migration code
-            // which is beyond the scope of a CLI tool
-            //
-            // In a full implementation, migrations would be registered at compile time
-            // and the CLI would invoke them through the compiled application
-
-            output.bullet("Migration type: BASELINE/AUTO");
-            output.bullet("Documents: 0 (placeholder)");
+            // Transforms are compiled into the application, so the CLI only moves documents to
+            // the new schema version, and leaves migrations that need a hand-written transform
+            // to the application
+            let documents = if migration.needs_transform() {
+                output.bullet("Documents: left to the application's transform");
+                0
+            } else {
+                let checkpoint = self.checkpoint_for(migration, position == 0, output).await?;
+                let documents = self.migrate_documents(migration, checkpoint, lock, output).await?;
+                output.bullet(&format!("Documents: {documents} moved to the new schema version"));
+                documents
+            };
+            stats.documents_transformed += documents;
 
             let migration_time = migration_start.elapsed().as_millis() as u64;
 
@@ -184,10 +259,11 @@ impl MigrationRunner {
                     applied_at: Utc::now(),
                     checksum: migration.checksum.clone(),
                     execution_time_ms: migration_time,
-                    documents_affected: 0,
+                    documents_affected: documents,
                     dry_run: false,
                 };
                 self.state.record_applied(record).await?;
+                self.state.clear_checkpoint(&migration.display_name).await?;
             }
 
             output.success(&format!(
@@ -204,6 +280,118 @@ impl MigrationRunner {
         Ok(stats)
     }
 
+    /// The checkpoint to start `migration`'s document pass from, following the resume mode.
+    async fn checkpoint_for(
+        &mut self,
+        migration: &MigrationInfo,
+        first: bool,
+        output: &OutputManager,
+    ) -> Result<MigrationCheckpoint> {
+        let name = migration.display_name.as_str();
+        match self.state.load_checkpoint(name).await? {
+            Some(_) if self.resume == ResumeMode::Restart => {
+                if !self.dry_run {
+                    self.state.clear_checkpoint(name).await?;
+                }
+                output.bullet("Discarded checkpoint; starting over");
+            }
+            Some(checkpoint) if checkpoint.checksum != migration.checksum => {
+                bail!("{name} was interrupted, but its file has changed since; run with --restart to start it over");
+            }
+            Some(checkpoint) => {
+                let last_key = checkpoint.last_key.as_deref().map(|key| format!(" (last key {key})")).unwrap_or_default();
+                output.bullet(&format!("Resuming after {} document(s){last_key}", checkpoint.documents));
+                return Ok(checkpoint);
+            }
+            None if first && self.resume == ResumeMode::Resume => {
+                bail!("Nothing to resume: {name} has no checkpoint");
+            }
+            None => {}
+        }
+        Ok(MigrationCheckpoint::start(name, &migration.checksum))
+    }
+
+    /// Move the documents of each target entity to its new schema version, saving a checkpoint
+    /// after every batch. Returns the documents moved, including those moved before a resume.
+    async fn migrate_documents(
+        &mut self,
+        migration: &MigrationInfo,
+        mut checkpoint: MigrationCheckpoint,
+        lock: Option<&MigrationLock>,
+        output: &OutputManager,
+    ) -> Result<u64> {
+        while let Some(target) = migration.targets.get(checkpoint.target) {
+            let Some(collection) = self.collections.get(&target.entity).cloned() else {
+                output.warning(&format!(
+                    "{} is no longer an entity in this project; skipping its documents",
+                    target.entity
+                ));
+                checkpoint.target += 1;
+                checkpoint.cursor = 0;
+                checkpoint.scanned = 0;
+                continue;
+            };
+
+            // A key-only scan is cheap next to the pass, and gives the progress bar its total
+            let total = self.ctx.count_keys(&collection, self.batch_size).await?;
+            let started = Instant::now();
+            let resumed_at = checkpoint.scanned;
+            loop {
+                if let Some(lock) = lock {
+                    lock.ensure_held()?;
+                }
+                let (next_cursor, scanned, versions) = self
+                    .ctx
+                    .scan_versions(&collection, checkpoint.cursor, self.batch_size, self.parallelism)
+                    .await?;
+                let outdated: Vec<String> = versions
+                    .iter()
+                    .filter(|(_, version)| version.is_none_or(|version| version < target.version))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                self.ctx
+                    .update_schema_versions(&outdated, target.version, self.parallelism)
+                    .await?;
+
+                checkpoint.documents += outdated.len() as u64;
+                checkpoint.scanned += scanned as u64;
+                if let Some((key, _)) = versions.into_iter().next_back() {
+                    checkpoint.last_key = Some(key);
+                }
+                checkpoint.cursor = next_cursor;
+                if next_cursor == 0 {
+                    break;
+                }
+                self.save_checkpoint(&mut checkpoint).await?;
+
+                let rate = throughput(
+                    checkpoint.scanned - resumed_at,
+                    started.elapsed(),
+                    total.saturating_sub(checkpoint.scanned),
+                );
+                output.progress_bar(&format!("{collection} ({rate})"), checkpoint.scanned, total);
+            }
+            output.clear_line();
+
+            // Record the finished collection so a resume doesn't scan it again
+            checkpoint.target += 1;
+            checkpoint.cursor = 0;
+            checkpoint.scanned = 0;
+            self.save_checkpoint(&mut checkpoint).await?;
+            output.bullet(&format!("{} ({collection}) -> v{}", target.entity, target.version));
+        }
+
+        Ok(checkpoint.documents)
+    }
+
+    async fn save_checkpoint(&mut self, checkpoint: &mut MigrationCheckpoint) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        checkpoint.updated_at = Utc::now();
+        self.state.save_checkpoint(checkpoint).await
+    }
+
     /// Get the migration state manager.
     #[allow(dead_code)]
     pub fn state(&mut self) -> &mut MigrationState {
@@ -211,12 +399,57 @@ impl MigrationRunner {
     }
 
     /// Get the migration context.
-    #[allow(dead_code)]
     pub fn context(&mut self) -> &mut MigrationContext {
         &mut self.ctx
     }
 }
 
+impl MigrationInfo {
+    /// Whether documents need a hand-written transform, which only the application can run.
+    fn needs_transform(&self) -> bool {
+        matches!(self.migration_type.as_deref(), Some("STUB" | "COMPLEX"))
+    }
+}
+
+/// Keys per second, and the time `remaining` keys take at that rate.
+fn throughput(keys: u64, elapsed: Duration, remaining: u64) -> String {
+    if keys == 0 {
+        return "0 keys/s".to_string();
+    }
+    let rate = keys as f64 / elapsed.as_secs_f64().max(0.001);
+    let eta = Duration::from_secs_f64(remaining as f64 / rate);
+    format!("{rate:.0} keys/s, ETA {}", format_duration(eta))
+}
+
+/// Read the target entities and the migration type from a generated migration's header:
+///
+/// ```text
+/// //   Guild (NEW - baseline v1)
+/// //   User (v1 → v2):
+/// // Migration type: AUTO
+/// ```
+fn parse_header(content: &str) -> (Vec<MigrationTarget>, Option<String>) {
+    let mut targets = Vec::new();
+    let mut migration_type = None;
+    for line in content.lines().take_while(|line| line.starts_with("//")) {
+        if let Some(kind) = line.strip_prefix("// Migration type: ") {
+            migration_type = Some(kind.trim().to_string());
+        } else if let Some(entry) = line.strip_prefix("//   ").filter(|entry| !entry.starts_with(' '))
+            && let Some((entity, detail)) = entry.split_once(" (")
+            && let Some(version) = detail
+                .rsplit('v')
+                .next()
+                .and_then(|version| version.trim_end_matches([')', ':']).parse().ok())
+        {
+            targets.push(MigrationTarget {
+                entity: entity.to_string(),
+                version,
+            });
+        }
+    }
+    (targets, migration_type)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -331,6 +564,47 @@ mod tests {
         assert_ne!(migrations[0].checksum, migrations[1].checksum);
     }
 
+    #[test]
+    fn test_parse_header() {
+        let content = "// src/migrations/_20241228_143000_add_avatar.rs\n\
+            //\n\
+            // Migration: 20241228_143000_add_avatar\n\
+            // Generated: 2024-12-28T14:30:00Z\n\
+            //\n\
+            // Changes:\n\
+            //   Guild (NEW - baseline v1)\n\
+            //   User (v1 → v2):\n\
+            //     + avatar_url: Option<String>\n\
+            //\n\
+            // Migration type: AUTO\n\
+            \n\
+            //   Ignored (v1 → v9):\n";
+
+        let (targets, migration_type) = parse_header(content);
+        assert_eq!(
+            targets,
+            vec![
+                MigrationTarget { entity: "Guild".to_string(), version: 1 },
+                MigrationTarget { entity: "User".to_string(), version: 2 },
+            ]
+        );
+        assert_eq!(migration_type.as_deref(), Some("AUTO"));
+    }
+
+    #[test]
+    fn test_parse_header_without_header() {
+        let (targets, migration_type) = parse_header("pub fn register() {}");
+        assert!(targets.is_empty());
+        assert_eq!(migration_type, None);
+    }
+
+    #[test]
+    fn test_throughput() {
+        assert_eq!(throughput(0, Duration::from_secs(1), 1000), "0 keys/s");
+        assert_eq!(throughput(5000, Duration::from_secs(2), 10_000), "2500 keys/s, ETA 4s");
+        assert_eq!(throughput(1000, Duration::from_secs(1), 0), "1000 keys/s, ETA 0s");
+    }
+
     #[test]
     fn test_migration_stats_default() {
         let stats = MigrationStats::default();
@@ -8,6 +8,9 @@ use serde::{Deserialize, Serialize};
 /// Key prefix for migration state.
 const MIGRATION_STATE_KEY: &str = "_snugom:migrations";
 
+/// Key prefix for checkpoints of interrupted migrations, one key per migration.
+const CHECKPOINT_KEY_PREFIX: &str = "_snugom:migrations:checkpoint:";
+
 /// Applied migration record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppliedMigration {
@@ -25,6 +28,45 @@ pub struct AppliedMigration {
     pub dry_run: bool,
 }
 
+/// Progress of a migration's document pass, saved after every batch so an interrupted deploy
+/// can resume where it stopped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationCheckpoint {
+    /// Migration name
+    pub name: String,
+    /// Checksum of the migration file the progress was made with
+    pub checksum: String,
+    /// Index of the entity being processed, in the migration's order
+    pub target: usize,
+    /// SCAN cursor to continue from within that entity's collection
+    pub cursor: u64,
+    /// Keys scanned so far in that entity's collection
+    #[serde(default)]
+    pub scanned: u64,
+    /// Last key processed
+    pub last_key: Option<String>,
+    /// Documents updated so far
+    pub documents: u64,
+    /// When the checkpoint was saved
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MigrationCheckpoint {
+    /// A checkpoint at the start of `name`'s document pass.
+    pub fn start(name: &str, checksum: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            checksum: checksum.to_string(),
+            target: 0,
+            cursor: 0,
+            scanned: 0,
+            last_key: None,
+            documents: 0,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
 /// Migration state manager.
 pub struct MigrationState {
     conn: ConnectionManager,
@@ -141,6 +183,44 @@ impl MigrationState {
         self.remove_applied(name).await
     }
 
+    /// Load the checkpoint left by an interrupted run of `name`.
+    pub async fn load_checkpoint(&mut self, name: &str) -> Result<Option<MigrationCheckpoint>> {
+        let data: Option<String> = redis::cmd("GET")
+            .arg(format!("{CHECKPOINT_KEY_PREFIX}{name}"))
+            .query_async(&mut self.conn)
+            .await
+            .context("Failed to load migration checkpoint")?;
+
+        data.map(|json_str| serde_json::from_str(&json_str).context("Failed to parse migration checkpoint"))
+            .transpose()
+    }
+
+    /// Save progress of a migration's document pass.
+    pub async fn save_checkpoint(&mut self, checkpoint: &MigrationCheckpoint) -> Result<()> {
+        let checkpoint_json = serde_json::to_string(checkpoint)
+            .context("Failed to serialize migration checkpoint")?;
+
+        let _: () = redis::cmd("SET")
+            .arg(format!("{CHECKPOINT_KEY_PREFIX}{}", checkpoint.name))
+            .arg(&checkpoint_json)
+            .query_async(&mut self.conn)
+            .await
+            .context("Failed to save migration checkpoint")?;
+
+        Ok(())
+    }
+
+    /// Discard the checkpoint of `name`, once it completes or is restarted or resolved.
+    pub async fn clear_checkpoint(&mut self, name: &str) -> Result<()> {
+        let _: () = redis::cmd("DEL")
+            .arg(format!("{CHECKPOINT_KEY_PREFIX}{name}"))
+            .query_async(&mut self.conn)
+            .await
+            .context("Failed to clear migration checkpoint")?;
+
+        Ok(())
+    }
+
     /// Get the last applied migration.
     #[allow(dead_code)]
     pub async fn last_applied(&mut self) -> Result<Option<AppliedMigration>> {
@@ -213,6 +293,20 @@ mod tests {
         assert_eq!(deserialized.dry_run, migration.dry_run);
     }
 
+    #[test]
+    fn test_migration_checkpoint_serialization() {
+        let mut checkpoint = MigrationCheckpoint::start("20241228_100000_init", "abc123def");
+        checkpoint.target = 1;
+        checkpoint.cursor = 4096;
+        checkpoint.last_key = Some("users:abc123".to_string());
+        checkpoint.documents = 2500;
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let deserialized: MigrationCheckpoint = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized, checkpoint);
+    }
+
     #[test]
     fn test_applied_migration_dry_run_flag() {
         let dry_run_migration = AppliedMigration {
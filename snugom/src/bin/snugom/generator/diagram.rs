@@ -0,0 +1,289 @@
+//! Entity relationship diagrams rendered from scanned entity schemas.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use clap::ValueEnum;
+
+use crate::scanner::{CascadeStrategy, EntitySchema, FieldInfo, RelationInfo, RelationKind};
+
+/// Diagram output formats
+#[derive(Clone, Copy, Debug, ValueEnum, Default, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// Mermaid `erDiagram`, rendered inline by GitHub and most doc sites
+    #[default]
+    Mermaid,
+    /// Graphviz DOT, for `dot -Tsvg`
+    Dot,
+}
+
+/// Render an entity relationship diagram of `schemas`.
+///
+/// Entities list their fields with id, unique, and index markers. Each relation becomes an edge
+/// from the entity declaring it to the entity owning the target collection, labelled with the
+/// field and, unless it is `none`, the cascade policy. Targets outside `schemas` are drawn by
+/// collection name.
+pub fn generate_diagram(schemas: &[EntitySchema], format: DiagramFormat) -> String {
+    let entities: HashMap<String, &str> = schemas
+        .iter()
+        .map(|schema| (schema.collection_name(), schema.entity.as_str()))
+        .collect();
+    let target_node = |relation: &RelationInfo| {
+        entities
+            .get(&relation.target)
+            .map(|entity| entity.to_string())
+            .unwrap_or_else(|| relation.target.clone())
+    };
+
+    match format {
+        DiagramFormat::Mermaid => render_mermaid(schemas, target_node),
+        DiagramFormat::Dot => render_dot(schemas, target_node),
+    }
+}
+
+fn render_mermaid(schemas: &[EntitySchema], target_node: impl Fn(&RelationInfo) -> String) -> String {
+    let mut out = String::from("erDiagram\n");
+
+    for schema in schemas {
+        let _ = writeln!(out, "    {} {{", schema.entity);
+        for field in &schema.fields {
+            let keys = field_keys(field, schema);
+            let notes = field_notes(field);
+            let _ = write!(out, "        {} {}", mermaid_type(&field.field_type), field.name.trim_start_matches("r#"));
+            if !keys.is_empty() {
+                let _ = write!(out, " {}", keys.join(", "));
+            }
+            if !notes.is_empty() {
+                let _ = write!(out, " \"{}\"", notes.join(", "));
+            }
+            out.push('\n');
+        }
+        out.push_str("    }\n");
+    }
+
+    for schema in schemas {
+        for relation in &schema.relations {
+            let cardinality = match relation.kind {
+                RelationKind::BelongsTo => "}o--||",
+                RelationKind::HasMany => "||--o{",
+                RelationKind::ManyToMany => "}o--o{",
+            };
+            let _ = writeln!(
+                out,
+                "    {} {cardinality} {} : \"{}\"",
+                schema.entity,
+                target_node(relation),
+                relation_label(relation, ", ")
+            );
+        }
+    }
+
+    out
+}
+
+fn render_dot(schemas: &[EntitySchema], target_node: impl Fn(&RelationInfo) -> String) -> String {
+    let mut out = String::from("digraph snugom {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    node [shape=record, fontname=\"Helvetica\"];\n");
+    out.push_str("    edge [fontname=\"Helvetica\", fontsize=10];\n");
+
+    for schema in schemas {
+        let mut rows = String::new();
+        for field in &schema.fields {
+            let mut row = format!("{}: {}", field.name.trim_start_matches("r#"), field.field_type);
+            let markers: Vec<String> = field_keys(field, schema)
+                .into_iter()
+                .map(str::to_string)
+                .chain(field_notes(field))
+                .collect();
+            if !markers.is_empty() {
+                let _ = write!(row, " ({})", markers.join(", "));
+            }
+            rows.push_str(&dot_record_escape(&row));
+            rows.push_str("\\l");
+        }
+        let _ = writeln!(
+            out,
+            "    \"{0}\" [label=\"{{{1}|{2}}}\"];",
+            schema.entity,
+            dot_record_escape(&format!("{} ({})", schema.entity, schema.collection_name())),
+            rows
+        );
+    }
+
+    for schema in schemas {
+        for relation in &schema.relations {
+            let arrows = match relation.kind {
+                RelationKind::BelongsTo => "arrowhead=tee",
+                RelationKind::HasMany => "arrowhead=crow",
+                RelationKind::ManyToMany => "dir=both, arrowhead=crow, arrowtail=crow",
+            };
+            let style = match relation.cascade {
+                CascadeStrategy::Delete => ", style=bold",
+                CascadeStrategy::Restrict => ", color=red",
+                CascadeStrategy::None | CascadeStrategy::Detach => "",
+            };
+            let _ = writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}\", {arrows}{style}];",
+                schema.entity,
+                target_node(relation),
+                relation_label(relation, "\\n").replace('"', "\\\"")
+            );
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Key markers: `PK` for the id, `UK` for unique fields, `FK` for belongs_to fields.
+fn field_keys(field: &FieldInfo, schema: &EntitySchema) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if field.id {
+        keys.push("PK");
+    }
+    if field.unique {
+        keys.push("UK");
+    }
+    if schema
+        .relations
+        .iter()
+        .any(|relation| relation.field == field.name && relation.kind == RelationKind::BelongsTo)
+    {
+        keys.push("FK");
+    }
+    keys
+}
+
+/// Index markers, e.g. `filterable(tag)` and `sortable`.
+fn field_notes(field: &FieldInfo) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(filterable) = field.filterable {
+        notes.push(format!("filterable({filterable})"));
+    }
+    if field.sortable {
+        notes.push("sortable".to_string());
+    }
+    notes
+}
+
+fn relation_label(relation: &RelationInfo, separator: &str) -> String {
+    let kind = match relation.kind {
+        RelationKind::BelongsTo => "belongs_to",
+        RelationKind::HasMany => "has_many",
+        RelationKind::ManyToMany => "many_to_many",
+    };
+    let cascade = match relation.cascade {
+        CascadeStrategy::None => None,
+        CascadeStrategy::Detach => Some("detach"),
+        CascadeStrategy::Delete => Some("delete"),
+        CascadeStrategy::Restrict => Some("restrict"),
+    };
+    match cascade {
+        Some(cascade) => format!("{kind} {}{separator}cascade {cascade}", relation.field),
+        None => format!("{kind} {}", relation.field),
+    }
+}
+
+/// Mermaid attribute types only allow word characters, brackets, and parentheses, so generics
+/// are written with parentheses and paths keep only their last segment.
+fn mermaid_type(rust_type: &str) -> String {
+    let mut out = String::new();
+    let mut segment = String::new();
+    let mut chars = rust_type.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                segment.clear();
+            }
+            '<' | '>' | ',' | ' ' => {
+                out.push_str(&segment);
+                segment.clear();
+                match c {
+                    '<' => out.push('('),
+                    '>' => out.push(')'),
+                    ',' => out.push('_'),
+                    _ => {}
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' => segment.push(c),
+            _ => segment.push('_'),
+        }
+    }
+    out.push_str(&segment);
+    out
+}
+
+/// Escape characters that structure a DOT record label.
+fn dot_record_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FilterableType;
+
+    fn schemas() -> Vec<EntitySchema> {
+        let mut guild = EntitySchema::new("Guild".to_string(), "src/models.rs".to_string(), 1);
+        let mut id = FieldInfo::new("id".to_string(), "String".to_string());
+        id.id = true;
+        let mut name = FieldInfo::new("name".to_string(), "String".to_string());
+        name.unique = true;
+        name.filterable = Some(FilterableType::Text);
+        guild.fields = vec![id, name, FieldInfo::new("members".to_string(), "Vec<String>".to_string())];
+        guild.relations.push(RelationInfo {
+            field: "members".to_string(),
+            target: "members".to_string(),
+            kind: RelationKind::HasMany,
+            cascade: CascadeStrategy::Delete,
+        });
+
+        let mut member = EntitySchema::new("Member".to_string(), "src/models.rs".to_string(), 20);
+        member.fields = vec![FieldInfo::new("guild_id".to_string(), "Option<chrono::DateTime<Utc>>".to_string())];
+        member.relations.push(RelationInfo {
+            field: "guild_id".to_string(),
+            target: "guilds".to_string(),
+            kind: RelationKind::BelongsTo,
+            cascade: CascadeStrategy::None,
+        });
+        vec![guild, member]
+    }
+
+    #[test]
+    fn test_mermaid_type() {
+        assert_eq!(mermaid_type("String"), "String");
+        assert_eq!(mermaid_type("Option<chrono::DateTime<Utc>>"), "Option(DateTime(Utc))");
+        assert_eq!(mermaid_type("HashMap<String, u64>"), "HashMap(String_u64)");
+    }
+
+    #[test]
+    fn test_generate_mermaid() {
+        let diagram = generate_diagram(&schemas(), DiagramFormat::Mermaid);
+        assert!(diagram.starts_with("erDiagram\n    Guild {\n        String id PK\n"));
+        assert!(diagram.contains("        String name UK \"filterable(text)\"\n"));
+        assert!(diagram.contains("        Option(DateTime(Utc)) guild_id FK\n"));
+        assert!(diagram.contains("    Guild ||--o{ Member : \"has_many members, cascade delete\"\n"));
+        assert!(diagram.contains("    Member }o--|| Guild : \"belongs_to guild_id\"\n"));
+    }
+
+    #[test]
+    fn test_generate_dot() {
+        let diagram = generate_diagram(&schemas(), DiagramFormat::Dot);
+        assert!(diagram.contains("\"Guild\" [label=\"{Guild (guilds)|id: String (PK)\\lname: String (UK, filterable(text))\\l"));
+        assert!(diagram.contains("guild_id: Option\\<chrono::DateTime\\<Utc\\>\\> (FK)\\l"));
+        assert!(diagram.contains(
+            "\"Guild\" -> \"Member\" [label=\"has_many members\\ncascade delete\", arrowhead=crow, style=bold];"
+        ));
+        assert!(diagram.ends_with("}\n"));
+    }
+}
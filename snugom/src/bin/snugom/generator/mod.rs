@@ -4,10 +4,15 @@
 //! - Generate Rust migration files from schema diffs
 //! - Update source files with new schema versions
 //! - Update migrations/mod.rs with new migration registrations
+//! - Generate TypeScript declarations and relationship diagrams from entity schemas
 
 mod codegen;
+mod diagram;
 mod source_updater;
+mod typescript;
 
 #[allow(unused_imports)]
 pub use codegen::{generate_migration_file, MigrationFile};
 pub use source_updater::{update_migrations_mod, update_source_schema_version};
+pub use diagram::{generate_diagram, DiagramFormat};
+pub use typescript::generate_typescript;
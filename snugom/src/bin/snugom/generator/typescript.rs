@@ -0,0 +1,241 @@
+//! TypeScript declarations generated from scanned entity schemas.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::scanner::{EntitySchema, EnumSchema, RelationInfo, RelationKind};
+
+/// Wrapper matching `snugom::search::PaginatedResponse`.
+const PAGINATED_RESPONSE: &str = "export interface PaginatedResponse<T> {
+  items: T[];
+  total: number;
+  page: number;
+  page_size: number;
+  has_more: boolean;
+}
+";
+
+/// Render TypeScript declarations for `schemas`.
+///
+/// Each entity becomes an interface, an `{Entity}Id` alias for its id, and an `{Entity}Page`
+/// alias over `PaginatedResponse`. Relation fields are typed with the target's id alias when the
+/// target collection is one of `schemas`. `Option<T>` fields are optional and nullable, `enums`
+/// become string unions, and any other non-primitive type becomes `unknown`.
+pub fn generate_typescript(schemas: &[EntitySchema], enums: &[EnumSchema]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// Generated by `snugom schema typescript`. Do not edit by hand.");
+    out.push('\n');
+    out.push_str(PAGINATED_RESPONSE);
+
+    let mut enum_names = HashSet::new();
+    for schema in enums {
+        if !enum_names.insert(schema.name.as_str()) {
+            continue;
+        }
+        let variants: Vec<String> = schema.variants.iter().map(|variant| format!("{variant:?}")).collect();
+        let union = if variants.is_empty() { "never".to_string() } else { variants.join(" | ") };
+        let _ = writeln!(out, "\nexport type {} = {union};", schema.name);
+    }
+
+    let id_aliases: HashMap<String, String> = schemas
+        .iter()
+        .filter(|schema| schema.fields.iter().any(|field| field.id))
+        .map(|schema| (schema.collection_name(), format!("{}Id", schema.entity)))
+        .collect();
+
+    for schema in schemas {
+        out.push('\n');
+        let id_field = schema.fields.iter().find(|field| field.id);
+        if let Some(id_field) = id_field {
+            let id_type = match &id_field.id_prefix {
+                Some(prefix) => format!("`{prefix}_${{string}}`"),
+                None => ts_type(&id_field.field_type, &enum_names),
+            };
+            let _ = writeln!(out, "export type {}Id = {id_type};\n", schema.entity);
+        }
+
+        let _ = writeln!(out, "/** `{}` collection, schema v{} */", schema.collection_name(), schema.schema);
+        let _ = writeln!(out, "export interface {} {{", schema.entity);
+        for field in &schema.fields {
+            let name = field.name.trim_start_matches("r#");
+            let relation = schema.relations.iter().find(|relation| relation.field == field.name);
+            if let Some(relation) = relation {
+                let _ = writeln!(out, "  /** {} `{}` */", relation_kind_name(relation.kind), relation.target);
+            }
+
+            let (optional, rust_type) = match generic_arg(&field.field_type, "Option") {
+                Some(inner) => (true, inner),
+                None => (false, field.field_type.as_str()),
+            };
+            let mut ty = if field.id {
+                format!("{}Id", schema.entity)
+            } else {
+                match relation.and_then(|relation| relation_id_type(relation, rust_type, &id_aliases)) {
+                    Some(ty) => ty,
+                    None => ts_type(rust_type, &enum_names),
+                }
+            };
+            if optional {
+                ty.push_str(" | null");
+            }
+            let _ = writeln!(out, "  {name}{}: {ty};", if optional { "?" } else { "" });
+        }
+        out.push_str("}\n\n");
+        let _ = writeln!(out, "export type {0}Page = PaginatedResponse<{0}>;", schema.entity);
+    }
+
+    out
+}
+
+/// The target's id alias for a `String` or `Vec<String>` relation field.
+fn relation_id_type(relation: &RelationInfo, rust_type: &str, id_aliases: &HashMap<String, String>) -> Option<String> {
+    let alias = id_aliases.get(&relation.target)?;
+    if base_name(rust_type) == "String" {
+        return Some(alias.clone());
+    }
+    let element = generic_arg(rust_type, "Vec")?;
+    (base_name(element) == "String").then(|| format!("{alias}[]"))
+}
+
+fn relation_kind_name(kind: RelationKind) -> &'static str {
+    match kind {
+        RelationKind::BelongsTo => "belongs_to",
+        RelationKind::HasMany => "has_many",
+        RelationKind::ManyToMany => "many_to_many",
+    }
+}
+
+/// Map a Rust type, as written by the scanner, to a TypeScript type.
+fn ts_type(rust_type: &str, enums: &HashSet<&str>) -> String {
+    let base = base_name(rust_type);
+    let args = generic_args(rust_type);
+    match (base, args.as_slice()) {
+        ("Option", [inner]) => format!("{} | null", ts_type(inner, enums)),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+            let element = ts_type(inner, enums);
+            if element.contains(' ') { format!("({element})[]") } else { format!("{element}[]") }
+        }
+        ("HashMap" | "BTreeMap", [_, value]) => format!("Record<string, {}>", ts_type(value, enums)),
+        ("Box" | "Arc" | "Rc", [inner]) => ts_type(inner, enums),
+        ("String" | "str" | "char" | "Uuid" | "DateTime" | "NaiveDate" | "NaiveDateTime" | "NaiveTime", _) => {
+            "string".to_string()
+        }
+        (
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "f32"
+            | "f64",
+            _,
+        ) => "number".to_string(),
+        ("bool", _) => "boolean".to_string(),
+        (name, []) if enums.contains(name) => name.to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Last path segment of a type, without generic arguments (`chrono::DateTime<Utc>` -> `DateTime`).
+fn base_name(rust_type: &str) -> &str {
+    let path = rust_type.split('<').next().unwrap_or(rust_type).trim();
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// The single generic argument of `rust_type` when its base is `wrapper`.
+fn generic_arg<'a>(rust_type: &'a str, wrapper: &str) -> Option<&'a str> {
+    match generic_args(rust_type).as_slice() {
+        [inner] if base_name(rust_type) == wrapper => Some(inner),
+        _ => None,
+    }
+}
+
+/// Top-level generic arguments of a type (`HashMap<String, Vec<u8>>` -> `["String", "Vec<u8>"]`).
+fn generic_args(rust_type: &str) -> Vec<&str> {
+    let Some(start) = rust_type.find('<') else {
+        return Vec::new();
+    };
+    let Some(inner) = rust_type[start + 1..].trim_end().strip_suffix('>') else {
+        return Vec::new();
+    };
+
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut arg_start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(inner[arg_start..i].trim());
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[arg_start..].trim());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{CascadeStrategy, FieldInfo};
+
+    fn field(name: &str, field_type: &str) -> FieldInfo {
+        FieldInfo::new(name.to_string(), field_type.to_string())
+    }
+
+    fn entity(name: &str, collection: &str, fields: Vec<FieldInfo>) -> EntitySchema {
+        let mut schema = EntitySchema::new(name.to_string(), "src/models.rs".to_string(), 1);
+        schema.collection = Some(collection.to_string());
+        schema.fields = fields;
+        schema
+    }
+
+    #[test]
+    fn test_ts_type() {
+        let enums = HashSet::from(["GuildRole"]);
+        assert_eq!(ts_type("String", &enums), "string");
+        assert_eq!(ts_type("chrono::DateTime<Utc>", &enums), "string");
+        assert_eq!(ts_type("u64", &enums), "number");
+        assert_eq!(ts_type("Vec<Option<i32>>", &enums), "(number | null)[]");
+        assert_eq!(ts_type("HashMap<String, Vec<bool>>", &enums), "Record<string, boolean[]>");
+        assert_eq!(ts_type("GuildRole", &enums), "GuildRole");
+        assert_eq!(ts_type("serde_json::Value", &enums), "unknown");
+    }
+
+    #[test]
+    fn test_generate_typescript() {
+        let mut id = field("guild_id", "String");
+        id.id = true;
+        let mut guild = entity(
+            "Guild",
+            "guilds",
+            vec![
+                id,
+                field("role", "GuildRole"),
+                field("motto", "Option<String>"),
+                field("member_ids", "Vec<String>"),
+            ],
+        );
+        guild.relations.push(RelationInfo {
+            field: "member_ids".to_string(),
+            target: "members".to_string(),
+            kind: RelationKind::HasMany,
+            cascade: CascadeStrategy::Delete,
+        });
+        let mut member_id = field("id", "String");
+        member_id.id = true;
+        member_id.id_prefix = Some("mem".to_string());
+        let member = entity("Member", "members", vec![member_id]);
+        let enums = vec![EnumSchema {
+            name: "GuildRole".to_string(),
+            variants: vec!["owner".to_string(), "member".to_string()],
+        }];
+
+        let ts = generate_typescript(&[guild, member], &enums);
+        assert!(ts.contains("export interface PaginatedResponse<T> {"));
+        assert!(ts.contains("export type GuildRole = \"owner\" | \"member\";"));
+        assert!(ts.contains("export type GuildId = string;"));
+        assert!(ts.contains("export type MemberId = `mem_${string}`;"));
+        assert!(ts.contains("  guild_id: GuildId;\n  role: GuildRole;\n  motto?: string | null;\n"));
+        assert!(ts.contains("  /** has_many `members` */\n  member_ids: MemberId[];\n"));
+        assert!(ts.contains("export type MemberPage = PaginatedResponse<Member>;"));
+    }
+}
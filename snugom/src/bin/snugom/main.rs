@@ -16,7 +16,7 @@ use clap::{
         Styles,
     },
     error::ErrorKind,
-    ColorChoice, Command, CommandFactory, FromArgMatches, Parser, Subcommand,
+    ColorChoice, Command, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
 };
 
 use colored::{control::ShouldColorize, Color as ThemeColor, Colorize};
@@ -24,8 +24,10 @@ use std::fmt::Write;
 use std::io::{self, Write as IoWrite};
 
 use commands::{
+    data::{handle_data_commands, DataCommands},
     init::{handle_init, InitArgs},
     migrate::{handle_migrate_commands, MigrateCommands},
+    query::{handle_query, QueryArgs},
     schema::{handle_schema_commands, SchemaCommands},
 };
 use examples::{command_examples, ExampleGroup};
@@ -34,6 +36,9 @@ use theme::{ICONS, THEME};
 
 const ENVIRONMENT_VARIABLES: &[(&str, &str)] = &[
     ("REDIS_URL", "Redis connection URL for migrations"),
+    ("SNUGOM_PREFIX", "Key prefix for data, query, and schema reindex (--prefix)"),
+    ("SNUGOM_KEY_SEPARATOR", "Separator between key segments, if the app installs a KeyScheme (--key-separator)"),
+    ("SNUGOM_KEY_ORDER", "Whether the prefix or the service leads each key (--key-order)"),
 ];
 
 #[derive(Parser)]
@@ -53,6 +58,8 @@ Commands:
   init      Initialize snugom in a project
   migrate   Generate and deploy migrations
   schema    View schema status and differences
+  data      Export, import, snapshot, restore, and copy data
+  query     Search collections interactively
 "#
 )]
 #[command(subcommand_required = true, arg_required_else_help = true)]
@@ -73,6 +80,14 @@ struct Cli {
     #[arg(long)]
     no_color: bool,
 
+    /// Separator between key segments, matching the application's key scheme
+    #[arg(long, env = "SNUGOM_KEY_SEPARATOR", default_value_t = ':')]
+    key_separator: char,
+
+    /// Which of the prefix and service leads each key, matching the application's key scheme
+    #[arg(long, value_enum, env = "SNUGOM_KEY_ORDER", default_value = "prefix-first")]
+    key_order: KeyOrder,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -285,6 +300,25 @@ fn color_to_clap_color(color: ThemeColor) -> ClapColor {
     }
 }
 
+/// Order of the prefix and service at the head of every key
+#[derive(Clone, Copy, Debug, ValueEnum, Default, PartialEq, Eq)]
+enum KeyOrder {
+    /// `prefix:service:...` (default)
+    #[default]
+    PrefixFirst,
+    /// `service:prefix:...`
+    ServiceFirst,
+}
+
+impl From<KeyOrder> for snugom::keys::SegmentOrder {
+    fn from(order: KeyOrder) -> Self {
+        match order {
+            KeyOrder::PrefixFirst => Self::PrefixFirst,
+            KeyOrder::ServiceFirst => Self::ServiceFirst,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize snugom in the current project
@@ -297,6 +331,24 @@ enum Commands {
     /// View schema status, differences, and validate data
     #[command(subcommand)]
     Schema(SchemaCommands),
+
+    /// Export, import, snapshot, restore, and copy data
+    #[command(subcommand)]
+    Data(DataCommands),
+
+    /// Search collections interactively
+    Query(QueryArgs),
+}
+
+impl Commands {
+    /// Whether the command writes its result to stdout, which then must not be padded.
+    fn writes_raw_stdout(&self) -> bool {
+        matches!(
+            self,
+            Commands::Schema(SchemaCommands::Diagram { out: None, .. })
+                | Commands::Data(DataCommands::Export(commands::data::ExportArgs { out: None, .. }))
+        )
+    }
 }
 
 #[tokio::main]
@@ -304,22 +356,33 @@ async fn main() {
     env_logger::init();
 
     let cli = Cli::parse_with_styles();
+    let padded = !cli.command.writes_raw_stdout();
 
-    let _ = print_blank_line_stdout();
+    if padded {
+        let _ = print_blank_line_stdout();
+    }
 
     match execute(cli).await {
         Ok(()) => {
-            let _ = print_blank_line_stdout();
+            if padded {
+                let _ = print_blank_line_stdout();
+            }
         }
         Err(err) => {
             eprintln!("Error: {err}");
-            let _ = print_blank_line_stdout();
+            if padded {
+                let _ = print_blank_line_stdout();
+            }
             std::process::exit(1);
         }
     }
 }
 
 async fn execute(cli: Cli) -> Result<()> {
+    snugom::keys::set_key_scheme(snugom::keys::CustomKeyScheme {
+        separator: cli.key_separator,
+        order: cli.key_order.into(),
+    })?;
     let global_options = GlobalOptions {
         output_format: cli.output,
         quiet: cli.quiet,
@@ -339,6 +402,12 @@ async fn execute(cli: Cli) -> Result<()> {
         Commands::Schema(schema_cmd) => {
             handle_schema_commands(schema_cmd, &output).await?;
         }
+        Commands::Data(data_cmd) => {
+            handle_data_commands(data_cmd, &output).await?;
+        }
+        Commands::Query(args) => {
+            handle_query(args, &output).await?;
+        }
     }
 
     Ok(())
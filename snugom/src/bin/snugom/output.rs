@@ -283,6 +283,39 @@ impl OutputManager {
         std::io::stdout().flush().ok();
     }
 
+    /// Display a progress bar for `done` out of `total` items
+    pub fn progress_bar(&self, message: &str, done: u64, total: u64) {
+        if self.options.quiet || matches!(self.options.output_format, OutputFormat::Json) {
+            return;
+        }
+
+        const WIDTH: u64 = 30;
+        let done = done.min(total);
+        let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        let bar = format!(
+            "[{}{}]",
+            "#".repeat(filled as usize),
+            "-".repeat((WIDTH - filled) as usize)
+        );
+        let counts = format!("{percent:>3}% ({done}/{total})");
+
+        let output = if self.options.no_color {
+            format!("{} {message} {bar} {counts}", ICONS.loading)
+        } else {
+            format!(
+                "{} {} {} {}",
+                ICONS.loading.color(THEME.highlight).bold(),
+                message.color(THEME.highlight),
+                bar.color(THEME.highlight),
+                counts.color(THEME.muted)
+            )
+        };
+
+        print!("\r{output}");
+        std::io::stdout().flush().ok();
+    }
+
     /// Clear the current line (useful after progress indicators)
     pub fn clear_line(&self) {
         if self.options.quiet || matches!(self.options.output_format, OutputFormat::Json) {
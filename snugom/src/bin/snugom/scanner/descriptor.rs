@@ -0,0 +1,558 @@
+//! Runtime entity descriptors and index definitions rebuilt from scanned schemas, for commands
+//! that write documents or indexes without the application's compiled entity types.
+
+use snugom::keys::{KeyContext, KeyOverrides};
+use snugom::search::{IndexDefinition, IndexField, IndexFieldType, SortField, SortOrder};
+use snugom::types::{
+    EntityDescriptor, FieldDescriptor, FieldType, StorageMode, UniqueConstraintDescriptor, ValidationDescriptor,
+    ValidationRule, ValidationScope,
+};
+
+use super::schema::{EntitySchema, FieldInfo, FieldValidation, FilterableType, NestedFieldInfo, RelationKind};
+
+/// Build the descriptor the derive would emit for `schema` in `service`.
+///
+/// Covers what the scanner records: ids, optionality, validations, unique constraints, datetime
+/// mirrors, auto timestamps, enum tag shadows, and storage. Relations, derived ids, compression,
+/// and `lua_check` are left out.
+pub fn entity_descriptor(schema: &EntitySchema, service: &str) -> EntityDescriptor {
+    let mut unique_constraints: Vec<UniqueConstraintDescriptor> = schema
+        .fields
+        .iter()
+        .filter(|field| field.unique)
+        .map(|field| UniqueConstraintDescriptor::single(field.stored_name(), field.unique_case_insensitive))
+        .collect();
+    unique_constraints.extend(
+        schema
+            .unique_constraints
+            .iter()
+            .map(|constraint| {
+                let fields = constraint.fields.iter().map(|name| stored_name(schema, name)).collect();
+                UniqueConstraintDescriptor::compound(fields, constraint.case_insensitive)
+            }),
+    );
+
+    EntityDescriptor {
+        service: service.to_string(),
+        collection: schema.collection_name(),
+        version: schema.schema,
+        id_field: schema.fields.iter().find(|field| field.id).map(|field| field.stored_name().to_string()),
+        id_prefix: schema.id_prefix().map(str::to_string),
+        fields: schema.fields.iter().map(|field| field_descriptor(schema, field)).collect(),
+        unique_constraints,
+        storage: storage_mode(schema),
+        keys: key_overrides(schema),
+        ..Default::default()
+    }
+}
+
+/// The separator and key components `schema` declares in place of the key scheme's.
+fn key_overrides(schema: &EntitySchema) -> KeyOverrides {
+    KeyOverrides {
+        separator: schema.key_separator,
+        components: schema.key_components.clone(),
+    }
+}
+
+/// Name of `schema`'s search index under `prefix` in `service`: its `index_name` override under
+/// the prefix, or the collection's default index name.
+pub fn index_name(schema: &EntitySchema, prefix: &str, service: &str) -> String {
+    let overrides = key_overrides(schema);
+    let keys = KeyContext::new(prefix, service).with_overrides(&overrides);
+    match &schema.index_name {
+        Some(index_name) => format!("{prefix}{}{index_name}", keys.layout().separator),
+        None => keys.index_name(&schema.collection_name()),
+    }
+}
+
+/// Build the index definition the derive would emit for `schema` under `prefix`.
+pub fn index_definition(schema: &EntitySchema, prefix: &str) -> IndexDefinition {
+    let (service, collection) = (schema.service_name(), schema.collection_name());
+    let overrides = key_overrides(schema);
+    let keys = KeyContext::new(prefix, &service).with_overrides(&overrides);
+    let key_prefix = keys.collection_prefix(&collection);
+    let nested = schema.fields.iter().flat_map(|field| &field.nested);
+    let fields: Vec<IndexField> = schema
+        .fields
+        .iter()
+        .flat_map(|field| index_field(field).into_iter().chain(order_index_field(field)))
+        .chain(nested.filter_map(nested_index_field))
+        .collect();
+    IndexDefinition {
+        name: index_name(schema, prefix, &service),
+        // Unique-constraint lookups are hashes under the collection prefix too
+        filter: schema
+            .hash_storage
+            .then(|| format!("!startswith(@__key, \"{}\")", keys.unique_prefix(&collection))),
+        prefixes: vec![key_prefix],
+        // The derive's schema is a static; a command builds a handful of these, so leaking is fine
+        schema: fields.leak(),
+        storage: storage_mode(schema),
+        language: schema.language.clone(),
+        language_field: schema.language_field.as_ref().map(|name| {
+            schema
+                .fields
+                .iter()
+                .find(|field| &field.name == name)
+                .map_or_else(|| name.clone(), |field| field.stored_name().to_string())
+        }),
+    }
+}
+
+/// The index attribute for `field`, following the derive: `searchable` wins over `filterable`,
+/// `sortable` alone infers the type, and auto timestamps are sortable numerics. Datetimes are
+/// indexed through their mirror, tag-filterable enums through their shadow tag, and casefold
+/// tags through their lowercase shadow. An `enum_order` field sorts through its numeric shadow
+/// instead, see [`order_index_field`]. Optional filterable fields index missing values.
+fn index_field(field: &FieldInfo) -> Option<IndexField> {
+    let rust_type = generic_arg(&field.field_type, "Option").unwrap_or(&field.field_type);
+    let field_type = field_type(rust_type);
+    let sortable = field.sortable || field.created_at || field.updated_at;
+    let index_type = if field.searchable {
+        Some(IndexFieldType::Text)
+    } else {
+        field.filterable.map(|filterable| match filterable {
+            FilterableType::Tag => IndexFieldType::Tag,
+            FilterableType::Text => IndexFieldType::Text,
+            FilterableType::Numeric => IndexFieldType::Numeric,
+            FilterableType::Geo => IndexFieldType::Geo,
+        })
+    };
+    let index_type = match index_type {
+        Some(index_type) => index_type,
+        None if sortable => match field_type {
+            FieldType::Number | FieldType::DateTime => IndexFieldType::Numeric,
+            _ => IndexFieldType::Tag,
+        },
+        None => return None,
+    };
+
+    let name = if field_type == FieldType::Object && field.filterable == Some(FilterableType::Tag) {
+        format!("__{}_tag", field.stored_name())
+    } else if field.casefold {
+        format!("__{}_lc", field.stored_name())
+    } else if field.has_datetime_mirror() {
+        format!("{}_ts", field.stored_name())
+    } else {
+        field.stored_name().to_string()
+    };
+    Some(IndexField {
+        path: format!("$.{name}").leak(),
+        field_name: name.leak(),
+        field_type: index_type,
+        sortable: sortable && field.enum_order.is_empty(),
+        index_missing: field.filterable.is_some() && generic_arg(&field.field_type, "Option").is_some(),
+    })
+}
+
+/// The numeric `__{field}_ord` attribute a sortable `enum_order` field is sorted by.
+fn order_index_field(field: &FieldInfo) -> Option<IndexField> {
+    if field.enum_order.is_empty() || !field.sortable {
+        return None;
+    }
+    let name = format!("__{}_ord", field.stored_name());
+    Some(IndexField {
+        path: format!("$.{name}").leak(),
+        field_name: name.leak(),
+        field_type: IndexFieldType::Numeric,
+        sortable: true,
+        index_missing: false,
+    })
+}
+
+/// The index attribute for a `flatten(...)` field: its dotted path, under an underscored name.
+fn nested_index_field(nested: &NestedFieldInfo) -> Option<IndexField> {
+    let field_type = if nested.searchable {
+        IndexFieldType::Text
+    } else {
+        match nested.filterable? {
+            FilterableType::Tag => IndexFieldType::Tag,
+            FilterableType::Text => IndexFieldType::Text,
+            FilterableType::Numeric => IndexFieldType::Numeric,
+            FilterableType::Geo => IndexFieldType::Geo,
+        }
+    };
+    Some(IndexField {
+        path: format!("$.{}", nested.path).leak(),
+        field_name: nested.path.replace('.', "_").leak(),
+        field_type,
+        sortable: nested.sortable,
+        index_missing: false,
+    })
+}
+
+/// Sort fields the derive would emit for `schema`: every sortable index field, numerics
+/// descending by default. `enum_order` fields sort by rank, ascending.
+pub fn sort_fields(schema: &EntitySchema) -> Vec<SortField> {
+    let nested = schema.fields.iter().flat_map(|field| &field.nested).filter_map(|nested| {
+        let index = nested_index_field(nested).filter(|index| index.sortable)?;
+        Some(SortField {
+            name: nested.path.clone().leak(),
+            path: index.field_name,
+            default_order: match index.field_type {
+                IndexFieldType::Numeric => SortOrder::Desc,
+                _ => SortOrder::Asc,
+            },
+        })
+    });
+    schema
+        .fields
+        .iter()
+        .filter_map(|field| {
+            if let Some(index) = order_index_field(field) {
+                return Some(SortField {
+                    name: field.stored_name().to_string().leak(),
+                    path: index.field_name,
+                    default_order: SortOrder::Asc,
+                });
+            }
+            let index = index_field(field).filter(|index| index.sortable)?;
+            let name = field.stored_name().to_string();
+            let path = if field.casefold {
+                format!("__{name}_lc")
+            } else if field.has_datetime_mirror() {
+                format!("{name}_ts")
+            } else {
+                name.clone()
+            };
+            Some(SortField {
+                name: name.leak(),
+                path: path.leak(),
+                default_order: match index.field_type {
+                    IndexFieldType::Numeric => SortOrder::Desc,
+                    _ => SortOrder::Asc,
+                },
+            })
+        })
+        .chain(nested)
+        .collect()
+}
+
+/// The `default_sort` of `schema` among `sorts`, falling back to the first sort field.
+pub fn default_sort(schema: &EntitySchema, sorts: &[SortField]) -> Option<SortField> {
+    let declared = schema.default_sort.as_deref().and_then(|raw| {
+        let (name, descending) = match raw.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (raw, false),
+        };
+        let name = stored_name(schema, name);
+        let mut sort = *sorts.iter().find(|sort| sort.name == name)?;
+        if descending {
+            sort.default_order = SortOrder::Desc;
+        }
+        Some(sort)
+    });
+    declared.or_else(|| sorts.first().copied())
+}
+
+/// The stored name of the field `name` refers to by its Rust name, as container attributes do.
+fn stored_name(schema: &EntitySchema, name: &str) -> String {
+    schema
+        .fields
+        .iter()
+        .find(|field| field.name == name)
+        .map_or(name, FieldInfo::stored_name)
+        .to_string()
+}
+
+fn storage_mode(schema: &EntitySchema) -> StorageMode {
+    if schema.hash_storage { StorageMode::Hash } else { StorageMode::Json }
+}
+
+fn field_descriptor(schema: &EntitySchema, field: &FieldInfo) -> FieldDescriptor {
+    let (optional, rust_type) = match generic_arg(&field.field_type, "Option") {
+        Some(inner) => (true, inner),
+        None => (false, field.field_type.as_str()),
+    };
+    let field_type = field_type(rust_type);
+    let element_type = match field_type {
+        FieldType::Array => element_arg(rust_type).map(field_type_of),
+        _ => None,
+    };
+
+    let mut validations: Vec<ValidationDescriptor> = field
+        .validations
+        .iter()
+        .map(|validation| ValidationDescriptor {
+            scope: ValidationScope::Field,
+            rule: validation_rule(validation),
+        })
+        .collect();
+    if field.unique {
+        validations.push(ValidationDescriptor {
+            scope: ValidationScope::Field,
+            rule: ValidationRule::Unique {
+                case_insensitive: field.unique_case_insensitive,
+            },
+        });
+    }
+
+    let is_relation_vec = field_type == FieldType::Array
+        && schema
+            .relations
+            .iter()
+            .any(|relation| relation.field == field.name && relation.kind != RelationKind::BelongsTo);
+
+    FieldDescriptor {
+        name: field.stored_name().to_string(),
+        optional,
+        is_id: field.id,
+        validations,
+        datetime_mirror: field.has_datetime_mirror().then(|| format!("{}_ts", field.stored_name())),
+        auto_updated: field.updated_at,
+        auto_created: field.created_at,
+        immutable: field.immutable,
+        redacted: field.redacted,
+        enum_order: field.enum_order.clone(),
+        field_type,
+        element_type,
+        is_relation_vec,
+        normalize_enum_tag: field_type == FieldType::Object && field.filterable == Some(FilterableType::Tag),
+        casefold_tag: field.casefold,
+        ..Default::default()
+    }
+}
+
+fn validation_rule(validation: &FieldValidation) -> ValidationRule {
+    match validation.clone() {
+        FieldValidation::Length { min, max } => ValidationRule::Length { min, max },
+        FieldValidation::Range { min, max } => ValidationRule::Range { min, max },
+        FieldValidation::Regex { pattern } => ValidationRule::Regex { pattern },
+        FieldValidation::Enum {
+            allowed,
+            case_insensitive,
+        } => ValidationRule::Enum {
+            allowed,
+            case_insensitive,
+        },
+        FieldValidation::Email => ValidationRule::Email,
+        FieldValidation::Url => ValidationRule::Url,
+        FieldValidation::Uuid => ValidationRule::Uuid,
+        FieldValidation::Phone => ValidationRule::Phone,
+        FieldValidation::IpAddr => ValidationRule::IpAddr,
+        FieldValidation::Hostname => ValidationRule::Hostname,
+        FieldValidation::Slug => ValidationRule::Slug,
+    }
+}
+
+/// Field type of a non-optional Rust type; `Vec<u8>` is stored as bytes.
+fn field_type(rust_type: &str) -> FieldType {
+    match element_arg(rust_type) {
+        Some(element) if base_name(element) == "u8" && base_name(rust_type) == "Vec" => FieldType::Bytes,
+        _ => field_type_of(rust_type),
+    }
+}
+
+fn field_type_of(rust_type: &str) -> FieldType {
+    match base_name(rust_type) {
+        "String" | "str" | "char" | "Uuid" => FieldType::String,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "f32"
+        | "f64" => FieldType::Number,
+        "bool" => FieldType::Boolean,
+        "DateTime" => FieldType::DateTime,
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => FieldType::Array,
+        "Option" => generic_arg(rust_type, "Option").map(field_type_of).unwrap_or_default(),
+        _ => FieldType::Object,
+    }
+}
+
+/// Element type of a list or set (`Vec<String>` -> `String`).
+fn element_arg(rust_type: &str) -> Option<&str> {
+    ["Vec", "VecDeque", "HashSet", "BTreeSet"]
+        .into_iter()
+        .find_map(|wrapper| generic_arg(rust_type, wrapper))
+}
+
+/// Last path segment of a type, without generic arguments (`chrono::DateTime<Utc>` -> `DateTime`).
+fn base_name(rust_type: &str) -> &str {
+    let path = rust_type.split('<').next().unwrap_or(rust_type).trim();
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// The generic argument of `rust_type` when its base is `wrapper`.
+fn generic_arg<'a>(rust_type: &'a str, wrapper: &str) -> Option<&'a str> {
+    if base_name(rust_type) != wrapper {
+        return None;
+    }
+    let start = rust_type.find('<')?;
+    rust_type[start + 1..].trim_end().strip_suffix('>').map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RelationInfo, UniqueConstraint};
+
+    #[test]
+    fn test_field_type() {
+        assert_eq!(field_type("String"), FieldType::String);
+        assert_eq!(field_type("chrono::DateTime<Utc>"), FieldType::DateTime);
+        assert_eq!(field_type("Vec<u8>"), FieldType::Bytes);
+        assert_eq!(field_type("Vec<String>"), FieldType::Array);
+        assert_eq!(field_type("GuildRole"), FieldType::Object);
+    }
+
+    #[test]
+    fn test_entity_descriptor() {
+        let mut schema = EntitySchema::new("Guild".to_string(), "src/models.rs".to_string(), 1);
+        let mut id = FieldInfo::new("id".to_string(), "String".to_string());
+        id.id = true;
+        let mut name = FieldInfo::new("name".to_string(), "String".to_string());
+        name.unique = true;
+        name.validations.push(FieldValidation::Length { min: Some(3), max: None });
+        let mut role = FieldInfo::new("role".to_string(), "GuildRole".to_string());
+        role.filterable = Some(FilterableType::Tag);
+        let mut created_at = FieldInfo::new("created_at".to_string(), "Option<DateTime<Utc>>".to_string());
+        created_at.created_at = true;
+        let member_ids = FieldInfo::new("member_ids".to_string(), "Vec<String>".to_string());
+        schema.fields = vec![id, name, role, created_at, member_ids];
+        schema.relations.push(RelationInfo {
+            field: "member_ids".to_string(),
+            target: "members".to_string(),
+            kind: RelationKind::HasMany,
+            cascade: Default::default(),
+        });
+        schema.unique_constraints.push(UniqueConstraint {
+            fields: vec!["name".to_string(), "role".to_string()],
+            case_insensitive: false,
+        });
+
+        let descriptor = entity_descriptor(&schema, "app");
+        assert_eq!(descriptor.collection, "guilds");
+        assert_eq!(descriptor.id_field.as_deref(), Some("id"));
+        assert_eq!(descriptor.unique_constraints.len(), 2);
+
+        let field = |name: &str| descriptor.fields.iter().find(|field| field.name == name).unwrap();
+        assert_eq!(field("name").validations.len(), 2);
+        assert!(field("role").normalize_enum_tag);
+        assert!(field("created_at").optional && field("created_at").auto_created);
+        assert_eq!(field("created_at").datetime_mirror.as_deref(), Some("created_at_ts"));
+        assert_eq!(field("created_at").field_type, FieldType::DateTime);
+        assert!(field("member_ids").is_relation_vec);
+        assert_eq!(field("member_ids").element_type, Some(FieldType::String));
+    }
+
+    #[test]
+    fn test_index_definition() {
+        let mut schema = EntitySchema::new("GuildMember".to_string(), "src/models.rs".to_string(), 1);
+        schema.service = Some("guild".to_string());
+        let mut name = FieldInfo::new("name".to_string(), "String".to_string());
+        name.searchable = true;
+        name.filterable = Some(FilterableType::Tag);
+        let mut role = FieldInfo::new("role".to_string(), "GuildRole".to_string());
+        role.filterable = Some(FilterableType::Tag);
+        let mut level = FieldInfo::new("level".to_string(), "u32".to_string());
+        level.sortable = true;
+        let mut joined_at = FieldInfo::new("joined_at".to_string(), "DateTime<Utc>".to_string());
+        joined_at.created_at = true;
+        let bio = FieldInfo::new("bio".to_string(), "String".to_string());
+        schema.fields = vec![name, role, level, joined_at, bio];
+
+        let definition = index_definition(&schema, "app");
+        assert_eq!(definition.name, "app:guild:guild_members:idx");
+        assert_eq!(definition.prefixes, vec!["app:guild:guild_members:".to_string()]);
+        assert_eq!(definition.filter, None);
+        let fields: Vec<_> = definition
+            .schema
+            .iter()
+            .map(|field| (field.path, field.field_name, field.field_type.as_str(), field.sortable))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("$.name", "name", "TEXT", false),
+                ("$.__role_tag", "__role_tag", "TAG", false),
+                ("$.level", "level", "NUMERIC", true),
+                ("$.joined_at_ts", "joined_at_ts", "NUMERIC", true),
+            ]
+        );
+
+        let sorts = sort_fields(&schema);
+        let sorts: Vec<_> = sorts.iter().map(|sort| (sort.name, sort.path, sort.default_order)).collect();
+        assert_eq!(
+            sorts,
+            vec![("level", "level", SortOrder::Desc), ("joined_at", "joined_at_ts", SortOrder::Desc)]
+        );
+        assert_eq!(default_sort(&schema, &sort_fields(&schema)).map(|sort| sort.name), Some("level"));
+        schema.default_sort = Some("-joined_at".to_string());
+        assert_eq!(default_sort(&schema, &sort_fields(&schema)).map(|sort| sort.path), Some("joined_at_ts"));
+
+        // Serde-renamed fields are indexed and sorted under their stored names
+        schema.fields[3].stored_as = Some("joinedAt".to_string());
+        let definition = index_definition(&schema, "app");
+        assert_eq!(definition.schema[3].path, "$.joinedAt_ts");
+        schema.index_name = Some("members_v2".to_string());
+        assert_eq!(index_definition(&schema, "app").name, "app:members_v2");
+        schema.index_name = None;
+        schema.key_separator = Some('/');
+        schema.key_components = vec!["eu".to_string()];
+        let definition = index_definition(&schema, "app");
+        assert_eq!(definition.name, "app/guild/eu/guild_members/idx");
+        assert_eq!(definition.prefixes, vec!["app/guild/eu/guild_members/".to_string()]);
+        schema.key_separator = None;
+        schema.key_components.clear();
+        assert_eq!(default_sort(&schema, &sort_fields(&schema)).map(|sort| sort.name), Some("joinedAt"));
+        schema.fields[3].stored_as = None;
+
+        // Fields of embedded structs are indexed by dotted path under underscored attributes
+        schema.fields[0].nested.push(NestedFieldInfo {
+            path: "name.first".to_string(),
+            filterable: Some(FilterableType::Tag),
+            sortable: true,
+            searchable: false,
+        });
+        let definition = index_definition(&schema, "app");
+        let nested = definition.schema.last().unwrap();
+        assert_eq!((nested.path, nested.field_name), ("$.name.first", "name_first"));
+        let sorts = sort_fields(&schema);
+        assert_eq!(sorts.last().map(|sort| (sort.name, sort.path)), Some(("name.first", "name_first")));
+        schema.fields[0].nested.clear();
+
+        // Casefold tags are indexed through their lowercase shadow
+        let mut email = FieldInfo::new("email".to_string(), "String".to_string());
+        email.filterable = Some(FilterableType::Tag);
+        email.casefold = true;
+        schema.fields.push(email);
+        let definition = index_definition(&schema, "app");
+        let email = definition.schema.last().unwrap();
+        assert_eq!((email.path, email.field_name), ("$.__email_lc", "__email_lc"));
+        assert!(entity_descriptor(&schema, "guild").fields.last().unwrap().casefold_tag);
+        schema.fields.pop();
+
+        // Optional filterable fields index missing values
+        let mut nickname = FieldInfo::new("nickname".to_string(), "Option<String>".to_string());
+        nickname.filterable = Some(FilterableType::Tag);
+        schema.fields.push(nickname);
+        assert!(index_definition(&schema, "app").schema.last().unwrap().index_missing);
+        assert!(!index_definition(&schema, "app").schema[0].index_missing);
+        schema.fields.pop();
+
+        // Ranked enums filter through their tag and sort through a numeric shadow
+        let mut priority = FieldInfo::new("priority".to_string(), "Priority".to_string());
+        priority.filterable = Some(FilterableType::Tag);
+        priority.sortable = true;
+        priority.enum_order = vec!["low".to_string(), "high".to_string()];
+        schema.fields.push(priority);
+        let definition = index_definition(&schema, "app");
+        let fields: Vec<_> = definition.schema[definition.schema.len() - 2..]
+            .iter()
+            .map(|field| (field.field_name, field.field_type.as_str(), field.sortable))
+            .collect();
+        assert_eq!(fields, vec![("__priority_tag", "TAG", false), ("__priority_ord", "NUMERIC", true)]);
+        let sorts = sort_fields(&schema);
+        assert_eq!(
+            sorts.last().map(|sort| (sort.name, sort.path, sort.default_order)),
+            Some(("priority", "__priority_ord", SortOrder::Asc))
+        );
+        assert_eq!(entity_descriptor(&schema, "guild").fields.last().unwrap().enum_order, ["low", "high"]);
+        schema.fields.pop();
+
+        schema.hash_storage = true;
+        let definition = index_definition(&schema, "app");
+        assert_eq!(definition.storage, StorageMode::Hash);
+        assert_eq!(
+            definition.filter.as_deref(),
+            Some("!startswith(@__key, \"app:guild:guild_members:unique\")")
+        );
+    }
+}
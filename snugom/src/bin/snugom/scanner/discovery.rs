@@ -13,6 +13,9 @@ pub struct DiscoveredFile {
     pub relative_path: String,
 }
 
+/// Directories, relative to the project root, searched for entity files.
+pub const SCAN_DIRS: &[&str] = &["src", "tests", "examples"];
+
 /// Discover all Rust files that might contain SnugomEntity derives.
 ///
 /// Walks the source directories and finds .rs files that contain
@@ -20,10 +23,7 @@ pub struct DiscoveredFile {
 pub fn discover_entities(project_root: &Path) -> Result<Vec<DiscoveredFile>> {
     let mut discovered = Vec::new();
 
-    // Directories to search
-    let search_dirs = ["src", "tests", "examples"];
-
-    for dir in &search_dirs {
+    for dir in SCAN_DIRS {
         let search_path = project_root.join(dir);
         if !search_path.exists() {
             continue;
@@ -5,16 +5,18 @@
 //! - Parse struct definitions and extract schema information
 //! - Generate schema snapshots for migration diffing
 
+mod descriptor;
 mod discovery;
 mod parser;
 mod schema;
 
-pub use discovery::discover_entities;
-pub use parser::parse_entity_file;
+pub use descriptor::{default_sort, entity_descriptor, index_definition, index_name, sort_fields};
+pub use discovery::{discover_entities, SCAN_DIRS};
+pub use parser::{parse_entity_file, parse_enum_file};
 
 // Re-export schema types for use by other modules
 #[allow(unused_imports)]
 pub use schema::{
-    CascadeStrategy, EntitySchema, FieldInfo, FilterableType, IndexInfo, IndexType,
+    CascadeStrategy, EntitySchema, EnumSchema, FieldInfo, FieldValidation, FilterableType, IndexInfo, IndexType,
     RelationInfo, RelationKind, UniqueConstraint,
 };
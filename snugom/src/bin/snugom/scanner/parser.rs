@@ -2,11 +2,12 @@
 
 use anyhow::{Context, Result};
 use std::path::Path;
-use syn::{Attribute, Field, Fields, GenericArgument, Ident, Lit, Meta, PathArguments, Type};
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, Field, Fields, GenericArgument, Ident, Lit, LitStr, Meta, PathArguments, Type, UnOp};
 
 use super::schema::{
-    CascadeStrategy, DateTimeFormat, EntitySchema, FieldInfo, FilterableType, IndexInfo, IndexType, RelationInfo,
-    RelationKind, UniqueConstraint,
+    CascadeStrategy, DateTimeFormat, EntitySchema, EnumSchema, FieldInfo, FieldValidation, FilterableType, IndexInfo, IndexType,
+    NestedFieldInfo, RelationInfo, RelationKind, UniqueConstraint,
 };
 
 /// Parse a Rust file and extract all SnugomEntity definitions.
@@ -42,6 +43,119 @@ pub fn parse_entity_file(path: &Path, relative_path: &str) -> Result<Vec<EntityS
     Ok(schemas)
 }
 
+/// Parse a Rust file and extract its fieldless enums, which serde writes as plain strings.
+///
+/// Variant names follow `#[serde(rename_all = "...")]` and `#[serde(rename = "...")]`. Enums
+/// with tuple or struct variants are skipped.
+pub fn parse_enum_file(path: &Path) -> Result<Vec<EnumSchema>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let syntax = syn::parse_file(&content).with_context(|| format!("Failed to parse Rust file: {}", path.display()))?;
+
+    let mut enums = Vec::new();
+    for item in syntax.items {
+        if let syn::Item::Enum(item_enum) = item
+            && item_enum.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit))
+        {
+            let rename_all = serde_str_arg(&item_enum.attrs, "rename_all")?;
+            let mut variants = Vec::new();
+            for variant in &item_enum.variants {
+                let name = match serde_str_arg(&variant.attrs, "rename")? {
+                    Some(renamed) => renamed,
+                    None => rename_variant(&variant.ident.to_string(), rename_all.as_deref()),
+                };
+                variants.push(name);
+            }
+            enums.push(EnumSchema {
+                name: item_enum.ident.to_string(),
+                variants,
+            });
+        }
+    }
+
+    Ok(enums)
+}
+
+/// Find `#[serde(key = "value")]` among attributes
+fn serde_str_arg(attrs: &[Attribute], key: &str) -> Result<Option<String>> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && meta.input.peek(syn::Token![=]) {
+                let lit: LitStr = meta.value()?.parse()?;
+                found = Some(lit.value());
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let _: proc_macro2::TokenStream = content.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+/// Whether `#[serde(...)]` sets the bare flag `key`, e.g. `flatten`
+fn has_serde_flag(attrs: &[Attribute], key: &str) -> Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && !meta.input.peek(syn::Token![=]) && !meta.input.peek(syn::token::Paren) {
+                found = true;
+                Ok(())
+            } else {
+                skip_meta_args(&meta)
+            }
+        })?;
+    }
+    Ok(found)
+}
+
+/// Apply a serde `rename_all` rule to a PascalCase variant name
+fn rename_variant(variant: &str, rule: Option<&str>) -> String {
+    match rule {
+        Some("lowercase") => variant.to_lowercase(),
+        Some("UPPERCASE") => variant.to_uppercase(),
+        Some("camelCase") => {
+            let mut chars = variant.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        Some("snake_case") => to_snake_case(variant),
+        Some("SCREAMING_SNAKE_CASE") => to_snake_case(variant).to_uppercase(),
+        Some("kebab-case") => to_snake_case(variant).replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => to_snake_case(variant).replace('_', "-").to_uppercase(),
+        _ => variant.to_string(),
+    }
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field name
+fn rename_field(field: &str, rule: Option<&str>) -> String {
+    let pascal = || -> String {
+        field
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    };
+    match rule {
+        Some("UPPERCASE") | Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("PascalCase") => pascal(),
+        Some("camelCase") => rename_variant(&pascal(), Some("camelCase")),
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.replace('_', "-").to_uppercase(),
+        _ => field.to_string(),
+    }
+}
+
 /// Find the line number where a struct is defined
 fn find_struct_line(lines: &[&str], struct_name: &str) -> Option<usize> {
     let pattern = format!("struct {struct_name}");
@@ -57,7 +171,7 @@ fn find_struct_line(lines: &[&str], struct_name: &str) -> Option<usize> {
 fn has_snugom_entity_derive(attrs: &[Attribute]) -> bool {
     for attr in attrs {
         if attr.path().is_ident("derive")
-            && let Ok(Meta::List(list)) = attr.meta.clone().try_into() as Result<Meta, _>
+            && let Meta::List(list) = &attr.meta
         {
             let tokens = list.tokens.to_string();
             if tokens.contains("SnugomEntity") {
@@ -78,9 +192,10 @@ fn parse_struct(item: &syn::ItemStruct, relative_path: &str, line: usize) -> Res
     parse_struct_attrs(&item.attrs, &mut schema)?;
 
     // Parse fields
+    let rename_all = serde_str_arg(&item.attrs, "rename_all")?;
     if let Fields::Named(fields) = &item.fields {
         for field in &fields.named {
-            if let Some(field_info) = parse_field(field)? {
+            if let Some((field_info, relation)) = parse_field(field, rename_all.as_deref())? {
                 // Build index info from field
                 if let Some(ref ft) = field_info.filterable {
                     schema.indexes.push(IndexInfo {
@@ -94,8 +209,18 @@ fn parse_struct(item: &syn::ItemStruct, relative_path: &str, line: usize) -> Res
                         index_type: IndexType::Numeric,
                     });
                 }
+                for nested in &field_info.nested {
+                    let filterable = if nested.searchable { Some(FilterableType::Text) } else { nested.filterable };
+                    if let Some(ft) = filterable {
+                        schema.indexes.push(IndexInfo {
+                            field: nested.path.clone(),
+                            index_type: IndexType::from(ft),
+                        });
+                    }
+                }
 
                 schema.fields.push(field_info);
+                schema.relations.extend(relation);
             }
         }
     }
@@ -131,6 +256,60 @@ fn parse_struct_attrs(attrs: &[Attribute], schema: &mut EntitySchema) -> Result<
                 return Ok(());
             }
 
+            // service = "name"
+            if meta.path.is_ident("service") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.service = Some(value.value());
+                return Ok(());
+            }
+
+            // storage = "json" | "hash"
+            if meta.path.is_ident("storage") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.hash_storage = value.value() == "hash";
+                return Ok(());
+            }
+
+            // language = "german", language_field = "lang"
+            if meta.path.is_ident("language") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.language = Some(value.value());
+                return Ok(());
+            }
+            if meta.path.is_ident("language_field") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.language_field = Some(value.value());
+                return Ok(());
+            }
+            if meta.path.is_ident("index_name") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.index_name = Some(value.value());
+                return Ok(());
+            }
+
+            // key_separator = "/", key_components = ["eu"]
+            if meta.path.is_ident("key_separator") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.key_separator = value.value().chars().next();
+                return Ok(());
+            }
+            if meta.path.is_ident("key_components") {
+                let _eq: syn::Token![=] = meta.input.parse()?;
+                let content;
+                syn::bracketed!(content in meta.input);
+                let components: syn::punctuated::Punctuated<LitStr, syn::Token![,]> =
+                    content.parse_terminated(|input| input.parse::<LitStr>(), syn::Token![,])?;
+                schema.key_components = components.iter().map(LitStr::value).collect();
+                return Ok(());
+            }
+
+            // default_sort = "-field"
+            if meta.path.is_ident("default_sort") {
+                let value: LitStr = meta.value()?.parse()?;
+                schema.default_sort = Some(value.value());
+                return Ok(());
+            }
+
             // collection = "name" (entity-level collection attribute)
             if meta.path.is_ident("collection") {
                 let _eq: syn::Token![=] = meta.input.parse()?;
@@ -167,15 +346,15 @@ fn parse_struct_attrs(attrs: &[Attribute], schema: &mut EntitySchema) -> Result<
                 return Ok(());
             }
 
-            Ok(())
+            skip_meta_args(&meta)
         })?;
     }
 
     Ok(())
 }
 
-/// Parse a field definition
-fn parse_field(field: &Field) -> Result<Option<FieldInfo>> {
+/// Parse a field definition, along with its relation if it has one
+fn parse_field(field: &Field, rename_all: Option<&str>) -> Result<Option<(FieldInfo, Option<RelationInfo>)>> {
     let field_name = field.ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
 
     if field_name.is_empty() {
@@ -183,31 +362,52 @@ fn parse_field(field: &Field) -> Result<Option<FieldInfo>> {
     }
 
     let field_type = type_to_string(&field.ty);
+    let renamed = rename_field(&field_name, rename_all);
     let mut info = FieldInfo::new(field_name, field_type);
+    if renamed != info.name {
+        info.stored_as = Some(renamed);
+    }
+    let mut relation = None;
 
     // Parse snugom attributes on the field
     for attr in &field.attrs {
         if attr.path().is_ident("snugom") {
-            parse_field_snugom_attr(attr, &mut info)?;
+            parse_field_snugom_attr(attr, &mut info, &mut relation)?;
         } else if attr.path().is_ident("serde") {
             parse_field_serde_attr(attr, &mut info)?;
         }
     }
 
-    // Check if this is a relation field (ends with _id or _ids)
-    if info.name.ends_with("_id") || info.name.ends_with("_ids") {
-        // Relation handling is done separately
+    // Nested paths hang off the stored name, or the root when serde flattens the struct
+    if !has_serde_flag(&field.attrs, "flatten")? {
+        let parent = info.stored_name().to_string();
+        for nested in &mut info.nested {
+            nested.path = format!("{parent}.{}", nested.path);
+        }
     }
 
-    Ok(Some(info))
+    Ok(Some((info, relation)))
 }
 
 /// Parse #[snugom(...)] attribute on a field
-fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()> {
+fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo, relation: &mut Option<RelationInfo>) -> Result<()> {
     attr.parse_nested_meta(|meta| {
         // id
         if meta.path.is_ident("id") {
             info.id = true;
+            if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|option| {
+                    if option.path.is_ident("prefix") {
+                        let value: LitStr = option.value()?.parse()?;
+                        info.id_prefix = Some(value.value());
+                    } else if option.path.is_ident("strategy") {
+                        // Time-ordered ids are indexed as a sortable tag
+                        let _: LitStr = option.value()?.parse()?;
+                        info.sortable = true;
+                    }
+                    Ok(())
+                })?;
+            }
             return Ok(());
         }
 
@@ -218,6 +418,10 @@ fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()>
                 syn::parenthesized!(content in meta.input);
                 let filter_type: Ident = content.parse()?;
                 info.filterable = Some(parse_filterable_type(&filter_type.to_string()));
+                if content.parse::<Option<syn::Token![,]>>()?.is_some() {
+                    let flag: Ident = content.parse()?;
+                    info.casefold = flag == "casefold";
+                }
             } else {
                 // Default based on field type
                 info.filterable = Some(infer_filterable_type(&info.field_type));
@@ -231,6 +435,12 @@ fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()>
             return Ok(());
         }
 
+        // searchable (full-text)
+        if meta.path.is_ident("searchable") {
+            info.searchable = true;
+            return Ok(());
+        }
+
         // unique or unique(case_insensitive)
         if meta.path.is_ident("unique") {
             info.unique = true;
@@ -245,6 +455,19 @@ fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()>
             return Ok(());
         }
 
+        // slug(from = "title") or slug(from = "title", suffix)
+        if meta.path.is_ident("slug") {
+            info.unique = true;
+            meta.parse_nested_meta(|option| {
+                if option.path.is_ident("from") {
+                    let value: LitStr = option.value()?.parse()?;
+                    info.slug_from = Some(value.value());
+                }
+                Ok(())
+            })?;
+            return Ok(());
+        }
+
         // datetime(format)
         if meta.path.is_ident("datetime") {
             if meta.input.peek(syn::token::Paren) {
@@ -258,35 +481,197 @@ fn parse_field_snugom_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()>
             return Ok(());
         }
 
-        // relation(...) - we capture but don't fully parse here
-        // Relations are processed separately to get full context
+        // relation or relation(...)
         if meta.path.is_ident("relation") {
-            // Skip the parenthesized content for now
-            if meta.input.peek(syn::token::Paren) {
-                let content;
-                syn::parenthesized!(content in meta.input);
-                // Just consume the tokens
-                let _: proc_macro2::TokenStream = content.parse()?;
+            *relation = Some(parse_relation_attr(&meta, &info.name, &info.field_type)?);
+            return Ok(());
+        }
+
+        // created_at / updated_at
+        if meta.path.is_ident("created_at") {
+            info.created_at = true;
+            return Ok(());
+        }
+        if meta.path.is_ident("updated_at") {
+            info.updated_at = true;
+            return Ok(());
+        }
+        if meta.path.is_ident("immutable") {
+            info.immutable = true;
+            return Ok(());
+        }
+        if meta.path.is_ident("redact") {
+            info.redacted = true;
+            return Ok(());
+        }
+        // enum_order = ["low", "medium", "high"]
+        if meta.path.is_ident("enum_order") {
+            let values: syn::ExprArray = meta.value()?.parse()?;
+            for value in values.elems {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(value),
+                    ..
+                }) = value
+                {
+                    info.enum_order.push(value.value());
+                }
             }
             return Ok(());
         }
 
-        // validate(...) - skip for now
+        // flatten(city(filterable(tag), sortable), ...)
+        if meta.path.is_ident("flatten") {
+            meta.parse_nested_meta(|item| {
+                let mut nested = NestedFieldInfo {
+                    path: item.path.get_ident().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default(),
+                    filterable: None,
+                    sortable: false,
+                    searchable: false,
+                };
+                item.parse_nested_meta(|option| {
+                    if option.path.is_ident("filterable") || option.path.is_ident("indexed") {
+                        let content;
+                        syn::parenthesized!(content in option.input);
+                        let index_type: Ident = content.parse()?;
+                        nested.filterable = Some(parse_filterable_type(&index_type.to_string()));
+                    } else if option.path.is_ident("sortable") {
+                        nested.sortable = true;
+                    } else if option.path.is_ident("searchable") {
+                        nested.searchable = true;
+                    } else if option.path.is_ident("rename") {
+                        let key: LitStr = option.value()?.parse()?;
+                        nested.path = key.value();
+                    } else {
+                        skip_meta_args(&option)?;
+                    }
+                    Ok(())
+                })?;
+                info.nested.push(nested);
+                Ok(())
+            })?;
+            return Ok(());
+        }
+
+        // validate(...)
         if meta.path.is_ident("validate") {
-            if meta.input.peek(syn::token::Paren) {
-                let content;
-                syn::parenthesized!(content in meta.input);
-                let _: proc_macro2::TokenStream = content.parse()?;
-            }
+            meta.parse_nested_meta(|rule| parse_validation_rule(rule, info))?;
             return Ok(());
         }
 
-        Ok(())
+        skip_meta_args(&meta)
     })?;
 
     Ok(())
 }
 
+/// Parse one rule inside #[snugom(validate(...))].
+///
+/// Rules that need the Rust type to evaluate (`required_if`, `forbidden_if`, `each`, `custom`)
+/// are skipped, as are `range` bounds that aren't numeric literals.
+fn parse_validation_rule(rule: ParseNestedMeta, info: &mut FieldInfo) -> syn::Result<()> {
+    let validation = if rule.path.is_ident("length") || rule.path.is_ident("size") {
+        let (mut min, mut max) = (None, None);
+        rule.parse_nested_meta(|item| {
+            let value: syn::LitInt = item.value()?.parse()?;
+            if item.path.is_ident("min") {
+                min = Some(value.base10_parse()?);
+            } else if item.path.is_ident("max") {
+                max = Some(value.base10_parse()?);
+            }
+            Ok(())
+        })?;
+        Some(FieldValidation::Length { min, max })
+    } else if rule.path.is_ident("range") {
+        let (mut min, mut max) = (None, None);
+        rule.parse_nested_meta(|item| {
+            let bound = numeric_literal(&item.value()?.parse()?);
+            if item.path.is_ident("min") {
+                min = bound;
+            } else if item.path.is_ident("max") {
+                max = bound;
+            }
+            Ok(())
+        })?;
+        Some(FieldValidation::Range { min, max })
+    } else if rule.path.is_ident("regex") {
+        let pattern: LitStr = rule.value()?.parse()?;
+        Some(FieldValidation::Regex { pattern: pattern.value() })
+    } else if rule.path.is_ident("enum") {
+        let mut allowed = Vec::new();
+        let mut case_insensitive = false;
+        rule.parse_nested_meta(|item| {
+            if item.path.is_ident("allowed") {
+                let array: syn::ExprArray = item.value()?.parse()?;
+                for element in array.elems {
+                    if let Expr::Lit(syn::ExprLit { lit: Lit::Str(lit), .. }) = element {
+                        allowed.push(lit.value());
+                    }
+                }
+            } else if item.path.is_ident("case_insensitive") {
+                case_insensitive = if item.input.peek(syn::Token![=]) {
+                    item.value()?.parse::<syn::LitBool>()?.value
+                } else {
+                    true
+                };
+            }
+            Ok(())
+        })?;
+        Some(FieldValidation::Enum { allowed, case_insensitive })
+    } else if rule.path.is_ident("email") {
+        Some(FieldValidation::Email)
+    } else if rule.path.is_ident("url") {
+        Some(FieldValidation::Url)
+    } else if rule.path.is_ident("uuid") {
+        Some(FieldValidation::Uuid)
+    } else if rule.path.is_ident("phone") {
+        Some(FieldValidation::Phone)
+    } else if rule.path.is_ident("ip_addr") {
+        Some(FieldValidation::IpAddr)
+    } else if rule.path.is_ident("hostname") {
+        Some(FieldValidation::Hostname)
+    } else if rule.path.is_ident("slug") {
+        Some(FieldValidation::Slug)
+    } else if rule.path.is_ident("unique") {
+        info.unique = true;
+        if rule.input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in rule.input);
+            let modifier: Ident = content.parse()?;
+            info.unique_case_insensitive = modifier == "case_insensitive";
+        }
+        None
+    } else {
+        skip_meta_args(&rule)?;
+        None
+    };
+    info.validations.extend(validation);
+    Ok(())
+}
+
+/// Consume the `= value` or `(...)` of an attribute item the scanner doesn't track.
+fn skip_meta_args(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        let _: Expr = meta.value()?.parse()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        let _: proc_macro2::TokenStream = content.parse()?;
+    }
+    Ok(())
+}
+
+/// A numeric literal, optionally negated, as written (`-5`, `0.5`).
+fn numeric_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Int(lit), .. }) => Some(lit.base10_digits().to_string()),
+        Expr::Lit(syn::ExprLit { lit: Lit::Float(lit), .. }) => Some(lit.base10_digits().to_string()),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            numeric_literal(&unary.expr).map(|digits| format!("-{digits}"))
+        }
+        _ => None,
+    }
+}
+
 /// Parse #[serde(...)] attribute on a field
 fn parse_field_serde_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()> {
     attr.parse_nested_meta(|meta| {
@@ -301,6 +686,15 @@ fn parse_field_serde_attr(attr: &Attribute, info: &mut FieldInfo) -> Result<()>
             } else {
                 info.serde_default = Some("Default::default".to_string());
             }
+        } else if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
+            let lit: LitStr = meta.value()?.parse()?;
+            info.stored_as = Some(lit.value()).filter(|renamed| *renamed != info.name);
+        } else if meta.input.peek(syn::Token![=]) {
+            let _: syn::Expr = meta.value()?.parse()?;
+        } else if meta.input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let _: proc_macro2::TokenStream = content.parse()?;
         }
         Ok(())
     })?;
@@ -389,114 +783,107 @@ fn parse_datetime_format(s: &str) -> DateTimeFormat {
     }
 }
 
-/// Parse relation attributes from a field to extract RelationInfo.
-/// This is a separate function for more complex relation parsing.
-#[allow(dead_code)]
-pub fn parse_relation_from_field(field: &Field) -> Option<RelationInfo> {
-    let field_name = field.ident.as_ref()?.to_string();
-
-    for attr in &field.attrs {
-        if !attr.path().is_ident("snugom") {
-            continue;
-        }
-
-        let mut has_relation = false;
-        let mut target = None;
-        let mut cascade = CascadeStrategy::default();
-        let mut kind = RelationKind::BelongsTo;
-
-        let _ = attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("relation") {
-                has_relation = true;
-
-                if meta.input.peek(syn::token::Paren) {
-                    let content;
-                    syn::parenthesized!(content in meta.input);
-
-                    // Parse relation options
-                    while !content.is_empty() {
-                        let ident: Ident = content.parse()?;
-                        let ident_str = ident.to_string();
-
-                        match ident_str.as_str() {
-                            "target" => {
-                                let _eq: syn::Token![=] = content.parse()?;
-                                let lit: Lit = content.parse()?;
-                                if let Lit::Str(s) = lit {
-                                    target = Some(s.value());
-                                }
-                            }
-                            "cascade" => {
-                                let _eq: syn::Token![=] = content.parse()?;
-                                let lit: Lit = content.parse()?;
-                                if let Lit::Str(s) = lit {
-                                    cascade = match s.value().as_str() {
-                                        "delete" => CascadeStrategy::Delete,
-                                        "restrict" => CascadeStrategy::Restrict,
-                                        _ => CascadeStrategy::Detach,
-                                    };
-                                }
-                            }
-                            "many_to_many" => {
-                                let _eq: syn::Token![=] = content.parse()?;
-                                let lit: Lit = content.parse()?;
-                                if let Lit::Str(s) = lit {
-                                    target = Some(s.value());
-                                    kind = RelationKind::ManyToMany;
-                                }
-                            }
-                            "kind" => {
-                                let _eq: syn::Token![=] = content.parse()?;
-                                let lit: Lit = content.parse()?;
-                                if let Lit::Str(s) = lit {
-                                    kind = match s.value().as_str() {
-                                        "has_many" => RelationKind::HasMany,
-                                        "many_to_many" => RelationKind::ManyToMany,
-                                        _ => RelationKind::BelongsTo,
-                                    };
-                                }
-                            }
-                            _ => {}
-                        }
-
-                        if content.peek(syn::Token![,]) {
-                            let _: syn::Token![,] = content.parse()?;
-                        }
-                    }
-                }
+/// Parse a relation attribute into a RelationInfo, inferring what the derive infers:
+/// `Vec<T>` fields are has_many (many_to_many when a junction is named) and `{entity}_id`
+/// fields are belongs_to, with the target collection derived from the type or field name.
+fn parse_relation_attr(
+    meta: &syn::meta::ParseNestedMeta,
+    field_name: &str,
+    field_type: &str,
+) -> syn::Result<RelationInfo> {
+    let mut target = None;
+    let mut junction = None;
+    let mut cascade = CascadeStrategy::default();
+
+    if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident("has_many") {
+                return Ok(());
             }
+            let value: LitStr = nested.value()?.parse()?;
+            if nested.path.is_ident("target") {
+                target = Some(value.value());
+            } else if nested.path.is_ident("many_to_many") {
+                junction = Some(value.value());
+            } else if nested.path.is_ident("cascade") {
+                cascade = parse_cascade_strategy(&value.value());
+            }
+            // alias, foreign_key, and counter don't affect the schema
             Ok(())
-        });
+        })?;
+    }
 
-        if has_relation {
-            // If no target specified, infer from field name
-            let inferred_target = target.unwrap_or_else(|| infer_relation_target(&field_name));
+    let element = field_type
+        .strip_prefix("Vec<")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .map(|element| element.rsplit("::").next().unwrap_or(element));
+    let kind = match (&junction, element) {
+        (Some(_), _) => RelationKind::ManyToMany,
+        (None, Some(_)) => RelationKind::HasMany,
+        (None, None) => RelationKind::BelongsTo,
+    };
+    let target = target.or(junction).unwrap_or_else(|| match element {
+        Some(element) => pluralize(&to_snake_case(element)),
+        None => format!("{}s", infer_relation_target(field_name)),
+    });
+
+    Ok(RelationInfo {
+        field: field_name.to_string(),
+        target,
+        kind,
+        cascade,
+    })
+}
 
-            return Some(RelationInfo {
-                field: field_name,
-                target: inferred_target,
-                kind,
-                cascade,
-            });
-        }
+/// Parse cascade policy string
+fn parse_cascade_strategy(s: &str) -> CascadeStrategy {
+    match s {
+        "delete" => CascadeStrategy::Delete,
+        "detach" => CascadeStrategy::Detach,
+        "restrict" => CascadeStrategy::Restrict,
+        _ => CascadeStrategy::None,
     }
-
-    None
 }
 
 /// Infer relation target from field name.
 /// e.g., "author_id" -> "author", "user_ids" -> "user"
-#[allow(dead_code)]
 fn infer_relation_target(field_name: &str) -> String {
-    if field_name.ends_with("_ids") {
-        field_name[..field_name.len() - 4].to_string()
-    } else if field_name.ends_with("_id") {
-        field_name[..field_name.len() - 3].to_string()
+    if let Some(stripped) = field_name.strip_suffix("_ids") {
+        stripped.to_string()
+    } else if let Some(stripped) = field_name.strip_suffix("_id") {
+        stripped.to_string()
     } else {
         field_name.to_string()
     }
 }
 
+/// Convert PascalCase to snake_case
+fn to_snake_case(s: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Pluralize a snake_case name the way the derive does
+fn pluralize(word: &str) -> String {
+    if word.ends_with('s') || word.ends_with('x') || word.ends_with("ch") || word.ends_with("sh") {
+        format!("{word}es")
+    } else if word.ends_with('y') && !["ay", "ey", "oy", "uy"].iter().any(|suffix| word.ends_with(suffix)) {
+        format!("{}ies", &word[..word.len() - 1])
+    } else {
+        format!("{word}s")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -523,6 +910,238 @@ mod tests {
         assert_eq!(infer_relation_target("name"), "name");
     }
 
+    #[test]
+    fn test_parse_struct_relations() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Guild {
+                #[snugom(id)]
+                id: String,
+                #[snugom(relation(target = "guild_members", cascade = "delete"))]
+                members: Vec<String>,
+                #[snugom(relation, filterable(tag))]
+                owner_id: String,
+                #[snugom(relation(many_to_many = "guild_tags"))]
+                tags: Vec<String>,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/guild.rs", 1).unwrap();
+
+        let relations: Vec<_> = schema
+            .relations
+            .iter()
+            .map(|relation| (relation.field.as_str(), relation.target.as_str(), relation.kind, relation.cascade))
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("members", "guild_members", RelationKind::HasMany, CascadeStrategy::Delete),
+                ("owner_id", "owners", RelationKind::BelongsTo, CascadeStrategy::None),
+                ("tags", "guild_tags", RelationKind::ManyToMany, CascadeStrategy::None),
+            ]
+        );
+        assert_eq!(schema.fields[2].filterable, Some(FilterableType::Tag));
+    }
+
+    #[test]
+    fn test_parse_field_validations() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Guild {
+                #[snugom(validate(length(min = 3, max = 32), regex = "^[a-z]+$", custom = "crate::check"))]
+                name: String,
+                #[snugom(validate(range(min = -5, max = MAX_LEVEL), enum(allowed = ["a", "b"], case_insensitive)))]
+                level: i32,
+                #[snugom(created_at)]
+                created_at: DateTime<Utc>,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/guild.rs", 1).unwrap();
+
+        assert_eq!(
+            schema.fields[0].validations,
+            vec![
+                FieldValidation::Length { min: Some(3), max: Some(32) },
+                FieldValidation::Regex { pattern: "^[a-z]+$".to_string() },
+            ]
+        );
+        assert_eq!(
+            schema.fields[1].validations,
+            vec![
+                FieldValidation::Range { min: Some("-5".to_string()), max: None },
+                FieldValidation::Enum {
+                    allowed: vec!["a".to_string(), "b".to_string()],
+                    case_insensitive: true,
+                },
+            ]
+        );
+        assert!(schema.fields[2].created_at && schema.fields[2].has_datetime_mirror());
+    }
+
+    #[test]
+    fn test_parse_service_and_storage() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            #[snugom(schema = 2, service = "guild", storage = "hash", default_sort = "-name")]
+            #[snugom(language = "german", language_field = "locale")]
+            struct GuildMember {
+                #[snugom(searchable, filterable(tag), compress(min_size = 64))]
+                name: String,
+                locale: String,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/guild.rs", 1).unwrap();
+
+        assert_eq!(schema.service_name(), "guild");
+        assert!(schema.hash_storage);
+        assert_eq!(schema.language.as_deref(), Some("german"));
+        assert_eq!(schema.language_field.as_deref(), Some("locale"));
+        assert_eq!(schema.default_sort.as_deref(), Some("-name"));
+        assert!(schema.fields[0].searchable);
+        assert_eq!(schema.fields[0].filterable, Some(FilterableType::Tag));
+    }
+
+    #[test]
+    fn test_rename_variant() {
+        assert_eq!(rename_variant("RegularMember", None), "RegularMember");
+        assert_eq!(rename_variant("RegularMember", Some("snake_case")), "regular_member");
+        assert_eq!(rename_variant("RegularMember", Some("camelCase")), "regularMember");
+        assert_eq!(rename_variant("RegularMember", Some("SCREAMING-KEBAB-CASE")), "REGULAR-MEMBER");
+        assert_eq!(rename_variant("RegularMember", Some("lowercase")), "regularmember");
+    }
+
+    #[test]
+    fn test_parse_serde_renames() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            #[serde(rename_all = "camelCase")]
+            struct Player {
+                #[snugom(id)]
+                id: String,
+                #[serde(default)]
+                display_name: String,
+                #[serde(rename = "lvl", default)]
+                skill_level: u32,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/player.rs", 1).unwrap();
+
+        let names: Vec<_> = schema.fields.iter().map(|field| (field.name.as_str(), field.stored_name())).collect();
+        assert_eq!(names, vec![("id", "id"), ("display_name", "displayName"), ("skill_level", "lvl")]);
+        assert_eq!(schema.fields[0].stored_as, None);
+        assert_eq!(schema.fields[2].serde_default.as_deref(), Some("Default::default"));
+        assert_eq!(rename_field("display_name", Some("PascalCase")), "DisplayName");
+        assert_eq!(rename_field("display_name", Some("SCREAMING-KEBAB-CASE")), "DISPLAY-NAME");
+    }
+
+    #[test]
+    fn test_parse_casefold() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Account {
+                #[snugom(id)]
+                id: String,
+                #[snugom(filterable(tag, casefold), unique)]
+                email: String,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/account.rs", 1).unwrap();
+        assert_eq!(schema.fields[1].filterable, Some(FilterableType::Tag));
+        assert!(schema.fields[1].casefold && schema.fields[1].unique);
+        assert!(!schema.fields[0].casefold);
+    }
+
+    #[test]
+    fn test_parse_id_prefix() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct User {
+                #[snugom(id(prefix = "usr"))]
+                id: String,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/user.rs", 1).unwrap();
+        assert!(schema.fields[0].id);
+        assert_eq!(schema.id_prefix(), Some("usr"));
+    }
+
+    #[test]
+    fn test_parse_id_strategy() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Event {
+                #[snugom(id(strategy = "ulid"))]
+                id: String,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/event.rs", 1).unwrap();
+        assert!(schema.fields[0].id && schema.fields[0].sortable);
+        assert_eq!(schema.id_prefix(), None);
+    }
+
+    #[test]
+    fn test_parse_slug() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Post {
+                #[snugom(id)]
+                id: String,
+                title: String,
+                #[snugom(slug(from = "title", suffix))]
+                slug: String,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/post.rs", 1).unwrap();
+        assert_eq!(schema.fields[2].slug_from.as_deref(), Some("title"));
+        assert!(schema.fields[2].unique && !schema.fields[2].unique_case_insensitive);
+    }
+
+    #[test]
+    fn test_parse_flatten() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            struct Venue {
+                #[snugom(id)]
+                id: String,
+                #[snugom(flatten(city(filterable(tag), sortable), floor(indexed(numeric)), note(searchable)))]
+                address: Address,
+                #[serde(flatten)]
+                #[snugom(flatten(phone(filterable(tag))))]
+                contact: Contact,
+            }
+            "#,
+        )
+        .unwrap();
+        let schema = parse_struct(&item, "src/venue.rs", 1).unwrap();
+
+        let nested: Vec<_> = schema.fields.iter().flat_map(|field| &field.nested).collect();
+        let paths: Vec<_> = nested.iter().map(|nested| nested.path.as_str()).collect();
+        assert_eq!(paths, vec!["address.city", "address.floor", "address.note", "phone"]);
+        assert_eq!(nested[0].filterable, Some(FilterableType::Tag));
+        assert!(nested[0].sortable);
+        assert_eq!(nested[1].filterable, Some(FilterableType::Numeric));
+        assert!(nested[2].searchable);
+
+        let indexed: Vec<_> = schema.indexes.iter().map(|index| index.field.as_str()).collect();
+        assert_eq!(indexed, vec!["address.city", "address.floor", "address.note", "phone"]);
+    }
+
     #[test]
     fn test_infer_filterable_type() {
         assert_eq!(infer_filterable_type("String"), FilterableType::Tag);
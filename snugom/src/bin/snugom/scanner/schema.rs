@@ -9,6 +9,38 @@ pub struct EntitySchema {
     /// Name of the entity struct (e.g., "User")
     pub entity: String,
 
+    /// Service from #[snugom(service = "...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+
+    /// Documents are plain hashes, from #[snugom(storage = "hash")]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hash_storage: bool,
+
+    /// Stemming language of the index, from #[snugom(language = "...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Field holding each document's language, from #[snugom(language_field = "...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_field: Option<String>,
+
+    /// Index name under the key prefix, from #[snugom(index_name = "...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_name: Option<String>,
+
+    /// Separator of the entity's keys, from #[snugom(key_separator = "...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_separator: Option<char>,
+
+    /// Segments after the key prefix and service, from #[snugom(key_components = [...])]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_components: Vec<String>,
+
+    /// Default search sort from #[snugom(default_sort = "-field")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_sort: Option<String>,
+
     /// Collection name from entity attributes (e.g., "users")
     /// This is populated from #[snugom(collection = "...")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -45,6 +77,14 @@ impl EntitySchema {
     pub fn new(entity: String, source_file: String, source_line: usize) -> Self {
         Self {
             entity,
+            service: None,
+            hash_storage: false,
+            language: None,
+            language_field: None,
+            index_name: None,
+            key_separator: None,
+            key_components: Vec::new(),
+            default_sort: None,
             collection: None,
             schema: 1,
             fields: Vec::new(),
@@ -57,6 +97,23 @@ impl EntitySchema {
         }
     }
 
+    /// Collection name, defaulting to the pluralized snake_case entity name like the derive does
+    pub fn collection_name(&self) -> String {
+        self.collection
+            .clone()
+            .unwrap_or_else(|| format!("{}s", to_snake_case(&self.entity)))
+    }
+
+    /// Prefix of the entity's ids, if its id field declares one
+    pub fn id_prefix(&self) -> Option<&str> {
+        self.fields.iter().find(|field| field.id).and_then(|field| field.id_prefix.as_deref())
+    }
+
+    /// Service name, defaulting to the snake_case entity name like the derive does
+    pub fn service_name(&self) -> String {
+        self.service.clone().unwrap_or_else(|| to_snake_case(&self.entity))
+    }
+
     /// Generate a snapshot filename for this entity and version
     pub fn snapshot_filename(&self) -> String {
         let entity_snake = to_snake_case(&self.entity);
@@ -70,6 +127,10 @@ pub struct FieldInfo {
     /// Field name
     pub name: String,
 
+    /// Name serde writes the field under, when `#[serde(rename)]` or `rename_all` changes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_as: Option<String>,
+
     /// Rust type as string (e.g., "String", "Option<String>", "Vec<String>")
     #[serde(rename = "type")]
     pub field_type: String,
@@ -78,14 +139,26 @@ pub struct FieldInfo {
     #[serde(default, skip_serializing_if = "is_false")]
     pub id: bool,
 
+    /// Prefix generated ids carry, from #[snugom(id(prefix = "..."))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_prefix: Option<String>,
+
     /// Filterable type if set
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filterable: Option<FilterableType>,
 
+    /// Filtered through a lowercase shadow via #[snugom(filterable(tag, casefold))]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub casefold: bool,
+
     /// Whether this field is sortable
     #[serde(default, skip_serializing_if = "is_false")]
     pub sortable: bool,
 
+    /// Full-text indexed via #[snugom(searchable)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub searchable: bool,
+
     /// Unique constraint on this field
     #[serde(default, skip_serializing_if = "is_false")]
     pub unique: bool,
@@ -94,6 +167,10 @@ pub struct FieldInfo {
     #[serde(default, skip_serializing_if = "is_false")]
     pub unique_case_insensitive: bool,
 
+    /// Field a slug is generated from, via #[snugom(slug(from = "..."))]; slugs are unique
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug_from: Option<String>,
+
     /// DateTime storage format
     #[serde(skip_serializing_if = "Option::is_none")]
     pub datetime_format: Option<DateTimeFormat>,
@@ -101,6 +178,51 @@ pub struct FieldInfo {
     /// Serde default value if any
     #[serde(skip_serializing_if = "Option::is_none")]
     pub serde_default: Option<String>,
+
+    /// Set on create via #[snugom(created_at)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub created_at: bool,
+
+    /// Refreshed on every write via #[snugom(updated_at)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub updated_at: bool,
+
+    /// Write-once via #[snugom(immutable)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub immutable: bool,
+
+    /// Left out of search hits via #[snugom(redact)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub redacted: bool,
+
+    /// Tag values in sort order from #[snugom(enum_order = [...])]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enum_order: Vec<String>,
+
+    /// Rules from #[snugom(validate(...))] that can be checked without the Rust type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validations: Vec<FieldValidation>,
+
+    /// Embedded struct fields indexed via #[snugom(flatten(...))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nested: Vec<NestedFieldInfo>,
+}
+
+/// A field of an embedded struct indexed through `#[snugom(flatten(...))]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NestedFieldInfo {
+    /// Dotted path from the document root (e.g. "address.city")
+    pub path: String,
+
+    /// Index type from filterable(...) or indexed(...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filterable: Option<FilterableType>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sortable: bool,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub searchable: bool,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -111,16 +233,71 @@ impl FieldInfo {
     pub fn new(name: String, field_type: String) -> Self {
         Self {
             name,
+            stored_as: None,
             field_type,
             id: false,
+            id_prefix: None,
             filterable: None,
+            casefold: false,
             sortable: false,
+            searchable: false,
             unique: false,
             unique_case_insensitive: false,
+            slug_from: None,
             datetime_format: None,
             serde_default: None,
+            created_at: false,
+            updated_at: false,
+            immutable: false,
+            redacted: false,
+            enum_order: Vec::new(),
+            validations: Vec::new(),
+            nested: Vec::new(),
         }
     }
+
+    /// The JSON key the field is stored under
+    pub fn stored_name(&self) -> &str {
+        self.stored_as.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether the derive keeps a `{field}_ts` epoch-millisecond mirror of this field
+    pub fn has_datetime_mirror(&self) -> bool {
+        self.datetime_format.is_some() || self.created_at || self.updated_at
+    }
+}
+
+/// A field validation rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum FieldValidation {
+    Length {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<usize>,
+    },
+    Range {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<String>,
+    },
+    Regex {
+        pattern: String,
+    },
+    Enum {
+        allowed: Vec<String>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        case_insensitive: bool,
+    },
+    Email,
+    Url,
+    Uuid,
+    Phone,
+    IpAddr,
+    Hostname,
+    Slug,
 }
 
 /// Filterable field type
@@ -187,11 +364,22 @@ pub enum RelationKind {
 #[derive(Default)]
 pub enum CascadeStrategy {
     #[default]
+    None,
     Detach,
     Delete,
     Restrict,
 }
 
+/// A fieldless enum declared next to an entity, serialized as one of its variant names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumSchema {
+    /// Name of the enum (e.g., "GuildRole")
+    pub name: String,
+
+    /// Variant names as serialized, after serde renames
+    pub variants: Vec<String>,
+}
+
 /// Compound unique constraint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniqueConstraint {
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 
 /// Format a Unix timestamp to a human-readable string
@@ -39,6 +41,16 @@ pub fn format_file_size(size: u64) -> String {
     }
 }
 
+/// Format a duration as its two largest units, e.g. "1h 05m" or "3m 20s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64().round() as u64;
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
 /// Generate a migration filename with timestamp
 #[allow(dead_code)]
 pub fn migration_filename(name: &str) -> String {
@@ -67,6 +79,14 @@ mod tests {
         assert_eq!(format_file_size(1024 * 1024), "1.00 MB");
     }
 
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(Duration::from_millis(59_400)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(200)), "3m 20s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 05m");
+    }
+
     #[test]
     fn test_sanitize_identifier() {
         assert_eq!(sanitize_identifier("add_user"), "add_user");
@@ -0,0 +1,128 @@
+//! Pattern-based key deletion for test and bundle cleanup.
+
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
+
+use redis::aio::ConnectionManager;
+use tokio::task::JoinSet;
+
+use crate::errors::RepoError;
+
+/// Batching used by [`cleanup_pattern_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupOptions {
+    /// `COUNT` hint sent with each `SCAN`
+    pub scan_count: usize,
+    /// Keys removed by a single `UNLINK`
+    pub chunk_size: usize,
+    /// `UNLINK` batches in flight at once
+    pub parallelism: usize,
+    /// Sleep after each round of batches, capping the delete rate on a busy server
+    pub pause: Option<Duration>,
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        Self {
+            scan_count: 1000,
+            chunk_size: 500,
+            parallelism: 1,
+            pause: None,
+        }
+    }
+}
+
+/// Outcome of a pattern cleanup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Keys returned by `SCAN`; a key can be returned more than once while the keyspace changes
+    pub scanned: u64,
+    /// Keys actually removed
+    pub deleted: u64,
+    pub elapsed: Duration,
+}
+
+/// Delete all keys matching a pattern with the default [`CleanupOptions`].
+///
+/// See [`cleanup_pattern_with`].
+pub async fn cleanup_pattern(conn: &mut ConnectionManager, pattern: &str) -> Result<CleanupReport, RepoError> {
+    cleanup_pattern_with(conn, pattern, CleanupOptions::default()).await
+}
+
+/// Delete all keys matching a pattern.
+///
+/// Keys are found with `SCAN` and removed in chunks of `options.chunk_size` with `UNLINK`,
+/// which frees their memory in the background instead of blocking the server the way `DEL`
+/// does on large values. Up to `options.parallelism` chunks run at once, and `options.pause`
+/// spaces out the rounds.
+///
+/// # Example
+/// ```ignore
+/// let report = snugom::cleanup_pattern_with(&mut conn, "staging:*", CleanupOptions {
+///     parallelism: 4,
+///     pause: Some(Duration::from_millis(10)),
+///     ..CleanupOptions::default()
+/// })
+/// .await?;
+/// println!("deleted {} of {} keys in {:?}", report.deleted, report.scanned, report.elapsed);
+/// ```
+pub async fn cleanup_pattern_with(
+    conn: &mut ConnectionManager,
+    pattern: &str,
+    options: CleanupOptions,
+) -> Result<CleanupReport, RepoError> {
+    let started = Instant::now();
+    let chunk_size = options.chunk_size.max(1);
+    let round_size = chunk_size * options.parallelism.max(1);
+    let mut report = CleanupReport::default();
+    let mut pending: Vec<String> = Vec::new();
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(options.scan_count.max(1))
+            .query_async(conn)
+            .await?;
+        report.scanned += keys.len() as u64;
+        pending.extend(keys);
+        cursor = next_cursor;
+
+        while pending.len() >= round_size || (cursor == 0 && !pending.is_empty()) {
+            let round: Vec<String> = pending.drain(..round_size.min(pending.len())).collect();
+            report.deleted += unlink_round(conn, round, chunk_size).await?;
+            if let Some(pause) = options.pause {
+                tokio::time::sleep(pause).await;
+            }
+        }
+        if cursor == 0 {
+            break;
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// `UNLINK` `keys` in concurrent chunks, returning how many were removed.
+async fn unlink_round(conn: &ConnectionManager, keys: Vec<String>, chunk_size: usize) -> Result<u64, RepoError> {
+    let mut tasks = JoinSet::new();
+    for chunk in keys.chunks(chunk_size) {
+        let chunk = chunk.to_vec();
+        let mut conn = conn.clone();
+        tasks.spawn(async move { redis::cmd("UNLINK").arg(&chunk).query_async::<u64>(&mut conn).await });
+    }
+    let mut deleted = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let removed = joined.map_err(|err| RepoError::Other {
+            message: Cow::Owned(format!("cleanup task failed: {err}")),
+        })??;
+        deleted += removed;
+    }
+    Ok(deleted)
+}
@@ -7,28 +7,84 @@
 //! let guilds = snugom.guilds().find_many(query).await?;
 //! ```
 
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
+
 use redis::aio::ConnectionManager;
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
+use tokio::task::{JoinError, JoinSet};
 
 use crate::{
-    errors::RepoError,
+    audit::AuditAction,
+    errors::{RepoError, ValidationError},
+    interceptor::{self, SharedInterceptor},
     repository::{
-        CreateResult, GetOrCreateResult, MutationPayloadBuilder, Repo, UpdatePatchBuilder, UpsertResult,
+        CreateResult, GetOrCreateResult, IdempotencyStatus, MutationPayloadBuilder, RelationRepairReport, Repo,
+        UpdatePatchBuilder, UpsertResult,
     },
-    search::{SearchQuery, SearchResult},
-    types::{EntityMetadata, SnugomModel},
+    runtime::{MutationPlan, execute_pipelined, execute_plan},
+    search::{FilterCondition, SearchEntity, SearchQuery, SearchResult, ViewModel},
+    telemetry,
+    types::{EntityMetadata, RelationData, RelationQueryOptions, SnugomModel},
 };
 
 /// Result of a bulk create operation.
-#[derive(Debug, Clone)]
+///
+/// A failing item does not stop the rest of the batch; it is reported in `failures` instead.
+#[derive(Debug)]
 pub struct BulkCreateResult {
     /// Number of entities created
     pub count: u64,
-    /// IDs of created entities
+    /// IDs of created entities, in input order
     pub ids: Vec<String>,
-    /// Raw responses from Redis
+    /// Raw responses from Redis, aligned with `ids`
     pub responses: Vec<Vec<Value>>,
+    /// Items that were not created, in input order
+    pub failures: Vec<BulkCreateFailure>,
+}
+
+impl BulkCreateResult {
+    /// True when every item was created.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Validation errors by input position, skipping failures of any other kind.
+    pub fn validation_errors(&self) -> impl Iterator<Item = (usize, &ValidationError)> {
+        self.failures.iter().filter_map(|failure| match &failure.error {
+            RepoError::Validation(err) => Some((failure.index, err)),
+            _ => None,
+        })
+    }
+}
+
+/// An item from a bulk create that was not written.
+#[derive(Debug)]
+pub struct BulkCreateFailure {
+    /// Position of the item in the input
+    pub index: usize,
+    pub error: RepoError,
+}
+
+/// Batching used by [`CollectionHandle::create_many_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkCreateOptions {
+    /// Creates sent to Redis in a single pipeline
+    pub chunk_size: usize,
+    /// Pipelines in flight at once
+    pub parallelism: usize,
+}
+
+impl Default for BulkCreateOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 100,
+            parallelism: 4,
+        }
+    }
 }
 
 /// Type-safe handle for CRUD operations on a single entity collection.
@@ -54,6 +110,8 @@ where
 {
     repo: Repo<T>,
     conn: ConnectionManager,
+    /// Connection reads go to instead of `conn`, see [`CollectionHandle::with_read_connection`]
+    replica: Option<ConnectionManager>,
 }
 
 impl<T> CollectionHandle<T>
@@ -65,7 +123,26 @@ where
     /// This is typically called via `Client::collection<T>()` or via
     /// named accessors generated by `#[derive(SnugomClient)]`.
     pub fn new(repo: Repo<T>, conn: ConnectionManager) -> Self {
-        Self { repo, conn }
+        Self {
+            repo,
+            conn,
+            replica: None,
+        }
+    }
+
+    /// Send reads (`get*`, `exists`, `count`, `find_*` and relation loads) to `conn`, typically
+    /// a replica, while writes stay on the primary connection.
+    ///
+    /// Replicas lag the primary, so a read right after a write may not see it; `create_and_get`
+    /// and `update_and_get` read back from the primary.
+    pub fn with_read_connection(mut self, conn: ConnectionManager) -> Self {
+        self.replica = Some(conn);
+        self
+    }
+
+    /// The repository and the connection reads go to.
+    fn reader(&mut self) -> (&Repo<T>, &mut ConnectionManager) {
+        (&self.repo, self.replica.as_mut().unwrap_or(&mut self.conn))
     }
 
     /// Get a mutable reference to the connection for advanced operations.
@@ -131,7 +208,19 @@ where
     ///
     /// Returns `None` if the entity doesn't exist.
     pub async fn get(&mut self, id: &str) -> Result<Option<T>, RepoError> {
-        self.repo.get(&mut self.conn, id).await
+        let (repo, conn) = self.reader();
+        repo.get(conn, id).await
+    }
+
+    /// Get several entities by ID in a single round trip.
+    ///
+    /// Results are in the same order as `ids`, with `None` for entities that don't exist.
+    pub async fn get_many<S>(&mut self, ids: &[S]) -> Result<Vec<Option<T>>, RepoError>
+    where
+        S: AsRef<str>,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_many(conn, ids).await
     }
 
     /// Get entity by ID, returning an error if not found.
@@ -143,14 +232,190 @@ where
         })
     }
 
+    /// Reload `entity` in place from the stored document.
+    ///
+    /// Reads from the primary even when a replica is configured, so writes made through this
+    /// handle are always seen. Fails with `RepoError::NotFound` if the entity was deleted.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut job = snugom.jobs().get_or_error(&job_id).await?;
+    /// run_step(&job).await;
+    /// snugom.jobs().refresh(&mut job).await?;
+    /// ```
+    pub async fn refresh(&mut self, entity: &mut T) -> Result<(), RepoError> {
+        let id = entity.get_id();
+        *entity = self.repo.get(&mut self.conn, &id).await?.ok_or(RepoError::NotFound { entity_id: Some(id) })?;
+        Ok(())
+    }
+
+    /// Get only `paths` of an entity, deserialized as `P`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let score: Option<Score> = snugom.players().get_projection(&id, ["name", "score"]).await?;
+    /// ```
+    pub async fn get_projection<P, S>(
+        &mut self,
+        id: &str,
+        paths: impl IntoIterator<Item = S>,
+    ) -> Result<Option<P>, RepoError>
+    where
+        P: DeserializeOwned,
+        S: Into<String>,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_projection(conn, id, paths).await
+    }
+
+    /// Get entity by ID as view model `V`, fetching only the fields it declares.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let summary: Option<UserSummary> = snugom.users().get_as(&id).await?;
+    /// ```
+    pub async fn get_as<V>(&mut self, id: &str) -> Result<Option<V>, RepoError>
+    where
+        V: ViewModel<T>,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_view(conn, id).await
+    }
+
     /// Check if an entity exists by ID.
     pub async fn exists(&mut self, id: &str) -> Result<bool, RepoError> {
-        self.repo.exists(&mut self.conn, id).await
+        let (repo, conn) = self.reader();
+        repo.exists(conn, id).await
+    }
+
+    /// Inspect an idempotency key: whether it was consumed, when it expires, and the stored response.
+    pub async fn idempotency_status(&mut self, key: &str) -> Result<IdempotencyStatus, RepoError> {
+        self.repo.idempotency_status(&mut self.conn, key).await
     }
 
     /// Count all entities in the collection.
     pub async fn count(&mut self) -> Result<u64, RepoError> {
-        self.repo.count(&mut self.conn).await
+        let (repo, conn) = self.reader();
+        repo.count(conn).await
+    }
+}
+
+// ============ Relation Traversal ============
+
+impl<T> CollectionHandle<T>
+where
+    T: SnugomModel,
+{
+    /// Resolve the far-side IDs of a `has_many_through` relation.
+    pub async fn through_ids(&mut self, id: &str, alias: &str) -> Result<Vec<String>, RepoError> {
+        let (repo, conn) = self.reader();
+        repo.through_ids(conn, alias, id).await
+    }
+
+    /// Load the far-side entities of a `has_many_through` relation.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Player -> memberships -> team
+    /// let teams: Vec<Team> = snugom.players().get_through(&player_id, "teams").await?;
+    /// ```
+    pub async fn get_through<U>(&mut self, id: &str, alias: &str) -> Result<Vec<U>, RepoError>
+    where
+        U: SnugomModel + DeserializeOwned,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_through(conn, alias, id).await
+    }
+
+    /// Read the edge payloads stored on a relation, keyed by related id.
+    pub async fn relation_edges(&mut self, id: &str, alias: &str) -> Result<BTreeMap<String, Value>, RepoError> {
+        let (repo, conn) = self.reader();
+        repo.relation_edges(conn, alias, id).await
+    }
+
+    /// Load related entities together with the edge payloads attached via `connect_with`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let members = snugom.teams().get_related_with_edges::<User>(&team_id, "members").await?;
+    /// let role: Option<Membership> = members.edge(&user_id).transpose()?;
+    /// ```
+    pub async fn get_related_with_edges<U>(&mut self, id: &str, alias: &str) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + DeserializeOwned,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_related_with_edges(conn, alias, id).await
+    }
+
+    /// Load a relation for a whole list of entities in two pipelined round trips.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let posts = snugom.posts().find_many(query).await?.items;
+    /// let authors: HashMap<String, Vec<User>> = snugom.posts().load_relations(&posts, "author").await?;
+    /// ```
+    pub async fn load_relations<U>(&mut self, parents: &[T], alias: &str) -> Result<HashMap<String, Vec<U>>, RepoError>
+    where
+        T: Serialize,
+        U: SnugomModel + DeserializeOwned + Clone,
+    {
+        let (repo, conn) = self.reader();
+        repo.load_relations(conn, parents, alias).await
+    }
+
+    /// Rebuild this collection's relation sets from the belongs-to foreign keys on each entity.
+    ///
+    /// See [`Repo::rebuild_relations`].
+    pub async fn rebuild_relations(&mut self) -> Result<RelationRepairReport, RepoError> {
+        self.repo.rebuild_relations(&mut self.conn).await
+    }
+
+    /// Load a page of related entities, reading the relation set directly unless `options`
+    /// filter or sort.
+    ///
+    /// See [`Repo::get_related`]; the generated `{Entity}Relations` accessors call this.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let posts = snugom.users().get_related::<Post>(&user_id, "posts",
+    ///     RelationQueryOptions::new().with_limit(20),
+    /// ).await?;
+    /// ```
+    pub async fn get_related<U>(
+        &mut self,
+        id: &str,
+        alias: &str,
+        options: RelationQueryOptions,
+    ) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + crate::search::SearchEntity,
+    {
+        let (repo, conn) = self.reader();
+        repo.get_related(conn, alias, id, options).await
+    }
+
+    /// Load related entities filtered and sorted by the target's search index.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let published = snugom.users().find_related::<Post>(&user_id, "posts",
+    ///     RelationQueryOptions::new()
+    ///         .with_condition(FilterCondition::tag_eq("status", "published"))
+    ///         .with_sort("-created_at"),
+    /// ).await?;
+    /// ```
+    pub async fn find_related<U>(
+        &mut self,
+        id: &str,
+        alias: &str,
+        options: RelationQueryOptions,
+    ) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + crate::search::SearchEntity,
+    {
+        let (repo, conn) = self.reader();
+        repo.find_related(conn, alias, id, options).await
     }
 }
 
@@ -170,7 +435,8 @@ where
             page_size: Some(1),
             ..query
         };
-        let result = self.repo.search_with_query(&mut self.conn, limited_query).await?;
+        let (repo, conn) = self.reader();
+        let result = repo.search_with_query(conn, limited_query).await?;
         Ok(result.items.into_iter().next())
     }
 
@@ -185,7 +451,35 @@ where
     ///
     /// Returns a `SearchResult` containing the matching entities and pagination info.
     pub async fn find_many(&mut self, query: SearchQuery) -> Result<SearchResult<T>, RepoError> {
-        self.repo.search_with_query(&mut self.conn, query).await
+        let (repo, conn) = self.reader();
+        repo.search_with_query(conn, query).await
+    }
+
+    /// Find entities matching query and convert each one with `f`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let cards = snugom.users().find_many_map(query, |user| UserCard::from(user)).await?;
+    /// ```
+    pub async fn find_many_map<V, F>(&mut self, query: SearchQuery, f: F) -> Result<SearchResult<V>, RepoError>
+    where
+        F: FnMut(T) -> V,
+    {
+        Ok(self.find_many(query).await?.map(f))
+    }
+
+    /// Find entities matching query as view model `V`, fetching only the fields it declares.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let cards: SearchResult<UserCard> = snugom.users().find_many_as(query).await?;
+    /// ```
+    pub async fn find_many_as<V>(&mut self, query: SearchQuery) -> Result<SearchResult<V>, RepoError>
+    where
+        V: ViewModel<T>,
+    {
+        let (repo, conn) = self.reader();
+        repo.search_view(conn, query).await
     }
 
     /// Count entities matching query.
@@ -209,7 +503,8 @@ where
 {
     /// Create an entity from a builder.
     ///
-    /// Returns the `CreateResult` containing the ID and raw responses.
+    /// Returns the `CreateResult` containing the ID and raw responses, or
+    /// `RepoError::AlreadyExists` if an entity with the same ID exists.
     /// Use `create_and_get` if you need the full entity back.
     pub async fn create<B>(&mut self, builder: B) -> Result<CreateResult, RepoError>
     where
@@ -244,7 +539,19 @@ where
             .await
     }
 
-    /// Update an entity and return the full updated entity.
+    /// Update an entity only while it matches `condition`, checked atomically with the write.
+    ///
+    /// Fails with `RepoError::PreconditionFailed` otherwise; see [`Repo::update_patch_if`].
+    pub async fn update_if<B>(&mut self, condition: FilterCondition, builder: B) -> Result<Vec<Value>, RepoError>
+    where
+        B: UpdatePatchBuilder,
+        B::Entity: EntityMetadata,
+        T: EntityMetadata + SearchEntity + Serialize,
+    {
+        self.repo.update_patch_if(&mut self.conn, condition, builder).await
+    }
+
+    /// Update an entity and return the full updated entity, read back from the primary.
     pub async fn update_and_get<B>(&mut self, id: &str, builder: B) -> Result<T, RepoError>
     where
         B: UpdatePatchBuilder,
@@ -254,7 +561,16 @@ where
         self.repo
             .update_patch_with_conn(&mut self.conn, builder)
             .await?;
-        self.get_or_error(id).await
+        self.repo.get(&mut self.conn, id).await?.ok_or(RepoError::NotFound {
+            entity_id: Some(id.to_string()),
+        })
+    }
+
+    /// Bump an entity's version and `updated_at` without changing anything else; see
+    /// [`Repo::touch`].
+    pub async fn touch(&mut self, id: &str) -> Result<(), RepoError> {
+        self.repo.touch_with_conn(&mut self.conn, id).await?;
+        Ok(())
     }
 
     /// Delete an entity by ID.
@@ -282,28 +598,129 @@ impl<T> CollectionHandle<T>
 where
     T: SnugomModel + DeserializeOwned,
 {
-    /// Create multiple entities.
+    /// Create multiple entities with the default [`BulkCreateOptions`].
     ///
-    /// Returns `BulkCreateResult` containing the count and IDs of created entities.
+    /// See [`CollectionHandle::create_many_with`].
     pub async fn create_many<B>(&mut self, builders: Vec<B>) -> Result<BulkCreateResult, RepoError>
     where
         B: MutationPayloadBuilder,
         B::Entity: EntityMetadata,
     {
-        let mut ids = Vec::with_capacity(builders.len());
-        let mut responses = Vec::with_capacity(builders.len());
+        self.create_many_with(builders, BulkCreateOptions::default()).await
+    }
 
-        for builder in builders {
-            let result = self.repo.create_with_conn(&mut self.conn, builder).await?;
-            ids.push(result.id);
-            responses.push(result.responses);
-        }
+    /// Create multiple entities, pipelining the writes in concurrent chunks.
+    ///
+    /// Every item is validated up front, async validators included. Valid items are split into
+    /// chunks of `options.chunk_size`, each sent as one pipeline of mutation scripts, with up to
+    /// `options.parallelism` chunks in flight. Items with nested creates run one at a time
+    /// afterwards. Like `create`, an item whose id already exists fails with
+    /// `RepoError::AlreadyExists`.
+    ///
+    /// Per-item errors (validation, conflicts, unique constraints) are collected in
+    /// `BulkCreateResult::failures` rather than returned, so one bad item never discards
+    /// the rest of the batch.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snugom.create_many",
+            skip_all,
+            fields(
+                collection = %self.repo.descriptor().collection,
+                items = builders.len(),
+                created = tracing::field::Empty,
+                failed = tracing::field::Empty,
+                elapsed_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn create_many_with<B>(
+        &mut self,
+        builders: Vec<B>,
+        options: BulkCreateOptions,
+    ) -> Result<BulkCreateResult, RepoError>
+    where
+        B: MutationPayloadBuilder,
+        B::Entity: EntityMetadata,
+    {
+        let collection = self.repo.descriptor().collection.clone();
+        telemetry::observe("create_many", &collection, async {
+            let mut outcomes: Vec<Option<Result<CreateResult, RepoError>>> = builders.iter().map(|_| None).collect();
+            let mut pipelined = Vec::new();
+            let mut pipelined_indices = Vec::new();
+            let mut standalone = Vec::new();
+
+            for (index, builder) in builders.into_iter().enumerate() {
+                let payload = match builder.into_payload() {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        outcomes[index] = Some(Err(RepoError::Validation(err)));
+                        continue;
+                    }
+                };
+                if !payload.nested.is_empty() {
+                    standalone.push((index, payload));
+                    continue;
+                }
+                match self.repo.prepare_bulk_create(&mut self.conn, payload).await {
+                    Ok((entity_id, plan)) => {
+                        pipelined_indices.push(index);
+                        pipelined.push((index, entity_id, plan));
+                    }
+                    Err(err) => outcomes[index] = Some(Err(err)),
+                }
+            }
+
+            let chunk_size = options.chunk_size.max(1);
+            let parallelism = options.parallelism.max(1);
+            let mut tasks = JoinSet::new();
+            let mut pending = pipelined.into_iter().peekable();
+            while pending.peek().is_some() {
+                if tasks.len() >= parallelism
+                    && let Some(joined) = tasks.join_next().await
+                {
+                    record_chunk(&mut outcomes, joined)?;
+                }
+                let chunk: Vec<_> = pending.by_ref().take(chunk_size).collect();
+                let interceptor = self.repo.interceptor().cloned();
+                tasks.spawn(telemetry::in_current_span(create_chunk(self.conn.clone(), interceptor, chunk)));
+            }
+            while let Some(joined) = tasks.join_next().await {
+                record_chunk(&mut outcomes, joined)?;
+            }
+
+            for (index, payload) in standalone {
+                outcomes[index] = Some(self.repo.create_payload_with_conn(&mut self.conn, payload).await);
+            }
+            // The repository audits standalone creates itself
+            for index in pipelined_indices {
+                if let Some(Ok(created)) = &outcomes[index] {
+                    self.repo.record_audit(&mut self.conn, &created.id, AuditAction::Create, &[]).await;
+                }
+            }
 
-        Ok(BulkCreateResult {
-            count: ids.len() as u64,
-            ids,
-            responses,
+            let mut result = BulkCreateResult {
+                count: 0,
+                ids: Vec::new(),
+                responses: Vec::new(),
+                failures: Vec::new(),
+            };
+            for (index, outcome) in outcomes.into_iter().enumerate() {
+                match outcome {
+                    Some(Ok(created)) => {
+                        result.ids.push(created.id);
+                        result.responses.push(created.responses);
+                    }
+                    Some(Err(error)) => result.failures.push(BulkCreateFailure { index, error }),
+                    None => unreachable!("every bulk create item is recorded"),
+                }
+            }
+            result.count = result.ids.len() as u64;
+            telemetry::record("created", result.count);
+            telemetry::record("failed", result.failures.len());
+            Ok(result)
         })
+        .await
     }
 
     /// Delete multiple entities by IDs.
@@ -355,6 +772,76 @@ where
     }
 }
 
+type ChunkOutcome = Vec<(usize, Result<CreateResult, RepoError>)>;
+
+/// Run one chunk of prepared creates, pipelining the single-command plans.
+///
+/// Each plan run on its own and the pipeline as a whole are reported to `interceptor`.
+async fn create_chunk(
+    mut conn: ConnectionManager,
+    interceptor: Option<SharedInterceptor>,
+    chunk: Vec<(usize, String, MutationPlan)>,
+) -> ChunkOutcome {
+    let mut outcome = Vec::with_capacity(chunk.len());
+    let mut batched = Vec::with_capacity(chunk.len());
+    let mut commands = Vec::with_capacity(chunk.len());
+    for (index, entity_id, plan) in chunk {
+        if plan.commands.len() == 1 {
+            batched.push((index, entity_id));
+            commands.extend(plan.commands);
+        } else {
+            // Relation follow-ups depend on the upsert, so the plan runs in order on its own
+            let (operation, key) = (plan.commands[0].operation(), plan.commands[0].key().to_string());
+            let result = interceptor::observe(interceptor.as_ref(), operation, &key, execute_plan(&mut conn, &plan)).await;
+            outcome.push((index, result.map(|responses| CreateResult::from_responses(entity_id, responses))));
+        }
+    }
+
+    let Some(first) = commands.first() else {
+        return outcome;
+    };
+    let (operation, key) = (first.operation(), first.key().to_string());
+    match interceptor::observe(interceptor.as_ref(), operation, &key, execute_pipelined(&mut conn, &commands)).await {
+        Ok(replies) => {
+            for ((index, entity_id), reply) in batched.into_iter().zip(replies) {
+                outcome.push((index, reply.map(|value| CreateResult::from_responses(entity_id, vec![value]))));
+            }
+        }
+        Err(err) => {
+            // The round trip failed as a whole, so none of the chunk is known to have been written
+            for (index, _) in batched {
+                outcome.push((index, Err(chunk_error(&err))));
+            }
+        }
+    }
+    outcome
+}
+
+fn record_chunk(
+    outcomes: &mut [Option<Result<CreateResult, RepoError>>],
+    joined: Result<ChunkOutcome, JoinError>,
+) -> Result<(), RepoError> {
+    let chunk = joined.map_err(|err| RepoError::Other {
+        message: Cow::Owned(format!("bulk create task failed: {err}")),
+    })?;
+    for (index, result) in chunk {
+        outcomes[index] = Some(result);
+    }
+    Ok(())
+}
+
+/// Copy a pipeline-wide error onto one item of the chunk.
+fn chunk_error(err: &RepoError) -> RepoError {
+    match err {
+        RepoError::Redis(redis_err) => {
+            RepoError::Redis((redis_err.kind(), "bulk create pipeline failed", redis_err.to_string()).into())
+        }
+        other => RepoError::Other {
+            message: Cow::Owned(other.to_string()),
+        },
+    }
+}
+
 // ============ Query-based Bulk Operations ============
 
 impl<T> CollectionHandle<T>
@@ -472,6 +959,27 @@ where
             .get_or_create(&mut self.conn, create_builder)
             .await
     }
+
+    /// Get or create, resolving the existing entity by the unique constraint on `unique_fields`
+    /// (then by id); see [`Repo::get_or_create_by`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// let user = snugom.users().get_or_create_by(&["email"], User::validation_builder().email(email)).await?;
+    /// ```
+    pub async fn get_or_create_by<C>(
+        &mut self,
+        unique_fields: &[&str],
+        create_builder: C,
+    ) -> Result<GetOrCreateResult<T>, RepoError>
+    where
+        C: MutationPayloadBuilder,
+        C::Entity: EntityMetadata,
+    {
+        self.repo
+            .get_or_create_by(&mut self.conn, unique_fields, create_builder)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -487,8 +995,10 @@ mod tests {
             count: 3,
             ids: vec!["id1".to_string(), "id2".to_string(), "id3".to_string()],
             responses: vec![],
+            failures: vec![],
         };
         assert_eq!(result.count, 3);
+        assert!(result.is_complete());
         assert_eq!(result.ids.len(), 3);
     }
 }
@@ -0,0 +1,366 @@
+//! Environment-driven connection settings for [`Client`](super::Client).
+
+use std::time::Duration;
+
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+
+use crate::errors::ConfigError;
+
+/// Primary Redis URL. Falls back to `REDIS_URL`, which the CLI also reads.
+pub const ENV_URL: &str = "SNUGOM_REDIS_URL";
+/// Key prefix for every collection.
+pub const ENV_PREFIX: &str = "SNUGOM_PREFIX";
+/// Timeout for establishing a connection, in milliseconds.
+pub const ENV_CONNECT_TIMEOUT_MS: &str = "SNUGOM_CONNECT_TIMEOUT_MS";
+/// Timeout for a single command response, in milliseconds.
+pub const ENV_RESPONSE_TIMEOUT_MS: &str = "SNUGOM_RESPONSE_TIMEOUT_MS";
+/// Reconnect attempts before a command fails.
+pub const ENV_RETRY_MAX: &str = "SNUGOM_RETRY_MAX";
+/// Base reconnect delay in milliseconds; doubles with each attempt.
+pub const ENV_RETRY_BASE_DELAY_MS: &str = "SNUGOM_RETRY_BASE_DELAY_MS";
+/// Upper bound on the reconnect delay, in milliseconds.
+pub const ENV_RETRY_MAX_DELAY_MS: &str = "SNUGOM_RETRY_MAX_DELAY_MS";
+/// `true` to require TLS (`rediss://`) on every URL. TLS needs snugom's `tls` feature.
+pub const ENV_TLS: &str = "SNUGOM_REDIS_TLS";
+/// Optional replica URL for read-only traffic.
+pub const ENV_READ_REPLICA_URL: &str = "SNUGOM_READ_REPLICA_URL";
+
+const FALLBACK_URL: &str = "REDIS_URL";
+
+/// Reconnect behaviour applied to the connection manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub base_delay: Duration,
+    pub max_delay: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 6,
+            base_delay: Duration::from_millis(100),
+            max_delay: None,
+        }
+    }
+}
+
+/// Timeouts and reconnect behaviour for a connection, as taken by
+/// [`Client::connect_with`](super::Client::connect_with) and
+/// [`Client::connect_lazy`](super::Client::connect_lazy).
+///
+/// The connection manager reconnects on its own after a dropped connection, waiting
+/// `retry.base_delay`, then twice that, and so on up to `retry.max_delay`, for at most
+/// `retry.max_retries` attempts before the command fails.
+///
+/// # Example
+/// ```ignore
+/// let options = ClientOptions::new()
+///     .with_connect_timeout(Duration::from_millis(500))
+///     .with_response_timeout(Duration::from_secs(2))
+///     .with_backoff(Duration::from_millis(50), Duration::from_secs(5))
+///     .with_max_retries(10);
+/// let client = Client::connect_with(url, "myapp", &options).await?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientOptions {
+    pub connect_timeout: Option<Duration>,
+    pub response_timeout: Option<Duration>,
+    pub retry: RetryPolicy,
+}
+
+impl ClientOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = Some(timeout);
+        self
+    }
+
+    /// Wait `base_delay` before the first reconnect attempt, doubling it each time up to `max_delay`.
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.retry.base_delay = base_delay;
+        self.retry.max_delay = Some(max_delay.max(base_delay));
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.retry.max_retries = max_retries;
+        self
+    }
+
+    /// Connection manager settings derived from the timeouts and retry policy.
+    pub fn manager_config(&self) -> ConnectionManagerConfig {
+        let mut manager = ConnectionManagerConfig::new()
+            .set_number_of_retries(self.retry.max_retries)
+            .set_exponent_base(2)
+            .set_factor(self.retry.base_delay.as_millis() as u64);
+        if let Some(max_delay) = self.retry.max_delay {
+            manager = manager.set_max_delay(max_delay.as_millis() as u64);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            manager = manager.set_connection_timeout(timeout);
+        }
+        if let Some(timeout) = self.response_timeout {
+            manager = manager.set_response_timeout(timeout);
+        }
+        manager
+    }
+}
+
+/// Connection settings for a SnugOM [`Client`](super::Client).
+///
+/// # Example
+/// ```ignore
+/// let config = ClientConfig::from_env()?;
+/// let client = Client::from_config(&config).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub url: String,
+    pub prefix: String,
+    pub connect_timeout: Option<Duration>,
+    pub response_timeout: Option<Duration>,
+    pub retry: RetryPolicy,
+    pub tls: bool,
+    pub read_replica_url: Option<String>,
+}
+
+impl ClientConfig {
+    /// Settings for `url` and `prefix` with defaults for everything else.
+    pub fn new(url: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            prefix: prefix.into(),
+            connect_timeout: None,
+            response_timeout: None,
+            retry: RetryPolicy::default(),
+            tls: false,
+            read_replica_url: None,
+        }
+    }
+
+    /// Load settings from the process environment. See the `ENV_*` constants for variable names.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|var| std::env::var(var).ok())
+    }
+
+    /// Load settings through `lookup`, which returns the value of a variable if set.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let get = |var: &str| lookup(var).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+        let url = get(ENV_URL).or_else(|| get(FALLBACK_URL)).ok_or(ConfigError::Missing { var: ENV_URL })?;
+        let prefix = get(ENV_PREFIX).ok_or(ConfigError::Missing { var: ENV_PREFIX })?;
+        let mut config = Self::new(url, prefix);
+
+        config.connect_timeout = get(ENV_CONNECT_TIMEOUT_MS)
+            .map(|value| parse_millis(ENV_CONNECT_TIMEOUT_MS, &value))
+            .transpose()?;
+        config.response_timeout = get(ENV_RESPONSE_TIMEOUT_MS)
+            .map(|value| parse_millis(ENV_RESPONSE_TIMEOUT_MS, &value))
+            .transpose()?;
+        if let Some(value) = get(ENV_RETRY_MAX) {
+            config.retry.max_retries = parse_number(ENV_RETRY_MAX, &value)?;
+        }
+        if let Some(value) = get(ENV_RETRY_BASE_DELAY_MS) {
+            config.retry.base_delay = parse_millis(ENV_RETRY_BASE_DELAY_MS, &value)?;
+        }
+        config.retry.max_delay = get(ENV_RETRY_MAX_DELAY_MS)
+            .map(|value| parse_millis(ENV_RETRY_MAX_DELAY_MS, &value))
+            .transpose()?;
+        if let Some(value) = get(ENV_TLS) {
+            config.tls = parse_bool(ENV_TLS, &value)?;
+        }
+        config.read_replica_url = get(ENV_READ_REPLICA_URL);
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the settings are usable before any connection is attempted.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_url(ENV_URL, &self.url, self.tls)?;
+        if let Some(replica) = &self.read_replica_url {
+            validate_url(ENV_READ_REPLICA_URL, replica, self.tls)?;
+        }
+        if self.prefix.contains(char::is_whitespace) {
+            return Err(invalid(ENV_PREFIX, &self.prefix, "must not contain whitespace"));
+        }
+        if let Some(max_delay) = self.retry.max_delay
+            && max_delay < self.retry.base_delay
+        {
+            return Err(invalid(
+                ENV_RETRY_MAX_DELAY_MS,
+                &max_delay.as_millis().to_string(),
+                "must not be less than the base delay",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The timeouts and retry policy as [`ClientOptions`].
+    pub fn options(&self) -> ClientOptions {
+        ClientOptions {
+            connect_timeout: self.connect_timeout,
+            response_timeout: self.response_timeout,
+            retry: self.retry.clone(),
+        }
+    }
+
+    /// Connection manager settings derived from the timeouts and retry policy.
+    pub fn manager_config(&self) -> ConnectionManagerConfig {
+        self.options().manager_config()
+    }
+
+    /// Open a connection manager for the primary URL.
+    pub async fn connect(&self) -> Result<ConnectionManager, redis::RedisError> {
+        let client = redis::Client::open(self.url.as_str())?;
+        ConnectionManager::new_with_config(client, self.manager_config()).await
+    }
+
+    /// Open a connection manager for the read replica, if one is configured.
+    pub async fn connect_read_replica(&self) -> Result<Option<ConnectionManager>, redis::RedisError> {
+        let Some(url) = self.read_replica_url.as_deref() else {
+            return Ok(None);
+        };
+        let client = redis::Client::open(url)?;
+        Ok(Some(ConnectionManager::new_with_config(client, self.manager_config()).await?))
+    }
+}
+
+fn invalid(var: &'static str, value: &str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        var,
+        value: value.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn parse_number(var: &'static str, value: &str) -> Result<usize, ConfigError> {
+    value
+        .parse()
+        .map_err(|_| invalid(var, value, "expected a non-negative integer"))
+}
+
+fn parse_millis(var: &'static str, value: &str) -> Result<Duration, ConfigError> {
+    let millis: u64 = value
+        .parse()
+        .map_err(|_| invalid(var, value, "expected a duration in milliseconds"))?;
+    if millis == 0 {
+        return Err(invalid(var, value, "must be greater than zero"));
+    }
+    Ok(Duration::from_millis(millis))
+}
+
+fn parse_bool(var: &'static str, value: &str) -> Result<bool, ConfigError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(invalid(var, value, "expected true or false")),
+    }
+}
+
+fn validate_url(var: &'static str, value: &str, tls: bool) -> Result<(), ConfigError> {
+    let parsed = url::Url::parse(value).map_err(|err| invalid(var, value, err.to_string()))?;
+    match parsed.scheme() {
+        "rediss" if cfg!(feature = "tls") => Ok(()),
+        "rediss" => Err(invalid(var, value, "rediss:// URLs need snugom's `tls` feature")),
+        "redis" | "unix" | "redis+unix" if !tls => Ok(()),
+        "redis" | "unix" | "redis+unix" => Err(invalid(var, value, format!("{ENV_TLS} is set; use a rediss:// URL"))),
+        other => Err(invalid(var, value, format!("unsupported scheme `{other}`"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn load(vars: &[(&str, &str)]) -> Result<ClientConfig, ConfigError> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ClientConfig::from_lookup(|var| vars.get(var).cloned())
+    }
+
+    #[test]
+    fn loads_defaults_and_falls_back_to_redis_url() {
+        let config = load(&[("REDIS_URL", "redis://localhost:6379"), (ENV_PREFIX, "app")]).unwrap();
+        assert_eq!(config, ClientConfig::new("redis://localhost:6379", "app"));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn loads_every_setting() {
+        let config = load(&[
+            (ENV_URL, "rediss://primary:6380"),
+            (ENV_PREFIX, "app"),
+            (ENV_CONNECT_TIMEOUT_MS, "250"),
+            (ENV_RESPONSE_TIMEOUT_MS, "1000"),
+            (ENV_RETRY_MAX, "3"),
+            (ENV_RETRY_BASE_DELAY_MS, "50"),
+            (ENV_RETRY_MAX_DELAY_MS, "2000"),
+            (ENV_TLS, "true"),
+            (ENV_READ_REPLICA_URL, "rediss://replica:6380"),
+        ])
+        .unwrap();
+        assert_eq!(config.connect_timeout, Some(Duration::from_millis(250)));
+        assert_eq!(config.response_timeout, Some(Duration::from_secs(1)));
+        assert_eq!(
+            config.retry,
+            RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(50),
+                max_delay: Some(Duration::from_secs(2)),
+            }
+        );
+        assert!(config.tls);
+        assert_eq!(config.read_replica_url.as_deref(), Some("rediss://replica:6380"));
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_values() {
+        assert!(matches!(load(&[(ENV_PREFIX, "app")]), Err(ConfigError::Missing { var: ENV_URL })));
+        assert!(matches!(
+            load(&[(ENV_URL, "redis://localhost")]),
+            Err(ConfigError::Missing { var: ENV_PREFIX })
+        ));
+
+        let err = load(&[(ENV_URL, "redis://localhost"), (ENV_PREFIX, "app"), (ENV_RETRY_MAX, "many")]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid value \"many\" for SNUGOM_RETRY_MAX: expected a non-negative integer");
+
+        assert!(matches!(
+            load(&[(ENV_URL, "http://localhost"), (ENV_PREFIX, "app")]),
+            Err(ConfigError::Invalid { var: ENV_URL, .. })
+        ));
+        assert!(matches!(
+            load(&[(ENV_URL, "redis://localhost"), (ENV_PREFIX, "app"), (ENV_TLS, "yes")]),
+            Err(ConfigError::Invalid { var: ENV_URL, .. })
+        ));
+        assert!(matches!(
+            load(&[
+                (ENV_URL, "redis://localhost"),
+                (ENV_PREFIX, "app"),
+                (ENV_RETRY_BASE_DELAY_MS, "500"),
+                (ENV_RETRY_MAX_DELAY_MS, "100"),
+            ]),
+            Err(ConfigError::Invalid { var: ENV_RETRY_MAX_DELAY_MS, .. })
+        ));
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn rejects_tls_urls_without_the_tls_feature() {
+        let err = load(&[(ENV_URL, "rediss://primary:6380"), (ENV_PREFIX, "app")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"rediss://primary:6380\" for SNUGOM_REDIS_URL: rediss:// URLs need snugom's `tls` feature"
+        );
+    }
+}
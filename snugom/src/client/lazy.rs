@@ -0,0 +1,107 @@
+//! A [`Client`] whose connection is opened on first use.
+
+use std::{fmt, sync::Arc};
+
+use redis::aio::ConnectionManager;
+use tokio::sync::OnceCell;
+
+use super::{Client, ClientOptions};
+
+type Setup = Arc<dyn Fn(Client) -> Client + Send + Sync>;
+
+/// Hands out a [`Client`], connecting to Redis the first time one is asked for.
+///
+/// Created by [`Client::connect_lazy`] without touching the network, so an application can boot
+/// while Redis is still coming up. A failed connection attempt is returned to that caller and
+/// the next call tries again; once connected, the client (and its connection manager, which
+/// reconnects on its own) is shared by every clone.
+///
+/// # Example
+/// ```ignore
+/// let lazy = Client::connect_lazy(url, "myapp", ClientOptions::new())?
+///     .with_setup(|client| client.with_slow_query_threshold(Duration::from_millis(50)));
+/// // ... later, in a request handler
+/// let guild = lazy.client().await?.collection::<Guild>().get(&id).await?;
+/// ```
+#[derive(Clone)]
+pub struct LazyClient {
+    redis: redis::Client,
+    prefix: String,
+    options: ClientOptions,
+    setup: Option<Setup>,
+    client: Arc<OnceCell<Client>>,
+}
+
+impl LazyClient {
+    pub(super) fn new(redis: redis::Client, prefix: String, options: ClientOptions) -> Self {
+        Self {
+            redis,
+            prefix,
+            options,
+            setup: None,
+            client: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Configure the client once it connects, e.g. with an interceptor or audit settings.
+    pub fn with_setup(mut self, setup: impl Fn(Client) -> Client + Send + Sync + 'static) -> Self {
+        self.setup = Some(Arc::new(setup));
+        self
+    }
+
+    /// The connected client, connecting first if no caller has yet.
+    pub async fn client(&self) -> Result<Client, redis::RedisError> {
+        let client = self
+            .client
+            .get_or_try_init(|| async {
+                let conn = ConnectionManager::new_with_config(self.redis.clone(), self.options.manager_config()).await?;
+                let client = Client::new(conn, self.prefix.clone());
+                Ok::<_, redis::RedisError>(match &self.setup {
+                    Some(setup) => setup(client),
+                    None => client,
+                })
+            })
+            .await?;
+        Ok(client.clone())
+    }
+
+    /// Whether a connection has been made.
+    pub fn is_connected(&self) -> bool {
+        self.client.initialized()
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+}
+
+impl fmt::Debug for LazyClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyClient")
+            .field("prefix", &self.prefix)
+            .field("options", &self.options)
+            .field("connected", &self.is_connected())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn connects_on_first_use_and_retries_after_failures() {
+        assert!(Client::connect_lazy("http://localhost", "app", ClientOptions::new()).is_err());
+
+        // Nothing listens on port 1, so every attempt fails fast
+        let options = ClientOptions::new().with_connect_timeout(Duration::from_millis(200)).with_max_retries(0);
+        let lazy = Client::connect_lazy("redis://127.0.0.1:1", "app", options).expect("valid url");
+        assert!(!lazy.is_connected());
+        assert!(lazy.client().await.is_err());
+        assert!(lazy.client().await.is_err());
+        assert!(!lazy.is_connected());
+        assert_eq!(lazy.prefix(), "app");
+    }
+}
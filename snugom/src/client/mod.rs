@@ -4,7 +4,11 @@
 //! - `Client` - Main entry point for database operations
 //! - `CollectionHandle<T>` - Type-safe accessor for CRUD operations
 //! - `EntityRegistration` - Auto-registration of entities via inventory
-//! - `BulkCreateResult` - Result type for bulk create operations
+//! - `BulkCreateResult` / `BulkCreateOptions` - Result and batching settings for bulk creates
+//! - `ClientConfig` - Environment-driven connection settings
+//! - `ClientOptions` - Timeouts and reconnect backoff for `Client::connect_with`
+//! - `LazyClient` - A client that connects on first use
+//! - `ReadPreference` - Whether reads go to the primary or a replica
 //!
 //! # Example
 //! ```ignore
@@ -30,17 +34,46 @@
 //! ```
 
 mod collection;
+mod config;
+mod lazy;
 mod registration;
 
-pub use collection::{BulkCreateResult, CollectionHandle};
+pub use collection::{BulkCreateFailure, BulkCreateOptions, BulkCreateResult, CollectionHandle};
+pub use config::{
+    ClientConfig, ClientOptions, ENV_CONNECT_TIMEOUT_MS, ENV_PREFIX, ENV_READ_REPLICA_URL,
+    ENV_RESPONSE_TIMEOUT_MS, ENV_RETRY_BASE_DELAY_MS, ENV_RETRY_MAX, ENV_RETRY_MAX_DELAY_MS, ENV_TLS, ENV_URL, RetryPolicy,
+};
+pub use lazy::LazyClient;
 pub use registration::{
     EntityRegistration, get_entity_by_collection, get_entity_by_name, is_entity_registered,
     registered_entities,
 };
 
+use std::time::Duration;
+
 use redis::aio::ConnectionManager;
 
-use crate::{repository::Repo, types::SnugomModel};
+use crate::{
+    audit::{AuditLog, AuditSettings},
+    errors::RepoError,
+    interceptor::{CommandInterceptor, SharedInterceptor},
+    repository::Repo,
+    runtime::scripts::ScriptRegistry,
+    search::{SearchDefaults, SlowQuery, SlowQueryLog},
+    types::SnugomModel,
+    unit_of_work::UnitOfWork,
+};
+
+/// Where a [`Client`]'s collections send reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadPreference {
+    /// Every command goes to the primary.
+    #[default]
+    Primary,
+    /// Reads go to the replica connection set with [`Client::with_read_replica`] (or opened by
+    /// [`Client::from_config`]); mutations stay on the primary.
+    Replica,
+}
 
 /// Main client for Prisma-style database operations.
 ///
@@ -61,12 +94,158 @@ use crate::{repository::Repo, types::SnugomModel};
 pub struct Client {
     conn: ConnectionManager,
     prefix: String,
+    slow_queries: Option<SlowQueryLog>,
+    search_defaults: SearchDefaults,
+    replica: Option<ConnectionManager>,
+    read_preference: ReadPreference,
+    audit: Option<AuditSettings>,
+    interceptor: Option<SharedInterceptor>,
 }
 
 impl Client {
     /// Create a new client with the given connection and key prefix.
     pub fn new(conn: ConnectionManager, prefix: String) -> Self {
-        Self { conn, prefix }
+        Self {
+            conn,
+            prefix,
+            slow_queries: None,
+            search_defaults: SearchDefaults::default(),
+            replica: None,
+            read_preference: ReadPreference::Primary,
+            audit: None,
+            interceptor: None,
+        }
+    }
+
+    /// Record searches taking at least `threshold` in a ring buffer read by [`Client::slow_queries`].
+    ///
+    /// The latest [`DEFAULT_SLOW_QUERY_CAPACITY`](crate::search::DEFAULT_SLOW_QUERY_CAPACITY) are
+    /// kept; use [`Client::with_slow_query_log`] to size the buffer. With the `tracing` feature
+    /// each slow search also emits a warning event.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::connect(url, "myapp").await?.with_slow_query_threshold(Duration::from_millis(50));
+    /// // ...
+    /// for slow in client.slow_queries() {
+    ///     println!("{} took {:?}: {}", slow.index, slow.duration, slow.query);
+    /// }
+    /// ```
+    pub fn with_slow_query_threshold(self, threshold: Duration) -> Self {
+        self.with_slow_query_log(SlowQueryLog::new(threshold))
+    }
+
+    /// Record slow searches in `log`, which may be shared with other clients.
+    pub fn with_slow_query_log(mut self, log: SlowQueryLog) -> Self {
+        self.slow_queries = Some(log);
+        self
+    }
+
+    /// Run searches under `dialect` unless their [`SearchParams`](crate::search::SearchParams)
+    /// pick one, e.g. 4 to opt into its faster intersections.
+    pub fn with_search_dialect(mut self, dialect: u8) -> Self {
+        self.search_defaults.dialect = Some(dialect);
+        self
+    }
+
+    /// Bound searches to `ms` milliseconds on the server unless their params set a timeout.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::connect(url, "myapp").await?.with_search_timeout(250);
+    /// ```
+    pub fn with_search_timeout(mut self, ms: u64) -> Self {
+        self.search_defaults.timeout_ms = Some(ms);
+        self
+    }
+
+    /// Use `conn` for reads when the read preference is [`ReadPreference::Replica`].
+    pub fn with_read_replica(mut self, conn: ConnectionManager) -> Self {
+        self.replica = Some(conn);
+        self
+    }
+
+    /// Choose where reads go. With [`ReadPreference::Replica`], `get`, `search` and relation
+    /// reads of every collection use the replica connection; without one they stay on the primary.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::from_config(&config).await?.with_read_preference(ReadPreference::Replica);
+    /// ```
+    pub fn with_read_preference(mut self, preference: ReadPreference) -> Self {
+        self.read_preference = preference;
+        self
+    }
+
+    /// Record every create, update, and delete made through this client's collections in the
+    /// entity's audit stream, read back with [`Client::audit`].
+    pub fn with_audit(mut self, settings: AuditSettings) -> Self {
+        self.audit = Some(settings);
+        self
+    }
+
+    /// Attribute this client's writes to `actor` in the audit trail, turning auditing on.
+    ///
+    /// Clients are cheap to clone, so a request handler can derive one per caller.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = app.client.clone().with_actor(format!("user:{}", session.user_id));
+    /// client.collection::<Guild>().update(patch).await?;
+    /// ```
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.audit.get_or_insert_with(AuditSettings::default).actor = Some(actor.into());
+        self
+    }
+
+    /// Hand every read, search, and mutation script run by this client's collections to
+    /// `interceptor` once it completes, with its target key or index, duration, and outcome.
+    ///
+    /// Clones of the client share the interceptor.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::connect(url, "myapp").await?.with_interceptor(|event: &CommandEvent<'_>| {
+    ///     tracing::debug!(operation = event.operation, target = event.target, elapsed = ?event.duration);
+    /// });
+    /// ```
+    pub fn with_interceptor(mut self, interceptor: impl CommandInterceptor + 'static) -> Self {
+        self.interceptor = Some(SharedInterceptor::new(interceptor));
+        self
+    }
+
+    /// The audit trail of one entity.
+    ///
+    /// # Example
+    /// ```ignore
+    /// for entry in client.audit::<Guild>(&id).list(..).await? {
+    ///     println!("{} {:?} by {:?}: {:?}", entry.at, entry.action, entry.actor, entry.fields);
+    /// }
+    /// ```
+    pub fn audit<T: SnugomModel>(&self, entity_id: &str) -> AuditLog {
+        let key = Repo::<T>::new(self.prefix.clone()).audit_key(entity_id);
+        AuditLog::new(key, self.conn.clone())
+    }
+
+    /// Start staging writes to send together; see [`UnitOfWork`].
+    ///
+    /// The unit of work audits its writes when this client does, and reports to its interceptor.
+    pub fn unit_of_work(&self) -> UnitOfWork {
+        let mut work = UnitOfWork::new(self.conn.clone(), self.prefix.clone());
+        if let Some(settings) = &self.audit {
+            work = work.with_audit(settings.clone());
+        }
+        if let Some(interceptor) = &self.interceptor {
+            work = work.with_interceptor(interceptor.clone());
+        }
+        work
+    }
+
+    /// Searches that exceeded the slow query threshold, oldest first.
+    ///
+    /// Empty unless a threshold was configured.
+    pub fn slow_queries(&self) -> Vec<SlowQuery> {
+        self.slow_queries.as_ref().map(SlowQueryLog::entries).unwrap_or_default()
     }
 
     /// Create a client from an existing Redis connection URL.
@@ -81,6 +260,69 @@ impl Client {
         Ok(Self::new(conn, prefix.into()))
     }
 
+    /// Connect with the timeouts and reconnect backoff in `options`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let options = ClientOptions::new().with_connect_timeout(Duration::from_secs(1)).with_max_retries(3);
+    /// let client = Client::connect_with("redis://localhost:6379", "myapp", &options).await?;
+    /// ```
+    pub async fn connect_with(
+        url: &str,
+        prefix: impl Into<String>,
+        options: &ClientOptions,
+    ) -> Result<Self, redis::RedisError> {
+        let redis_client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new_with_config(redis_client, options.manager_config()).await?;
+        Ok(Self::new(conn, prefix.into()))
+    }
+
+    /// A [`LazyClient`] that connects with `options` the first time a client is asked for, so
+    /// startup doesn't fail while Redis is briefly unavailable.
+    ///
+    /// Only parsing `url` can fail here.
+    pub fn connect_lazy(
+        url: &str,
+        prefix: impl Into<String>,
+        options: ClientOptions,
+    ) -> Result<LazyClient, redis::RedisError> {
+        let redis_client = redis::Client::open(url)?;
+        Ok(LazyClient::new(redis_client, prefix.into(), options))
+    }
+
+    /// Create a client from validated [`ClientConfig`] settings.
+    ///
+    /// A configured read replica is connected too; reads use it once the read preference is
+    /// [`ReadPreference::Replica`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::from_config(&ClientConfig::from_env()?).await?;
+    /// ```
+    pub async fn from_config(config: &ClientConfig) -> Result<Self, redis::RedisError> {
+        let conn = config.connect().await?;
+        let client = Self::new(conn, config.prefix.clone());
+        Ok(match config.connect_read_replica().await? {
+            Some(replica) => client.with_read_replica(replica),
+            None => client,
+        })
+    }
+
+    /// Load the mutation scripts into the server's script cache, so the first write of each kind
+    /// doesn't pay for a `NOSCRIPT` retry.
+    ///
+    /// Optional: scripts are invoked by digest and load themselves when missing. See
+    /// [`ScriptRegistry`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// let client = Client::from_config(&ClientConfig::from_env()?).await?;
+    /// client.load_scripts().await?;
+    /// ```
+    pub async fn load_scripts(&self) -> Result<(), RepoError> {
+        ScriptRegistry::load(&mut self.conn.clone()).await
+    }
+
     /// Get a type-safe handle for the specified entity collection.
     ///
     /// This is the generic way to access any registered entity type.
@@ -92,8 +334,22 @@ impl Client {
     /// let guild = guilds.get(&id).await?;
     /// ```
     pub fn collection<T: SnugomModel>(&self) -> CollectionHandle<T> {
-        let repo = Repo::new(self.prefix.clone());
-        CollectionHandle::new(repo, self.conn.clone())
+        let mut repo = Repo::new(self.prefix.clone());
+        if let Some(log) = &self.slow_queries {
+            repo = repo.with_slow_query_log(log.clone());
+        }
+        repo = repo.with_search_defaults(self.search_defaults);
+        if let Some(settings) = &self.audit {
+            repo = repo.with_audit(settings.clone());
+        }
+        if let Some(interceptor) = &self.interceptor {
+            repo = repo.with_interceptor(interceptor.clone());
+        }
+        let handle = CollectionHandle::new(repo, self.conn.clone());
+        match (&self.replica, self.read_preference) {
+            (Some(replica), ReadPreference::Replica) => handle.with_read_connection(replica.clone()),
+            _ => handle,
+        }
     }
 
     /// Get the key prefix used by this client.
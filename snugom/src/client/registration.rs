@@ -21,6 +21,9 @@ pub struct EntityRegistration {
     pub service_name: &'static str,
     /// Function to get the entity descriptor
     pub descriptor_fn: fn() -> crate::types::EntityDescriptor,
+    /// Function building the entity's index definition under a key prefix; `None` for entities
+    /// without indexed fields
+    pub index_definition_fn: Option<fn(&str) -> crate::search::IndexDefinition>,
 }
 
 // Collect all EntityRegistration instances via inventory
@@ -0,0 +1,167 @@
+//! Transparent compression for `#[snugom(compressed)]` fields.
+//!
+//! A compressed field is serialized to JSON, compressed, and stored as a tagged base64
+//! string (`"gz:..."` or `"zstd:..."`) in place of its value. The tag records the codec
+//! so values written before a field was marked compressed, which carry no tag, are still
+//! read back untouched. The codecs live behind the `gzip` and `zstd` features; the derive
+//! refuses to compile a compressed field whose codec feature is off.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::Value;
+
+use crate::{errors::RepoError, types::Compression};
+
+impl Compression {
+    fn tag(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz:",
+            Compression::Zstd => "zstd:",
+        }
+    }
+
+    fn from_stored(stored: &str) -> Option<(Self, &str)> {
+        [Compression::Gzip, Compression::Zstd]
+            .into_iter()
+            .find_map(|codec| stored.strip_prefix(codec.tag()).map(|body| (codec, body)))
+    }
+}
+
+/// Compress `value` into its stored form. `null` stays `null` so optional fields can be cleared.
+pub fn compress_value(codec: Compression, value: &Value) -> Result<Value, RepoError> {
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    let json = serde_json::to_vec(value).map_err(|err| codec_error(codec, err))?;
+    let compressed = encode(codec, &json)?;
+    Ok(Value::String(format!("{}{}", codec.tag(), STANDARD.encode(compressed))))
+}
+
+/// Restore a value written by [`compress_value`]. Untagged values are returned unchanged.
+pub fn decompress_value(value: &Value) -> Result<Value, RepoError> {
+    let Some((codec, body)) = value.as_str().and_then(Compression::from_stored) else {
+        return Ok(value.clone());
+    };
+    let compressed = STANDARD.decode(body).map_err(|err| codec_error(codec, err))?;
+    let json = decode(codec, &compressed)?;
+    serde_json::from_slice(&json).map_err(|err| codec_error(codec, err))
+}
+
+fn codec_error(codec: Compression, err: impl std::fmt::Display) -> RepoError {
+    RepoError::Other {
+        message: format!("{codec:?} compression failed: {err}").into(),
+    }
+}
+
+#[allow(unused_variables)]
+fn encode(codec: Compression, bytes: &[u8]) -> Result<Vec<u8>, RepoError> {
+    match codec {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            use std::io::Write;
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes).map_err(|err| codec_error(codec, err))?;
+            encoder.finish().map_err(|err| codec_error(codec, err))
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::encode_all(bytes, 0).map_err(|err| codec_error(codec, err)),
+        #[allow(unreachable_patterns)]
+        _ => Err(missing_feature(codec)),
+    }
+}
+
+#[allow(unused_variables)]
+fn decode(codec: Compression, bytes: &[u8]) -> Result<Vec<u8>, RepoError> {
+    match codec {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            use std::io::Read;
+            let mut decoded = Vec::new();
+            flate2::read::GzDecoder::new(bytes)
+                .read_to_end(&mut decoded)
+                .map_err(|err| codec_error(codec, err))?;
+            Ok(decoded)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::decode_all(bytes).map_err(|err| codec_error(codec, err)),
+        #[allow(unreachable_patterns)]
+        _ => Err(missing_feature(codec)),
+    }
+}
+
+#[allow(dead_code)]
+fn missing_feature(codec: Compression) -> RepoError {
+    let feature = match codec {
+        Compression::Gzip => "gzip",
+        Compression::Zstd => "zstd",
+    };
+    RepoError::Other {
+        message: format!("snugom was built without the `{feature}` feature").into(),
+    }
+}
+
+/// Fails compilation when a compressed field's codec feature is disabled.
+#[cfg(feature = "gzip")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_require_gzip {
+    () => {};
+}
+
+#[cfg(not(feature = "gzip"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_require_gzip {
+    () => {
+        compile_error!("#[snugom(compressed)] requires the `gzip` feature of snugom");
+    };
+}
+
+/// Fails compilation when a compressed field's codec feature is disabled.
+#[cfg(feature = "zstd")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_require_zstd {
+    () => {};
+}
+
+#[cfg(not(feature = "zstd"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_require_zstd {
+    () => {
+        compile_error!("#[snugom(compressed = \"zstd\")] requires the `zstd` feature of snugom");
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn untagged_values_pass_through() {
+        assert_eq!(decompress_value(&json!("plain text")).unwrap(), json!("plain text"));
+        assert_eq!(decompress_value(&json!({ "a": 1 })).unwrap(), json!({ "a": 1 }));
+        assert_eq!(compress_value(Compression::Gzip, &Value::Null).unwrap(), Value::Null);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        let value = json!({ "body": "lorem ipsum ".repeat(200) });
+        let stored = compress_value(Compression::Gzip, &value).unwrap();
+        let text = stored.as_str().unwrap();
+        assert!(text.starts_with("gz:"));
+        assert!(text.len() < value.to_string().len());
+        assert_eq!(decompress_value(&stored).unwrap(), value);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let value = json!("lorem ipsum ".repeat(200));
+        let stored = compress_value(Compression::Zstd, &value).unwrap();
+        assert!(stored.as_str().unwrap().starts_with("zstd:"));
+        assert_eq!(decompress_value(&stored).unwrap(), value);
+    }
+}
@@ -1,5 +1,7 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap};
 
+use serde::Serialize;
+use serde_json::Value;
 use thiserror::Error;
 
 /// Top-level error type returned by SnugOM repositories.
@@ -26,6 +28,10 @@ pub enum RepoError {
     InvalidRequest { message: String },
 
     /// Unique constraint violation - the value(s) already exist on another entity.
+    ///
+    /// Raised by the create, upsert, and patch scripts before anything is written: `fields` are
+    /// the constrained fields (several for `unique_together`), `values` what the write tried to
+    /// store in them, and `existing_entity_id` the entity that holds them.
     #[error("unique constraint violation: fields {fields:?} with values {values:?} already exist on entity '{existing_entity_id}'")]
     UniqueConstraintViolation {
         fields: Vec<String>,
@@ -33,6 +39,10 @@ pub enum RepoError {
         existing_entity_id: String,
     },
 
+    /// A conditional update found the stored document no longer matching its condition.
+    #[error("precondition failed for entity '{entity_id}'")]
+    PreconditionFailed { entity_id: String },
+
     /// Entity already exists (for strict create operations).
     #[error("entity already exists: {entity_id}")]
     AlreadyExists { entity_id: String },
@@ -43,7 +53,10 @@ pub enum RepoError {
 }
 
 /// Collection of validation issues encountered while preparing a mutation.
-#[derive(Debug, Error)]
+///
+/// Serializes as `{"issues": [...]}`, suitable as a 422 response body.
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[derive(Debug, Error, Serialize)]
 #[error("validation errors: {issues:?}")]
 pub struct ValidationError {
     pub issues: Vec<ValidationIssue>,
@@ -67,14 +80,33 @@ impl ValidationError {
     pub fn is_empty(&self) -> bool {
         self.issues.is_empty()
     }
+
+    /// Every issue's English message, as `field: message` joined with `; `.
+    pub fn to_message(&self) -> String {
+        self.issues.iter().map(ValidationIssue::to_message).collect::<Vec<_>>().join("; ")
+    }
 }
 
 /// Detailed validation failure for a single field or logical path.
-#[derive(Debug, Clone)]
+///
+/// Serializes as `{"field", "code", "message", "params"}`. `code` is stable (`validation.length`,
+/// `validation.range`, `validation.regex`, `validation.enum`, ...) and `params` holds the values
+/// the message mentions, so a frontend can localize from `code` and `params` and ignore the
+/// English `message`:
+///
+/// ```json
+/// {"field": "name", "code": "validation.length", "message": "length must be at least 3", "params": {"min": 3}}
+/// ```
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationIssue {
     pub field: String,
     pub code: String,
+    /// English fallback
     pub message: String,
+    /// `min`/`max` for length and range rules, `pattern` for regex, `allowed` for enums
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
 }
 
 impl ValidationIssue {
@@ -83,9 +115,104 @@ impl ValidationIssue {
             field: field.into(),
             code: code.into(),
             message: message.into(),
+            params: BTreeMap::new(),
         }
     }
+
+    pub fn with_param(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// [`ValidationIssue::with_param`] for a numeric bound written as source text, e.g. `"0.5"`,
+    /// stored as a JSON number when it parses as one.
+    pub fn with_bound(self, name: impl Into<String>, repr: &str) -> Self {
+        let value = repr
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| repr.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number))
+            .unwrap_or_else(|| Value::String(repr.to_string()));
+        self.with_param(name, value)
+    }
+
+    /// The English message with its field, e.g. `name: length must be at least 3`.
+    pub fn to_message(&self) -> String {
+        format!("{}: {}", self.field, self.message)
+    }
 }
 
 /// Convenience alias used by later phases when validation passed.
 pub type ValidationResult<T> = Result<T, ValidationError>;
+
+/// Error raised while loading a [`ClientConfig`](crate::client::ClientConfig).
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// A required variable was not set.
+    #[error("missing required environment variable {var}")]
+    Missing { var: &'static str },
+
+    /// A variable was set but could not be parsed or failed validation.
+    #[error("invalid value {value:?} for {var}: {reason}")]
+    Invalid {
+        var: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Two different descriptors registered for one service and collection, typically two versions
+/// of an entity crate linked into the same binary.
+#[derive(Debug, Error)]
+#[error(
+    "conflicting descriptors for `{service}:{collection}` (registered schema {registered_version}, new schema \
+     {conflicting_version}): {}",
+    differences.join(", ")
+)]
+pub struct DescriptorConflict {
+    pub service: String,
+    pub collection: String,
+    pub registered_version: u32,
+    pub conflicting_version: u32,
+    /// What sets them apart, e.g. `+ field total: Number`; `-` marks the registered descriptor's
+    /// side and `+` the new one's
+    pub differences: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn validation_issues_serialize_codes_and_params() {
+        let error = ValidationError::new(vec![
+            ValidationIssue::new("name", "validation.length", "length must be at least 3").with_param("min", 3),
+            ValidationIssue::new("score", "validation.range", "value must be at most 0.5").with_bound("max", "0.5"),
+            ValidationIssue::new("email", "validation.email", "invalid email"),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap()["issues"],
+            json!([
+                {
+                    "field": "name",
+                    "code": "validation.length",
+                    "message": "length must be at least 3",
+                    "params": {"min": 3},
+                },
+                {
+                    "field": "score",
+                    "code": "validation.range",
+                    "message": "value must be at most 0.5",
+                    "params": {"max": 0.5},
+                },
+                {"field": "email", "code": "validation.email", "message": "invalid email"},
+            ])
+        );
+        assert_eq!(
+            error.to_message(),
+            "name: length must be at least 3; score: value must be at most 0.5; email: invalid email"
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! - Single entity creation with struct-literal syntax
 //! - Getting the created entity back
 //! - Bulk creation with `create_many()` (collection-level operation)
+//! - Per-item failure reporting and batching options with `create_many_with()`
 
 use anyhow::Result;
 use chrono::Utc;
@@ -11,7 +12,7 @@ use redis::aio::ConnectionManager;
 use serde::{Deserialize, Serialize};
 
 use super::support;
-use crate::{SnugomClient, SnugomEntity, snugom_create};
+use crate::{BulkCreateOptions, SnugomClient, SnugomEntity, snugom_create};
 
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
 #[snugom(schema = 1, service = "examples", collection = "products")]
@@ -102,5 +103,35 @@ pub async fn run() -> Result<()> {
     // Verify total count
     assert_eq!(products.count().await?, 5, "should have 5 products total");
 
+    // ============ create_many_with() ============
+    // Writes are pipelined in chunks; a bad item is reported without failing the batch
+    let builders = vec![
+        Product::validation_builder()
+            .name("Monitor".to_string())
+            .price(329)
+            .category("displays".to_string())
+            .created_at(Utc::now()),
+        // Missing the required `name`
+        Product::validation_builder()
+            .price(19)
+            .category("accessories".to_string())
+            .created_at(Utc::now()),
+        Product::validation_builder()
+            .name("Webcam".to_string())
+            .price(89)
+            .category("accessories".to_string())
+            .created_at(Utc::now()),
+    ];
+    let options = BulkCreateOptions {
+        chunk_size: 2,
+        parallelism: 2,
+    };
+    let partial = products.create_many_with(builders, options).await?;
+    assert_eq!(partial.count, 2);
+    assert!(!partial.is_complete());
+    let invalid: Vec<usize> = partial.validation_errors().map(|(index, _)| index).collect();
+    assert_eq!(invalid, vec![1], "the second item should fail validation");
+    assert_eq!(products.count().await?, 7, "valid items are still created");
+
     Ok(())
 }
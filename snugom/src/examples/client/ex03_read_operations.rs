@@ -3,6 +3,7 @@
 //! Demonstrates the various ways to read entities:
 //! - `get()` - Returns Option<T>
 //! - `get_or_error()` - Returns T or error if not found
+//! - `get_many()` - Batch fetch by IDs in one round trip
 //! - `exists()` - Check if entity exists
 //! - `count()` - Count all entities in collection
 //!
@@ -18,7 +19,7 @@ use super::support;
 use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_delete, errors::RepoError};
 
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "users")]
+#[snugom(schema = 1, service = "examples", collection = "read_users")]
 struct User {
     #[snugom(id)]
     id: String,
@@ -79,6 +80,14 @@ pub async fn run() -> Result<()> {
         other => panic!("expected NotFound error, got {other:?}"),
     }
 
+    // ============ get_many() ============
+    // One pipelined round trip; results keep the order of the IDs, with None for misses
+    let batch = users.get_many(&[bob.id.as_str(), "nonexistent", alice.id.as_str()]).await?;
+    assert_eq!(batch.len(), 3);
+    assert_eq!(batch[0].as_ref().map(|user| user.id.as_str()), Some(bob.id.as_str()));
+    assert!(batch[1].is_none());
+    assert_eq!(batch[2].as_ref().map(|user| user.id.as_str()), Some(alice.id.as_str()));
+
     // ============ exists() ============
     // Returns bool indicating if entity exists
     assert!(users.exists(&alice.id).await?, "alice should exist");
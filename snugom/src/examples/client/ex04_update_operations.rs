@@ -14,7 +14,7 @@ use super::support;
 use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_update, SearchQuery};
 
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "articles")]
+#[snugom(schema = 1, service = "examples", collection = "update_articles")]
 struct Article {
     #[snugom(id)]
     id: String,
@@ -17,7 +17,7 @@ use super::support;
 use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_update, errors::RepoError};
 
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "profiles")]
+#[snugom(schema = 1, service = "examples", collection = "validated_profiles")]
 struct Profile {
     #[snugom(id)]
     id: String,
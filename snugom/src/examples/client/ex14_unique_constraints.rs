@@ -16,7 +16,7 @@ use super::support;
 use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_update, errors::RepoError};
 
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "accounts")]
+#[snugom(schema = 1, service = "examples", collection = "unique_accounts")]
 struct Account {
     #[snugom(id)]
     id: String,
@@ -4,6 +4,8 @@
 //! - `connect` - Add IDs to a relation
 //! - `disconnect` - Remove IDs from a relation
 //! - `delete` - Disconnect and delete the related entity
+//! - `set` - Replace the whole relation
+//! - `connect_or_create` - Connect an entity by id, creating it if missing
 
 use anyhow::Result;
 use chrono::Utc;
@@ -156,5 +158,35 @@ pub async fn run() -> Result<()> {
     assert!(members.exists(&carol_id).await?); // Disconnected but exists
     assert!(members.exists(&dave_id).await?);
 
+    // ============ Replace the Whole Relation ============
+    // `set` disconnects everyone not listed; `connect_or_create` creates Erin the first time
+    // and just connects her after that
+    let erin_id = format!("{team_id}-erin");
+    for _ in 0..2 {
+        snugom_update!(client, Team(entity_id = team.id.clone()) {
+            members: [
+                set [alice_id.clone()],
+                connect_or_create TeamMember {
+                    id: erin_id.clone(),
+                    name: "Erin".to_string(),
+                    role: "designer".to_string(),
+                    team_id: team.id.clone(),
+                    created_at: Utc::now(),
+                },
+            ],
+        }).await?;
+    }
+
+    let mut roster: Vec<String> = teams
+        .get_related_with_edges::<TeamMember>(&team_id, "members")
+        .await?
+        .items
+        .into_iter()
+        .map(|member| member.name)
+        .collect();
+    roster.sort();
+    assert_eq!(roster, ["Alice", "Erin"]);
+    assert!(members.exists(&dave_id).await?, "set disconnects without deleting");
+
     Ok(())
 }
@@ -47,7 +47,7 @@ struct File {
 
 /// A project with tasks. No cascade - tasks remain when project deleted.
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "projects")]
+#[snugom(schema = 1, service = "examples", collection = "cascade_projects")]
 struct Project {
     #[snugom(id)]
     id: String,
@@ -73,7 +73,7 @@ struct Task {
     created_at: chrono::DateTime<Utc>,
     #[snugom(filterable(text))]
     title: String,
-    #[snugom(relation(target = "projects"))]
+    #[snugom(relation(target = "cascade_projects"))]
     project_id: String,
 }
 
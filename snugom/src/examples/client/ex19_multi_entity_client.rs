@@ -30,7 +30,7 @@ struct User {
 
 /// A product in the catalog.
 #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
-#[snugom(schema = 1, service = "examples", collection = "products")]
+#[snugom(schema = 1, service = "examples", collection = "shop_products")]
 struct Product {
     #[snugom(id)]
     id: String,
@@ -61,7 +61,7 @@ struct Order {
 
     /// Products in this order
     #[serde(default)]
-    #[snugom(relation(target = "products"))]
+    #[snugom(relation(target = "shop_products"))]
     products: Vec<String>,
 }
 
@@ -75,7 +75,7 @@ struct Review {
     created_at: chrono::DateTime<Utc>,
     #[snugom(filterable(tag), relation(target = "shop_users"))]
     user_id: String,
-    #[snugom(filterable(tag), relation(target = "products"))]
+    #[snugom(filterable(tag), relation(target = "shop_products"))]
     product_id: String,
     #[snugom(filterable, sortable)]
     rating: i64,
@@ -3,6 +3,7 @@
 //! Demonstrates using idempotency keys for safe retries:
 //! - Preventing duplicate entity creation
 //! - Safe retry behavior
+//! - Detecting replays and inspecting a key with `idempotency_status`
 //! - Idempotency key patterns
 
 use anyhow::Result;
@@ -97,6 +98,14 @@ pub async fn run() -> Result<()> {
             payment1_id.id, payment2_id.id,
             "same idempotency key should return same ID"
         );
+        assert!(!payment1_id.replayed && payment2_id.replayed);
+
+        // The key's record can be inspected without running a mutation
+        let status = payments.idempotency_status(&idempotency_key).await?;
+        assert!(status.consumed);
+        assert!(status.expires_at.is_some());
+        let original = status.replayed_create().expect("stored create response");
+        assert_eq!(original.id, payment1_id.id);
 
         // Verify only one payment exists
         let all_payments = payments.find_many(crate::SearchQuery::default()).await?;
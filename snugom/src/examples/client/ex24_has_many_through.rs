@@ -0,0 +1,107 @@
+//! Example 24 – Has-Many-Through
+//!
+//! Demonstrates traversing a relation through an intermediate entity:
+//! - `has_many_through` - Player -> memberships -> team
+//! - `through_ids` / `get_through` - resolve the far side without a manual join
+
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "thr_players")]
+#[snugom(has_many_through(alias = "teams", through = "memberships", source = "team"))]
+struct Player {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    /// Has many memberships - the first hop of the `teams` traversal
+    #[serde(default)]
+    #[snugom(relation(target = "thr_memberships"))]
+    memberships: Vec<String>,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "thr_memberships")]
+struct Membership {
+    #[snugom(id)]
+    id: String,
+    #[snugom(created_at)]
+    created_at: chrono::DateTime<Utc>,
+    #[snugom(filterable(tag))]
+    role: String,
+
+    /// Belongs to a team - the second hop of the `teams` traversal
+    #[snugom(relation(target = "thr_teams"))]
+    team_id: String,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "thr_teams")]
+struct Squad {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Player, Membership, Squad])]
+struct LeagueClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("has_many_through");
+    let client = LeagueClient::new(conn, prefix);
+
+    let mut players = client.players();
+
+    // ============ Create Teams and a Player ============
+    let red_id = snugom_create!(client, Squad { name: "Red".to_string() }).await?.id;
+    let blue_id = snugom_create!(client, Squad { name: "Blue".to_string() }).await?.id;
+    let player_id = snugom_create!(client, Player { name: "Ada".to_string() }).await?.id;
+
+    // ============ Link Player to Teams via Memberships ============
+    let mut membership_ids = Vec::new();
+    for (team_id, role) in [(&red_id, "captain"), (&blue_id, "sub"), (&red_id, "coach")] {
+        let membership_id = snugom_create!(client, Membership {
+            role: role.to_string(),
+            team_id: team_id.clone(),
+            created_at: Utc::now(),
+        })
+        .await?
+        .id;
+        membership_ids.push(membership_id);
+    }
+
+    for membership_id in &membership_ids {
+        snugom_update!(client, Player(entity_id = player_id.clone()) {
+            memberships: [connect membership_id.clone()],
+        })
+        .await?;
+    }
+
+    // ============ Traverse ============
+    // Two memberships point at Red, so the far side is de-duplicated.
+    let mut team_ids = players.through_ids(&player_id, "teams").await?;
+    team_ids.sort();
+    let mut expected = vec![red_id.clone(), blue_id.clone()];
+    expected.sort();
+    assert_eq!(team_ids, expected);
+
+    let mut teams: Vec<Squad> = players.get_through(&player_id, "teams").await?;
+    teams.sort_by(|a, b| a.name.cmp(&b.name));
+    let names: Vec<&str> = teams.iter().map(|team| team.name.as_str()).collect();
+    assert_eq!(names, vec!["Blue", "Red"]);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Example 25 – Relation Edge Attributes
+//!
+//! Demonstrates attaching a payload to a many-to-many edge:
+//! - `connect_with` - connect a member and store `role` / `joined_at` on the link
+//! - `relation_edges` - read the raw edge payloads keyed by member id
+//! - `get_related_with_edges` - load related entities alongside their edges
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomClient, SnugomEntity, snugom_create};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "edge_users")]
+struct Member {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    handle: String,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "edge_teams")]
+struct Crew {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    #[serde(default)]
+    #[snugom(relation(many_to_many = "edge_users"))]
+    members: Vec<String>,
+}
+
+/// Payload stored on each Crew -> Member edge.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Membership {
+    role: String,
+    joined_at: DateTime<Utc>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Member, Crew])]
+struct EdgeClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("relation_edges");
+    let client = EdgeClient::new(conn, prefix);
+
+    let mut crews = client.crews();
+
+    let ada_id = snugom_create!(client, Member { handle: "ada".to_string() }).await?.id;
+    let bob_id = snugom_create!(client, Member { handle: "bob".to_string() }).await?.id;
+    let crew_id = snugom_create!(client, Crew { name: "Core".to_string() }).await?.id;
+
+    // ============ connect_with ============
+    let ada_edge = Membership {
+        role: "owner".to_string(),
+        joined_at: Utc::now(),
+    };
+    crews
+        .update(
+            Crew::patch_builder()
+                .entity_id(crew_id.clone())
+                .connect_with("members", ada_id.clone(), &ada_edge)
+                .connect("members", vec![bob_id.clone()]),
+        )
+        .await?;
+
+    // ============ relation_edges ============
+    // Only members connected with a payload have an edge entry.
+    let edges = crews.relation_edges(&crew_id, "members").await?;
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[&ada_id]["role"], "owner");
+
+    // ============ get_related_with_edges ============
+    let members = crews.get_related_with_edges::<Member>(&crew_id, "members").await?;
+    assert_eq!(members.items.len(), 2);
+    let edge: Option<Membership> = members.edge(&ada_id).transpose()?;
+    assert_eq!(edge, Some(ada_edge));
+    assert!(members.edge::<Membership>(&bob_id).is_none());
+
+    // ============ Disconnect drops the edge ============
+    crews
+        .update(
+            Crew::patch_builder()
+                .entity_id(crew_id.clone())
+                .disconnect("members", vec![ada_id.clone()]),
+        )
+        .await?;
+    let edges = crews.relation_edges(&crew_id, "members").await?;
+    assert!(edges.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Example 26 – Filtered and Sorted Relations
+//!
+//! Demonstrates querying a relation through the target's search index:
+//! - `find_related` - intersect a relation set with a search on the target index
+//! - `WriterRelations::posts` - the generated accessor for the `posts` relation, typed by its `model`
+//! - `RelationQueryOptions::with_condition` - typed `FilterCondition` on related entities
+//! - `with_sort` / `with_limit` / `with_offset` - order and page the related entities
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{
+    SnugomClient, SnugomEntity, search::FilterCondition, snugom_create, snugom_update, types::RelationQueryOptions,
+};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "rq_authors")]
+struct Writer {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    #[serde(default)]
+    #[snugom(relation(target = "rq_posts", model = "Entry"))]
+    posts: Vec<String>,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "rq_posts")]
+struct Entry {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    title: String,
+    #[snugom(filterable(tag))]
+    status: String,
+    #[snugom(filterable, sortable)]
+    published_at: i64,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Writer, Entry])]
+struct BlogClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("relation_queries");
+    let mut client = BlogClient::new(conn, prefix);
+    client.ensure_indexes().await?; // Required for relation searches
+
+    let mut writers = client.writers();
+
+    let writer_id = snugom_create!(client, Writer { name: "Ada".to_string() }).await?.id;
+    let other_id = snugom_create!(client, Writer { name: "Bob".to_string() }).await?.id;
+
+    // ============ Create Posts ============
+    let entries = [
+        ("Intro", "published", 10),
+        ("Draft notes", "draft", 20),
+        ("Deep dive", "published", 30),
+        ("Follow-up", "published", 40),
+    ];
+    for (title, status, published_at) in entries {
+        let entry_id = snugom_create!(client, Entry {
+            title: title.to_string(),
+            status: status.to_string(),
+            published_at: published_at,
+        })
+        .await?
+        .id;
+        snugom_update!(client, Writer(entity_id = writer_id.clone()) {
+            posts: [connect entry_id],
+        })
+        .await?;
+    }
+
+    // A published post owned by someone else must not leak into Ada's relation.
+    let foreign_id = snugom_create!(client, Entry {
+        title: "Elsewhere".to_string(),
+        status: "published".to_string(),
+        published_at: 50,
+    })
+    .await?
+    .id;
+    snugom_update!(client, Writer(entity_id = other_id.clone()) {
+        posts: [connect foreign_id],
+    })
+    .await?;
+
+    // ============ Filter + Sort ============
+    // `posts` is generated from the relation field; it's `get_related::<Entry>` with the alias filled in.
+    let published = writers
+        .posts(
+            &writer_id,
+            RelationQueryOptions::new()
+                .with_condition(FilterCondition::tag_eq("status", "published"))
+                .with_sort("-published_at"),
+        )
+        .await?;
+    let titles: Vec<&str> = published.items.iter().map(|entry| entry.title.as_str()).collect();
+    assert_eq!(titles, vec!["Follow-up", "Deep dive", "Intro"]);
+    assert_eq!(published.total, Some(3));
+    assert_eq!(published.has_more, Some(false));
+
+    // ============ Pagination ============
+    let second_page = writers
+        .find_related::<Entry>(
+            &writer_id,
+            "posts",
+            RelationQueryOptions::new()
+                .with_filter("status:eq:published")
+                .with_sort("published_at")
+                .with_limit(1)
+                .with_offset(1),
+        )
+        .await?;
+    let titles: Vec<&str> = second_page.items.iter().map(|entry| entry.title.as_str()).collect();
+    assert_eq!(titles, vec!["Deep dive"]);
+    assert_eq!(second_page.has_more, Some(true));
+
+    // ============ Without Filters ============
+    // With no filter or sort the relation set is read directly, in id order, without the index.
+    let first_two = writers.posts(&writer_id, RelationQueryOptions::new().with_limit(2)).await?;
+    assert_eq!(first_two.items.len(), 2);
+    assert_eq!(first_two.total, Some(4));
+    assert_eq!(first_two.has_more, Some(true));
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Example 27 – Batch Relation Hydration
+//!
+//! Demonstrates loading a relation for a whole result list without N+1 round trips:
+//! - `load_relations` on a belongs-to relation - foreign keys are read from the parents
+//! - `load_relations` on a has-many relation - relation sets are read in one pipeline
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "hy_authors")]
+struct Author {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    #[serde(default)]
+    #[snugom(relation(target = "hy_posts"))]
+    posts: Vec<String>,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "hy_posts")]
+struct Post {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    title: String,
+
+    #[snugom(relation(target = "hy_authors"))]
+    author_id: String,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Author, Post])]
+struct HydrationClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("batch_hydration");
+    let client = HydrationClient::new(conn, prefix);
+
+    let mut authors = client.authors();
+    let mut posts = client.posts();
+
+    let ada_id = snugom_create!(client, Author { name: "Ada".to_string() }).await?.id;
+    let bob_id = snugom_create!(client, Author { name: "Bob".to_string() }).await?.id;
+
+    let mut post_ids = Vec::new();
+    for (title, author_id) in [("One", &ada_id), ("Two", &ada_id), ("Three", &bob_id)] {
+        let post_id = snugom_create!(client, Post {
+            title: title.to_string(),
+            author_id: author_id.clone(),
+        })
+        .await?
+        .id;
+        snugom_update!(client, Author(entity_id = author_id.clone()) {
+            posts: [connect post_id.clone()],
+        })
+        .await?;
+        post_ids.push(post_id);
+    }
+
+    // ============ Belongs-To ============
+    let mut loaded_posts = Vec::new();
+    for post_id in &post_ids {
+        loaded_posts.push(posts.get_or_error(post_id).await?);
+    }
+    let post_authors: HashMap<String, Vec<Author>> = posts.load_relations(&loaded_posts, "author").await?;
+    assert_eq!(post_authors.len(), 3);
+    assert_eq!(post_authors[&post_ids[0]][0].name, "Ada");
+    assert_eq!(post_authors[&post_ids[1]][0].name, "Ada");
+    assert_eq!(post_authors[&post_ids[2]][0].name, "Bob");
+
+    // ============ Has-Many ============
+    let loaded_authors = vec![authors.get_or_error(&ada_id).await?, authors.get_or_error(&bob_id).await?];
+    let author_posts: HashMap<String, Vec<Post>> = authors.load_relations(&loaded_authors, "posts").await?;
+    let mut ada_titles: Vec<&str> = author_posts[&ada_id].iter().map(|post| post.title.as_str()).collect();
+    ada_titles.sort();
+    assert_eq!(ada_titles, vec!["One", "Two"]);
+    assert_eq!(author_posts[&bob_id].len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Example 28 – Denormalized Relation Counters
+//!
+//! Demonstrates a counter field kept in step with a relation set:
+//! - `relation(has_many, counter = "post_count")` - the mutation scripts refresh the count
+//! - deleting a post drops it from its blog's set through the `blog_id` foreign key
+//! - the counter is an ordinary numeric field, so it can be filtered and sorted on
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SearchQuery, SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "ctr_blogs")]
+struct Blog {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    /// Number of posts, maintained by the relation scripts
+    #[serde(default)]
+    #[snugom(filterable, sortable)]
+    post_count: u64,
+
+    #[serde(default)]
+    #[snugom(relation(has_many, target = "ctr_posts", counter = "post_count"))]
+    posts: Vec<String>,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "ctr_posts")]
+struct Article {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    title: String,
+    #[snugom(relation(target = "ctr_blogs"))]
+    blog_id: String,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Blog, Article])]
+struct CounterClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("relation_counters");
+    let mut client = CounterClient::new(conn, prefix);
+    client.ensure_indexes().await?;
+
+    let mut blogs = client.blogs();
+
+    // The counter starts at zero without being set explicitly.
+    let busy_id = snugom_create!(client, Blog { name: "Busy".to_string() }).await?.id;
+    let quiet_id = snugom_create!(client, Blog { name: "Quiet".to_string() }).await?.id;
+    assert_eq!(blogs.get_or_error(&busy_id).await?.post_count, 0);
+
+    // ============ Connect ============
+    let mut article_ids = Vec::new();
+    for title in ["One", "Two", "Three"] {
+        let article_id = snugom_create!(client, Article {
+            title: title.to_string(),
+            blog_id: busy_id.clone(),
+        })
+        .await?
+        .id;
+        snugom_update!(client, Blog(entity_id = busy_id.clone()) {
+            posts: [connect article_id.clone()],
+        })
+        .await?;
+        article_ids.push(article_id);
+    }
+    assert_eq!(blogs.get_or_error(&busy_id).await?.post_count, 3);
+
+    // ============ Disconnect ============
+    snugom_update!(client, Blog(entity_id = busy_id.clone()) {
+        posts: [disconnect article_ids[0].clone()],
+    })
+    .await?;
+    assert_eq!(blogs.get_or_error(&busy_id).await?.post_count, 2);
+
+    // ============ Delete ============
+    client.articles().delete(&article_ids[1]).await?;
+    assert_eq!(blogs.get_or_error(&busy_id).await?.post_count, 1);
+
+    // ============ Filter on the Counter ============
+    let query = SearchQuery {
+        filter: vec!["post_count:range:1,".to_string()],
+        ..Default::default()
+    };
+    let active = blogs.find_many(query).await?;
+    let ids: Vec<&str> = active.items.iter().map(|blog| blog.id.as_str()).collect();
+    assert_eq!(ids, vec![busy_id.as_str()]);
+    assert!(!ids.contains(&quiet_id.as_str()));
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Example 29 – View Models
+//!
+//! Demonstrates producing API-facing shapes straight from search:
+//! - `find_many_map` - convert each loaded entity with a closure
+//! - `find_many_as` - fetch only the fields a `ViewModel` declares and deserialize into it
+//! - `get_projection` - fetch a few paths of one entity into a caller-supplied DTO
+//! - `#[snugom(view(...))]` - generated partial structs usable with `find_many_as`/`get_as`
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SearchQuery, SnugomClient, SnugomEntity, ViewModel, snugom_create};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "vm_profiles", view(Contact = [handle, email]))]
+struct Profile {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text), sortable)]
+    handle: String,
+    email: String,
+    bio: String,
+}
+
+/// Public card shown in listings; never carries the email.
+#[derive(Deserialize, Debug, PartialEq)]
+struct ProfileCard {
+    id: String,
+    handle: String,
+}
+
+impl ViewModel<Profile> for ProfileCard {
+    fn fields() -> &'static [&'static str] {
+        &["id", "handle"]
+    }
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Profile])]
+struct ViewClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("view_models");
+    let mut client = ViewClient::new(conn, prefix);
+    client.ensure_indexes().await?;
+
+    let mut ids = Vec::new();
+    for handle in ["ada", "grace"] {
+        let created = snugom_create!(client, Profile {
+            handle: handle.to_string(),
+            email: format!("{handle}@example.com"),
+            bio: "x".repeat(512),
+        })
+        .await?;
+        ids.push(created.id);
+    }
+
+    let mut profiles = client.profiles();
+    let query = || SearchQuery {
+        sort_by: Some("handle".to_string()),
+        ..Default::default()
+    };
+
+    // ============ Closure Mapping ============
+    let handles = profiles.find_many_map(query(), |profile| profile.handle.to_uppercase()).await?;
+    assert_eq!(handles.items, vec!["ADA".to_string(), "GRACE".to_string()]);
+    assert_eq!(handles.total, 2);
+
+    // ============ Projected View Models ============
+    let cards = profiles.find_many_as::<ProfileCard>(query()).await?;
+    let names: Vec<&str> = cards.items.iter().map(|card| card.handle.as_str()).collect();
+    assert_eq!(names, vec!["ada", "grace"]);
+    assert!(cards.items.iter().all(|card| !card.id.is_empty()));
+
+    // ============ Single-Entity Projection ============
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Contact {
+        handle: String,
+        email: String,
+        #[serde(default)]
+        phone: Option<String>,
+    }
+    let contact: Option<Contact> = profiles.get_projection(&ids[0], ["handle", "$.email", "phone"]).await?;
+    assert_eq!(
+        contact,
+        Some(Contact {
+            handle: "ada".to_string(),
+            email: "ada@example.com".to_string(),
+            phone: None,
+        })
+    );
+    let missing: Option<Contact> = profiles.get_projection("no-such-id", ["handle"]).await?;
+    assert!(missing.is_none());
+
+    // ============ Generated Views ============
+    // view(Contact = [handle, email]) generates `ProfileContact`, a ViewModel<Profile>
+    let contacts = profiles.find_many_as::<ProfileContact>(query()).await?;
+    let emails: Vec<&str> = contacts.items.iter().map(|contact| contact.email.as_str()).collect();
+    assert_eq!(emails, vec!["ada@example.com", "grace@example.com"]);
+    let grace = profiles.get_as::<ProfileContact>(&ids[1]).await?.expect("profile exists");
+    assert_eq!(grace.handle, "grace");
+
+    Ok(())
+}
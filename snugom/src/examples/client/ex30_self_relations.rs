@@ -0,0 +1,77 @@
+//! Example 30 – Self-Referential Relations
+//!
+//! Demonstrates a relation whose target is the entity's own collection:
+//! - `relation(target = "categories", cascade = "delete")` on `Category` - a category tree
+//! - deleting a node removes its whole subtree, level by level
+//! - cycles in the data (a node reachable from itself) still terminate
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomClient, SnugomEntity, snugom_create, snugom_delete, snugom_update};
+
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "categories")]
+struct Category {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(text))]
+    name: String,
+
+    /// Sub-categories; deleting a category deletes everything beneath it
+    #[serde(default)]
+    #[snugom(relation(has_many, target = "categories", cascade = "delete"))]
+    children: Vec<String>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Category])]
+struct CatalogClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("self_relations");
+    let mut client = CatalogClient::new(conn, prefix);
+
+    let mut categories = client.categories();
+
+    // ============ Build a Tree ============
+    // root -> books -> fiction
+    //      -> music
+    let mut ids = Vec::new();
+    for name in ["root", "books", "fiction", "music"] {
+        ids.push(snugom_create!(client, Category { name: name.to_string() }).await?.id);
+    }
+    let (root, books, fiction, music) = (&ids[0], &ids[1], &ids[2], &ids[3]);
+    snugom_update!(client, Category(entity_id = root.clone()) {
+        children: [connect books.clone(), connect music.clone()],
+    })
+    .await?;
+    snugom_update!(client, Category(entity_id = books.clone()) {
+        children: [connect fiction.clone()],
+    })
+    .await?;
+
+    // ============ Cascade Through the Tree ============
+    snugom_delete!(client, Category(root)).await?;
+    for id in &ids {
+        assert!(!categories.exists(id).await?, "subtree should be deleted");
+    }
+
+    // ============ Cycles Terminate ============
+    let a = snugom_create!(client, Category { name: "a".to_string() }).await?.id;
+    let b = snugom_create!(client, Category { name: "b".to_string() }).await?.id;
+    snugom_update!(client, Category(entity_id = a.clone()) { children: [connect b.clone()] }).await?;
+    snugom_update!(client, Category(entity_id = b.clone()) { children: [connect a.clone()] }).await?;
+
+    snugom_delete!(client, Category(&a)).await?;
+    assert!(!categories.exists(&a).await?);
+    assert!(!categories.exists(&b).await?);
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Example 31 – GraphQL Integration (`graphql` feature)
+//!
+//! Demonstrates standing up a GraphQL query on top of a collection:
+//! - `#[snugom(graphql)]` - generates `OrderConnection` and `OrderFilter`
+//! - `SearchInput::into_query` - maps pagination, sort, and filter arguments to a `SearchQuery`
+
+use anyhow::Result;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomClient, SnugomEntity, graphql::SearchInput, snugom_create};
+
+#[derive(SnugomEntity, SimpleObject, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "gql_orders", graphql)]
+struct Order {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    status: String,
+    #[snugom(filterable, sortable)]
+    total_cents: i64,
+}
+
+#[derive(SnugomClient, Clone)]
+#[snugom_client(entities = [Order])]
+struct ShopClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        search: Option<SearchInput>,
+        filter: Option<OrderFilter>,
+    ) -> async_graphql::Result<OrderConnection> {
+        let client = ctx.data::<ShopClient>()?;
+        let query = search.unwrap_or_default().into_query(filter);
+        Ok(client.orders().find_many(query).await?.into())
+    }
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("graphql");
+    let mut client = ShopClient::new(conn, prefix);
+    client.ensure_indexes().await?;
+
+    for (status, total_cents) in [("paid", 1_500), ("paid", 4_200), ("refunded", 900)] {
+        snugom_create!(client, Order { status: status.to_string(), total_cents: total_cents }).await?;
+    }
+
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription).data(client).finish();
+    let response = schema
+        .execute(
+            r#"{
+                orders(
+                    search: { sortBy: "total_cents", sortOrder: DESC },
+                    filter: { status: ["paid"], totalCentsMin: 1000 }
+                ) {
+                    total
+                    hasMore
+                    items { status totalCents }
+                }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    assert_eq!(data["orders"]["total"], 2);
+    assert_eq!(data["orders"]["hasMore"], false);
+    assert_eq!(data["orders"]["items"][0]["totalCents"], 4_200);
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Example 32 – Hash Storage
+//!
+//! Demonstrates `#[snugom(storage = "hash")]` for deployments without RedisJSON:
+//! - each entity is a plain Redis hash, one field per struct field
+//! - the search index is created `ON HASH`, so filters and sorting work as usual
+//! - creates, patches, unique constraints, and deletes switch to hash commands
+
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{RepoError, SearchQuery, SnugomClient, SnugomEntity, snugom_create, snugom_delete, snugom_update};
+
+/// A feature flag, stored as a hash.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "hash_flags", storage = "hash")]
+struct Flag {
+    #[snugom(id)]
+    id: String,
+    #[snugom(created_at)]
+    created_at: chrono::DateTime<Utc>,
+    #[snugom(unique)]
+    #[snugom(filterable(tag))]
+    key: String,
+    #[snugom(filterable(text))]
+    description: String,
+    #[snugom(filterable, sortable)]
+    rollout: i64,
+    #[snugom(filterable)]
+    enabled: bool,
+    owner: Option<String>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Flag])]
+struct FlagClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("hash_storage");
+    let mut client = FlagClient::new(conn.clone(), prefix.clone());
+    client.ensure_indexes().await?;
+
+    // ============ Create ============
+    let mut ids = Vec::new();
+    for (name, percent, on) in [("dark_mode", 100, true), ("beta_search", 25, true), ("legacy_ui", 0, false)] {
+        let created = snugom_create!(client, Flag {
+            key: name.to_string(),
+            description: format!("{name} rollout"),
+            rollout: percent,
+            enabled: on,
+            created_at: Utc::now(),
+        })
+        .await?;
+        ids.push(created.id);
+    }
+
+    // The document really is a hash, with the version flattened into `metadata.version`
+    let mut raw = conn.clone();
+    let key = format!("{prefix}:examples:hash_flags:{}", ids[0]);
+    let kind: String = redis::cmd("TYPE").arg(&key).query_async(&mut raw).await?;
+    assert_eq!(kind, "hash");
+    let version: u64 = redis::cmd("HGET").arg(&key).arg("metadata.version").query_async(&mut raw).await?;
+    assert_eq!(version, 1);
+
+    let mut flags = client.flags();
+    let dark_mode = flags.get_or_error(&ids[0]).await?;
+    assert_eq!(dark_mode.key, "dark_mode");
+    assert_eq!(dark_mode.rollout, 100);
+    assert!(dark_mode.enabled && dark_mode.owner.is_none());
+
+    // ============ Search ON HASH ============
+    let enabled = flags
+        .find_many(SearchQuery {
+            filter: vec!["enabled:eq:true".to_string()],
+            sort_by: Some("rollout".to_string()),
+            ..Default::default()
+        })
+        .await?;
+    let keys: Vec<&str> = enabled.items.iter().map(|flag| flag.key.as_str()).collect();
+    assert_eq!(keys, vec!["beta_search", "dark_mode"]);
+
+    // ============ Patch ============
+    snugom_update!(client, Flag(entity_id = ids[1].clone()) {
+        rollout: 50,
+        owner: Some("search-team".to_string()),
+    })
+    .await?;
+    let beta = client.flags().get_or_error(&ids[1]).await?;
+    assert_eq!(beta.rollout, 50);
+    assert_eq!(beta.owner.as_deref(), Some("search-team"));
+
+    // ============ Unique Constraints ============
+    let duplicate = snugom_create!(client, Flag {
+        key: "dark_mode".to_string(),
+        description: "again".to_string(),
+        rollout: 0,
+        enabled: false,
+        created_at: Utc::now(),
+    })
+    .await;
+    assert!(matches!(duplicate, Err(RepoError::UniqueConstraintViolation { .. })));
+
+    // ============ Delete ============
+    snugom_delete!(client, Flag(&ids[2])).await?;
+    assert!(!client.flags().exists(&ids[2]).await?);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! Example 33 – Compressed Fields
+//!
+//! Demonstrates `#[snugom(compressed)]` for large string and JSON blobs:
+//! - compressed fields are gzip/zstd-compressed on write and decompressed on read
+//! - the stored value is an opaque tagged string, so it never reaches the index
+//! - patches replace the whole value; gets and searches return it decompressed
+
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::support;
+use crate::{SearchQuery, SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+/// A rendered page with a large body and a JSON layout tree.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "compressed_pages")]
+struct Page {
+    #[snugom(id)]
+    id: String,
+    #[snugom(created_at)]
+    created_at: chrono::DateTime<Utc>,
+    #[snugom(filterable(tag))]
+    slug: String,
+    #[snugom(compressed)]
+    body: String,
+    #[snugom(compressed = "gzip")]
+    layout: serde_json::Value,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Page])]
+struct PageClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("compressed_fields");
+    let mut client = PageClient::new(conn.clone(), prefix.clone());
+    client.ensure_indexes().await?;
+
+    // ============ Create ============
+    let body = "All work and no play makes Jack a dull boy. ".repeat(500);
+    let layout = json!({ "columns": 2, "blocks": ["hero", "text", "footer"] });
+    let created = snugom_create!(client, Page {
+        slug: "jack".to_string(),
+        body: body.clone(),
+        layout: layout.clone(),
+        created_at: Utc::now(),
+    })
+    .await?;
+
+    // Redis holds a tagged, compressed string rather than the original text
+    let mut raw = conn.clone();
+    let key = format!("{prefix}:examples:compressed_pages:{}", created.id);
+    let stored: String = redis::cmd("JSON.GET").arg(&key).arg("$.body").query_async(&mut raw).await?;
+    let stored: Vec<String> = serde_json::from_str(&stored)?;
+    assert!(stored[0].starts_with("gz:"));
+    assert!(stored[0].len() < body.len() / 10);
+
+    // ============ Read ============
+    let page = client.pages().get_or_error(&created.id).await?;
+    assert_eq!(page.body, body);
+    assert_eq!(page.layout, layout);
+
+    let found = client
+        .pages()
+        .find_many(SearchQuery {
+            filter: vec!["slug:eq:jack".to_string()],
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(found.items[0].body, body);
+
+    // ============ Patch ============
+    snugom_update!(client, Page(entity_id = created.id.clone()) {
+        body: "Short and sweet.".to_string(),
+    })
+    .await?;
+    let page = client.pages().get_or_error(&created.id).await?;
+    assert_eq!(page.body, "Short and sweet.");
+    assert_eq!(page.layout, layout);
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Example 34 – Binary Fields
+//!
+//! Demonstrates `Vec<u8>` fields:
+//! - bytes are stored as a base64 string in the JSON document (`FieldType::Bytes`)
+//! - `validate(size(max = N))` caps the payload in bytes
+//! - reads and patches work with plain `Vec<u8>` values
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{
+    RepoError, SnugomClient, SnugomEntity, snugom_create, snugom_update,
+    types::{EntityMetadata, FieldType},
+};
+
+/// A file attachment with a small inline thumbnail.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "attachments")]
+struct Attachment {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    file_name: String,
+    #[snugom(validate(size(max = 64)))]
+    thumbnail: Vec<u8>,
+    checksum: Option<Vec<u8>>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Attachment])]
+struct AttachmentClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("binary_fields");
+    let mut client = AttachmentClient::new(conn.clone(), prefix.clone());
+    client.ensure_indexes().await?;
+
+    let descriptor = Attachment::entity_descriptor();
+    let thumbnail_field = descriptor.fields.iter().find(|field| field.name == "thumbnail").unwrap();
+    assert_eq!(thumbnail_field.field_type, FieldType::Bytes);
+
+    // ============ Create ============
+    let thumbnail = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    let created = snugom_create!(client, Attachment {
+        file_name: "logo.png".to_string(),
+        thumbnail: thumbnail.clone(),
+    })
+    .await?;
+
+    // Redis holds base64 rather than an array of numbers
+    let mut raw = conn.clone();
+    let key = format!("{prefix}:examples:attachments:{}", created.id);
+    let stored: String = redis::cmd("JSON.GET").arg(&key).arg("$.thumbnail").query_async(&mut raw).await?;
+    assert_eq!(stored, r#"["iVBORw0KGgo="]"#);
+
+    let attachment = client.attachments().get_or_error(&created.id).await?;
+    assert_eq!(attachment.thumbnail, thumbnail);
+    assert!(attachment.checksum.is_none());
+
+    // ============ Patch ============
+    snugom_update!(client, Attachment(entity_id = created.id.clone()) {
+        checksum: Some(vec![0xde, 0xad, 0xbe, 0xef]),
+    })
+    .await?;
+    let attachment = client.attachments().get_or_error(&created.id).await?;
+    assert_eq!(attachment.checksum, Some(vec![0xde, 0xad, 0xbe, 0xef]));
+
+    // ============ Size Validation ============
+    let too_big = snugom_create!(client, Attachment {
+        file_name: "huge.png".to_string(),
+        thumbnail: vec![0; 65],
+    })
+    .await;
+    assert!(matches!(too_big, Err(RepoError::Validation(_))));
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Example 35 – Atomic Increments
+//!
+//! Demonstrates counter updates without read-modify-write:
+//! - `snugom_update!` accepts `field += n` and `field -= n` for numeric fields
+//! - increments run as `JSON.NUMINCRBY` inside the patch script, so concurrent writers never conflict
+//! - the patch builder exposes the same operation as `increment_{field}(by)`
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{RepoError, SearchQuery, SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+/// Engagement counters for a published post.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "post_counters")]
+struct PostCounter {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    slug: String,
+    #[snugom(filterable, sortable)]
+    views: i64,
+    likes: u32,
+    rating_total: f64,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [PostCounter])]
+struct CounterClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("atomic_increments");
+    let mut client = CounterClient::new(conn.clone(), prefix.clone());
+    client.ensure_indexes().await?;
+
+    let created = snugom_create!(client, PostCounter {
+        slug: "hello-world".to_string(),
+        views: 0,
+        likes: 0,
+        rating_total: 0.0,
+    })
+    .await?;
+
+    // ============ Increment and Decrement ============
+    snugom_update!(client, PostCounter(entity_id = created.id.clone()) {
+        views += 10,
+        likes += 3,
+        rating_total += 4.5,
+    })
+    .await?;
+    snugom_update!(client, PostCounter(entity_id = created.id.clone()) {
+        likes -= 1,
+    })
+    .await?;
+
+    let stats = client.post_counters().get_or_error(&created.id).await?;
+    assert_eq!(stats.views, 10);
+    assert_eq!(stats.likes, 2);
+    assert_eq!(stats.rating_total, 4.5);
+
+    // ============ Concurrent Writers ============
+    // Each task bumps the counter without knowing the current value or version
+    let mut tasks = Vec::new();
+    for _ in 0..20 {
+        let client = CounterClient::new(conn.clone(), prefix.clone());
+        let id = created.id.clone();
+        tasks.push(tokio::spawn(async move {
+            snugom_update!(client, PostCounter(entity_id = id) {
+                views += 1,
+            })
+            .await
+        }));
+    }
+    for task in tasks {
+        task.await??;
+    }
+
+    let stats = client.post_counters().get_or_error(&created.id).await?;
+    assert_eq!(stats.views, 30);
+
+    // The index sees the new value immediately
+    let popular = client
+        .post_counters()
+        .find_many(SearchQuery {
+            filter: vec!["views:range:25,100".to_string()],
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(popular.items.len(), 1);
+
+    // ============ Patch Builder ============
+    client
+        .post_counters()
+        .update(PostCounter::patch_builder().entity_id(created.id.clone()).increment_views(-5.0))
+        .await?;
+    let stats = client.post_counters().get_or_error(&created.id).await?;
+    assert_eq!(stats.views, 25);
+
+    // Non-finite deltas are rejected before reaching Redis
+    let bad = client
+        .post_counters()
+        .update(PostCounter::patch_builder().entity_id(created.id.clone()).increment_views(f64::NAN))
+        .await;
+    assert!(matches!(bad, Err(RepoError::Validation(_))));
+
+    Ok(())
+}
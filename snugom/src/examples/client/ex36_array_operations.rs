@@ -0,0 +1,76 @@
+//! Example 36 – Array Operations
+//!
+//! Demonstrates in-place array patches:
+//! - `tags: push("a", "b")` appends with `JSON.ARRAPPEND` instead of rewriting the array
+//! - `tags: remove("a")` drops every matching element inside the patch script
+//! - filterable arrays are indexed element by element, so tag filters see the change immediately
+
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{RepoError, SearchQuery, SnugomClient, SnugomEntity, snugom_create, snugom_update};
+
+/// A saved link with free-form tags.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "examples", collection = "bookmarks")]
+struct Bookmark {
+    #[snugom(id)]
+    id: String,
+    url: String,
+    #[snugom(filterable(tag))]
+    #[snugom(validate(each = "length(min = 1, max = 20)"))]
+    tags: Vec<String>,
+    visit_days: Option<Vec<u32>>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Bookmark])]
+struct BookmarkClient {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+pub async fn run() -> Result<()> {
+    let conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("array_operations");
+    let mut client = BookmarkClient::new(conn, prefix);
+    client.ensure_indexes().await?;
+
+    let created = snugom_create!(client, Bookmark {
+        url: "https://redis.io".to_string(),
+        tags: vec!["databases".to_string(), "draft".to_string()],
+    })
+    .await?;
+
+    // ============ Push and Remove ============
+    snugom_update!(client, Bookmark(entity_id = created.id.clone()) {
+        tags: push("redis", "search"),
+        tags: remove("draft"),
+        visit_days: push(12),
+    })
+    .await?;
+
+    let bookmark = client.bookmarks().get_or_error(&created.id).await?;
+    assert_eq!(bookmark.tags, vec!["databases", "redis", "search"]);
+    assert_eq!(bookmark.visit_days, Some(vec![12]));
+
+    // ============ Index Consistency ============
+    let tagged = |tag: &str| SearchQuery {
+        filter: vec![format!("tags:eq:{tag}")],
+        ..Default::default()
+    };
+    assert_eq!(client.bookmarks().find_many(tagged("redis")).await?.items.len(), 1);
+    assert!(client.bookmarks().find_many(tagged("draft")).await?.items.is_empty());
+
+    // ============ Validation ============
+    // `each` rules apply to pushed elements
+    let too_long = snugom_update!(client, Bookmark(entity_id = created.id.clone()) {
+        tags: push("a-tag-that-is-far-too-long"),
+    })
+    .await;
+    assert!(matches!(too_long, Err(RepoError::Validation(_))));
+
+    Ok(())
+}
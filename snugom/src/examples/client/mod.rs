@@ -26,7 +26,7 @@
 //!
 //! ## Relations (16-18)
 //! - ex16: Relations - defining belongs_to/has_many relations
-//! - ex17: Relation Mutations - connect/disconnect/delete with snugom_update!
+//! - ex17: Relation Mutations - connect/disconnect/delete/set/connect_or_create with snugom_update!
 //! - ex18: Cascade Strategies - cascade delete behavior
 //!
 //! ## Advanced Patterns (19-23)
@@ -35,6 +35,19 @@
 //! - ex21: Optimistic Locking - version-based conditional updates
 //! - ex22: Idempotency Keys - safe retry patterns
 //! - ex23: Batch Workflows - bulk operations for efficiency
+//! - ex24: Has-Many-Through - traversing relations via an intermediate entity
+//! - ex25: Relation Edges - attributes stored on many-to-many links
+//! - ex26: Relation Queries - filtering and sorting related entities
+//! - ex27: Batch Hydration - loading a relation for a list of entities
+//! - ex28: Relation Counters - denormalized member counts on the parent
+//! - ex29: View Models - mapped and projected search results
+//! - ex30: Self-Referential Relations - trees with cycle-safe cascades
+//! - ex31: GraphQL - generated connection and filter types (`graphql` feature)
+//! - ex32: Hash Storage - entities stored as plain hashes, indexed `ON HASH`
+//! - ex33: Compressed Fields - gzip/zstd blobs kept out of the index (`gzip` feature)
+//! - ex34: Binary Fields - `Vec<u8>` stored as base64 with size validation
+//! - ex35: Atomic Increments - `field += n` counters without version conflicts
+//! - ex36: Array Operations - `push(...)` / `remove(...)` array patches
 //!
 //! ## Social Network Application
 //! - social_network: Complete multi-file example showing how to structure a real application
@@ -65,6 +78,21 @@ pub mod ex20_error_handling;
 pub mod ex21_optimistic_locking;
 pub mod ex22_idempotency_keys;
 pub mod ex23_batch_workflows;
+pub mod ex24_has_many_through;
+pub mod ex25_relation_edges;
+pub mod ex26_relation_queries;
+pub mod ex27_batch_hydration;
+pub mod ex28_relation_counters;
+pub mod ex29_view_models;
+pub mod ex30_self_relations;
+#[cfg(feature = "graphql")]
+pub mod ex31_graphql;
+pub mod ex32_hash_storage;
+#[cfg(feature = "gzip")]
+pub mod ex33_compressed_fields;
+pub mod ex34_binary_fields;
+pub mod ex35_atomic_increments;
+pub mod ex36_array_operations;
 
 use anyhow::Result;
 
@@ -127,6 +155,38 @@ pub async fn run_all() -> Result<()> {
     ex22_idempotency_keys::run().await?;
     println!("Running ex23_batch_workflows...");
     ex23_batch_workflows::run().await?;
+    println!("Running ex24_has_many_through...");
+    ex24_has_many_through::run().await?;
+    println!("Running ex25_relation_edges...");
+    ex25_relation_edges::run().await?;
+    println!("Running ex26_relation_queries...");
+    ex26_relation_queries::run().await?;
+    println!("Running ex27_batch_hydration...");
+    ex27_batch_hydration::run().await?;
+    println!("Running ex28_relation_counters...");
+    ex28_relation_counters::run().await?;
+    println!("Running ex29_view_models...");
+    ex29_view_models::run().await?;
+    println!("Running ex30_self_relations...");
+    ex30_self_relations::run().await?;
+    #[cfg(feature = "graphql")]
+    {
+        println!("Running ex31_graphql...");
+        ex31_graphql::run().await?;
+    }
+    println!("Running ex32_hash_storage...");
+    ex32_hash_storage::run().await?;
+    #[cfg(feature = "gzip")]
+    {
+        println!("Running ex33_compressed_fields...");
+        ex33_compressed_fields::run().await?;
+    }
+    println!("Running ex34_binary_fields...");
+    ex34_binary_fields::run().await?;
+    println!("Running ex35_atomic_increments...");
+    ex35_atomic_increments::run().await?;
+    println!("Running ex36_array_operations...");
+    ex36_array_operations::run().await?;
 
     println!("\n=== Social Network Application ===");
     println!("Running social_network tour...");
@@ -75,7 +75,7 @@ pub async fn get_home_feed(
     }
 
     // Sort by created_at (newest first)
-    feed_items.sort_by(|a, b| b.post.created_at.cmp(&a.post.created_at));
+    feed_items.sort_by_key(|item| ::std::cmp::Reverse(item.post.created_at));
 
     // Apply pagination
     let start = ((page - 1) * page_size) as usize;
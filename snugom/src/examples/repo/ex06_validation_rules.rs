@@ -44,6 +44,8 @@ struct AdvancedEntity {
     status: String,
     #[snugom(validate(each = "length(min = 2, max = 8)"))]
     labels: Vec<String>,
+    #[snugom(validate(each = "hostname"))]
+    mirrors: Vec<String>,
     #[snugom(validate(unique))]
     tags: Vec<String>,
     #[snugom(validate(required_if = "status == \"published\""))]
@@ -126,6 +128,7 @@ pub async fn run() -> Result<()> {
         external_id: "550e8400-e29b-41d4-a716-446655440000".into(),
         status: "Published".into(),
         labels: vec!["ok".into(), "good".into()],
+        mirrors: vec!["cdn.example.com".into()],
         tags: vec!["one".into(), "two".into()],
         published_at: Some(Utc::now()),
         draft_reason: None,
@@ -140,6 +143,7 @@ pub async fn run() -> Result<()> {
         external_id: "nope".into(),
         status: "published".into(),
         labels: vec!["a".into(), "toolongvalue".into()],
+        mirrors: vec!["ok.example.com".into(), "bad_host".into()],
         tags: vec!["dup".into(), "dup".into()],
         published_at: None,
         draft_reason: Some("should be blank".into()),
@@ -153,6 +157,8 @@ pub async fn run() -> Result<()> {
     assert!(advanced_fields.contains(&"external_id".into()));
     assert!(advanced_fields.contains(&"labels[0]".into()));
     assert!(advanced_fields.contains(&"labels[1]".into()));
+    assert!(advanced_fields.contains(&"mirrors[1]".into()));
+    assert!(!advanced_fields.contains(&"mirrors[0]".into()));
     // Note: Vec-level unique validation (checking for duplicate elements) is enforced
     // at database level via Lua script, not in local validation. See validation_emit.rs.
     assert!(advanced_fields.contains(&"published_at".into()));
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use super::support;
 use crate::repository::Repo;
-use crate::search::{SearchQuery, SortOrder};
+use crate::search::{FilterCondition, SearchParams, SearchQuery, SortOrder};
 use crate::SnugomEntity;
 
 #[derive(SnugomEntity, Serialize, Deserialize)]
@@ -69,6 +69,22 @@ pub async fn run() -> Result<()> {
         "results sorted by descending member_count"
     );
 
+    // Inspect how the same filters are parsed and where the search spends its time.
+    let params = SearchParams::new()
+        .with_condition(FilterCondition::tag_eq("visibility", "public"))
+        .with_condition(FilterCondition::numeric_range("member_count", Some(10.0), None));
+    let explanation = repo.explain(&mut conn, params.clone()).await?;
+    assert!(
+        explanation.raw.contains("visibility") && explanation.raw.contains("member_count"),
+        "both filters appear in the parse tree: {}",
+        explanation.raw
+    );
+    assert!(!explanation.tree.is_empty(), "the parse tree has a root");
+
+    let profile = repo.profile_search(&mut conn, params).await?;
+    assert_eq!(profile.result.total, 2, "profiling returns the same matches");
+    assert!(profile.iterators.is_some(), "the profile reports its query iterators");
+
     // Drop the index and keys to avoid polluting other tests/examples.
     let _: () = redis::cmd("FT.DROPINDEX")
         .arg(format!("{prefix}:idx"))
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use super::support;
+use crate::{SnugomEntity, repository::Repo};
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "examples", collection = "repair_shelves")]
+struct Shelf {
+    #[snugom(id)]
+    id: String,
+    label: String,
+    #[serde(default)]
+    #[snugom(relation(target = "repair_volumes"))]
+    volumes: Vec<String>,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "examples", collection = "repair_volumes")]
+struct Volume {
+    #[snugom(id)]
+    id: String,
+    title: String,
+    #[snugom(relation(target = "repair_shelves", cascade = "delete"))]
+    shelf_id: String,
+}
+
+/// Example 14 – rebuilding relation sets from stored belongs-to foreign keys.
+pub async fn run() -> Result<()> {
+    let mut conn = support::redis_connection().await?;
+    let prefix = support::unique_namespace("relation_repair");
+    let shelf_repo: Repo<Shelf> = Repo::new(prefix.clone());
+    let volume_repo: Repo<Volume> = Repo::new(prefix);
+
+    let shelf = shelf_repo
+        .create_with_conn(&mut conn, Shelf::validation_builder().label("A".to_string()))
+        .await?;
+    let shelf_id = shelf.id.clone();
+
+    // Simulate an import that wrote documents directly, bypassing relation plans.
+    let mut volume_ids = Vec::new();
+    for title in ["Dune", "Emma"] {
+        let volume_id = crate::id::generate_entity_id();
+        let document = serde_json::json!({
+            "id": volume_id,
+            "title": title,
+            "shelf_id": shelf_id,
+            "metadata": { "version": 1 },
+        });
+        let _: () = redis::cmd("JSON.SET")
+            .arg(volume_repo.entity_key(&volume_id))
+            .arg("$")
+            .arg(document.to_string())
+            .query_async(&mut conn)
+            .await?;
+        volume_ids.push(volume_id);
+    }
+    let shelf_volumes: Vec<String> = conn.smembers(shelf_repo.relation_key("volumes", &shelf_id)).await?;
+    assert!(shelf_volumes.is_empty());
+
+    // Each volume gets its forward set, the shelf's reverse set, and the shelf's has-many set.
+    let report = volume_repo.rebuild_relations(&mut conn).await?;
+    assert_eq!(report.scanned, 2);
+    assert_eq!(report.repaired, 6);
+
+    let mut shelf_volumes: Vec<String> = conn.smembers(shelf_repo.relation_key("volumes", &shelf_id)).await?;
+    shelf_volumes.sort();
+    volume_ids.sort();
+    assert_eq!(shelf_volumes, volume_ids);
+    let forward: Vec<String> = conn.smembers(volume_repo.relation_key("shelf", &volume_ids[0])).await?;
+    assert_eq!(forward, vec![shelf_id.clone()]);
+    let reverse_count: u64 = conn.scard(volume_repo.relation_reverse_key("shelf", &shelf_id)).await?;
+    assert_eq!(reverse_count, 2);
+
+    // A second pass finds nothing left to repair.
+    let report = volume_repo.rebuild_relations(&mut conn).await?;
+    assert_eq!(report.repaired, 0);
+
+    Ok(())
+}
@@ -19,6 +19,7 @@ pub mod ex10_idempotency;
 pub mod ex11_relation_mutations;
 pub mod ex12_search_manager;
 pub mod ex13_unique_constraints;
+pub mod ex14_relation_repair;
 
 use anyhow::Result;
 
@@ -78,6 +79,10 @@ pub async fn run_all() -> Result<()> {
     ex13_unique_constraints::run().await?;
     println!("    ✓ passed\n");
 
+    println!("14. Relation Repair...");
+    ex14_relation_repair::run().await?;
+    println!("    ✓ passed\n");
+
     println!("=== All Repo Examples Passed ===");
     Ok(())
 }
@@ -0,0 +1,128 @@
+//! Runtime support for the factories generated by `#[snugom(factory)]`.
+//!
+//! `#[snugom(factory)]` on a `SnugomEntity` generates `{Entity}Factory`, whose `builder()` fills
+//! every required field with a fake value that passes the field's validation rules:
+//!
+//! - strings are `{field}-{n}`, padded or trimmed to fit `length(min, max)`
+//! - `email`, `url`, `uuid`, `phone`, `ip_addr`, `hostname`, and `slug` fields get well-formed
+//!   values, and `enum` fields the first allowed value
+//! - numbers start at `range(min)` (or zero), clamped to `range(max)`; `unique` numbers add `n`
+//! - booleans are `false`, datetimes are now, and other types use `Default`
+//!
+//! `n` comes from a per-entity sequence, so string fakes never collide on unique indexes.
+//! Fields validated by `regex` or a custom function still need an explicit value.
+//!
+//! ```ignore
+//! let guild = GuildFactory::create(&mut client.guilds()).await?;
+//! let owners = GuildFactory::create_batch_with(&mut client.guilds(), 3, |_, builder| {
+//!     builder.owner_id(&owner.id)
+//! })
+//! .await?;
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counter shared by every builder of one factory.
+#[derive(Debug, Default)]
+pub struct Sequence(AtomicU64);
+
+impl Sequence {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    /// The next value, starting at 1.
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Pad `value` with `x` up to `min` characters, or keep its last `max` characters so the
+/// sequence number at the end survives.
+pub fn fit_length(value: String, min: Option<usize>, max: Option<usize>) -> String {
+    let mut value = value;
+    let len = value.chars().count();
+    if let Some(min) = min
+        && len < min
+    {
+        value.extend(std::iter::repeat_n('x', min - len));
+    }
+    if let Some(max) = max {
+        let len = value.chars().count();
+        if len > max {
+            value = value.chars().skip(len - max).collect();
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::SnugomEntity;
+
+    #[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "factory", collection = "members", factory)]
+    struct Member {
+        #[snugom(id)]
+        id: String,
+        #[snugom(created_at)]
+        created_at: chrono::DateTime<chrono::Utc>,
+        #[snugom(validate(length(min = 12, max = 16)), unique, filterable(tag))]
+        handle: String,
+        #[snugom(validate(email), unique, filterable(tag))]
+        email: String,
+        #[snugom(validate(url))]
+        website: String,
+        #[snugom(validate(uuid))]
+        external_id: String,
+        #[snugom(validate(phone))]
+        phone: String,
+        #[snugom(validate(ip_addr))]
+        last_ip: String,
+        #[snugom(validate(hostname))]
+        mail_host: String,
+        #[snugom(validate(slug), unique, filterable(tag))]
+        profile_slug: String,
+        #[snugom(validate(enum(allowed = ["owner", "member"])), filterable(tag))]
+        role: String,
+        #[snugom(validate(range(min = 18, max = 130)), filterable)]
+        age: u32,
+        #[snugom(validate(range(min = 1)), unique, filterable)]
+        badge: i64,
+        #[snugom(validate(range(max = 5.0)))]
+        rating: f64,
+        #[snugom(validate(length(min = 2)))]
+        labels: Vec<String>,
+        active: bool,
+        #[snugom(datetime)]
+        joined_at: chrono::DateTime<chrono::Utc>,
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn fits_length_keeping_the_sequence_suffix() {
+        assert_eq!(fit_length("ab-1".into(), Some(6), None), "ab-1xx");
+        assert_eq!(fit_length("handle-123".into(), None, Some(5)), "e-123");
+        assert_eq!(fit_length("name-1".into(), Some(2), Some(10)), "name-1");
+    }
+
+    #[test]
+    fn factory_builders_pass_validation_with_distinct_values() {
+        let first = MemberFactory::builder().build().expect("fakes should validate");
+        let second = MemberFactory::builder().nickname(Some("override")).build().expect("fakes should validate");
+
+        assert!((12..=16).contains(&first.handle.len()));
+        assert_ne!(first.handle, second.handle);
+        assert_ne!(first.email, second.email);
+        assert_ne!(first.profile_slug, second.profile_slug);
+        assert_ne!(first.badge, second.badge);
+        assert_eq!(first.role, "owner");
+        assert_eq!(first.age, 18);
+        assert_eq!(first.labels.len(), 2);
+        assert!(first.nickname.is_none());
+        assert_eq!(second.nickname.as_deref(), Some("override"));
+    }
+}
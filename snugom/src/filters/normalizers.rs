@@ -16,19 +16,28 @@ pub fn build_numeric_filter(descriptor: FilterDescriptor, target_field: &str) ->
             let numeric = value.parse::<f64>().map_err(|_| RepoError::InvalidRequest {
                 message: format!("Invalid numeric value: {}", value),
             })?;
-            Ok(FilterCondition::NumericRange {
-                field: target_field.to_string(),
-                min: Some(numeric),
-                max: Some(numeric),
-            })
+            Ok(FilterCondition::numeric_eq(target_field, numeric))
         }
+        FilterOperator::Neq => descriptor.map_with(|descriptor| build_numeric_filter(descriptor, target_field)),
         FilterOperator::Range => {
             let min = parse_numeric_bound(descriptor.values.first())?;
             let max = parse_numeric_bound(descriptor.values.get(1))?;
+            Ok(FilterCondition::numeric_range(target_field, min, max))
+        }
+        FilterOperator::Gt | FilterOperator::Gte | FilterOperator::Lt | FilterOperator::Lte => {
+            let bound = parse_numeric_bound(descriptor.values.first())?.ok_or_else(|| RepoError::InvalidRequest {
+                message: format!("Numeric filter on {} requires a value", target_field),
+            })?;
+            let (min, max) = match descriptor.operator {
+                FilterOperator::Gt | FilterOperator::Gte => (Some(bound), None),
+                _ => (None, Some(bound)),
+            };
             Ok(FilterCondition::NumericRange {
                 field: target_field.to_string(),
                 min,
                 max,
+                min_exclusive: descriptor.operator == FilterOperator::Gt,
+                max_exclusive: descriptor.operator == FilterOperator::Lt,
             })
         }
         FilterOperator::Bool => Err(RepoError::InvalidRequest {
@@ -133,6 +142,7 @@ pub fn build_text_filter(descriptor: FilterDescriptor, target_field: &str) -> Re
         FilterOperator::Contains => build_text_contains_filter(descriptor, target_field),
         FilterOperator::Exact => build_text_exact_filter(descriptor, target_field),
         FilterOperator::Fuzzy => build_text_fuzzy_filter(descriptor, target_field),
+        FilterOperator::Neq => descriptor.map_with(|descriptor| build_text_filter(descriptor, target_field)),
         FilterOperator::Eq => {
             // For backwards compatibility, Eq on TEXT fields creates a prefix filter
             let value = descriptor.values.into_iter().next().ok_or_else(|| RepoError::InvalidRequest {
@@ -0,0 +1,135 @@
+//! async-graphql integration, enabled by the `graphql` feature.
+//!
+//! Entities marked `#[snugom(graphql)]` get an `{Entity}Connection` output type and, when they
+//! have filterable fields, an `{Entity}Filter` input type. The entity itself is exposed by
+//! deriving `async_graphql::SimpleObject` on it, so the application must depend on
+//! `async-graphql` directly.
+//!
+//! # Example
+//! ```ignore
+//! #[derive(SnugomEntity, SimpleObject, Serialize, Deserialize)]
+//! #[snugom(schema = 1, service = "shop", collection = "orders", graphql)]
+//! struct Order { /* ... */ }
+//!
+//! #[Object]
+//! impl QueryRoot {
+//!     async fn orders(
+//!         &self,
+//!         ctx: &Context<'_>,
+//!         search: Option<SearchInput>,
+//!         filter: Option<OrderFilter>,
+//!     ) -> async_graphql::Result<OrderConnection> {
+//!         let mut client = ctx.data::<Shop>()?.clone();
+//!         let query = search.unwrap_or_default().into_query(filter);
+//!         Ok(client.orders().find_many(query).await?.into())
+//!     }
+//! }
+//! ```
+
+use async_graphql::InputObject;
+
+use crate::search::{SearchQuery, SortOrder};
+
+/// A generated filter input that lowers to `SearchQuery::filter` strings.
+pub trait GraphqlFilter {
+    fn into_filters(self) -> Vec<String>;
+}
+
+/// Pagination, sorting, and free-text arguments shared by every collection query.
+#[derive(InputObject, Debug, Clone, Default)]
+pub struct SearchInput {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+    /// Name of a sortable field.
+    pub sort_by: Option<String>,
+    pub sort_order: Option<SortOrder>,
+    /// Free-text query across the entity's text fields.
+    pub q: Option<String>,
+}
+
+impl SearchInput {
+    /// Combine with an optional generated filter into a [`SearchQuery`].
+    pub fn into_query<F: GraphqlFilter>(self, filter: Option<F>) -> SearchQuery {
+        SearchQuery {
+            page: self.page,
+            page_size: self.page_size,
+            sort_by: self.sort_by,
+            sort_order: self.sort_order,
+            q: self.q,
+            filter: filter.map(GraphqlFilter::into_filters).unwrap_or_default(),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_graphql {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::SnugomEntity;
+
+    #[derive(SnugomEntity, SimpleObject, Serialize, Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "gql", collection = "tickets", graphql)]
+    struct Ticket {
+        #[snugom(id)]
+        id: String,
+        #[snugom(filterable(tag))]
+        status: String,
+        #[snugom(filterable(text))]
+        title: String,
+        #[snugom(filterable, sortable)]
+        priority: i64,
+        #[snugom(filterable)]
+        archived: bool,
+    }
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn tickets(&self, _search: Option<SearchInput>, _filter: Option<TicketFilter>) -> TicketConnection {
+            crate::search::SearchResult {
+                items: Vec::new(),
+                total: 0,
+                page: 1,
+                page_size: 25,
+                scores: Vec::new(),
+            }
+            .into()
+        }
+    }
+
+    #[test]
+    fn filter_lowers_to_search_filters() {
+        let filter = TicketFilter {
+            status: Some(vec!["open".to_string(), "blocked".to_string()]),
+            title: Some("login".to_string()),
+            priority_min: Some(2.0),
+            priority_max: None,
+            archived: Some(false),
+        };
+        let query = SearchInput::default().into_query(Some(filter));
+        assert_eq!(
+            query.filter,
+            vec!["status:eq:open|blocked", "title:contains:login", "priority:range:2,", "archived:bool:false"]
+        );
+    }
+
+    #[test]
+    fn schema_exposes_generated_types() {
+        let sdl = Schema::build(QueryRoot, EmptyMutation, EmptySubscription).finish().sdl();
+        assert!(sdl.contains("input TicketFilter"), "{sdl}");
+        assert!(sdl.contains("priorityMin: Float"), "{sdl}");
+        assert!(sdl.contains("type TicketConnection"), "{sdl}");
+        assert!(sdl.contains("hasMore: Boolean!"), "{sdl}");
+    }
+}
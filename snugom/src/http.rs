@@ -0,0 +1,211 @@
+//! HTTP error mapping, enabled by the `http` feature (and by `axum`, which builds on it).
+//!
+//! [`RepoError::status_code`] picks the status and [`RepoError::problem`] builds an RFC 9457
+//! `application/problem+json` body, so services on any HTTP stack share one mapping:
+//!
+//! | Error | Status | `code` |
+//! |-------|--------|--------|
+//! | `Validation` | 422 | `validation_failed`, with `issues` |
+//! | `NotFound` | 404 | `not_found` |
+//! | `VersionConflict`, `PreconditionFailed` | 409, with `Retry-After` | `version_conflict`, `precondition_failed` |
+//! | `UniqueConstraintViolation`, `AlreadyExists` | 409 | `unique_violation`, `already_exists` |
+//! | `InvalidRequest` | 400 | `invalid_request` |
+//! | `Redis`, `Other` | 500 | `internal`; details are not exposed |
+//!
+//! # Example
+//! ```ignore
+//! let response = match result {
+//!     Ok(guild) => json_response(guild),
+//!     Err(err) => err.to_http_response().map(Full::from),
+//! };
+//! ```
+
+use std::time::Duration;
+
+use ::http::{HeaderValue, Response, StatusCode, header};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::errors::{RepoError, ValidationIssue};
+
+/// Media type of [`ProblemDetails`] bodies.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// How long a client should wait before retrying a write that lost an optimistic-concurrency
+/// race; it needs to re-read the entity first, not back off.
+pub const CONFLICT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// RFC 9457 problem details for a [`RepoError`].
+///
+/// `type` is always `about:blank`, so `title` is the status reason phrase; clients branch on
+/// `code` and show or localize `detail` and `issues`.
+///
+/// ```json
+/// {"type": "about:blank", "title": "Unprocessable Entity", "status": 422, "code": "validation_failed",
+///  "detail": "validation failed", "issues": [{"field": "name", "code": "validation.required", ...}]}
+/// ```
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    /// Stable machine-readable error code
+    pub code: String,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<ValidationIssue>,
+    /// Seconds to wait before retrying, also sent as the `Retry-After` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    /// Error-specific members such as `entity_id`, `expected`/`actual`, or `fields`
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl RepoError {
+    /// HTTP status for this error: 422 validation, 404 not found, 409 conflicts, 400 bad input,
+    /// 500 otherwise.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            RepoError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            RepoError::NotFound { .. } => StatusCode::NOT_FOUND,
+            RepoError::VersionConflict { .. }
+            | RepoError::UniqueConstraintViolation { .. }
+            | RepoError::AlreadyExists { .. }
+            | RepoError::PreconditionFailed { .. } => StatusCode::CONFLICT,
+            RepoError::InvalidRequest { .. } => StatusCode::BAD_REQUEST,
+            RepoError::Redis(_) | RepoError::Other { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable code naming the kind of error, e.g. `version_conflict`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RepoError::Validation(_) => "validation_failed",
+            RepoError::NotFound { .. } => "not_found",
+            RepoError::VersionConflict { .. } => "version_conflict",
+            RepoError::UniqueConstraintViolation { .. } => "unique_violation",
+            RepoError::AlreadyExists { .. } => "already_exists",
+            RepoError::PreconditionFailed { .. } => "precondition_failed",
+            RepoError::InvalidRequest { .. } => "invalid_request",
+            RepoError::Redis(_) | RepoError::Other { .. } => "internal",
+        }
+    }
+
+    /// [`CONFLICT_RETRY_AFTER`] for conflicts a retry can resolve; `None` otherwise.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            RepoError::VersionConflict { .. } | RepoError::PreconditionFailed { .. } => Some(CONFLICT_RETRY_AFTER),
+            _ => None,
+        }
+    }
+
+    /// Problem details body for this error.
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status_code();
+        let mut extensions = Map::new();
+        let mut issues = Vec::new();
+        let detail = match self {
+            RepoError::Validation(err) => {
+                issues = err.issues.clone();
+                self.to_string()
+            }
+            RepoError::NotFound { entity_id } => {
+                extensions.insert("entity_id".to_string(), json!(entity_id));
+                self.to_string()
+            }
+            RepoError::VersionConflict { expected, actual } => {
+                extensions.insert("expected".to_string(), json!(expected));
+                extensions.insert("actual".to_string(), json!(actual));
+                self.to_string()
+            }
+            // Names the fields, not the values or the entity holding them
+            RepoError::UniqueConstraintViolation { fields, .. } => {
+                extensions.insert("fields".to_string(), json!(fields));
+                format!("values for {fields:?} already exist")
+            }
+            RepoError::AlreadyExists { entity_id } | RepoError::PreconditionFailed { entity_id } => {
+                extensions.insert("entity_id".to_string(), json!(entity_id));
+                self.to_string()
+            }
+            RepoError::InvalidRequest { message } => message.clone(),
+            // Internal details stay in logs, not in responses
+            RepoError::Redis(_) | RepoError::Other { .. } => "internal server error".to_string(),
+        };
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            code: self.code().to_string(),
+            detail,
+            issues,
+            retry_after: self.retry_after().map(|delay| delay.as_secs()),
+            extensions,
+        }
+    }
+
+    /// A complete response: status, `Content-Type: application/problem+json`, `Retry-After`
+    /// when set, and the serialized [`ProblemDetails`].
+    pub fn to_http_response(&self) -> Response<Vec<u8>> {
+        let problem = self.problem();
+        let mut response = Response::new(serde_json::to_vec(&problem).unwrap_or_default());
+        *response.status_mut() = self.status_code();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let Some(seconds) = problem.retry_after {
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ValidationError;
+
+    #[test]
+    fn problems_carry_codes_and_extensions() {
+        let validation = RepoError::Validation(ValidationError::single("name", "validation.required", "required"));
+        let problem = serde_json::to_value(validation.problem()).unwrap();
+        assert_eq!(problem["type"], "about:blank");
+        assert_eq!(problem["title"], "Unprocessable Entity");
+        assert_eq!(problem["status"], 422);
+        assert_eq!(problem["code"], "validation_failed");
+        assert_eq!(problem["issues"][0]["field"], "name");
+        assert!(problem.get("retry_after").is_none());
+
+        let unique = RepoError::UniqueConstraintViolation {
+            fields: vec!["email".to_string()],
+            values: vec!["a@example.com".to_string()],
+            existing_entity_id: "u1".to_string(),
+        };
+        let problem = serde_json::to_value(unique.problem()).unwrap();
+        assert_eq!(problem["code"], "unique_violation");
+        assert_eq!(problem["fields"], json!(["email"]));
+        assert!(!problem.to_string().contains("a@example.com"));
+        assert!(problem.get("issues").is_none());
+    }
+
+    #[test]
+    fn conflicts_send_retry_after() {
+        let conflict = RepoError::VersionConflict {
+            expected: Some(2),
+            actual: Some(3),
+        };
+        let response = conflict.to_http_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body: Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["retry_after"], 1);
+        assert_eq!(body["expected"], 2);
+
+        let exists = RepoError::AlreadyExists {
+            entity_id: "g1".to_string(),
+        };
+        assert!(!exists.to_http_response().headers().contains_key(header::RETRY_AFTER));
+    }
+}
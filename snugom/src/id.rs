@@ -1,3 +1,8 @@
+use std::{
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use nanoid::nanoid;
 
 /// Canonical alphabet for SnugOM entity identifiers (no ambiguous glyphs).
@@ -13,6 +18,61 @@ pub fn generate_entity_id() -> String {
     nanoid!(ENTITY_ID_LENGTH, ENTITY_ID_ALPHABET)
 }
 
+/// Generates an identifier under an entity's `#[snugom(id(prefix = "..."))]`, e.g. `usr_K3hT...`.
+pub fn generate_prefixed_id(prefix: &str) -> String {
+    format!("{prefix}_{}", generate_entity_id())
+}
+
+/// Crockford base32, the ULID alphabet; its ASCII order matches the value order.
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Largest value of a ULID's 80-bit random part.
+const ULID_RANDOM_MAX: u128 = (1 << 80) - 1;
+
+/// Millisecond and 80-bit random part of the last ULID handed out.
+static LAST_ULID: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// Generates a ULID for `#[snugom(id(strategy = "ulid"))]`: 26 characters that sort by creation time.
+///
+/// Ids made in the same millisecond increment the random part of the previous one, so ids from
+/// this process sort in the order they were generated.
+pub fn generate_ulid() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let (millis, random) = {
+        let mut last = LAST_ULID.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *last = next_ulid(*last, now, || {
+            let bytes = nanoid::rngs::default(10);
+            bytes.iter().fold(0u128, |acc, byte| acc << 8 | u128::from(*byte))
+        });
+        *last
+    };
+    let value = u128::from(millis) << 80 | random;
+    (0..26).rev().map(|digit| ULID_ALPHABET[(value >> (digit * 5)) as usize & 31] as char).collect()
+}
+
+/// The millisecond and random part following `last`: fresh randomness in a new millisecond,
+/// otherwise the previous random part plus one. When that would overflow its 80 bits, the id
+/// moves on to the next millisecond instead of carrying into the timestamp.
+fn next_ulid(last: (u64, u128), now: u64, random: impl FnOnce() -> u128) -> (u64, u128) {
+    match last {
+        (millis, previous) if now <= millis && previous < ULID_RANDOM_MAX => (millis, previous + 1),
+        (millis, _) if now <= millis => (millis + 1, 0),
+        _ => (now, random() & ULID_RANDOM_MAX),
+    }
+}
+
+/// Generates a UUIDv7 for `#[snugom(id(strategy = "uuid_v7"))]`, e.g. `01926f3c-...`; these sort by creation time.
+pub fn generate_uuid_v7() -> String {
+    uuid::Uuid::now_v7().to_string()
+}
+
+/// Whether `id` is `{prefix}_` followed by a non-empty remainder.
+pub fn has_id_prefix(id: &str, prefix: &str) -> bool {
+    id.strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .is_some_and(|rest| !rest.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,4 +83,30 @@ mod tests {
         assert_eq!(id.len(), ENTITY_ID_LENGTH);
         assert!(id.chars().all(|c| ENTITY_ID_ALPHABET.contains(&c)));
     }
+
+    #[test]
+    fn prefixed_id_carries_prefix() {
+        let id = generate_prefixed_id("usr");
+        assert!(has_id_prefix(&id, "usr"));
+        assert_eq!(id.len(), "usr_".len() + ENTITY_ID_LENGTH);
+        assert!(!has_id_prefix(&id, "us"));
+        assert!(!has_id_prefix("usr_", "usr"));
+        assert!(!has_id_prefix("usrK3hT", "usr"));
+    }
+
+    #[test]
+    fn time_ordered_ids_ascend() {
+        let ulids: Vec<String> = (0..100).map(|_| generate_ulid()).collect();
+        assert!(ulids.iter().all(|id| id.len() == 26 && id.bytes().all(|byte| ULID_ALPHABET.contains(&byte))));
+        assert!(ulids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(next_ulid((5, 7), 5, || 0), (5, 8));
+        assert_eq!(next_ulid((5, ULID_RANDOM_MAX), 5, || 0), (6, 0));
+        assert_eq!(next_ulid((5, ULID_RANDOM_MAX), 4, || 0), (6, 0));
+        assert_eq!(next_ulid((5, 7), 9, || u128::MAX), (9, ULID_RANDOM_MAX));
+
+        let uuids: Vec<String> = (0..100).map(|_| generate_uuid_v7()).collect();
+        assert!(uuids.iter().all(|id| id.len() == 36 && id.as_bytes()[14] == b'7'));
+        assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
 }
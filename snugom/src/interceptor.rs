@@ -0,0 +1,98 @@
+//! Hooks that see every Redis interaction made by a client's repositories.
+//!
+//! Register one with [`Client::with_interceptor`](crate::Client::with_interceptor) (or
+//! [`Repo::with_interceptor`](crate::Repo::with_interceptor)) to log commands, count them
+//! against a budget, or feed them to a metrics system of your own. Each read, search, and
+//! mutation script is reported once it completes, with how long it took and whether it failed.
+//!
+//! # Example
+//! ```ignore
+//! let client = Client::connect(url, "myapp").await?.with_interceptor(|event: &CommandEvent<'_>| {
+//!     if event.duration > Duration::from_millis(20) {
+//!         eprintln!("{} {} took {:?} ({:?})", event.operation, event.target, event.duration, event.outcome);
+//!     }
+//! });
+//! ```
+
+use std::{
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::errors::RepoError;
+
+/// One completed Redis interaction.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandEvent<'a> {
+    /// What was done, e.g. `get`, `search`, `relation_members`, or the mutation script that ran
+    /// (`entity_patch`)
+    pub operation: &'static str,
+    /// The key, index name, or key pattern the interaction was about; for a mutation plan, the
+    /// key of its first command
+    pub target: &'a str,
+    pub duration: Duration,
+    pub outcome: Result<(), &'a RepoError>,
+}
+
+/// Receives a [`CommandEvent`] after each Redis interaction.
+///
+/// Called inline on the task that made the request, so it should return quickly; hand slow
+/// work off to a channel. Closures taking `&CommandEvent` implement this trait.
+pub trait CommandInterceptor: Send + Sync {
+    fn intercept(&self, event: &CommandEvent<'_>);
+}
+
+impl<F> CommandInterceptor for F
+where
+    F: Fn(&CommandEvent<'_>) + Send + Sync,
+{
+    fn intercept(&self, event: &CommandEvent<'_>) {
+        self(event)
+    }
+}
+
+/// A [`CommandInterceptor`] shared by the repositories of a client.
+#[derive(Clone)]
+pub struct SharedInterceptor(Arc<dyn CommandInterceptor>);
+
+impl SharedInterceptor {
+    pub fn new(interceptor: impl CommandInterceptor + 'static) -> Self {
+        Self(Arc::new(interceptor))
+    }
+
+    pub(crate) fn intercept(&self, event: &CommandEvent<'_>) {
+        self.0.intercept(event);
+    }
+}
+
+/// Await one Redis interaction, reporting it to `interceptor` when there is one.
+pub(crate) async fn observe<R, E>(
+    interceptor: Option<&SharedInterceptor>,
+    operation: &'static str,
+    target: &str,
+    future: impl Future<Output = Result<R, E>>,
+) -> Result<R, RepoError>
+where
+    E: Into<RepoError>,
+{
+    let Some(interceptor) = interceptor else {
+        return future.await.map_err(Into::into);
+    };
+    let started = Instant::now();
+    let result = future.await.map_err(Into::into);
+    interceptor.intercept(&CommandEvent {
+        operation,
+        target,
+        duration: started.elapsed(),
+        outcome: result.as_ref().map(|_| ()),
+    });
+    result
+}
+
+impl fmt::Debug for SharedInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedInterceptor")
+    }
+}
@@ -1,50 +1,294 @@
+//! How Redis keys are laid out.
+//!
+//! Every key is a list of segments led by a head of the key prefix and service:
+//! `prefix:service:collection:id` for entities, `prefix:service:rel:alias:id` for relation sets,
+//! and so on. A [`KeyScheme`] picks the separator between segments and which of the prefix and
+//! service comes first, so an application adopting SnugOM over an existing keyspace, say
+//! `orders/legacy/...`, can keep it. The segments after the head stay in SnugOM's order, since
+//! search indexes cover a collection by the key prefix its entities share.
+//!
+//! One entity can depart from the scheme with [`KeyOverrides`]: its own separator, and extra
+//! segments after the head, e.g. a region in `prefix:service:eu:orders:id`.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::errors::RepoError;
+use crate::types::EntityDescriptor;
+
+/// Chooses the separator placed between key segments, and the order of the key's head.
+///
+/// Installed once per process with [`set_key_scheme`], before any repository builds a key, and
+/// shared by repositories, index definitions, mutation scripts, and (with `--key-separator` and
+/// `--key-order`) the CLI. The separator must not appear in key prefixes, service or collection names, or
+/// relation aliases.
+pub trait KeyScheme: fmt::Debug + Send + Sync {
+    fn separator(&self) -> char;
+
+    /// Order of the prefix and service at the head of every key.
+    fn order(&self) -> SegmentOrder {
+        SegmentOrder::PrefixFirst
+    }
+}
+
+/// Which of the key prefix and the service leads a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegmentOrder {
+    /// `prefix:service:...`
+    #[default]
+    PrefixFirst,
+    /// `service:prefix:...`, for keyspaces partitioned by service
+    ServiceFirst,
+}
+
+/// The `prefix:service:collection:id` layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultKeyScheme;
+
+impl KeyScheme for DefaultKeyScheme {
+    fn separator(&self) -> char {
+        ':'
+    }
+}
+
+/// Segments joined by the given character, e.g. `SeparatorKeyScheme('/')` for
+/// `prefix/service/collection/id`.
+#[derive(Debug, Clone, Copy)]
+pub struct SeparatorKeyScheme(pub char);
+
+impl KeyScheme for SeparatorKeyScheme {
+    fn separator(&self) -> char {
+        self.0
+    }
+}
+
+/// A separator and segment order, e.g. `/` with [`SegmentOrder::ServiceFirst`] for
+/// `service/prefix/collection/id`.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomKeyScheme {
+    pub separator: char,
+    pub order: SegmentOrder,
+}
+
+impl KeyScheme for CustomKeyScheme {
+    fn separator(&self) -> char {
+        self.separator
+    }
+
+    fn order(&self) -> SegmentOrder {
+        self.order
+    }
+}
+
+static KEY_SCHEME: OnceLock<Box<dyn KeyScheme>> = OnceLock::new();
+
+/// Install the process-wide key scheme.
+///
+/// Fails once any key has been built (which fixes the [`DefaultKeyScheme`]) or a scheme was
+/// already installed, and for separators that RediSearch filters, glob patterns, or the scripts'
+/// key parsing treat specially: anything other than ASCII punctuation, and `_ * ? [ ] \ " '`.
+/// `-` is refused as well, since UUIDs, nanoids, and slugs contain it.
+///
+/// # Example
+/// ```ignore
+/// snugom::keys::set_key_scheme(SeparatorKeyScheme('/'))?;
+/// let client = Client::connect(url, "legacy").await?;   // keys like legacy/shop/orders/{id}
+/// ```
+pub fn set_key_scheme(scheme: impl KeyScheme + 'static) -> Result<(), RepoError> {
+    validate_separator(scheme.separator())?;
+    KEY_SCHEME.set(Box::new(scheme)).map_err(|_| RepoError::InvalidRequest {
+        message: "the key scheme is already in use; set it before building any keys".to_string(),
+    })
+}
+
+/// The installed key scheme, [`DefaultKeyScheme`] unless [`set_key_scheme`] was called first.
+pub fn key_scheme() -> &'static dyn KeyScheme {
+    KEY_SCHEME.get_or_init(|| Box::new(DefaultKeyScheme)).as_ref()
+}
+
+/// Separator of the installed key scheme.
+pub fn key_separator() -> char {
+    key_scheme().separator()
+}
+
+pub(crate) fn validate_separator(separator: char) -> Result<(), RepoError> {
+    if separator.is_ascii_punctuation()
+        && !matches!(separator, '_' | '-' | '*' | '?' | '[' | ']' | '\\' | '"' | '\'')
+    {
+        Ok(())
+    } else {
+        Err(RepoError::InvalidRequest {
+            message: format!("`{separator}` can't separate key segments"),
+        })
+    }
+}
+
+/// How one entity's keys depart from the installed scheme, from
+/// `#[snugom(key_separator = "/", key_components = ["eu"])]`.
+///
+/// Keys naming the entity's collection (its documents, unique indexes, history, audit stream,
+/// and search index) and the relation sets it owns are built with the overrides; keys of other
+/// entities keep their own.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KeyOverrides {
+    /// Separator of this entity's keys in place of the scheme's
+    pub separator: Option<char>,
+    /// Segments placed after the prefix and service, in order
+    pub components: Vec<String>,
+}
+
+static NO_OVERRIDES: KeyOverrides = KeyOverrides {
+    separator: None,
+    components: Vec::new(),
+};
+
+/// Where one entity's keys live, as the mutation scripts see it: the separator, and the head
+/// every key starts with, already joined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyLayout {
+    pub separator: char,
+    pub head: String,
+}
+
+impl KeyLayout {
+    /// The head and `segments`, joined by the separator.
+    pub fn join(&self, segments: &[&str]) -> String {
+        let mut key = self.head.clone();
+        for segment in segments {
+            key.push(self.separator);
+            key.push_str(segment);
+        }
+        key
+    }
+
+    /// Up to `count` segments of `key` after the head; the last keeps any separators left in
+    /// it, so ids containing the separator survive. `None` when `key` is not under the head.
+    pub fn tail<'k>(&self, key: &'k str, count: usize) -> Option<Vec<&'k str>> {
+        let rest = key.strip_prefix(self.head.as_str())?.strip_prefix(self.separator)?;
+        Some(rest.splitn(count, self.separator).collect())
+    }
+}
+
 /// Common key-construction helpers used across SnugOM.
 #[derive(Debug, Clone)]
 pub struct KeyContext<'a> {
     pub prefix: &'a str,
     pub service: &'a str,
+    pub scheme: &'a dyn KeyScheme,
+    pub overrides: &'a KeyOverrides,
 }
 
 impl<'a> KeyContext<'a> {
+    /// Keys laid out by the installed [`key_scheme`].
     pub fn new(prefix: &'a str, service: &'a str) -> Self {
-        Self { prefix, service }
+        Self::with_scheme(prefix, service, key_scheme())
+    }
+
+    pub fn with_scheme(prefix: &'a str, service: &'a str, scheme: &'a dyn KeyScheme) -> Self {
+        Self {
+            prefix,
+            service,
+            scheme,
+            overrides: &NO_OVERRIDES,
+        }
+    }
+
+    /// Keys of the entity `descriptor` describes, with its [`KeyOverrides`].
+    pub fn for_entity(prefix: &'a str, descriptor: &'a EntityDescriptor) -> Self {
+        Self::new(prefix, &descriptor.service).with_overrides(&descriptor.keys)
+    }
+
+    pub fn with_overrides(self, overrides: &'a KeyOverrides) -> Self {
+        Self { overrides, ..self }
+    }
+
+    /// The separator and head of every key built from this context.
+    pub fn layout(&self) -> KeyLayout {
+        let separator = self.overrides.separator.unwrap_or_else(|| self.scheme.separator());
+        let mut head = match self.scheme.order() {
+            SegmentOrder::PrefixFirst => format!("{}{separator}{}", self.prefix, self.service),
+            SegmentOrder::ServiceFirst => format!("{}{separator}{}", self.service, self.prefix),
+        };
+        for component in &self.overrides.components {
+            head.push(separator);
+            head.push_str(component);
+        }
+        KeyLayout { separator, head }
+    }
+
+    /// The head and `segments`, joined by the separator.
+    pub fn join(&self, segments: &[&str]) -> String {
+        self.layout().join(segments)
     }
 
     pub fn entity(&self, collection: &str, entity_id: &str) -> String {
-        format!("{}:{}:{}:{}", self.prefix, self.service, collection, entity_id)
+        self.join(&[collection, entity_id])
     }
 
     /// Returns a glob pattern matching all entities in a collection.
     /// Useful for test cleanup or batch operations.
     pub fn collection_pattern(&self, collection: &str) -> String {
-        format!("{}:{}:{}:*", self.prefix, self.service, collection)
+        self.join(&[collection, "*"])
+    }
+
+    /// The key prefix shared by a collection's entities, which its search index covers.
+    pub fn collection_prefix(&self, collection: &str) -> String {
+        self.join(&[collection, ""])
+    }
+
+    /// Prefix of a collection's unique-constraint indexes, which share the collection's key
+    /// prefix and are skipped when scanning for entities.
+    pub fn unique_prefix(&self, collection: &str) -> String {
+        self.join(&[collection, "unique"])
+    }
+
+    /// Name of a collection's search index, unless `#[snugom(index_name = "...")]` overrides it.
+    pub fn index_name(&self, collection: &str) -> String {
+        self.join(&[collection, "idx"])
     }
 
     /// Returns a glob pattern matching all keys in this service.
     /// Useful for test cleanup of all service data (entities + auxiliary keys).
     pub fn service_pattern(&self) -> String {
-        format!("{}:{}:*", self.prefix, self.service)
+        self.join(&["*"])
+    }
+
+    /// Service-wide record of a consumed idempotency key and the response it produced.
+    pub fn idempotency(&self, key: &str) -> String {
+        self.join(&["idempotency", key])
+    }
+
+    /// Stream of audit entries for one entity, kept outside the collection's key prefix so it
+    /// is neither indexed nor removed with the entity.
+    pub fn audit(&self, collection: &str, entity_id: &str) -> String {
+        self.join(&["audit", collection, entity_id])
+    }
+
+    /// List of previous documents of a versioned entity, newest first.
+    pub fn history(&self, collection: &str, entity_id: &str) -> String {
+        self.join(&["history", collection, entity_id])
     }
 
     pub fn relation(&self, alias: &str, left_id: &str) -> String {
-        format!("{}:{}:rel:{}:{}", self.prefix, self.service, alias, left_id)
+        self.join(&["rel", alias, left_id])
+    }
+
+    /// Hash of per-member edge payloads stored alongside a relation set.
+    pub fn relation_edges(&self, alias: &str, left_id: &str) -> String {
+        self.join(&["rel", alias, left_id, "edges"])
     }
 
     pub fn relation_reverse(&self, alias: &str, right_id: &str) -> String {
-        format!(
-            "{}:{}:rel:{}_reverse:{}",
-            self.prefix, self.service, alias, right_id
-        )
+        self.join(&["rel", &format!("{alias}_reverse"), right_id])
     }
 
     /// Key for reverse relation lookup - finds all children of a given collection
     /// that have a belongs_to relation pointing to a specific parent entity.
     /// Format: prefix:service:child_collection:rev_rel:alias:parent_id
     pub fn reverse_relation(&self, child_collection: &str, alias: &str, parent_id: &str) -> String {
-        format!(
-            "{}:{}:{}:rev_rel:{}:{}",
-            self.prefix, self.service, child_collection, alias, parent_id
-        )
+        self.join(&[child_collection, "rev_rel", alias, parent_id])
     }
 }
 
@@ -57,4 +301,79 @@ mod tests {
         let ctx = KeyContext::new("snug", "svc");
         assert_eq!(ctx.entity("users", "abc"), "snug:svc:users:abc");
     }
+
+    #[test]
+    fn builds_relation_edge_keys() {
+        let ctx = KeyContext::new("snug", "svc");
+        assert_eq!(ctx.relation_edges("members", "t1"), "snug:svc:rel:members:t1:edges");
+    }
+
+    #[test]
+    fn builds_idempotency_keys() {
+        let ctx = KeyContext::new("snug", "svc");
+        assert_eq!(ctx.idempotency("req-1"), "snug:svc:idempotency:req-1");
+    }
+
+    #[test]
+    fn builds_audit_keys() {
+        let ctx = KeyContext::new("snug", "svc");
+        assert_eq!(ctx.audit("users", "abc"), "snug:svc:audit:users:abc");
+        assert_eq!(ctx.history("users", "abc"), "snug:svc:history:users:abc");
+    }
+
+    #[test]
+    fn schemes_choose_the_separator() {
+        let ctx = KeyContext::with_scheme("legacy", "shop", &SeparatorKeyScheme('/'));
+        assert_eq!(ctx.entity("orders", "o1"), "legacy/shop/orders/o1");
+        assert_eq!(ctx.relation_edges("items", "o1"), "legacy/shop/rel/items/o1/edges");
+        assert_eq!(ctx.relation_reverse("items", "i1"), "legacy/shop/rel/items_reverse/i1");
+        assert_eq!(ctx.collection_prefix("orders"), "legacy/shop/orders/");
+        assert_eq!(ctx.index_name("orders"), "legacy/shop/orders/idx");
+
+        assert!(validate_separator('.').is_ok());
+        assert!(validate_separator('_').is_err());
+        assert!(validate_separator('-').is_err());
+        assert!(validate_separator('*').is_err());
+        assert!(validate_separator('x').is_err());
+    }
+
+    #[test]
+    fn schemes_choose_the_segment_order() {
+        let scheme = CustomKeyScheme {
+            separator: '/',
+            order: SegmentOrder::ServiceFirst,
+        };
+        let ctx = KeyContext::with_scheme("legacy", "shop", &scheme);
+        assert_eq!(ctx.entity("orders", "o1"), "shop/legacy/orders/o1");
+        assert_eq!(ctx.relation("items", "o1"), "shop/legacy/rel/items/o1");
+
+        let layout = ctx.layout();
+        assert_eq!(layout.head, "shop/legacy");
+        assert_eq!(layout.tail("shop/legacy/rel/items/a/b", 3), Some(vec!["rel", "items", "a/b"]));
+        assert_eq!(layout.tail("legacy/shop/orders/o1", 2), None);
+    }
+
+    #[test]
+    fn entities_override_the_separator_and_add_components() {
+        let overrides = KeyOverrides {
+            separator: Some('/'),
+            components: vec!["eu".to_string()],
+        };
+        let ctx = KeyContext::new("snug", "svc").with_overrides(&overrides);
+        assert_eq!(ctx.entity("orders", "o1"), "snug/svc/eu/orders/o1");
+        assert_eq!(ctx.relation_edges("items", "o1"), "snug/svc/eu/rel/items/o1/edges");
+        assert_eq!(ctx.index_name("orders"), "snug/svc/eu/orders/idx");
+        assert_eq!(ctx.layout().tail("snug/svc/eu/orders/o1", 2), Some(vec!["orders", "o1"]));
+
+        let descriptor = EntityDescriptor {
+            service: "svc".to_string(),
+            keys: KeyOverrides {
+                separator: None,
+                components: vec!["us".to_string(), "east".to_string()],
+            },
+            ..Default::default()
+        };
+        let ctx = KeyContext::for_entity("snug", &descriptor);
+        assert_eq!(ctx.history("orders", "o1"), "snug:svc:us:east:history:orders:o1");
+    }
 }
@@ -33,6 +33,36 @@ pub const fn validate_relation_targets(entity_name: &str, relation_targets: &[&s
     }
 }
 
+/// Compile-time validation that every relation into another service names an entity the
+/// client knows, either one of its own or one listed as `shared`.
+///
+/// # Panics
+///
+/// Panics at compile time if a `(service, collection)` target is not among `known`.
+pub const fn validate_service_relation_targets(
+    entity_name: &str,
+    relation_targets: &[(&str, &str)],
+    known: &[(&str, &str)],
+) {
+    let mut i = 0;
+    while i < relation_targets.len() {
+        let (service, collection) = relation_targets[i];
+        let mut found = false;
+        let mut j = 0;
+        while j < known.len() {
+            if const_str_eq(service, known[j].0) && const_str_eq(collection, known[j].1) {
+                found = true;
+                break;
+            }
+            j += 1;
+        }
+        if !found {
+            const_panic_unknown_service_target(entity_name, service, collection);
+        }
+        i += 1;
+    }
+}
+
 /// Const string equality comparison
 const fn const_str_eq(a: &str, b: &str) -> bool {
     let a_bytes = a.as_bytes();
@@ -64,6 +94,16 @@ const fn const_panic_invalid_target(entity_name: &str, target: &str, valid_colle
     )
 }
 
+/// Const panic for a relation into a service the client has no entity for
+#[allow(unused_variables)]
+const fn const_panic_unknown_service_target(entity_name: &str, service: &str, collection: &str) -> ! {
+    panic!(
+        "Invalid cross-service relation target. No entity of the client matches the relation's \
+            `target_service` and `target`. Add the target entity to #[snugom_client(shared = [...])], \
+            or correct the relation attribute."
+    )
+}
+
 /// Compile-time validation that an entity has at least one indexed field.
 ///
 /// Entities must have at least one filterable or sortable field
@@ -83,30 +123,60 @@ pub const fn validate_entity_has_indexed_fields(entity_name: &str, has_indexed_f
     }
 }
 
+pub mod audit;
+#[cfg(feature = "axum")]
+pub mod axum;
+pub mod bench;
+mod cleanup;
 pub mod client;
+pub mod compression;
 pub mod errors;
 pub mod examples;
+pub mod factory;
 pub mod filters;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "http")]
+pub mod http;
 pub mod id;
+pub mod interceptor;
 pub mod keys;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod prefix_copy;
 pub mod registry;
 pub mod repository;
 pub mod runtime;
 pub mod search;
+pub mod slug;
+pub mod storage;
+mod telemetry;
+mod unit_of_work;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 pub mod types;
 pub mod validators;
 
 pub mod macros;
 
-pub use client::{BulkCreateResult, Client, CollectionHandle, EntityRegistration};
+pub use cleanup::{CleanupOptions, CleanupReport, cleanup_pattern, cleanup_pattern_with};
+pub use client::{
+    BulkCreateFailure, BulkCreateOptions, BulkCreateResult, Client, ClientConfig, ClientOptions, CollectionHandle,
+    EntityRegistration, LazyClient, ReadPreference, RetryPolicy,
+};
 pub use errors::*;
+pub use interceptor::{CommandEvent, CommandInterceptor, SharedInterceptor};
+pub use prefix_copy::{
+    PrefixCopyOptions, PrefixCopyReport, PrefixVerification, copy_prefix, copy_prefix_with, verify_prefix_copy,
+};
 pub use registry::*;
 pub use repository::*;
+pub use unit_of_work::UnitOfWork;
 pub use snugom_macros::{
     SearchableFilters, SnugomClient, SnugomEntity, snug, snugom_create, snugom_delete,
-    snugom_get_or_create, snugom_update, snugom_upsert,
+    snugom_get_or_create, snugom_run, snugom_update, snugom_upsert,
 };
-pub use search::{SearchQuery, SortOrder};
+pub use search::{SearchQuery, SlowQuery, SlowQueryLog, SortOrder, ViewModel};
 pub use types::{
     DEFAULT_RELATION_LIMIT, MAX_RELATION_LIMIT, RelationData, RelationQueryOptions, RelationState,
     SnugomModel,
@@ -119,34 +189,35 @@ pub use redis::aio::ConnectionManager;
 // Re-export inventory for auto-registration in entity derive macro
 pub use inventory;
 
-/// Delete all keys matching a pattern (for test cleanup).
-///
-/// This performs a SCAN + DEL operation to safely delete keys without blocking Redis.
-pub async fn cleanup_pattern(conn: &mut ConnectionManager, pattern: &str) -> Result<u64, RepoError> {
-    const SCAN_COUNT: usize = 1000;
-    let mut cursor: u64 = 0;
-    let mut total_deleted: u64 = 0;
-
-    loop {
-        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
-            .arg(cursor)
-            .arg("MATCH")
-            .arg(pattern)
-            .arg("COUNT")
-            .arg(SCAN_COUNT)
-            .query_async(conn)
-            .await?;
-
-        if !keys.is_empty() {
-            let deleted: u64 = redis::cmd("DEL").arg(&keys).query_async(conn).await?;
-            total_deleted += deleted;
-        }
+#[cfg(feature = "graphql")]
+pub use async_graphql;
 
-        cursor = next_cursor;
-        if cursor == 0 {
-            break;
-        }
-    }
+#[cfg(feature = "utoipa")]
+pub use utoipa;
+
+/// Emits generated OpenAPI types only when the `utoipa` feature is enabled.
+#[cfg(feature = "utoipa")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_utoipa {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "utoipa"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_utoipa {
+    ($($item:tt)*) => {};
+}
 
-    Ok(total_deleted)
+/// Without the `graphql` feature, `#[snugom(graphql)]` is a compile error rather than a silent no-op.
+#[cfg(not(feature = "graphql"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snugom_graphql {
+    ($($item:tt)*) => {
+        compile_error!("#[snugom(graphql)] requires the `graphql` feature of snugom");
+    };
 }
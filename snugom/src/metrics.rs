@@ -0,0 +1,79 @@
+//! Operation metrics, enabled by the `metrics` feature.
+//!
+//! Metrics are emitted through the [`metrics`](https://docs.rs/metrics) facade, so they reach
+//! whichever recorder the application installs (for Prometheus, `metrics-exporter-prometheus`).
+//! Nothing is recorded until a recorder is installed.
+//!
+//! - [`OPERATIONS_TOTAL`] counts creates, patches, deletes, searches, and relation mutations by
+//!   `collection`, `operation`, and `outcome` (`ok` or `error`).
+//! - [`OPERATION_DURATION_SECONDS`] is a histogram of the same operations by `collection` and
+//!   `operation`.
+//! - [`record_index_stats`] sets the per-`index` gauges from `FT.INFO`; call it periodically or
+//!   from a scrape handler.
+//!
+//! # Example
+//! ```ignore
+//! let handle = PrometheusBuilder::new().install_recorder()?;
+//! snugom::metrics::describe();
+//! // ... serve requests ...
+//! snugom::metrics::record_index_stats(&mut conn, "myapp:guilds:idx").await?;
+//! let body = handle.render();
+//! ```
+
+use std::time::Duration;
+
+use ::metrics::{Unit, counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use redis::aio::ConnectionManager;
+
+use crate::{errors::RepoError, search::index_info};
+
+/// Counter of completed operations.
+pub const OPERATIONS_TOTAL: &str = "snugom_operations_total";
+/// Histogram of operation latency, in seconds.
+pub const OPERATION_DURATION_SECONDS: &str = "snugom_operation_duration_seconds";
+/// Gauge of documents in a search index.
+pub const INDEX_DOCUMENTS: &str = "snugom_index_documents";
+/// Gauge of documents the index failed to ingest.
+pub const INDEX_INDEXING_FAILURES: &str = "snugom_index_indexing_failures";
+/// Gauge of the inverted index size, in bytes.
+pub const INDEX_INVERTED_SIZE_BYTES: &str = "snugom_index_inverted_size_bytes";
+
+/// Register descriptions and units for every snugom metric with the installed recorder.
+pub fn describe() {
+    describe_counter!(OPERATIONS_TOTAL, "Repository and search operations by collection and outcome");
+    describe_histogram!(
+        OPERATION_DURATION_SECONDS,
+        Unit::Seconds,
+        "Latency of repository and search operations"
+    );
+    describe_gauge!(INDEX_DOCUMENTS, "Documents in the search index");
+    describe_gauge!(INDEX_INDEXING_FAILURES, "Documents the search index failed to ingest");
+    describe_gauge!(INDEX_INVERTED_SIZE_BYTES, Unit::Bytes, "Size of the inverted index");
+}
+
+/// Read `FT.INFO` for `index_name` and set its size gauges.
+pub async fn record_index_stats(conn: &mut ConnectionManager, index_name: &str) -> Result<(), RepoError> {
+    let info = index_info(conn, index_name).await?;
+    let index = index_name.to_string();
+    gauge!(INDEX_DOCUMENTS, "index" => index.clone()).set(info.num_docs as f64);
+    gauge!(INDEX_INDEXING_FAILURES, "index" => index.clone()).set(info.indexing_failures as f64);
+    gauge!(INDEX_INVERTED_SIZE_BYTES, "index" => index).set(info.inverted_size_mb * 1024.0 * 1024.0);
+    Ok(())
+}
+
+pub(crate) fn record_operation(collection: &str, operation: &'static str, elapsed: Duration, ok: bool) {
+    let outcome = if ok { "ok" } else { "error" };
+    counter!(
+        OPERATIONS_TOTAL,
+        "collection" => collection.to_string(),
+        "operation" => operation,
+        "outcome" => outcome
+    )
+    .increment(1);
+    histogram!(
+        OPERATION_DURATION_SECONDS,
+        "collection" => collection.to_string(),
+        "operation" => operation
+    )
+    .record(elapsed.as_secs_f64());
+}
@@ -0,0 +1,292 @@
+//! Copying or moving every key under one prefix to another, e.g. `staging` to `perf`.
+
+use std::time::{Duration, Instant};
+
+use redis::aio::ConnectionManager;
+
+use crate::{
+    errors::RepoError,
+    keys::{SegmentOrder, key_scheme, key_separator},
+};
+
+/// Batching and overwrite behaviour of [`copy_prefix_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixCopyOptions {
+    /// `COUNT` hint sent with each `SCAN`
+    pub scan_count: usize,
+    /// Keys dumped and restored per pipelined round trip
+    pub chunk_size: usize,
+    /// Overwrite keys that already exist under the target prefix instead of refusing to copy
+    pub replace: bool,
+    /// Delete each source key once its copy is written, turning the copy into a move
+    pub remove_source: bool,
+}
+
+impl Default for PrefixCopyOptions {
+    fn default() -> Self {
+        Self {
+            scan_count: 1000,
+            chunk_size: 500,
+            replace: false,
+            remove_source: false,
+        }
+    }
+}
+
+/// Outcome of a prefix copy, also passed to the progress callback after each chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixCopyReport {
+    /// Keys written under the target prefix
+    pub copied: u64,
+    /// Source keys deleted, when moving
+    pub removed: u64,
+    pub elapsed: Duration,
+}
+
+/// What [`verify_prefix_copy`] found under both prefixes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixVerification {
+    pub source_keys: u64,
+    pub target_keys: u64,
+    /// Source keys, without the prefix, that have no counterpart under the target prefix
+    pub missing: Vec<String>,
+}
+
+/// Copy all keys under `from` to `to` with the default [`PrefixCopyOptions`].
+///
+/// See [`copy_prefix_with`].
+pub async fn copy_prefix(conn: &mut ConnectionManager, from: &str, to: &str) -> Result<PrefixCopyReport, RepoError> {
+    copy_prefix_with(conn, from, to, PrefixCopyOptions::default(), |_| {}).await
+}
+
+/// Copy all keys under `{from}:` to `{to}:`, keeping their values and time to live.
+///
+/// Keys are found with `SCAN` and copied with `DUMP`/`RESTORE`, so documents, relation sets,
+/// unique-constraint keys, idempotency records, audit streams, and version history all come
+/// across unchanged; none of them embed the prefix. Unless `options.replace` is set, the copy
+/// refuses to start when the target prefix already holds keys. `on_progress` sees the running
+/// totals after every chunk.
+///
+/// Search indexes are not keys: create them for the target prefix afterwards, e.g. with a
+/// client's `ensure_indexes`. Keys written to the source during the copy may be missed.
+///
+/// # Example
+/// ```ignore
+/// let report = snugom::copy_prefix_with(&mut conn, "staging", "perf", PrefixCopyOptions::default(), |progress| {
+///     println!("copied {} keys", progress.copied);
+/// })
+/// .await?;
+/// ```
+pub async fn copy_prefix_with(
+    conn: &mut ConnectionManager,
+    from: &str,
+    to: &str,
+    options: PrefixCopyOptions,
+    mut on_progress: impl FnMut(&PrefixCopyReport),
+) -> Result<PrefixCopyReport, RepoError> {
+    check_prefixes(from, to)?;
+    let started = Instant::now();
+    if !options.replace && any_key(conn, &under(to, "*"), options.scan_count).await? {
+        return Err(RepoError::InvalidRequest {
+            message: format!("prefix `{to}` already holds keys; set replace to overwrite them"),
+        });
+    }
+
+    let source = under(from, "");
+    let chunk_size = options.chunk_size.max(1);
+    let mut report = PrefixCopyReport::default();
+    let mut pending: Vec<String> = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{source}*"))
+            .arg("COUNT")
+            .arg(options.scan_count.max(1))
+            .query_async(conn)
+            .await?;
+        pending.extend(keys);
+        cursor = next_cursor;
+
+        while pending.len() >= chunk_size || (cursor == 0 && !pending.is_empty()) {
+            let chunk: Vec<String> = pending.drain(..chunk_size.min(pending.len())).collect();
+            copy_chunk(conn, &chunk, &source, to, &options, &mut report).await?;
+            report.elapsed = started.elapsed();
+            on_progress(&report);
+        }
+        if cursor == 0 {
+            break;
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// Check that every key under `{from}:` has a counterpart under `{to}:`.
+///
+/// After a move the source is expected to be empty and `target_keys` to match the copy's count.
+pub async fn verify_prefix_copy(
+    conn: &mut ConnectionManager,
+    from: &str,
+    to: &str,
+) -> Result<PrefixVerification, RepoError> {
+    check_prefixes(from, to)?;
+    let source = under(from, "");
+    let mut verification = PrefixVerification::default();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{source}*"))
+            .arg("COUNT")
+            .arg(1000)
+            .query_async(conn)
+            .await?;
+        let names: Vec<&str> = keys.iter().filter_map(|key| key.strip_prefix(&source)).collect();
+        if !names.is_empty() {
+            let mut pipe = redis::pipe();
+            for name in &names {
+                pipe.cmd("EXISTS").arg(under(to, name));
+            }
+            let found: Vec<bool> = pipe.query_async(conn).await?;
+            verification.source_keys += names.len() as u64;
+            verification
+                .missing
+                .extend(names.iter().zip(found).filter(|(_, found)| !found).map(|(name, _)| name.to_string()));
+        }
+        cursor = next_cursor;
+        if cursor == 0 {
+            break;
+        }
+    }
+    verification.target_keys = count_keys(conn, &under(to, "*")).await?;
+    Ok(verification)
+}
+
+/// `rest` under `prefix`, joined by the key separator.
+fn under(prefix: &str, rest: &str) -> String {
+    format!("{prefix}{}{rest}", key_separator())
+}
+
+/// Prefixes that overlap would have the copy scan its own output. Keys led by their service
+/// do not share a leading prefix, so there is nothing to scan for.
+fn check_prefixes(from: &str, to: &str) -> Result<(), RepoError> {
+    if key_scheme().order() != SegmentOrder::PrefixFirst {
+        return Err(RepoError::InvalidRequest {
+            message: "prefix copies need keys led by their prefix; the key scheme puts the service first".to_string(),
+        });
+    }
+    let nested = |outer: &str, inner: &str| inner.starts_with(&under(outer, ""));
+    if from.is_empty() || to.is_empty() || from == to || nested(from, to) || nested(to, from) {
+        return Err(RepoError::InvalidRequest {
+            message: format!(
+                "cannot copy keys from prefix `{from}` to `{to}`; prefixes must be distinct and not nested"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// `DUMP` a chunk of source keys and `RESTORE` them under the target prefix, skipping keys
+/// deleted since they were scanned.
+async fn copy_chunk(
+    conn: &mut ConnectionManager,
+    keys: &[String],
+    source: &str,
+    to: &str,
+    options: &PrefixCopyOptions,
+    report: &mut PrefixCopyReport,
+) -> Result<(), RepoError> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("DUMP").arg(key).cmd("PTTL").arg(key);
+    }
+    let dumps: Vec<(Option<Vec<u8>>, i64)> = pipe.query_async(conn).await?;
+
+    let mut copied = Vec::with_capacity(keys.len());
+    let mut pipe = redis::pipe();
+    for (key, (dump, ttl)) in keys.iter().zip(dumps) {
+        let (Some(name), Some(dump)) = (key.strip_prefix(source), dump) else {
+            continue;
+        };
+        // -1 is a key without expiry, -2 one that expired in between
+        if ttl == -2 {
+            continue;
+        }
+        pipe.cmd("RESTORE").arg(under(to, name)).arg(ttl.max(0)).arg(dump);
+        if options.replace {
+            pipe.arg("REPLACE");
+        }
+        pipe.ignore();
+        copied.push(key.as_str());
+    }
+    if copied.is_empty() {
+        return Ok(());
+    }
+    pipe.query_async::<()>(conn).await?;
+    report.copied += copied.len() as u64;
+
+    if options.remove_source {
+        report.removed += redis::cmd("UNLINK").arg(&copied).query_async::<u64>(conn).await?;
+    }
+    Ok(())
+}
+
+async fn any_key(conn: &mut ConnectionManager, pattern: &str, scan_count: usize) -> Result<bool, RepoError> {
+    let mut cursor: u64 = 0;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(scan_count.max(1))
+            .query_async(conn)
+            .await?;
+        if !keys.is_empty() {
+            return Ok(true);
+        }
+        cursor = next_cursor;
+        if cursor == 0 {
+            return Ok(false);
+        }
+    }
+}
+
+async fn count_keys(conn: &mut ConnectionManager, pattern: &str) -> Result<u64, RepoError> {
+    let mut count = 0;
+    let mut cursor: u64 = 0;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(1000)
+            .query_async(conn)
+            .await?;
+        count += keys.len() as u64;
+        cursor = next_cursor;
+        if cursor == 0 {
+            return Ok(count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_overlapping_prefixes() {
+        assert!(check_prefixes("staging", "perf").is_ok());
+        assert!(check_prefixes("staging", "staging-2").is_ok());
+        assert!(check_prefixes("staging", "staging").is_err());
+        assert!(check_prefixes("staging", "staging:perf").is_err());
+        assert!(check_prefixes("staging:perf", "staging").is_err());
+        assert!(check_prefixes("", "perf").is_err());
+    }
+}
@@ -1,5 +1,9 @@
-use crate::types::EntityDescriptor;
-use std::collections::HashMap;
+use crate::errors::DescriptorConflict;
+use crate::search::{IndexDefinition, IndexFieldType};
+use crate::types::{CascadePolicy, EntityDescriptor, FieldType, RelationKind, StorageMode, ValidationRule};
+use crate::validators::compiled_regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, hash_map::Entry};
 use std::sync::{OnceLock, RwLock};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -14,12 +18,92 @@ fn registry() -> &'static RwLock<HashMap<DescriptorKey, EntityDescriptor>> {
     REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
+/// Register a descriptor, panicking if a different one is already registered for its service and
+/// collection.
+///
+/// Called by `#[derive(SnugomEntity)]` the first time an entity is used. Two entities claiming
+/// the same collection would otherwise overwrite each other here, and cascades would follow
+/// whichever registered last.
+///
+/// The descriptor's `validate(regex)` patterns are compiled into the shared cache first; one that
+/// doesn't compile panics here instead of being skipped by every write's validation.
 pub fn register_descriptor(descriptor: &EntityDescriptor) {
+    for field in &descriptor.fields {
+        for validation in &field.validations {
+            if let ValidationRule::Regex { pattern } = &validation.rule
+                && let Err(err) = compiled_regex(pattern)
+            {
+                panic!(
+                    "invalid regex for `{}:{}` field `{}`: {err}",
+                    descriptor.service, descriptor.collection, field.name
+                );
+            }
+        }
+    }
+    if let Err(conflict) = try_register_descriptor(descriptor) {
+        panic!("{conflict}");
+    }
+}
+
+/// Register a descriptor, or report how it differs from the one already registered for its
+/// service and collection. Registering an equivalent descriptor again is not a conflict.
+pub fn try_register_descriptor(descriptor: &EntityDescriptor) -> Result<(), DescriptorConflict> {
     let key = DescriptorKey {
         service: descriptor.service.clone(),
         collection: descriptor.collection.clone(),
     };
-    registry().write().unwrap().insert(key, descriptor.clone());
+    match registry().write().unwrap().entry(key) {
+        Entry::Occupied(mut entry) => {
+            let differences = descriptor_differences(entry.get(), descriptor);
+            if !differences.is_empty() {
+                return Err(DescriptorConflict {
+                    service: descriptor.service.clone(),
+                    collection: descriptor.collection.clone(),
+                    registered_version: entry.get().version,
+                    conflicting_version: descriptor.version,
+                    differences,
+                });
+            }
+            entry.insert(descriptor.clone());
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(descriptor.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Lines present in only one of the two descriptors' shapes, `-` for `registered` and `+` for
+/// `new`. Validators and hooks are functions and can't be compared.
+pub(crate) fn descriptor_differences(registered: &EntityDescriptor, new: &EntityDescriptor) -> Vec<String> {
+    let (registered, new) = (descriptor_shape(registered), descriptor_shape(new));
+    let removed = registered.iter().filter(|line| !new.contains(line)).map(|line| format!("- {line}"));
+    let added = new.iter().filter(|line| !registered.contains(line)).map(|line| format!("+ {line}"));
+    removed.chain(added).collect()
+}
+
+fn descriptor_shape(descriptor: &EntityDescriptor) -> Vec<String> {
+    let mut shape = vec![
+        format!("schema {}", descriptor.version),
+        format!("id {:?}", descriptor.id_field),
+        format!("storage {:?}", descriptor.storage),
+    ];
+    shape.extend(descriptor.fields.iter().map(|field| {
+        let optional = if field.optional { "?" } else { "" };
+        format!("field {}: {:?}{optional}", field.name, field.field_type)
+    }));
+    shape.extend(descriptor.relations.iter().map(|relation| {
+        let service = relation.target_service.as_deref().unwrap_or(&descriptor.service);
+        format!(
+            "relation {} -> {service}:{} ({:?}, cascade {:?}, foreign key {:?})",
+            relation.alias, relation.target, relation.kind, relation.cascade, relation.foreign_key
+        )
+    }));
+    shape.extend(descriptor.unique_constraints.iter().map(|constraint| {
+        let case = if constraint.case_insensitive { " case-insensitive" } else { "" };
+        format!("unique {:?}{case}", constraint.fields)
+    }));
+    shape
 }
 
 pub fn get_descriptor(service: &str, collection: &str) -> Option<EntityDescriptor> {
@@ -73,3 +157,346 @@ pub fn find_incoming_relations(target_service: &str, target_collection: &str) ->
 
     incoming
 }
+
+/// Every known entity, as returned by [`describe`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaDescription {
+    /// Sorted by service, then collection
+    pub entities: Vec<EntityDescription>,
+    /// Every relation between entities, with both ends resolved to `service:collection`
+    pub relation_graph: Vec<RelationEdge>,
+}
+
+/// An [`EntityDescriptor`] and its index, flattened for serialization.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityDescription {
+    pub service: String,
+    pub collection: String,
+    /// Rust type name; `None` for entities registered at runtime without the derive
+    pub type_name: Option<String>,
+    pub version: u32,
+    pub id_field: Option<String>,
+    pub description: Option<String>,
+    pub storage: StorageMode,
+    pub keep_versions: Option<usize>,
+    pub fields: Vec<FieldDescription>,
+    pub unique_constraints: Vec<UniqueConstraintDescription>,
+    pub relations: Vec<RelationDescription>,
+    pub through_relations: Vec<ThroughRelationDescription>,
+    /// `None` for entities without indexed fields
+    pub index: Option<IndexDescription>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDescription {
+    pub name: String,
+    pub field_type: FieldType,
+    pub optional: bool,
+    pub is_id: bool,
+    pub immutable: bool,
+    pub redacted: bool,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UniqueConstraintDescription {
+    pub fields: Vec<String>,
+    pub case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationDescription {
+    pub alias: String,
+    /// Service of the target, which is this entity's own unless `target_service` was given
+    pub target_service: String,
+    pub target_collection: String,
+    pub kind: RelationKind,
+    pub cascade: CascadePolicy,
+    pub foreign_key: Option<String>,
+    pub counter: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThroughRelationDescription {
+    pub alias: String,
+    pub through: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexDescription {
+    pub name: String,
+    pub prefixes: Vec<String>,
+    pub filter: Option<String>,
+    pub language: Option<String>,
+    pub fields: Vec<IndexFieldDescription>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexFieldDescription {
+    pub name: String,
+    pub path: String,
+    pub field_type: IndexFieldType,
+    pub sortable: bool,
+    pub index_missing: bool,
+}
+
+/// One edge of the relation graph, e.g. `shop:orders` -`items`-> `shop:order_items`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationEdge {
+    pub from: String,
+    pub alias: String,
+    pub to: String,
+    pub kind: RelationKind,
+    pub cascade: CascadePolicy,
+}
+
+/// Describe every entity this process knows of, for admin UIs and diagnostics endpoints.
+///
+/// Covers entities registered at runtime as well as every `#[derive(SnugomEntity)]` linked into
+/// the binary, whether or not a repository has been created for it yet. `prefix` is the client
+/// key prefix the index names and key prefixes are built under.
+///
+/// # Example
+/// ```ignore
+/// let schema = snugom::registry::describe(client.prefix());
+/// Json(serde_json::to_value(&schema)?)
+/// ```
+pub fn describe(prefix: &str) -> SchemaDescription {
+    let mut entities: BTreeMap<(String, String), EntityDescription> = registry()
+        .read()
+        .unwrap()
+        .values()
+        .map(|descriptor| {
+            let key = (descriptor.service.clone(), descriptor.collection.clone());
+            (key, describe_entity(descriptor))
+        })
+        .collect();
+    for entry in crate::client::registered_entities() {
+        let key = (entry.service_name.to_string(), entry.collection_name.to_string());
+        let index = entry.index_definition_fn.map(|index_definition| describe_index(&index_definition(prefix)));
+        let description = entities
+            .remove(&key)
+            .unwrap_or_else(|| describe_entity(&(entry.descriptor_fn)()));
+        entities.insert(
+            key,
+            EntityDescription {
+                type_name: Some(entry.type_name.to_string()),
+                index,
+                ..description
+            },
+        );
+    }
+
+    let entities: Vec<EntityDescription> = entities.into_values().collect();
+    let relation_graph = entities
+        .iter()
+        .flat_map(|entity| {
+            entity.relations.iter().map(move |relation| RelationEdge {
+                from: format!("{}:{}", entity.service, entity.collection),
+                alias: relation.alias.clone(),
+                to: format!("{}:{}", relation.target_service, relation.target_collection),
+                kind: relation.kind,
+                cascade: relation.cascade,
+            })
+        })
+        .collect();
+    SchemaDescription {
+        entities,
+        relation_graph,
+    }
+}
+
+fn describe_entity(descriptor: &EntityDescriptor) -> EntityDescription {
+    EntityDescription {
+        service: descriptor.service.clone(),
+        collection: descriptor.collection.clone(),
+        type_name: None,
+        version: descriptor.version,
+        id_field: descriptor.id_field.clone(),
+        description: descriptor.description.clone(),
+        storage: descriptor.storage,
+        keep_versions: descriptor.keep_versions,
+        fields: descriptor
+            .fields
+            .iter()
+            .map(|field| FieldDescription {
+                name: field.name.clone(),
+                field_type: field.field_type,
+                optional: field.optional,
+                is_id: field.is_id,
+                immutable: field.immutable,
+                redacted: field.redacted,
+                description: field.description.clone(),
+            })
+            .collect(),
+        unique_constraints: descriptor
+            .unique_constraints
+            .iter()
+            .map(|constraint| UniqueConstraintDescription {
+                fields: constraint.fields.clone(),
+                case_insensitive: constraint.case_insensitive,
+            })
+            .collect(),
+        relations: descriptor
+            .relations
+            .iter()
+            .map(|relation| RelationDescription {
+                alias: relation.alias.clone(),
+                target_service: relation.target_service.clone().unwrap_or_else(|| descriptor.service.clone()),
+                target_collection: relation.target.clone(),
+                kind: relation.kind,
+                cascade: relation.cascade,
+                foreign_key: relation.foreign_key.clone(),
+                counter: relation.counter.clone(),
+            })
+            .collect(),
+        through_relations: descriptor
+            .through_relations
+            .iter()
+            .map(|through| ThroughRelationDescription {
+                alias: through.alias.clone(),
+                through: through.through.clone(),
+                source: through.source.clone(),
+            })
+            .collect(),
+        index: None,
+    }
+}
+
+fn describe_index(definition: &IndexDefinition) -> IndexDescription {
+    IndexDescription {
+        name: definition.name.clone(),
+        prefixes: definition.prefixes.clone(),
+        filter: definition.filter.clone(),
+        language: definition.language.clone(),
+        fields: definition
+            .schema
+            .iter()
+            .map(|field| IndexFieldDescription {
+                name: field.field_name.to_string(),
+                path: field.path.to_string(),
+                field_type: field.field_type,
+                sortable: field.sortable,
+                index_missing: field.index_missing,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::{FieldDescriptor, RelationDescriptor, ValidationDescriptor, ValidationScope};
+
+    #[test]
+    fn conflicting_registrations_name_both_versions() {
+        let field = |name: &str, field_type| FieldDescriptor {
+            name: name.to_string(),
+            field_type,
+            ..FieldDescriptor::default()
+        };
+        let registered = EntityDescriptor {
+            service: "registry_tests".to_string(),
+            collection: "orders".to_string(),
+            version: 1,
+            fields: vec![field("id", FieldType::String), field("total", FieldType::Number)],
+            ..EntityDescriptor::default()
+        };
+        try_register_descriptor(&registered).unwrap();
+        try_register_descriptor(&registered.clone()).unwrap();
+
+        let mut newer = registered.clone();
+        newer.version = 2;
+        newer.fields[1] = field("total", FieldType::String);
+        newer.relations.push(RelationDescriptor {
+            alias: "items".to_string(),
+            target: "items".to_string(),
+            target_service: None,
+            kind: RelationKind::HasMany,
+            cascade: CascadePolicy::Delete,
+            foreign_key: None,
+            counter: None,
+        });
+        let conflict = try_register_descriptor(&newer).unwrap_err();
+        assert_eq!((conflict.registered_version, conflict.conflicting_version), (1, 2));
+        assert!(conflict.differences.contains(&"- field total: Number".to_string()));
+        assert!(conflict.differences.contains(&"+ field total: String".to_string()));
+        assert!(conflict.to_string().starts_with("conflicting descriptors for `registry_tests:orders`"));
+        assert_eq!(get_descriptor("registry_tests", "orders").unwrap().version, 1);
+    }
+
+    #[test]
+    fn registration_compiles_patterns() {
+        let with_pattern = |collection: &str, pattern: &str| EntityDescriptor {
+            service: "registry_tests".to_string(),
+            collection: collection.to_string(),
+            fields: vec![FieldDescriptor {
+                name: "code".to_string(),
+                validations: vec![ValidationDescriptor {
+                    scope: ValidationScope::Field,
+                    rule: ValidationRule::Regex {
+                        pattern: pattern.to_string(),
+                    },
+                }],
+                ..FieldDescriptor::default()
+            }],
+            ..EntityDescriptor::default()
+        };
+        register_descriptor(&with_pattern("codes", "^[A-Z]{3}-registered$"));
+        assert!(crate::validators::matches_regex("^[A-Z]{3}-registered$", "ABC-registered"));
+
+        let invalid = with_pattern("broken_codes", "[A-Z");
+        let panic = std::panic::catch_unwind(|| register_descriptor(&invalid)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("invalid regex for `registry_tests:broken_codes` field `code`"));
+        assert!(get_descriptor("registry_tests", "broken_codes").is_none());
+    }
+
+    #[test]
+    fn linked_entities_agree_on_shared_collections() {
+        let entries: Vec<_> = crate::client::registered_entities().collect();
+        for (index, entry) in entries.iter().enumerate() {
+            for other in &entries[index + 1..] {
+                if (entry.service_name, entry.collection_name) == (other.service_name, other.collection_name) {
+                    let differences = descriptor_differences(&(entry.descriptor_fn)(), &(other.descriptor_fn)());
+                    assert!(
+                        differences.is_empty(),
+                        "{} and {} disagree on {}:{}: {differences:?}",
+                        entry.type_name,
+                        other.type_name,
+                        entry.service_name,
+                        entry.collection_name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn describes_linked_entities_and_their_relations() {
+        let schema = describe("app");
+        let order = schema
+            .entities
+            .iter()
+            .find(|entity| entity.service == "bench" && entity.collection == "orders")
+            .expect("derived entities are described before any repository is created");
+        assert_eq!(order.type_name.as_deref(), Some("BenchOrder"));
+        assert_eq!(order.id_field.as_deref(), Some("id"));
+        let index = order.index.as_ref().expect("bench orders are indexed");
+        assert_eq!(index.prefixes, vec!["app:bench:orders:".to_string()]);
+        assert!(index.fields.iter().any(|field| field.name == "total_cents" && field.sortable));
+
+        assert!(schema.relation_graph.iter().any(|edge| {
+            edge.from == "examples:rq_authors" && edge.alias == "posts" && edge.to == "examples:rq_posts"
+        }));
+
+        let value = serde_json::to_value(order).unwrap();
+        assert_eq!(value["storage"], "json");
+        let fields = value["fields"].as_array().unwrap();
+        let shipped_at = fields.iter().find(|field| field["name"] == "shipped_at").unwrap();
+        assert_eq!(shipped_at["field_type"], json!("datetime"));
+    }
+}
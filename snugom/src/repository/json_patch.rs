@@ -0,0 +1,343 @@
+//! Translating standard JSON patch bodies, RFC 6902 JSON Patch and RFC 7386 JSON Merge Patch,
+//! into [`MutationPatch`] operations, so HTTP handlers can accept them without mapping by hand.
+
+use std::marker::PhantomData;
+
+use serde_json::{Map, Value};
+
+use super::{MutationPatch, PatchOpKind, PatchOperation, UpdatePatchBuilder, datetime_millis};
+use crate::{
+    errors::{RepoError, ValidationError, ValidationResult},
+    types::{DatetimeMirrorValue, EntityDescriptor, EntityMetadata, FieldDescriptor, FieldType},
+};
+
+impl MutationPatch {
+    /// Translate an RFC 6902 JSON Patch document (an array of operations) for `entity_id`.
+    ///
+    /// Paths are JSON Pointers into the stored document, and their first segment must name a
+    /// field of `descriptor`. Supported operations:
+    ///
+    /// - `add` / `replace` of a top-level field assigns it; `add` to `/field/-` appends to an array
+    /// - `remove` of a top-level field deletes it
+    /// - `test` of `/metadata/version` becomes the patch's expected version
+    ///
+    /// `move`, `copy`, other `test`s, and paths below a field (array indexes, nested keys) are
+    /// rejected with `RepoError::InvalidRequest`; use [`MutationPatch::from_merge_patch`] to change
+    /// nested keys. Unknown fields fail validation. Unlike RFC 6902, `replace` doesn't check that
+    /// the field is already set.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let body = json!([
+    ///     { "op": "test", "path": "/metadata/version", "value": 3 },
+    ///     { "op": "replace", "path": "/name", "value": "Renamed" },
+    ///     { "op": "add", "path": "/tags/-", "value": "featured" },
+    /// ]);
+    /// let patch = MutationPatch::from_json_patch(&Guild::entity_descriptor(), &id, &body)?;
+    /// ```
+    pub fn from_json_patch(
+        descriptor: &EntityDescriptor,
+        entity_id: impl Into<String>,
+        patch: &Value,
+    ) -> Result<Self, RepoError> {
+        let Value::Array(steps) = patch else {
+            return Err(invalid("a JSON Patch document must be an array of operations".to_string()));
+        };
+        let mut result = empty_patch(entity_id.into());
+        for (index, step) in steps.iter().enumerate() {
+            let fail = |message: &str| invalid(format!("JSON Patch operation {index}: {message}"));
+            let op = step.get("op").and_then(Value::as_str).ok_or_else(|| fail("`op` must be a string"))?;
+            let pointer = step.get("path").and_then(Value::as_str).ok_or_else(|| fail("`path` must be a string"))?;
+            let segments =
+                pointer_segments(pointer).ok_or_else(|| fail("`path` must be a JSON Pointer below the root"))?;
+            let value = step.get("value");
+
+            if op == "test" {
+                let version = match (segments.as_slice(), value) {
+                    ([metadata, version], Some(value)) if metadata == "metadata" && version == "version" => {
+                        value.as_u64().ok_or_else(|| fail("`/metadata/version` must be tested against a number"))?
+                    }
+                    _ => return Err(fail("only `/metadata/version` can be tested")),
+                };
+                result.expected_version = Some(version);
+                continue;
+            }
+
+            let field = descriptor_field(descriptor, &segments[0])?;
+            let kind = match (op, &segments[1..], value) {
+                ("add" | "replace", [], Some(value)) => PatchOpKind::Assign(value.clone()),
+                ("add", [end], Some(value)) if end == "-" => PatchOpKind::ArrayAppend(vec![value.clone()]),
+                ("remove", [], _) => PatchOpKind::Delete,
+                ("add" | "replace", _, None) => return Err(fail("`value` is required")),
+                ("add" | "replace" | "remove", _, _) => {
+                    return Err(fail("only top-level fields can be changed, or appended to with `/-`"));
+                }
+                ("move" | "copy", _, _) => return Err(fail("`move` and `copy` are not supported")),
+                _ => return Err(fail(&format!("unknown operation `{op}`"))),
+            };
+            result.operations.push(operation(field, kind));
+        }
+        Ok(result)
+    }
+
+    /// Translate an RFC 7386 JSON Merge Patch (an object of changes) for `entity_id`.
+    ///
+    /// Each key must name a field of `descriptor`: `null` deletes the field, an object merges into
+    /// an object field (nested `null`s delete nested keys), and anything else assigns it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let body = json!({ "name": "Renamed", "settings": { "theme": "dark", "beta": null } });
+    /// let patch = MutationPatch::from_merge_patch(&Guild::entity_descriptor(), &id, &body)?;
+    /// ```
+    pub fn from_merge_patch(
+        descriptor: &EntityDescriptor,
+        entity_id: impl Into<String>,
+        patch: &Value,
+    ) -> Result<Self, RepoError> {
+        let Value::Object(changes) = patch else {
+            return Err(invalid("a JSON Merge Patch must be an object".to_string()));
+        };
+        let mut result = empty_patch(entity_id.into());
+        for (name, value) in changes {
+            let field = descriptor_field(descriptor, name)?;
+            let kind = match value {
+                Value::Null => PatchOpKind::Delete,
+                Value::Object(_) if field.field_type == FieldType::Object && field.compression.is_none() => {
+                    PatchOpKind::Merge(value.clone())
+                }
+                // A merge patch replaces anything that isn't an object, leaving out its nulls
+                _ => PatchOpKind::Assign(without_nulls(value)),
+            };
+            result.operations.push(operation(field, kind));
+        }
+        Ok(result)
+    }
+}
+
+/// A [`MutationPatch`] for entity `T` that was built directly, e.g. from a JSON Patch body,
+/// accepted anywhere a derived patch builder is.
+///
+/// # Example
+/// ```ignore
+/// let patch = PreparedPatch::<Guild>::json_patch(&id, &body)?;
+/// client.guilds().update(patch).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreparedPatch<T> {
+    patch: MutationPatch,
+    entity: PhantomData<fn() -> T>,
+}
+
+impl<T: EntityMetadata> PreparedPatch<T> {
+    pub fn new(patch: MutationPatch) -> Self {
+        Self {
+            patch,
+            entity: PhantomData,
+        }
+    }
+
+    /// [`MutationPatch::from_json_patch`] against `T`'s descriptor.
+    pub fn json_patch(entity_id: impl Into<String>, patch: &Value) -> Result<Self, RepoError> {
+        MutationPatch::from_json_patch(&T::entity_descriptor(), entity_id, patch).map(Self::new)
+    }
+
+    /// [`MutationPatch::from_merge_patch`] against `T`'s descriptor.
+    pub fn merge_patch(entity_id: impl Into<String>, patch: &Value) -> Result<Self, RepoError> {
+        MutationPatch::from_merge_patch(&T::entity_descriptor(), entity_id, patch).map(Self::new)
+    }
+}
+
+impl<T: EntityMetadata> UpdatePatchBuilder for PreparedPatch<T> {
+    type Entity = T;
+
+    fn into_patch(self) -> ValidationResult<MutationPatch> {
+        Ok(self.patch)
+    }
+}
+
+fn empty_patch(entity_id: String) -> MutationPatch {
+    MutationPatch {
+        entity_id,
+        expected_version: None,
+        operations: Vec::new(),
+        relations: Vec::new(),
+        nested: Vec::new(),
+        idempotency_key: None,
+        idempotency_ttl: None,
+        precondition: None,
+    }
+}
+
+fn invalid(message: String) -> RepoError {
+    RepoError::InvalidRequest { message }
+}
+
+fn descriptor_field<'a>(descriptor: &'a EntityDescriptor, name: &str) -> Result<&'a FieldDescriptor, RepoError> {
+    descriptor.fields.iter().find(|field| field.name == name).ok_or_else(|| {
+        RepoError::Validation(ValidationError::single(
+            name,
+            "patch.unknown_field",
+            format!("field `{name}` is not defined on entity"),
+        ))
+    })
+}
+
+/// An operation on a top-level field, keeping its datetime mirror in step.
+fn operation(field: &FieldDescriptor, kind: PatchOpKind) -> PatchOperation {
+    let mirror = field.datetime_mirror.as_ref().and_then(|mirror| {
+        let millis = match &kind {
+            PatchOpKind::Assign(value) => datetime_millis(value),
+            PatchOpKind::Delete => None,
+            _ => return None,
+        };
+        Some(DatetimeMirrorValue::new(&field.name, mirror, millis))
+    });
+    PatchOperation {
+        path: format!("$.{}", field.name),
+        kind,
+        mirror,
+    }
+}
+
+/// Unescaped segments of a JSON Pointer; `None` for the root or a pointer without a leading `/`.
+fn pointer_segments(pointer: &str) -> Option<Vec<String>> {
+    let rest = pointer.strip_prefix('/')?;
+    Some(rest.split('/').map(|segment| segment.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect::<Map<String, Value>>(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn descriptor() -> EntityDescriptor {
+        let field = |name: &str, field_type| FieldDescriptor {
+            name: name.to_string(),
+            field_type,
+            ..Default::default()
+        };
+        EntityDescriptor {
+            fields: vec![
+                field("name", FieldType::String),
+                field("tags", FieldType::Array),
+                field("settings", FieldType::Object),
+                FieldDescriptor {
+                    datetime_mirror: Some("closes_at_ts".to_string()),
+                    ..field("closes_at", FieldType::DateTime)
+                },
+                field("a/b", FieldType::String),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn summary(patch: &MutationPatch) -> Vec<(String, String)> {
+        patch
+            .operations
+            .iter()
+            .map(|op| {
+                let kind = match &op.kind {
+                    PatchOpKind::Assign(value) => format!("assign {value}"),
+                    PatchOpKind::Merge(value) => format!("merge {value}"),
+                    PatchOpKind::Delete => "delete".to_string(),
+                    PatchOpKind::ArrayAppend(values) => format!("append {}", Value::from(values.clone())),
+                    other => format!("{other:?}"),
+                };
+                (op.path.clone(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn translates_json_patch_operations() {
+        let body = json!([
+            { "op": "test", "path": "/metadata/version", "value": 3 },
+            { "op": "replace", "path": "/name", "value": "Renamed" },
+            { "op": "add", "path": "/tags/-", "value": "featured" },
+            { "op": "add", "path": "/closes_at", "value": "2024-01-01T00:00:00Z" },
+            { "op": "remove", "path": "/a~1b" },
+        ]);
+        let patch = MutationPatch::from_json_patch(&descriptor(), "g1", &body).unwrap();
+        assert_eq!(patch.entity_id, "g1");
+        assert_eq!(patch.expected_version, Some(3));
+        assert_eq!(
+            summary(&patch),
+            [
+                ("$.name".to_string(), "assign \"Renamed\"".to_string()),
+                ("$.tags".to_string(), "append [\"featured\"]".to_string()),
+                ("$.closes_at".to_string(), "assign \"2024-01-01T00:00:00Z\"".to_string()),
+                ("$.a/b".to_string(), "delete".to_string()),
+            ]
+        );
+        let mirror = patch.operations[2].mirror.as_ref().unwrap();
+        assert_eq!((mirror.mirror_field.as_str(), mirror.value), ("closes_at_ts", Some(1_704_067_200_000)));
+    }
+
+    #[test]
+    fn rejects_unsupported_json_patches() {
+        let rejected = |body: Value| MutationPatch::from_json_patch(&descriptor(), "g1", &body).unwrap_err();
+        assert!(matches!(rejected(json!({ "op": "add" })), RepoError::InvalidRequest { .. }));
+        assert!(matches!(
+            rejected(json!([{ "op": "move", "from": "/name", "path": "/a~1b" }])),
+            RepoError::InvalidRequest { .. }
+        ));
+        assert!(matches!(
+            rejected(json!([{ "op": "remove", "path": "/tags/0" }])),
+            RepoError::InvalidRequest { .. }
+        ));
+        assert!(matches!(
+            rejected(json!([{ "op": "test", "path": "/name", "value": "x" }])),
+            RepoError::InvalidRequest { .. }
+        ));
+        assert!(matches!(rejected(json!([{ "op": "replace", "path": "" }])), RepoError::InvalidRequest { .. }));
+        assert!(matches!(
+            rejected(json!([{ "op": "replace", "path": "/missing", "value": 1 }])),
+            RepoError::Validation(err) if err.issues[0].code == "patch.unknown_field"
+        ));
+    }
+
+    #[test]
+    fn translates_merge_patches() {
+        let body = json!({
+            "name": "Renamed",
+            "settings": { "beta": null, "theme": "dark" },
+            "tags": null,
+            "closes_at": null,
+        });
+        let patch = MutationPatch::from_merge_patch(&descriptor(), "g1", &body).unwrap();
+        let mut operations = summary(&patch);
+        operations.sort();
+        assert_eq!(
+            operations,
+            [
+                ("$.closes_at".to_string(), "delete".to_string()),
+                ("$.name".to_string(), "assign \"Renamed\"".to_string()),
+                ("$.settings".to_string(), "merge {\"beta\":null,\"theme\":\"dark\"}".to_string()),
+                ("$.tags".to_string(), "delete".to_string()),
+            ]
+        );
+        let cleared = patch.operations.iter().find(|op| op.path == "$.closes_at").unwrap();
+        assert_eq!(cleared.mirror.as_ref().map(|mirror| mirror.value), Some(None));
+
+        let body = json!({ "name": { "a": null, "b": 1 } });
+        let patch = MutationPatch::from_merge_patch(&descriptor(), "g1", &body).unwrap();
+        assert_eq!(summary(&patch), [("$.name".to_string(), "assign {\"b\":1}".to_string())]);
+        assert!(MutationPatch::from_merge_patch(&descriptor(), "g1", &json!({ "metadata": {} })).is_err());
+        assert!(MutationPatch::from_merge_patch(&descriptor(), "g1", &json!([])).is_err());
+    }
+}
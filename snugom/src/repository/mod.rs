@@ -1,33 +1,62 @@
-use std::{borrow::Cow, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    future::Future,
+    marker::PhantomData,
+    time::Instant,
+};
 
 const MAX_CASCADE_DEPTH: usize = 8;
+/// How many levels a relation from a collection to itself (replies, parent categories) is expanded
+/// during cascade deletes. Descendants below this depth keep their documents.
+const MAX_SELF_CASCADE_DEPTH: usize = 8;
+/// Creates tried for a `slug(suffix)` field: the slug as generated, then `-2` through `-20`.
+const MAX_SLUG_ATTEMPTS: u32 = 20;
 
 use crate::{
+    audit::{self, AuditAction, AuditSettings},
     errors::{RepoError, ValidationError, ValidationIssue, ValidationResult},
-    keys::KeyContext,
+    interceptor::{self, SharedInterceptor},
+    keys::{KeyContext, KeyOverrides},
     registry,
     runtime::{
         MutationExecutor, RedisExecutor,
         commands::{
-            CascadeDirective, CascadeRelationSpec, DeleteCascadeRelation, GetOrCreateCommand, MutationCommand,
-            MutationPlan, PatchOperationPayload, PatchOperationType, RelationMutation, UniqueConstraintCheck,
+            CascadeDirective, CascadeRelationSpec, DeleteCascadeRelation, GetOrCreateCommand, HistoryRecord, LengthBounds, MutationCommand,
+            MutationPlan, ParentRelationDetach, PatchOperationPayload, Precondition, PatchOperationType, RelationCounter, RelationEdge, RelationMutation,
+            UniqueConstraintCheck,
             UniqueConstraintDefinition, UpsertCommand, build_entity_delete, build_entity_mutation,
-            build_entity_patch, build_unique_constraint_checks,
+            array_elements_json, build_entity_patch, build_unique_constraint_checks, increment_value,
         },
     },
-    search::{self, SearchEntity, SearchParams, SearchQuery, SearchResult},
+    search::{
+        self, AggOp, BucketCount, DistinctValue, FilterCondition, IndexDefinition, IndexInfo, QueryExplanation,
+        SearchDefaults, SearchEntity, SearchParams, SearchProfile, SearchQuery, SearchResult, SlowQueryLog, TimeBucket,
+        ViewModel,
+    },
+    storage,
+    telemetry,
+    unit_of_work::StagedWrite,
     types::{
-        SnugomModel, CascadePolicy, DatetimeMirrorValue, EntityDescriptor, EntityMetadata, FieldDescriptor,
-        FieldType, RelationKind, ValidationRule, ValidationScope,
+        SnugomModel, BeforeHookFn, CascadePolicy, DatetimeMirrorValue, EntityDescriptor, EntityMetadata,
+        FieldDescriptor, FieldType, RelationData, RelationDescriptor, RelationKind, RelationQueryOptions, StorageMode,
+        ValidationRule, ValidationScope,
+    },
+    validators::{
+        is_valid_email, is_valid_hostname, is_valid_ip_addr, is_valid_phone, is_valid_slug, is_valid_url,
+        is_valid_uuid, matches_regex,
     },
-    validators::{is_valid_email, is_valid_url, is_valid_uuid},
 };
 use chrono::Utc;
+use futures_util::stream::{self, Stream, TryStreamExt};
 use redis::{aio::ConnectionManager, cmd};
-use regex::Regex;
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json::{Map, Number, Value};
 
+mod json_patch;
+
+pub use json_patch::PreparedPatch;
+
 pub trait MutationPayloadBuilder {
     type Entity: EntityMetadata;
 
@@ -46,62 +75,764 @@ where
 {
     pub async fn get(&self, conn: &mut ConnectionManager, entity_id: &str) -> Result<Option<T>, RepoError> {
         let key = self.entity_key(entity_id);
-        let result: Option<String> = cmd("JSON.GET").arg(&key).query_async(conn).await?;
-        match result {
-            Some(json) => {
-                let value = serde_json::from_str::<T>(&json).map_err(|err| RepoError::Other {
-                    message: format!("failed to deserialize entity: {err}").into(),
-                })?;
-                Ok(Some(value))
-            }
-            None => Ok(None),
+        let mut documents =
+            self.intercept("get", &key, storage::read_documents::<T>(conn, &self.descriptor, [key.clone()])).await?;
+        Ok(documents.pop().flatten())
+    }
+
+    /// Fetch several entities in one pipelined round trip.
+    ///
+    /// Results line up with `entity_ids`; missing entities are `None`.
+    pub async fn get_many<S>(&self, conn: &mut ConnectionManager, entity_ids: &[S]) -> Result<Vec<Option<T>>, RepoError>
+    where
+        S: AsRef<str>,
+    {
+        if entity_ids.is_empty() {
+            return Ok(Vec::new());
         }
+        let keys = entity_ids.iter().map(|id| self.entity_key(id.as_ref()));
+        let pattern = self.collection_pattern();
+        self.intercept("get_many", &pattern, storage::read_documents::<T>(conn, &self.descriptor, keys)).await
+    }
+
+    /// Fetch only `paths` of an entity and deserialize them as `P`.
+    ///
+    /// Paths are dotted field paths (`"name"`, `"profile.city"`), optionally prefixed with
+    /// `$.`. Paths absent from the document are omitted, so `P` should mark them
+    /// `#[serde(default)]` or `Option`. Returns `None` when the entity does not exist.
+    ///
+    /// ```ignore
+    /// #[derive(Deserialize)]
+    /// struct Score { name: String, score: i64 }
+    ///
+    /// let score: Option<Score> = repo.get_projection(&mut conn, &id, ["name", "score"]).await?;
+    /// ```
+    pub async fn get_projection<P, S>(
+        &self,
+        conn: &mut ConnectionManager,
+        entity_id: &str,
+        paths: impl IntoIterator<Item = S>,
+    ) -> Result<Option<P>, RepoError>
+    where
+        P: DeserializeOwned,
+        S: Into<String>,
+    {
+        let paths: Vec<String> = paths.into_iter().map(Into::into).collect();
+        if paths.is_empty() {
+            return Err(RepoError::InvalidRequest {
+                message: "get_projection requires at least one path".to_string(),
+            });
+        }
+        let key = self.entity_key(entity_id);
+        let read = storage::read_projection(conn, &self.descriptor, &key, &paths);
+        let Some(document) = self.intercept("get_projection", &key, read).await? else {
+            return Ok(None);
+        };
+        serde_json::from_value(document).map(Some).map_err(|err| RepoError::Other {
+            message: Cow::Owned(format!("failed to deserialize projection: {err}")),
+        })
+    }
+
+    /// Fetch an entity as view `V`, reading only `V::fields()`.
+    pub async fn get_view<V>(&self, conn: &mut ConnectionManager, entity_id: &str) -> Result<Option<V>, RepoError>
+    where
+        V: ViewModel<T>,
+    {
+        self.get_projection(conn, entity_id, V::fields().iter().copied()).await
     }
 
     pub async fn count(&self, conn: &mut ConnectionManager) -> Result<u64, RepoError> {
         const SCAN_COUNT: usize = 1024;
-        let pattern = format!(
-            "{}:{}:{}:*",
-            self.prefix, self.descriptor.service, self.descriptor.collection
-        );
+        let pattern = self.collection_pattern();
         // Prefix to filter out unique constraint keys
         // Key format: {prefix}:{service}:{collection}:{fourth_segment}:...
         // Entity keys have entity_id as fourth segment
         // Unique constraint keys have "unique" or "unique_compound" as fourth segment
-        let unique_prefix = format!(
-            "{}:{}:{}:unique",
-            self.prefix, self.descriptor.service, self.descriptor.collection
-        );
-        let mut cursor: u64 = 0;
-        let mut total: u64 = 0;
-        loop {
-            let (next_cursor, batch): (u64, Vec<String>) = cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(&pattern)
-                .arg("COUNT")
-                .arg(SCAN_COUNT)
-                .query_async(conn)
-                .await?;
-            // Filter out unique constraint keys (both :unique: and :unique_compound:)
-            let entity_count = batch
-                .iter()
-                .filter(|key| !key.starts_with(&unique_prefix))
-                .count();
-            total += entity_count as u64;
-            cursor = next_cursor;
-            if cursor == 0 {
-                break;
+        let unique_prefix = self.key_context().unique_prefix(&self.descriptor.collection);
+        self.intercept("count", &pattern, async {
+            let mut cursor: u64 = 0;
+            let mut total: u64 = 0;
+            loop {
+                let (next_cursor, batch): (u64, Vec<String>) = cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(SCAN_COUNT)
+                    .query_async(conn)
+                    .await?;
+                // Filter out unique constraint keys (both :unique: and :unique_compound:)
+                let entity_count = batch
+                    .iter()
+                    .filter(|key| !key.starts_with(&unique_prefix))
+                    .count();
+                total += entity_count as u64;
+                cursor = next_cursor;
+                if cursor == 0 {
+                    break;
+                }
+            }
+            Ok::<_, RepoError>(total)
+        })
+        .await
+    }
+}
+
+// ============ Relation Traversal ============
+
+impl<T> Repo<T>
+where
+    T: SnugomModel,
+{
+    /// Resolve the ids on the far side of a has-many-through relation.
+    ///
+    /// The first hop reads this entity's `through` relation set; the second hop reads the
+    /// `source` relation on every intermediate entity in a single pipeline. A belongs-to
+    /// `source` with a foreign key is read from the intermediate documents instead.
+    /// Ids are de-duplicated and returned in first-seen order.
+    pub async fn through_ids(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        entity_id: &str,
+    ) -> Result<Vec<String>, RepoError> {
+        let hop = self.resolve_through(alias)?;
+
+        let key = self.relation_key(&hop.through.alias, entity_id);
+        self.intercept("through_ids", &key, async {
+            let intermediate_ids: Vec<String> = cmd("SMEMBERS").arg(&key).query_async(conn).await?;
+            if intermediate_ids.is_empty() {
+                return Ok::<_, RepoError>(Vec::new());
+            }
+
+            let intermediate_ctx = KeyContext::for_entity(&self.prefix, &hop.intermediate);
+            let mut pipe = redis::pipe();
+            let far_ids: Vec<Vec<String>> = match (&hop.source.kind, &hop.source.foreign_key) {
+                (RelationKind::BelongsTo, Some(foreign_key)) if hop.intermediate.storage.is_hash() => {
+                    for id in &intermediate_ids {
+                        pipe.cmd("HGET")
+                            .arg(intermediate_ctx.entity(&hop.intermediate.collection, id))
+                            .arg(foreign_key);
+                    }
+                    let raw: Vec<Option<String>> = pipe.query_async(conn).await?;
+                    raw.into_iter().map(|id| id.into_iter().collect()).collect()
+                }
+                (RelationKind::BelongsTo, Some(foreign_key)) => {
+                    let path = format!("$.{foreign_key}");
+                    for id in &intermediate_ids {
+                        pipe.cmd("JSON.GET")
+                            .arg(intermediate_ctx.entity(&hop.intermediate.collection, id))
+                            .arg(&path);
+                    }
+                    let raw: Vec<Option<String>> = pipe.query_async(conn).await?;
+                    raw.into_iter()
+                        .map(|json| {
+                            json.and_then(|json| serde_json::from_str::<Vec<Value>>(&json).ok())
+                                .unwrap_or_default()
+                                .into_iter()
+                                .filter_map(|value| value.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .collect()
+                }
+                _ => {
+                    for id in &intermediate_ids {
+                        pipe.cmd("SMEMBERS").arg(intermediate_ctx.relation(&hop.source.alias, id));
+                    }
+                    pipe.query_async(conn).await?
+                }
+            };
+
+            let mut seen = HashSet::new();
+            Ok(far_ids
+                .into_iter()
+                .flatten()
+                .filter(|id| seen.insert(id.clone()))
+                .collect())
+        })
+        .await
+    }
+
+    /// Load the far-side entities of a has-many-through relation.
+    ///
+    /// `U` must be the entity the `source` relation points at. Missing documents are skipped.
+    pub async fn get_through<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        entity_id: &str,
+    ) -> Result<Vec<U>, RepoError>
+    where
+        U: SnugomModel + DeserializeOwned,
+    {
+        let hop = self.resolve_through(alias)?;
+        let target_service = hop.source.target_service.as_deref().unwrap_or(&hop.intermediate.service);
+        if hop.source.target != U::COLLECTION || target_service != U::SERVICE {
+            return Err(RepoError::InvalidRequest {
+                message: format!(
+                    "relation `{alias}` resolves to `{target_service}:{}`, not `{}:{}`",
+                    hop.source.target,
+                    U::SERVICE,
+                    U::COLLECTION
+                ),
+            });
+        }
+
+        let ids = self.through_ids(conn, alias, entity_id).await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let descriptor = U::entity_descriptor();
+        let target_ctx = KeyContext::for_entity(&self.prefix, &descriptor);
+        let keys = ids.iter().map(|id| target_ctx.entity(U::COLLECTION, id));
+        let pattern = target_ctx.collection_pattern(U::COLLECTION);
+        let read = storage::read_documents::<U>(conn, &descriptor, keys);
+        let documents = self.intercept("get_many", &pattern, read).await?;
+        Ok(documents.into_iter().flatten().collect())
+    }
+
+    /// Read the edge payloads stored on a relation, keyed by member id.
+    ///
+    /// Members connected without edge data are absent from the map.
+    pub async fn relation_edges(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        left_id: &str,
+    ) -> Result<BTreeMap<String, Value>, RepoError> {
+        let key = self.key_context().relation_edges(alias, left_id);
+        let raw: BTreeMap<String, String> =
+            self.intercept("relation_edges", &key, cmd("HGETALL").arg(&key).query_async(conn)).await?;
+        raw.into_iter()
+            .map(|(member_id, json)| {
+                serde_json::from_str(&json)
+                    .map(|value| (member_id, value))
+                    .map_err(|err| RepoError::Other {
+                        message: format!("failed to deserialize relation edge: {err}").into(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Load the related entities of `alias` together with their edge payloads.
+    ///
+    /// `U` must be the relation's target entity. Missing documents are skipped.
+    pub async fn get_related_with_edges<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        left_id: &str,
+    ) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + DeserializeOwned,
+    {
+        self.ensure_relation_target::<U>(alias)?;
+
+        let key = self.relation_key(alias, left_id);
+        let mut ids: Vec<String> =
+            self.intercept("relation_members", &key, cmd("SMEMBERS").arg(&key).query_async(conn)).await?;
+        ids.sort();
+        let edges = self.relation_edges(conn, alias, left_id).await?;
+        if ids.is_empty() {
+            return Ok(RelationData::new(Vec::new()).with_edges(edges));
+        }
+
+        let descriptor = U::entity_descriptor();
+        let target_ctx = KeyContext::for_entity(&self.prefix, &descriptor);
+        let keys = ids.iter().map(|id| target_ctx.entity(U::COLLECTION, id));
+        let pattern = target_ctx.collection_pattern(U::COLLECTION);
+        let read = storage::read_documents::<U>(conn, &descriptor, keys);
+        let items: Vec<U> = self
+            .intercept("get_many", &pattern, read)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        let total = items.len() as u64;
+        Ok(RelationData::with_metadata(items, total, false).with_edges(edges))
+    }
+
+    /// Load `alias` for every entity in `parents` without an N+1 round trip per parent.
+    ///
+    /// Related ids are collected from belongs-to foreign keys on the parents themselves, or
+    /// from the relation sets in a single pipeline. The ids are de-duplicated and fetched in a
+    /// second pipeline. The result is keyed by parent id; parents with no related entities map
+    /// to an empty `Vec`. A related entity shared by several parents is cloned into each.
+    pub async fn load_relations<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        parents: &[T],
+        alias: &str,
+    ) -> Result<HashMap<String, Vec<U>>, RepoError>
+    where
+        T: Serialize,
+        U: SnugomModel + DeserializeOwned + Clone,
+    {
+        let relation = self.ensure_relation_target::<U>(alias)?.clone();
+        let id_field = self.descriptor.id_field.as_deref().unwrap_or("id");
+
+        let mut parent_ids = Vec::with_capacity(parents.len());
+        let mut foreign_keys = Vec::with_capacity(parents.len());
+        for parent in parents {
+            let value = serde_json::to_value(parent).map_err(|err| RepoError::Other {
+                message: format!("failed to serialize entity: {err}").into(),
+            })?;
+            let parent_id = value
+                .get(id_field)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| RepoError::InvalidRequest {
+                    message: format!("entity is missing its `{id_field}` field"),
+                })?;
+            if let (RelationKind::BelongsTo, Some(foreign_key)) = (&relation.kind, &relation.foreign_key) {
+                foreign_keys.push(value.get(foreign_key).and_then(Value::as_str).map(str::to_string));
             }
+            parent_ids.push(parent_id);
         }
-        Ok(total)
+
+        let related_ids: Vec<Vec<String>> = if matches!(relation.kind, RelationKind::BelongsTo)
+            && relation.foreign_key.is_some()
+        {
+            foreign_keys.into_iter().map(|id| id.into_iter().collect()).collect()
+        } else {
+            let mut pipe = redis::pipe();
+            for parent_id in &parent_ids {
+                pipe.cmd("SMEMBERS").arg(self.relation_key(alias, parent_id));
+            }
+            self.intercept("relation_members", &self.relation_key(alias, "*"), pipe.query_async(conn)).await?
+        };
+
+        let mut seen = HashSet::new();
+        let unique_ids: Vec<&String> = related_ids.iter().flatten().filter(|id| seen.insert(*id)).collect();
+        let mut loaded: HashMap<String, U> = HashMap::with_capacity(unique_ids.len());
+        if !unique_ids.is_empty() {
+            let descriptor = U::entity_descriptor();
+            let target_ctx = KeyContext::for_entity(&self.prefix, &descriptor);
+            let keys = unique_ids.iter().map(|id| target_ctx.entity(U::COLLECTION, id));
+            let pattern = target_ctx.collection_pattern(U::COLLECTION);
+            let read = storage::read_documents::<U>(conn, &descriptor, keys);
+            let documents = self.intercept("get_many", &pattern, read).await?;
+            for (id, entity) in unique_ids.into_iter().zip(documents) {
+                let Some(entity) = entity else { continue };
+                loaded.insert(id.clone(), entity);
+            }
+        }
+
+        Ok(parent_ids
+            .into_iter()
+            .zip(related_ids)
+            .map(|(parent_id, ids)| {
+                let items = ids.iter().filter_map(|id| loaded.get(id).cloned()).collect();
+                (parent_id, items)
+            })
+            .collect())
+    }
+
+    /// Load related entities that match `options`, searching the target index.
+    ///
+    /// The relation member set is intersected with a search on `U`'s index (`INKEYS`), so the
+    /// filter, typed condition, and sort from `options` are evaluated by RediSearch. `total`
+    /// counts all matching members, not just the returned page.
+    pub async fn find_related<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        left_id: &str,
+        options: RelationQueryOptions,
+    ) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + SearchEntity,
+    {
+        self.ensure_relation_target::<U>(alias)?;
+
+        let key = self.relation_key(alias, left_id);
+        let ids: Vec<String> =
+            self.intercept("relation_members", &key, cmd("SMEMBERS").arg(&key).query_async(conn)).await?;
+        if ids.is_empty() {
+            return Ok(RelationData::with_metadata(Vec::new(), 0, false));
+        }
+
+        let (sort_by, sort_order) = match options.parse_sort() {
+            Some((field, order)) => (Some(field.to_string()), Some(order)),
+            None => (None, None),
+        };
+        let query = SearchQuery {
+            sort_by,
+            sort_order,
+            filter: options.filter.clone().into_iter().collect(),
+            ..Default::default()
+        };
+        let mut params = query.into_params(U::allowed_sorts(), U::default_sort(), |descriptor| U::map_filter(descriptor))?;
+
+        // SearchParams paginates by page, so fetch through the end of the window and skip the offset.
+        let offset = u64::from(options.offset.unwrap_or(0));
+        let limit = u64::from(options.effective_limit());
+        params.page = 1;
+        params.page_size = offset + limit;
+        if let Some(condition) = options.condition {
+            params.conditions.push(condition);
+        }
+        let target_descriptor = U::entity_descriptor();
+        let target_ctx = KeyContext::for_entity(&self.prefix, &target_descriptor);
+        params = params.with_in_keys(ids.iter().map(|id| target_ctx.entity(U::COLLECTION, id)));
+
+        let definition = U::index_definition(&self.prefix);
+        let result: SearchResult<U> =
+            self.run_search(conn, &definition, &U::entity_descriptor(), &params, &U::base_filter()).await?;
+        let items: Vec<U> = result.items.into_iter().skip(offset as usize).collect();
+        let has_more = offset + (items.len() as u64) < result.total;
+        Ok(RelationData::with_metadata(items, result.total, has_more))
+    }
+
+    /// Load a page of the related entities of `alias`.
+    ///
+    /// Without a filter, condition, or sort in `options`, the relation set is read and its page
+    /// of members, ordered by id, is fetched directly, so the target needs no search index.
+    /// Otherwise this is [`Repo::find_related`]. Missing documents are skipped.
+    pub async fn get_related<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        alias: &str,
+        left_id: &str,
+        options: RelationQueryOptions,
+    ) -> Result<RelationData<Vec<U>>, RepoError>
+    where
+        U: SnugomModel + SearchEntity,
+    {
+        if options.filter.is_some() || options.condition.is_some() || options.sort.is_some() {
+            return self.find_related(conn, alias, left_id, options).await;
+        }
+        self.ensure_relation_target::<U>(alias)?;
+
+        let key = self.relation_key(alias, left_id);
+        let mut ids: Vec<String> =
+            self.intercept("relation_members", &key, cmd("SMEMBERS").arg(&key).query_async(conn)).await?;
+        ids.sort();
+        let offset = options.offset.unwrap_or(0) as usize;
+        let page: Vec<&String> = ids.iter().skip(offset).take(options.effective_limit() as usize).collect();
+        let has_more = offset + page.len() < ids.len();
+        if page.is_empty() {
+            return Ok(RelationData::with_metadata(Vec::new(), ids.len() as u64, has_more));
+        }
+
+        let descriptor = U::entity_descriptor();
+        let target_ctx = KeyContext::for_entity(&self.prefix, &descriptor);
+        let keys = page.iter().map(|id| target_ctx.entity(U::COLLECTION, id));
+        let pattern = target_ctx.collection_pattern(U::COLLECTION);
+        let read = storage::read_documents::<U>(conn, &descriptor, keys);
+        let items: Vec<U> = self
+            .intercept("get_many", &pattern, read)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(RelationData::with_metadata(items, ids.len() as u64, has_more))
+    }
+
+    /// Check that `alias` is a relation on this entity whose target is `U`.
+    fn ensure_relation_target<U>(&self, alias: &str) -> Result<&RelationDescriptor, RepoError>
+    where
+        U: SnugomModel,
+    {
+        let relation = self
+            .descriptor
+            .relations
+            .iter()
+            .find(|relation| relation.alias == alias)
+            .ok_or_else(|| RepoError::InvalidRequest {
+                message: format!("`{alias}` is not a relation on `{}`", self.descriptor.collection),
+            })?;
+        let target_service = relation.target_service.as_deref().unwrap_or(&self.descriptor.service);
+        if relation.target != U::COLLECTION || target_service != U::SERVICE {
+            return Err(RepoError::InvalidRequest {
+                message: format!(
+                    "relation `{alias}` targets `{target_service}:{}`, not `{}:{}`",
+                    relation.target,
+                    U::SERVICE,
+                    U::COLLECTION
+                ),
+            });
+        }
+        Ok(relation)
+    }
+
+    fn resolve_through(&self, alias: &str) -> Result<ThroughHop, RepoError> {
+        let through = self
+            .descriptor
+            .through_relations
+            .iter()
+            .find(|relation| relation.alias == alias)
+            .ok_or_else(|| RepoError::InvalidRequest {
+                message: format!(
+                    "`{alias}` is not a has_many_through relation on `{}`",
+                    self.descriptor.collection
+                ),
+            })?;
+        let first = self
+            .descriptor
+            .relations
+            .iter()
+            .find(|relation| relation.alias == through.through)
+            .cloned()
+            .ok_or_else(|| RepoError::InvalidRequest {
+                message: format!("through relation `{}` is not defined", through.through),
+            })?;
+        let intermediate_service = first.target_service.as_deref().unwrap_or(&self.descriptor.service);
+        let intermediate = lookup_descriptor(intermediate_service, &first.target).ok_or_else(|| {
+            RepoError::InvalidRequest {
+                message: format!(
+                    "intermediate entity `{intermediate_service}:{}` is not registered",
+                    first.target
+                ),
+            }
+        })?;
+        let source = intermediate
+            .relations
+            .iter()
+            .find(|relation| relation.alias == through.source)
+            .cloned()
+            .ok_or_else(|| RepoError::InvalidRequest {
+                message: format!(
+                    "source relation `{}` is not defined on `{}`",
+                    through.source, intermediate.collection
+                ),
+            })?;
+        Ok(ThroughHop {
+            through: first,
+            intermediate,
+            source,
+        })
+    }
+}
+
+/// Resolved descriptors for both hops of a has-many-through relation.
+struct ThroughHop {
+    through: RelationDescriptor,
+    intermediate: EntityDescriptor,
+    source: RelationDescriptor,
+}
+
+/// Look up a descriptor in the registry, falling back to auto-registered entities that
+/// have not been touched yet in this process.
+///
+/// Entities of another service resolve the same way as long as their crate is linked in, which
+/// is what lets cascades follow `target_service` relations across clients.
+fn lookup_descriptor(service: &str, collection: &str) -> Option<EntityDescriptor> {
+    registry::get_descriptor(service, collection).or_else(|| {
+        crate::client::registered_entities()
+            .find(|entry| entry.service_name == service && entry.collection_name == collection)
+            .map(|entry| (entry.descriptor_fn)())
+    })
+}
+
+fn unregistered_descriptor(service: &str, collection: &str) -> RepoError {
+    RepoError::Other {
+        message: Cow::Owned(format!(
+            "descriptor for service `{service}` collection `{collection}` is not registered; link the crate \
+             defining it, or list it in the client's `shared` entities"
+        )),
+    }
+}
+
+// ============ Relation Repair ============
+
+/// Outcome of [`Repo::rebuild_relations`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelationRepairReport {
+    /// Entity documents examined
+    pub scanned: u64,
+    /// Relation set members that were missing and have been added
+    pub repaired: u64,
+}
+
+/// Relation sets implied by one belongs-to foreign key.
+struct ForeignKeyRepair {
+    alias: String,
+    foreign_key: String,
+    maintain_reverse: bool,
+    /// Parent-side has-many relation mirroring this belongs-to
+    parent_has_many: Option<ParentHasMany>,
+}
+
+struct ParentHasMany {
+    service: String,
+    collection: String,
+    alias: String,
+    /// Counter field kept equal to the size of the has-many set
+    counter: Option<String>,
+    keys: KeyOverrides,
+}
+
+impl<T> Repo<T>
+where
+    T: SnugomModel,
+{
+    /// Rebuild relation sets for this collection from the belongs-to foreign keys stored on
+    /// each entity.
+    ///
+    /// For every entity with a foreign key set, this restores the entity's own belongs-to set,
+    /// the parent's reverse set (when the relation cascades), and the parent's has-many set
+    /// targeting this collection. Sets are only added to, never trimmed, so running it on a
+    /// healthy collection is a no-op. Counters on those parents are rewritten from the size of
+    /// the has-many set, which corrects counts that drifted from it. Intended for recovering from imports or historical bugs
+    /// that wrote documents without going through relation plans.
+    pub async fn rebuild_relations(&self, conn: &mut ConnectionManager) -> Result<RelationRepairReport, RepoError> {
+        const SCAN_COUNT: usize = 512;
+        let repairs = self.foreign_key_repairs();
+        let mut report = RelationRepairReport::default();
+        if repairs.is_empty() {
+            return Ok(report);
+        }
+
+        let id_field = self.descriptor.id_field.as_deref().unwrap_or("id");
+        let pattern = self.collection_pattern();
+        let key_context = self.key_context();
+        let unique_prefix = key_context.unique_prefix(&self.descriptor.collection);
+        self.intercept("rebuild_relations", &pattern, async {
+            let mut cursor: u64 = 0;
+            loop {
+                let (next_cursor, batch): (u64, Vec<String>) = cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(SCAN_COUNT)
+                    .query_async(conn)
+                    .await?;
+                cursor = next_cursor;
+
+                let candidates: Vec<String> =
+                    batch.into_iter().filter(|key| !key.starts_with(&unique_prefix)).collect();
+                if !candidates.is_empty() {
+                    // Only documents of the entity's storage type are entities; skip any auxiliary
+                    // keys sharing the prefix.
+                    let document_type = match self.descriptor.storage {
+                        StorageMode::Json => "ReJSON-RL",
+                        StorageMode::Hash => "hash",
+                    };
+                    let mut pipe = redis::pipe();
+                    for key in &candidates {
+                        pipe.cmd("TYPE").arg(key);
+                    }
+                    let types: Vec<String> = pipe.query_async(conn).await?;
+                    let documents: Vec<&String> = candidates
+                        .iter()
+                        .zip(types)
+                        .filter(|(_, kind)| kind == document_type)
+                        .map(|(key, _)| key)
+                        .collect();
+
+                    let mut pipe = redis::pipe();
+                    for key in &documents {
+                        pipe.add_command(storage::read_document_cmd(self.descriptor.storage, key));
+                    }
+                    let raw: Vec<redis::Value> = pipe.query_async(conn).await?;
+
+                    let mut writes = redis::pipe();
+                    let mut pending = 0usize;
+                    // (parent key, has-many set, counter path) of every parent seen in this batch
+                    let mut counters = BTreeSet::new();
+                    for reply in &raw {
+                        let Ok(Some(document)) = storage::decode_document(&self.descriptor, reply) else {
+                            continue;
+                        };
+                        report.scanned += 1;
+                        let Some(entity_id) = document.get(id_field).and_then(Value::as_str) else {
+                            continue;
+                        };
+                        for repair in &repairs {
+                            let Some(parent_id) = document.get(&repair.foreign_key).and_then(Value::as_str) else {
+                                continue;
+                            };
+                            if parent_id.is_empty() {
+                                continue;
+                            }
+                            writes.cmd("SADD").arg(key_context.relation(&repair.alias, entity_id)).arg(parent_id);
+                            pending += 1;
+                            if repair.maintain_reverse {
+                                writes
+                                    .cmd("SADD")
+                                    .arg(key_context.relation_reverse(&repair.alias, parent_id))
+                                    .arg(entity_id);
+                                pending += 1;
+                            }
+                            if let Some(parent) = &repair.parent_has_many {
+                                let parent_context =
+                                    KeyContext::new(&self.prefix, &parent.service).with_overrides(&parent.keys);
+                                let relation_key = parent_context.relation(&parent.alias, parent_id);
+                                writes.cmd("SADD").arg(&relation_key).arg(entity_id);
+                                pending += 1;
+                                if let Some(field) = &parent.counter {
+                                    counters.insert((
+                                        parent_context.entity(&parent.collection, parent_id),
+                                        relation_key,
+                                        format!("$.{field}"),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                    if pending > 0 {
+                        let added: Vec<u64> = writes.query_async(conn).await?;
+                        report.repaired += added.into_iter().sum::<u64>();
+                    }
+                    if !counters.is_empty() {
+                        let mut sizes = redis::pipe();
+                        for (entity_key, relation_key, _) in &counters {
+                            sizes.cmd("EXISTS").arg(entity_key).cmd("SCARD").arg(relation_key);
+                        }
+                        let sizes: Vec<u64> = sizes.query_async(conn).await?;
+                        let mut refresh = redis::pipe();
+                        for ((entity_key, _, path), size) in counters.iter().zip(sizes.chunks(2)) {
+                            if size[0] == 1 {
+                                refresh.cmd("JSON.SET").arg(entity_key).arg(path).arg(size[1]).ignore();
+                            }
+                        }
+                        refresh.query_async::<()>(conn).await?;
+                    }
+                }
+
+                if cursor == 0 {
+                    break;
+                }
+            }
+            Ok::<_, RepoError>(report)
+        })
+        .await
+    }
+
+    fn foreign_key_repairs(&self) -> Vec<ForeignKeyRepair> {
+        self.descriptor
+            .relations
+            .iter()
+            .filter(|relation| matches!(relation.kind, RelationKind::BelongsTo))
+            .filter_map(|relation| {
+                let foreign_key = relation.foreign_key.clone()?;
+                let parent_has_many = mirrored_has_many(&self.descriptor, relation).map(|(parent, has_many)| {
+                    ParentHasMany {
+                        service: parent.service,
+                        collection: parent.collection,
+                        alias: has_many.alias,
+                        counter: has_many.counter,
+                        keys: parent.keys,
+                    }
+                });
+                Some(ForeignKeyRepair {
+                    alias: relation.alias.clone(),
+                    foreign_key,
+                    maintain_reverse: !matches!(relation.cascade, CascadePolicy::None),
+                    parent_has_many,
+                })
+            })
+            .collect()
     }
 }
 
 fn length_for_value(field_type: FieldType, value: &Value) -> Option<usize> {
     match field_type {
         FieldType::String | FieldType::DateTime => value.as_str().map(|s| s.chars().count()),
-        FieldType::Array => value.as_array().map(|arr| arr.len()),
+        FieldType::Array | FieldType::Bytes => value.as_array().map(|arr| arr.len()),
         _ => None,
     }
 }
@@ -130,7 +861,7 @@ fn validate_rule_on_value(
                             field_name,
                             "validation.length",
                             format!("length must be at least {}", min_len),
-                        ));
+                        ).with_param("min", *min_len));
                     }
                 if let Some(max_len) = max
                     && len > *max_len {
@@ -138,7 +869,7 @@ fn validate_rule_on_value(
                             field_name,
                             "validation.length",
                             format!("length must be at most {}", max_len),
-                        ));
+                        ).with_param("max", *max_len));
                     }
             }
         }
@@ -151,7 +882,7 @@ fn validate_rule_on_value(
                                 field_name,
                                 "validation.range",
                                 format!("value must be at least {}", min_repr),
-                            ));
+                            ).with_bound("min", min_repr));
                         }
                 if let Some(max_repr) = max
                     && let Ok(parsed_max) = max_repr.parse::<f64>()
@@ -160,18 +891,18 @@ fn validate_rule_on_value(
                                 field_name,
                                 "validation.range",
                                 format!("value must be at most {}", max_repr),
-                            ));
+                            ).with_bound("max", max_repr));
                         }
             }
         }
         ValidationRule::Regex { pattern } => {
             if let Some(candidate) = value.as_str()
-                && Regex::new(pattern).map(|regex| !regex.is_match(candidate)).unwrap_or(false) {
+                && !matches_regex(pattern, candidate) {
                     issues.push(ValidationIssue::new(
                         field_name,
                         "validation.regex",
                         format!("value does not match pattern {}", pattern),
-                    ));
+                    ).with_param("pattern", pattern.as_str()));
                 }
         }
         ValidationRule::Enum {
@@ -194,7 +925,7 @@ fn validate_rule_on_value(
                         field_name,
                         "validation.enum",
                         format!("value must be one of {:?}", allowed),
-                    ));
+                    ).with_param("allowed", allowed.clone()));
                 }
             }
         }
@@ -224,11 +955,49 @@ fn validate_rule_on_value(
                     ));
                 }
         }
-        ValidationRule::RequiredIf { .. }
-        | ValidationRule::ForbiddenIf { .. }
-        | ValidationRule::Unique { .. }
-        | ValidationRule::Custom { .. } => {
-            // These rules depend on wider entity context and are enforced during full entity validation.
+        ValidationRule::Phone => {
+            if let Some(candidate) = value.as_str()
+                && !is_valid_phone(candidate) {
+                    issues.push(ValidationIssue::new(
+                        field_name,
+                        "validation.phone",
+                        "value must be a valid phone number",
+                    ));
+                }
+        }
+        ValidationRule::IpAddr => {
+            if let Some(candidate) = value.as_str()
+                && !is_valid_ip_addr(candidate) {
+                    issues.push(ValidationIssue::new(
+                        field_name,
+                        "validation.ip_addr",
+                        "value must be a valid IP address",
+                    ));
+                }
+        }
+        ValidationRule::Hostname => {
+            if let Some(candidate) = value.as_str()
+                && !is_valid_hostname(candidate) {
+                    issues.push(ValidationIssue::new(
+                        field_name,
+                        "validation.hostname",
+                        "value must be a valid hostname",
+                    ));
+                }
+        }
+        ValidationRule::Slug => {
+            if let Some(candidate) = value.as_str()
+                && !is_valid_slug(candidate) {
+                    issues.push(ValidationIssue::new(field_name, "validation.slug", "value must be a lowercase slug"));
+                }
+        }
+        ValidationRule::Custom { check, .. } => {
+            if let Err(err) = check(value) {
+                issues.extend(err.issues);
+            }
+        }
+        ValidationRule::RequiredIf { .. } | ValidationRule::ForbiddenIf { .. } | ValidationRule::Unique { .. } => {
+            // Conditional rules need the whole entity and are checked in validate_entity_json.
             // Unique constraints are enforced at database level via Lua script.
         }
     }
@@ -244,8 +1013,17 @@ fn validate_field_assignment(field: &FieldDescriptor, value: &Value) -> Vec<Vali
             ValidationScope::EachElement => {
                 if let Some(array) = value.as_array() {
                     let element_type = field.element_type.unwrap_or(FieldType::Object);
-                    for element in array {
-                        validate_rule_on_value(&field.name, element_type, &descriptor.rule, element, &mut issues);
+                    for (index, element) in array.iter().enumerate() {
+                        if let ValidationRule::Custom { check, .. } = &descriptor.rule {
+                            // Like the derive, element issues are reported at the element's path
+                            if let Err(err) = check(element) {
+                                issues.extend(err.issues.into_iter().map(|issue| {
+                                    ValidationIssue::new(format!("{}[{index}]", field.name), issue.code, issue.message)
+                                }));
+                            }
+                        } else {
+                            validate_rule_on_value(&field.name, element_type, &descriptor.rule, element, &mut issues);
+                        }
                     }
                 }
             }
@@ -269,6 +1047,13 @@ fn apply_patch_operations_to_value(target: &mut Value, operations: &[PatchOperat
             PatchOpKind::Assign(value) => set_value_at_path(target, &segments, value.clone())?,
             PatchOpKind::Merge(value) => merge_value_at_path(target, &segments, value.clone())?,
             PatchOpKind::Delete => delete_value_at_path(target, &segments)?,
+            PatchOpKind::Increment(by) => increment_value_at_path(target, &segments, *by)?,
+            PatchOpKind::ArrayAppend(elements) => {
+                array_at_path(target, &segments)?.extend(elements.iter().cloned());
+            }
+            PatchOpKind::ArrayRemove(elements) => {
+                array_at_path(target, &segments)?.retain(|element| !elements.contains(element));
+            }
         }
     }
     Ok(())
@@ -318,6 +1103,44 @@ fn set_value_at_path(target: &mut Value, segments: &[&str], value: Value) -> Res
     Ok(())
 }
 
+fn increment_value_at_path(target: &mut Value, segments: &[&str], by: f64) -> Result<(), RepoError> {
+    let key = segments.last().copied().unwrap_or("");
+    let parent = parent_map_mut(target, &segments[..segments.len() - 1])?;
+    let incremented = match parent.get(key) {
+        Some(Value::Number(current)) => match (current.as_i64(), increment_value(by).as_i64()) {
+            (Some(current), Some(delta)) => current.checked_add(delta).map(Value::from),
+            _ => current.as_f64().map(|current| Value::from(current + by)),
+        },
+        _ => None,
+    };
+    let incremented = incremented.ok_or_else(|| {
+        RepoError::Validation(ValidationError::single(
+            key,
+            "patch.increment_non_numeric",
+            "increment target is not a number",
+        ))
+    })?;
+    parent.insert(key.to_string(), incremented);
+    Ok(())
+}
+
+/// The array at `segments`, created empty when the field is missing or null.
+fn array_at_path<'a>(target: &'a mut Value, segments: &[&str]) -> Result<&'a mut Vec<Value>, RepoError> {
+    let key = segments.last().copied().unwrap_or("");
+    let parent = parent_map_mut(target, &segments[..segments.len() - 1])?;
+    let slot = parent.entry(key.to_string()).or_insert(Value::Null);
+    if slot.is_null() {
+        *slot = Value::Array(Vec::new());
+    }
+    slot.as_array_mut().ok_or_else(|| {
+        RepoError::Validation(ValidationError::single(
+            key,
+            "patch.array_non_array",
+            "array operation target is not an array",
+        ))
+    })
+}
+
 fn delete_value_at_path(target: &mut Value, segments: &[&str]) -> Result<(), RepoError> {
     if segments.is_empty() {
         return Err(RepoError::Validation(ValidationError::single(
@@ -364,6 +1187,84 @@ fn parent_map_mut<'a>(value: &'a mut Value, segments: &[&str]) -> Result<&'a mut
     }
 }
 
+/// Replace assigned values with their stored form (base64 bytes, compressed blobs).
+fn encode_patch_assignments(descriptor: &EntityDescriptor, operations: &mut [PatchOperation]) -> Result<(), RepoError> {
+    for op in operations {
+        let field_name = op.path.strip_prefix("$.").unwrap_or(op.path.as_str());
+        let Some(field) = descriptor.fields.iter().find(|field| field.name == field_name) else {
+            continue;
+        };
+        if let PatchOpKind::Assign(value) = &mut op.kind {
+            *value = storage::encode_field_value(field, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Increments only apply to plain numeric fields whose value the index doesn't need to re-check.
+fn ensure_incrementable(descriptor: &EntityDescriptor, field: &FieldDescriptor, by: f64) -> Result<(), RepoError> {
+    let reject = |code: &str, message: &str| {
+        Err(RepoError::Validation(ValidationError::single(field.name.clone(), code, message)))
+    };
+    if field.field_type != FieldType::Number {
+        return reject("patch.increment_non_numeric", "only numeric fields can be incremented");
+    }
+    if !by.is_finite() {
+        return reject("patch.increment_invalid", "increment must be a finite number");
+    }
+    if descriptor.unique_constraints.iter().any(|constraint| constraint.fields.contains(&field.name)) {
+        return reject("patch.increment_unique", "unique fields must be assigned, not incremented");
+    }
+    Ok(())
+}
+
+/// Array operations are limited to plain JSON arrays: relation vecs are owned by their
+/// relation sets, and unique or compressed values must be rewritten as a whole.
+fn ensure_array_operable(descriptor: &EntityDescriptor, field: &FieldDescriptor) -> Result<(), RepoError> {
+    let reject = |code: &str, message: &str| {
+        Err(RepoError::Validation(ValidationError::single(field.name.clone(), code, message)))
+    };
+    if field.field_type != FieldType::Array {
+        return reject("patch.array_non_array", "only array fields support push and remove");
+    }
+    if field.is_relation_vec {
+        return reject("patch.array_relation", "use connect/disconnect to change relation fields");
+    }
+    if field.compression.is_some() {
+        return reject("patch.compressed_merge", "compressed fields can only be assigned as a whole");
+    }
+    if descriptor.unique_constraints.iter().any(|constraint| constraint.fields.contains(&field.name)) {
+        return reject("patch.array_unique", "unique fields must be assigned, not pushed to");
+    }
+    Ok(())
+}
+
+/// The field's own `length` rule, which applies to the whole array rather than its elements.
+fn array_length_bounds(field: &FieldDescriptor) -> Option<LengthBounds> {
+    field.validations.iter().find_map(|descriptor| match (&descriptor.scope, &descriptor.rule) {
+        (ValidationScope::Field, ValidationRule::Length { min, max }) => Some(LengthBounds { min: *min, max: *max }),
+        _ => None,
+    })
+}
+
+fn validate_array_elements(field: &FieldDescriptor, elements: &[Value]) -> Vec<ValidationIssue> {
+    let element_type = field.element_type.unwrap_or(FieldType::Object);
+    let mut issues = Vec::new();
+    for descriptor in &field.validations {
+        if matches!(descriptor.scope, ValidationScope::EachElement) {
+            for element in elements {
+                validate_rule_on_value(&field.name, element_type, &descriptor.rule, element, &mut issues);
+            }
+        }
+    }
+    issues
+}
+
+/// Whether a script response is a stored idempotent result rather than a fresh write.
+fn is_replay(response: &Value) -> bool {
+    response.get("replayed").and_then(Value::as_bool).unwrap_or(false)
+}
+
 fn validate_entity_json(descriptor: &EntityDescriptor, value: &Value) -> ValidationResult<()> {
     let object = value.as_object().ok_or_else(|| {
         ValidationError::single("__entity", "validation.invalid_type", "expected object for entity payload")
@@ -388,6 +1289,108 @@ fn validate_entity_json(descriptor: &EntityDescriptor, value: &Value) -> Validat
         }
     }
 
+    issues.extend(conditional_rule_issues(descriptor, object, value));
+
+    // Entity validators take the whole entity, which only deserializes once the fields are valid
+    if issues.is_empty() {
+        for validator in &descriptor.custom_validators {
+            if let Err(err) = (validator.check)(value) {
+                issues.extend(err.issues);
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError::new(issues))
+    }
+}
+
+/// Issues for `required_if`/`forbidden_if` rules whose condition holds for the document. A
+/// condition that cannot be evaluated, because the document does not deserialize, is skipped; the
+/// field checks already report why.
+fn conditional_rule_issues(
+    descriptor: &EntityDescriptor,
+    object: &serde_json::Map<String, Value>,
+    document: &Value,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for field in &descriptor.fields {
+        let present = object.get(&field.name).is_some_and(|value| !value.is_null());
+        for validation in &field.validations {
+            match &validation.rule {
+                ValidationRule::RequiredIf { condition, .. } if !present && condition(document) == Some(true) => {
+                    issues.push(ValidationIssue::new(
+                        field.name.clone(),
+                        "validation.required_if",
+                        "field is required when condition is met",
+                    ));
+                }
+                ValidationRule::ForbiddenIf { condition, .. } if present && condition(document) == Some(true) => {
+                    issues.push(ValidationIssue::new(
+                        field.name.clone(),
+                        "validation.forbidden_if",
+                        "field must be absent when condition is met",
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+    issues
+}
+
+/// Reject a patch operation on the id or an `#[snugom(immutable)]` field.
+fn ensure_patchable(field: &FieldDescriptor) -> Result<(), RepoError> {
+    let message = if field.is_id {
+        "cannot patch identifier field"
+    } else if field.immutable {
+        "field is immutable once created"
+    } else {
+        return Ok(());
+    };
+    Err(RepoError::Validation(ValidationError::single(field.name.clone(), "patch.immutable_field", message)))
+}
+
+/// Reject a new document whose id lacks the entity's `id(prefix)`. Only creates are checked, so
+/// documents written before the prefix was added can still be updated.
+fn validate_id_prefix(descriptor: &EntityDescriptor, value: &Value) -> ValidationResult<()> {
+    let (Some(prefix), Some(id_field)) = (&descriptor.id_prefix, &descriptor.id_field) else {
+        return Ok(());
+    };
+    match value.get(id_field).and_then(Value::as_str) {
+        Some(id) if !crate::id::has_id_prefix(id, prefix) => Err(ValidationError::single(
+            id_field.clone(),
+            "validation.id_prefix",
+            format!("id must start with `{prefix}_`"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Run the entity's `validate(async_custom = "...")` validators against `document`. Field
+/// validators skip absent fields and, when `touched` is given, fields outside it.
+async fn run_async_validators(
+    descriptor: &EntityDescriptor,
+    conn: &mut ConnectionManager,
+    document: &Value,
+    touched: Option<&[&str]>,
+) -> ValidationResult<()> {
+    let mut issues = Vec::new();
+    for validator in &descriptor.async_validators {
+        let value = match &validator.field {
+            Some(field) if touched.is_some_and(|touched| !touched.contains(&field.as_str())) => continue,
+            Some(field) => match document.get(field) {
+                Some(value) => value,
+                None => continue,
+            },
+            None => document,
+        };
+        if let Err(err) = (validator.check)(conn, value).await {
+            issues.extend(err.issues);
+        }
+    }
     if issues.is_empty() {
         Ok(())
     } else {
@@ -396,6 +1399,7 @@ fn validate_entity_json(descriptor: &EntityDescriptor, value: &Value) -> Validat
 }
 
 fn cascade_relation_specs_for(
+    prefix: &str,
     descriptor: &EntityDescriptor,
     stack: &mut Vec<(String, String)>,
     depth: usize,
@@ -424,32 +1428,31 @@ fn cascade_relation_specs_for(
             CascadePolicy::Delete => CascadeDirective::DeleteDependents,
         };
 
+        let service = relation.target_service.clone().unwrap_or_else(|| descriptor.service.clone());
         let child_relations = if matches!(relation.cascade, CascadePolicy::Delete) {
-            let service = relation.target_service.clone().unwrap_or_else(|| descriptor.service.clone());
-            if stack.contains(&(service.clone(), relation.target.clone())) {
+            if is_self_reference(descriptor, &service, &relation.target) {
+                self_cascade_specs_for(prefix, descriptor, stack, depth)?
+            } else if stack.contains(&(service.clone(), relation.target.clone())) {
                 return Err(RepoError::Other {
                     message: Cow::Owned(format!(
                         "cycle detected in cascade chain: {}:{}, relation {} -> {}:{}",
                         descriptor.service, descriptor.collection, relation.alias, service, relation.target
                     )),
                 });
+            } else {
+                let target_descriptor = lookup_descriptor(&service, &relation.target)
+                    .ok_or_else(|| unregistered_descriptor(&service, &relation.target))?;
+                cascade_relation_specs_for(prefix, &target_descriptor, stack, depth + 1)?
             }
-            let target_descriptor =
-                registry::get_descriptor(&service, &relation.target).ok_or_else(|| RepoError::Other {
-                    message: Cow::Owned(format!(
-                        "descriptor for service `{}` collection `{}` is not registered",
-                        service, relation.target
-                    )),
-                })?;
-            cascade_relation_specs_for(&target_descriptor, stack, depth + 1)?
         } else {
             Vec::new()
         };
+        let target_keys = lookup_descriptor(&service, &relation.target).map(|target| target.keys).unwrap_or_default();
 
         specs.push(CascadeRelationSpec {
             alias: relation.alias.clone(),
             target_collection: Some(relation.target.clone()),
-            target_service: relation.target_service.clone(),
+            target_layout: KeyContext::new(prefix, &service).with_overrides(&target_keys).layout(),
             cascade: directive,
             maintain_reverse: matches!(relation.kind, RelationKind::ManyToMany),
             child_relations,
@@ -468,8 +1471,10 @@ fn cascade_relation_specs_for(
             CascadePolicy::Delete => CascadeDirective::DeleteDependents,
         };
 
-        // Check for cycles
-        if stack.contains(&(inc.source_service.clone(), inc.source_collection.clone())) {
+        let self_reference = is_self_reference(descriptor, &inc.source_service, &inc.source_collection);
+
+        // Check for cycles; a collection pointing at itself is bounded by MAX_SELF_CASCADE_DEPTH instead
+        if !self_reference && stack.contains(&(inc.source_service.clone(), inc.source_collection.clone())) {
             return Err(RepoError::Other {
                 message: Cow::Owned(format!(
                     "cycle detected in cascade chain via belongs_to: {}:{} -> {}:{}",
@@ -479,8 +1484,10 @@ fn cascade_relation_specs_for(
         }
 
         let child_relations = if matches!(inc.cascade, CascadePolicy::Delete) {
-            if let Some(child_desc) = registry::get_descriptor(&inc.source_service, &inc.source_collection) {
-                cascade_relation_specs_for(&child_desc, stack, depth + 1)?
+            if self_reference {
+                self_cascade_specs_for(prefix, descriptor, stack, depth)?
+            } else if let Some(child_desc) = lookup_descriptor(&inc.source_service, &inc.source_collection) {
+                cascade_relation_specs_for(prefix, &child_desc, stack, depth + 1)?
             } else {
                 Vec::new()
             }
@@ -492,8 +1499,12 @@ fn cascade_relation_specs_for(
         let reverse_alias = format!("{}_reverse", inc.alias);
         specs.push(CascadeRelationSpec {
             alias: reverse_alias,
+            target_layout: lookup_descriptor(&inc.source_service, &inc.source_collection)
+                .map_or_else(
+                    || KeyContext::new(prefix, &inc.source_service).layout(),
+                    |source| KeyContext::for_entity(prefix, &source).layout(),
+                ),
             target_collection: Some(inc.source_collection),
-            target_service: Some(inc.source_service),
             cascade: directive,
             maintain_reverse: false,
             child_relations,
@@ -504,6 +1515,27 @@ fn cascade_relation_specs_for(
     Ok(specs)
 }
 
+fn is_self_reference(descriptor: &EntityDescriptor, service: &str, collection: &str) -> bool {
+    descriptor.service == service && descriptor.collection == collection
+}
+
+/// Expand a self-referential cascade one more level, stopping once the collection already
+/// appears `MAX_SELF_CASCADE_DEPTH` times on the stack. Self levels don't count toward
+/// `MAX_CASCADE_DEPTH`, which still guards chains across distinct collections.
+fn self_cascade_specs_for(
+    prefix: &str,
+    descriptor: &EntityDescriptor,
+    stack: &mut Vec<(String, String)>,
+    depth: usize,
+) -> Result<Vec<CascadeRelationSpec>, RepoError> {
+    let current = (descriptor.service.clone(), descriptor.collection.clone());
+    let self_depth = stack.iter().filter(|entry| **entry == current).count();
+    if self_depth >= MAX_SELF_CASCADE_DEPTH {
+        return Ok(Vec::new());
+    }
+    cascade_relation_specs_for(prefix, descriptor, stack, depth)
+}
+
 fn delete_cascades_for_descriptor(
     descriptor: &EntityDescriptor,
     key_context: &KeyContext<'_>,
@@ -512,7 +1544,7 @@ fn delete_cascades_for_descriptor(
     let mut stack = Vec::new();
 
     // Get cascades from both the entity's own declared relations AND incoming belongs_to
-    let specs = cascade_relation_specs_for(descriptor, &mut stack, 0)?;
+    let specs = cascade_relation_specs_for(key_context.prefix, descriptor, &mut stack, 0)?;
     let mut cascades = Vec::new();
     for spec in specs {
         // Determine the relation key based on whether this is a reverse (incoming belongs_to) relation
@@ -526,20 +1558,81 @@ fn delete_cascades_for_descriptor(
             key_context.relation(&spec.alias, entity_id)
         };
 
+        let counter_path = descriptor
+            .relations
+            .iter()
+            .find(|relation| relation.alias == spec.alias)
+            .and_then(|relation| relation.counter.as_ref())
+            .map(|field| format!("$.{field}"));
         cascades.push(DeleteCascadeRelation {
             alias: spec.alias,
             relation_key,
             target_collection: spec.target_collection.clone(),
-            target_service: spec.target_service.clone(),
+            target_layout: spec.target_layout,
             cascade: spec.cascade,
             maintain_reverse: spec.maintain_reverse,
             child_relations: spec.child_relations,
+            counter_path,
         });
     }
 
     Ok(cascades)
 }
 
+/// Parent has-many sets a deleted entity must leave, one per belongs-to relation that a parent
+/// mirrors.
+fn parent_detaches_for(descriptor: &EntityDescriptor, prefix: &str) -> Vec<ParentRelationDetach> {
+    descriptor
+        .relations
+        .iter()
+        .filter(|relation| matches!(relation.kind, RelationKind::BelongsTo))
+        .filter_map(|relation| {
+            let foreign_key = relation.foreign_key.clone()?;
+            let (parent, has_many) = mirrored_has_many(descriptor, relation)?;
+            Some(ParentRelationDetach {
+                foreign_key,
+                parent_layout: KeyContext::for_entity(prefix, &parent).layout(),
+                parent_collection: parent.collection,
+                alias: has_many.alias,
+                counter_path: has_many.counter.map(|field| format!("$.{field}")),
+            })
+        })
+        .collect()
+}
+
+/// The has-many relation on a belongs-to relation's parent that lists `descriptor`'s entities,
+/// with the parent's descriptor.
+///
+/// Among the parent's has-many relations to this collection, the one naming the belongs-to
+/// relation's foreign key wins, then the one sharing its alias; a lone has-many without a
+/// foreign key is taken as is. Several that none of those tell apart mirror nothing.
+fn mirrored_has_many(
+    descriptor: &EntityDescriptor,
+    relation: &RelationDescriptor,
+) -> Option<(EntityDescriptor, RelationDescriptor)> {
+    let parent_service = relation.target_service.as_deref().unwrap_or(&descriptor.service);
+    let parent = lookup_descriptor(parent_service, &relation.target)?;
+    let candidates: Vec<&RelationDescriptor> = parent
+        .relations
+        .iter()
+        .filter(|candidate| {
+            matches!(candidate.kind, RelationKind::HasMany)
+                && candidate.target == descriptor.collection
+                && candidate.target_service.as_deref().unwrap_or(&parent.service) == descriptor.service
+        })
+        .collect();
+    let has_many = candidates
+        .iter()
+        .find(|candidate| candidate.foreign_key.is_some() && candidate.foreign_key == relation.foreign_key)
+        .or_else(|| candidates.iter().find(|candidate| candidate.alias == relation.alias))
+        .or_else(|| match candidates.as_slice() {
+            [only] if only.foreign_key.is_none() => candidates.first(),
+            _ => None,
+        })
+        .map(|has_many| (*has_many).clone())?;
+    Some((parent, has_many))
+}
+
 #[derive(Debug, Clone)]
 pub struct MutationPayload {
     pub entity_id: String,
@@ -557,6 +1650,12 @@ pub enum PatchOpKind {
     Assign(Value),
     Merge(Value),
     Delete,
+    /// Atomically add to a numeric field (`JSON.NUMINCRBY` / `HINCRBYFLOAT`)
+    Increment(f64),
+    /// Append elements to an array field (`JSON.ARRAPPEND`)
+    ArrayAppend(Vec<Value>),
+    /// Remove every occurrence of the given elements from an array field
+    ArrayRemove(Vec<Value>),
 }
 
 #[derive(Debug, Clone)]
@@ -575,12 +1674,54 @@ pub struct MutationPatch {
     pub nested: Vec<NestedMutation>,
     pub idempotency_key: Option<String>,
     pub idempotency_ttl: Option<u64>,
+    /// Condition the stored document must meet, checked atomically with the write
+    pub precondition: Option<Precondition>,
 }
 
 #[derive(Debug, Clone)]
 pub struct CreateResult {
     pub id: String,
     pub responses: Vec<Value>,
+    /// True when the idempotency key was already consumed and this is the original result
+    /// replayed, rather than a new entity.
+    pub replayed: bool,
+}
+
+impl CreateResult {
+    /// Build a result from a create plan's responses, preferring the id the script reports.
+    pub(crate) fn from_responses(entity_id: String, responses: Vec<Value>) -> Self {
+        let id = responses
+            .last()
+            .and_then(|value| value.get("entity_id"))
+            .and_then(|value| value.as_str())
+            .map_or(entity_id, str::to_string);
+        let replayed = responses.last().is_some_and(is_replay);
+        Self { id, responses, replayed }
+    }
+}
+
+/// State of an idempotency key, from [`Repo::idempotency_status`].
+#[derive(Debug, Clone, Default)]
+pub struct IdempotencyStatus {
+    /// Whether a mutation has already run with this key
+    pub consumed: bool,
+    /// When the record lapses and the key can be reused; `None` if it never expires
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    /// The response stored for the original mutation, returned as-is on replay
+    pub response: Option<Value>,
+}
+
+impl IdempotencyStatus {
+    /// The original create's result, if the stored response came from a create.
+    pub fn replayed_create(&self) -> Option<CreateResult> {
+        let response = self.response.as_ref()?;
+        let id = response.get("entity_id")?.as_str()?.to_string();
+        Some(CreateResult {
+            id,
+            responses: vec![response.clone()],
+            replayed: true,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -589,6 +1730,14 @@ pub enum UpsertResult {
     Updated(Vec<Value>),
 }
 
+/// A previous document of a versioned entity, from [`Repo::history`].
+#[derive(Debug, Clone)]
+pub struct EntityVersion<T> {
+    /// `metadata.version` the document had while it was current
+    pub version: u64,
+    pub entity: T,
+}
+
 /// Result of a get_or_create operation.
 /// Contains the entity and whether it was created or found.
 #[derive(Debug, Clone)]
@@ -632,6 +1781,8 @@ pub struct NestedMutation {
     pub alias: String,
     pub descriptor: EntityDescriptor,
     pub payload: MutationPayload,
+    /// Connect an existing entity with the payload's id rather than fail with `AlreadyExists`
+    pub connect_existing: bool,
 }
 
 pub fn link_nested_to_parent(parent_descriptor: &EntityDescriptor, parent_id: &str, nested: &mut [NestedMutation]) {
@@ -664,15 +1815,27 @@ pub fn link_nested_to_parent(parent_descriptor: &EntityDescriptor, parent_id: &s
             continue;
         }
 
-        let child_relation = mutation.descriptor.relations.iter().find(|relation| {
-            matches!(relation.kind, RelationKind::BelongsTo)
-                && relation.target == parent_collection
-                && relation
-                    .target_service
-                    .as_ref()
-                    .map(|svc| svc == &parent_service)
-                    .unwrap_or(true)
-        });
+        let belongs_to: Vec<&RelationDescriptor> = mutation
+            .descriptor
+            .relations
+            .iter()
+            .filter(|relation| {
+                matches!(relation.kind, RelationKind::BelongsTo)
+                    && relation.target == parent_collection
+                    && relation
+                        .target_service
+                        .as_ref()
+                        .map(|svc| svc == &parent_service)
+                        .unwrap_or(true)
+            })
+            .collect();
+        // The has-many's foreign key or alias picks between several belongs-to relations to the parent
+        let child_relation = belongs_to
+            .iter()
+            .find(|relation| parent_relation.foreign_key.is_some() && relation.foreign_key == parent_relation.foreign_key)
+            .or_else(|| belongs_to.iter().find(|relation| relation.alias == parent_relation.alias))
+            .or_else(|| belongs_to.first())
+            .copied();
 
         let Some(child_relation) = child_relation else {
             continue;
@@ -722,6 +1885,9 @@ pub struct RelationPlan {
     pub add: Vec<String>,
     pub remove: Vec<String>,
     pub delete: Vec<String>,
+    pub edges: Vec<RelationEdge>,
+    /// Make `add` the whole relation, disconnecting the current members not in it
+    pub replace: bool,
 }
 
 impl RelationPlan {
@@ -732,6 +1898,8 @@ impl RelationPlan {
             add,
             remove,
             delete: Vec::new(),
+            edges: Vec::new(),
+            replace: false,
         }
     }
 
@@ -747,8 +1915,43 @@ impl RelationPlan {
             add,
             remove,
             delete: Vec::new(),
+            edges: Vec::new(),
+            replace: false,
         }
     }
+
+    /// Connect a single id and attach `edge_data` to the edge.
+    ///
+    /// The payload is stored next to the relation set and read back through
+    /// [`Repo::relation_edges`] or [`Repo::get_related_with_edges`].
+    pub fn connect_with<E>(alias: impl Into<String>, id: impl Into<String>, edge_data: &E) -> Result<Self, ValidationIssue>
+    where
+        E: Serialize + ?Sized,
+    {
+        let alias = alias.into();
+        let member_id = id.into();
+        let data_json = serde_json::to_string(edge_data).map_err(|err| {
+            ValidationIssue::new(
+                format!("relations.{alias}"),
+                "relation.edge_invalid",
+                format!("edge data could not be serialized: {err}"),
+            )
+        })?;
+        let mut plan = Self::new(alias, vec![member_id.clone()], Vec::new());
+        plan.edges.push(RelationEdge { member_id, data_json });
+        Ok(plan)
+    }
+
+    /// Replace the relation with exactly `values`.
+    ///
+    /// The script reads the current members and disconnects those not in `values` in the same
+    /// call, so a concurrent connect can't slip in between. Disconnected members are not
+    /// deleted, whatever the cascade policy.
+    pub fn set(alias: impl Into<String>, values: Vec<String>) -> Self {
+        let mut plan = Self::new(alias, values, Vec::new());
+        plan.replace = true;
+        plan
+    }
 }
 
 fn apply_derived_id(descriptor: &EntityDescriptor, payload: &mut Value) -> Option<String> {
@@ -770,6 +1973,131 @@ fn apply_derived_id(descriptor: &EntityDescriptor, payload: &mut Value) -> Optio
     Some(derived_id)
 }
 
+/// Plan the create of a raw JSON document for tooling that has no compiled entity type, such as
+/// `snugom data import`.
+///
+/// The document goes through the same normalization and validation as [`Repo::create`]: auto
+/// timestamps, the `metadata` object, enum shadow tags, derived ids, field validations, id prefixes, stored
+/// encodings, and unique constraints. Datetime mirrors are computed from the RFC 3339 (or epoch
+/// millisecond) values in the document. Relation fields are stored as given, without touching
+/// relation sets. With `if_absent` the script fails with `already_exists` rather than overwrite.
+pub fn plan_document_create(
+    descriptor: &EntityDescriptor,
+    prefix: &str,
+    document: Value,
+    if_absent: bool,
+) -> Result<(String, MutationPlan), RepoError> {
+    let mut payload = document;
+    let mut mirrors = datetime_mirrors_from_json(descriptor, &payload);
+    ensure_auto_timestamps(descriptor, &mut payload, &mut mirrors, &Default::default(), false);
+    ensure_metadata_object(&mut payload);
+    inject_tag_shadows(descriptor, &mut payload);
+    apply_derived_id(descriptor, &mut payload);
+    validate_entity_json(descriptor, &payload).map_err(RepoError::Validation)?;
+    validate_id_prefix(descriptor, &payload).map_err(RepoError::Validation)?;
+    storage::encode_fields(descriptor, &mut payload)?;
+
+    let id_field = descriptor
+        .id_field
+        .as_deref()
+        .ok_or_else(|| ValidationError::single("id", "missing", "entity id field is not defined"))?;
+    let entity_id = payload
+        .get(id_field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ValidationError::single(id_field, "missing", "entity id must be present"))?
+        .to_string();
+    let key_context = KeyContext::for_entity(prefix, descriptor);
+    let key = key_context.entity(&descriptor.collection, &entity_id);
+    let mut mutation =
+        build_entity_mutation(descriptor, key, key_context.layout(), payload, mirrors, None, None, None, Vec::new())?;
+    mutation.if_absent = if_absent;
+
+    let mut plan = MutationPlan::new();
+    plan.push(MutationCommand::UpsertEntity(mutation));
+    Ok((entity_id, plan))
+}
+
+/// Mirror values for every datetime field of `payload` that has a mirror.
+fn datetime_mirrors_from_json(descriptor: &EntityDescriptor, payload: &Value) -> Vec<DatetimeMirrorValue> {
+    descriptor
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let mirror = field.datetime_mirror.as_ref()?;
+            let millis = payload.get(&field.name).and_then(datetime_millis);
+            Some(DatetimeMirrorValue::new(&field.name, mirror, millis))
+        })
+        .collect()
+}
+
+/// Epoch milliseconds of an RFC 3339 string or a number already in milliseconds.
+fn datetime_millis(value: &Value) -> Option<i64> {
+    match value {
+        Value::String(raw) => chrono::DateTime::parse_from_rfc3339(raw)
+            .ok()
+            .map(|datetime| datetime.timestamp_millis()),
+        Value::Number(number) => number.as_i64(),
+        _ => None,
+    }
+}
+
+/// Top-level fields and relation aliases a patch touches, in order, for its audit entry.
+fn audited_fields(patch: &MutationPatch) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    let touched = patch
+        .operations
+        .iter()
+        .filter_map(|op| op.path.trim_start_matches('$').trim_start_matches('.').split('.').next())
+        .chain(patch.relations.iter().map(|relation| relation.alias.as_str()));
+    for field in touched {
+        if !field.is_empty() && !fields.iter().any(|existing| existing == field) {
+            fields.push(field.to_string());
+        }
+    }
+    fields
+}
+
+/// Run `hook` on a create payload, refreshing the datetime mirrors in case it changed a datetime.
+fn run_before_hook(
+    descriptor: &EntityDescriptor,
+    hook: Option<BeforeHookFn>,
+    payload: &mut Value,
+    mirrors: &mut Vec<DatetimeMirrorValue>,
+) -> Result<(), RepoError> {
+    if let Some(hook) = hook {
+        hook(payload).map_err(RepoError::Validation)?;
+        *mirrors = datetime_mirrors_from_json(descriptor, payload);
+    }
+    Ok(())
+}
+
+/// Patch operations turning `before` into `after` at the top level of the document, for what a
+/// `before_update` hook changed or what a restored version differs in.
+fn hook_patch_operations(descriptor: &EntityDescriptor, before: &Value, after: &Value) -> Vec<PatchOperation> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+    let mirrors = datetime_mirrors_from_json(descriptor, &Value::Object(after.clone()));
+    let mirror_for = |field: &str| mirrors.iter().find(|mirror| mirror.field == field).cloned();
+    let assigned = after
+        .iter()
+        .filter(|(field, value)| before.get(*field) != Some(*value))
+        .map(|(field, value)| PatchOperation {
+            path: format!("$.{field}"),
+            kind: PatchOpKind::Assign(value.clone()),
+            mirror: mirror_for(field),
+        });
+    let deleted = before
+        .keys()
+        .filter(|field| !after.contains_key(*field))
+        .map(|field| PatchOperation {
+            path: format!("$.{field}"),
+            kind: PatchOpKind::Delete,
+            mirror: mirror_for(field),
+        });
+    assigned.chain(deleted).collect()
+}
+
 impl<T> Repo<T>
 where
     T: SnugomModel + SearchEntity,
@@ -777,7 +2105,7 @@ where
     /// Ensure the RediSearch index for this repository exists.
     pub async fn ensure_search_index(&self, conn: &mut ConnectionManager) -> Result<(), RepoError> {
         let definition = T::index_definition(&self.prefix);
-        search::ensure_index(conn, &definition).await
+        self.intercept("ensure_index", &definition.name, search::ensure_index(conn, &definition)).await
     }
 
     /// Execute a search using pre-built parameters.
@@ -787,8 +2115,138 @@ where
         params: SearchParams,
     ) -> Result<SearchResult<T>, RepoError> {
         let definition = T::index_definition(&self.prefix);
+        self.run_search(conn, &definition, &self.descriptor, &params, &T::base_filter()).await
+    }
+
+    /// Stream every hit of `params`, fetching page after page until the results run out.
+    ///
+    /// Pages hold `params.page_size` hits, capped at the largest page a query may ask for, and
+    /// are read by offset from `params.page` on; documents written while the stream is read can
+    /// shift hits between pages, so sort by a stable field for exports.
+    pub fn search_all<'a>(
+        &'a self,
+        conn: &'a mut ConnectionManager,
+        mut params: SearchParams,
+    ) -> impl Stream<Item = Result<T, RepoError>> + Unpin + 'a {
+        params.page_size = params.page_size.clamp(1, search::MAX_PAGE_SIZE);
+        params.page = params.page.max(1);
+        let pages = stream::try_unfold(Some((conn, params)), move |state| async move {
+            let Some((conn, params)) = state else {
+                return Ok::<_, RepoError>(None);
+            };
+            let page = self.search(conn, params.clone()).await?;
+            if page.items.is_empty() {
+                return Ok(None);
+            }
+            let next = page.has_more().then(|| {
+                let page = params.page + 1;
+                (conn, SearchParams { page, ..params })
+            });
+            Ok(Some((stream::iter(page.items.into_iter().map(Ok)), next)))
+        });
+        Box::pin(pages.try_flatten())
+    }
+
+    /// The distinct values of the TAG field `field` across this repository's documents, most
+    /// common first and at most `limit` of them, each with its document count (`FT.AGGREGATE`).
+    ///
+    /// Meant for filter dropdowns built from the data; see [`search::distinct_values`] for how
+    /// enum and casefold fields are read.
+    pub async fn distinct(
+        &self,
+        conn: &mut ConnectionManager,
+        field: &str,
+        limit: usize,
+    ) -> Result<Vec<DistinctValue>, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        let query = SearchParams::new().build_query(&T::base_filter());
+        let read = search::distinct_values(conn, &definition, field, &query, limit);
+        self.intercept("distinct", &definition.name, read).await
+    }
+
+    /// Reduce the NUMERIC field `field` over the documents matching `condition` with `op`
+    /// (`MIN`, `MAX`, `AVG` or `SUM` via `FT.AGGREGATE`), without fetching them.
+    ///
+    /// Returns `None` when no matching document has a value for `field`.
+    pub async fn aggregate_numeric(
+        &self,
+        conn: &mut ConnectionManager,
+        field: &str,
+        op: AggOp,
+        condition: FilterCondition,
+    ) -> Result<Option<f64>, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        let query = SearchParams::new().with_condition(condition).build_query(&T::base_filter());
+        let read = search::aggregate_numeric(conn, &definition, field, op, &query);
+        self.intercept("aggregate", &definition.name, read).await
+    }
+
+    /// Count the documents matching `condition` per day, week or month of the datetime field
+    /// `field`, oldest bucket first; empty buckets are left out.
+    pub async fn time_buckets(
+        &self,
+        conn: &mut ConnectionManager,
+        field: &str,
+        bucket: TimeBucket,
+        condition: FilterCondition,
+    ) -> Result<Vec<BucketCount>, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        let query = SearchParams::new().with_condition(condition).build_query(&T::base_filter());
+        let read = search::time_buckets(conn, &definition, field, bucket, &query);
+        self.intercept("time_buckets", &definition.name, read).await
+    }
+
+    /// Search and deserialize each hit as view `V`, fetching only `V::fields()`.
+    pub async fn search_view<V>(&self, conn: &mut ConnectionManager, query: SearchQuery) -> Result<SearchResult<V>, RepoError>
+    where
+        V: ViewModel<T>,
+    {
+        let params = query
+            .with_text_query(
+                T::allowed_sorts(),
+                T::default_sort(),
+                |descriptor| T::map_filter(descriptor),
+                T::text_search_fields(),
+            )?
+            .with_return_fields(V::fields().iter().copied());
+        let definition = T::index_definition(&self.prefix);
+        self.run_search(conn, &definition, &self.descriptor, &params, &T::base_filter()).await
+    }
+
+    /// Read statistics and the live schema of this repository's index (`FT.INFO`).
+    pub async fn index_info(&self, conn: &mut ConnectionManager) -> Result<IndexInfo, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        self.intercept("index_info", &definition.name, search::index_info(conn, &definition.name)).await
+    }
+
+    /// Show how RediSearch parses the query `params` would run, via `FT.EXPLAIN`.
+    ///
+    /// Useful for checking how a combination of filter conditions is turned into
+    /// intersections and unions before looking at timings with [`Repo::profile_search`].
+    pub async fn explain(
+        &self,
+        conn: &mut ConnectionManager,
+        params: SearchParams,
+    ) -> Result<QueryExplanation, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        let params = self.search_defaults.apply(&params);
+        let base_filter = T::base_filter();
+        let read = search::explain_index_search(conn, &definition, &params, &base_filter);
+        self.intercept("explain", &definition.name, read).await
+    }
+
+    /// Run a search under `FT.PROFILE`, returning its results with per-iterator and
+    /// per-stage timings.
+    pub async fn profile_search(
+        &self,
+        conn: &mut ConnectionManager,
+        params: SearchParams,
+    ) -> Result<SearchProfile<T>, RepoError> {
+        let definition = T::index_definition(&self.prefix);
+        let params = self.search_defaults.apply(&params);
         let base_filter = T::base_filter();
-        search::execute_search(conn, definition.name.as_str(), &params, &base_filter).await
+        let read = search::profile_index_search(conn, &definition, &self.descriptor, &params, &base_filter);
+        self.intercept("profile", &definition.name, read).await
     }
 
     /// Convenience helper mirroring the legacy manager's `with_text_query` flow.
@@ -813,6 +2271,10 @@ where
 {
     descriptor: EntityDescriptor,
     prefix: String,
+    slow_queries: Option<SlowQueryLog>,
+    search_defaults: SearchDefaults,
+    audit: Option<AuditSettings>,
+    interceptor: Option<SharedInterceptor>,
     _marker: PhantomData<T>,
 }
 
@@ -825,25 +2287,147 @@ where
         Self {
             descriptor: T::entity_descriptor(),
             prefix: prefix.into(),
+            slow_queries: None,
+            search_defaults: SearchDefaults::default(),
+            audit: None,
+            interceptor: None,
             _marker: PhantomData,
         }
     }
 
+    /// Record searches run through this repository that exceed the log's threshold.
+    pub fn with_slow_query_log(mut self, log: SlowQueryLog) -> Self {
+        self.slow_queries = Some(log);
+        self
+    }
+
+    /// Send `defaults` with searches whose params leave the dialect or timeout unset.
+    pub fn with_search_defaults(mut self, defaults: SearchDefaults) -> Self {
+        self.search_defaults = defaults;
+        self
+    }
+
+    /// Append an entry to the entity's audit stream after each create, update, and delete made
+    /// through the `*_with_conn` methods, `upsert`, and `get_or_create`.
+    pub fn with_audit(mut self, settings: AuditSettings) -> Self {
+        self.audit = Some(settings);
+        self
+    }
+
+    /// Report each read, search, and mutation plan this repository sends to `interceptor`.
+    pub fn with_interceptor(mut self, interceptor: SharedInterceptor) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    /// Await one Redis interaction, reporting it to the interceptor when one is set.
+    async fn intercept<R, E>(
+        &self,
+        operation: &'static str,
+        target: &str,
+        future: impl Future<Output = Result<R, E>>,
+    ) -> Result<R, RepoError>
+    where
+        E: Into<RepoError>,
+    {
+        interceptor::observe(self.interceptor.as_ref(), operation, target, future).await
+    }
+
+    /// The interceptor this repository reports to, if any.
+    pub(crate) fn interceptor(&self) -> Option<&SharedInterceptor> {
+        self.interceptor.as_ref()
+    }
+
     pub fn descriptor(&self) -> &EntityDescriptor {
         &self.descriptor
     }
 
+    /// Key of the list holding the previous documents of `entity_id`, for versioned entities.
+    pub fn history_key(&self, entity_id: &str) -> String {
+        self.key_context().history(&self.descriptor.collection, entity_id)
+    }
+
+    /// Where the patch and upsert scripts copy the document before an update, when versioned.
+    fn history_record(&self, entity_id: &str) -> Option<HistoryRecord> {
+        self.descriptor.keep_versions.map(|keep| HistoryRecord {
+            key: self.history_key(entity_id),
+            keep,
+        })
+    }
+
+    /// Key of the stream holding the audit entries of `entity_id`.
+    pub fn audit_key(&self, entity_id: &str) -> String {
+        self.key_context().audit(&self.descriptor.collection, entity_id)
+    }
+
+    /// Append to the entity's audit stream when auditing is on.
+    ///
+    /// The write has already committed by now, so a failed append is not returned as an error;
+    /// with the `tracing` feature it is reported as a warning.
+    pub(crate) async fn record_audit(
+        &self,
+        conn: &mut ConnectionManager,
+        entity_id: &str,
+        action: AuditAction,
+        fields: &[String],
+    ) {
+        let Some(settings) = &self.audit else {
+            return;
+        };
+        let key = self.audit_key(entity_id);
+        let record = audit::record(conn, &key, settings, action, fields);
+        if let Err(_err) = self.intercept("audit", &key, record).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(key = %key, error = %_err, "failed to append audit entry");
+        }
+    }
+
     pub fn key_context(&self) -> KeyContext<'_> {
-        KeyContext::new(&self.prefix, &self.descriptor.service)
+        KeyContext::for_entity(&self.prefix, &self.descriptor)
     }
 
     /// Check if an entity with the given ID exists.
     pub async fn exists(&self, conn: &mut ConnectionManager, entity_id: &str) -> Result<bool, RepoError> {
         let key = self.entity_key(entity_id);
-        let exists: i64 = cmd("EXISTS").arg(&key).query_async(conn).await?;
+        let exists: i64 = self.intercept("exists", &key, cmd("EXISTS").arg(&key).query_async(conn)).await?;
         Ok(exists == 1)
     }
 
+    /// Run a search, recording it in the slow query log when one is configured.
+    async fn run_search<U>(
+        &self,
+        conn: &mut ConnectionManager,
+        definition: &IndexDefinition,
+        descriptor: &EntityDescriptor,
+        params: &SearchParams,
+        base_filter: &str,
+    ) -> Result<SearchResult<U>, RepoError>
+    where
+        U: DeserializeOwned,
+    {
+        let params = self.search_defaults.apply(params);
+        let started = Instant::now();
+        let search = search::execute_index_search(conn, definition, descriptor, &params, base_filter);
+        let result = self.intercept("search", &definition.name, search).await;
+        if let Some(log) = &self.slow_queries {
+            log.observe(&definition.name, &params, base_filter, started.elapsed());
+        }
+        result
+    }
+
+    /// Reject operations whose scripts only speak RedisJSON.
+    fn ensure_json_storage(&self, operation: &str) -> Result<(), RepoError> {
+        if self.descriptor.storage.is_hash() {
+            return Err(RepoError::InvalidRequest {
+                message: format!(
+                    "{operation} is not supported for `{}`, which uses hash storage",
+                    self.descriptor.collection
+                ),
+            });
+        }
+        Ok(())
+    }
+
     pub fn entity_key(&self, entity_id: &str) -> String {
         self.key_context().entity(&self.descriptor.collection, entity_id)
     }
@@ -868,11 +2452,40 @@ where
         self.key_context().relation_reverse(alias, right_id)
     }
 
+    /// Look up an idempotency key: whether a mutation already consumed it, when the record
+    /// expires, and the response that a replay will return.
+    pub async fn idempotency_status(
+        &self,
+        conn: &mut ConnectionManager,
+        idempotency_key: &str,
+    ) -> Result<IdempotencyStatus, RepoError> {
+        let key = self.key_context().idempotency(idempotency_key);
+        let mut pipe = redis::pipe();
+        pipe.cmd("GET").arg(&key).cmd("TTL").arg(&key);
+        let (stored, ttl): (Option<String>, i64) =
+            self.intercept("idempotency_status", &key, pipe.query_async(conn)).await?;
+        let Some(stored) = stored else {
+            return Ok(IdempotencyStatus::default());
+        };
+        let response = serde_json::from_str(&stored).map_err(|err| RepoError::Other {
+            message: Cow::Owned(format!("failed to parse idempotency record: {err}")),
+        })?;
+        Ok(IdempotencyStatus {
+            consumed: true,
+            expires_at: (ttl >= 0).then(|| Utc::now() + chrono::Duration::seconds(ttl)),
+            response: Some(response),
+        })
+    }
+
     pub async fn execute<E>(&self, executor: &mut E, plan: MutationPlan) -> Result<Vec<Value>, RepoError>
     where
         E: MutationExecutor + ?Sized,
     {
-        executor.execute(plan).await
+        let Some(first) = plan.commands.first() else {
+            return executor.execute(plan).await;
+        };
+        let (operation, key) = (first.operation(), first.key().to_string());
+        self.intercept(operation, &key, executor.execute(plan)).await
     }
 
     pub async fn create<E, B>(&self, executor: &mut E, builder: B) -> Result<CreateResult, RepoError>
@@ -881,66 +2494,157 @@ where
         B: MutationPayloadBuilder,
         B::Entity: EntityMetadata,
     {
-        let MutationPayload {
-            mut entity_id,
-            mut payload,
-            mirrors,
-            relations,
-            nested,
-            idempotency_key,
-            idempotency_ttl,
-            managed_overrides,
-        } = builder.into_payload()?;
-        let overrides: ::std::collections::BTreeSet<_> = managed_overrides.into_iter().collect();
-        let mut mirrors = mirrors;
-        ensure_auto_timestamps(self.descriptor(), &mut payload, &mut mirrors, &overrides, false);
-        ensure_metadata_object(&mut payload);
-        inject_enum_tag_shadows(self.descriptor(), &mut payload);
-        if let Some(derived_id) = apply_derived_id(self.descriptor(), &mut payload) {
-            entity_id = derived_id;
-        }
-        if let Err(err) = validate_entity_json(self.descriptor(), &payload) {
-            return Err(RepoError::Validation(err));
-        }
-        let mut nested = nested;
-        link_nested_to_parent(self.descriptor(), &entity_id, &mut nested);
-        self.execute_nested(executor, nested).await?;
-        let key = self.entity_key(&entity_id);
-        let key_context = self.key_context();
-        let (relation_mutations, pending_deletes) =
-            Self::relation_mutations_for(self.descriptor(), &key_context, Some(&entity_id), relations)?;
-        let mut plan = MutationPlan::new();
-        let mutation = build_entity_mutation(
-            self.descriptor(),
-            key,
-            payload,
-            mirrors,
-            None,
-            idempotency_key,
-            idempotency_ttl,
-            relation_mutations,
-        )?;
-        plan.push(MutationCommand::UpsertEntity(mutation));
-        Self::enqueue_relation_deletes_for_context(&key_context, self.descriptor(), pending_deletes, &mut plan)?;
-        let responses = self.execute(executor, plan).await?;
-        if let Some(actual_id) = responses
-            .last()
-            .and_then(|value| value.get("entity_id"))
-            .and_then(|value| value.as_str())
-        {
-            entity_id = actual_id.to_string();
+        self.create_from_payload(executor, builder.into_payload()?, false).await
+    }
+
+    /// Create an entity, failing with `RepoError::AlreadyExists` if its key is present.
+    ///
+    /// Unlike [`Repo::create`], which writes over an existing document with the same id, the
+    /// check runs inside the mutation script, so it holds even when callers race on a
+    /// user-supplied id.
+    ///
+    /// Nested creates are rejected with `RepoError::InvalidRequest`: they run before the parent's
+    /// check, so an existing parent would leave them orphaned. [`Repo::create_with_conn`] checks
+    /// for the parent first and accepts them.
+    pub async fn create_if_absent<E, B>(&self, executor: &mut E, builder: B) -> Result<CreateResult, RepoError>
+    where
+        E: MutationExecutor + ?Sized,
+        B: MutationPayloadBuilder,
+        B::Entity: EntityMetadata,
+    {
+        let payload = builder.into_payload()?;
+        if !payload.nested.is_empty() {
+            return Err(RepoError::InvalidRequest {
+                message: format!(
+                    "create_if_absent on `{}` cannot run nested creates; use create_with_conn",
+                    self.descriptor.collection
+                ),
+            });
         }
-        Ok(CreateResult {
-            id: entity_id,
-            responses,
-        })
+        self.create_from_payload(executor, payload, true).await
+    }
+
+    /// Build the mutation plan a create would run, without touching Redis.
+    ///
+    /// Nested creates are not included; they run as separate plans before the parent.
+    pub fn plan_create<B>(&self, builder: B) -> Result<MutationPlan, RepoError>
+    where
+        B: MutationPayloadBuilder,
+        B::Entity: EntityMetadata,
+    {
+        let (_, plan, _, _) = self.prepare_create(builder.into_payload()?)?;
+        Ok(plan)
     }
 
     /// Internal method to create from an already-validated payload.
-    async fn create_from_payload<E>(&self, executor: &mut E, payload: MutationPayload) -> Result<CreateResult, RepoError>
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snugom.create",
+            skip_all,
+            err(Display),
+            fields(
+                collection = %self.descriptor.collection,
+                key = tracing::field::Empty,
+                elapsed_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn create_from_payload<E>(
+        &self,
+        executor: &mut E,
+        payload: MutationPayload,
+        if_absent: bool,
+    ) -> Result<CreateResult, RepoError>
     where
         E: MutationExecutor + ?Sized,
     {
+        telemetry::observe("create", &self.descriptor.collection, async {
+            let slug = self.suffixed_slug(&payload);
+            let retry_payload = slug.as_ref().map(|_| MutationPayload {
+                nested: Vec::new(),
+                ..payload.clone()
+            });
+            let (entity_id, mut plan, nested, mut document) = self.prepare_create(payload)?;
+            telemetry::record("key", self.entity_key(&entity_id).as_str());
+            if if_absent {
+                plan.set_if_absent();
+            }
+            self.execute_nested(executor, nested).await?;
+
+            let mut attempt = 1;
+            loop {
+                match self.execute(executor, plan).await {
+                    Ok(responses) => {
+                        if let (Some(hook), Some(document)) = (self.descriptor().hooks.after_create, &document) {
+                            hook(document);
+                        }
+                        return Ok(CreateResult::from_responses(entity_id, responses));
+                    }
+                    Err(RepoError::UniqueConstraintViolation { fields, .. })
+                        if attempt < MAX_SLUG_ATTEMPTS
+                            && let (Some((field, base)), Some(retry_payload)) = (&slug, &retry_payload)
+                            && fields.len() == 1
+                            && &fields[0] == field =>
+                    {
+                        attempt += 1;
+                        let mut retry = retry_payload.clone();
+                        if let Some(object) = retry.payload.as_object_mut() {
+                            object.insert(field.clone(), Value::String(crate::slug::with_suffix(base, attempt)));
+                        }
+                        (_, plan, _, document) = self.prepare_create(retry)?;
+                        if if_absent {
+                            plan.set_if_absent();
+                        }
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        })
+        .await
+    }
+
+    /// The field and value of a `slug(from = "...", suffix)` field in a create payload, so a
+    /// collision can be retried under a suffixed slug.
+    fn suffixed_slug(&self, payload: &MutationPayload) -> Option<(String, String)> {
+        let field = self
+            .descriptor
+            .fields
+            .iter()
+            .find(|field| field.slug.as_ref().is_some_and(|slug| slug.suffix))?;
+        let value = payload.payload.get(&field.name)?.as_str()?;
+        Some((field.name.clone(), value.to_string()))
+    }
+
+    /// Prepare a create that can share a pipeline with other creates, after running the
+    /// entity's async validators.
+    ///
+    /// Payloads with nested creates must go through [`Repo::create_payload_with_conn`]
+    /// instead, since those creates run before the parent. The returned plan already has the
+    /// atomic existence check enabled.
+    pub(crate) async fn prepare_bulk_create(
+        &self,
+        conn: &mut ConnectionManager,
+        payload: MutationPayload,
+    ) -> Result<(String, MutationPlan), RepoError> {
+        debug_assert!(payload.nested.is_empty(), "nested creates cannot be pipelined");
+        run_async_validators(self.descriptor(), conn, &payload.payload, None)
+            .await
+            .map_err(RepoError::Validation)?;
+        let (entity_id, mut plan, _, _) = self.prepare_create(payload)?;
+        plan.set_if_absent();
+        Ok((entity_id, plan))
+    }
+
+    /// Normalize and validate a create payload, returning the entity id, its plan, and the
+    /// nested mutations that must run first.
+    ///
+    /// Runs the `before_create` hook; when the entity has an `after_create` hook, the validated
+    /// document is returned for it as well.
+    fn prepare_create(
+        &self,
+        payload: MutationPayload,
+    ) -> Result<(String, MutationPlan, Vec<NestedMutation>, Option<Value>), RepoError> {
         let MutationPayload {
             mut entity_id,
             mut payload,
@@ -955,16 +2659,18 @@ where
         let mut mirrors = mirrors;
         ensure_auto_timestamps(self.descriptor(), &mut payload, &mut mirrors, &overrides, false);
         ensure_metadata_object(&mut payload);
-        inject_enum_tag_shadows(self.descriptor(), &mut payload);
+        run_before_hook(self.descriptor(), self.descriptor().hooks.before_create, &mut payload, &mut mirrors)?;
+        inject_tag_shadows(self.descriptor(), &mut payload);
         if let Some(derived_id) = apply_derived_id(self.descriptor(), &mut payload) {
             entity_id = derived_id;
         }
         if let Err(err) = validate_entity_json(self.descriptor(), &payload) {
             return Err(RepoError::Validation(err));
         }
+        let document = self.descriptor().hooks.after_create.map(|_| payload.clone());
+        storage::encode_fields(self.descriptor(), &mut payload)?;
         let mut nested = nested;
         link_nested_to_parent(self.descriptor(), &entity_id, &mut nested);
-        self.execute_nested(executor, nested).await?;
         let key = self.entity_key(&entity_id);
         let key_context = self.key_context();
         let (relation_mutations, pending_deletes) =
@@ -973,6 +2679,7 @@ where
         let mutation = build_entity_mutation(
             self.descriptor(),
             key,
+            key_context.layout(),
             payload,
             mirrors,
             None,
@@ -982,20 +2689,22 @@ where
         )?;
         plan.push(MutationCommand::UpsertEntity(mutation));
         Self::enqueue_relation_deletes_for_context(&key_context, self.descriptor(), pending_deletes, &mut plan)?;
-        let responses = self.execute(executor, plan).await?;
-        if let Some(actual_id) = responses
-            .last()
-            .and_then(|value| value.get("entity_id"))
-            .and_then(|value| value.as_str())
-        {
-            entity_id = actual_id.to_string();
-        }
-        Ok(CreateResult {
-            id: entity_id,
-            responses,
-        })
+        Ok((entity_id, plan, nested, document))
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snugom.delete",
+            skip_all,
+            err(Display),
+            fields(
+                collection = %self.descriptor.collection,
+                key = %self.entity_key(entity_id),
+                elapsed_ms = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn delete<E>(
         &self,
         executor: &mut E,
@@ -1005,50 +2714,120 @@ where
     where
         E: MutationExecutor + ?Sized,
     {
+        telemetry::observe("delete", &self.descriptor.collection, async {
+            let plan = self.prepare_delete(entity_id, expected_version)?;
+            self.execute(executor, plan).await
+        })
+        .await
+    }
+
+    /// The plan of a delete, cascades included.
+    pub(crate) fn prepare_delete(&self, entity_id: &str, expected_version: Option<u64>) -> Result<MutationPlan, RepoError> {
         let key_context = self.key_context();
         let key = key_context.entity(&self.descriptor.collection, entity_id);
         let cascades = delete_cascades_for_descriptor(self.descriptor(), &key_context, entity_id)?;
         let unique_constraints = unique_constraint_definitions_for(self.descriptor());
-        let delete = build_entity_delete(key, expected_version, cascades, unique_constraints);
+        let mut delete = build_entity_delete(key, key_context.layout(), expected_version, cascades, unique_constraints);
+        delete.parents = parent_detaches_for(self.descriptor(), key_context.prefix);
         let mut plan = MutationPlan::new();
         plan.push(MutationCommand::DeleteEntity(delete));
-        self.execute(executor, plan).await
+        Ok(plan)
+    }
+
+    pub async fn update_patch<E, B>(&self, executor: &mut E, builder: B) -> Result<Vec<Value>, RepoError>
+    where
+        E: MutationExecutor + ?Sized,
+        B: UpdatePatchBuilder,
+        B::Entity: EntityMetadata,
+    {
+        let patch = builder.into_patch()?;
+        self.execute_patch(executor, patch).await
+    }
+
+    /// Bump `metadata.version` and set `updated_at` fields to now, changing nothing else.
+    ///
+    /// A cheap liveness marker for long-running workflows: no hooks, validation, history, or
+    /// audit entry. Fails with `RepoError::NotFound` when the entity doesn't exist.
+    pub async fn touch<E>(&self, executor: &mut E, entity_id: &str) -> Result<Vec<Value>, RepoError>
+    where
+        E: MutationExecutor + ?Sized,
+        T: EntityMetadata,
+    {
+        let mut plan = self.prepare_patch(MutationPatch {
+            entity_id: entity_id.to_string(),
+            expected_version: None,
+            operations: Vec::new(),
+            relations: Vec::new(),
+            nested: Vec::new(),
+            idempotency_key: None,
+            idempotency_ttl: None,
+            precondition: None,
+        })?;
+        for command in &mut plan.commands {
+            if let MutationCommand::PatchEntity(patch) = command {
+                patch.touch = true;
+                patch.history = None;
+            }
+        }
+        telemetry::observe("touch", &self.descriptor.collection, self.execute(executor, plan)).await
     }
 
-    pub async fn update_patch<E, B>(&self, executor: &mut E, builder: B) -> Result<Vec<Value>, RepoError>
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snugom.patch",
+            skip_all,
+            err(Display),
+            fields(
+                collection = %self.descriptor.collection,
+                key = %self.entity_key(&patch.entity_id),
+                elapsed_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn execute_patch<E>(&self, executor: &mut E, patch: MutationPatch) -> Result<Vec<Value>, RepoError>
     where
         E: MutationExecutor + ?Sized,
-        B: UpdatePatchBuilder,
-        B::Entity: EntityMetadata,
+        T: EntityMetadata,
     {
-        let patch = builder.into_patch()?;
-        self.execute_patch(executor, patch).await
+        telemetry::observe("patch", &self.descriptor.collection, self.apply_patch(executor, patch)).await
     }
 
-    async fn execute_patch<E>(&self, executor: &mut E, patch: MutationPatch) -> Result<Vec<Value>, RepoError>
+    async fn apply_patch<E>(&self, executor: &mut E, mut patch: MutationPatch) -> Result<Vec<Value>, RepoError>
     where
         E: MutationExecutor + ?Sized,
         T: EntityMetadata,
+    {
+        if patch.operations.is_empty() && patch.relations.is_empty() && patch.nested.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if !patch.nested.is_empty() {
+            let mut nested = ::std::mem::take(&mut patch.nested);
+            link_nested_to_parent(self.descriptor(), &patch.entity_id, &mut nested);
+            self.execute_nested(executor, nested).await?;
+        }
+
+        let plan = self.prepare_patch(patch)?;
+        self.execute(executor, plan).await
+    }
+
+    /// Validate a patch and build its plan. Nested creates are left out; they must have run already.
+    fn prepare_patch(&self, patch: MutationPatch) -> Result<MutationPlan, RepoError>
+    where
+        T: EntityMetadata,
     {
         let MutationPatch {
             entity_id,
             expected_version,
             mut operations,
             relations,
-            mut nested,
+            nested: _,
             idempotency_key,
             idempotency_ttl,
+            precondition,
         } = patch;
 
-        if operations.is_empty() && relations.is_empty() && nested.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        if !nested.is_empty() {
-            link_nested_to_parent(self.descriptor(), &entity_id, &mut nested);
-            self.execute_nested(executor, ::std::mem::take(&mut nested)).await?;
-        }
-
         let key_context = self.key_context();
         let key = key_context.entity(&self.descriptor.collection, &entity_id);
         let (relation_mutations, pending_deletes) =
@@ -1070,22 +2849,40 @@ where
                     ))
                 })?;
 
-            if descriptor_field.is_id {
+            ensure_patchable(descriptor_field)?;
+
+            if matches!(op.kind, PatchOpKind::Delete) && !descriptor_field.optional {
                 return Err(RepoError::Validation(ValidationError::single(
                     field_name,
-                    "patch.immutable_field",
-                    "cannot patch identifier field",
+                    "patch.non_optional_delete",
+                    "field cannot be deleted because it is not optional",
                 )));
             }
 
-            if matches!(op.kind, PatchOpKind::Delete) && !descriptor_field.optional {
+            if matches!(op.kind, PatchOpKind::Merge(_)) {
+                self.ensure_json_storage("merge patches")?;
+            }
+
+            if descriptor_field.compression.is_some() && matches!(op.kind, PatchOpKind::Merge(_)) {
                 return Err(RepoError::Validation(ValidationError::single(
                     field_name,
-                    "patch.non_optional_delete",
-                    "field cannot be deleted because it is not optional",
+                    "patch.compressed_merge",
+                    "compressed fields can only be assigned as a whole",
                 )));
             }
 
+            if let PatchOpKind::Increment(by) = op.kind {
+                ensure_incrementable(self.descriptor(), descriptor_field, by)?;
+            }
+
+            if let PatchOpKind::ArrayAppend(elements) | PatchOpKind::ArrayRemove(elements) = &op.kind {
+                self.ensure_json_storage("array patches")?;
+                ensure_array_operable(self.descriptor(), descriptor_field)?;
+                if matches!(op.kind, PatchOpKind::ArrayAppend(_)) {
+                    validation_issues.extend(validate_array_elements(descriptor_field, elements));
+                }
+            }
+
             if let PatchOpKind::Assign(value) = &op.kind {
                 validation_issues.extend(validate_field_assignment(descriptor_field, value));
             }
@@ -1097,6 +2894,7 @@ where
 
         // Build unique constraint checks for fields being patched
         let unique_constraints = build_patch_unique_constraint_checks(self.descriptor(), &operations);
+        encode_patch_assignments(self.descriptor(), &mut operations)?;
 
         for field in &self.descriptor.fields {
             if !field.auto_updated {
@@ -1123,10 +2921,11 @@ where
         }
 
         // Inject shadow tag operations for any enum fields being patched
-        inject_enum_tag_shadow_operations(self.descriptor(), &mut operations);
+        inject_tag_shadow_operations(self.descriptor(), &mut operations);
 
-        let patch_command = build_entity_patch(
+        let mut patch_command = build_entity_patch(
             key,
+            self.key_context().layout(),
             Some(entity_id.clone()),
             expected_version,
             operations,
@@ -1135,13 +2934,54 @@ where
             relation_mutations,
             unique_constraints,
         );
+        patch_command.lua_check = self.descriptor.lua_check.clone();
+        patch_command.history = self.history_record(&entity_id);
+        patch_command.precondition = precondition;
+        patch_command.immutable_paths = self
+            .descriptor
+            .fields
+            .iter()
+            .filter(|field| field.immutable)
+            .map(|field| format!("$.{}", field.name))
+            .collect();
+        // The array an append or removal leaves is only known to the script, which checks its length
+        for operation in &mut patch_command.operations {
+            if matches!(operation.op_type, PatchOperationType::ArrayAppend | PatchOperationType::ArrayRemove) {
+                let field_name = operation.path.strip_prefix("$.").unwrap_or(&operation.path);
+                operation.length = self
+                    .descriptor
+                    .fields
+                    .iter()
+                    .find(|field| field.name == field_name)
+                    .and_then(array_length_bounds);
+            }
+        }
+        if self.descriptor.storage.is_hash() {
+            patch_command.hash = true;
+            for operation in &mut patch_command.operations {
+                operation.hash_value = operation.value.as_ref().and_then(storage::encode_hash_value);
+            }
+        }
 
         let mut plan = MutationPlan::new();
         plan.push(MutationCommand::PatchEntity(patch_command));
         Self::enqueue_relation_deletes_for_context(&key_context, self.descriptor(), pending_deletes, &mut plan)?;
-        self.execute(executor, plan).await
+        Ok(plan)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snugom.relations",
+            skip_all,
+            err(Display),
+            fields(
+                collection = %self.descriptor.collection,
+                relations = relations.len(),
+                elapsed_ms = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn mutate_relations<E>(
         &self,
         executor: &mut E,
@@ -1150,21 +2990,30 @@ where
     where
         E: MutationExecutor + ?Sized,
     {
+        telemetry::observe("relation_mutation", &self.descriptor.collection, async {
+            let plan = self.prepare_relations(relations)?;
+            if plan.is_empty() {
+                return Ok(Vec::new());
+            }
+            self.execute(executor, plan).await
+        })
+        .await
+    }
+
+    /// The plan of standalone relation changes; empty when there is nothing to change.
+    pub(crate) fn prepare_relations(&self, relations: Vec<RelationPlan>) -> Result<MutationPlan, RepoError> {
+        let mut plan = MutationPlan::new();
         if relations.is_empty() {
-            return Ok(Vec::new());
+            return Ok(plan);
         }
         let key_context = self.key_context();
         let (relation_mutations, pending_deletes) =
             Self::relation_mutations_for(self.descriptor(), &key_context, None, relations).map_err(RepoError::from)?;
-        if relation_mutations.is_empty() && pending_deletes.is_empty() {
-            return Ok(Vec::new());
-        }
-        let mut plan = MutationPlan::new();
         for relation in relation_mutations {
             plan.push(MutationCommand::MutateRelations(relation));
         }
         Self::enqueue_relation_deletes_for_context(&key_context, self.descriptor(), pending_deletes, &mut plan)?;
-        self.execute(executor, plan).await
+        Ok(plan)
     }
 
     /// Create an entity, failing if it already exists.
@@ -1176,20 +3025,69 @@ where
         B: MutationPayloadBuilder,
         B::Entity: EntityMetadata,
     {
-        // Convert builder to payload to get the entity_id for existence check
-        let payload = builder.into_payload()?;
+        self.create_payload_with_conn(conn, builder.into_payload()?).await
+    }
+
+    /// Validate a create as [`Repo::create_with_conn`] would and build its plan for a
+    /// [`UnitOfWork`](crate::UnitOfWork), without writing anything.
+    pub(crate) async fn stage_create(
+        &self,
+        conn: &mut ConnectionManager,
+        payload: MutationPayload,
+    ) -> Result<StagedWrite, RepoError> {
+        if !payload.nested.is_empty() {
+            return Err(RepoError::InvalidRequest {
+                message: "nested creates cannot be staged in a unit of work".to_string(),
+            });
+        }
+        run_async_validators(self.descriptor(), conn, &payload.payload, None)
+            .await
+            .map_err(RepoError::Validation)?;
+        let (entity_id, mut plan, _, document) = self.prepare_create(payload)?;
+        plan.set_if_absent();
+        Ok(StagedWrite {
+            plan,
+            audit_key: self.audit_key(&entity_id),
+            action: AuditAction::Create,
+            fields: Vec::new(),
+            after: self.descriptor().hooks.after_create.zip(document),
+            entity_id,
+        })
+    }
+
+    /// [`Repo::create_with_conn`] for a payload that has already been built.
+    pub(crate) async fn create_payload_with_conn(
+        &self,
+        conn: &mut ConnectionManager,
+        payload: MutationPayload,
+    ) -> Result<CreateResult, RepoError> {
         let entity_id = &payload.entity_id;
 
-        // Check if entity already exists
+        // A consumed idempotency key replays the original result, before nested creates run
+        // or an explicit id trips the existence check
+        if let Some(key) = &payload.idempotency_key
+            && let Some(original) = self.idempotency_status(conn, key).await?.replayed_create()
+        {
+            return Ok(original);
+        }
+
+        // Fail fast before nested creates run; the script re-checks atomically
         if self.exists(conn, entity_id).await? {
             return Err(RepoError::AlreadyExists {
                 entity_id: entity_id.clone(),
             });
         }
 
-        // Proceed with create
+        run_async_validators(self.descriptor(), conn, &payload.payload, None)
+            .await
+            .map_err(RepoError::Validation)?;
+
         let mut executor = RedisExecutor::new(conn);
-        self.create_from_payload(&mut executor, payload).await
+        let created = self.create_from_payload(&mut executor, payload, true).await?;
+        if !created.replayed {
+            self.record_audit(conn, &created.id, AuditAction::Create, &[]).await;
+        }
+        Ok(created)
     }
 
     /// Create an entity and return the full entity (Prisma-style).
@@ -1231,16 +3129,20 @@ where
         U::Entity: EntityMetadata,
         T: EntityMetadata + Serialize + DeserializeOwned,
     {
+        self.ensure_json_storage("upsert")?;
+
         // Process the create payload
         let create_payload = create_builder.into_payload()?;
         let entity_id = create_payload.entity_id.clone();
 
         // Process update patch
         let update_patch = update_builder.into_patch()?;
+        let update_entity_id = update_patch.entity_id.clone();
+        let updated_fields = audited_fields(&update_patch);
 
         // Build the upsert command
-        let command = self
-            .build_upsert_command(create_payload, update_patch)
+        let (command, created_document, updated_document) = self
+            .build_upsert_command(conn, create_payload, update_patch)
             .await?;
 
         // Execute the command
@@ -1269,12 +3171,26 @@ where
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string())
                     .unwrap_or(entity_id);
+                if let (Some(hook), Some(document)) = (self.descriptor().hooks.after_create, &created_document) {
+                    hook(document);
+                }
+                let replayed = is_replay(&response);
+                if !replayed {
+                    self.record_audit(conn, &result_id, AuditAction::Create, &[]).await;
+                }
                 Ok(UpsertResult::Created(CreateResult {
                     id: result_id,
                     responses: vec![response],
+                    replayed,
                 }))
             }
-            "updated" => Ok(UpsertResult::Updated(vec![response])),
+            "updated" => {
+                if let (Some(hook), Some(document)) = (self.descriptor().hooks.after_update, &updated_document) {
+                    hook(document);
+                }
+                self.record_audit(conn, &update_entity_id, AuditAction::Update, &updated_fields).await;
+                Ok(UpsertResult::Updated(vec![response]))
+            }
             other => Err(RepoError::Other {
                 message: Cow::Owned(format!("unexpected upsert branch: {other}")),
             }),
@@ -1282,13 +3198,18 @@ where
     }
 
     /// Build the upsert command from create payload and update patch.
+    ///
+    /// Runs the `before_create` hook on the create payload and, when the entity already exists,
+    /// the `before_update` hook on the patched document. Also returns those documents for the
+    /// `after_create` and `after_update` hooks, whichever branch the script takes.
     async fn build_upsert_command(
         &self,
+        conn: &mut ConnectionManager,
         mut create_payload: MutationPayload,
-        update_patch: MutationPatch,
-    ) -> Result<UpsertCommand, RepoError>
+        mut update_patch: MutationPatch,
+    ) -> Result<(UpsertCommand, Option<Value>, Option<Value>), RepoError>
     where
-        T: EntityMetadata,
+        T: EntityMetadata + Serialize + DeserializeOwned,
     {
         // Update uses the entity_id from the update patch (the one we check for existence)
         let update_entity_id = update_patch.entity_id.clone();
@@ -1311,12 +3232,32 @@ where
             false,
         );
         ensure_metadata_object(&mut create_payload.payload);
-        inject_enum_tag_shadows(self.descriptor(), &mut create_payload.payload);
+        run_before_hook(
+            self.descriptor(),
+            self.descriptor().hooks.before_create,
+            &mut create_payload.payload,
+            &mut create_payload.mirrors,
+        )?;
+        inject_tag_shadows(self.descriptor(), &mut create_payload.payload);
 
         // Validate create payload
         if let Err(err) = validate_entity_json(self.descriptor(), &create_payload.payload) {
             return Err(RepoError::Validation(err));
         }
+        let created_document = self.descriptor().hooks.after_create.map(|_| create_payload.payload.clone());
+        storage::encode_fields(self.descriptor(), &mut create_payload.payload)?;
+
+        // The update hooks see the stored document with the patch applied; a missing entity
+        // takes the create branch, so it has nothing for them
+        let hooks = &self.descriptor().hooks;
+        let updated_document = if hooks.before_update.is_some() || hooks.after_update.is_some() {
+            match self.validate_patch_against_entity(conn, &mut update_patch).await {
+                Err(RepoError::NotFound { .. }) => None,
+                other => other?,
+            }
+        } else {
+            None
+        };
 
         // Serialize create payload
         let create_payload_json = serde_json::to_string(&create_payload.payload).map_err(|err| {
@@ -1362,7 +3303,8 @@ where
             .idempotency_ttl
             .or(update_patch.idempotency_ttl);
 
-        Ok(UpsertCommand {
+        let command = UpsertCommand {
+            history: self.history_record(&update_entity_id),
             update_key,
             update_entity_id,
             create_key,
@@ -1376,7 +3318,10 @@ where
             update_relations,
             idempotency_key,
             idempotency_ttl,
-        })
+            lua_check: self.descriptor.lua_check.clone(),
+            layout: key_context.layout(),
+        };
+        Ok((command, created_document, updated_document))
     }
 
     /// Atomically gets an existing entity or creates it if it doesn't exist.
@@ -1398,11 +3343,56 @@ where
         C::Entity: EntityMetadata,
         T: EntityMetadata + Serialize + DeserializeOwned,
     {
+        self.get_or_create_with(conn, create_builder, None).await
+    }
+
+    /// [`Repo::get_or_create`], finding the entity by a unique constraint rather than only by id.
+    ///
+    /// `unique_fields` names a declared `unique`/`unique_together` constraint, in any order; the
+    /// values to look up are the builder's. The script returns the entity holding those values
+    /// if there is one, then falls back to the builder's id, and otherwise creates the entity,
+    /// claiming the constraint in the same call so concurrent callers can't both create.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let user = repo
+    ///     .get_or_create_by(&mut conn, &["email"], User::validation_builder().email(email).name(name))
+    ///     .await?;
+    /// if user.was_created() { send_welcome(user.as_inner()); }
+    /// ```
+    pub async fn get_or_create_by<C>(
+        &self,
+        conn: &mut ConnectionManager,
+        unique_fields: &[&str],
+        create_builder: C,
+    ) -> Result<GetOrCreateResult<T>, RepoError>
+    where
+        C: MutationPayloadBuilder,
+        C::Entity: EntityMetadata,
+        T: EntityMetadata + Serialize + DeserializeOwned,
+    {
+        self.get_or_create_with(conn, create_builder, Some(unique_fields)).await
+    }
+
+    async fn get_or_create_with<C>(
+        &self,
+        conn: &mut ConnectionManager,
+        create_builder: C,
+        unique_fields: Option<&[&str]>,
+    ) -> Result<GetOrCreateResult<T>, RepoError>
+    where
+        C: MutationPayloadBuilder,
+        C::Entity: EntityMetadata,
+        T: EntityMetadata + Serialize + DeserializeOwned,
+    {
+        self.ensure_json_storage("get_or_create")?;
+
         // Process the create payload
         let create_payload = create_builder.into_payload()?;
+        let entity_id = create_payload.entity_id.clone();
 
         // Build the get_or_create command
-        let command = self.build_get_or_create_command(create_payload).await?;
+        let command = self.build_get_or_create_command(create_payload, unique_fields).await?;
 
         // Execute the command
         let mut plan = MutationPlan::new();
@@ -1431,18 +3421,22 @@ where
             })?;
 
         // The entity is returned as an array with single element from JSON.GET with $
-        let entity_json = if let Some(arr) = entity_value.as_array() {
+        let mut entity_json = if let Some(arr) = entity_value.as_array() {
             arr.first().cloned().unwrap_or(entity_value.clone())
         } else {
             entity_value.clone()
         };
+        storage::decode_fields(self.descriptor(), &mut entity_json)?;
 
         let entity: T = serde_json::from_value(entity_json).map_err(|err| RepoError::Other {
             message: Cow::Owned(format!("failed to deserialize entity: {err}")),
         })?;
 
         match branch {
-            "created" => Ok(GetOrCreateResult::Created(entity)),
+            "created" => {
+                self.record_audit(conn, &entity_id, AuditAction::Create, &[]).await;
+                Ok(GetOrCreateResult::Created(entity))
+            }
             "found" => Ok(GetOrCreateResult::Found(entity)),
             other => Err(RepoError::Other {
                 message: Cow::Owned(format!("unexpected get_or_create branch: {other}")),
@@ -1454,6 +3448,7 @@ where
     async fn build_get_or_create_command(
         &self,
         mut create_payload: MutationPayload,
+        unique_fields: Option<&[&str]>,
     ) -> Result<GetOrCreateCommand, RepoError>
     where
         T: EntityMetadata,
@@ -1473,12 +3468,19 @@ where
             false,
         );
         ensure_metadata_object(&mut create_payload.payload);
-        inject_enum_tag_shadows(self.descriptor(), &mut create_payload.payload);
+        run_before_hook(
+            self.descriptor(),
+            self.descriptor().hooks.before_create,
+            &mut create_payload.payload,
+            &mut create_payload.mirrors,
+        )?;
+        inject_tag_shadows(self.descriptor(), &mut create_payload.payload);
 
         // Validate create payload
         if let Err(err) = validate_entity_json(self.descriptor(), &create_payload.payload) {
             return Err(RepoError::Validation(err));
         }
+        storage::encode_fields(self.descriptor(), &mut create_payload.payload)?;
 
         // Serialize create payload
         let create_payload_json = serde_json::to_string(&create_payload.payload).map_err(|err| {
@@ -1492,6 +3494,21 @@ where
             self.descriptor(),
             &create_payload.payload,
         );
+        let lookup = match unique_fields {
+            Some(fields) => Some(
+                unique_constraints
+                    .iter()
+                    .find(|check| {
+                        check.fields.len() == fields.len()
+                            && fields.iter().all(|field| check.fields.iter().any(|name| name == field))
+                    })
+                    .cloned()
+                    .ok_or_else(|| RepoError::InvalidRequest {
+                        message: format!("{} has no unique constraint on {fields:?}", self.descriptor.collection),
+                    })?,
+            ),
+            None => None,
+        };
 
         // Build relations
         let (relations, _) = Self::relation_mutations_for(
@@ -1510,6 +3527,9 @@ where
             datetime_mirrors: create_payload.mirrors,
             idempotency_key: create_payload.idempotency_key,
             idempotency_ttl: create_payload.idempotency_ttl,
+            lua_check: self.descriptor.lua_check.clone(),
+            lookup,
+            layout: key_context.layout(),
         })
     }
 
@@ -1527,7 +3547,7 @@ where
             let field_name = op.path.strip_prefix("$.").unwrap_or(op.path.as_str());
 
             // Validate field exists
-            let _descriptor_field = self
+            let descriptor_field = self
                 .descriptor
                 .fields
                 .iter()
@@ -1539,12 +3559,35 @@ where
                         format!("field `{field_name}` is not defined on entity"),
                     ))
                 })?;
+            ensure_patchable(descriptor_field)?;
 
-            let (op_type, value) = match &op.kind {
-                PatchOpKind::Assign(v) => (PatchOperationType::Assign, Some(v.clone())),
-                PatchOpKind::Merge(v) => (PatchOperationType::Merge, Some(v.clone())),
-                PatchOpKind::Delete => (PatchOperationType::Delete, None),
+            let (op_type, value) = match (&op.kind, descriptor_field.compression) {
+                (PatchOpKind::Assign(v), _) => {
+                    (PatchOperationType::Assign, Some(storage::encode_field_value(descriptor_field, v)?))
+                }
+                (PatchOpKind::Merge(_), Some(_)) => {
+                    return Err(RepoError::Validation(ValidationError::single(
+                        field_name,
+                        "patch.compressed_merge",
+                        "compressed fields can only be assigned as a whole",
+                    )));
+                }
+                (PatchOpKind::Merge(v), None) => (PatchOperationType::Merge, Some(v.clone())),
+                (PatchOpKind::Delete, _) => (PatchOperationType::Delete, None),
+                (PatchOpKind::Increment(by), _) => {
+                    ensure_incrementable(&self.descriptor, descriptor_field, *by)?;
+                    (PatchOperationType::Increment, Some(increment_value(*by)))
+                }
+                (PatchOpKind::ArrayAppend(elements), _) => {
+                    ensure_array_operable(&self.descriptor, descriptor_field)?;
+                    (PatchOperationType::ArrayAppend, Some(Value::Array(elements.clone())))
+                }
+                (PatchOpKind::ArrayRemove(elements), _) => {
+                    ensure_array_operable(&self.descriptor, descriptor_field)?;
+                    (PatchOperationType::ArrayRemove, Some(Value::Array(elements.clone())))
+                }
             };
+            let elements_json = array_elements_json(&op_type, value.as_ref());
 
             let value_json = value.as_ref().map(|v| {
                 serde_json::to_string(v).expect("serde_json::Value serialization should not fail")
@@ -1562,6 +3605,9 @@ where
                 value_json,
                 mirror: op.mirror.clone(),
                 mirror_value_json,
+                hash_value: None,
+                elements_json,
+                length: None,
             });
         }
 
@@ -1589,6 +3635,9 @@ where
                             PatchOpKind::Assign(v) => Some(v.clone()),
                             PatchOpKind::Merge(v) => Some(v.clone()),
                             PatchOpKind::Delete => Some(Value::Null),
+                            PatchOpKind::Increment(_)
+                            | PatchOpKind::ArrayAppend(_)
+                            | PatchOpKind::ArrayRemove(_) => None,
                         }
                     } else {
                         None
@@ -1626,10 +3675,97 @@ where
         B::Entity: EntityMetadata,
         T: EntityMetadata + Serialize + DeserializeOwned,
     {
-        let patch = builder.into_patch()?;
-        self.validate_patch_against_entity(conn, &patch).await?;
+        self.patch_with_conn(conn, builder.into_patch()?).await
+    }
+
+    pub async fn touch_with_conn(&self, conn: &mut ConnectionManager, entity_id: &str) -> Result<Vec<Value>, RepoError>
+    where
+        T: EntityMetadata,
+    {
+        let mut executor = RedisExecutor::new(conn);
+        self.touch(&mut executor, entity_id).await
+    }
+
+    /// Apply a patch only while the stored document matches `condition`, e.g. cancel an order
+    /// only if its status is still `pending`.
+    ///
+    /// The patch script evaluates the condition against the document it is about to change, so
+    /// no concurrent write can slip in between; see [`Precondition`] for how each kind of filter
+    /// compares. A document that doesn't match fails with `RepoError::PreconditionFailed` and is
+    /// left untouched. Fuzzy text filters are rejected.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let patch = Order::patch_builder().entity_id(&id).status(OrderStatus::Cancelled);
+    /// repo.update_patch_if(&mut conn, FilterCondition::tag_eq("status", "pending"), patch).await?;
+    /// ```
+    pub async fn update_patch_if<B>(
+        &self,
+        conn: &mut ConnectionManager,
+        condition: FilterCondition,
+        builder: B,
+    ) -> Result<Vec<Value>, RepoError>
+    where
+        B: UpdatePatchBuilder,
+        B::Entity: EntityMetadata,
+        T: EntityMetadata + SearchEntity + Serialize + DeserializeOwned,
+    {
+        let mut patch = builder.into_patch()?;
+        let definition = T::index_definition(&self.prefix);
+        patch.precondition = Some(Precondition::from_filter(&definition, &condition)?);
+        self.patch_with_conn(conn, patch).await
+    }
+
+    /// Validate a patch as [`Repo::update_patch_with_conn`] would and build its plan for a
+    /// [`UnitOfWork`](crate::UnitOfWork), without writing anything.
+    pub(crate) async fn stage_patch(
+        &self,
+        conn: &mut ConnectionManager,
+        mut patch: MutationPatch,
+    ) -> Result<StagedWrite, RepoError>
+    where
+        T: EntityMetadata + Serialize + DeserializeOwned,
+    {
+        if !patch.nested.is_empty() {
+            return Err(RepoError::InvalidRequest {
+                message: "nested creates cannot be staged in a unit of work".to_string(),
+            });
+        }
+        let document = self.validate_patch_against_entity(conn, &mut patch).await?;
+        let entity_id = patch.entity_id.clone();
+        let fields = audited_fields(&patch);
+        let plan = if patch.operations.is_empty() && patch.relations.is_empty() {
+            MutationPlan::new()
+        } else {
+            self.prepare_patch(patch)?
+        };
+        Ok(StagedWrite {
+            plan,
+            audit_key: self.audit_key(&entity_id),
+            action: AuditAction::Update,
+            fields,
+            after: self.descriptor().hooks.after_update.zip(document),
+            entity_id,
+        })
+    }
+
+    /// [`Repo::update_patch_with_conn`] for a patch that has already been built.
+    async fn patch_with_conn(&self, conn: &mut ConnectionManager, mut patch: MutationPatch) -> Result<Vec<Value>, RepoError>
+    where
+        T: EntityMetadata + Serialize + DeserializeOwned,
+    {
+        let document = self.validate_patch_against_entity(conn, &mut patch).await?;
+        let entity_id = patch.entity_id.clone();
+        let fields = audited_fields(&patch);
         let mut executor = RedisExecutor::new(conn);
-        self.execute_patch(&mut executor, patch).await
+        let responses = self.execute_patch(&mut executor, patch).await?;
+        if let (Some(hook), Some(document)) = (self.descriptor().hooks.after_update, &document) {
+            hook(document);
+        }
+        if !responses.is_empty() {
+            self.record_audit(conn, &entity_id, AuditAction::Update, &fields).await;
+        }
+        Ok(responses)
     }
 
     pub async fn delete_with_conn(
@@ -1639,7 +3775,87 @@ where
         expected_version: Option<u64>,
     ) -> Result<Vec<Value>, RepoError> {
         let mut executor = RedisExecutor::new(conn);
-        self.delete(&mut executor, entity_id, expected_version).await
+        let responses = self.delete(&mut executor, entity_id, expected_version).await?;
+        self.record_audit(conn, entity_id, AuditAction::Delete, &[]).await;
+        Ok(responses)
+    }
+
+    /// Previous versions of a `#[snugom(versioned)]` entity, newest first.
+    ///
+    /// Each update stores the document as it was before the update, so the first entry is the
+    /// version the current one replaced. Empty for entities that are not versioned.
+    pub async fn history(
+        &self,
+        conn: &mut ConnectionManager,
+        entity_id: &str,
+    ) -> Result<Vec<EntityVersion<T>>, RepoError>
+    where
+        T: DeserializeOwned,
+    {
+        let key = self.history_key(entity_id);
+        let raw: Vec<String> =
+            self.intercept("history", &key, cmd("LRANGE").arg(&key).arg(0).arg(-1).query_async(conn)).await?;
+        raw.iter().map(|json| self.decode_version(json)).collect()
+    }
+
+    /// Make a previous version current again, as an update that assigns every field that differs.
+    ///
+    /// The restore runs through the usual patch checks (validation, unique constraints, hooks)
+    /// and is itself versioned, so it can be undone the same way. `version` is the
+    /// `metadata.version` listed by [`Repo::history`]; an unknown one is `RepoError::NotFound`.
+    pub async fn restore_version(
+        &self,
+        conn: &mut ConnectionManager,
+        entity_id: &str,
+        version: u64,
+    ) -> Result<Vec<Value>, RepoError>
+    where
+        T: EntityMetadata + Serialize + DeserializeOwned,
+    {
+        let not_found = || RepoError::NotFound {
+            entity_id: Some(format!("{entity_id}@{version}")),
+        };
+        let previous = self
+            .history(conn, entity_id)
+            .await?
+            .into_iter()
+            .find(|entry| entry.version == version)
+            .ok_or_else(not_found)?;
+        let current = self.get(conn, entity_id).await?.ok_or_else(|| RepoError::NotFound {
+            entity_id: Some(entity_id.to_string()),
+        })?;
+        let to_json = |entity: &T| {
+            serde_json::to_value(entity).map_err(|err| RepoError::Other {
+                message: Cow::Owned(format!("failed to serialize entity: {err}")),
+            })
+        };
+        let operations = hook_patch_operations(self.descriptor(), &to_json(&current)?, &to_json(&previous.entity)?);
+        let patch = MutationPatch {
+            entity_id: entity_id.to_string(),
+            expected_version: None,
+            operations,
+            relations: Vec::new(),
+            nested: Vec::new(),
+            idempotency_key: None,
+            idempotency_ttl: None,
+            precondition: None,
+        };
+        self.patch_with_conn(conn, patch).await
+    }
+
+    fn decode_version(&self, raw: &str) -> Result<EntityVersion<T>, RepoError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut document: Value = serde_json::from_str(raw).map_err(|err| RepoError::Other {
+            message: Cow::Owned(format!("failed to parse stored version: {err}")),
+        })?;
+        let version = document.pointer("/metadata/version").and_then(Value::as_u64).unwrap_or_default();
+        storage::decode_fields(self.descriptor(), &mut document)?;
+        let entity = serde_json::from_value(document).map_err(|err| RepoError::Other {
+            message: Cow::Owned(format!("failed to deserialize entity: {err}")),
+        })?;
+        Ok(EntityVersion { version, entity })
     }
 
     pub async fn mutate_relations_with_conn(
@@ -1654,13 +3870,13 @@ where
     async fn validate_patch_against_entity(
         &self,
         conn: &mut ConnectionManager,
-        patch: &MutationPatch,
-    ) -> Result<(), RepoError>
+        patch: &mut MutationPatch,
+    ) -> Result<Option<Value>, RepoError>
     where
         T: EntityMetadata + Serialize + DeserializeOwned,
     {
         if patch.operations.is_empty() {
-            return Ok(());
+            return Ok(None);
         }
 
         let current = self.get(conn, &patch.entity_id).await?.ok_or_else(|| RepoError::NotFound {
@@ -1673,14 +3889,30 @@ where
 
         apply_patch_operations_to_value(&mut json, &patch.operations)?;
 
+        // Whatever the hook changes is written along with the patch
+        if let Some(hook) = self.descriptor().hooks.before_update {
+            let patched = json.clone();
+            hook(&mut json).map_err(RepoError::Validation)?;
+            patch.operations.extend(hook_patch_operations(self.descriptor(), &patched, &json));
+        }
+
         if let Err(err) = validate_entity_json(self.descriptor(), &json) {
             return Err(RepoError::Validation(err));
         }
 
-        serde_json::from_value::<T>(json).map_err(|err| {
+        let touched: Vec<&str> = patch
+            .operations
+            .iter()
+            .filter_map(|op| op.path.trim_start_matches('$').trim_start_matches('.').split('.').next())
+            .collect();
+        run_async_validators(self.descriptor(), conn, &json, Some(&touched))
+            .await
+            .map_err(RepoError::Validation)?;
+
+        T::deserialize(&json).map_err(|err| {
             RepoError::Validation(ValidationError::single("__patch", "deserialization.failed", err.to_string()))
         })?;
-        Ok(())
+        Ok(Some(json))
     }
 }
 
@@ -1705,6 +3937,8 @@ where
                 add,
                 mut remove,
                 delete,
+                edges,
+                replace,
             } = plan;
 
             let relation_info = descriptor.relations.iter().find(|relation| relation.alias == alias);
@@ -1748,12 +3982,21 @@ where
                         None
                     };
 
+                    let counter = relation_descriptor.counter.as_ref().map(|field| RelationCounter {
+                        entity_key: key_context.entity(&descriptor.collection, &left),
+                        path: format!("$.{field}"),
+                    });
+
                     mutations.push(RelationMutation {
                         relation_key,
                         add,
                         remove,
+                        replace,
                         cascade,
                         maintain_reverse,
+                        edges,
+                        counter,
+                        layout: key_context.layout(),
                     });
                 }
                 None => {
@@ -1793,9 +4036,9 @@ where
                     }
                 }
                 NestedTask::Execute(mut mutation) => {
-                    let key_context = KeyContext::new(&self.prefix, &mutation.descriptor.service);
+                    let key_context = KeyContext::for_entity(&self.prefix, &mutation.descriptor);
                     let key = key_context.entity(&mutation.descriptor.collection, &mutation.payload.entity_id);
-                    let mirrors = ::std::mem::take(&mut mutation.payload.mirrors);
+                    let mut mirrors = ::std::mem::take(&mut mutation.payload.mirrors);
                     let relations = ::std::mem::take(&mut mutation.payload.relations);
                     let idempotency_key = mutation.payload.idempotency_key.take();
                     let idempotency_ttl = mutation.payload.idempotency_ttl.take();
@@ -1806,13 +4049,21 @@ where
                         relations,
                     )?;
                     ensure_metadata_object(&mut mutation.payload.payload);
-                    inject_enum_tag_shadows(&mutation.descriptor, &mut mutation.payload.payload);
+                    run_before_hook(
+                        &mutation.descriptor,
+                        mutation.descriptor.hooks.before_create,
+                        &mut mutation.payload.payload,
+                        &mut mirrors,
+                    )?;
+                    inject_tag_shadows(&mutation.descriptor, &mut mutation.payload.payload);
                     if let Err(err) = validate_entity_json(&mutation.descriptor, &mutation.payload.payload) {
                         return Err(RepoError::Validation(err));
                     }
+                    storage::encode_fields(&mutation.descriptor, &mut mutation.payload.payload)?;
                     let mutation_command = build_entity_mutation(
                         &mutation.descriptor,
                         key,
+                        key_context.layout(),
                         mutation.payload.payload,
                         mirrors,
                         None,
@@ -1828,7 +4079,16 @@ where
                         pending_deletes,
                         &mut plan,
                     )?;
-                    executor.execute(plan).await?;
+                    if mutation.connect_existing {
+                        // The parent's relation plan connects the id either way
+                        plan.set_if_absent();
+                        match self.execute(executor, plan).await {
+                            Ok(_) | Err(RepoError::AlreadyExists { .. }) => {}
+                            Err(err) => return Err(err),
+                        }
+                    } else {
+                        self.execute(executor, plan).await?;
+                    }
                 }
             }
         }
@@ -1848,23 +4108,18 @@ where
 
         for pending in deletes {
             let target_service = pending.target_service.unwrap_or_else(|| descriptor.service.clone());
-            let target_descriptor =
-                registry::get_descriptor(&target_service, &pending.target_collection).ok_or_else(|| {
-                    RepoError::Other {
-                        message: Cow::Owned(format!(
-                            "descriptor for service `{}` collection `{}` is not registered",
-                            target_service, pending.target_collection
-                        )),
-                    }
-                })?;
+            let target_descriptor = lookup_descriptor(&target_service, &pending.target_collection)
+                .ok_or_else(|| unregistered_descriptor(&target_service, &pending.target_collection))?;
 
-            let child_context = KeyContext::new(key_context.prefix, target_service.as_str());
+            let child_context = KeyContext::for_entity(key_context.prefix, &target_descriptor);
 
             for id in pending.ids {
                 let cascades = delete_cascades_for_descriptor(&target_descriptor, &child_context, &id)?;
                 let unique_constraints = unique_constraint_definitions_for(&target_descriptor);
                 let child_key = child_context.entity(&target_descriptor.collection, &id);
-                let delete = build_entity_delete(child_key, None, cascades, unique_constraints);
+                let mut delete =
+                    build_entity_delete(child_key, child_context.layout(), None, cascades, unique_constraints);
+                delete.parents = parent_detaches_for(&target_descriptor, key_context.prefix);
                 plan.push(MutationCommand::DeleteEntity(delete));
             }
         }
@@ -2015,7 +4270,9 @@ fn ensure_metadata_object(payload: &mut Value) {
     }
 }
 
-/// Injects shadow tag fields for enum fields that need them for RediSearch indexing.
+/// Injects shadow tag fields for enum and casefold fields that need them for RediSearch indexing.
+///
+/// `filterable(tag, casefold)` string fields get a lowercase copy in `__{field}_lc`.
 ///
 /// For enum fields marked with `#[snugom(filterable)]` and `normalize_enum_tag: true`,
 /// enums with associated data serialize to JSON objects (e.g., `{"swiss": {"rounds": 6}}`),
@@ -2025,12 +4282,22 @@ fn ensure_metadata_object(payload: &mut Value) {
 /// The original field value is preserved for proper deserialization.
 /// Unit variant enums that already serialize to strings don't need shadow fields,
 /// but we add them anyway for consistency (the value will match the original).
-fn inject_enum_tag_shadows(descriptor: &EntityDescriptor, payload: &mut Value) {
+fn inject_tag_shadows(descriptor: &EntityDescriptor, payload: &mut Value) {
     let Some(object) = payload.as_object_mut() else {
         return;
     };
 
     for field in &descriptor.fields {
+        if let Some(position) = object.get(&field.name).and_then(|value| enum_position(field, value)) {
+            object.insert(order_shadow_name(&field.name), position);
+        }
+        if field.casefold_tag {
+            if let Some(Value::String(value)) = object.get(&field.name) {
+                let folded = value.to_lowercase();
+                object.insert(casefold_shadow_name(&field.name), Value::String(folded));
+            }
+            continue;
+        }
         if !field.normalize_enum_tag {
             continue;
         }
@@ -2039,25 +4306,50 @@ fn inject_enum_tag_shadows(descriptor: &EntityDescriptor, payload: &mut Value) {
             continue;
         };
 
-        // Extract the discriminant from the field value
-        let discriminant = match field_value {
-            Value::String(s) => Some(s.clone()),
-            Value::Object(map) => map.keys().next().cloned(),
-            _ => None,
-        };
-
-        if let Some(tag) = discriminant {
+        if let Some(tag) = tag_discriminant(field_value) {
             let shadow_name = format!("__{}_tag", field.name);
             object.insert(shadow_name, Value::String(tag));
         }
     }
 }
 
-/// Injects shadow tag operations for enum fields in patch operations.
+/// The tag an enum value is indexed under: a unit variant's string, or the key of a variant
+/// with data (`{"swiss": {"rounds": 6}}` is `swiss`).
+fn tag_discriminant(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(map) => map.keys().next().cloned(),
+        _ => None,
+    }
+}
+
+/// Position of `value` in the field's `enum_order`, for its `__{field}_ord` shadow; values
+/// missing from the list sort after every listed one.
+fn enum_position(field: &FieldDescriptor, value: &Value) -> Option<Value> {
+    if field.enum_order.is_empty() {
+        return None;
+    }
+    let tag = tag_discriminant(value)?;
+    let position = field.enum_order.iter().position(|listed| *listed == tag).unwrap_or(field.enum_order.len());
+    Some(Value::from(position))
+}
+
+/// Name of the numeric shadow kept for an `enum_order` field.
+fn order_shadow_name(field: &str) -> String {
+    format!("__{field}_ord")
+}
+
+/// Name of the lowercase shadow kept for a `filterable(tag, casefold)` field.
+fn casefold_shadow_name(field: &str) -> String {
+    format!("__{field}_lc")
+}
+
+/// Injects shadow tag operations for enum and casefold fields in patch operations.
 ///
 /// When a field with `normalize_enum_tag: true` is being patched, this function
-/// adds a corresponding operation for the shadow field containing the discriminant.
-fn inject_enum_tag_shadow_operations(descriptor: &EntityDescriptor, operations: &mut Vec<PatchOperation>) {
+/// adds a corresponding operation for the shadow field containing the discriminant;
+/// a `casefold_tag` field gets its lowercase shadow reassigned or deleted alongside it.
+fn inject_tag_shadow_operations(descriptor: &EntityDescriptor, operations: &mut Vec<PatchOperation>) {
     let mut shadow_ops: Vec<PatchOperation> = Vec::new();
 
     for op in operations.iter() {
@@ -2068,6 +4360,30 @@ fn inject_enum_tag_shadow_operations(descriptor: &EntityDescriptor, operations:
             continue;
         };
 
+        if !field.enum_order.is_empty() {
+            let path = format!("$.{}", order_shadow_name(&field.name));
+            match &op.kind {
+                PatchOpKind::Assign(Value::Null) | PatchOpKind::Delete => {
+                    shadow_ops.push(PatchOperation { path, kind: PatchOpKind::Delete, mirror: None });
+                }
+                PatchOpKind::Assign(value) => {
+                    if let Some(position) = enum_position(field, value) {
+                        shadow_ops.push(PatchOperation { path, kind: PatchOpKind::Assign(position), mirror: None });
+                    }
+                }
+                _ => {}
+            }
+        }
+        if field.casefold_tag {
+            let path = format!("$.{}", casefold_shadow_name(&field.name));
+            let kind = match &op.kind {
+                PatchOpKind::Assign(Value::String(value)) => PatchOpKind::Assign(Value::String(value.to_lowercase())),
+                PatchOpKind::Assign(Value::Null) | PatchOpKind::Delete => PatchOpKind::Delete,
+                _ => continue,
+            };
+            shadow_ops.push(PatchOperation { path, kind, mirror: None });
+            continue;
+        }
         if !field.normalize_enum_tag {
             continue;
         }
@@ -2076,13 +4392,7 @@ fn inject_enum_tag_shadow_operations(descriptor: &EntityDescriptor, operations:
 
         match &op.kind {
             PatchOpKind::Assign(value) => {
-                // Extract discriminant from the value
-                let discriminant = match value {
-                    Value::String(s) => Some(s.clone()),
-                    Value::Object(map) => map.keys().next().cloned(),
-                    _ => None,
-                };
-                if let Some(tag) = discriminant {
+                if let Some(tag) = tag_discriminant(value) {
                     shadow_ops.push(PatchOperation {
                         path: shadow_path,
                         kind: PatchOpKind::Assign(Value::String(tag)),
@@ -2098,9 +4408,12 @@ fn inject_enum_tag_shadow_operations(descriptor: &EntityDescriptor, operations:
                     mirror: None,
                 });
             }
-            PatchOpKind::Merge(_) => {
+            PatchOpKind::Merge(_) | PatchOpKind::Increment(_) => {
                 // Merge operations don't change the discriminant, so no shadow update needed
             }
+            PatchOpKind::ArrayAppend(_) | PatchOpKind::ArrayRemove(_) => {
+                // Array fields are indexed element by element (`$.tags[*]`) and never carry a shadow
+            }
         }
     }
 
@@ -1,8 +1,12 @@
 use crate::{
-    errors::{ValidationError, ValidationResult},
+    errors::{RepoError, ValidationError, ValidationIssue, ValidationResult},
+    keys::KeyLayout,
+    search::{FilterCondition, IndexDefinition},
+    storage::encode_hash_fields,
     types::{DatetimeMirrorValue, EntityDescriptor},
 };
 use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +19,44 @@ pub enum MutationCommand {
     GetOrCreate(GetOrCreateCommand),
 }
 
+impl MutationCommand {
+    /// Name of the script the command runs, e.g. `entity_patch`.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            MutationCommand::UpsertEntity(_) => "entity_mutation",
+            MutationCommand::PatchEntity(_) => "entity_patch",
+            MutationCommand::DeleteEntity(_) => "entity_delete",
+            MutationCommand::MutateRelations(_) => "relation_mutation",
+            MutationCommand::Upsert(_) => "entity_upsert",
+            MutationCommand::GetOrCreate(_) => "entity_get_or_create",
+        }
+    }
+
+    /// The entity or relation key the command writes.
+    pub fn key(&self) -> &str {
+        match self {
+            MutationCommand::UpsertEntity(command) => &command.key,
+            MutationCommand::PatchEntity(command) => &command.key,
+            MutationCommand::DeleteEntity(command) => &command.key,
+            MutationCommand::MutateRelations(command) => &command.relation_key,
+            MutationCommand::Upsert(command) => &command.update_key,
+            MutationCommand::GetOrCreate(command) => &command.entity_key,
+        }
+    }
+
+    /// Layout of the keys the command writes, from which the script derives related keys.
+    pub fn layout(&self) -> &KeyLayout {
+        match self {
+            MutationCommand::UpsertEntity(command) => &command.layout,
+            MutationCommand::PatchEntity(command) => &command.layout,
+            MutationCommand::DeleteEntity(command) => &command.layout,
+            MutationCommand::MutateRelations(command) => &command.layout,
+            MutationCommand::Upsert(command) => &command.layout,
+            MutationCommand::GetOrCreate(command) => &command.layout,
+        }
+    }
+}
+
 /// Upsert command - creates if not exists, updates if exists.
 /// Executed in a single Lua script to avoid race conditions.
 #[derive(Debug, Serialize)]
@@ -53,6 +95,15 @@ pub struct UpsertCommand {
     /// TTL for idempotency key in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub idempotency_ttl: Option<u64>,
+    /// Entity-level Lua check run against the candidate document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua_check: Option<String>,
+    /// Where the update path copies the document before changing it, for versioned entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryRecord>,
+    /// Separator and head of the entity's keys, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
 }
 
 /// GetOrCreate command - returns existing entity or creates new one.
@@ -81,6 +132,15 @@ pub struct GetOrCreateCommand {
     /// TTL for idempotency key in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub idempotency_ttl: Option<u64>,
+    /// Entity-level Lua check run against the candidate document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua_check: Option<String>,
+    /// Unique constraint to resolve the entity by before falling back to `entity_key`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lookup: Option<UniqueConstraintCheck>,
+    /// Separator and head of the entity's keys, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
 }
 
 /// Represents a unique constraint check to be enforced by the Lua script.
@@ -111,6 +171,17 @@ pub struct EntityMutation {
     pub relations: Vec<RelationMutation>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub unique_constraints: Vec<UniqueConstraintCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua_check: Option<String>,
+    /// Alternating field/value strings for `HSET`; present only for hash-stored entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_fields: Option<Vec<String>>,
+    /// Fail with `already_exists` instead of overwriting a document at `key`
+    #[serde(skip_serializing_if = "skip_false")]
+    pub if_absent: bool,
+    /// Separator and head of the entity's keys, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
 }
 
 #[derive(Debug, Serialize)]
@@ -126,6 +197,48 @@ pub struct PatchOperationPayload {
     pub mirror: Option<DatetimeMirrorValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mirror_value_json: Option<String>,
+    /// The assigned value encoded as a hash field, for hash-stored entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_value: Option<String>,
+    /// Each element encoded separately, for array appends and removals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elements_json: Option<Vec<String>>,
+    /// Bounds of the field's `length` rule, which the array must still meet once every
+    /// operation has applied; set for array appends and removals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<LengthBounds>,
+}
+
+/// `validate(length(min = .., max = ..))` of an array field, checked by the patch script against
+/// the array an append or removal leaves behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LengthBounds {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+}
+
+impl LengthBounds {
+    /// The error for an array of `length` elements at `field`, unless it is within the bounds.
+    pub(crate) fn violation(&self, field: &str, length: usize) -> Option<ValidationError> {
+        let issue = match (self.min, self.max) {
+            (Some(min), _) if length < min => ValidationIssue::new(
+                field,
+                "validation.length",
+                format!("length must be at least {min}"),
+            )
+            .with_param("min", min),
+            (_, Some(max)) if length > max => ValidationIssue::new(
+                field,
+                "validation.length",
+                format!("length must be at most {max}"),
+            )
+            .with_param("max", max),
+            _ => return None,
+        };
+        Some(ValidationError::new(vec![issue]))
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -134,6 +247,9 @@ pub enum PatchOperationType {
     Assign,
     Merge,
     Delete,
+    Increment,
+    ArrayAppend,
+    ArrayRemove,
 }
 
 #[derive(Debug, Serialize)]
@@ -154,6 +270,220 @@ pub struct EntityPatch {
     /// This contains the constraint definition plus the NEW values from the patch.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub unique_constraints: Vec<UniqueConstraintCheck>,
+    /// Paths (`$.field`) of immutable fields; the script rejects operations on them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub immutable_paths: Vec<String>,
+    /// Entity-level Lua check run against the patched document; the patch is
+    /// rolled back when the check rejects it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua_check: Option<String>,
+    /// Apply operations with hash commands; the entity uses hash storage
+    #[serde(skip_serializing_if = "skip_false")]
+    pub hash: bool,
+    /// Where the document is copied before the operations change it, for versioned entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryRecord>,
+    /// Condition the stored document must meet for the patch to apply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precondition: Option<Precondition>,
+    /// Bump the version even when there are no operations or relations
+    #[serde(skip_serializing_if = "skip_false")]
+    pub touch: bool,
+    /// Separator and head of the entity's keys, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
+}
+
+/// A [`FilterCondition`] resolved to document paths, which the patch script checks against the
+/// stored document before changing it.
+///
+/// Tags, booleans, and text compare ignoring ASCII case; an array field matches when any
+/// element does. Text conditions compare the whole value: `prefix` and `contains` as their names
+/// say, `exact` for equality.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Precondition {
+    Tag {
+        path: Vec<String>,
+        values: Vec<String>,
+    },
+    Numeric {
+        path: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+        #[serde(skip_serializing_if = "skip_false")]
+        min_exclusive: bool,
+        #[serde(skip_serializing_if = "skip_false")]
+        max_exclusive: bool,
+    },
+    Boolean {
+        path: Vec<String>,
+        value: bool,
+    },
+    Prefix {
+        path: Vec<String>,
+        value: String,
+    },
+    Contains {
+        path: Vec<String>,
+        value: String,
+    },
+    Exact {
+        path: Vec<String>,
+        value: String,
+    },
+    Missing {
+        path: Vec<String>,
+    },
+    And {
+        conditions: Vec<Precondition>,
+    },
+    Or {
+        conditions: Vec<Precondition>,
+    },
+    Not {
+        condition: Box<Precondition>,
+    },
+}
+
+impl Precondition {
+    /// Resolve the index attributes `condition` names to document paths through `definition`.
+    pub fn from_filter(definition: &IndexDefinition, condition: &FilterCondition) -> Result<Self, RepoError> {
+        let path = |field: &str| -> Result<Vec<String>, RepoError> {
+            let attribute = definition
+                .schema
+                .iter()
+                .find(|candidate| candidate.field_name == field)
+                .ok_or_else(|| RepoError::InvalidRequest {
+                    message: format!("Unknown index field: {field}"),
+                })?;
+            Ok(attribute
+                .path
+                .trim_start_matches('$')
+                .split('.')
+                .map(|segment| segment.trim_end_matches("[*]"))
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+                .collect())
+        };
+        let all = |conditions: &[FilterCondition]| {
+            conditions
+                .iter()
+                .map(|condition| Self::from_filter(definition, condition))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match condition {
+            FilterCondition::TagEquals { field, values } => Precondition::Tag {
+                path: path(field)?,
+                values: values.clone(),
+            },
+            FilterCondition::NumericRange {
+                field,
+                min,
+                max,
+                min_exclusive,
+                max_exclusive,
+            } => Precondition::Numeric {
+                path: path(field)?,
+                min: *min,
+                max: *max,
+                min_exclusive: *min_exclusive,
+                max_exclusive: *max_exclusive,
+            },
+            FilterCondition::BooleanEquals { field, value } => Precondition::Boolean {
+                path: path(field)?,
+                value: *value,
+            },
+            FilterCondition::TextPrefix { field, value } => Precondition::Prefix {
+                path: path(field)?,
+                value: value.clone(),
+            },
+            FilterCondition::TextContains { field, value } => Precondition::Contains {
+                path: path(field)?,
+                value: value.clone(),
+            },
+            FilterCondition::TextExact { field, value } => Precondition::Exact {
+                path: path(field)?,
+                value: value.clone(),
+            },
+            FilterCondition::TextFuzzy { field, .. } => {
+                return Err(RepoError::InvalidRequest {
+                    message: format!("fuzzy matching on `{field}` cannot be used as a precondition"),
+                });
+            }
+            FilterCondition::IsMissing { field } => Precondition::Missing { path: path(field)? },
+            FilterCondition::And(conditions) => Precondition::And {
+                conditions: all(conditions)?,
+            },
+            FilterCondition::Or(conditions) => Precondition::Or {
+                conditions: all(conditions)?,
+            },
+            FilterCondition::Not(condition) => Precondition::Not {
+                condition: Box::new(Self::from_filter(definition, condition)?),
+            },
+        })
+    }
+
+    /// Evaluate against a stored document, as the patch script does.
+    pub fn matches(&self, document: &Value) -> bool {
+        let values = |path: &[String]| -> Vec<&Value> {
+            match path.iter().try_fold(document, |value, segment| value.get(segment)) {
+                Some(Value::Array(items)) => items.iter().collect(),
+                Some(Value::Null) | None => Vec::new(),
+                Some(value) => vec![value],
+            }
+        };
+        let texts = |path: &[String]| -> Vec<String> {
+            values(path)
+                .into_iter()
+                .map(|value| match value {
+                    Value::String(text) => text.to_ascii_lowercase(),
+                    other => other.to_string(),
+                })
+                .collect()
+        };
+        match self {
+            Precondition::Tag { path, values } => texts(path)
+                .iter()
+                .any(|stored| values.iter().any(|value| stored.eq_ignore_ascii_case(value))),
+            Precondition::Numeric {
+                path,
+                min,
+                max,
+                min_exclusive,
+                max_exclusive,
+            } => values(path).into_iter().any(|value| {
+                let number = value.as_f64().or_else(|| value.as_str().and_then(|text| text.parse().ok()));
+                number.is_some_and(|number| {
+                    min.is_none_or(|min| if *min_exclusive { number > min } else { number >= min })
+                        && max.is_none_or(|max| if *max_exclusive { number < max } else { number <= max })
+                })
+            }),
+            Precondition::Boolean { path, value } => texts(path).iter().any(|stored| *stored == value.to_string()),
+            Precondition::Prefix { path, value } => {
+                texts(path).iter().any(|stored| stored.starts_with(&value.to_ascii_lowercase()))
+            }
+            Precondition::Contains { path, value } => {
+                texts(path).iter().any(|stored| stored.contains(&value.to_ascii_lowercase()))
+            }
+            Precondition::Exact { path, value } => texts(path).iter().any(|stored| stored.eq_ignore_ascii_case(value)),
+            Precondition::Missing { path } => values(path).is_empty(),
+            Precondition::And { conditions } => conditions.iter().all(|condition| condition.matches(document)),
+            Precondition::Or { conditions } => {
+                conditions.is_empty() || conditions.iter().any(|condition| condition.matches(document))
+            }
+            Precondition::Not { condition } => !condition.matches(document),
+        }
+    }
+}
+
+/// A list the script pushes the previous document onto, trimmed to the newest `keep` entries.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryRecord {
+    pub key: String,
+    pub keep: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -167,6 +497,26 @@ pub struct EntityDelete {
     /// Unlike create, we only need field names and case_insensitive - values are read from the entity.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub unique_constraints: Vec<UniqueConstraintDefinition>,
+    /// Has-many sets on parents that list the entity, found through its belongs-to foreign keys
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<ParentRelationDetach>,
+    /// Separator and head of the entity's keys, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
+}
+
+/// A parent's has-many relation to drop a deleted entity from.
+/// The parent id is read from the entity's `foreign_key` field in Lua, before the delete.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParentRelationDetach {
+    pub foreign_key: String,
+    pub parent_collection: String,
+    /// Layout of the parent's keys
+    pub parent_layout: KeyLayout,
+    pub alias: String,
+    /// JSON path of the parent's counter for the relation, refreshed after the removal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter_path: Option<String>,
 }
 
 /// Represents a unique constraint definition for delete cleanup.
@@ -189,8 +539,8 @@ pub struct CascadeRelationSpec {
     pub alias: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_collection: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target_service: Option<String>,
+    /// Layout of the target entities' keys, and of the relation keys derived for them
+    pub target_layout: KeyLayout,
     pub cascade: CascadeDirective,
     #[serde(skip_serializing_if = "skip_false")]
     pub maintain_reverse: bool,
@@ -204,13 +554,16 @@ pub struct DeleteCascadeRelation {
     pub relation_key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_collection: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target_service: Option<String>,
+    /// Layout of the target entities' keys
+    pub target_layout: KeyLayout,
     pub cascade: CascadeDirective,
     #[serde(skip_serializing_if = "skip_false")]
     pub maintain_reverse: bool,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub child_relations: Vec<CascadeRelationSpec>,
+    /// JSON path of the relation's counter, refreshed on entities whose set loses the deleted one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter_path: Option<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -220,10 +573,34 @@ pub struct RelationMutation {
     pub add: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub remove: Vec<String>,
+    /// Also remove every current member that is not in `add`
+    #[serde(skip_serializing_if = "skip_false")]
+    pub replace: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cascade: Option<CascadeDirective>,
     #[serde(skip_serializing_if = "skip_false")]
     pub maintain_reverse: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<RelationEdge>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter: Option<RelationCounter>,
+    /// Separator and head of the relation key, passed to the script as arguments
+    #[serde(skip)]
+    pub layout: KeyLayout,
+}
+
+/// Counter field refreshed from the relation set's cardinality after it is mutated.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RelationCounter {
+    pub entity_key: String,
+    pub path: String,
+}
+
+/// Payload attached to a single relation edge, stored as JSON in the relation's edge hash.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RelationEdge {
+    pub member_id: String,
+    pub data_json: String,
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -243,11 +620,22 @@ impl MutationPlan {
     pub fn is_empty(&self) -> bool {
         self.commands.is_empty()
     }
+
+    /// Make every entity write fail with `already_exists` instead of overwriting.
+    pub(crate) fn set_if_absent(&mut self) {
+        for command in &mut self.commands {
+            if let MutationCommand::UpsertEntity(mutation) = command {
+                mutation.if_absent = true;
+            }
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_entity_mutation(
     descriptor: &EntityDescriptor,
     key: String,
+    layout: KeyLayout,
     payload: serde_json::Value,
     mirrors: Vec<DatetimeMirrorValue>,
     expected_version: Option<u64>,
@@ -282,6 +670,7 @@ pub fn build_entity_mutation(
 
     // Build unique constraint checks from descriptor
     let unique_constraints = build_unique_constraint_checks(descriptor, &payload);
+    let hash_fields = descriptor.storage.is_hash().then(|| encode_hash_fields(&payload));
 
     Ok(EntityMutation {
         key,
@@ -293,6 +682,10 @@ pub fn build_entity_mutation(
         idempotency_ttl,
         relations: relation_mutations,
         unique_constraints,
+        lua_check: descriptor.lua_check.clone(),
+        hash_fields,
+        if_absent: false,
+        layout,
     })
 }
 
@@ -325,6 +718,7 @@ pub fn build_unique_constraint_checks(
 
 pub fn build_entity_delete(
     key: String,
+    layout: KeyLayout,
     expected_version: Option<u64>,
     relations: Vec<DeleteCascadeRelation>,
     unique_constraints: Vec<UniqueConstraintDefinition>,
@@ -334,11 +728,38 @@ pub fn build_entity_delete(
         expected_version,
         relations,
         unique_constraints,
+        parents: Vec::new(),
+        layout,
+    }
+}
+
+/// Encode an increment delta, keeping whole numbers integral so integer fields stay integers.
+pub(crate) fn increment_value(by: f64) -> serde_json::Value {
+    if by.fract() == 0.0 && by.abs() < i64::MAX as f64 {
+        serde_json::Value::from(by as i64)
+    } else {
+        serde_json::Value::from(by)
+    }
+}
+
+/// Encode array operation elements one by one, since `JSON.ARRAPPEND` takes each as its own argument.
+pub(crate) fn array_elements_json(op_type: &PatchOperationType, value: Option<&serde_json::Value>) -> Option<Vec<String>> {
+    if !matches!(op_type, PatchOperationType::ArrayAppend | PatchOperationType::ArrayRemove) {
+        return None;
     }
+    let elements = value?.as_array()?;
+    Some(
+        elements
+            .iter()
+            .map(|element| serde_json::to_string(element).expect("serde_json::Value serialization should not fail"))
+            .collect(),
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_entity_patch(
     key: String,
+    layout: KeyLayout,
     entity_id: Option<String>,
     expected_version: Option<u64>,
     operations: Vec<crate::repository::PatchOperation>,
@@ -355,7 +776,11 @@ pub fn build_entity_patch(
                 PatchOpKind::Assign(value) => (PatchOperationType::Assign, Some(value)),
                 PatchOpKind::Merge(value) => (PatchOperationType::Merge, Some(value)),
                 PatchOpKind::Delete => (PatchOperationType::Delete, None),
+                PatchOpKind::Increment(by) => (PatchOperationType::Increment, Some(increment_value(by))),
+                PatchOpKind::ArrayAppend(elements) => (PatchOperationType::ArrayAppend, Some(serde_json::Value::Array(elements))),
+                PatchOpKind::ArrayRemove(elements) => (PatchOperationType::ArrayRemove, Some(serde_json::Value::Array(elements))),
             };
+            let elements_json = array_elements_json(&op_type, value.as_ref());
             let value_json = value
                 .as_ref()
                 .map(|val| serde_json::to_string(val).expect("serde_json::Value serialization should not fail"));
@@ -367,8 +792,11 @@ pub fn build_entity_patch(
                 op_type,
                 value,
                 value_json,
+                hash_value: None,
                 mirror: operation.mirror,
                 mirror_value_json,
+                elements_json,
+                length: None,
             }
         })
         .collect();
@@ -382,6 +810,13 @@ pub fn build_entity_patch(
         idempotency_ttl,
         relations: relation_mutations,
         unique_constraints,
+        immutable_paths: Vec::new(),
+        lua_check: None,
+        hash: false,
+        history: None,
+        precondition: None,
+        touch: false,
+        layout,
     }
 }
 
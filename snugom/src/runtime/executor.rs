@@ -1,111 +1,347 @@
 use std::borrow::Cow;
 
-use redis::aio::ConnectionLike;
+use redis::{Script, aio::ConnectionLike};
 use serde_json::Value;
 
 use crate::{
-    errors::RepoError,
+    errors::{RepoError, ValidationError},
     runtime::{
-        commands::{MutationCommand, MutationPlan},
+        commands::{LengthBounds, MutationCommand, MutationPlan},
         scripts::{
-            ENTITY_DELETE_SCRIPT, ENTITY_DELETE_SCRIPT_BODY, ENTITY_GET_OR_CREATE_SCRIPT,
-            ENTITY_GET_OR_CREATE_SCRIPT_BODY, ENTITY_MUTATION_SCRIPT, ENTITY_MUTATION_SCRIPT_BODY,
-            ENTITY_PATCH_SCRIPT, ENTITY_PATCH_SCRIPT_BODY, ENTITY_UPSERT_SCRIPT, ENTITY_UPSERT_SCRIPT_BODY,
-            RELATION_MUTATION_SCRIPT, RELATION_MUTATION_SCRIPT_BODY,
+            ENTITY_DELETE_SCRIPT, ENTITY_GET_OR_CREATE_SCRIPT, ENTITY_MUTATION_SCRIPT, ENTITY_PATCH_SCRIPT,
+            ENTITY_UPSERT_SCRIPT, PLAN_SCRIPT, RELATION_MUTATION_SCRIPT,
         },
     },
+    telemetry::Latency,
 };
 
+/// Run each command of `plan` in order, stopping at the first failure.
+///
+/// Scripts are invoked by digest and loaded on `NOSCRIPT`; see
+/// [`ScriptRegistry`](crate::runtime::scripts::ScriptRegistry).
+///
+/// With the `tracing` feature, the scripts run inside a `snugom.lua` span nested under the
+/// calling operation's span.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "snugom.lua",
+        skip_all,
+        err(Display),
+        fields(commands = plan.commands.len(), elapsed_ms = tracing::field::Empty)
+    )
+)]
 pub async fn execute_plan<C>(conn: &mut C, plan: &MutationPlan) -> Result<Vec<Value>, RepoError>
 where
     C: ConnectionLike + Send,
 {
+    let _latency = Latency::start();
     let mut responses = Vec::with_capacity(plan.commands.len());
 
     for command in &plan.commands {
-        let (script, script_body) = match command {
-            MutationCommand::UpsertEntity(_) => (&*ENTITY_MUTATION_SCRIPT, ENTITY_MUTATION_SCRIPT_BODY),
-            MutationCommand::PatchEntity(_) => (&*ENTITY_PATCH_SCRIPT, ENTITY_PATCH_SCRIPT_BODY),
-            MutationCommand::DeleteEntity(_) => (&*ENTITY_DELETE_SCRIPT, ENTITY_DELETE_SCRIPT_BODY),
-            MutationCommand::MutateRelations(_) => (&*RELATION_MUTATION_SCRIPT, RELATION_MUTATION_SCRIPT_BODY),
-            MutationCommand::Upsert(_) => (&*ENTITY_UPSERT_SCRIPT, ENTITY_UPSERT_SCRIPT_BODY),
-            MutationCommand::GetOrCreate(_) => (&*ENTITY_GET_OR_CREATE_SCRIPT, ENTITY_GET_OR_CREATE_SCRIPT_BODY),
-        };
-
-        let payload = serde_json::to_string(command).map_err(|err| RepoError::Other {
-            message: Cow::Owned(format!("failed to serialize command: {err}")),
-        })?;
+        let script = script_for(command);
+        let payload = serialize_command(command)?;
 
         let mut invocation = script.prepare_invoke();
         invocation.arg(payload);
-        invocation.arg(script_body);
+        invocation.arg(script.get_hash());
+        invocation.arg(command.layout().separator.to_string());
+        invocation.arg(&command.layout().head);
         let raw: String = invocation.invoke_async(conn).await.map_err(RepoError::from)?;
 
-        let value: Value = serde_json::from_str(&raw).map_err(|err| RepoError::Other {
-            message: Cow::Owned(format!("failed to parse lua response: {err}")),
-        })?;
-
-        if let Some(error) = value.get("error") {
-            if let Some(code) = error.as_str() {
-                match code {
-                    "version_conflict" => {
-                        let expected = value.get("expected").and_then(|v| v.as_u64());
-                        let actual = value.get("actual").and_then(|v| v.as_u64());
-                        return Err(RepoError::VersionConflict { expected, actual });
-                    }
-                    "entity_not_found" => {
-                        let entity_id = value.get("entity_id").and_then(|v| v.as_str()).map(|s| s.to_string());
-                        return Err(RepoError::NotFound { entity_id });
-                    }
-                    "unique_constraint_violation" => {
-                        let fields = value
-                            .get("fields")
-                            .and_then(|v| v.as_array())
-                            .map(|arr| {
-                                arr.iter()
-                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-                        let values = value
-                            .get("values")
-                            .and_then(|v| v.as_array())
-                            .map(|arr| {
-                                arr.iter()
-                                    .map(|v| match v {
-                                        Value::String(s) => s.clone(),
-                                        other => other.to_string(),
-                                    })
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-                        let existing_entity_id = value
-                            .get("existing_entity_id")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .unwrap_or_default();
-                        return Err(RepoError::UniqueConstraintViolation {
-                            fields,
-                            values,
-                            existing_entity_id,
-                        });
-                    }
-                    other => {
-                        return Err(RepoError::Other {
-                            message: Cow::Owned(other.to_string()),
-                        });
+        responses.push(parse_response(&raw)?);
+    }
+
+    Ok(responses)
+}
+
+/// Run independent commands in a single pipeline, reporting each outcome separately.
+///
+/// Unlike [`execute_plan`], a failing command does not stop the ones after it, so callers
+/// must only batch commands that do not depend on each other. The outer error is reserved
+/// for failures that affect the whole round trip, such as a dropped connection.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "snugom.lua_pipeline",
+        skip_all,
+        err(Display),
+        fields(commands = commands.len(), elapsed_ms = tracing::field::Empty)
+    )
+)]
+pub async fn execute_pipelined<C>(
+    conn: &mut C,
+    commands: &[MutationCommand],
+) -> Result<Vec<Result<Value, RepoError>>, RepoError>
+where
+    C: ConnectionLike + Send,
+{
+    let _latency = Latency::start();
+    if commands.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut replies = invoke_pipelined(conn, commands.iter()).await?;
+
+    // A command answered with NOSCRIPT never ran, so load its script and send it again
+    let missing: Vec<usize> = (0..replies.len()).filter(|&index| is_noscript(&replies[index])).collect();
+    if !missing.is_empty() {
+        load_scripts(conn, missing.iter().map(|&index| &commands[index])).await?;
+        let retried = invoke_pipelined(conn, missing.iter().map(|&index| &commands[index])).await?;
+        for (index, reply) in missing.into_iter().zip(retried) {
+            replies[index] = reply;
+        }
+    }
+
+    Ok(replies.into_iter().map(parse_reply).collect())
+}
+
+/// Run every command of `plan` as one unit, in a single round trip.
+///
+/// The commands run in order inside one script, `lua/plan.lua`, so no other client's command
+/// runs between them. Each keeps its own checks (versions, unique constraints, preconditions);
+/// when one fails, the writes of the commands before it are undone and its error is returned,
+/// leaving Redis as it was before the plan.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "snugom.lua_transaction",
+        skip_all,
+        err(Display),
+        fields(commands = plan.commands.len(), elapsed_ms = tracing::field::Empty)
+    )
+)]
+pub async fn execute_atomic<C>(conn: &mut C, plan: &MutationPlan) -> Result<Vec<Value>, RepoError>
+where
+    C: ConnectionLike + Send,
+{
+    let _latency = Latency::start();
+    if plan.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let steps = plan
+        .commands
+        .iter()
+        .map(|command| {
+            Ok(serde_json::json!({
+                "operation": command.operation(),
+                "payload": serialize_command(command)?,
+                "sha": script_for(command).get_hash(),
+                "separator": command.layout().separator.to_string(),
+                "head": command.layout().head,
+            }))
+        })
+        .collect::<Result<Vec<Value>, RepoError>>()?;
+    let mut invocation = PLAN_SCRIPT.prepare_invoke();
+    invocation.arg(Value::Array(steps).to_string());
+    let reply: redis::Value = invocation.invoke_async(conn).await.map_err(RepoError::from)?;
+
+    match reply {
+        redis::Value::Array(replies) => replies.into_iter().map(parse_reply).collect(),
+        // A failed step answers with its own reply, which carries the error
+        other => {
+            parse_reply(other)?;
+            Err(RepoError::Other {
+                message: Cow::Borrowed("Unexpected plan response"),
+            })
+        }
+    }
+}
+
+/// Send one `EVALSHA` per command in a single pipeline, returning the raw replies so one script
+/// error does not mask the others.
+async fn invoke_pipelined<'c, C>(
+    conn: &mut C,
+    commands: impl Iterator<Item = &'c MutationCommand>,
+) -> Result<Vec<redis::Value>, RepoError>
+where
+    C: ConnectionLike + Send,
+{
+    let mut pipe = redis::pipe();
+    let count = queue_scripts(&mut pipe, commands)?;
+    conn.req_packed_commands(&pipe, 0, count).await.map_err(RepoError::from)
+}
+
+/// Queue an `EVALSHA` per command; returns how many were queued.
+fn queue_scripts<'c>(
+    pipe: &mut redis::Pipeline,
+    commands: impl Iterator<Item = &'c MutationCommand>,
+) -> Result<usize, RepoError> {
+    let mut count = 0;
+    for command in commands {
+        let script = script_for(command);
+        let mut invocation = script.prepare_invoke();
+        invocation.arg(serialize_command(command)?);
+        invocation.arg(script.get_hash());
+        invocation.arg(command.layout().separator.to_string());
+        invocation.arg(&command.layout().head);
+        pipe.invoke_script(&invocation);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The distinct scripts run by `commands`.
+fn distinct_scripts<'c>(commands: impl Iterator<Item = &'c MutationCommand>) -> Vec<&'static Script> {
+    let mut scripts: Vec<&'static Script> = Vec::new();
+    for command in commands {
+        let script = script_for(command);
+        if !scripts.iter().any(|known| known.get_hash() == script.get_hash()) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+/// `SCRIPT LOAD` the scripts run by `commands`, in one pipeline.
+async fn load_scripts<'c, C>(conn: &mut C, commands: impl Iterator<Item = &'c MutationCommand>) -> Result<(), RepoError>
+where
+    C: ConnectionLike + Send,
+{
+    let mut pipe = redis::pipe();
+    for script in distinct_scripts(commands) {
+        pipe.load_script(script).ignore();
+    }
+    pipe.query_async::<()>(conn).await?;
+    Ok(())
+}
+
+fn is_noscript(reply: &redis::Value) -> bool {
+    matches!(reply, redis::Value::ServerError(err) if err.code() == "NOSCRIPT")
+}
+
+fn parse_reply(reply: redis::Value) -> Result<Value, RepoError> {
+    let reply = reply.extract_error().map_err(RepoError::from)?;
+    let raw: String = redis::from_owned_redis_value(reply).map_err(RepoError::from)?;
+    parse_response(&raw)
+}
+
+fn script_for(command: &MutationCommand) -> &'static Script {
+    match command {
+        MutationCommand::UpsertEntity(_) => &ENTITY_MUTATION_SCRIPT,
+        MutationCommand::PatchEntity(_) => &ENTITY_PATCH_SCRIPT,
+        MutationCommand::DeleteEntity(_) => &ENTITY_DELETE_SCRIPT,
+        MutationCommand::MutateRelations(_) => &RELATION_MUTATION_SCRIPT,
+        MutationCommand::Upsert(_) => &ENTITY_UPSERT_SCRIPT,
+        MutationCommand::GetOrCreate(_) => &ENTITY_GET_OR_CREATE_SCRIPT,
+    }
+}
+
+fn serialize_command(command: &MutationCommand) -> Result<String, RepoError> {
+    serde_json::to_string(command).map_err(|err| RepoError::Other {
+        message: Cow::Owned(format!("failed to serialize command: {err}")),
+    })
+}
+
+/// Decode a script reply, mapping its `error` field onto the matching `RepoError`.
+fn parse_response(raw: &str) -> Result<Value, RepoError> {
+    let value: Value = serde_json::from_str(raw).map_err(|err| RepoError::Other {
+        message: Cow::Owned(format!("failed to parse lua response: {err}")),
+    })?;
+
+    if let Some(error) = value.get("error") {
+        if let Some(code) = error.as_str() {
+            match code {
+                "version_conflict" => {
+                    let expected = value.get("expected").and_then(|v| v.as_u64());
+                    let actual = value.get("actual").and_then(|v| v.as_u64());
+                    return Err(RepoError::VersionConflict { expected, actual });
+                }
+                "already_exists" => {
+                    let entity_id = value.get("entity_id").and_then(|v| v.as_str()).unwrap_or_default();
+                    return Err(RepoError::AlreadyExists {
+                        entity_id: entity_id.to_string(),
+                    });
+                }
+                "precondition_failed" => {
+                    let entity_id = value.get("entity_id").and_then(|v| v.as_str()).unwrap_or_default();
+                    return Err(RepoError::PreconditionFailed {
+                        entity_id: entity_id.to_string(),
+                    });
+                }
+                "entity_not_found" => {
+                    let entity_id = value.get("entity_id").and_then(|v| v.as_str()).map(|s| s.to_string());
+                    return Err(RepoError::NotFound { entity_id });
+                }
+                "unique_constraint_violation" => {
+                    let fields = value
+                        .get("fields")
+                        .and_then(|v| v.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let values = value
+                        .get("values")
+                        .and_then(|v| v.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .map(|v| match v {
+                                    Value::String(s) => s.clone(),
+                                    other => other.to_string(),
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let existing_entity_id = value
+                        .get("existing_entity_id")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
+                    return Err(RepoError::UniqueConstraintViolation {
+                        fields,
+                        values,
+                        existing_entity_id,
+                    });
+                }
+                "immutable_field" => {
+                    let field = value.get("field").and_then(|v| v.as_str()).unwrap_or_default();
+                    return Err(RepoError::Validation(ValidationError::single(
+                        field,
+                        "patch.immutable_field",
+                        "field is immutable once created",
+                    )));
+                }
+                "length_violation" => {
+                    let field = value.get("field").and_then(|v| v.as_str()).unwrap_or_default();
+                    let length = value.get("length").and_then(Value::as_u64).unwrap_or_default() as usize;
+                    let bound = |name: &str| value.get(name).and_then(Value::as_u64).map(|bound| bound as usize);
+                    let bounds = LengthBounds {
+                        min: bound("min"),
+                        max: bound("max"),
+                    };
+                    if let Some(err) = bounds.violation(field, length) {
+                        return Err(RepoError::Validation(err));
                     }
                 }
+                "lua_check_failed" => {
+                    let field = value.get("field").and_then(|v| v.as_str()).unwrap_or("__entity");
+                    let message = value
+                        .get("message")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("rejected by lua_check");
+                    return Err(RepoError::Validation(ValidationError::single(
+                        field,
+                        "validation.lua_check",
+                        message,
+                    )));
+                }
+                other => {
+                    return Err(RepoError::Other {
+                        message: Cow::Owned(other.to_string()),
+                    });
+                }
             }
-            return Err(RepoError::Other {
-                message: Cow::Owned("lua_error".to_string()),
-            });
         }
-
-        responses.push(value);
+        return Err(RepoError::Other {
+            message: Cow::Owned("lua_error".to_string()),
+        });
     }
 
-    Ok(responses)
+    Ok(value)
 }
 
 #[allow(async_fn_in_trait)]
@@ -113,11 +349,25 @@ pub trait MutationExecutor {
     async fn execute(&mut self, plan: MutationPlan) -> Result<Vec<Value>, RepoError>;
 }
 
+/// How a [`RedisExecutor`] sends the commands of a plan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlanExecution {
+    /// One round trip per command, stopping at the first failure; see [`execute_plan`]
+    #[default]
+    Sequential,
+    /// Every command in one pipeline. A failure doesn't stop the commands after it, so use this
+    /// for plans whose commands don't depend on each other, such as a batch of deletes.
+    Pipelined,
+    /// Every command in one round trip, applied whole or not at all; see [`execute_atomic`]
+    Atomic,
+}
+
 pub struct RedisExecutor<'a, C>
 where
     C: ConnectionLike + Send,
 {
     connection: &'a mut C,
+    mode: PlanExecution,
 }
 
 impl<'a, C> RedisExecutor<'a, C>
@@ -125,7 +375,22 @@ where
     C: ConnectionLike + Send,
 {
     pub fn new(connection: &'a mut C) -> Self {
-        Self { connection }
+        Self {
+            connection,
+            mode: PlanExecution::Sequential,
+        }
+    }
+
+    /// Send plans with more than one command as `mode` says; single commands always go on their own.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut executor = RedisExecutor::new(&mut conn).with_mode(PlanExecution::Atomic);
+    /// repo.delete(&mut executor, &id, None).await?;
+    /// ```
+    pub fn with_mode(mut self, mode: PlanExecution) -> Self {
+        self.mode = mode;
+        self
     }
 }
 
@@ -133,7 +398,61 @@ impl<'a, C> MutationExecutor for RedisExecutor<'a, C>
 where
     C: ConnectionLike + Send,
 {
+    /// Responses come back in plan order; in every mode the first failure is returned.
     async fn execute(&mut self, plan: MutationPlan) -> Result<Vec<Value>, RepoError> {
-        execute_plan(self.connection, &plan).await
+        if plan.commands.len() < 2 {
+            return execute_plan(self.connection, &plan).await;
+        }
+        match self.mode {
+            PlanExecution::Sequential => execute_plan(self.connection, &plan).await,
+            PlanExecution::Pipelined => execute_pipelined(self.connection, &plan.commands).await?.into_iter().collect(),
+            PlanExecution::Atomic => execute_atomic(self.connection, &plan).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_violations_become_typed_errors() {
+        let raw = r#"{"error":"unique_constraint_violation","fields":["tenant_id","seat"],"values":["acme",12],
+            "existing_entity_id":"u1"}"#;
+        match parse_response(raw) {
+            Err(RepoError::UniqueConstraintViolation {
+                fields,
+                values,
+                existing_entity_id,
+            }) => {
+                assert_eq!(fields, ["tenant_id", "seat"]);
+                assert_eq!(values, ["acme", "12"]);
+                assert_eq!(existing_entity_id, "u1");
+            }
+            other => panic!("expected a unique violation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn pipelines_send_digests_only() {
+        let layout = crate::keys::KeyContext::new("app", "shop").layout();
+        let delete = |id: &str| {
+            MutationCommand::DeleteEntity(crate::runtime::commands::build_entity_delete(
+                layout.join(&["orders", id]),
+                layout.clone(),
+                None,
+                Vec::new(),
+                Vec::new(),
+            ))
+        };
+        let commands = [delete("o1"), delete("o2")];
+        let mut pipe = redis::pipe();
+        assert_eq!(queue_scripts(&mut pipe, commands.iter()).unwrap(), 2);
+
+        let packed = String::from_utf8_lossy(&pipe.get_packed_pipeline()).into_owned();
+        assert_eq!(packed.matches("EVALSHA").count(), 2);
+        assert!(packed.contains(ENTITY_DELETE_SCRIPT.get_hash()));
+        assert!(!packed.contains("SCRIPT"));
+        assert_eq!(distinct_scripts(commands.iter()).len(), 1);
     }
 }
@@ -1,12 +1,31 @@
-use redis::Script;
+use redis::{Script, aio::ConnectionLike};
 use std::sync::LazyLock;
 
-pub const ENTITY_MUTATION_SCRIPT_BODY: &str = include_str!("../../lua/entity_mutation.lua");
-pub const ENTITY_PATCH_SCRIPT_BODY: &str = include_str!("../../lua/entity_patch.lua");
-pub const ENTITY_DELETE_SCRIPT_BODY: &str = include_str!("../../lua/entity_delete.lua");
-pub const ENTITY_UPSERT_SCRIPT_BODY: &str = include_str!("../../lua/entity_upsert.lua");
-pub const ENTITY_GET_OR_CREATE_SCRIPT_BODY: &str = include_str!("../../lua/entity_get_or_create.lua");
-pub const RELATION_MUTATION_SCRIPT_BODY: &str = include_str!("../../lua/relation_mutation.lua");
+use crate::errors::RepoError;
+
+/// Prepends the helpers shared by the scripts: `lua/keys.lua` for every one, and
+/// `lua/lua_check.lua` for those that run an entity's `lua_check`.
+macro_rules! script_body {
+    ($file:literal) => {
+        concat!(include_str!("../../lua/keys.lua"), "\n", include_str!($file))
+    };
+    ($file:literal, lua_check) => {
+        concat!(
+            include_str!("../../lua/keys.lua"),
+            "\n",
+            include_str!("../../lua/lua_check.lua"),
+            "\n",
+            include_str!($file)
+        )
+    };
+}
+
+pub const ENTITY_MUTATION_SCRIPT_BODY: &str = script_body!("../../lua/entity_mutation.lua", lua_check);
+pub const ENTITY_PATCH_SCRIPT_BODY: &str = script_body!("../../lua/entity_patch.lua", lua_check);
+pub const ENTITY_DELETE_SCRIPT_BODY: &str = script_body!("../../lua/entity_delete.lua");
+pub const ENTITY_UPSERT_SCRIPT_BODY: &str = script_body!("../../lua/entity_upsert.lua", lua_check);
+pub const ENTITY_GET_OR_CREATE_SCRIPT_BODY: &str = script_body!("../../lua/entity_get_or_create.lua", lua_check);
+pub const RELATION_MUTATION_SCRIPT_BODY: &str = script_body!("../../lua/relation_mutation.lua");
 
 pub static ENTITY_MUTATION_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(ENTITY_MUTATION_SCRIPT_BODY));
 pub static ENTITY_PATCH_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(ENTITY_PATCH_SCRIPT_BODY));
@@ -14,3 +33,130 @@ pub static ENTITY_DELETE_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new
 pub static ENTITY_UPSERT_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(ENTITY_UPSERT_SCRIPT_BODY));
 pub static ENTITY_GET_OR_CREATE_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(ENTITY_GET_OR_CREATE_SCRIPT_BODY));
 pub static RELATION_MUTATION_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(RELATION_MUTATION_SCRIPT_BODY));
+
+/// `lua/plan.lua` followed by every mutation script as one of its steps, with the script's
+/// writes journaled so a failing step can undo the ones before it.
+pub static PLAN_SCRIPT_BODY: LazyLock<String> = LazyLock::new(|| {
+    let mut body = String::from(include_str!("../../lua/plan.lua"));
+    for (operation, source) in [
+        ("entity_mutation", ENTITY_MUTATION_SCRIPT_BODY),
+        ("entity_patch", ENTITY_PATCH_SCRIPT_BODY),
+        ("entity_delete", ENTITY_DELETE_SCRIPT_BODY),
+        ("entity_upsert", ENTITY_UPSERT_SCRIPT_BODY),
+        ("entity_get_or_create", ENTITY_GET_OR_CREATE_SCRIPT_BODY),
+        ("relation_mutation", RELATION_MUTATION_SCRIPT_BODY),
+    ] {
+        body.push_str(&format!(
+            "\nSTEPS[\"{operation}\"] = function(ARGV)\n{}\nend\n",
+            source.replace("redis.call(", "journaled_call(")
+        ));
+    }
+    body.push_str("\nreturn run_plan(cjson.decode(ARGV[1]))\n");
+    body
+});
+pub static PLAN_SCRIPT: LazyLock<Script> = LazyLock::new(|| Script::new(&PLAN_SCRIPT_BODY));
+
+/// The mutation scripts, cached on the server ahead of use.
+///
+/// Invocations go out as `EVALSHA`, carrying the script's SHA1 digest rather than its body. When
+/// the server answers `NOSCRIPT` (a fresh server, a failover, or `SCRIPT FLUSH`), the script is
+/// loaded and the call retried, so loading up front only moves that extra round trip to startup.
+pub struct ScriptRegistry;
+
+impl ScriptRegistry {
+    /// Every mutation script, by the name of its file in `lua/`.
+    pub fn scripts() -> [(&'static str, &'static Script); 7] {
+        [
+            ("entity_mutation", &*ENTITY_MUTATION_SCRIPT),
+            ("entity_patch", &*ENTITY_PATCH_SCRIPT),
+            ("entity_delete", &*ENTITY_DELETE_SCRIPT),
+            ("entity_upsert", &*ENTITY_UPSERT_SCRIPT),
+            ("entity_get_or_create", &*ENTITY_GET_OR_CREATE_SCRIPT),
+            ("relation_mutation", &*RELATION_MUTATION_SCRIPT),
+            ("plan", &*PLAN_SCRIPT),
+        ]
+    }
+
+    /// `SCRIPT LOAD` every mutation script in one pipeline.
+    pub async fn load<C>(conn: &mut C) -> Result<(), RepoError>
+    where
+        C: ConnectionLike + Send,
+    {
+        let mut pipe = redis::pipe();
+        for (_, script) in Self::scripts() {
+            pipe.load_script(script).ignore();
+        }
+        pipe.query_async::<()>(conn).await?;
+        Ok(())
+    }
+
+    /// Names of the mutation scripts missing from the server's script cache.
+    pub async fn missing<C>(conn: &mut C) -> Result<Vec<&'static str>, RepoError>
+    where
+        C: ConnectionLike + Send,
+    {
+        let scripts = Self::scripts();
+        let mut command = redis::cmd("SCRIPT");
+        command.arg("EXISTS");
+        for (_, script) in &scripts {
+            command.arg(script.get_hash());
+        }
+        let cached: Vec<bool> = command.query_async(conn).await?;
+        Ok(scripts
+            .iter()
+            .zip(cached)
+            .filter(|(_, cached)| !cached)
+            .map(|((name, _), _)| *name)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_lists_distinct_scripts() {
+        let scripts = ScriptRegistry::scripts();
+        for (index, (name, script)) in scripts.iter().enumerate() {
+            assert_eq!(script.get_hash().len(), 40, "{name}");
+            assert!(scripts[index + 1..].iter().all(|(_, other)| other.get_hash() != script.get_hash()), "{name}");
+        }
+    }
+
+    #[test]
+    fn lua_check_helpers_are_defined_once_per_script() {
+        for body in [
+            ENTITY_MUTATION_SCRIPT_BODY,
+            ENTITY_PATCH_SCRIPT_BODY,
+            ENTITY_UPSERT_SCRIPT_BODY,
+            ENTITY_GET_OR_CREATE_SCRIPT_BODY,
+        ] {
+            assert_eq!(body.matches("local function run_lua_check(").count(), 1);
+            assert_eq!(body.matches("local function read_document(").count(), 1);
+        }
+    }
+
+    #[test]
+    fn every_script_starts_with_the_key_helpers() {
+        for body in [
+            ENTITY_MUTATION_SCRIPT_BODY,
+            ENTITY_PATCH_SCRIPT_BODY,
+            ENTITY_DELETE_SCRIPT_BODY,
+            ENTITY_UPSERT_SCRIPT_BODY,
+            ENTITY_GET_OR_CREATE_SCRIPT_BODY,
+            RELATION_MUTATION_SCRIPT_BODY,
+        ] {
+            assert!(body.starts_with(include_str!("../../lua/keys.lua")));
+            assert!(!body.contains("split_key("));
+        }
+    }
+
+    #[test]
+    fn plan_steps_journal_their_writes() {
+        let (driver, steps) = PLAN_SCRIPT_BODY.split_at(include_str!("../../lua/plan.lua").len());
+        assert!(driver.contains("local function run_plan("));
+        assert_eq!(steps.matches("STEPS[\"").count(), 6);
+        assert!(!steps.contains("redis.call("));
+    }
+}
@@ -0,0 +1,471 @@
+//! `FT.AGGREGATE` helpers that summarize indexed values rather than returning documents.
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use redis::{Value, aio::ConnectionManager, cmd, from_redis_value};
+
+use super::{IndexDefinition, IndexFieldType, SEARCH_DIALECT};
+use crate::errors::RepoError;
+
+/// One value of a TAG field and the number of matching documents holding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistinctValue {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Most buckets [`time_buckets`] returns; over twenty-five years of days.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Reducer applied by [`aggregate_numeric`] over a NUMERIC field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggOp {
+    Min,
+    Max,
+    Avg,
+    Sum,
+}
+
+impl AggOp {
+    /// The `FT.AGGREGATE` reducer name.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AggOp::Min => "MIN",
+            AggOp::Max => "MAX",
+            AggOp::Avg => "AVG",
+            AggOp::Sum => "SUM",
+        }
+    }
+}
+
+/// Width of the buckets [`time_buckets`] groups documents into, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    Day,
+    /// Weeks starting on Monday
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl TimeBucket {
+    /// `APPLY` expression rounding the epoch-millisecond attribute down to its bucket start.
+    ///
+    /// Days and weeks have a fixed length, so they are floored arithmetically (the epoch fell on
+    /// a Thursday, so weeks are offset by four days); months use RediSearch's `month()`, which
+    /// works in seconds.
+    fn apply_expression(self, attribute: &str) -> String {
+        const DAY_MS: i64 = 86_400_000;
+        const WEEK_MS: i64 = 7 * DAY_MS;
+        const MONDAY_OFFSET_MS: i64 = 4 * DAY_MS;
+        match self {
+            TimeBucket::Day => format!("floor(@{attribute} / {DAY_MS}) * {DAY_MS}"),
+            TimeBucket::Week => format!(
+                "floor((@{attribute} - {MONDAY_OFFSET_MS}) / {WEEK_MS}) * {WEEK_MS} + {MONDAY_OFFSET_MS}"
+            ),
+            TimeBucket::Month => format!("month(floor(@{attribute} / 1000)) * 1000"),
+        }
+    }
+}
+
+/// The number of documents whose timestamp falls in the bucket starting at `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketCount {
+    pub start: DateTime<Utc>,
+    pub count: u64,
+}
+
+/// The distinct values of the TAG field `field` among the documents matching `query`, most
+/// common first, at most `limit` of them.
+///
+/// `field` is a filter name such as `status` or `address.city`; enum fields are read from
+/// their `__{field}_tag` shadow, and `casefold` fields report lowercased values. Documents
+/// without the field are not counted.
+pub async fn distinct_values(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    field: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<DistinctValue>, RepoError> {
+    let attribute = tag_attribute(definition, field)?;
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let raw: Value = cmd("FT.AGGREGATE")
+        .arg(definition.name.as_str())
+        .arg(query)
+        .arg("GROUPBY")
+        .arg(1)
+        .arg(format!("@{attribute}"))
+        .arg("REDUCE")
+        .arg("COUNT")
+        .arg(0)
+        .arg("AS")
+        .arg("count")
+        .arg("SORTBY")
+        .arg(4)
+        .arg("@count")
+        .arg("DESC")
+        .arg(format!("@{attribute}"))
+        .arg("ASC")
+        .arg("MAX")
+        .arg(limit)
+        .arg("LIMIT")
+        .arg(0)
+        .arg(limit)
+        .arg("DIALECT")
+        .arg(SEARCH_DIALECT)
+        .query_async(conn)
+        .await?;
+    parse_distinct_reply(attribute, raw)
+}
+
+/// Reduce the NUMERIC field `field` over the documents matching `query` with `op`, without
+/// fetching them.
+///
+/// `field` is a filter name; datetime fields are read from their `{field}_ts` mirror, in
+/// epoch milliseconds. Returns `None` when no document has a value to reduce.
+pub async fn aggregate_numeric(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    field: &str,
+    op: AggOp,
+    query: &str,
+) -> Result<Option<f64>, RepoError> {
+    let attribute = numeric_attribute(definition, field)?;
+    let raw: Value = cmd("FT.AGGREGATE")
+        .arg(definition.name.as_str())
+        .arg(query)
+        .arg("GROUPBY")
+        .arg(0)
+        .arg("REDUCE")
+        .arg(op.as_str())
+        .arg(1)
+        .arg(format!("@{attribute}"))
+        .arg("AS")
+        .arg("value")
+        .arg("DIALECT")
+        .arg(SEARCH_DIALECT)
+        .query_async(conn)
+        .await?;
+    parse_numeric_reply(raw)
+}
+
+/// Count the documents matching `query` per `bucket` of the datetime field `field`, oldest
+/// bucket first, for activity charts.
+///
+/// `field` is read from its `{field}_ts` mirror, or may itself be a NUMERIC field holding epoch
+/// milliseconds. Buckets without documents are left out, and so are documents without the field.
+pub async fn time_buckets(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    field: &str,
+    bucket: TimeBucket,
+    query: &str,
+) -> Result<Vec<BucketCount>, RepoError> {
+    let attribute = numeric_attribute(definition, field)?;
+    let raw: Value = cmd("FT.AGGREGATE")
+        .arg(definition.name.as_str())
+        .arg(query)
+        .arg("LOAD")
+        .arg(1)
+        .arg(format!("@{attribute}"))
+        .arg("FILTER")
+        .arg(format!("exists(@{attribute})"))
+        .arg("APPLY")
+        .arg(bucket.apply_expression(attribute))
+        .arg("AS")
+        .arg("bucket")
+        .arg("GROUPBY")
+        .arg(1)
+        .arg("@bucket")
+        .arg("REDUCE")
+        .arg("COUNT")
+        .arg(0)
+        .arg("AS")
+        .arg("count")
+        .arg("SORTBY")
+        .arg(2)
+        .arg("@bucket")
+        .arg("ASC")
+        .arg("LIMIT")
+        .arg(0)
+        .arg(MAX_BUCKETS)
+        .arg("DIALECT")
+        .arg(SEARCH_DIALECT)
+        .query_async(conn)
+        .await?;
+    parse_bucket_reply(raw)
+}
+
+/// The TAG attribute holding `field`, preferring the enum shadow and the stored casing over
+/// the casefold shadow.
+fn tag_attribute<'a>(definition: &'a IndexDefinition, field: &str) -> Result<&'a str, RepoError> {
+    let candidates = [
+        format!("__{field}_tag"),
+        field.to_string(),
+        field.replace('.', "_"),
+        format!("__{field}_lc"),
+    ];
+    find_attribute(definition, field, &candidates, IndexFieldType::Tag)
+}
+
+/// The NUMERIC attribute holding `field`, preferring the mirror of a datetime field.
+fn numeric_attribute<'a>(definition: &'a IndexDefinition, field: &str) -> Result<&'a str, RepoError> {
+    let candidates = [format!("{field}_ts"), field.to_string(), field.replace('.', "_")];
+    find_attribute(definition, field, &candidates, IndexFieldType::Numeric)
+}
+
+/// The first of `candidates` the index holds as a `field_type` attribute.
+fn find_attribute<'a>(
+    definition: &'a IndexDefinition,
+    field: &str,
+    candidates: &[String],
+    field_type: IndexFieldType,
+) -> Result<&'a str, RepoError> {
+    candidates
+        .iter()
+        .find_map(|candidate| {
+            definition
+                .schema
+                .iter()
+                .find(|index| index.field_name == candidate && index.field_type.as_str() == field_type.as_str())
+        })
+        .map(|index| index.field_name)
+        .ok_or_else(|| RepoError::InvalidRequest {
+            message: format!("`{field}` is not a {} field of index `{}`", field_type.as_str(), definition.name),
+        })
+}
+
+fn parse_distinct_reply(attribute: &str, raw: Value) -> Result<Vec<DistinctValue>, RepoError> {
+    let rows = aggregate_rows(raw)?;
+    Ok(rows.into_iter().filter_map(|row| distinct_value(attribute, row)).collect())
+}
+
+fn parse_bucket_reply(raw: Value) -> Result<Vec<BucketCount>, RepoError> {
+    let rows = aggregate_rows(raw)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let start = DateTime::from_timestamp_millis(numeric_value(row.clone(), "bucket")? as i64)?;
+            let count = numeric_value(row, "count").unwrap_or(0.0) as u64;
+            Some(BucketCount { start, count })
+        })
+        .collect())
+}
+
+fn parse_numeric_reply(raw: Value) -> Result<Option<f64>, RepoError> {
+    let rows = aggregate_rows(raw)?;
+    Ok(rows.into_iter().next().and_then(|row| numeric_value(row, "value")))
+}
+
+/// The key/value pairs of each row: `[total, [key, value, ...], ...]` in RESP2, or a map whose
+/// `results` hold each row's `extra_attributes` in RESP3.
+fn aggregate_rows(raw: Value) -> Result<Vec<Vec<(Value, Value)>>, RepoError> {
+    let rows = match raw {
+        Value::Array(entries) => entries.into_iter().skip(1).collect(),
+        Value::Map(pairs) => match pairs.into_iter().find(|(key, _)| is_key(key, "results")) {
+            Some((_, Value::Array(rows))) => rows,
+            _ => Vec::new(),
+        },
+        other => {
+            return Err(RepoError::Other {
+                message: Cow::Owned(format!("Unexpected FT.AGGREGATE response: {other:?}")),
+            });
+        }
+    };
+    Ok(rows.into_iter().filter_map(row_pairs).collect())
+}
+
+fn row_pairs(row: Value) -> Option<Vec<(Value, Value)>> {
+    match row {
+        Value::Array(entries) => {
+            let mut entries = entries.into_iter();
+            Some(std::iter::from_fn(|| Some((entries.next()?, entries.next()?))).collect())
+        }
+        Value::Map(pairs) => match pairs.into_iter().find(|(key, _)| is_key(key, "extra_attributes")) {
+            Some((_, Value::Map(attributes))) => Some(attributes),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn distinct_value(attribute: &str, row: Vec<(Value, Value)>) -> Option<DistinctValue> {
+    let value = row
+        .iter()
+        .find(|(key, _)| is_key(key, attribute))
+        .and_then(|(_, value)| from_redis_value::<String>(value).ok())?;
+    let count = numeric_value(row, "count").unwrap_or(0.0) as u64;
+    Some(DistinctValue { value, count })
+}
+
+/// A reducer output, which arrives as a string in RESP2 and a number in RESP3; `nan` (a
+/// reducer over no values) reads as `None`.
+fn numeric_value(row: Vec<(Value, Value)>, name: &str) -> Option<f64> {
+    let (_, value) = row.into_iter().find(|(key, _)| is_key(key, name))?;
+    let value = match value {
+        Value::Int(value) => value as f64,
+        Value::Double(value) => value,
+        other => from_redis_value::<String>(&other).ok()?.parse().ok()?,
+    };
+    (!value.is_nan()).then_some(value)
+}
+
+fn is_key(key: &Value, name: &str) -> bool {
+    from_redis_value::<String>(key).is_ok_and(|key| key == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::IndexField;
+    use crate::types::StorageMode;
+
+    static SCHEMA: &[IndexField] = &[
+        IndexField {
+            path: "$.status",
+            field_name: "__status_tag",
+            field_type: IndexFieldType::Tag,
+            sortable: false,
+            index_missing: false,
+        },
+        IndexField {
+            path: "$.address.city",
+            field_name: "address_city",
+            field_type: IndexFieldType::Tag,
+            sortable: false,
+            index_missing: false,
+        },
+        IndexField {
+            path: "$.total",
+            field_name: "total",
+            field_type: IndexFieldType::Numeric,
+            sortable: true,
+            index_missing: false,
+        },
+        IndexField {
+            path: "$.created_at_ts",
+            field_name: "created_at_ts",
+            field_type: IndexFieldType::Numeric,
+            sortable: true,
+            index_missing: false,
+        },
+    ];
+
+    fn definition() -> IndexDefinition {
+        IndexDefinition {
+            name: "app:shop:orders:idx".to_string(),
+            prefixes: vec!["app:shop:orders:".to_string()],
+            filter: None,
+            schema: SCHEMA,
+            storage: StorageMode::Json,
+            language: None,
+            language_field: None,
+        }
+    }
+
+    fn bulk(text: &str) -> Value {
+        Value::BulkString(text.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn tag_attribute_resolves_shadows_and_nested_names() {
+        let definition = definition();
+        assert_eq!(tag_attribute(&definition, "status").unwrap(), "__status_tag");
+        assert_eq!(tag_attribute(&definition, "address.city").unwrap(), "address_city");
+        assert!(matches!(tag_attribute(&definition, "total"), Err(RepoError::InvalidRequest { .. })));
+        assert!(matches!(tag_attribute(&definition, "missing"), Err(RepoError::InvalidRequest { .. })));
+    }
+
+    #[test]
+    fn parses_resp2_rows_skipping_documents_without_the_field() {
+        let raw = Value::Array(vec![
+            Value::Int(3),
+            Value::Array(vec![bulk("__status_tag"), bulk("paid"), bulk("count"), bulk("7")]),
+            Value::Array(vec![bulk("__status_tag"), bulk("pending"), bulk("count"), bulk("2")]),
+            Value::Array(vec![bulk("__status_tag"), Value::Nil, bulk("count"), bulk("1")]),
+        ]);
+        let values = parse_distinct_reply("__status_tag", raw).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                DistinctValue {
+                    value: "paid".to_string(),
+                    count: 7,
+                },
+                DistinctValue {
+                    value: "pending".to_string(),
+                    count: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_resp3_results() {
+        let row = |value: &str, count: i64| {
+            Value::Map(vec![
+                (
+                    bulk("extra_attributes"),
+                    Value::Map(vec![(bulk("address_city"), bulk(value)), (bulk("count"), Value::Int(count))]),
+                ),
+                (bulk("values"), Value::Array(Vec::new())),
+            ])
+        };
+        let raw = Value::Map(vec![
+            (bulk("total_results"), Value::Int(2)),
+            (bulk("results"), Value::Array(vec![row("Oslo", 4), row("Bergen", 1)])),
+        ]);
+        let values = parse_distinct_reply("address_city", raw).unwrap();
+        let pairs: Vec<_> = values.iter().map(|value| (value.value.as_str(), value.count)).collect();
+        assert_eq!(pairs, vec![("Oslo", 4), ("Bergen", 1)]);
+    }
+
+    #[test]
+    fn numeric_attribute_prefers_the_datetime_mirror() {
+        let definition = definition();
+        assert_eq!(numeric_attribute(&definition, "total").unwrap(), "total");
+        assert_eq!(numeric_attribute(&definition, "created_at").unwrap(), "created_at_ts");
+        assert!(matches!(numeric_attribute(&definition, "status"), Err(RepoError::InvalidRequest { .. })));
+    }
+
+    #[test]
+    fn bucket_expressions_floor_to_the_bucket_start() {
+        assert_eq!(
+            TimeBucket::Day.apply_expression("created_at_ts"),
+            "floor(@created_at_ts / 86400000) * 86400000"
+        );
+        assert_eq!(
+            TimeBucket::Week.apply_expression("created_at_ts"),
+            "floor((@created_at_ts - 345600000) / 604800000) * 604800000 + 345600000"
+        );
+        assert_eq!(TimeBucket::Month.apply_expression("created_at_ts"), "month(floor(@created_at_ts / 1000)) * 1000");
+    }
+
+    #[test]
+    fn parses_bucket_rows_as_utc_starts() {
+        let raw = Value::Array(vec![
+            Value::Int(2),
+            Value::Array(vec![bulk("bucket"), bulk("1704067200000"), bulk("count"), bulk("3")]),
+            Value::Array(vec![bulk("bucket"), bulk("1704153600000"), bulk("count"), bulk("1")]),
+        ]);
+        let buckets = parse_bucket_reply(raw).unwrap();
+        let starts: Vec<_> = buckets.iter().map(|bucket| (bucket.start.to_rfc3339(), bucket.count)).collect();
+        assert_eq!(
+            starts,
+            vec![("2024-01-01T00:00:00+00:00".to_string(), 3), ("2024-01-02T00:00:00+00:00".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn parses_numeric_reducer_output() {
+        let reply = |value: Value| Value::Array(vec![Value::Int(1), Value::Array(vec![bulk("value"), value])]);
+        assert_eq!(parse_numeric_reply(reply(bulk("12.5"))).unwrap(), Some(12.5));
+        assert_eq!(parse_numeric_reply(reply(Value::Double(3.0))).unwrap(), Some(3.0));
+        assert_eq!(parse_numeric_reply(reply(bulk("nan"))).unwrap(), None);
+        assert_eq!(parse_numeric_reply(Value::Array(vec![Value::Int(0)])).unwrap(), None);
+    }
+}
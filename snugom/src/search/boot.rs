@@ -0,0 +1,211 @@
+//! Creating or updating a set of indexes at startup, with a report of what changed.
+
+use std::{borrow::Cow, fmt};
+
+use futures_util::future::join_all;
+use redis::{aio::ConnectionManager, cmd};
+
+use super::{
+    IndexDefinition, create_index,
+    info::{IndexDrift, detect_drift, index_info},
+    push_field_args,
+};
+use crate::errors::RepoError;
+
+/// What [`ensure_indexes`] did with one index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexBootOutcome {
+    Created,
+    /// The index was already there and left as is; `drift` lists how it differs from the
+    /// declaration, which only dropping and recreating it can fix
+    Existed { drift: Vec<IndexDrift> },
+    /// The index was there without some declared fields, which were added with `FT.ALTER`
+    Altered { added: Vec<String> },
+    Failed { reason: String },
+}
+
+/// One declared index and its [`IndexBootOutcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexBootStatus {
+    pub index: String,
+    pub outcome: IndexBootOutcome,
+}
+
+impl fmt::Display for IndexBootStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            IndexBootOutcome::Created => write!(f, "{}: created", self.index),
+            IndexBootOutcome::Existed { drift } if drift.is_empty() => write!(f, "{}: up to date", self.index),
+            IndexBootOutcome::Existed { drift } => write!(f, "{}: exists with drift {drift:?}", self.index),
+            IndexBootOutcome::Altered { added } => write!(f, "{}: added {}", self.index, added.join(", ")),
+            IndexBootOutcome::Failed { reason } => write!(f, "{}: failed: {reason}", self.index),
+        }
+    }
+}
+
+/// Outcome of [`ensure_indexes`] or [`boot_indexes`], one entry per definition in the order given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexBootReport {
+    pub indexes: Vec<IndexBootStatus>,
+}
+
+impl IndexBootReport {
+    /// True when no index failed.
+    pub fn is_ok(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    pub fn created(&self) -> impl Iterator<Item = &IndexBootStatus> {
+        self.with(|outcome| matches!(outcome, IndexBootOutcome::Created))
+    }
+
+    pub fn altered(&self) -> impl Iterator<Item = &IndexBootStatus> {
+        self.with(|outcome| matches!(outcome, IndexBootOutcome::Altered { .. }))
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &IndexBootStatus> {
+        self.with(|outcome| matches!(outcome, IndexBootOutcome::Failed { .. }))
+    }
+
+    fn with(&self, matches: fn(&IndexBootOutcome) -> bool) -> impl Iterator<Item = &IndexBootStatus> {
+        self.indexes.iter().filter(move |status| matches(&status.outcome))
+    }
+}
+
+/// Create each missing index and add declared fields missing from existing ones, concurrently.
+///
+/// An existing index with any other drift (changed field types, prefixes, storage) is left
+/// alone and reported as [`IndexBootOutcome::Existed`], as is one that is fully up to date. A
+/// failure on one index doesn't stop the others, but once they are done any failure is returned
+/// as an error naming each failed index and its reason; [`boot_indexes`] returns the report
+/// with the failures in it instead.
+///
+/// # Example
+/// ```ignore
+/// let report = snugom::search::ensure_indexes(&mut conn, &definitions).await?;
+/// for status in &report.indexes {
+///     tracing::info!("{status}");   // "app:shop:orders:idx: created"
+/// }
+/// ```
+pub async fn ensure_indexes(
+    conn: &mut ConnectionManager,
+    definitions: &[IndexDefinition],
+) -> Result<IndexBootReport, RepoError> {
+    let report = boot_indexes(conn, definitions).await?;
+    if report.is_ok() {
+        return Ok(report);
+    }
+    let failed: Vec<String> = report.failed().map(ToString::to_string).collect();
+    Err(RepoError::Other {
+        message: Cow::Owned(format!("index setup failed: {}", failed.join("; "))),
+    })
+}
+
+/// [`ensure_indexes`], reporting failed indexes as [`IndexBootOutcome::Failed`] rather than as
+/// an error. The only error returned is failing to list the server's indexes.
+pub async fn boot_indexes(
+    conn: &mut ConnectionManager,
+    definitions: &[IndexDefinition],
+) -> Result<IndexBootReport, RepoError> {
+    let existing: Vec<String> = cmd("FT._LIST").query_async(conn).await?;
+    let statuses = definitions.iter().map(|definition| {
+        let mut conn = conn.clone();
+        let exists = existing.contains(&definition.name);
+        async move {
+            let outcome = boot_index(&mut conn, definition, exists)
+                .await
+                .unwrap_or_else(|err| IndexBootOutcome::Failed { reason: err.to_string() });
+            IndexBootStatus {
+                index: definition.name.clone(),
+                outcome,
+            }
+        }
+    });
+    Ok(IndexBootReport {
+        indexes: join_all(statuses).await,
+    })
+}
+
+async fn boot_index(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    exists: bool,
+) -> Result<IndexBootOutcome, RepoError> {
+    if !exists {
+        return Ok(if create_index(conn, definition).await? {
+            IndexBootOutcome::Created
+        } else {
+            IndexBootOutcome::Existed { drift: Vec::new() }
+        });
+    }
+    let drift = detect_drift(definition, &index_info(conn, &definition.name).await?);
+    let Some(added) = additive_fields(&drift) else {
+        return Ok(IndexBootOutcome::Existed { drift });
+    };
+    for field in definition.schema.iter().filter(|field| added.iter().any(|name| name == field.field_name)) {
+        let mut command = cmd("FT.ALTER");
+        command.arg(definition.name.as_str()).arg("SCHEMA").arg("ADD");
+        push_field_args(&mut command, definition.storage, field);
+        command.query_async::<()>(conn).await?;
+    }
+    Ok(IndexBootOutcome::Altered { added })
+}
+
+/// The missing fields, when adding them is all the drift calls for.
+fn additive_fields(drift: &[IndexDrift]) -> Option<Vec<String>> {
+    drift
+        .iter()
+        .map(|drift| match drift {
+            IndexDrift::MissingField { field } => Some(field.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|fields| !fields.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_missing_fields_are_altered() {
+        let missing = |field: &str| IndexDrift::MissingField {
+            field: field.to_string(),
+        };
+        assert_eq!(additive_fields(&[]), None);
+        assert_eq!(
+            additive_fields(&[missing("status"), missing("total")]),
+            Some(vec!["status".to_string(), "total".to_string()])
+        );
+        assert_eq!(
+            additive_fields(&[
+                missing("status"),
+                IndexDrift::UnexpectedField {
+                    field: "legacy".to_string()
+                }
+            ]),
+            None
+        );
+
+        let report = IndexBootReport {
+            indexes: vec![
+                IndexBootStatus {
+                    index: "app:shop:orders:idx".to_string(),
+                    outcome: IndexBootOutcome::Altered {
+                        added: vec!["status".to_string()],
+                    },
+                },
+                IndexBootStatus {
+                    index: "app:shop:carts:idx".to_string(),
+                    outcome: IndexBootOutcome::Failed {
+                        reason: "Unknown argument".to_string(),
+                    },
+                },
+            ],
+        };
+        assert!(!report.is_ok());
+        assert_eq!(report.altered().count(), 1);
+        assert_eq!(report.indexes[0].to_string(), "app:shop:orders:idx: added status");
+        assert_eq!(report.failed().next().map(|status| status.index.as_str()), Some("app:shop:carts:idx"));
+    }
+}
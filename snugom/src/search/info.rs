@@ -0,0 +1,551 @@
+//! `FT.INFO` parsing and drift detection between declared and live indexes.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use redis::{Value, aio::ConnectionManager, cmd, from_redis_value};
+
+use super::{FilterCondition, FilterDescriptor, FilterOperator, IndexDefinition};
+use crate::{
+    errors::RepoError,
+    filters::{build_numeric_filter, build_text_filter},
+    types::StorageMode,
+};
+
+/// Statistics and schema of a live index, from `FT.INFO`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+    pub num_docs: u64,
+    /// Documents the index failed to ingest, e.g. because a field had the wrong type
+    pub indexing_failures: u64,
+    /// Whether a background scan of existing keys is still running
+    pub indexing: bool,
+    /// Share of existing keys scanned so far, from 0.0 to 1.0
+    pub percent_indexed: Option<f64>,
+    pub inverted_size_mb: f64,
+    /// Memory used by the index as a whole, in megabytes
+    pub total_memory_mb: f64,
+    pub storage: Option<StorageMode>,
+    pub prefixes: Vec<String>,
+    pub filter: Option<String>,
+    pub fields: Vec<IndexFieldInfo>,
+}
+
+/// One attribute of a live index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexFieldInfo {
+    /// JSONPath or hash field the attribute reads
+    pub identifier: String,
+    /// Name queries use for the attribute
+    pub attribute: String,
+    /// `TAG`, `TEXT`, `NUMERIC`, `GEO`, ...
+    pub field_type: String,
+    pub sortable: bool,
+    /// Created with `INDEXMISSING`
+    pub index_missing: bool,
+}
+
+impl IndexInfo {
+    /// Map a filter onto this index's attributes, for callers without an entity type whose
+    /// `map_filter` knows the fields, such as tooling working from `FT.INFO` alone.
+    ///
+    /// The attribute is looked up by name, preferring the `__{field}_tag` shadow of enum fields
+    /// and the `{field}_ts` mirror of datetime fields; nested paths like `address.city` are found
+    /// under their underscored attribute. Values for a `__{field}_lc` casefold shadow are
+    /// lowercased. The attribute's type decides how the values are read.
+    pub fn filter_condition(&self, mut descriptor: FilterDescriptor) -> Result<FilterCondition, RepoError> {
+        let casefold = format!("__{}_lc", descriptor.field);
+        let candidates = [
+            format!("__{}_tag", descriptor.field),
+            casefold.clone(),
+            format!("{}_ts", descriptor.field),
+            descriptor.field.clone(),
+            descriptor.field.replace('.', "_"),
+        ];
+        let field = candidates
+            .iter()
+            .find_map(|name| self.fields.iter().find(|field| &field.attribute == name))
+            .ok_or_else(|| RepoError::InvalidRequest {
+                message: format!("{} is not indexed by {}", descriptor.field, self.name),
+            })?;
+        let attribute = field.attribute.as_str();
+        if attribute == casefold {
+            descriptor.values = descriptor.values.iter().map(|value| value.to_lowercase()).collect();
+        }
+
+        match field.field_type.to_ascii_uppercase().as_str() {
+            "NUMERIC" => build_numeric_filter(descriptor, attribute),
+            "TEXT" => build_text_filter(descriptor, attribute),
+            "TAG" => match descriptor.operator {
+                FilterOperator::Eq if !descriptor.values.is_empty() => Ok(FilterCondition::TagEquals {
+                    field: attribute.to_string(),
+                    values: descriptor.values,
+                }),
+                FilterOperator::Bool => match descriptor.values.first().map(|value| value.trim()) {
+                    Some("true") => Ok(FilterCondition::bool_eq(attribute, true)),
+                    Some("false") => Ok(FilterCondition::bool_eq(attribute, false)),
+                    _ => Err(RepoError::InvalidRequest {
+                        message: format!("{} filter requires true or false", descriptor.field),
+                    }),
+                },
+                FilterOperator::Eq => Err(RepoError::InvalidRequest {
+                    message: format!("{} filter requires at least one value", descriptor.field),
+                }),
+                _ => Err(RepoError::InvalidRequest {
+                    message: format!("{} filter only supports eq and bool operators", descriptor.field),
+                }),
+            },
+            other => Err(RepoError::InvalidRequest {
+                message: format!("Filtering on {other} field {} is not supported", descriptor.field),
+            }),
+        }
+    }
+}
+
+/// A difference between a declared [`IndexDefinition`] and the index on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexDrift {
+    /// No index with the declared name exists
+    Missing,
+    StorageMismatch {
+        declared: StorageMode,
+        actual: Option<StorageMode>,
+    },
+    PrefixMismatch {
+        declared: Vec<String>,
+        actual: Vec<String>,
+    },
+    FilterMismatch {
+        declared: Option<String>,
+        actual: Option<String>,
+    },
+    /// A declared field is not indexed on the server
+    MissingField { field: String },
+    /// The server indexes a field that is no longer declared
+    UnexpectedField { field: String },
+    FieldMismatch {
+        field: String,
+        declared: String,
+        actual: String,
+    },
+}
+
+/// Health of one declared index, from [`index_health`].
+#[derive(Debug, Clone)]
+pub struct IndexStatus {
+    pub index: String,
+    /// `None` when the index does not exist
+    pub info: Option<IndexInfo>,
+    pub drift: Vec<IndexDrift>,
+}
+
+impl IndexStatus {
+    /// True when the index matches its declaration and has not failed to ingest any document.
+    pub fn is_healthy(&self) -> bool {
+        self.drift.is_empty() && self.info.as_ref().is_some_and(|info| info.indexing_failures == 0)
+    }
+}
+
+/// Health of a set of declared indexes, from [`index_health`].
+#[derive(Debug, Clone, Default)]
+pub struct IndexHealthReport {
+    pub indexes: Vec<IndexStatus>,
+}
+
+impl IndexHealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.indexes.iter().all(IndexStatus::is_healthy)
+    }
+
+    /// Indexes that are missing, drifted, or failing to ingest documents.
+    pub fn unhealthy(&self) -> impl Iterator<Item = &IndexStatus> {
+        self.indexes.iter().filter(|status| !status.is_healthy())
+    }
+}
+
+/// Read `FT.INFO` for `index_name`.
+pub async fn index_info(conn: &mut ConnectionManager, index_name: &str) -> Result<IndexInfo, RepoError> {
+    let raw: Value = cmd("FT.INFO").arg(index_name).query_async(conn).await?;
+    parse_index_info(index_name, raw)
+}
+
+/// Compare each declared index with the server, reporting missing indexes and schema drift.
+///
+/// Drifted indexes are not changed; `ensure_index` only creates missing ones, so a changed
+/// schema needs the index dropped and recreated (or a migration) to take effect.
+pub async fn index_health(
+    conn: &mut ConnectionManager,
+    definitions: &[IndexDefinition],
+) -> Result<IndexHealthReport, RepoError> {
+    let existing: Vec<String> = cmd("FT._LIST").query_async(conn).await?;
+    let mut report = IndexHealthReport::default();
+    for definition in definitions {
+        let status = if existing.contains(&definition.name) {
+            let info = index_info(conn, &definition.name).await?;
+            IndexStatus {
+                index: definition.name.clone(),
+                drift: detect_drift(definition, &info),
+                info: Some(info),
+            }
+        } else {
+            IndexStatus {
+                index: definition.name.clone(),
+                info: None,
+                drift: vec![IndexDrift::Missing],
+            }
+        };
+        report.indexes.push(status);
+    }
+    Ok(report)
+}
+
+pub(super) fn detect_drift(definition: &IndexDefinition, info: &IndexInfo) -> Vec<IndexDrift> {
+    let mut drift = Vec::new();
+    if info.storage != Some(definition.storage) {
+        drift.push(IndexDrift::StorageMismatch {
+            declared: definition.storage,
+            actual: info.storage,
+        });
+    }
+
+    let mut declared_prefixes = definition.prefixes.clone();
+    let mut actual_prefixes = info.prefixes.clone();
+    declared_prefixes.sort();
+    actual_prefixes.sort();
+    if declared_prefixes != actual_prefixes {
+        drift.push(IndexDrift::PrefixMismatch {
+            declared: declared_prefixes,
+            actual: actual_prefixes,
+        });
+    }
+
+    let declared_filter = definition.filter.as_deref().map(str::trim).filter(|filter| !filter.is_empty());
+    if declared_filter != info.filter.as_deref().map(str::trim) {
+        drift.push(IndexDrift::FilterMismatch {
+            declared: definition.filter.clone(),
+            actual: info.filter.clone(),
+        });
+    }
+
+    for field in definition.schema {
+        let Some(actual) = info.fields.iter().find(|actual| actual.attribute == field.field_name) else {
+            drift.push(IndexDrift::MissingField {
+                field: field.field_name.to_string(),
+            });
+            continue;
+        };
+        let identifier = match definition.storage {
+            StorageMode::Json => field.path,
+            StorageMode::Hash => field.path.strip_prefix("$.").unwrap_or(field.path),
+        };
+        let declared = describe_field(identifier, field.field_type.as_str(), field.sortable, field.index_missing);
+        let live = describe_field(&actual.identifier, &actual.field_type, actual.sortable, actual.index_missing);
+        if declared != live {
+            drift.push(IndexDrift::FieldMismatch {
+                field: field.field_name.to_string(),
+                declared,
+                actual: live,
+            });
+        }
+    }
+    for actual in &info.fields {
+        if !definition.schema.iter().any(|field| field.field_name == actual.attribute) {
+            drift.push(IndexDrift::UnexpectedField {
+                field: actual.attribute.clone(),
+            });
+        }
+    }
+    drift
+}
+
+fn describe_field(identifier: &str, field_type: &str, sortable: bool, index_missing: bool) -> String {
+    let mut description = format!("{identifier} {field_type}");
+    if sortable {
+        description.push_str(" SORTABLE");
+    }
+    if index_missing {
+        description.push_str(" INDEXMISSING");
+    }
+    description
+}
+
+fn parse_index_info(index_name: &str, raw: Value) -> Result<IndexInfo, RepoError> {
+    let info = info_map(raw)?;
+    let number = |key: &str| info.get(key).and_then(as_f64);
+    let memory_parts = [
+        "inverted_sz_mb",
+        "vector_index_sz_mb",
+        "offset_vectors_sz_mb",
+        "doc_table_size_mb",
+        "sortable_values_size_mb",
+        "key_table_size_mb",
+    ];
+
+    let definition = info.get("index_definition").map(|value| info_map(value.clone())).transpose()?;
+    let definition = definition.unwrap_or_default();
+    let storage = definition.get("key_type").and_then(as_string).and_then(|key_type| {
+        match key_type.to_ascii_uppercase().as_str() {
+            "JSON" => Some(StorageMode::Json),
+            "HASH" => Some(StorageMode::Hash),
+            _ => None,
+        }
+    });
+    let prefixes = match definition.get("prefixes") {
+        Some(Value::Array(prefixes)) => prefixes.iter().filter_map(as_string).collect(),
+        _ => Vec::new(),
+    };
+
+    let fields = match info.get("attributes").or_else(|| info.get("fields")) {
+        Some(Value::Array(fields)) => fields.iter().filter_map(parse_field).collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(IndexInfo {
+        name: info.get("index_name").and_then(as_string).unwrap_or_else(|| index_name.to_string()),
+        num_docs: number("num_docs").unwrap_or_default() as u64,
+        indexing_failures: number("hash_indexing_failures").unwrap_or_default() as u64,
+        indexing: number("indexing").is_some_and(|indexing| indexing != 0.0),
+        percent_indexed: number("percent_indexed"),
+        inverted_size_mb: number("inverted_sz_mb").unwrap_or_default(),
+        total_memory_mb: number("total_index_memory_sz_mb")
+            .unwrap_or_else(|| memory_parts.iter().filter_map(|key| number(key)).sum()),
+        storage,
+        prefixes,
+        filter: definition.get("filter").and_then(as_string).filter(|filter| !filter.is_empty()),
+        fields,
+    })
+}
+
+/// Attributes are listed as `identifier`/`attribute`/`type` pairs followed by bare flags
+/// such as `SORTABLE`, and options such as `SEPARATOR |` that take a value.
+fn parse_field(value: &Value) -> Option<IndexFieldInfo> {
+    let Value::Array(entries) = value else {
+        return None;
+    };
+    let mut field = IndexFieldInfo {
+        identifier: String::new(),
+        attribute: String::new(),
+        field_type: String::new(),
+        sortable: false,
+        index_missing: false,
+    };
+    let mut entries = entries.iter();
+    while let Some(entry) = entries.next() {
+        let Some(key) = as_string(entry) else {
+            continue;
+        };
+        match key.as_str() {
+            "identifier" => field.identifier = entries.next().and_then(as_string)?,
+            "attribute" => field.attribute = entries.next().and_then(as_string)?,
+            "type" => field.field_type = entries.next().and_then(as_string)?,
+            "SORTABLE" => field.sortable = true,
+            "INDEXMISSING" => field.index_missing = true,
+            "SEPARATOR" | "WEIGHT" | "PHONETIC" => {
+                entries.next();
+            }
+            _ => {}
+        }
+    }
+    (!field.attribute.is_empty()).then_some(field)
+}
+
+/// Pair up a flat key/value reply, as `FT.INFO` returns in RESP2.
+fn info_map(raw: Value) -> Result<HashMap<String, Value>, RepoError> {
+    let entries = match raw {
+        Value::Array(entries) => entries,
+        Value::Map(pairs) => pairs.into_iter().flat_map(|(key, value)| [key, value]).collect(),
+        other => {
+            return Err(RepoError::Other {
+                message: Cow::Owned(format!("Unexpected FT.INFO response: {other:?}")),
+            });
+        }
+    };
+    let mut map = HashMap::new();
+    let mut entries = entries.into_iter();
+    while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+        if let Some(key) = as_string(&key) {
+            map.insert(key, value);
+        }
+    }
+    Ok(map)
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    from_redis_value::<String>(value).ok()
+}
+
+/// `FT.INFO` numbers arrive as integers, doubles, or strings depending on the field.
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(value) => Some(*value as f64),
+        Value::Double(value) => Some(*value),
+        other => as_string(other)?.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{IndexField, IndexFieldType};
+
+    fn bulk(text: &str) -> Value {
+        Value::BulkString(text.as_bytes().to_vec())
+    }
+
+    fn sample_info() -> Value {
+        Value::Array(vec![
+            bulk("index_name"),
+            bulk("app:shop:orders:idx"),
+            bulk("index_definition"),
+            Value::Array(vec![
+                bulk("key_type"),
+                bulk("JSON"),
+                bulk("prefixes"),
+                Value::Array(vec![bulk("app:shop:orders:")]),
+                bulk("default_score"),
+                bulk("1"),
+            ]),
+            bulk("attributes"),
+            Value::Array(vec![
+                Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("$.status"),
+                    bulk("attribute"),
+                    bulk("status"),
+                    bulk("type"),
+                    bulk("TAG"),
+                    bulk("SEPARATOR"),
+                    bulk("|"),
+                    bulk("INDEXMISSING"),
+                ]),
+                Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("$.total"),
+                    bulk("attribute"),
+                    bulk("total"),
+                    bulk("type"),
+                    bulk("NUMERIC"),
+                    bulk("SORTABLE"),
+                ]),
+            ]),
+            bulk("num_docs"),
+            Value::Int(12),
+            bulk("hash_indexing_failures"),
+            bulk("0"),
+            bulk("indexing"),
+            bulk("0"),
+            bulk("percent_indexed"),
+            bulk("1"),
+            bulk("inverted_sz_mb"),
+            bulk("0.25"),
+            bulk("doc_table_size_mb"),
+            bulk("0.5"),
+        ])
+    }
+
+    static SCHEMA: &[IndexField] = &[
+        IndexField {
+            path: "$.status",
+            field_name: "status",
+            field_type: IndexFieldType::Tag,
+            sortable: false,
+            index_missing: true,
+        },
+        IndexField {
+            path: "$.total",
+            field_name: "total",
+            field_type: IndexFieldType::Numeric,
+            sortable: false,
+            index_missing: true,
+        },
+        IndexField {
+            path: "$.placed_at",
+            field_name: "placed_at",
+            field_type: IndexFieldType::Numeric,
+            sortable: true,
+            index_missing: false,
+        },
+    ];
+
+    #[test]
+    fn parses_statistics_and_schema() {
+        let info = parse_index_info("fallback", sample_info()).expect("info");
+        assert_eq!(info.name, "app:shop:orders:idx");
+        assert_eq!(info.num_docs, 12);
+        assert_eq!(info.indexing_failures, 0);
+        assert!(!info.indexing);
+        assert_eq!(info.percent_indexed, Some(1.0));
+        assert_eq!(info.total_memory_mb, 0.75);
+        assert_eq!(info.storage, Some(StorageMode::Json));
+        assert_eq!(info.prefixes, vec!["app:shop:orders:".to_string()]);
+        assert_eq!(info.filter, None);
+        assert_eq!(info.fields.len(), 2);
+        assert_eq!(info.fields[0].field_type, "TAG");
+        assert!(!info.fields[0].sortable);
+        assert!(info.fields[0].index_missing);
+        assert!(info.fields[1].sortable);
+        assert!(!info.fields[1].index_missing);
+    }
+
+    #[test]
+    fn reports_field_drift() {
+        let info = parse_index_info("fallback", sample_info()).expect("info");
+        let definition = IndexDefinition {
+            name: info.name.clone(),
+            prefixes: vec!["app:shop:orders:".to_string()],
+            filter: None,
+            schema: SCHEMA,
+            storage: StorageMode::Json,
+            language: None,
+            language_field: None,
+        };
+        let drift = detect_drift(&definition, &info);
+        assert_eq!(
+            drift,
+            vec![
+                IndexDrift::FieldMismatch {
+                    field: "total".to_string(),
+                    declared: "$.total NUMERIC INDEXMISSING".to_string(),
+                    actual: "$.total NUMERIC SORTABLE".to_string(),
+                },
+                IndexDrift::MissingField {
+                    field: "placed_at".to_string(),
+                },
+            ]
+        );
+    }
+    #[test]
+    fn maps_filters_onto_live_attributes() {
+        let mut info = parse_index_info("fallback", sample_info()).expect("info");
+
+        let status = info.filter_condition(FilterDescriptor::parse("status:eq:open|paid").unwrap()).unwrap();
+        assert_eq!(status.to_query_clause(), "(@status:{open|paid})");
+        let total = info.filter_condition(FilterDescriptor::parse("total:range:10,*").unwrap()).unwrap();
+        assert_eq!(total.to_query_clause(), "(@total:[10 +inf])");
+
+        let unknown = info.filter_condition(FilterDescriptor::parse("owner:eq:ann").unwrap());
+        assert!(matches!(unknown, Err(RepoError::InvalidRequest { message }) if message.contains("not indexed")));
+        let prefix = info.filter_condition(FilterDescriptor::parse("status:prefix:op").unwrap());
+        assert!(prefix.is_err());
+
+        info.fields.push(IndexFieldInfo {
+            identifier: "$.address.city".to_string(),
+            attribute: "address_city".to_string(),
+            field_type: "TAG".to_string(),
+            sortable: false,
+            index_missing: false,
+        });
+        let city = info.filter_condition(FilterDescriptor::parse("address.city:eq:Lisbon").unwrap()).unwrap();
+        assert_eq!(city.to_query_clause(), "(@address_city:{Lisbon})");
+
+        info.fields.push(IndexFieldInfo {
+            identifier: "$.__email_lc".to_string(),
+            attribute: "__email_lc".to_string(),
+            field_type: "TAG".to_string(),
+            sortable: false,
+            index_missing: false,
+        });
+        let email = info.filter_condition(FilterDescriptor::parse("email:eq:Ann|BOB").unwrap()).unwrap();
+        assert_eq!(email.to_query_clause(), "(@__email_lc:{ann|bob})");
+    }
+}
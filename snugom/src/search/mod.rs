@@ -43,14 +43,40 @@ use serde_json::Value as JsonValue;
 use std::borrow::Cow;
 
 #[cfg(feature = "utoipa")]
-use utoipa::ToSchema;
-
-use crate::{errors::RepoError, types::EntityMetadata};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    errors::RepoError,
+    storage::{decode_fields, decode_hash_fields},
+    telemetry,
+    types::{EntityDescriptor, EntityMetadata, StorageMode},
+};
+
+mod aggregate;
+mod boot;
+mod info;
+mod profile;
+mod slow_log;
+
+pub use aggregate::{
+    AggOp, BucketCount, DistinctValue, TimeBucket, aggregate_numeric, distinct_values, time_buckets,
+};
+pub use boot::{IndexBootOutcome, IndexBootReport, IndexBootStatus, boot_indexes, ensure_indexes};
+pub use info::{IndexDrift, IndexFieldInfo, IndexHealthReport, IndexInfo, IndexStatus, index_health, index_info};
+
+pub use profile::{
+    ExplainNode, ProfileIterator, ProfileProcessor, QueryExplanation, SearchProfile, explain_index_search,
+    profile_index_search,
+};
+pub use slow_log::{DEFAULT_SLOW_QUERY_CAPACITY, SlowQuery, SlowQueryLog};
 
 const DEFAULT_PAGE: u64 = 1;
 const DEFAULT_PAGE_SIZE: u64 = 25;
-const MAX_PAGE_SIZE: u64 = 100;
+pub(crate) const MAX_PAGE_SIZE: u64 = 100;
 const TAG_SEPARATOR: &str = "|";
+/// Query dialect sent with `FT.SEARCH` unless [`SearchParams::with_dialect`] or
+/// [`SearchDefaults`] pick another; queries built by [`SearchParams`] assume at least this one.
+pub const SEARCH_DIALECT: u8 = 3;
 
 /// Trait implemented by entities that expose SnugOM search metadata.
 pub trait SearchEntity: EntityMetadata + DeserializeOwned {
@@ -77,6 +103,7 @@ pub trait SearchEntity: EntityMetadata + DeserializeOwned {
 }
 
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
@@ -105,7 +132,14 @@ pub struct SortField {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterOperator {
     Eq,
+    /// Negated `eq`, mapped through [`FilterDescriptor::map_with`]
+    Neq,
     Range,
+    /// Open-ended numeric bounds: `gt`/`lt` exclude the value, `gte`/`lte` include it
+    Gt,
+    Gte,
+    Lt,
+    Lte,
     Bool,
     Prefix,
     Contains,
@@ -120,6 +154,74 @@ pub struct FilterDescriptor {
     pub values: Vec<String>,
 }
 
+impl FilterDescriptor {
+    /// Parse a filter written as `field:operator:value`, e.g. `status:eq:active|pending` or
+    /// `count:range:10,50` or `count:gte:10`, the syntax of [`SearchQuery::filter`].
+    pub fn parse(raw: &str) -> Result<Self, RepoError> {
+        let parts: Vec<&str> = raw.splitn(3, ':').collect();
+        if parts.len() != 3 {
+            return Err(RepoError::InvalidRequest {
+                message: format!("Invalid filter syntax: {}", raw),
+            });
+        }
+
+        let operator = match parts[1].to_ascii_lowercase().as_str() {
+            "eq" => FilterOperator::Eq,
+            "neq" | "not" => FilterOperator::Neq,
+            "range" => FilterOperator::Range,
+            "gt" => FilterOperator::Gt,
+            "gte" => FilterOperator::Gte,
+            "lt" => FilterOperator::Lt,
+            "lte" => FilterOperator::Lte,
+            "bool" | "boolean" => FilterOperator::Bool,
+            "prefix" => FilterOperator::Prefix,
+            "contains" => FilterOperator::Contains,
+            "exact" => FilterOperator::Exact,
+            "fuzzy" => FilterOperator::Fuzzy,
+            other => {
+                return Err(RepoError::InvalidRequest {
+                    message: format!("Unsupported filter operator: {}", other),
+                });
+            }
+        };
+
+        let values = match operator {
+            FilterOperator::Eq | FilterOperator::Neq | FilterOperator::Bool => parts[2]
+                .split(['|', ','])
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.trim().to_string())
+                .collect(),
+            FilterOperator::Range => parts[2].split(',').map(|segment| segment.trim().to_string()).collect(),
+            FilterOperator::Gt | FilterOperator::Gte | FilterOperator::Lt | FilterOperator::Lte => {
+                vec![parts[2].trim().to_string()]
+            }
+            // TEXT field filters take a single value (no splitting)
+            FilterOperator::Prefix | FilterOperator::Contains | FilterOperator::Exact | FilterOperator::Fuzzy => {
+                vec![parts[2].to_string()]
+            }
+        };
+
+        Ok(FilterDescriptor {
+            field: parts[0].trim().to_string(),
+            operator,
+            values,
+        })
+    }
+
+    /// Map the filter to a condition with `mapper`. A `neq` filter is mapped as `eq` and then
+    /// negated, so mappers only need to handle the positive form.
+    pub fn map_with<F>(mut self, mapper: F) -> Result<FilterCondition, RepoError>
+    where
+        F: FnOnce(FilterDescriptor) -> Result<FilterCondition, RepoError>,
+    {
+        if self.operator != FilterOperator::Neq {
+            return mapper(self);
+        }
+        self.operator = FilterOperator::Eq;
+        Ok(FilterCondition::negate(mapper(self)?))
+    }
+}
+
 /// A composable filter condition for RediSearch queries.
 ///
 /// Leaf conditions represent individual field filters, while `And` and `Or`
@@ -148,17 +250,20 @@ pub struct FilterDescriptor {
 ///     FilterCondition::tag_eq("owner", "user123"),
 /// ]);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FilterCondition {
     // Leaf conditions
     TagEquals {
         field: String,
         values: Vec<String>,
     },
+    /// Values between `min` and `max`; each bound is inclusive unless flagged exclusive
     NumericRange {
         field: String,
         min: Option<f64>,
         max: Option<f64>,
+        min_exclusive: bool,
+        max_exclusive: bool,
     },
     BooleanEquals {
         field: String,
@@ -180,9 +285,14 @@ pub enum FilterCondition {
         field: String,
         value: String,
     },
+    /// Documents without the field; it must be indexed with `INDEXMISSING`
+    IsMissing {
+        field: String,
+    },
     // Composite conditions
     And(Vec<FilterCondition>),
     Or(Vec<FilterCondition>),
+    Not(Box<FilterCondition>),
 }
 
 impl FilterCondition {
@@ -206,6 +316,18 @@ impl FilterCondition {
         }
     }
 
+    /// Create a TAG field filter excluding a single value.
+    #[inline]
+    pub fn tag_ne(field: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::negate(Self::tag_eq(field, value))
+    }
+
+    /// Create a TAG field filter excluding all of the given values.
+    #[inline]
+    pub fn tag_not_in<S: Into<String>>(field: impl Into<String>, values: impl IntoIterator<Item = S>) -> Self {
+        Self::negate(Self::tag_in(field, values))
+    }
+
     /// Create a boolean field equality filter.
     #[inline]
     pub fn bool_eq(field: impl Into<String>, value: bool) -> Self {
@@ -222,6 +344,8 @@ impl FilterCondition {
             field: field.into(),
             min,
             max,
+            min_exclusive: false,
+            max_exclusive: false,
         }
     }
 
@@ -232,6 +356,8 @@ impl FilterCondition {
             field: field.into(),
             min: Some(min),
             max: None,
+            min_exclusive: false,
+            max_exclusive: false,
         }
     }
 
@@ -242,6 +368,8 @@ impl FilterCondition {
             field: field.into(),
             min: None,
             max: Some(max),
+            min_exclusive: false,
+            max_exclusive: false,
         }
     }
 
@@ -252,6 +380,8 @@ impl FilterCondition {
             field: field.into(),
             min: Some(value),
             max: Some(value),
+            min_exclusive: false,
+            max_exclusive: false,
         }
     }
 
@@ -291,6 +421,19 @@ impl FilterCondition {
         }
     }
 
+    /// Match documents that lack the field (absent or `null`). The attribute must be indexed with
+    /// `INDEXMISSING`, which the derive does for optional filterable fields.
+    #[inline]
+    pub fn is_missing(field: impl Into<String>) -> Self {
+        Self::IsMissing { field: field.into() }
+    }
+
+    /// Match documents that have the field, the negation of [`FilterCondition::is_missing`].
+    #[inline]
+    pub fn is_present(field: impl Into<String>) -> Self {
+        Self::negate(Self::is_missing(field))
+    }
+
     // ========== Composite Constructors ==========
 
     /// Combine conditions with AND logic.
@@ -305,6 +448,12 @@ impl FilterCondition {
         Self::Or(conditions.into_iter().collect())
     }
 
+    /// Match documents the condition does not match.
+    #[inline]
+    pub fn negate(condition: FilterCondition) -> Self {
+        Self::Not(Box::new(condition))
+    }
+
     // ========== Query Generation ==========
 
     /// Convert this condition to a RediSearch query clause.
@@ -314,9 +463,20 @@ impl FilterCondition {
                 let escaped: Vec<String> = values.iter().map(|v| escape_for_tag_query(v)).collect();
                 format!("(@{}:{{{}}})", field, escaped.join(TAG_SEPARATOR))
             }
-            Self::NumericRange { field, min, max } => {
-                let min_s = min.map(format_numeric).unwrap_or_else(|| "-inf".to_string());
-                let max_s = max.map(format_numeric).unwrap_or_else(|| "+inf".to_string());
+            Self::NumericRange {
+                field,
+                min,
+                max,
+                min_exclusive,
+                max_exclusive,
+            } => {
+                let bound = |value: Option<f64>, exclusive: bool, unbounded: &str| match value {
+                    Some(value) if exclusive => format!("({}", format_numeric(value)),
+                    Some(value) => format_numeric(value),
+                    None => unbounded.to_string(),
+                };
+                let min_s = bound(*min, *min_exclusive, "-inf");
+                let max_s = bound(*max, *max_exclusive, "+inf");
                 format!("(@{}:[{} {}])", field, min_s, max_s)
             }
             Self::BooleanEquals { field, value } => {
@@ -335,6 +495,7 @@ impl FilterCondition {
             Self::TextFuzzy { field, value } => {
                 format!("(@{}:{})", field, escape_for_text_fuzzy(value))
             }
+            Self::IsMissing { field } => format!("(ismissing(@{}))", field),
             Self::And(conditions) => {
                 if conditions.is_empty() {
                     return String::new();
@@ -365,6 +526,13 @@ impl FilterCondition {
                     _ => format!("({})", clauses.join("|")),
                 }
             }
+            Self::Not(condition) => {
+                let clause = condition.to_query_clause();
+                if clause.is_empty() {
+                    return String::new();
+                }
+                format!("-{}", clause)
+            }
         }
     }
 }
@@ -403,6 +571,39 @@ pub struct SearchParams {
     pub text_query: Option<String>,
     /// Raw RediSearch query escape hatch. Use sparingly.
     pub raw: Option<String>,
+    /// Restrict the search to these document keys (`INKEYS`). Empty means no restriction.
+    pub in_keys: Vec<String>,
+    /// Top-level fields to return instead of the whole document. Empty returns `$`.
+    pub return_fields: Vec<String>,
+    /// Ask for each hit's relevance score (`WITHSCORES`), returned in [`SearchResult::scores`].
+    pub scores: bool,
+    /// Server-side time limit in milliseconds (`TIMEOUT`); the server's `TIMEOUT` setting applies when unset.
+    pub timeout_ms: Option<u64>,
+    /// Query dialect (`DIALECT`); [`SEARCH_DIALECT`] when unset.
+    pub dialect: Option<u8>,
+}
+
+/// Client-wide `DIALECT` and `TIMEOUT` for searches whose [`SearchParams`] don't set their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchDefaults {
+    pub dialect: Option<u8>,
+    pub timeout_ms: Option<u64>,
+}
+
+impl SearchDefaults {
+    /// `params` with the defaults filled in, borrowed when there is nothing to fill.
+    pub(crate) fn apply<'a>(&self, params: &'a SearchParams) -> Cow<'a, SearchParams> {
+        let dialect = params.dialect.or(self.dialect);
+        let timeout_ms = params.timeout_ms.or(self.timeout_ms);
+        if dialect == params.dialect && timeout_ms == params.timeout_ms {
+            return Cow::Borrowed(params);
+        }
+        Cow::Owned(SearchParams {
+            dialect,
+            timeout_ms,
+            ..params.clone()
+        })
+    }
 }
 
 impl Default for SearchParams {
@@ -420,6 +621,11 @@ impl SearchParams {
             conditions: Vec::new(),
             text_query: None,
             raw: None,
+            in_keys: Vec::new(),
+            return_fields: Vec::new(),
+            scores: false,
+            timeout_ms: None,
+            dialect: None,
         }
     }
 
@@ -462,6 +668,45 @@ impl SearchParams {
         self
     }
 
+    /// Restrict the search to the given document keys.
+    #[inline]
+    pub fn with_in_keys(mut self, keys: impl IntoIterator<Item = String>) -> Self {
+        self.in_keys.extend(keys);
+        self
+    }
+
+    /// Fetch only these top-level fields; results are deserialized from a partial object.
+    #[inline]
+    pub fn with_return_fields<S: Into<String>>(mut self, fields: impl IntoIterator<Item = S>) -> Self {
+        self.return_fields.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Return the relevance score of each hit alongside it.
+    #[inline]
+    pub fn with_scores(mut self) -> Self {
+        self.scores = true;
+        self
+    }
+
+    /// Bound how long the server may spend on the query, in milliseconds.
+    ///
+    /// What happens on timeout follows the server's `ON_TIMEOUT` policy: partial results by
+    /// default, or an error with `ON_TIMEOUT FAIL`.
+    #[inline]
+    pub fn with_timeout(mut self, ms: u64) -> Self {
+        self.timeout_ms = Some(ms);
+        self
+    }
+
+    /// Run the query under `dialect` instead of [`SEARCH_DIALECT`], e.g. 4 for its faster
+    /// intersections.
+    #[inline]
+    pub fn with_dialect(mut self, dialect: u8) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
     #[inline]
     pub fn with_page(mut self, page: u64, page_size: u64) -> Self {
         self.page = page;
@@ -519,6 +764,7 @@ impl SearchParams {
     }
 }
 
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
 #[derive(Debug, Clone, Serialize)]
 pub struct PaginatedResponse<T> {
     pub items: Vec<T>,
@@ -534,6 +780,9 @@ pub struct SearchResult<T> {
     pub total: u64,
     pub page: u64,
     pub page_size: u64,
+    /// Relevance score of each item, in the same order, when searched with
+    /// [`SearchParams::with_scores`]; empty otherwise.
+    pub scores: Vec<f64>,
 }
 
 impl<T> SearchResult<T> {
@@ -541,6 +790,49 @@ impl<T> SearchResult<T> {
     pub fn has_more(&self) -> bool {
         self.page * self.page_size < self.total
     }
+
+    /// Convert each item, keeping the pagination metadata and scores.
+    pub fn map<V>(self, f: impl FnMut(T) -> V) -> SearchResult<V> {
+        SearchResult {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            page_size: self.page_size,
+            scores: self.scores,
+        }
+    }
+
+    /// Items paired with their relevance scores; empty unless searched with scores.
+    pub fn scored(&self) -> impl Iterator<Item = (&T, f64)> {
+        self.items.iter().zip(self.scores.iter().copied())
+    }
+}
+
+/// A read-side view of entity `T` that search can produce directly.
+///
+/// Only [`ViewModel::fields`] are fetched from RediSearch, and the view is deserialized
+/// from that partial document, so unused fields are never transferred or parsed.
+///
+/// ```
+/// use serde::Deserialize;
+/// use snugom::search::ViewModel;
+///
+/// # struct User;
+/// #[derive(Deserialize)]
+/// struct UserCard {
+///     id: String,
+///     display_name: String,
+/// }
+///
+/// impl ViewModel<User> for UserCard {
+///     fn fields() -> &'static [&'static str] {
+///         &["id", "display_name"]
+///     }
+/// }
+/// ```
+pub trait ViewModel<T>: DeserializeOwned {
+    /// Top-level entity fields the view is built from.
+    fn fields() -> &'static [&'static str];
 }
 
 impl<T: Serialize> From<SearchResult<T>> for PaginatedResponse<T> {
@@ -555,14 +847,21 @@ impl<T: Serialize> From<SearchResult<T>> for PaginatedResponse<T> {
     }
 }
 
+#[cfg_attr(feature = "utoipa", derive(ToSchema, IntoParams))]
+#[cfg_attr(feature = "utoipa", into_params(parameter_in = Query))]
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct SearchQuery {
+    /// 1-based page number.
     pub page: Option<u64>,
+    /// Items per page.
     #[serde(rename = "page_size")]
     pub page_size: Option<u64>,
+    /// Name of a sortable field.
     pub sort_by: Option<String>,
     pub sort_order: Option<SortOrder>,
+    /// Free-text query across the entity's text fields.
     pub q: Option<String>,
+    /// Filters as `field:operator:value`, e.g. `status:eq:active` or `count:range:10,50`.
     #[serde(default)]
     pub filter: Vec<String>,
 }
@@ -607,48 +906,7 @@ impl SearchQuery {
 
         let mut conditions = Vec::new();
         for raw in self.filter {
-            let parts: Vec<&str> = raw.splitn(3, ':').collect();
-            if parts.len() != 3 {
-                return Err(RepoError::InvalidRequest {
-                    message: format!("Invalid filter syntax: {}", raw),
-                });
-            }
-
-            let operator = match parts[1].to_ascii_lowercase().as_str() {
-                "eq" => FilterOperator::Eq,
-                "range" => FilterOperator::Range,
-                "bool" | "boolean" => FilterOperator::Bool,
-                "prefix" => FilterOperator::Prefix,
-                "contains" => FilterOperator::Contains,
-                "exact" => FilterOperator::Exact,
-                "fuzzy" => FilterOperator::Fuzzy,
-                other => {
-                    return Err(RepoError::InvalidRequest {
-                        message: format!("Unsupported filter operator: {}", other),
-                    });
-                }
-            };
-
-            let values = match operator {
-                FilterOperator::Eq | FilterOperator::Bool => parts[2]
-                    .split(['|', ','])
-                    .filter(|segment| !segment.is_empty())
-                    .map(|segment| segment.trim().to_string())
-                    .collect(),
-                FilterOperator::Range => parts[2].split(',').map(|segment| segment.trim().to_string()).collect(),
-                // TEXT field filters take a single value (no splitting)
-                FilterOperator::Prefix | FilterOperator::Contains | FilterOperator::Exact | FilterOperator::Fuzzy => {
-                    vec![parts[2].to_string()]
-                }
-            };
-
-            let descriptor = FilterDescriptor {
-                field: parts[0].trim().to_string(),
-                operator,
-                values,
-            };
-
-            conditions.push(filter_mapper(descriptor)?);
+            conditions.push(FilterDescriptor::parse(&raw)?.map_with(&mut filter_mapper)?);
         }
 
         Ok(SearchParams::new()
@@ -677,7 +935,8 @@ impl SearchQuery {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IndexFieldType {
     Tag,
     Text,
@@ -685,12 +944,27 @@ pub enum IndexFieldType {
     Geo,
 }
 
+impl IndexFieldType {
+    /// The type keyword used by `FT.CREATE` and reported by `FT.INFO`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexFieldType::Tag => "TAG",
+            IndexFieldType::Text => "TEXT",
+            IndexFieldType::Numeric => "NUMERIC",
+            IndexFieldType::Geo => "GEO",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct IndexField {
     pub path: &'static str,
     pub field_name: &'static str,
     pub field_type: IndexFieldType,
     pub sortable: bool,
+    /// Created with `INDEXMISSING`, so [`FilterCondition::is_missing`] can find documents
+    /// without the field. Needs RediSearch 2.10 or later.
+    pub index_missing: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -699,6 +973,13 @@ pub struct IndexDefinition {
     pub prefixes: Vec<String>,
     pub filter: Option<String>,
     pub schema: &'static [IndexField],
+    /// Data type of the indexed documents; hash indexes address fields by name, not JSONPath.
+    pub storage: StorageMode,
+    /// Stemming language of TEXT fields (`LANGUAGE`), from `#[snugom(language = "...")]`;
+    /// RediSearch stems English when unset.
+    pub language: Option<String>,
+    /// Field holding each document's own language (`LANGUAGE_FIELD`), overriding `language`.
+    pub language_field: Option<String>,
 }
 
 pub async fn ensure_index(conn: &mut ConnectionManager, definition: &IndexDefinition) -> Result<(), RepoError> {
@@ -706,10 +987,21 @@ pub async fn ensure_index(conn: &mut ConnectionManager, definition: &IndexDefini
     if indexes.iter().any(|name| name == &definition.name) {
         return Ok(());
     }
+    create_index(conn, definition).await?;
+    Ok(())
+}
 
+/// `FT.CREATE` the index, returning false when another client created it first.
+pub(crate) async fn create_index(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+) -> Result<bool, RepoError> {
     let mut command = cmd("FT.CREATE");
     command.arg(definition.name.as_str());
-    command.arg("ON").arg("JSON");
+    command.arg("ON").arg(match definition.storage {
+        StorageMode::Json => "JSON",
+        StorageMode::Hash => "HASH",
+    });
     command.arg("PREFIX").arg(definition.prefixes.len());
     for prefix in &definition.prefixes {
         command.arg(prefix.as_str());
@@ -718,42 +1010,87 @@ pub async fn ensure_index(conn: &mut ConnectionManager, definition: &IndexDefini
     if let Some(filter) = &definition.filter {
         command.arg("FILTER").arg(filter.as_str());
     }
+    if let Some(language) = &definition.language {
+        command.arg("LANGUAGE").arg(language.as_str());
+    }
+    if let Some(field) = &definition.language_field {
+        match definition.storage {
+            StorageMode::Json => command.arg("LANGUAGE_FIELD").arg(format!("$.{field}")),
+            StorageMode::Hash => command.arg("LANGUAGE_FIELD").arg(field.as_str()),
+        };
+    }
 
     command.arg("SCHEMA");
     for field in definition.schema {
-        command.arg(field.path);
-        command.arg("AS").arg(field.field_name);
-        match field.field_type {
-            IndexFieldType::Tag => {
-                command.arg("TAG");
-                command.arg("SEPARATOR").arg(TAG_SEPARATOR);
-            }
-            IndexFieldType::Text => {
-                command.arg("TEXT");
-            }
-            IndexFieldType::Numeric => {
-                command.arg("NUMERIC");
-            }
-            IndexFieldType::Geo => {
-                command.arg("GEO");
-            }
-        }
-
-        if field.sortable {
-            command.arg("SORTABLE");
-        }
+        push_field_args(&mut command, definition.storage, field);
     }
 
     if let Err(err) = command.query_async::<()>(conn).await {
         if index_exists_error(&err) {
-            return Ok(());
+            return Ok(false);
         }
         return Err(err.into());
     }
 
+    Ok(true)
+}
+
+/// `{identifier} AS {name} {TYPE} [SORTABLE] [INDEXMISSING]` of one field, as `FT.CREATE` and
+/// `FT.ALTER` take it.
+pub(crate) fn push_field_args(command: &mut redis::Cmd, storage: StorageMode, field: &IndexField) {
+    match storage {
+        StorageMode::Json => command.arg(field.path),
+        StorageMode::Hash => command.arg(field.path.strip_prefix("$.").unwrap_or(field.path)),
+    };
+    command.arg("AS").arg(field.field_name);
+    match field.field_type {
+        IndexFieldType::Tag => {
+            command.arg("TAG");
+            command.arg("SEPARATOR").arg(TAG_SEPARATOR);
+        }
+        IndexFieldType::Text => {
+            command.arg("TEXT");
+        }
+        IndexFieldType::Numeric => {
+            command.arg("NUMERIC");
+        }
+        IndexFieldType::Geo => {
+            command.arg("GEO");
+        }
+    }
+
+    if field.sortable {
+        command.arg("SORTABLE");
+    }
+    if field.index_missing {
+        command.arg("INDEXMISSING");
+    }
+}
+
+/// Drop the index named by `definition`, keeping its documents, and create it again.
+///
+/// RediSearch re-scans every key under the prefixes in the background, so searches return partial
+/// results until `FT.INFO` stops reporting `indexing`.
+pub async fn rebuild_index(conn: &mut ConnectionManager, definition: &IndexDefinition) -> Result<(), RepoError> {
+    drop_index(conn, &definition.name).await?;
+    ensure_index(conn, definition).await
+}
+
+/// Drop the index `name` if it exists, keeping its documents.
+pub async fn drop_index(conn: &mut ConnectionManager, name: &str) -> Result<(), RepoError> {
+    if let Err(err) = cmd("FT.DROPINDEX").arg(name).query_async::<()>(conn).await
+        && !unknown_index_error(&err)
+    {
+        return Err(err.into());
+    }
     Ok(())
 }
 
+fn unknown_index_error(err: &redis::RedisError) -> bool {
+    let msg = err.to_string().to_ascii_lowercase();
+    msg.contains("unknown index") || msg.contains("no such index")
+}
+
 fn index_exists_error(err: &redis::RedisError) -> bool {
     let msg = err.to_string().to_ascii_lowercase();
     msg.contains("already exists") && msg.contains("index")
@@ -796,14 +1133,126 @@ pub async fn execute_search<T>(
     params: &SearchParams,
     base_query: &str,
 ) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    run_search(conn, index_name, params, base_query, StorageMode::Json, None, None).await
+}
+
+/// Search an index created `ON HASH`, rebuilding each hit from its hash fields using
+/// `descriptor`'s field types.
+pub async fn execute_hash_search<T>(
+    conn: &mut ConnectionManager,
+    index_name: &str,
+    params: &SearchParams,
+    base_query: &str,
+    descriptor: &EntityDescriptor,
+) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    run_search(conn, index_name, params, base_query, StorageMode::Hash, Some(descriptor), None).await
+}
+
+/// Search `definition`, dispatching on the storage mode of its documents and stemming the query
+/// in the index's language.
+///
+/// Unlike [`execute_search`], hits have their stored-form fields decoded (see [`crate::storage::decode_fields`]).
+pub async fn execute_index_search<T>(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    descriptor: &EntityDescriptor,
+    params: &SearchParams,
+    base_query: &str,
+) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    run_search(
+        conn,
+        &definition.name,
+        params,
+        base_query,
+        definition.storage,
+        Some(descriptor),
+        definition.language.as_deref(),
+    )
+    .await
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "snugom.search",
+        skip_all,
+        err(Display),
+        fields(
+            collection = descriptor.map(|descriptor| descriptor.collection.as_str()),
+            index = index_name,
+            query = tracing::field::Empty,
+            total = tracing::field::Empty,
+            returned = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        )
+    )
+)]
+async fn run_search<T>(
+    conn: &mut ConnectionManager,
+    index_name: &str,
+    params: &SearchParams,
+    base_query: &str,
+    storage: StorageMode,
+    descriptor: Option<&EntityDescriptor>,
+    language: Option<&str>,
+) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    let collection = descriptor.map_or(index_name, |descriptor| descriptor.collection.as_str());
+    telemetry::observe(
+        "search",
+        collection,
+        query_index(conn, index_name, params, base_query, storage, descriptor, language),
+    )
+    .await
+}
+
+async fn query_index<T>(
+    conn: &mut ConnectionManager,
+    index_name: &str,
+    params: &SearchParams,
+    base_query: &str,
+    storage: StorageMode,
+    descriptor: Option<&EntityDescriptor>,
+    language: Option<&str>,
+) -> Result<SearchResult<T>, RepoError>
 where
     T: DeserializeOwned,
 {
     let query = params.build_query(base_query);
+    telemetry::record("query", query.as_str());
 
     let mut command = cmd("FT.SEARCH");
     command.arg(index_name);
     command.arg(query);
+    push_search_options(&mut command, params, storage, language);
+
+    let raw: Value = command.query_async(conn).await?;
+    let result = parse_search_reply(&raw, params, storage, descriptor)?;
+    telemetry::record("total", result.total);
+    telemetry::record("returned", result.items.len());
+    Ok(result)
+}
+
+/// Append the `FT.SEARCH` options that follow the query string; `language` stems the query's
+/// terms the way the index stemmed its documents.
+fn push_search_options(command: &mut redis::Cmd, params: &SearchParams, storage: StorageMode, language: Option<&str>) {
+    if params.scores {
+        command.arg("WITHSCORES");
+    }
+    if !params.in_keys.is_empty() {
+        command.arg("INKEYS").arg(params.in_keys.len()).arg(&params.in_keys);
+    }
 
     if let Some(sort) = &params.sort {
         command.arg("SORTBY").arg(&sort.field).arg(sort.order.as_str());
@@ -812,11 +1261,39 @@ where
     let start = params.offset();
     let count = params.page_size;
     command.arg("LIMIT").arg(start).arg(count);
-    command.arg("RETURN").arg(1).arg("$");
-    command.arg("DIALECT").arg(3);
+    if storage.is_hash() {
+        // Hash hits carry their fields directly; an empty projection returns them all.
+        if !params.return_fields.is_empty() {
+            command.arg("RETURN").arg(params.return_fields.len()).arg(&params.return_fields);
+        }
+    } else if params.return_fields.is_empty() {
+        command.arg("RETURN").arg(1).arg("$");
+    } else {
+        command.arg("RETURN").arg(params.return_fields.len() * 3);
+        for field in &params.return_fields {
+            command.arg(format!("$.{field}")).arg("AS").arg(field);
+        }
+    }
+    if let Some(language) = language {
+        command.arg("LANGUAGE").arg(language);
+    }
+    if let Some(timeout) = params.timeout_ms {
+        command.arg("TIMEOUT").arg(timeout);
+    }
+    command.arg("DIALECT").arg(params.dialect.unwrap_or(SEARCH_DIALECT));
+}
 
-    let raw: Value = command.query_async(conn).await?;
-    let values: Vec<Value> = from_redis_value(&raw).map_err(|err| RepoError::Other {
+/// Decode an `FT.SEARCH` reply into a page of results.
+fn parse_search_reply<T>(
+    raw: &Value,
+    params: &SearchParams,
+    storage: StorageMode,
+    descriptor: Option<&EntityDescriptor>,
+) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    let values: Vec<Value> = from_redis_value(raw).map_err(|err| RepoError::Other {
         message: Cow::Owned(format!("Failed to parse search response: {}", err)),
     })?;
 
@@ -826,6 +1303,7 @@ where
             total: 0,
             page: params.page,
             page_size: params.page_size,
+            scores: Vec::new(),
         });
     }
 
@@ -845,16 +1323,33 @@ where
         }
     };
 
+    // Each hit is its key, its score when asked for, then its document
+    let stride = if params.scores { 3 } else { 2 };
     let mut items = Vec::new();
+    let mut scores = Vec::new();
     let mut idx = 1;
-    while idx + 1 < values.len() {
-        let doc_value = &values[idx + 1];
-        let json_payload = extract_json_payload(doc_value)?;
-        let item: T = serde_json::from_str(&json_payload).map_err(|err| RepoError::Other {
+    while idx + stride - 1 < values.len() {
+        if params.scores {
+            scores.push(parse_score(&values[idx + 1])?);
+        }
+        let doc_value = &values[idx + stride - 1];
+        let deserialize_error = |err: serde_json::Error| RepoError::Other {
             message: Cow::Owned(format!("Failed to deserialize search document: {}", err)),
-        })?;
+        };
+        let mut document = match descriptor {
+            Some(descriptor) if storage.is_hash() => decode_hash_fields(descriptor, extract_hash_fields(doc_value)?),
+            _ if params.return_fields.is_empty() => {
+                serde_json::from_str(&extract_json_payload(doc_value)?).map_err(deserialize_error)?
+            }
+            _ => extract_projected_object(doc_value)?,
+        };
+        if let Some(descriptor) = descriptor {
+            strip_redacted(descriptor, &mut document);
+            decode_fields(descriptor, &mut document)?;
+        }
+        let item: T = serde_json::from_value(document).map_err(deserialize_error)?;
         items.push(item);
-        idx += 2;
+        idx += stride;
     }
 
     Ok(SearchResult {
@@ -862,9 +1357,27 @@ where
         total,
         page: params.page,
         page_size: params.page_size,
+        scores,
+    })
+}
+
+/// A `WITHSCORES` score: a double under RESP3, a bulk string under RESP2.
+fn parse_score(value: &Value) -> Result<f64, RepoError> {
+    let score = value_to_string(value)?;
+    score.parse().map_err(|_| RepoError::Other {
+        message: Cow::Owned(format!("Invalid score in search response: {score}")),
     })
 }
 
+/// Remove the entity's `#[snugom(redact)]` fields from a search hit.
+pub(crate) fn strip_redacted(descriptor: &EntityDescriptor, document: &mut JsonValue) {
+    if let Some(object) = document.as_object_mut() {
+        for field in descriptor.fields.iter().filter(|field| field.redacted) {
+            object.remove(&field.name);
+        }
+    }
+}
+
 pub fn build_text_query(term: Option<String>, fields: &[&str]) -> Option<String> {
     let raw = term?.trim().to_string();
     if raw.is_empty() {
@@ -914,6 +1427,42 @@ fn extract_json_payload(value: &Value) -> Result<String, RepoError> {
     }
 }
 
+/// Assemble `alias -> value` pairs from a projected search document into a JSON object.
+/// Fields missing from the document are absent from the object.
+fn extract_projected_object(value: &Value) -> Result<JsonValue, RepoError> {
+    let mut object = serde_json::Map::new();
+    if let Value::Array(items) = value {
+        for chunk in items.chunks(2) {
+            if chunk.len() != 2 {
+                continue;
+            }
+            let alias: String = from_redis_value(&chunk[0]).map_err(|err| RepoError::Other {
+                message: Cow::Owned(format!("Invalid field alias in search document: {}", err)),
+            })?;
+            let payload = normalize_json_payload(value_to_string(&chunk[1])?)?;
+            let field: JsonValue = serde_json::from_str(&payload).map_err(|err| RepoError::Other {
+                message: Cow::Owned(format!("Failed to parse projected field `{}`: {}", alias, err)),
+            })?;
+            object.insert(alias, field);
+        }
+    }
+    Ok(JsonValue::Object(object))
+}
+
+/// Collect the `field -> value` pairs of a hash search document.
+fn extract_hash_fields(value: &Value) -> Result<Vec<(String, String)>, RepoError> {
+    let mut fields = Vec::new();
+    if let Value::Array(items) = value {
+        for chunk in items.chunks(2) {
+            if chunk.len() != 2 {
+                continue;
+            }
+            fields.push((value_to_string(&chunk[0])?, value_to_string(&chunk[1])?));
+        }
+    }
+    Ok(fields)
+}
+
 fn normalize_json_payload(mut payload: String) -> Result<String, RepoError> {
     let trimmed = payload.trim();
     if trimmed.starts_with('[') && trimmed.ends_with(']') {
@@ -1249,7 +1798,7 @@ mod tests {
             }
             "active" => {
                 assert_eq!(descriptor.operator, FilterOperator::Bool);
-                let value = descriptor.values.get(0).ok_or_else(|| RepoError::InvalidRequest {
+                let value = descriptor.values.first().ok_or_else(|| RepoError::InvalidRequest {
                     message: "active filter requires a value".to_string(),
                 })?;
                 let flag = match value.as_str() {
@@ -1318,6 +1867,51 @@ mod tests {
         assert_eq!(params.conditions[1].to_query_clause(), "(@member_count:[10 50])");
     }
 
+    #[test]
+    fn into_params_maps_comparison_operators_to_open_ranges() {
+        let query = SearchQuery {
+            page: None,
+            page_size: None,
+            sort_by: None,
+            sort_order: None,
+            q: None,
+            filter: vec![
+                "member_count:gte:10".to_string(),
+                "member_count:LT:50".to_string(),
+                "created_at:gt:1704067200000".to_string(),
+                "created_at:lte:1704067300000".to_string(),
+            ],
+        };
+
+        let sorts = default_sorts();
+        let params = query
+            .into_params(&sorts, &sorts[0], mock_filter_mapper)
+            .expect("query should parse");
+        let clauses: Vec<_> = params.conditions.iter().map(FilterCondition::to_query_clause).collect();
+        assert_eq!(
+            clauses,
+            [
+                "(@member_count:[10 +inf])",
+                "(@member_count:[-inf (50])",
+                "(@created_at_ts:[(1704067200000 +inf])",
+                "(@created_at_ts:[-inf 1704067300000])",
+            ]
+        );
+
+        for raw in ["member_count:gt:", "member_count:lte:ten"] {
+            let query = SearchQuery {
+                page: None,
+                page_size: None,
+                sort_by: None,
+                sort_order: None,
+                q: None,
+                filter: vec![raw.to_string()],
+            };
+            let err = query.into_params(&sorts, &sorts[0], mock_filter_mapper).expect_err(raw);
+            assert!(matches!(err, RepoError::InvalidRequest { .. }));
+        }
+    }
+
     #[test]
     fn into_params_caps_page_size_and_overrides_sort_order() {
         let query = SearchQuery {
@@ -1383,7 +1977,7 @@ mod tests {
             sort_by: None,
             sort_order: None,
             q: None,
-            filter: vec!["member_count:gt:10".to_string()],
+            filter: vec!["member_count:between:10,50".to_string()],
         };
 
         let err = query
@@ -1413,6 +2007,89 @@ mod tests {
         assert_eq!(params.conditions[0].to_query_clause(), "(@active:{true})");
     }
 
+    #[test]
+    fn with_scores_requests_and_parses_scores() {
+        let params = SearchParams::new().with_scores();
+        let mut command = cmd("FT.SEARCH");
+        push_search_options(&mut command, &params, StorageMode::Json, None);
+        assert_eq!(command.args_iter().nth(1), Some(redis::Arg::Simple(&b"WITHSCORES"[..])));
+
+        let bulk = |text: &str| Value::BulkString(text.as_bytes().to_vec());
+        let reply = Value::Array(vec![
+            Value::Int(2),
+            bulk("app:shop:items:a"),
+            bulk("2.5"),
+            Value::Array(vec![bulk("$"), bulk(r#"{"name":"a"}"#)]),
+            bulk("app:shop:items:b"),
+            Value::Double(0.75),
+            Value::Array(vec![bulk("$"), bulk(r#"{"name":"b"}"#)]),
+        ]);
+        let result: SearchResult<JsonValue> = parse_search_reply(&reply, &params, StorageMode::Json, None).unwrap();
+        let scored: Vec<_> = result.scored().map(|(item, score)| (item["name"].as_str().unwrap(), score)).collect();
+        assert_eq!(scored, [("a", 2.5), ("b", 0.75)]);
+
+        // Without scores, hits are a key and a document
+        let plain = SearchParams::new();
+        let reply = Value::Array(vec![Value::Int(1), bulk("k"), Value::Array(vec![bulk("$"), bulk("{}")])]);
+        let result: SearchResult<JsonValue> = parse_search_reply(&reply, &plain, StorageMode::Json, None).unwrap();
+        assert_eq!((result.items.len(), result.scores.len()), (1, 0));
+    }
+
+    #[test]
+    fn timeout_and_dialect_come_from_params_then_defaults() {
+        let args = |params: &SearchParams| {
+            let mut command = cmd("FT.SEARCH");
+            push_search_options(&mut command, params, StorageMode::Json, None);
+            let args: Vec<String> = command
+                .args_iter()
+                .map(|arg| match arg {
+                    redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                    redis::Arg::Cursor => String::new(),
+                })
+                .collect();
+            args.join(" ")
+        };
+        let plain = SearchParams::new();
+        assert!(args(&plain).ends_with("RETURN 1 $ DIALECT 3"));
+        assert!(args(&SearchParams::new().with_timeout(200).with_dialect(4)).ends_with("$ TIMEOUT 200 DIALECT 4"));
+
+        let defaults = SearchDefaults {
+            dialect: Some(4),
+            timeout_ms: Some(500),
+        };
+        assert!(matches!(SearchDefaults::default().apply(&plain), Cow::Borrowed(_)));
+        assert!(args(&defaults.apply(&plain)).ends_with("$ TIMEOUT 500 DIALECT 4"));
+        assert!(args(&defaults.apply(&SearchParams::new().with_timeout(50))).ends_with("$ TIMEOUT 50 DIALECT 4"));
+    }
+
+    #[test]
+    fn searches_name_the_index_language() {
+        let mut command = cmd("FT.SEARCH");
+        push_search_options(&mut command, &SearchParams::new(), StorageMode::Json, Some("german"));
+        let args: Vec<_> = command.args_iter().collect();
+        let language = args.iter().position(|arg| *arg == redis::Arg::Simple(&b"LANGUAGE"[..]));
+        assert_eq!(language.map(|at| &args[at + 1]), Some(&redis::Arg::Simple(&b"german"[..])));
+    }
+
+    #[test]
+    fn into_params_negates_neq_filters() {
+        let query = SearchQuery {
+            page: None,
+            page_size: None,
+            sort_by: None,
+            sort_order: None,
+            q: None,
+            filter: vec!["visibility:neq:private|hidden".to_string(), "member_count:not:0".to_string()],
+        };
+
+        let params = query
+            .into_params(&default_sorts(), &default_sorts()[0], mock_filter_mapper)
+            .expect("neq filter should parse");
+
+        let clauses: Vec<_> = params.conditions.iter().map(FilterCondition::to_query_clause).collect();
+        assert_eq!(clauses, ["-(@visibility:{private|hidden})", "-(@member_count:[0 0])"]);
+    }
+
     #[test]
     fn with_text_query_attaches_full_text_clause() {
         let query = SearchQuery {
@@ -1505,6 +2182,8 @@ mod tests {
             field: "created_at".to_string(),
             min: Some(100.0),
             max: None,
+            min_exclusive: false,
+            max_exclusive: false,
         };
 
         assert_eq!(condition.to_query_clause(), "(@created_at:[100 +inf])");
@@ -1964,6 +2643,30 @@ mod tests {
         assert_eq!(condition.to_query_clause(), "(@status:{active})");
     }
 
+    #[test]
+    fn not_negates_the_inner_clause() {
+        assert_eq!(FilterCondition::tag_ne("status", "archived").to_query_clause(), "-(@status:{archived})");
+        assert_eq!(
+            FilterCondition::tag_not_in("status", ["archived", "deleted"]).to_query_clause(),
+            "-(@status:{archived|deleted})"
+        );
+        let nested = FilterCondition::and([
+            FilterCondition::bool_eq("verified", true),
+            FilterCondition::negate(FilterCondition::or([
+                FilterCondition::tag_eq("role", "guest"),
+                FilterCondition::numeric_lt("age", 18.0),
+            ])),
+        ]);
+        assert_eq!(nested.to_query_clause(), "((@verified:{true}) -((@role:{guest})|(@age:[-inf 18])))");
+        assert_eq!(FilterCondition::negate(FilterCondition::and([])).to_query_clause(), "");
+    }
+
+    #[test]
+    fn missing_and_present_use_ismissing() {
+        assert_eq!(FilterCondition::is_missing("avatar").to_query_clause(), "(ismissing(@avatar))");
+        assert_eq!(FilterCondition::is_present("avatar").to_query_clause(), "-(ismissing(@avatar))");
+    }
+
     #[test]
     fn and_with_single_condition_simplifies() {
         let condition = FilterCondition::and([FilterCondition::bool_eq("verified", true)]);
@@ -2007,6 +2710,8 @@ mod tests {
                 field: "price".to_string(),
                 min: Some(100.0),
                 max: Some(500.0),
+                min_exclusive: false,
+                max_exclusive: false,
             },
         ]);
 
@@ -2025,6 +2730,8 @@ mod tests {
                 field: "rating".to_string(),
                 min: Some(4.5),
                 max: None,
+                min_exclusive: false,
+                max_exclusive: false,
             },
         ]);
 
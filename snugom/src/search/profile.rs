@@ -0,0 +1,330 @@
+//! `FT.EXPLAIN` and `FT.PROFILE` wrappers for understanding how a search executes.
+//!
+//! [`explain_index_search`] returns the parse tree RediSearch builds for a query, and
+//! [`profile_index_search`] runs the search and reports where the time went. Profile replies
+//! differ between RediSearch versions (a flat profile, or one per shard), so the typed fields
+//! are picked out of the reply wherever they appear and the full reply is kept in `raw`.
+
+use std::borrow::Cow;
+
+use redis::{Value, aio::ConnectionManager, cmd, from_redis_value};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{
+    IndexDefinition, SEARCH_DIALECT, SearchParams, SearchResult, parse_search_reply, push_search_options,
+};
+use crate::{errors::RepoError, types::EntityDescriptor};
+
+/// One node of an `FT.EXPLAIN` parse tree, e.g. `INTERSECT` or `@status:TAG:{`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainNode {
+    pub label: String,
+    pub children: Vec<ExplainNode>,
+}
+
+/// How RediSearch parses a query, from [`explain_index_search`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryExplanation {
+    /// The generated query string that was explained
+    pub query: String,
+    /// Top-level nodes of the parse tree, usually one
+    pub tree: Vec<ExplainNode>,
+    /// The reply as RediSearch printed it
+    pub raw: String,
+}
+
+/// A query iterator from a search profile, e.g. `INTERSECT` over `TAG` and `NUMERIC` children.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfileIterator {
+    /// Iterator type, such as `INTERSECT`, `UNION`, `TAG`, `TEXT`, or `NUMERIC`
+    pub kind: String,
+    /// The term or numeric range the iterator matches, when it has one
+    pub detail: Option<String>,
+    pub time_ms: Option<f64>,
+    /// Documents the iterator produced
+    pub counter: Option<u64>,
+    pub children: Vec<ProfileIterator>,
+}
+
+/// A result processor stage from a search profile, e.g. `Sorter` or `Loader`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfileProcessor {
+    pub kind: String,
+    pub time_ms: Option<f64>,
+    pub counter: Option<u64>,
+}
+
+/// Results and timings of a search run under `FT.PROFILE`, from [`profile_index_search`].
+#[derive(Debug, Clone)]
+pub struct SearchProfile<T> {
+    pub result: SearchResult<T>,
+    pub total_time_ms: Option<f64>,
+    pub parsing_time_ms: Option<f64>,
+    pub pipeline_creation_time_ms: Option<f64>,
+    /// Root of the iterator tree that matched documents
+    pub iterators: Option<ProfileIterator>,
+    /// Stages applied to matched documents, in order
+    pub result_processors: Vec<ProfileProcessor>,
+    /// The profile section of the reply, with key/value arrays turned into objects
+    pub raw: JsonValue,
+}
+
+/// Explain the query `params` would run against `definition` (`FT.EXPLAIN`).
+pub async fn explain_index_search(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    params: &SearchParams,
+    base_query: &str,
+) -> Result<QueryExplanation, RepoError> {
+    let query = params.build_query(base_query);
+    let raw: String = cmd("FT.EXPLAIN")
+        .arg(definition.name.as_str())
+        .arg(query.as_str())
+        .arg("DIALECT")
+        .arg(params.dialect.unwrap_or(SEARCH_DIALECT))
+        .query_async(conn)
+        .await?;
+    Ok(QueryExplanation {
+        tree: parse_explain(&raw),
+        query,
+        raw,
+    })
+}
+
+/// Run the search `params` describes under `FT.PROFILE`.
+pub async fn profile_index_search<T>(
+    conn: &mut ConnectionManager,
+    definition: &IndexDefinition,
+    descriptor: &EntityDescriptor,
+    params: &SearchParams,
+    base_query: &str,
+) -> Result<SearchProfile<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    let mut command = cmd("FT.PROFILE");
+    command.arg(definition.name.as_str()).arg("SEARCH").arg("QUERY");
+    command.arg(params.build_query(base_query));
+    push_search_options(&mut command, params, definition.storage, definition.language.as_deref());
+
+    let raw: Value = command.query_async(conn).await?;
+    let mut parts: Vec<Value> = from_redis_value(&raw).map_err(|err| RepoError::Other {
+        message: Cow::Owned(format!("Failed to parse profile response: {err}")),
+    })?;
+    if parts.len() != 2 {
+        return Err(RepoError::Other {
+            message: Cow::Owned(format!("Expected results and profile, got {} entries", parts.len())),
+        });
+    }
+    let profile = to_json(parts.pop().unwrap_or(Value::Nil));
+    let result = parse_search_reply(&parts[0], params, definition.storage, Some(descriptor))?;
+
+    Ok(SearchProfile {
+        result,
+        total_time_ms: find_key(&profile, "Total profile time").and_then(as_f64),
+        parsing_time_ms: find_key(&profile, "Parsing time").and_then(as_f64),
+        pipeline_creation_time_ms: find_key(&profile, "Pipeline creation time").and_then(as_f64),
+        iterators: find_key(&profile, "Iterators profile").and_then(parse_iterator),
+        result_processors: find_key(&profile, "Result processors profile")
+            .map(|value| match value {
+                JsonValue::Array(stages) => stages.iter().filter_map(parse_processor).collect(),
+                stage => parse_processor(stage).into_iter().collect(),
+            })
+            .unwrap_or_default(),
+        raw: profile,
+    })
+}
+
+/// Build the tree from `FT.EXPLAIN`'s indented text, where `{` opens a node and `}` closes it.
+fn parse_explain(text: &str) -> Vec<ExplainNode> {
+    let node = |label: &str| ExplainNode {
+        label: label.to_string(),
+        children: Vec::new(),
+    };
+    let mut stack = vec![node("")];
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(rest) = line.strip_prefix('}') {
+            if stack.len() > 1 {
+                let mut closed = stack.pop().unwrap_or_else(|| node(""));
+                // Modifiers such as `} => { $weight: 0.5; }` describe the node just closed
+                let rest = rest.trim();
+                if !rest.is_empty() {
+                    closed.label = format!("{} {rest}", closed.label);
+                }
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(closed);
+                }
+            }
+        } else if let Some(label) = line.strip_suffix('{') {
+            stack.push(node(label.trim()));
+        } else if let Some(parent) = stack.last_mut() {
+            parent.children.push(node(line));
+        }
+    }
+    // Fold in anything left unclosed by a truncated reply
+    while stack.len() > 1 {
+        if let (Some(open), Some(parent)) = (stack.pop(), stack.last_mut()) {
+            parent.children.push(open);
+        }
+    }
+    stack.pop().map(|root| root.children).unwrap_or_default()
+}
+
+/// Convert a profile reply to JSON, turning `[key, value, key, value]` arrays into objects.
+fn to_json(value: Value) -> JsonValue {
+    match value {
+        Value::Nil => JsonValue::Null,
+        Value::Int(value) => JsonValue::from(value),
+        Value::Double(value) => JsonValue::from(value),
+        Value::Boolean(value) => JsonValue::Bool(value),
+        Value::BulkString(bytes) => JsonValue::String(String::from_utf8_lossy(&bytes).into_owned()),
+        Value::SimpleString(text) => JsonValue::String(text),
+        Value::Map(pairs) => JsonValue::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (to_json(key).as_str().unwrap_or_default().to_string(), to_json(value)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let items: Vec<JsonValue> = items.into_iter().map(to_json).collect();
+            let keyed = !items.is_empty()
+                && items.len().is_multiple_of(2)
+                && items.iter().step_by(2).all(JsonValue::is_string);
+            if !keyed {
+                return JsonValue::Array(items);
+            }
+            let mut object = Map::new();
+            let mut items = items.into_iter();
+            while let (Some(JsonValue::String(key)), Some(value)) = (items.next(), items.next()) {
+                object.insert(key, value);
+            }
+            JsonValue::Object(object)
+        }
+        other => JsonValue::String(format!("{other:?}")),
+    }
+}
+
+/// Depth-first search for the first value stored under `key`.
+fn find_key<'a>(value: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match value {
+        JsonValue::Object(object) => object
+            .get(key)
+            .or_else(|| object.values().find_map(|child| find_key(child, key))),
+        JsonValue::Array(items) => items.iter().find_map(|child| find_key(child, key)),
+        _ => None,
+    }
+}
+
+/// Profile numbers arrive as numbers or as strings, depending on the version.
+fn as_f64(value: &JsonValue) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str()?.trim().parse().ok())
+}
+
+fn parse_iterator(value: &JsonValue) -> Option<ProfileIterator> {
+    let object = match value {
+        JsonValue::Object(object) => object,
+        JsonValue::Array(items) => return items.iter().find_map(parse_iterator),
+        _ => return None,
+    };
+    let children = ["Child iterators", "Child iterator"]
+        .iter()
+        .find_map(|key| object.get(*key))
+        .map(|value| match value {
+            JsonValue::Array(children) => children.iter().filter_map(parse_iterator).collect(),
+            child => parse_iterator(child).into_iter().collect(),
+        })
+        .unwrap_or_default();
+    Some(ProfileIterator {
+        kind: object.get("Type")?.as_str()?.to_string(),
+        detail: ["Term", "Query type"]
+            .iter()
+            .find_map(|key| object.get(*key))
+            .map(|value| value.as_str().map_or_else(|| value.to_string(), str::to_string)),
+        time_ms: object.get("Time").and_then(as_f64),
+        counter: object.get("Counter").and_then(as_f64).map(|count| count as u64),
+        children,
+    })
+}
+
+fn parse_processor(value: &JsonValue) -> Option<ProfileProcessor> {
+    let object = value.as_object()?;
+    Some(ProfileProcessor {
+        kind: object.get("Type")?.as_str()?.to_string(),
+        time_ms: object.get("Time").and_then(as_f64),
+        counter: object.get("Counter").and_then(as_f64).map(|count| count as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(text: &str) -> Value {
+        Value::BulkString(text.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn explain_text_becomes_a_tree() {
+        let raw = "INTERSECT {\n  TAG:@status {\n    active\n  }\n  @count:NUMERIC [1 10]\n} => { $weight: 0.5; }\n";
+        let tree = parse_explain(raw);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].label, "INTERSECT => { $weight: 0.5; }");
+        let children: Vec<&str> = tree[0].children.iter().map(|child| child.label.as_str()).collect();
+        assert_eq!(children, vec!["TAG:@status", "@count:NUMERIC [1 10]"]);
+        assert_eq!(tree[0].children[0].children[0].label, "active");
+    }
+
+    #[test]
+    fn profile_timings_are_found_in_shard_replies() {
+        let iterator = Value::Array(vec![
+            bulk("Type"),
+            bulk("INTERSECT"),
+            bulk("Time"),
+            bulk("0.25"),
+            bulk("Counter"),
+            Value::Int(2),
+            bulk("Child iterators"),
+            Value::Array(vec![Value::Array(vec![
+                bulk("Type"),
+                bulk("TAG"),
+                bulk("Term"),
+                bulk("active"),
+                bulk("Time"),
+                Value::Double(0.1),
+                bulk("Counter"),
+                Value::Int(3),
+            ])]),
+        ]);
+        let shard = Value::Array(vec![
+            bulk("Total profile time"),
+            bulk("1.5"),
+            bulk("Parsing time"),
+            bulk("0.02"),
+            bulk("Iterators profile"),
+            iterator,
+            bulk("Result processors profile"),
+            Value::Array(vec![Value::Array(vec![
+                bulk("Type"),
+                bulk("Sorter"),
+                bulk("Time"),
+                bulk("0.3"),
+                bulk("Counter"),
+                Value::Int(2),
+            ])]),
+        ]);
+        let profile = to_json(Value::Array(vec![bulk("Shards"), Value::Array(vec![shard])]));
+
+        assert_eq!(find_key(&profile, "Total profile time").and_then(as_f64), Some(1.5));
+        assert_eq!(find_key(&profile, "Pipeline creation time"), None);
+        let root = find_key(&profile, "Iterators profile").and_then(parse_iterator).expect("iterators");
+        assert_eq!(root.kind, "INTERSECT");
+        assert_eq!(root.counter, Some(2));
+        assert_eq!(root.children[0].detail.as_deref(), Some("active"));
+        assert_eq!(root.children[0].time_ms, Some(0.1));
+        let JsonValue::Array(stages) = find_key(&profile, "Result processors profile").expect("processors") else {
+            panic!("expected processor list");
+        };
+        assert_eq!(parse_processor(&stages[0]).map(|stage| stage.kind), Some("Sorter".to_string()));
+    }
+}
@@ -0,0 +1,116 @@
+//! Recording of searches that exceed a latency threshold.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use super::SearchParams;
+
+/// Entries kept by [`SlowQueryLog::new`] before the oldest are dropped.
+pub const DEFAULT_SLOW_QUERY_CAPACITY: usize = 100;
+
+/// A search that ran longer than the slow query threshold.
+#[derive(Debug, Clone)]
+pub struct SlowQuery {
+    pub index: String,
+    /// The generated RediSearch query string
+    pub query: String,
+    /// Filters, sort, paging, and projection the search ran with
+    pub params: SearchParams,
+    pub duration: Duration,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Ring buffer of searches slower than a threshold.
+///
+/// Clones share the same buffer, so a log handed to every repository of a client can be
+/// read back from any of them.
+#[derive(Debug, Clone)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    capacity: usize,
+    entries: Arc<Mutex<VecDeque<SlowQuery>>>,
+}
+
+impl SlowQueryLog {
+    /// Record searches taking at least `threshold`, keeping the latest
+    /// [`DEFAULT_SLOW_QUERY_CAPACITY`].
+    pub fn new(threshold: Duration) -> Self {
+        Self::with_capacity(threshold, DEFAULT_SLOW_QUERY_CAPACITY)
+    }
+
+    /// Record searches taking at least `threshold`, keeping the latest `capacity`.
+    pub fn with_capacity(threshold: Duration, capacity: usize) -> Self {
+        Self {
+            threshold,
+            capacity: capacity.max(1),
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Recorded searches, oldest first.
+    pub fn entries(&self) -> Vec<SlowQuery> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Record the search if it was slow; the query string is only built when it is kept.
+    pub(crate) fn observe(&self, index: &str, params: &SearchParams, base_query: &str, duration: Duration) {
+        if duration < self.threshold {
+            return;
+        }
+        let query = params.build_query(base_query);
+        #[cfg(feature = "tracing")]
+        tracing::warn!(index, query = %query, elapsed_ms = duration.as_secs_f64() * 1000.0, "slow search");
+        let mut entries = self.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(SlowQuery {
+            index: index.to_string(),
+            query,
+            params: params.clone(),
+            duration,
+            recorded_at: Utc::now(),
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<SlowQuery>> {
+        // A panic while holding the lock cannot leave the buffer inconsistent, so keep using it
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_slow_searches_up_to_capacity() {
+        let log = SlowQueryLog::with_capacity(Duration::from_millis(10), 2);
+        let params = SearchParams::new();
+        log.observe("idx", &params, "", Duration::from_millis(5));
+        assert!(log.entries().is_empty());
+
+        for ms in [10, 20, 30] {
+            log.observe("idx", &params, "", Duration::from_millis(ms));
+        }
+        let durations: Vec<_> = log.entries().iter().map(|entry| entry.duration.as_millis()).collect();
+        assert_eq!(durations, vec![20, 30]);
+        assert_eq!(log.entries()[0].query, "*");
+
+        log.clone().clear();
+        assert!(log.entries().is_empty());
+    }
+}
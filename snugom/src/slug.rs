@@ -0,0 +1,70 @@
+/// Turns `text` into a URL-safe slug: lowercase ASCII letters and digits joined by single `-`.
+///
+/// Accented Latin letters are folded to their base letter (`é` to `e`, `ß` to `ss`); any other
+/// character separates words, so `"Crème brûlée, 2 ways!"` becomes `creme-brulee-2-ways`.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    let mut pending_dash = false;
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        let folded = fold(ch);
+        if !ch.is_ascii_alphanumeric() && folded.is_none() {
+            pending_dash = true;
+            continue;
+        }
+        if pending_dash && !slug.is_empty() {
+            slug.push('-');
+        }
+        pending_dash = false;
+        match folded {
+            Some(ascii) => slug.push_str(ascii),
+            None => slug.push(ch),
+        }
+    }
+    slug
+}
+
+/// The `attempt`th alternative to a taken slug: `post-2`, `post-3`, ...
+pub fn with_suffix(slug: &str, attempt: u32) -> String {
+    format!("{slug}-{attempt}")
+}
+
+/// ASCII spelling of a lowercase accented Latin letter.
+fn fold(ch: char) -> Option<&'static str> {
+    let ascii = match ch {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => "a",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => "o",
+        'ř' => "r",
+        'ś' | 'š' => "s",
+        'ť' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        'þ' => "th",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_joins_words_with_dashes() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Rust 2024 -- Edition "), "rust-2024-edition");
+        assert_eq!(slugify("Crème brûlée, 2 ways!"), "creme-brulee-2-ways");
+        assert_eq!(slugify("Straße"), "strasse");
+        assert_eq!(slugify("!!!"), "");
+        assert_eq!(with_suffix("post", 2), "post-2");
+    }
+}
@@ -0,0 +1,422 @@
+//! Document encoding for the Redis data type behind each entity.
+//!
+//! JSON entities are read and written whole with `JSON.GET`/`JSON.SET`. Hash entities
+//! (`#[snugom(storage = "hash")]`) keep one hash field per top-level document field:
+//! strings are stored raw, numbers and booleans as their JSON text, nulls by omission,
+//! and any other value as a JSON string. The `metadata` object is flattened into
+//! `metadata.*` fields so the version can be read and bumped with `HGET`/`HSET`.
+//!
+//! In both modes some fields are stored in a different shape than they serialize to:
+//! `Vec<u8>` fields become base64 strings and `#[snugom(compressed)]` fields become
+//! compressed blobs. [`encode_fields`] and [`decode_fields`] convert between the two.
+
+use std::collections::BTreeMap;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use redis::{Cmd, Value as RedisValue, aio::ConnectionManager, cmd, from_redis_value};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{
+    compression,
+    errors::{RepoError, ValidationError},
+    types::{EntityDescriptor, FieldDescriptor, FieldType, StorageMode},
+};
+
+/// Hash field holding the document version, flattened from `metadata.version`.
+pub const HASH_VERSION_FIELD: &str = "metadata.version";
+
+/// Encode a single document value as a hash field value.
+///
+/// Returns `None` for `null`, which hash entities store by leaving the field out.
+pub fn encode_hash_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Flatten a document into alternating field/value strings, ready to splat into `HSET`.
+pub fn encode_hash_fields(document: &Value) -> Vec<String> {
+    let mut fields = Vec::new();
+    let Some(object) = document.as_object() else {
+        return fields;
+    };
+    for (name, value) in object {
+        match value {
+            Value::Object(metadata) if name == "metadata" => {
+                for (child, value) in metadata {
+                    if let Some(encoded) = encode_hash_value(value) {
+                        fields.push(format!("{name}.{child}"));
+                        fields.push(encoded);
+                    }
+                }
+            }
+            _ => {
+                if let Some(encoded) = encode_hash_value(value) {
+                    fields.push(name.clone());
+                    fields.push(encoded);
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Rebuild a document from its hash fields.
+///
+/// Fields the descriptor declares as strings, datetimes, or bytes are kept verbatim; everything
+/// else is parsed as JSON, falling back to a string when the text is not valid JSON.
+pub fn decode_hash_fields<I>(descriptor: &EntityDescriptor, fields: I) -> Value
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut object = Map::new();
+    for (name, raw) in fields {
+        if let Some((parent, child)) = name.split_once('.') {
+            let entry = object
+                .entry(parent.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(nested) = entry {
+                nested.insert(child.to_string(), decode_untyped(raw));
+            }
+            continue;
+        }
+        let field_type = descriptor.fields.iter().find(|field| field.name == name).map(|field| field.field_type);
+        let value = match field_type {
+            Some(FieldType::String | FieldType::DateTime | FieldType::Bytes) => Value::String(raw),
+            _ => decode_untyped(raw),
+        };
+        object.insert(name, value);
+    }
+    Value::Object(object)
+}
+
+fn decode_untyped(raw: String) -> Value {
+    serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+}
+
+/// Convert a field's serialized value to the form kept in Redis.
+///
+/// Byte arrays are base64-encoded, then compressed fields are compressed. Values already
+/// in stored form, and `null`, pass through.
+pub fn encode_field_value(field: &FieldDescriptor, value: &Value) -> Result<Value, RepoError> {
+    let mut stored = match (field.field_type, value) {
+        (FieldType::Bytes, Value::Array(items)) => {
+            let bytes = items
+                .iter()
+                .map(|item| item.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| {
+                    RepoError::Validation(ValidationError::single(
+                        field.name.clone(),
+                        "bytes.invalid",
+                        "expected an array of bytes",
+                    ))
+                })?;
+            Value::String(STANDARD.encode(bytes))
+        }
+        _ => value.clone(),
+    };
+    if let Some(codec) = field.compression {
+        stored = compression::compress_value(codec, &stored)?;
+    }
+    Ok(stored)
+}
+
+/// Reverse [`encode_field_value`], restoring the value the field serializes to.
+pub fn decode_field_value(field: &FieldDescriptor, stored: &Value) -> Result<Value, RepoError> {
+    let value = match field.compression {
+        Some(_) => compression::decompress_value(stored)?,
+        None => stored.clone(),
+    };
+    match (field.field_type, &value) {
+        (FieldType::Bytes, Value::String(encoded)) => {
+            let bytes = STANDARD.decode(encoded).map_err(|err| RepoError::Other {
+                message: format!("field `{}` is not valid base64: {err}", field.name).into(),
+            })?;
+            Ok(Value::Array(bytes.into_iter().map(Value::from).collect()))
+        }
+        _ => Ok(value),
+    }
+}
+
+fn has_stored_form(field: &FieldDescriptor) -> bool {
+    field.compression.is_some() || field.field_type == FieldType::Bytes
+}
+
+/// Encode every field of `document` that has a distinct stored form, in place.
+pub fn encode_fields(descriptor: &EntityDescriptor, document: &mut Value) -> Result<(), RepoError> {
+    let Some(object) = document.as_object_mut() else {
+        return Ok(());
+    };
+    for field in descriptor.fields.iter().filter(|field| has_stored_form(field)) {
+        if let Some(value) = object.get_mut(&field.name) {
+            *value = encode_field_value(field, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decode every field of `document` that has a distinct stored form, in place.
+pub fn decode_fields(descriptor: &EntityDescriptor, document: &mut Value) -> Result<(), RepoError> {
+    let Some(object) = document.as_object_mut() else {
+        return Ok(());
+    };
+    for field in descriptor.fields.iter().filter(|field| has_stored_form(field)) {
+        if let Some(value) = object.get_mut(&field.name) {
+            *value = decode_field_value(field, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Build the command that reads the whole document at `key`.
+pub fn read_document_cmd(storage: StorageMode, key: &str) -> Cmd {
+    match storage {
+        StorageMode::Json => {
+            let mut command = cmd("JSON.GET");
+            command.arg(key);
+            command
+        }
+        StorageMode::Hash => {
+            let mut command = cmd("HGETALL");
+            command.arg(key);
+            command
+        }
+    }
+}
+
+/// Decode the reply to [`read_document_cmd`], returning `None` when the document is missing.
+///
+/// Fields are returned in their serialized form, see [`decode_fields`].
+pub fn decode_document(descriptor: &EntityDescriptor, reply: &RedisValue) -> Result<Option<Value>, RepoError> {
+    let document = match descriptor.storage {
+        StorageMode::Json => {
+            let raw: Option<String> = from_redis_value(reply)?;
+            raw.map(|json| {
+                serde_json::from_str(&json).map_err(|err| RepoError::Other {
+                    message: format!("failed to parse entity document: {err}").into(),
+                })
+            })
+            .transpose()?
+        }
+        StorageMode::Hash => {
+            let fields: BTreeMap<String, String> = from_redis_value(reply)?;
+            (!fields.is_empty()).then(|| decode_hash_fields(descriptor, fields))
+        }
+    };
+    document
+        .map(|mut document| {
+            decode_fields(descriptor, &mut document)?;
+            Ok(document)
+        })
+        .transpose()
+}
+
+/// Read the documents at `keys` in a single pipeline, deserializing each as `U`.
+///
+/// Missing documents come back as `None`, in the same position as their key.
+pub(crate) async fn read_documents<U>(
+    conn: &mut ConnectionManager,
+    descriptor: &EntityDescriptor,
+    keys: impl IntoIterator<Item = String>,
+) -> Result<Vec<Option<U>>, RepoError>
+where
+    U: DeserializeOwned,
+{
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.add_command(read_document_cmd(descriptor.storage, &key));
+    }
+    let replies: Vec<RedisValue> = pipe.query_async(conn).await?;
+    replies
+        .iter()
+        .map(|reply| {
+            decode_document(descriptor, reply)?
+                .map(|document| {
+                    serde_json::from_value(document).map_err(|err| RepoError::Other {
+                        message: format!("failed to deserialize entity: {err}").into(),
+                    })
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Read only `paths` of the document at `key`, rebuilt as a (possibly nested) object.
+///
+/// Paths are dotted field paths such as `name` or `profile.city`, with or without a
+/// leading `$.`. Paths missing from the document are left out of the result. Returns
+/// `None` when the document does not exist.
+pub(crate) async fn read_projection(
+    conn: &mut ConnectionManager,
+    descriptor: &EntityDescriptor,
+    key: &str,
+    paths: &[String],
+) -> Result<Option<Value>, RepoError> {
+    let fields: Vec<&str> = paths
+        .iter()
+        .map(|path| path.strip_prefix("$.").unwrap_or(path))
+        .collect();
+    let mut document = match descriptor.storage {
+        StorageMode::Json => {
+            let json_paths: Vec<String> = fields.iter().map(|field| format!("$.{field}")).collect();
+            let raw: Option<String> = cmd("JSON.GET").arg(key).arg(&json_paths).query_async(conn).await?;
+            let Some(raw) = raw else {
+                return Ok(None);
+            };
+            let reply: Value = serde_json::from_str(&raw).map_err(|err| RepoError::Other {
+                message: format!("failed to parse projection: {err}").into(),
+            })?;
+            // A single path replies with its match array, several with an object keyed by path
+            let mut matches = match reply {
+                Value::Array(_) if json_paths.len() == 1 => Map::from_iter([(json_paths[0].clone(), reply)]),
+                Value::Object(object) => object,
+                _ => Map::new(),
+            };
+            let mut document = Value::Object(Map::new());
+            for (field, json_path) in fields.iter().zip(&json_paths) {
+                if let Some(Value::Array(mut values)) = matches.remove(json_path)
+                    && !values.is_empty()
+                {
+                    insert_at_path(&mut document, field, values.swap_remove(0));
+                }
+            }
+            document
+        }
+        StorageMode::Hash => {
+            let (exists, values): (bool, Vec<Option<String>>) = redis::pipe()
+                .cmd("EXISTS")
+                .arg(key)
+                .cmd("HMGET")
+                .arg(key)
+                .arg(&fields)
+                .query_async(conn)
+                .await?;
+            if !exists {
+                return Ok(None);
+            }
+            let pairs = fields
+                .iter()
+                .zip(values)
+                .filter_map(|(field, value)| value.map(|value| (field.to_string(), value)));
+            decode_hash_fields(descriptor, pairs)
+        }
+    };
+    decode_fields(descriptor, &mut document)?;
+    Ok(Some(document))
+}
+
+fn insert_at_path(document: &mut Value, path: &str, value: Value) {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let Some(last) = segments.pop() else {
+        return;
+    };
+    let mut current = document;
+    for segment in segments {
+        let Value::Object(object) = current else {
+            return;
+        };
+        current = object
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(object) = current {
+        object.insert(last.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FieldDescriptor;
+    use serde_json::json;
+
+    fn descriptor() -> EntityDescriptor {
+        let field = |name: &str, field_type| FieldDescriptor {
+            name: name.to_string(),
+            field_type,
+            ..FieldDescriptor::default()
+        };
+        EntityDescriptor {
+            fields: vec![
+                field("id", FieldType::String),
+                field("code", FieldType::String),
+                field("count", FieldType::Number),
+                field("active", FieldType::Boolean),
+                field("tags", FieldType::Array),
+            ],
+            storage: StorageMode::Hash,
+            ..EntityDescriptor::default()
+        }
+    }
+
+    #[test]
+    fn hash_fields_round_trip_through_descriptor_types() {
+        let document = json!({
+            "id": "a1",
+            "code": "0042",
+            "count": 7,
+            "active": true,
+            "tags": ["x", "y"],
+            "note": null,
+            "metadata": { "version": 3 },
+        });
+        let encoded = encode_hash_fields(&document);
+        let pairs: Vec<(String, String)> = encoded
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        assert!(pairs.contains(&("code".to_string(), "0042".to_string())));
+        assert!(pairs.contains(&("metadata.version".to_string(), "3".to_string())));
+        assert!(!pairs.iter().any(|(name, _)| name == "note"));
+
+        let decoded = decode_hash_fields(&descriptor(), pairs);
+        let mut expected = document;
+        expected.as_object_mut().unwrap().remove("note");
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn bytes_fields_are_stored_as_base64() {
+        let descriptor = EntityDescriptor {
+            fields: vec![FieldDescriptor {
+                name: "avatar".to_string(),
+                field_type: FieldType::Bytes,
+                ..FieldDescriptor::default()
+            }],
+            ..EntityDescriptor::default()
+        };
+        let mut document = json!({ "avatar": [0, 159, 146, 150], "name": "x" });
+        encode_fields(&descriptor, &mut document).unwrap();
+        assert_eq!(document, json!({ "avatar": "AJ+Slg==", "name": "x" }));
+        decode_fields(&descriptor, &mut document).unwrap();
+        assert_eq!(document, json!({ "avatar": [0, 159, 146, 150], "name": "x" }));
+
+        let mut invalid = json!({ "avatar": [256] });
+        assert!(matches!(encode_fields(&descriptor, &mut invalid), Err(RepoError::Validation(_))));
+    }
+
+    #[test]
+    fn projected_paths_rebuild_nested_objects() {
+        let mut document = Value::Object(Map::new());
+        insert_at_path(&mut document, "name", json!("Ada"));
+        insert_at_path(&mut document, "profile.city", json!("London"));
+        insert_at_path(&mut document, "profile.links.site", json!("ada.dev"));
+        assert_eq!(
+            document,
+            json!({ "name": "Ada", "profile": { "city": "London", "links": { "site": "ada.dev" } } })
+        );
+    }
+
+    #[test]
+    fn undeclared_fields_fall_back_to_strings() {
+        let decoded = decode_hash_fields(
+            &descriptor(),
+            [("status".to_string(), "Active".to_string()), ("status_ts".to_string(), "1700".to_string())],
+        );
+        assert_eq!(decoded, json!({ "status": "Active", "status_ts": 1700 }));
+    }
+}
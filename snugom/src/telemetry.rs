@@ -0,0 +1,74 @@
+//! Span and metric helpers for the optional `tracing` and `metrics` features.
+//!
+//! Operations open their spans with `#[cfg_attr(feature = "tracing", tracing::instrument(...))]`.
+//! The helpers here fill in fields that are only known once the operation is underway, and
+//! compile to nothing when both features are off.
+
+use std::future::Future;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+use crate::errors::RepoError;
+
+/// Run one repository operation, timing it on the current span and in the operation metrics.
+#[allow(unused_variables)]
+pub(crate) async fn observe<F, R>(operation: &'static str, collection: &str, future: F) -> Result<R, RepoError>
+where
+    F: Future<Output = Result<R, RepoError>>,
+{
+    let _latency = Latency::start();
+    #[cfg(feature = "metrics")]
+    let started = Instant::now();
+    let result = future.await;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_operation(collection, operation, started.elapsed(), result.is_ok());
+    result
+}
+
+/// Record `value` as `field` on the current span, if that span declared the field.
+#[cfg(feature = "tracing")]
+pub(crate) fn record(field: &'static str, value: impl tracing::Value) {
+    tracing::Span::current().record(field, value);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record<V>(_field: &'static str, _value: V) {}
+
+/// Carry the current span into a future that runs elsewhere, such as a spawned task.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_current_span<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    tracing::Instrument::in_current_span(future)
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_current_span<F: Future>(future: F) -> F {
+    future
+}
+
+/// Records the time since [`Latency::start`] as `elapsed_ms` on the current span when dropped,
+/// so operations that fail part way are timed too.
+pub(crate) struct Latency {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    started: Instant,
+}
+
+impl Latency {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+            #[cfg(feature = "tracing")]
+            started: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Latency {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        self.span.record("elapsed_ms", elapsed.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,1717 @@
+//! In-memory stand-ins for the Lua mutation scripts and RediSearch.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, LazyLock, Mutex, MutexGuard},
+};
+
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::{
+    errors::{RepoError, ValidationError},
+    keys::KeyLayout,
+    repository::Repo,
+    runtime::{
+        CascadeDirective, CascadeRelationSpec, DeleteCascadeRelation, EntityDelete, EntityMutation, EntityPatch,
+        MutationCommand, MutationExecutor, MutationPlan, PatchOperationType, RelationMutation,
+        UniqueConstraintCheck, UniqueConstraintDefinition,
+    },
+    search::{
+        FilterCondition, IndexDefinition, IndexField, SearchEntity, SearchParams, SearchQuery, SearchResult,
+        SortOrder,
+    },
+    storage,
+    types::{DatetimeMirrorValue, EntityDescriptor, SnugomModel},
+};
+
+/// Clauses emitted by [`crate::search::build_text_query`]: `@field:(token* token*)`.
+static TEXT_QUERY_CLAUSE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"@(\w+):\(((?:[^()\\]|\\.)*)\)").expect("text query pattern is valid"));
+
+/// A [`MutationExecutor`] that applies plans to an in-memory store instead of Redis.
+///
+/// It reproduces what the mutation scripts do for creates, patches, deletes, and relation
+/// mutations: versions, datetime mirrors, unique constraints, relation sets and edges,
+/// cascades, and idempotency replays. Documents are always kept as JSON, whatever the
+/// entity's storage mode. `lua_check` snippets are not run, and idempotency records never
+/// expire. Upserts and get-or-create go through a live connection and are rejected.
+///
+/// Clones share the same store, so a clone handed to the code under test can be inspected
+/// afterwards.
+///
+/// ```ignore
+/// let repo: Repo<Order> = Repo::new("test");
+/// let mut store = MemoryExecutor::new();
+/// let created = repo.create(&mut store, Order::validation_builder().status("paid".into())).await?;
+/// let paid = store.search(&repo, &SearchParams::new().with_condition(FilterCondition::tag_eq("status", "paid")))?;
+/// assert_eq!(paid.total, 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryExecutor {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+impl MemoryExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stored document at `key`, including its metadata and mirror fields.
+    pub fn document(&self, key: &str) -> Option<Value> {
+        self.lock().documents.get(key).cloned()
+    }
+
+    /// Keys of every stored document, in order.
+    pub fn keys(&self) -> Vec<String> {
+        self.lock().documents.keys().cloned().collect()
+    }
+
+    /// Members of the relation set at `key`, in order; empty when the set does not exist.
+    pub fn members(&self, key: &str) -> Vec<String> {
+        self.lock()
+            .sets
+            .get(key)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Every command executed so far, serialized as the scripts would receive it.
+    pub fn history(&self) -> Vec<Value> {
+        self.lock().history.clone()
+    }
+
+    /// Drop all documents, relations, idempotency records, and history.
+    pub fn clear(&self) {
+        *self.lock() = MemoryState::default();
+    }
+
+    /// Read an entity of `repo` back, like [`Repo::get`].
+    pub fn get<T>(&self, repo: &Repo<T>, entity_id: &str) -> Result<Option<T>, RepoError>
+    where
+        T: SnugomModel + DeserializeOwned,
+    {
+        let Some(document) = self.document(&repo.entity_key(entity_id)) else {
+            return Ok(None);
+        };
+        deserialize_document(repo.descriptor(), document).map(Some)
+    }
+
+    /// Run a search against the documents stored under `repo`'s index prefixes.
+    ///
+    /// Filter conditions, free-text queries built by [`SearchQuery`], sorting, paging, and
+    /// return fields are evaluated over the stored JSON. Matching follows RediSearch
+    /// loosely: tags compare case-insensitively, and text is split into lowercase words.
+    /// Raw clauses and entity base filters cannot be evaluated and fail with
+    /// `RepoError::InvalidRequest`.
+    pub fn search<T>(&self, repo: &Repo<T>, params: &SearchParams) -> Result<SearchResult<T>, RepoError>
+    where
+        T: SnugomModel + SearchEntity,
+    {
+        let definition = T::index_definition(repo.key_context().prefix);
+        if !T::base_filter().is_empty() {
+            return Err(unsupported("base filters"));
+        }
+        let state = self.lock();
+        search_documents(&state.documents, &definition, repo.descriptor(), params)
+    }
+
+    /// Parse `query` with the entity's sorts and filters, then search like [`MemoryExecutor::search`].
+    pub fn search_with_query<T>(&self, repo: &Repo<T>, query: SearchQuery) -> Result<SearchResult<T>, RepoError>
+    where
+        T: SnugomModel + SearchEntity,
+    {
+        let params = query.with_text_query(
+            T::allowed_sorts(),
+            T::default_sort(),
+            |descriptor| T::map_filter(descriptor),
+            T::text_search_fields(),
+        )?;
+        self.search(repo, &params)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryState> {
+        // Commands validate before writing, so a panic cannot leave the store half-applied
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl MutationExecutor for MemoryExecutor {
+    async fn execute(&mut self, plan: MutationPlan) -> Result<Vec<Value>, RepoError> {
+        let mut state = self.lock();
+        let mut responses = Vec::with_capacity(plan.commands.len());
+        for command in &plan.commands {
+            state.history.push(serde_json::to_value(command).map_err(|err| RepoError::Other {
+                message: format!("failed to serialize command: {err}").into(),
+            })?);
+            responses.push(state.apply(command)?);
+        }
+        Ok(responses)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    documents: BTreeMap<String, Value>,
+    sets: BTreeMap<String, BTreeSet<String>>,
+    hashes: BTreeMap<String, BTreeMap<String, String>>,
+    idempotency: BTreeMap<String, Value>,
+    history: Vec<Value>,
+}
+
+/// The collection segment of an entity key, `{head}:{collection}:{entity_id}`.
+fn collection_of<'k>(layout: &KeyLayout, key: &'k str) -> &'k str {
+    layout.tail(key, 2).and_then(|parts| parts.first().copied()).unwrap_or_default()
+}
+
+/// `key` with `suffix` appended as one more segment.
+fn suffixed(layout: &KeyLayout, key: &str, suffix: &str) -> String {
+    format!("{key}{}{suffix}", layout.separator)
+}
+
+/// `{head}:rel:{alias}_reverse:{right_id}`, as the scripts build it.
+fn reverse_key(layout: &KeyLayout, alias: &str, right_id: &str) -> String {
+    layout.join(&["rel", &format!("{alias}_reverse"), right_id])
+}
+
+impl MemoryState {
+    fn apply(&mut self, command: &MutationCommand) -> Result<Value, RepoError> {
+        match command {
+            MutationCommand::UpsertEntity(mutation) => self.upsert_entity(mutation),
+            MutationCommand::PatchEntity(patch) => self.patch_entity(patch),
+            MutationCommand::DeleteEntity(delete) => self.delete_entity(delete),
+            MutationCommand::MutateRelations(relation) => {
+                self.apply_relation(relation);
+                Ok(json!({ "ok": true }))
+            }
+            MutationCommand::Upsert(_) => Err(unsupported("upsert")),
+            MutationCommand::GetOrCreate(_) => Err(unsupported("get_or_create")),
+        }
+    }
+
+    fn upsert_entity(&mut self, mutation: &EntityMutation) -> Result<Value, RepoError> {
+        let layout = &mutation.layout;
+        let idempotency_key = mutation
+            .idempotency_key
+            .as_ref()
+            .map(|key| layout.join(&["idempotency", key]));
+        if let Some(stored) = idempotency_key.as_ref().and_then(|key| self.idempotency.get(key)) {
+            let mut replay = stored.clone();
+            replay["replayed"] = Value::Bool(true);
+            return Ok(replay);
+        }
+
+        if mutation.if_absent && self.documents.contains_key(&mutation.key) {
+            return Err(RepoError::AlreadyExists {
+                entity_id: mutation.entity_id.clone(),
+            });
+        }
+        let stored_version = self.version(&mutation.key);
+        if let Some(expected) = mutation.expected_version
+            && stored_version != Some(expected)
+        {
+            return Err(RepoError::VersionConflict {
+                expected: Some(expected),
+                actual: stored_version,
+            });
+        }
+
+        let mut document: Value = serde_json::from_str(&mutation.payload_json).map_err(|err| RepoError::Other {
+            message: format!("failed to parse payload: {err}").into(),
+        })?;
+        let unique_head = (layout, collection_of(layout, &mutation.key));
+        let reservations = self.check_unique(unique_head, &mutation.unique_constraints, &mutation.entity_id)?;
+
+        let version = stored_version.unwrap_or(0) + 1;
+        set_version(&mut document, version);
+        for mirror in &mutation.datetime_mirrors {
+            apply_mirror(&mut document, mirror);
+        }
+        self.documents.insert(mutation.key.clone(), document);
+        for (unique_key, lookup) in reservations {
+            self.hset(&unique_key, lookup, mutation.entity_id.clone());
+        }
+        for relation in &mutation.relations {
+            self.apply_relation(relation);
+        }
+
+        let response = json!({
+            "ok": true,
+            "version": version,
+            "entity_id": mutation.entity_id,
+            "datetime_mirrors": mutation.datetime_mirrors,
+        });
+        if let Some(key) = idempotency_key {
+            self.idempotency.insert(key, response.clone());
+        }
+        Ok(response)
+    }
+
+    fn patch_entity(&mut self, patch: &EntityPatch) -> Result<Value, RepoError> {
+        let Some(current) = self.documents.get(&patch.key) else {
+            return Err(RepoError::NotFound {
+                entity_id: patch.entity_id.clone(),
+            });
+        };
+        if patch.operations.is_empty() && patch.relations.is_empty() && !patch.touch {
+            return Ok(json!({ "ok": true, "version": null, "entity_id": null }));
+        }
+
+        let layout = &patch.layout;
+        let collection = collection_of(layout, &patch.key);
+        let idempotency_keys = patch.idempotency_key.as_ref().map(|key| {
+            [
+                suffixed(layout, &suffixed(layout, &patch.key, "idempotency"), key),
+                layout.join(&[collection, "idempotency", key]),
+            ]
+        });
+        if let Some(stored) = idempotency_keys.iter().flatten().find_map(|key| self.idempotency.get(key)) {
+            return Ok(stored.clone());
+        }
+
+        let current_version = self.version(&patch.key);
+        if let Some(expected) = patch.expected_version
+            && current_version != Some(expected)
+        {
+            // A retried request whose first attempt already bumped the version is not a conflict
+            if let Some(keys) = idempotency_keys {
+                let response = json!({ "ok": true, "version": current_version, "entity_id": patch.entity_id });
+                for key in keys {
+                    self.idempotency.insert(key, response.clone());
+                }
+                return Ok(response);
+            }
+            return Err(RepoError::VersionConflict {
+                expected: Some(expected),
+                actual: current_version,
+            });
+        }
+
+        if let Some(precondition) = &patch.precondition
+            && !precondition.matches(current)
+        {
+            return Err(RepoError::PreconditionFailed {
+                entity_id: patch.entity_id.clone().unwrap_or_default(),
+            });
+        }
+
+        if let Some(operation) = patch.operations.iter().find(|op| patch.immutable_paths.contains(&op.path)) {
+            let field = operation.path.strip_prefix("$.").unwrap_or(&operation.path);
+            return Err(RepoError::Validation(ValidationError::single(
+                field,
+                "patch.immutable_field",
+                "field is immutable once created",
+            )));
+        }
+
+        let entity_id = patch.entity_id.clone().unwrap_or_default();
+        let mut document = current.clone();
+        let reservations =
+            self.check_unique_change((layout, collection), &patch.unique_constraints, &document, &entity_id)?;
+        for operation in &patch.operations {
+            let path = path_segments(&operation.path);
+            match operation.op_type {
+                PatchOperationType::Assign => match &operation.value {
+                    Some(Value::Null) | None if patch.hash => remove_path(&mut document, &path),
+                    value => set_path(&mut document, &path, value.clone().unwrap_or(Value::Null)),
+                },
+                PatchOperationType::Merge => {
+                    let patch_value = operation.value.clone().unwrap_or(Value::Null);
+                    merge_path(&mut document, &path, patch_value);
+                }
+                PatchOperationType::Delete => remove_path(&mut document, &path),
+                PatchOperationType::Increment => {
+                    let by = operation.value.clone().unwrap_or(Value::from(0));
+                    increment_path(&mut document, &path, &by);
+                }
+                PatchOperationType::ArrayAppend => {
+                    let elements = array_elements(operation.value.as_ref());
+                    match get_path_mut(&mut document, &path) {
+                        Some(Value::Array(items)) => items.extend(elements),
+                        _ => set_path(&mut document, &path, Value::Array(elements)),
+                    }
+                }
+                PatchOperationType::ArrayRemove => {
+                    let elements = array_elements(operation.value.as_ref());
+                    if let Some(Value::Array(items)) = get_path_mut(&mut document, &path) {
+                        items.retain(|item| !elements.contains(item));
+                    }
+                }
+            }
+            if let Some(mirror) = &operation.mirror {
+                apply_mirror(&mut document, mirror);
+            }
+        }
+        for operation in &patch.operations {
+            if let Some(bounds) = &operation.length {
+                let field = operation.path.strip_prefix("$.").unwrap_or(&operation.path);
+                let length = document.get(field).and_then(Value::as_array).map_or(0, Vec::len);
+                if let Some(err) = bounds.violation(field, length) {
+                    return Err(RepoError::Validation(err));
+                }
+            }
+        }
+
+        let version = current_version.map_or(1, |version| version + 1);
+        set_version(&mut document, version);
+        self.documents.insert(patch.key.clone(), document);
+        for relation in &patch.relations {
+            self.apply_relation(relation);
+        }
+        for (unique_key, old_lookup, new_lookup) in reservations {
+            if let Some(old_lookup) = old_lookup {
+                self.hdel(&unique_key, &old_lookup);
+            }
+            self.hset(&unique_key, new_lookup, entity_id.clone());
+        }
+
+        let response = json!({ "ok": true, "version": version, "entity_id": patch.entity_id });
+        for key in idempotency_keys.into_iter().flatten() {
+            self.idempotency.insert(key, response.clone());
+        }
+        Ok(response)
+    }
+
+    fn delete_entity(&mut self, delete: &EntityDelete) -> Result<Value, RepoError> {
+        let relations: Vec<_> = delete.relations.iter().map(CascadeTarget::from_relation).collect();
+        let parent_links: Vec<_> = match self.documents.get(&delete.key) {
+            Some(document) => delete
+                .parents
+                .iter()
+                .filter_map(|parent| {
+                    let parent_id = document.get(&parent.foreign_key)?.as_str().filter(|id| !id.is_empty())?;
+                    Some((parent, parent_id.to_string()))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut visited = BTreeSet::new();
+        self.delete_with_relations(
+            &delete.key,
+            &delete.layout,
+            delete.expected_version,
+            &relations,
+            &delete.unique_constraints,
+            &mut visited,
+        )?;
+
+        let entity_id = delete.layout.tail(&delete.key, 2).and_then(|parts| parts.last().cloned());
+        if let Some(entity_id) = entity_id {
+            for (parent, parent_id) in parent_links {
+                let layout = &parent.parent_layout;
+                let forward_key = layout.join(&["rel", &parent.alias, &parent_id]);
+                self.srem(&forward_key, entity_id);
+                self.hdel(&suffixed(layout, &forward_key, "edges"), entity_id);
+                if let Some(path) = &parent.counter_path {
+                    self.refresh_counter(&layout.join(&[&parent.parent_collection, &parent_id]), path, &forward_key);
+                }
+            }
+        }
+        Ok(json!({ "ok": true }))
+    }
+
+    fn delete_with_relations(
+        &mut self,
+        key: &str,
+        layout: &KeyLayout,
+        expected_version: Option<u64>,
+        relations: &[CascadeTarget<'_>],
+        unique_constraints: &[UniqueConstraintDefinition],
+        visited: &mut BTreeSet<String>,
+    ) -> Result<(), RepoError> {
+        // Guards against cycles in self-referential data
+        if !visited.insert(key.to_string()) {
+            return Ok(());
+        }
+        let stored_version = self.version(key);
+        if let Some(expected) = expected_version
+            && stored_version != Some(expected)
+        {
+            return Err(RepoError::VersionConflict {
+                expected: Some(expected),
+                actual: stored_version,
+            });
+        }
+
+        let collection = collection_of(layout, key);
+        if let Some(document) = self.documents.remove(key) {
+            for constraint in unique_constraints {
+                let values: Vec<_> = constraint
+                    .fields
+                    .iter()
+                    .map(|field| document.get(field).cloned().unwrap_or(Value::Null))
+                    .collect();
+                if let Some(lookup) = unique_lookup(&values, constraint.case_insensitive) {
+                    self.hdel(&unique_key((layout, collection), &constraint.fields), &lookup);
+                }
+            }
+        }
+
+        for relation in relations {
+            let relation_layout = relation.layout.unwrap_or(layout);
+            let (alias, left_id) = relation_alias_and_left(relation_layout, &relation.relation_key);
+            let reverse_key = |member: &str| reverse_key(relation_layout, alias, member);
+            let members = self.members(&relation.relation_key);
+
+            if matches!(relation.cascade, CascadeDirective::DeleteDependents)
+                && let Some(target_collection) = relation.target_collection
+            {
+                let target_layout = relation.target_layout;
+                for member in &members {
+                    let child_key = target_layout.join(&[target_collection, member]);
+                    let child_relations: Vec<_> = relation
+                        .child_relations
+                        .iter()
+                        .map(|spec| CascadeTarget::from_spec(spec, member))
+                        .collect();
+                    self.delete_with_relations(&child_key, target_layout, None, &child_relations, &[], visited)?;
+                }
+            }
+            if relation.maintain_reverse {
+                for member in &members {
+                    self.srem(&reverse_key(member), left_id);
+                }
+            }
+            self.sets.remove(&relation.relation_key);
+            self.hashes.remove(&suffixed(relation_layout, &relation.relation_key, "edges"));
+
+            if relation.maintain_reverse {
+                let reverse_self_key = reverse_key(left_id);
+                for parent in self.members(&reverse_self_key) {
+                    let forward_key = relation_layout.join(&["rel", alias, &parent]);
+                    self.srem(&forward_key, left_id);
+                    self.hdel(&suffixed(relation_layout, &forward_key, "edges"), left_id);
+                    if let Some(path) = relation.counter_path {
+                        self.refresh_counter(&layout.join(&[collection, &parent]), path, &forward_key);
+                    }
+                }
+                self.sets.remove(&reverse_self_key);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_relation(&mut self, relation: &RelationMutation) {
+        let layout = &relation.layout;
+        let edges_key = suffixed(layout, &relation.relation_key, "edges");
+        for edge in &relation.edges {
+            self.hset(&edges_key, edge.member_id.clone(), edge.data_json.clone());
+        }
+        for member in &relation.remove {
+            self.hdel(&edges_key, member);
+        }
+
+        let (alias, left_id) = relation_alias_and_left(layout, &relation.relation_key);
+        let reverse_key = |member: &str| reverse_key(layout, alias, member);
+        for member in &relation.add {
+            self.sets.entry(relation.relation_key.clone()).or_default().insert(member.clone());
+            if relation.maintain_reverse {
+                self.sets.entry(reverse_key(member)).or_default().insert(left_id.to_string());
+            }
+        }
+        for member in &relation.remove {
+            self.srem(&relation.relation_key, member);
+            if relation.maintain_reverse {
+                self.srem(&reverse_key(member), left_id);
+            }
+        }
+
+        if let Some(counter) = &relation.counter {
+            self.refresh_counter(&counter.entity_key, &counter.path, &relation.relation_key);
+        }
+    }
+
+    /// Set the counter at `path` of an existing document to the size of the relation set.
+    fn refresh_counter(&mut self, entity_key: &str, path: &str, relation_key: &str) {
+        let count = self.sets.get(relation_key).map_or(0, BTreeSet::len);
+        if let Some(document) = self.documents.get_mut(entity_key) {
+            set_path(document, &path_segments(path), Value::from(count));
+        }
+    }
+
+    /// Check constraints for a whole new document, returning the `(unique key, lookup)`
+    /// pairs to reserve once it is written.
+    fn check_unique(
+        &self,
+        unique_head: (&KeyLayout, &str),
+        constraints: &[UniqueConstraintCheck],
+        entity_id: &str,
+    ) -> Result<Vec<(String, String)>, RepoError> {
+        let mut reservations = Vec::new();
+        for constraint in constraints {
+            let Some(lookup) = unique_lookup(&constraint.values, constraint.case_insensitive) else {
+                continue;
+            };
+            let unique_key = unique_key(unique_head, &constraint.fields);
+            self.ensure_unique_free(&unique_key, &lookup, entity_id, constraint, &constraint.values)?;
+            reservations.push((unique_key, lookup));
+        }
+        Ok(reservations)
+    }
+
+    /// Check constraints touched by a patch, where missing values fall back to the current
+    /// document. Returns `(unique key, old lookup, new lookup)` for each changed value.
+    fn check_unique_change(
+        &self,
+        unique_head: (&KeyLayout, &str),
+        constraints: &[UniqueConstraintCheck],
+        document: &Value,
+        entity_id: &str,
+    ) -> Result<Vec<(String, Option<String>, String)>, RepoError> {
+        let mut changes = Vec::new();
+        for constraint in constraints {
+            let current: Vec<_> = constraint
+                .fields
+                .iter()
+                .map(|field| document.get(field).cloned().unwrap_or(Value::Null))
+                .collect();
+            let next: Vec<_> = constraint
+                .values
+                .iter()
+                .zip(&current)
+                .map(|(value, current)| if value.is_null() { current.clone() } else { value.clone() })
+                .collect();
+            let Some(new_lookup) = unique_lookup(&next, constraint.case_insensitive) else {
+                continue;
+            };
+            let old_lookup = unique_lookup(&current, constraint.case_insensitive);
+            if old_lookup.as_deref() == Some(new_lookup.as_str()) {
+                continue;
+            }
+            let unique_key = unique_key(unique_head, &constraint.fields);
+            self.ensure_unique_free(&unique_key, &new_lookup, entity_id, constraint, &next)?;
+            changes.push((unique_key, old_lookup, new_lookup));
+        }
+        Ok(changes)
+    }
+
+    fn ensure_unique_free(
+        &self,
+        unique_key: &str,
+        lookup: &str,
+        entity_id: &str,
+        constraint: &UniqueConstraintCheck,
+        values: &[Value],
+    ) -> Result<(), RepoError> {
+        match self.hashes.get(unique_key).and_then(|hash| hash.get(lookup)) {
+            Some(existing) if existing != entity_id => Err(RepoError::UniqueConstraintViolation {
+                fields: constraint.fields.clone(),
+                values: values
+                    .iter()
+                    .map(|value| match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect(),
+                existing_entity_id: existing.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn version(&self, key: &str) -> Option<u64> {
+        self.documents.get(key)?.get("metadata")?.get("version")?.as_u64()
+    }
+
+    fn members(&self, key: &str) -> Vec<String> {
+        self.sets.get(key).map(|members| members.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Remove a set member, dropping the set once empty as Redis does.
+    fn srem(&mut self, key: &str, member: &str) {
+        if let Some(members) = self.sets.get_mut(key) {
+            members.remove(member);
+            if members.is_empty() {
+                self.sets.remove(key);
+            }
+        }
+    }
+
+    fn hset(&mut self, key: &str, field: String, value: String) {
+        self.hashes.entry(key.to_string()).or_default().insert(field, value);
+    }
+
+    /// Remove a hash field, dropping the hash once empty as Redis does.
+    fn hdel(&mut self, key: &str, field: &str) {
+        if let Some(hash) = self.hashes.get_mut(key) {
+            hash.remove(field);
+            if hash.is_empty() {
+                self.hashes.remove(key);
+            }
+        }
+    }
+}
+
+/// A cascade to follow on delete, either from the command or derived from a child spec.
+struct CascadeTarget<'a> {
+    relation_key: String,
+    /// Layout of `relation_key`, when it differs from the deleted entity's
+    layout: Option<&'a KeyLayout>,
+    target_collection: Option<&'a str>,
+    target_layout: &'a KeyLayout,
+    cascade: &'a CascadeDirective,
+    maintain_reverse: bool,
+    child_relations: &'a [CascadeRelationSpec],
+    /// Counter path refreshed on entities whose set loses the deleted one
+    counter_path: Option<&'a str>,
+}
+
+impl<'a> CascadeTarget<'a> {
+    fn from_relation(relation: &'a DeleteCascadeRelation) -> Self {
+        Self {
+            relation_key: relation.relation_key.clone(),
+            layout: None,
+            target_collection: relation.target_collection.as_deref(),
+            target_layout: &relation.target_layout,
+            cascade: &relation.cascade,
+            maintain_reverse: relation.maintain_reverse,
+            child_relations: &relation.child_relations,
+            counter_path: relation.counter_path.as_deref(),
+        }
+    }
+
+    fn from_spec(spec: &'a CascadeRelationSpec, entity_id: &str) -> Self {
+        Self {
+            relation_key: spec.target_layout.join(&["rel", &spec.alias, entity_id]),
+            layout: Some(&spec.target_layout),
+            target_collection: spec.target_collection.as_deref(),
+            target_layout: &spec.target_layout,
+            cascade: &spec.cascade,
+            maintain_reverse: spec.maintain_reverse,
+            child_relations: &spec.child_relations,
+            counter_path: None,
+        }
+    }
+}
+
+/// Alias and left id of a `{head}:rel:{alias}:{left_id}` key.
+fn relation_alias_and_left<'k>(layout: &KeyLayout, relation_key: &'k str) -> (&'k str, &'k str) {
+    match layout.tail(relation_key, 3).as_deref() {
+        Some([_, alias, left_id]) => (alias, left_id),
+        _ => ("", ""),
+    }
+}
+
+/// `{head}:{collection}:unique:{field}`, or `unique_compound` over several fields.
+fn unique_key((layout, collection): (&KeyLayout, &str), fields: &[String]) -> String {
+    match fields {
+        [field] => layout.join(&[collection, "unique", field]),
+        _ => layout.join(&[collection, "unique_compound", &fields.join("_")]),
+    }
+}
+
+/// The unique index entry for `values`, or `None` when any of them is null.
+fn unique_lookup(values: &[Value], case_insensitive: bool) -> Option<String> {
+    let mut parts = Vec::with_capacity(values.len());
+    for value in values {
+        let part = match value {
+            Value::Null => return None,
+            Value::String(s) if case_insensitive => s.to_lowercase(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        parts.push(part);
+    }
+    Some(parts.join(":"))
+}
+
+fn set_version(document: &mut Value, version: u64) {
+    set_path(document, &["metadata", "version"], Value::from(version));
+}
+
+fn apply_mirror(document: &mut Value, mirror: &DatetimeMirrorValue) {
+    match mirror.value {
+        Some(value) => set_path(document, &[mirror.mirror_field.as_str()], Value::from(value)),
+        None => remove_path(document, &[mirror.mirror_field.as_str()]),
+    }
+}
+
+fn array_elements(value: Option<&Value>) -> Vec<Value> {
+    match value {
+        Some(Value::Array(elements)) => elements.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Split a `$.a.b` JSONPath into its object keys.
+fn path_segments(path: &str) -> Vec<&str> {
+    path.trim_start_matches('$').split('.').filter(|segment| !segment.is_empty()).collect()
+}
+
+fn get_path<'a>(document: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(document, |value, segment| value.get(segment))
+}
+
+fn get_path_mut<'a>(document: &'a mut Value, path: &[&str]) -> Option<&'a mut Value> {
+    path.iter().try_fold(document, |value, segment| value.get_mut(segment))
+}
+
+/// Set the value at `path`, creating intermediate objects as needed.
+fn set_path(document: &mut Value, path: &[&str], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        *document = value;
+        return;
+    };
+    let mut target = document;
+    for segment in parents {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .expect("just ensured an object")
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    target
+        .as_object_mut()
+        .expect("just ensured an object")
+        .insert(last.to_string(), value);
+}
+
+fn remove_path(document: &mut Value, path: &[&str]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    if let Some(Value::Object(object)) = get_path_mut(document, parents) {
+        object.remove(*last);
+    }
+}
+
+/// Apply an RFC 7396 merge patch at `path`, as `JSON.MERGE` does.
+fn merge_path(document: &mut Value, path: &[&str], patch: Value) {
+    match get_path_mut(document, path) {
+        Some(target) => merge_value(target, patch),
+        None if !patch.is_null() => set_path(document, path, patch),
+        None => {}
+    }
+}
+
+fn merge_value(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let object = target.as_object_mut().expect("just ensured an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            object.remove(&key);
+        } else {
+            merge_value(object.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn increment_path(document: &mut Value, path: &[&str], by: &Value) {
+    let current = get_path(document, path).cloned().unwrap_or(Value::from(0));
+    let next = match (current.as_i64(), by.as_i64()) {
+        (Some(current), Some(by)) => Value::from(current + by),
+        _ => Value::from(current.as_f64().unwrap_or(0.0) + by.as_f64().unwrap_or(0.0)),
+    };
+    set_path(document, path, next);
+}
+
+fn unsupported(what: &str) -> RepoError {
+    RepoError::InvalidRequest {
+        message: format!("{what} is not supported by the in-memory backend"),
+    }
+}
+
+fn deserialize_document<U>(descriptor: &EntityDescriptor, mut document: Value) -> Result<U, RepoError>
+where
+    U: DeserializeOwned,
+{
+    storage::decode_fields(descriptor, &mut document)?;
+    serde_json::from_value(document).map_err(|err| RepoError::Other {
+        message: format!("failed to deserialize entity: {err}").into(),
+    })
+}
+
+fn search_documents<T>(
+    documents: &BTreeMap<String, Value>,
+    definition: &IndexDefinition,
+    descriptor: &EntityDescriptor,
+    params: &SearchParams,
+) -> Result<SearchResult<T>, RepoError>
+where
+    T: DeserializeOwned,
+{
+    if params.raw.as_deref().is_some_and(|raw| !raw.is_empty()) {
+        return Err(unsupported("raw search clauses"));
+    }
+    let text_clauses = match params.text_query.as_deref() {
+        Some(query) if !query.is_empty() => parse_text_query(query)?,
+        _ => Vec::new(),
+    };
+
+    let mut matches = Vec::new();
+    for (key, document) in documents {
+        if !definition.prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())) {
+            continue;
+        }
+        if !params.in_keys.is_empty() && !params.in_keys.contains(key) {
+            continue;
+        }
+        let mut matched = true;
+        for condition in &params.conditions {
+            if !condition_matches(condition, document, definition)? {
+                matched = false;
+                break;
+            }
+        }
+        if matched && !text_clauses.is_empty() {
+            matched = text_clauses.iter().any(|(field, tokens)| {
+                let words = field_words(document, definition, field);
+                tokens.iter().all(|(token, prefix)| {
+                    words.iter().any(|word| if *prefix { word.starts_with(token.as_str()) } else { word == token })
+                })
+            });
+        }
+        if matched {
+            matches.push(document);
+        }
+    }
+
+    if let Some(sort) = &params.sort {
+        let path = path_segments(field_path(definition, &sort.field)?);
+        matches.sort_by(|a, b| {
+            let ordering = compare_sort_values(get_path(a, &path), get_path(b, &path));
+            match (sort.order, get_path(a, &path).is_some(), get_path(b, &path).is_some()) {
+                // Documents without the field come last in either direction
+                (_, true, false) | (_, false, true) => ordering,
+                (SortOrder::Desc, _, _) => ordering.reverse(),
+                (SortOrder::Asc, _, _) => ordering,
+            }
+        });
+    }
+
+    let total = matches.len() as u64;
+    let items = matches
+        .into_iter()
+        .skip(params.offset() as usize)
+        .take(params.page_size as usize)
+        .map(|document| {
+            let mut document = if params.return_fields.is_empty() {
+                document.clone()
+            } else {
+                let object = document.as_object().into_iter().flatten();
+                Value::Object(
+                    object
+                        .filter(|(field, _)| params.return_fields.contains(field))
+                        .map(|(field, value)| (field.clone(), value.clone()))
+                        .collect(),
+                )
+            };
+            crate::search::strip_redacted(descriptor, &mut document);
+            deserialize_document(descriptor, document)
+        })
+        .collect::<Result<Vec<T>, _>>()?;
+
+    // Hits aren't ranked here, so every one scores the same
+    let scores = if params.scores { vec![1.0; items.len()] } else { Vec::new() };
+    Ok(SearchResult {
+        items,
+        total,
+        page: params.page,
+        page_size: params.page_size,
+        scores,
+    })
+}
+
+/// A text field and the words it must contain, each flagged when it matches as a prefix.
+type TextClause = (String, Vec<(String, bool)>);
+
+/// Parse the `(@field:(token* ...) | ...)` form built by `build_text_query` into
+/// `(field, [(word, is_prefix)])` clauses, splitting tokens into words as the index would.
+fn parse_text_query(query: &str) -> Result<Vec<TextClause>, RepoError> {
+    let clauses: Vec<_> = TEXT_QUERY_CLAUSE
+        .captures_iter(query)
+        .map(|captures| {
+            let tokens = captures[2]
+                .split_whitespace()
+                .flat_map(|token| {
+                    let prefix = token.ends_with('*') && !token.ends_with("\\*");
+                    let token = token.strip_suffix('*').filter(|_| prefix).unwrap_or(token);
+                    words(&token.replace('\\', "")).into_iter().map(move |word| (word, prefix))
+                })
+                .collect();
+            (captures[1].to_string(), tokens)
+        })
+        .collect();
+    if clauses.is_empty() {
+        return Err(unsupported("this text query"));
+    }
+    Ok(clauses)
+}
+
+fn index_field<'a>(definition: &'a IndexDefinition, field: &str) -> Result<&'a IndexField, RepoError> {
+    definition
+        .schema
+        .iter()
+        .find(|candidate| candidate.field_name == field)
+        .ok_or_else(|| RepoError::InvalidRequest {
+            message: format!("Unknown index field: {field}"),
+        })
+}
+
+fn field_path<'a>(definition: &'a IndexDefinition, field: &str) -> Result<&'a str, RepoError> {
+    index_field(definition, field).map(|field| field.path)
+}
+
+/// Scalar values at the field's path; arrays contribute each element.
+fn field_values<'a>(document: &'a Value, path: &str) -> Vec<&'a Value> {
+    match get_path(document, &path_segments(path)) {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(value) => vec![value],
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Lowercase words of a text field, split on anything that is not alphanumeric.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn field_words(document: &Value, definition: &IndexDefinition, field: &str) -> Vec<String> {
+    let Ok(path) = field_path(definition, field) else {
+        return Vec::new();
+    };
+    field_values(document, path)
+        .into_iter()
+        .flat_map(|value| words(&value_text(value)))
+        .collect()
+}
+
+fn condition_matches(condition: &FilterCondition, document: &Value, definition: &IndexDefinition) -> Result<bool, RepoError> {
+    let texts = |field: &str| -> Result<Vec<String>, RepoError> {
+        let path = field_path(definition, field)?;
+        Ok(field_values(document, path).into_iter().map(value_text).collect())
+    };
+    Ok(match condition {
+        FilterCondition::TagEquals { field, values } => texts(field)?
+            .iter()
+            .any(|stored| values.iter().any(|value| stored.eq_ignore_ascii_case(value))),
+        FilterCondition::BooleanEquals { field, value } => {
+            let expected = value.to_string();
+            texts(field)?.iter().any(|stored| stored.eq_ignore_ascii_case(&expected))
+        }
+        FilterCondition::NumericRange {
+            field,
+            min,
+            max,
+            min_exclusive,
+            max_exclusive,
+        } => {
+            let path = field_path(definition, field)?;
+            field_values(document, path).into_iter().any(|value| {
+                let number = value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse().ok()));
+                number.is_some_and(|number| {
+                    min.is_none_or(|min| if *min_exclusive { number > min } else { number >= min })
+                        && max.is_none_or(|max| if *max_exclusive { number < max } else { number <= max })
+                })
+            })
+        }
+        FilterCondition::TextPrefix { field, value } => {
+            let stored = field_words(document, definition, field);
+            words(value).iter().all(|token| stored.iter().any(|word| word.starts_with(token.as_str())))
+        }
+        FilterCondition::TextContains { field, value } => {
+            let needle = value.to_lowercase();
+            texts(field)?.iter().any(|stored| stored.to_lowercase().contains(&needle))
+        }
+        FilterCondition::TextExact { field, value } => {
+            let phrase = words(value);
+            let stored = field_words(document, definition, field);
+            !phrase.is_empty() && stored.windows(phrase.len()).any(|window| window == phrase.as_slice())
+        }
+        FilterCondition::TextFuzzy { field, value } => {
+            let stored = field_words(document, definition, field);
+            words(value)
+                .iter()
+                .all(|token| stored.iter().any(|word| edit_distance(word, token) <= 1))
+        }
+        FilterCondition::And(conditions) => {
+            for condition in conditions {
+                if !condition_matches(condition, document, definition)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        FilterCondition::Or(conditions) => {
+            if conditions.is_empty() {
+                return Ok(true);
+            }
+            for condition in conditions {
+                if condition_matches(condition, document, definition)? {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+        FilterCondition::IsMissing { field } => {
+            let path = field_path(definition, field)?;
+            matches!(get_path(document, &path_segments(path)), None | Some(Value::Null))
+        }
+        FilterCondition::Not(condition) => !condition_matches(condition, document, definition)?,
+    })
+}
+
+fn compare_sort_values(a: Option<&Value>, b: Option<&Value>) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a, b) {
+        (Some(a), Some(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => value_text(a).to_lowercase().cmp(&value_text(b).to_lowercase()),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Levenshtein distance, for RediSearch's `%term%` fuzzy matching.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bench::{BenchOrder, order_builder},
+        search::{SearchSort, build_text_query},
+    };
+
+    fn params() -> SearchParams {
+        SearchParams::new()
+    }
+
+    #[tokio::test]
+    async fn applies_creates_patches_and_deletes() {
+        let repo = Repo::<BenchOrder>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let created = repo.create(&mut store, order_builder(1)).await.expect("create");
+        let stored = store.get(&repo, &created.id).expect("get").expect("order exists");
+        assert_eq!(stored.total_cents, 5_000);
+        let key = repo.entity_key(&created.id);
+        assert_eq!(store.document(&key).unwrap()["metadata"]["version"], 1);
+
+        let patch = crate::snug! {
+            BenchOrder(entity_id = created.id.clone()) {
+                status: "refunded".to_string(),
+            }
+        };
+        repo.update_patch(&mut store, patch).await.expect("patch");
+        let stored = store.get(&repo, &created.id).expect("get").expect("order exists");
+        assert_eq!(stored.status, "refunded");
+        assert_eq!(store.document(&key).unwrap()["metadata"]["version"], 2);
+
+        let err = repo.delete(&mut store, &created.id, Some(1)).await.expect_err("stale version");
+        assert!(matches!(err, RepoError::VersionConflict { expected: Some(1), actual: Some(2) }));
+        repo.delete(&mut store, &created.id, Some(2)).await.expect("delete");
+        assert!(store.get(&repo, &created.id).expect("get").is_none());
+        assert_eq!(store.history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn touch_bumps_only_the_version() {
+        let repo = Repo::<BenchOrder>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let created = repo.create(&mut store, order_builder(1)).await.expect("create");
+        let key = repo.entity_key(&created.id);
+        let before = store.document(&key).unwrap().clone();
+
+        repo.touch(&mut store, &created.id).await.expect("touch");
+        let mut after = store.document(&key).unwrap().clone();
+        assert_eq!(after["metadata"]["version"], 2);
+        after["metadata"]["version"] = before["metadata"]["version"].clone();
+        assert_eq!(after, before);
+
+        let err = repo.touch(&mut store, "missing").await.expect_err("no such order");
+        assert!(matches!(err, RepoError::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn interceptor_sees_each_mutation_script() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::interceptor::{CommandEvent, SharedInterceptor};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = events.clone();
+        let interceptor = SharedInterceptor::new(move |event: &CommandEvent<'_>| {
+            seen.lock().unwrap().push((event.operation, event.target.to_string(), event.outcome.is_ok()));
+        });
+        let repo = Repo::<BenchOrder>::new("mem").with_interceptor(interceptor);
+        let mut store = MemoryExecutor::new();
+        let created = repo.create(&mut store, order_builder(1)).await.expect("create");
+        repo.touch(&mut store, &created.id).await.expect("touch");
+        repo.touch(&mut store, "missing").await.expect_err("no such order");
+
+        let key = repo.entity_key(&created.id);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("entity_mutation", key.clone(), true),
+                ("entity_patch", key, true),
+                ("entity_patch", repo.entity_key("missing"), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_strict_creates_of_existing_entities() {
+        let repo = Repo::<BenchOrder>::new("mem");
+        let mut store = MemoryExecutor::new();
+        repo.create(&mut store, order_builder(1)).await.expect("create");
+        let err = repo.create_if_absent(&mut store, order_builder(1)).await.expect_err("duplicate");
+        assert!(matches!(err, RepoError::AlreadyExists { entity_id } if entity_id == "order-1"));
+    }
+
+    #[tokio::test]
+    async fn rejects_nested_creates_if_absent() {
+        let repo = Repo::<Shelf>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let builder = crate::snug! {
+            Shelf {
+                books: [create Book { shelf_id: String::new() }],
+            }
+        };
+        let err = repo.create_if_absent(&mut store, builder).await.expect_err("nested create");
+        assert!(matches!(err, RepoError::InvalidRequest { .. }));
+        assert!(store.keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn searches_stored_documents() {
+        let repo = Repo::<BenchOrder>::new("mem");
+        let mut store = MemoryExecutor::new();
+        for n in 0..5 {
+            repo.create(&mut store, order_builder(n)).await.expect("create");
+        }
+
+        let gifts = store
+            .search(&repo, &params().with_condition(FilterCondition::bool_eq("gift", true)))
+            .expect("search");
+        assert_eq!(gifts.total, 3);
+        let others = store
+            .search(&repo, &params().with_condition(FilterCondition::negate(FilterCondition::bool_eq("gift", true))))
+            .expect("search");
+        assert_eq!(others.total, 2);
+
+        let patch = crate::snug! { BenchOrder(entity_id = "order-4".to_string()) { shipped_at: None } };
+        repo.update_patch(&mut store, patch).await.expect("patch");
+        let unshipped = store
+            .search(&repo, &params().with_condition(FilterCondition::is_missing("shipped_at_ts")))
+            .expect("search");
+        assert_eq!(unshipped.items.iter().map(|order| order.id.as_str()).collect::<Vec<_>>(), ["order-4"]);
+        let shipped = store
+            .search(&repo, &params().with_condition(FilterCondition::is_present("shipped_at_ts")))
+            .expect("search");
+        assert_eq!(shipped.total, 4);
+
+        let ranged = store
+            .search(
+                &repo,
+                &params()
+                    .with_condition(FilterCondition::numeric_range("total_cents", Some(5_000.0), Some(5_002.0)))
+                    .with_condition(FilterCondition::tag_in("tags", ["BATCH-1", "batch-2"]))
+                    .with_sort(Some(SearchSort {
+                        field: "total_cents".to_string(),
+                        order: SortOrder::Desc,
+                    })),
+            )
+            .expect("search");
+        let ids: Vec<_> = ranged.items.iter().map(|order| order.id.as_str()).collect();
+        assert_eq!(ids, ["order-2", "order-1"]);
+
+        let text = store
+            .search(&repo, &params().with_condition(FilterCondition::text_prefix("title", "wire")).with_page(2, 2))
+            .expect("search");
+        assert_eq!((text.total, text.items.len()), (5, 2));
+
+        let text_query = build_text_query(Some("headphones #3".to_string()), &["title"]).unwrap();
+        let found = store.search(&repo, &params().with_text_query(text_query)).expect("search");
+        assert_eq!(found.items.iter().map(|order| order.id.as_str()).collect::<Vec<_>>(), ["order-3"]);
+
+        let err = store.search(&repo, &params().with_raw("@status:{paid}")).expect_err("raw");
+        assert!(matches!(err, RepoError::InvalidRequest { .. }));
+    }
+
+    #[tokio::test]
+    async fn checks_patch_preconditions() {
+        use crate::{runtime::commands::Precondition, search::SearchEntity};
+
+        let repo = Repo::<BenchOrder>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let created = repo.create(&mut store, order_builder(3)).await.expect("create");
+        let definition = BenchOrder::index_definition("mem");
+        let precondition = |condition| Precondition::from_filter(&definition, &condition).expect("resolves");
+        let refund = |precondition| {
+            let mut command = crate::runtime::commands::build_entity_patch(
+                repo.entity_key(&created.id),
+                repo.key_context().layout(),
+                Some(created.id.clone()),
+                None,
+                vec![crate::repository::PatchOperation {
+                    path: "$.status".to_string(),
+                    kind: crate::repository::PatchOpKind::Assign(json!("refunded")),
+                    mirror: None,
+                }],
+                None,
+                None,
+                Vec::new(),
+                Vec::new(),
+            );
+            command.precondition = Some(precondition);
+            let mut plan = MutationPlan::new();
+            plan.push(MutationCommand::PatchEntity(command));
+            plan
+        };
+
+        let pending = precondition(FilterCondition::tag_eq("status", "pending"));
+        let err = store.execute(refund(pending)).await.expect_err("status is paid");
+        assert!(matches!(err, RepoError::PreconditionFailed { entity_id } if entity_id == "order-3"));
+
+        let paid_and_large = precondition(FilterCondition::And(vec![
+            FilterCondition::tag_eq("status", "PAID"),
+            FilterCondition::numeric_gt("total_cents", 5_000.0),
+            FilterCondition::tag_in("tags", ["audio"]),
+            FilterCondition::negate(FilterCondition::bool_eq("gift", true)),
+        ]));
+        store.execute(refund(paid_and_large.clone())).await.expect("matches");
+        assert_eq!(store.get(&repo, &created.id).expect("get").expect("order exists").status, "refunded");
+        assert!(!paid_and_large.matches(&store.document(&repo.entity_key(&created.id)).unwrap()));
+
+        let above = |min_exclusive| {
+            precondition(FilterCondition::NumericRange {
+                field: "total_cents".to_string(),
+                min: Some(5_000.0),
+                max: None,
+                min_exclusive,
+                max_exclusive: false,
+            })
+        };
+        assert!(above(false).matches(&json!({"total_cents": 5_000})));
+        assert!(!above(true).matches(&json!({"total_cents": 5_000})));
+        assert!(above(true).matches(&json!({"total_cents": 5_001})));
+
+        let title = precondition(FilterCondition::text_prefix("title", "wireless HEAD"));
+        assert!(title.matches(&json!({"title": "Wireless headphones #3"})));
+        assert!(!title.matches(&json!({"title": "Wired headphones"})));
+        let err = Precondition::from_filter(&definition, &FilterCondition::text_fuzzy("title", "wirless"));
+        assert!(matches!(err, Err(RepoError::InvalidRequest { .. })));
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "posts")]
+    struct SluggedPost {
+        #[snugom(id)]
+        id: String,
+        title: String,
+        #[snugom(slug(from = "title", suffix))]
+        slug: String,
+    }
+
+    #[tokio::test]
+    async fn suffixes_colliding_slugs() {
+        let repo = Repo::<SluggedPost>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let mut slugs = Vec::new();
+        for _ in 0..3 {
+            let builder = SluggedPost::validation_builder().title("Hello, World!".to_string());
+            let created = repo.create(&mut store, builder).await.expect("create");
+            slugs.push(store.get(&repo, &created.id).expect("get").expect("post exists").slug);
+        }
+        assert_eq!(slugs, ["hello-world", "hello-world-2", "hello-world-3"]);
+    }
+
+    static CREATED_CONTACTS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "contacts")]
+    #[snugom(before_create = "normalize_phone", after_create = "count_contact")]
+    struct Contact {
+        #[snugom(id)]
+        id: String,
+        #[snugom(validate(length(min = 10)))]
+        phone: String,
+    }
+
+    fn normalize_phone(contact: &mut Contact) {
+        contact.phone.retain(|ch| ch.is_ascii_digit());
+    }
+
+    fn count_contact(_: &Contact) {
+        CREATED_CONTACTS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    #[tokio::test]
+    async fn runs_create_hooks_around_validation() {
+        let repo = Repo::<Contact>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let builder = Contact::validation_builder().phone("(555) 010-0199".to_string());
+        let created = repo.create(&mut store, builder).await.expect("create");
+        let stored = store.get(&repo, &created.id).expect("get").expect("contact exists");
+        assert_eq!(stored.phone, "5550100199");
+        assert_eq!(CREATED_CONTACTS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // The hook's output is what gets validated
+        let builder = Contact::validation_builder().phone("555-01-99 ext".to_string());
+        let err = repo.create(&mut store, builder).await.expect_err("too short once normalized");
+        assert!(matches!(err, RepoError::Validation(err) if err.issues[0].field == "phone"));
+        assert_eq!(CREATED_CONTACTS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "badges", before_create = "shout_label")]
+    #[serde(deny_unknown_fields)]
+    struct Badge {
+        #[snugom(id)]
+        id: String,
+        label: String,
+    }
+
+    fn shout_label(badge: &mut Badge) {
+        badge.label = badge.label.to_uppercase();
+    }
+
+    #[test]
+    fn before_hooks_read_documents_with_metadata_and_reject_unreadable_ones() {
+        let hook = Repo::<Badge>::new("mem").descriptor().hooks.before_create.expect("before_create hook");
+        let mut document = json!({ "id": "b1", "label": "gold", "metadata": {} });
+        hook(&mut document).expect("metadata is snugom's, not an unknown field");
+        assert_eq!(document["label"], "GOLD");
+
+        let mut document = json!({ "id": "b1", "label": 7 });
+        let err = hook(&mut document).expect_err("label is not a string");
+        assert_eq!(err.issues[0].code, "deserialization.failed");
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "accounts")]
+    struct Account {
+        #[snugom(id)]
+        id: String,
+        #[snugom(immutable)]
+        owner_id: String,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn rejects_patches_to_immutable_fields() {
+        let repo = Repo::<Account>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let builder = Account::validation_builder().owner_id("u1".to_string()).name("Ada".to_string());
+        let created = repo.create(&mut store, builder).await.expect("create");
+
+        let patch = crate::snug! { Account(entity_id = created.id.clone()) { owner_id: "u2".to_string() } };
+        let err = repo.update_patch(&mut store, patch).await.expect_err("immutable field");
+        assert!(matches!(err, RepoError::Validation(err) if err.issues[0].code == "patch.immutable_field"));
+
+        // The executor enforces it too, for plans built without the repository's checks
+        let mut command = crate::runtime::commands::build_entity_patch(
+            repo.entity_key(&created.id),
+            repo.key_context().layout(),
+            Some(created.id.clone()),
+            None,
+            vec![crate::repository::PatchOperation {
+                path: "$.owner_id".to_string(),
+                kind: crate::repository::PatchOpKind::Assign(json!("u2")),
+                mirror: None,
+            }],
+            None,
+            None,
+            Vec::new(),
+            Vec::new(),
+        );
+        command.immutable_paths = vec!["$.owner_id".to_string()];
+        let mut plan = MutationPlan::new();
+        plan.push(MutationCommand::PatchEntity(command));
+        let err = store.execute(plan).await.expect_err("immutable field");
+        assert!(matches!(err, RepoError::Validation(err) if err.issues[0].field == "owner_id"));
+
+        let patch = crate::snug! { Account(entity_id = created.id.clone()) { name: "Grace".to_string() } };
+        repo.update_patch(&mut store, patch).await.expect("mutable field");
+        let stored = store.get(&repo, &created.id).expect("get").expect("account exists");
+        assert_eq!((stored.owner_id.as_str(), stored.name.as_str()), ("u1", "Grace"));
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "logins")]
+    struct Login {
+        #[snugom(id)]
+        id: String,
+        #[snugom(filterable(tag))]
+        email: String,
+        #[serde(default)]
+        #[snugom(redact)]
+        password_hash: String,
+    }
+
+    #[tokio::test]
+    async fn strips_redacted_fields_from_search_hits() {
+        let repo = Repo::<Login>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let builder = Login::validation_builder()
+            .email("ada@example.com".to_string())
+            .password_hash("argon2$secret".to_string());
+        let created = repo.create(&mut store, builder).await.expect("create");
+
+        let hits = store.search(&repo, &params()).expect("search").items;
+        assert_eq!(hits[0].email, "ada@example.com");
+        assert!(hits[0].password_hash.is_empty());
+        let stored = store.get(&repo, &created.id).expect("get").expect("login exists");
+        assert_eq!(stored.password_hash, "argon2$secret");
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum Severity {
+        Low,
+        High,
+        Critical,
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "incidents")]
+    struct Incident {
+        #[snugom(id)]
+        id: String,
+        #[snugom(filterable(tag), sortable, enum_order = ["low", "high", "critical"])]
+        severity: Severity,
+    }
+
+    #[tokio::test]
+    async fn sorts_ranked_enums_by_their_order() {
+        let repo = Repo::<Incident>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let mut ids = Vec::new();
+        for severity in [Severity::High, Severity::Critical, Severity::Low] {
+            let created = repo.create(&mut store, Incident::validation_builder().severity(severity)).await.expect("create");
+            ids.push(created.id);
+        }
+        let sorted = |store: &MemoryExecutor| {
+            let sort = SearchSort {
+                field: "__severity_ord".to_string(),
+                order: SortOrder::Asc,
+            };
+            let hits = store.search(&repo, &params().with_sort(Some(sort))).expect("search").items;
+            hits.into_iter().map(|incident| incident.severity).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(&store), [Severity::Low, Severity::High, Severity::Critical]);
+
+        // Patches keep the rank in step, and filtering still goes through the tag
+        let patch = crate::snug! { Incident(entity_id = ids[1].clone()) { severity: Severity::Low } };
+        repo.update_patch(&mut store, patch).await.expect("patch");
+        assert_eq!(sorted(&store), [Severity::Low, Severity::Low, Severity::High]);
+        let high = store
+            .search(&repo, &params().with_condition(FilterCondition::tag_eq("__severity_tag", "high")))
+            .expect("search");
+        assert_eq!(high.total, 1);
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "shelves")]
+    struct Shelf {
+        #[snugom(id)]
+        id: String,
+        #[serde(default)]
+        book_count: u64,
+        #[serde(default)]
+        #[snugom(relation(has_many, target = "books", counter = "book_count"))]
+        books: Vec<String>,
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "books")]
+    struct Book {
+        #[snugom(id)]
+        id: String,
+        #[snugom(relation(target = "shelves"))]
+        shelf_id: String,
+    }
+
+    #[tokio::test]
+    async fn deletes_refresh_parent_counters() {
+        let shelves = Repo::<Shelf>::new("mem");
+        let books = Repo::<Book>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let shelf_id = shelves.create(&mut store, Shelf::validation_builder()).await.expect("create").id;
+        let mut book_ids = Vec::new();
+        for _ in 0..2 {
+            let builder = Book::validation_builder().shelf_id(shelf_id.clone());
+            let book_id = books.create(&mut store, builder).await.expect("create").id;
+            let connect = crate::snug! { Shelf(entity_id = shelf_id.clone()) { books: [connect book_id.clone()] } };
+            shelves.update_patch(&mut store, connect).await.expect("connect");
+            book_ids.push(book_id);
+        }
+        let count = |store: &MemoryExecutor| store.get(&shelves, &shelf_id).expect("get").expect("shelf exists").book_count;
+        assert_eq!(count(&store), 2);
+
+        books.delete(&mut store, &book_ids[0], None).await.expect("delete");
+        assert_eq!(count(&store), 1);
+        assert_eq!(store.members(&shelves.relation_key("books", &shelf_id)), vec![book_ids[1].clone()]);
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "editors")]
+    struct Editor {
+        #[snugom(id)]
+        id: String,
+        #[serde(default)]
+        written_count: u64,
+        #[serde(default)]
+        edited_count: u64,
+        #[serde(default)]
+        #[snugom(relation(has_many, target = "manuscripts", foreign_key = "writer_id", counter = "written_count"))]
+        written: Vec<String>,
+        #[serde(default)]
+        #[snugom(relation(has_many, target = "manuscripts", foreign_key = "reviewer_id", counter = "edited_count"))]
+        edited: Vec<String>,
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "manuscripts")]
+    struct Manuscript {
+        #[snugom(id)]
+        id: String,
+        #[snugom(relation(target = "editors"))]
+        reviewer_id: String,
+        #[snugom(relation(target = "editors"))]
+        writer_id: String,
+    }
+
+    #[tokio::test]
+    async fn deletes_leave_the_has_many_naming_their_foreign_key() {
+        let editors = Repo::<Editor>::new("mem");
+        let manuscripts = Repo::<Manuscript>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let writer = editors.create(&mut store, Editor::validation_builder()).await.expect("create").id;
+        let reviewer = editors.create(&mut store, Editor::validation_builder()).await.expect("create").id;
+        let builder = Manuscript::validation_builder().writer_id(writer.clone()).reviewer_id(reviewer.clone());
+        let manuscript = manuscripts.create(&mut store, builder).await.expect("create").id;
+        let connect = crate::snug! { Editor(entity_id = writer.clone()) { written: [connect manuscript.clone()] } };
+        editors.update_patch(&mut store, connect).await.expect("connect");
+        let connect = crate::snug! { Editor(entity_id = reviewer.clone()) { edited: [connect manuscript.clone()] } };
+        editors.update_patch(&mut store, connect).await.expect("connect");
+
+        manuscripts.delete(&mut store, &manuscript, None).await.expect("delete");
+        assert!(store.members(&editors.relation_key("written", &writer)).is_empty());
+        assert!(store.members(&editors.relation_key("edited", &reviewer)).is_empty());
+        let editor = |id: &str| store.get(&editors, id).expect("get").expect("editor exists");
+        assert_eq!((editor(&writer).written_count, editor(&reviewer).edited_count), (0, 0));
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "depots", key_separator = "/", key_components = ["eu"])]
+    struct Depot {
+        #[snugom(id)]
+        id: String,
+        #[serde(default)]
+        pallet_count: u64,
+        #[serde(default)]
+        #[snugom(relation(has_many, target = "pallets", counter = "pallet_count"))]
+        pallets: Vec<String>,
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "pallets")]
+    struct Pallet {
+        #[snugom(id)]
+        id: String,
+        #[snugom(relation(target = "depots"))]
+        depot_id: String,
+    }
+
+    #[tokio::test]
+    async fn entities_keep_their_own_key_layout() {
+        let depots = Repo::<Depot>::new("mem");
+        let pallets = Repo::<Pallet>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let depot_id = depots.create(&mut store, Depot::validation_builder()).await.expect("create").id;
+        assert_eq!(depots.entity_key(&depot_id), format!("mem/mem/eu/depots/{depot_id}"));
+        assert!(store.keys().contains(&depots.entity_key(&depot_id)));
+
+        let builder = Pallet::validation_builder().depot_id(depot_id.clone());
+        let pallet_id = pallets.create(&mut store, builder).await.expect("create").id;
+        let connect = crate::snug! { Depot(entity_id = depot_id.clone()) { pallets: [connect pallet_id.clone()] } };
+        depots.update_patch(&mut store, connect).await.expect("connect");
+        let relation_key = depots.relation_key("pallets", &depot_id);
+        assert_eq!(relation_key, format!("mem/mem/eu/rel/pallets/{depot_id}"));
+        assert_eq!(store.members(&relation_key), vec![pallet_id.clone()]);
+
+        // The pallet's delete reaches the depot's set and counter under the depot's layout
+        pallets.delete(&mut store, &pallet_id, None).await.expect("delete");
+        assert!(store.members(&relation_key).is_empty());
+        let depot = store.get(&depots, &depot_id).expect("get").expect("depot exists");
+        assert_eq!(depot.pallet_count, 0);
+    }
+
+    #[derive(crate::SnugomEntity, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[snugom(schema = 1, service = "mem", collection = "playlists")]
+    struct Playlist {
+        #[snugom(id)]
+        id: String,
+        #[serde(default)]
+        #[snugom(validate(length(min = 1, max = 2)))]
+        tracks: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn array_operations_keep_the_field_length() {
+        let repo = Repo::<Playlist>::new("mem");
+        let mut store = MemoryExecutor::new();
+        let builder = Playlist::validation_builder().tracks(vec!["intro".to_string()]);
+        let created = repo.create(&mut store, builder).await.expect("create");
+
+        let patch = crate::snug! { Playlist(entity_id = created.id.clone()) { tracks: push("verse", "chorus") } };
+        let err = repo.update_patch(&mut store, patch).await.expect_err("too many tracks");
+        assert!(matches!(err, RepoError::Validation(err) if err.issues[0].code == "validation.length"));
+        let stored = store.get(&repo, &created.id).expect("get").expect("playlist exists");
+        assert_eq!(stored.tracks, vec!["intro".to_string()]);
+
+        let patch = crate::snug! { Playlist(entity_id = created.id.clone()) { tracks: remove("intro") } };
+        let err = repo.update_patch(&mut store, patch).await.expect_err("too few tracks");
+        assert!(matches!(err, RepoError::Validation(err) if err.issues[0].params["min"] == 1));
+
+        let patch = crate::snug! { Playlist(entity_id = created.id.clone()) { tracks: push("verse") } };
+        repo.update_patch(&mut store, patch).await.expect("within bounds");
+        let stored = store.get(&repo, &created.id).expect("get").expect("playlist exists");
+        assert_eq!(stored.tracks, vec!["intro".to_string(), "verse".to_string()]);
+    }
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(edit_distance("wireless", "wireles"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,13 @@
+//! Helpers for testing code built on SnugOM, enabled by the `test-utils` feature.
+//!
+//! [`MemoryExecutor`] stands in for Redis when unit-testing services that take a
+//! [`crate::Repo`], so they can run without a live server. With the `testcontainers` feature,
+//! [`redis_stack`] starts a real server for integration tests.
+
+mod memory;
+#[cfg(feature = "testcontainers")]
+mod redis_stack;
+
+pub use memory::MemoryExecutor;
+#[cfg(feature = "testcontainers")]
+pub use redis_stack::{ENV_TEST_REDIS_URL, REDIS_STACK_IMAGE, REDIS_STACK_TAG, RedisStack, redis_stack};
@@ -0,0 +1,190 @@
+//! Throwaway Redis Stack servers for integration tests, enabled by the `testcontainers` feature.
+
+use std::{borrow::Cow, ops::Deref};
+
+use redis::aio::ConnectionManager;
+use testcontainers::{
+    ContainerAsync, GenericImage,
+    core::{ContainerPort, WaitFor},
+    runners::AsyncRunner,
+};
+
+use crate::{CleanupOptions, Client, errors::RepoError, id::generate_entity_id, keys::SegmentOrder};
+
+/// Image started by [`redis_stack`].
+pub const REDIS_STACK_IMAGE: &str = "redis/redis-stack-server";
+/// Tag of [`REDIS_STACK_IMAGE`] started by [`redis_stack`].
+pub const REDIS_STACK_TAG: &str = "7.4.0-v3";
+/// When set, [`redis_stack`] uses this server instead of starting a container.
+pub const ENV_TEST_REDIS_URL: &str = "SNUGOM_TEST_REDIS_URL";
+
+const REDIS_PORT: u16 = 6379;
+
+/// A connected [`Client`] with a prefix unique to one test.
+///
+/// Derefs to the client. Call [`RedisStack::cleanup`] at the end of a test to remove the container
+/// it started, or, on a shared server, every key and search index under the prefix. Dropping it
+/// without that is a fallback: a container is still removed, but a shared server is cleaned on a
+/// separate thread that the dropping thread waits for, which stalls the async runtime's thread
+/// until the SCAN finishes.
+pub struct RedisStack {
+    client: Client,
+    url: String,
+    container: Option<ContainerAsync<GenericImage>>,
+    cleaned: bool,
+}
+
+/// Start a Redis Stack container and connect a [`Client`] to it under a fresh `test_*` prefix.
+///
+/// Set [`ENV_TEST_REDIS_URL`] to reuse a running server instead, e.g. a CI service container;
+/// the unique prefix keeps concurrent tests apart and is cleaned up by [`RedisStack::cleanup`].
+///
+/// # Example
+/// ```ignore
+/// #[tokio::test]
+/// async fn creates_guilds() -> Result<(), RepoError> {
+///     let stack = snugom::testing::redis_stack().await?;
+///     let mut guilds = stack.collection::<Guild>();
+///     guilds.create(Guild::validation_builder().name("Rustaceans")).await?;
+///     assert_eq!(guilds.count().await?, 1);
+///     stack.cleanup().await
+/// }
+/// ```
+pub async fn redis_stack() -> Result<RedisStack, RepoError> {
+    let (url, container) = match std::env::var(ENV_TEST_REDIS_URL) {
+        Ok(url) => (url, None),
+        Err(_) => {
+            let container = GenericImage::new(REDIS_STACK_IMAGE, REDIS_STACK_TAG)
+                .with_exposed_port(ContainerPort::Tcp(REDIS_PORT))
+                .with_wait_for(WaitFor::message_on_stdout("Ready to accept connections"))
+                .start()
+                .await
+                .map_err(container_error)?;
+            let host = container.get_host().await.map_err(container_error)?;
+            let port = container.get_host_port_ipv4(REDIS_PORT).await.map_err(container_error)?;
+            (format!("redis://{host}:{port}"), Some(container))
+        }
+    };
+    let prefix = format!("test_{}", generate_entity_id());
+    let client = Client::connect(&url, prefix).await?;
+    Ok(RedisStack {
+        client,
+        url,
+        container,
+        cleaned: false,
+    })
+}
+
+impl RedisStack {
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn client_mut(&mut self) -> &mut Client {
+        &mut self.client
+    }
+
+    /// URL of the server, for building a `#[derive(SnugomClient)]` client or a second connection.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn prefix(&self) -> &str {
+        self.client.prefix()
+    }
+
+    pub fn connection(&self) -> ConnectionManager {
+        self.client.connection()
+    }
+
+    /// Remove the container, or on a shared server delete every key and search index under the prefix.
+    pub async fn cleanup(mut self) -> Result<(), RepoError> {
+        self.cleaned = true;
+        match self.container.take() {
+            Some(container) => container.rm().await.map_err(removal_error),
+            None => self.clean(&mut self.connection()).await,
+        }
+    }
+
+    /// SCAN pattern for the prefix's keys, and a test for whether a search index belongs to it.
+    fn patterns(&self) -> (String, impl Fn(&str) -> bool + use<>) {
+        let scheme = crate::keys::key_scheme();
+        let separator = scheme.separator().to_string();
+        let under_prefix = format!("{}{separator}", self.prefix());
+        // Service-first keys carry the prefix second, behind each service's name
+        let pattern = match scheme.order() {
+            SegmentOrder::PrefixFirst => format!("{under_prefix}*"),
+            SegmentOrder::ServiceFirst => format!("*{separator}{under_prefix}*"),
+        };
+        let behind_service = format!("{separator}{under_prefix}");
+        let owns_index = move |index: &str| index.starts_with(&under_prefix) || index.contains(&behind_service);
+        (pattern, owns_index)
+    }
+
+    /// Delete the prefix's keys with [`crate::cleanup_pattern_with`], then drop its search indexes.
+    async fn clean(&self, conn: &mut ConnectionManager) -> Result<(), RepoError> {
+        let (pattern, owns_index) = self.patterns();
+        crate::cleanup_pattern_with(conn, &pattern, CleanupOptions::default()).await?;
+        let indexes: Vec<String> = redis::cmd("FT._LIST").query_async(conn).await?;
+        for index in indexes.iter().filter(|index| owns_index(index)) {
+            redis::cmd("FT.DROPINDEX").arg(index).exec_async(conn).await?;
+        }
+        Ok(())
+    }
+
+    /// Blocking counterpart of [`RedisStack::cleanup`] for a shared server, used by `Drop`.
+    ///
+    /// Runs [`RedisStack::clean`] on a runtime of its own in a separate thread, since the stack is
+    /// usually dropped inside a test's runtime, where blocking on another future would panic.
+    fn clean_blocking(&self) -> Result<(), RepoError> {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|err| RepoError::Other {
+                            message: Cow::Owned(format!("failed to build cleanup runtime: {err}")),
+                        })?;
+                    runtime.block_on(async {
+                        let mut conn = ConnectionManager::new(redis::Client::open(self.url.as_str())?).await?;
+                        self.clean(&mut conn).await
+                    })
+                })
+                .join()
+                .unwrap_or(Err(RepoError::Other {
+                    message: Cow::Borrowed("Redis Stack cleanup panicked"),
+                }))
+        })
+    }
+}
+
+impl Deref for RedisStack {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl Drop for RedisStack {
+    fn drop(&mut self) {
+        // Fallback for stacks never passed to `cleanup`. A container is removed with its data, so
+        // only a shared server needs cleaning, and that blocks the current thread
+        if !self.cleaned && self.container.is_none() {
+            let _ = self.clean_blocking();
+        }
+    }
+}
+
+fn container_error(err: testcontainers::TestcontainersError) -> RepoError {
+    RepoError::Other {
+        message: Cow::Owned(format!("failed to start Redis Stack container: {err}")),
+    }
+}
+
+fn removal_error(err: testcontainers::TestcontainersError) -> RepoError {
+    RepoError::Other {
+        message: Cow::Owned(format!("failed to remove Redis Stack container: {err}")),
+    }
+}
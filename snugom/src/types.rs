@@ -1,5 +1,8 @@
-use crate::search::SortOrder;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+use crate::keys::KeyOverrides;
+use crate::search::{FilterCondition, SortOrder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
 
 /// Placeholder metadata structures emitted by the derive macro in later phases.
 #[derive(Debug, Default, Clone)]
@@ -8,11 +11,32 @@ pub struct EntityDescriptor {
     pub collection: String,
     pub version: u32,
     pub id_field: Option<String>,
+    /// Prefix from `#[snugom(id(prefix = "..."))]`; ids are `{prefix}_...`
+    pub id_prefix: Option<String>,
     pub relations: Vec<RelationDescriptor>,
     pub fields: Vec<FieldDescriptor>,
     pub derived_id: Option<DerivedIdDescriptor>,
     /// Unique constraints on this entity (single-field and compound)
     pub unique_constraints: Vec<UniqueConstraintDescriptor>,
+    /// Has-many-through relations resolved by hopping across two relation sets
+    pub through_relations: Vec<ThroughRelationDescriptor>,
+    /// Entity-level custom validators, run after the field validations pass
+    pub custom_validators: Vec<CustomValidatorDescriptor>,
+    /// `validate(async_custom = "...")` validators, run with a connection before create and patch
+    pub async_validators: Vec<AsyncValidatorDescriptor>,
+    /// Lua snippet from `#[snugom(lua_check = "path")]`, evaluated inside the mutation
+    /// script against the candidate document before it is committed.
+    pub lua_check: Option<String>,
+    /// Doc comment on the entity struct, for schema docs and admin tooling
+    pub description: Option<String>,
+    /// How documents are laid out in Redis, from `#[snugom(storage = "...")]`
+    pub storage: StorageMode,
+    /// Callbacks from `#[snugom(before_create = "...", after_update = "...")]`
+    pub hooks: LifecycleHooks,
+    /// Previous versions kept per entity, from `#[snugom(versioned(keep = N))]`
+    pub keep_versions: Option<usize>,
+    /// Separator and extra key segments from `#[snugom(key_separator = "...", key_components = [...])]`
+    pub keys: KeyOverrides,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +47,22 @@ pub struct RelationDescriptor {
     pub kind: RelationKind,
     pub cascade: CascadePolicy,
     pub foreign_key: Option<String>,
+    /// Numeric field on this entity kept equal to the relation's member count
+    pub counter: Option<String>,
+}
+
+/// Describes a relation that is reached through an intermediate entity.
+///
+/// Declared at the entity level with
+/// `#[snugom(has_many_through(alias = "teams", through = "memberships", source = "team"))]`:
+/// `through` names a relation on this entity and `source` names a relation on the
+/// intermediate entity. No data is stored for the relation itself; it is resolved at
+/// read time from the two underlying relation sets.
+#[derive(Debug, Clone)]
+pub struct ThroughRelationDescriptor {
+    pub alias: String,
+    pub through: String,
+    pub source: String,
 }
 
 #[derive(Debug, Clone)]
@@ -83,8 +123,9 @@ impl UniqueConstraintDescriptor {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[derive(Default)]
+#[serde(rename_all = "snake_case")]
 pub enum RelationKind {
     #[default]
     HasMany,
@@ -93,8 +134,9 @@ pub enum RelationKind {
 }
 
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[derive(Default)]
+#[serde(rename_all = "snake_case")]
 pub enum CascadePolicy {
     Delete,
     Detach,
@@ -103,6 +145,26 @@ pub enum CascadePolicy {
 }
 
 
+/// Redis data type backing an entity's documents.
+///
+/// `Json` stores each entity as a RedisJSON document. `Hash` stores flat entities as
+/// plain hash fields for deployments without RedisJSON; nested values are kept as JSON
+/// strings and the `metadata` object is flattened into `metadata.*` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    #[default]
+    Json,
+    Hash,
+}
+
+impl StorageMode {
+    pub fn is_hash(self) -> bool {
+        matches!(self, StorageMode::Hash)
+    }
+}
+
 pub trait EntityMetadata {
     /// Whether this entity has at least one indexed field (filterable or sortable).
     /// Entities used in bundles must have indexed fields to support search operations.
@@ -141,6 +203,14 @@ pub struct FieldDescriptor {
     pub auto_created: bool,
     pub field_type: FieldType,
     pub element_type: Option<FieldType>,
+    /// From `#[snugom(immutable)]`: set on create, rejected in patches like the id field
+    pub immutable: bool,
+    /// From `#[snugom(redact)]`: stripped from every search hit, so secrets such as password
+    /// hashes only leave Redis through a direct get
+    pub redacted: bool,
+    /// From `enum_order = [...]`: tag values in sort order. The value's position (unlisted values
+    /// sort last) is written to a numeric `__{field}_ord` shadow that sorting uses.
+    pub enum_order: Vec<String>,
     /// True if this field is a relation Vec (has_many, many_to_many) that defaults to empty
     pub is_relation_vec: bool,
     /// When true, normalize enum values to just their discriminant (variant name) at write time.
@@ -148,12 +218,47 @@ pub struct FieldDescriptor {
     /// which RediSearch cannot index as TAG fields. The full enum value is preserved in the document,
     /// but the indexed value becomes just the variant name string (e.g., "swiss").
     pub normalize_enum_tag: bool,
+    /// From `filterable(tag, casefold)`: a lowercase copy of the string is written to a
+    /// `__{field}_lc` shadow at write time, and that shadow is what gets indexed and filtered.
+    pub casefold_tag: bool,
+    /// From `#[snugom(slug(from = "..."))]`: generated from another field on create and unique
+    /// within the collection.
+    pub slug: Option<SlugDescriptor>,
+    /// Doc comment on the field, for schema docs and admin tooling
+    pub description: Option<String>,
+    /// Codec from `#[snugom(compressed)]`. Compressed fields are opaque blobs to RediSearch,
+    /// so they are never indexable.
+    pub compression: Option<Compression>,
 }
 
-pub type DatetimeMirrors = Vec<DatetimeMirrorValue>;
+impl FieldDescriptor {
+    /// Whether the field may appear in a search index.
+    pub fn is_indexable(&self) -> bool {
+        self.compression.is_none()
+    }
+}
 
+/// How a slug field is generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugDescriptor {
+    /// Stored name of the field the slug is generated from
+    pub from: String,
+    /// On a unique-constraint collision, creates retry with `-2`, `-3`, ... appended
+    pub suffix: bool,
+}
+
+/// Codec for a `#[snugom(compressed)]` field.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+pub type DatetimeMirrors = Vec<DatetimeMirrorValue>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[derive(Default)]
+#[serde(rename_all = "snake_case")]
 pub enum FieldType {
     String,
     Number,
@@ -161,7 +266,10 @@ pub enum FieldType {
     Array,
     #[default]
     Object,
+    #[serde(rename = "datetime")]
     DateTime,
+    /// `Vec<u8>`, stored as a base64 string
+    Bytes,
 }
 
 
@@ -197,22 +305,88 @@ pub enum ValidationRule {
     Email,
     Url,
     Uuid,
+    /// `validate(phone)`; see [`is_valid_phone`](crate::validators::is_valid_phone)
+    Phone,
+    /// `validate(ip_addr)`; IPv4 or IPv6
+    IpAddr,
+    /// `validate(hostname)`; see [`is_valid_hostname`](crate::validators::is_valid_hostname)
+    Hostname,
+    /// `validate(slug)`; lowercase ASCII words joined by single `-`
+    Slug,
+    /// `validate(required_if = "...")`; the field must be present when `condition` holds
     RequiredIf {
         expr: String,
+        condition: ConditionFn,
     },
+    /// `validate(forbidden_if = "...")`; the field must be absent when `condition` holds
     ForbiddenIf {
         expr: String,
+        condition: ConditionFn,
     },
     /// Unique constraint on a field. Duplicate values are rejected at creation/update time.
     Unique {
         /// Whether string comparisons ignore case
         case_insensitive: bool,
     },
+    /// `validate(custom = "path")`; `check` runs the function against the JSON value
     Custom {
         path: String,
+        check: CustomValidatorFn,
     },
 }
 
+/// A `required_if`/`forbidden_if` expression as compiled by the derive, evaluated against the whole
+/// document. `None` when the document does not deserialize as the entity.
+pub type ConditionFn = fn(&serde_json::Value) -> Option<bool>;
+
+/// A `validate(custom = "...")` function as wrapped by the derive: the value is deserialized to
+/// the function's argument type first, so the repository can run it on create and patch payloads.
+pub type CustomValidatorFn = fn(&serde_json::Value) -> crate::errors::ValidationResult<()>;
+
+/// An entity-level `#[snugom(validate(custom = "path"))]`, run against the whole document.
+#[derive(Debug, Clone)]
+pub struct CustomValidatorDescriptor {
+    pub path: String,
+    pub check: CustomValidatorFn,
+}
+
+/// A `before_create`/`before_update` callback as wrapped by the derive: the document is
+/// deserialized to the entity, handed to the function as `&mut`, and written back.
+pub type BeforeHookFn = fn(&mut serde_json::Value) -> crate::errors::ValidationResult<()>;
+
+/// An `after_create`/`after_update` callback as wrapped by the derive, given the stored document.
+pub type AfterHookFn = fn(&serde_json::Value);
+
+/// Entity lifecycle callbacks declared on the struct.
+///
+/// Before hooks run ahead of validation, so whatever they change is validated and stored; after
+/// hooks run once the write has committed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LifecycleHooks {
+    pub before_create: Option<BeforeHookFn>,
+    pub after_create: Option<AfterHookFn>,
+    pub before_update: Option<BeforeHookFn>,
+    pub after_update: Option<AfterHookFn>,
+}
+
+/// Future returned by an [`AsyncValidatorFn`].
+pub type AsyncValidatorFuture<'a> =
+    std::pin::Pin<Box<dyn Future<Output = crate::errors::ValidationResult<()>> + Send + 'a>>;
+
+/// A `validate(async_custom = "...")` validator as wrapped by the derive: the value is deserialized
+/// to the validated type before [`AsyncValidator::validate`](crate::validators::AsyncValidator) runs.
+pub type AsyncValidatorFn =
+    for<'a> fn(&'a mut redis::aio::ConnectionManager, &'a serde_json::Value) -> AsyncValidatorFuture<'a>;
+
+/// A `validate(async_custom = "path")` on a field or on the entity.
+#[derive(Debug, Clone)]
+pub struct AsyncValidatorDescriptor {
+    /// Field the validator checks, or `None` when it is given the whole document
+    pub field: Option<String>,
+    pub path: String,
+    pub check: AsyncValidatorFn,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct DatetimeMirrorValue {
     pub field: String,
@@ -409,6 +583,9 @@ pub struct RelationData<T> {
     /// Whether more items exist beyond what was fetched
     #[serde(skip_serializing_if = "Option::is_none")]
     pub has_more: Option<bool>,
+    /// Edge payloads keyed by related id, for relations connected with `connect_with`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub edges: BTreeMap<String, serde_json::Value>,
 }
 
 impl<T> RelationData<T> {
@@ -418,6 +595,7 @@ impl<T> RelationData<T> {
             items,
             total: None,
             has_more: None,
+            edges: BTreeMap::new(),
         }
     }
 
@@ -427,8 +605,20 @@ impl<T> RelationData<T> {
             items,
             total: Some(total),
             has_more: Some(has_more),
+            edges: BTreeMap::new(),
         }
     }
+
+    /// Attach edge payloads keyed by related id
+    pub fn with_edges(mut self, edges: BTreeMap<String, serde_json::Value>) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    /// Deserialize the edge payload stored for `id`, if any
+    pub fn edge<E: DeserializeOwned>(&self, id: &str) -> Option<Result<E, serde_json::Error>> {
+        self.edges.get(id).map(|value| E::deserialize(value))
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -458,6 +648,12 @@ impl<T> RelationData<T> {
 ///     .with_limit(10)
 ///     .with_sort("role")
 ///     .with_filter("role:eq:admin");
+///
+/// // Typed conditions are ANDed with the filter expression
+/// use snugom::search::FilterCondition;
+/// let options = RelationQueryOptions::default()
+///     .with_condition(FilterCondition::tag_eq("status", "published"))
+///     .with_sort("-created_at");
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct RelationQueryOptions {
@@ -467,6 +663,8 @@ pub struct RelationQueryOptions {
     pub sort: Option<String>,
     /// Filter expression (e.g., "role:eq:admin")
     pub filter: Option<String>,
+    /// Typed filter condition evaluated against the target index
+    pub condition: Option<FilterCondition>,
     /// Offset for pagination (combine with limit)
     pub offset: Option<u32>,
 }
@@ -502,6 +700,19 @@ impl RelationQueryOptions {
         self
     }
 
+    /// Add a typed filter condition; repeated calls are ANDed together
+    pub fn with_condition(mut self, condition: FilterCondition) -> Self {
+        self.condition = Some(match self.condition.take() {
+            Some(FilterCondition::And(mut conditions)) => {
+                conditions.push(condition);
+                FilterCondition::And(conditions)
+            }
+            Some(existing) => FilterCondition::and([existing, condition]),
+            None => condition,
+        });
+        self
+    }
+
     /// Set the pagination offset
     pub fn with_offset(mut self, offset: u32) -> Self {
         self.offset = Some(offset);
@@ -515,7 +726,11 @@ impl RelationQueryOptions {
 
     /// Check if any options are set
     pub fn has_options(&self) -> bool {
-        self.limit.is_some() || self.sort.is_some() || self.filter.is_some() || self.offset.is_some()
+        self.limit.is_some()
+            || self.sort.is_some()
+            || self.filter.is_some()
+            || self.condition.is_some()
+            || self.offset.is_some()
     }
 
     /// Parse sort specification into field and direction
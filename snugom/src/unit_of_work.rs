@@ -0,0 +1,196 @@
+//! Staging writes across entities and sending them to Redis together.
+
+use redis::aio::ConnectionManager;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    audit::{self, AuditAction, AuditSettings},
+    errors::RepoError,
+    interceptor::{self, SharedInterceptor},
+    repository::{MutationPayloadBuilder, RelationPlan, Repo, UpdatePatchBuilder},
+    runtime::{MutationPlan, execute_atomic},
+    types::{AfterHookFn, SnugomModel},
+};
+
+/// A validated write waiting in a [`UnitOfWork`].
+pub(crate) struct StagedWrite {
+    pub(crate) plan: MutationPlan,
+    pub(crate) entity_id: String,
+    pub(crate) audit_key: String,
+    pub(crate) action: AuditAction,
+    pub(crate) fields: Vec<String>,
+    /// The `after_create`/`after_update` hook to call with the document once committed
+    pub(crate) after: Option<(AfterHookFn, Value)>,
+}
+
+/// Creates, updates, deletes, and relation changes across any number of entities, flushed as
+/// one combined [`MutationPlan`].
+///
+/// Staging validates each write as the matching [`CollectionHandle`](crate::CollectionHandle)
+/// method would, reading the current document for updates, but writes nothing. [`commit`]
+/// then runs every staged command inside one script, so no other client sees some of the writes
+/// without the others. Each command keeps its own checks (versions, unique constraints,
+/// preconditions); if one fails, the writes staged before it are undone and the error is
+/// returned, so the commit applies whole or not at all.
+///
+/// Nested creates can't be staged, since they would have to run before their parent. Audit
+/// entries and after hooks follow a successful commit.
+///
+/// [`commit`]: UnitOfWork::commit
+///
+/// # Example
+/// ```ignore
+/// let mut work = client.unit_of_work();
+/// let guild_id = work.create(Guild::validation_builder().name("Dragon Knights".to_string())).await?;
+/// work.create(GuildMember::validation_builder().guild_id(guild_id.clone()).name("Aria".to_string())).await?;
+/// work.update(Player::patch_builder().entity_id(&player_id).guild_id(Some(guild_id))).await?;
+/// work.delete::<Invite>(&invite_id, None)?;
+/// work.commit().await?;
+/// ```
+pub struct UnitOfWork {
+    conn: ConnectionManager,
+    prefix: String,
+    audit: Option<AuditSettings>,
+    interceptor: Option<SharedInterceptor>,
+    plan: MutationPlan,
+    staged: Vec<StagedWrite>,
+}
+
+impl UnitOfWork {
+    pub fn new(conn: ConnectionManager, prefix: impl Into<String>) -> Self {
+        Self {
+            conn,
+            prefix: prefix.into(),
+            audit: None,
+            interceptor: None,
+            plan: MutationPlan::new(),
+            staged: Vec::new(),
+        }
+    }
+
+    /// Record audit entries for the committed writes; see [`Client::with_audit`](crate::Client::with_audit).
+    pub fn with_audit(mut self, settings: AuditSettings) -> Self {
+        self.audit = Some(settings);
+        self
+    }
+
+    /// Report the reads made while staging, the commit, and its audit appends to `interceptor`;
+    /// see [`Client::with_interceptor`](crate::Client::with_interceptor).
+    pub fn with_interceptor(mut self, interceptor: SharedInterceptor) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    /// Stage a create, returning the id the entity will have.
+    ///
+    /// As with [`Repo::create_with_conn`], the create fails at commit if the id is taken.
+    pub async fn create<B>(&mut self, builder: B) -> Result<String, RepoError>
+    where
+        B: MutationPayloadBuilder,
+        B::Entity: SnugomModel,
+    {
+        let repo = self.repo::<B::Entity>();
+        let staged = repo.stage_create(&mut self.conn, builder.into_payload()?).await?;
+        let entity_id = staged.entity_id.clone();
+        self.stage(staged);
+        Ok(entity_id)
+    }
+
+    /// Stage a patch, validated against the entity as it is stored now.
+    pub async fn update<B>(&mut self, builder: B) -> Result<(), RepoError>
+    where
+        B: UpdatePatchBuilder,
+        B::Entity: SnugomModel + Serialize + DeserializeOwned,
+    {
+        let repo = self.repo::<B::Entity>();
+        let staged = repo.stage_patch(&mut self.conn, builder.into_patch()?).await?;
+        if !staged.plan.is_empty() {
+            self.stage(staged);
+        }
+        Ok(())
+    }
+
+    /// Stage a delete of `entity_id`, with its cascades.
+    pub fn delete<T>(&mut self, entity_id: &str, expected_version: Option<u64>) -> Result<(), RepoError>
+    where
+        T: SnugomModel,
+    {
+        let repo = self.repo::<T>();
+        self.stage(StagedWrite {
+            plan: repo.prepare_delete(entity_id, expected_version)?,
+            entity_id: entity_id.to_string(),
+            audit_key: repo.audit_key(entity_id),
+            action: AuditAction::Delete,
+            fields: Vec::new(),
+            after: None,
+        });
+        Ok(())
+    }
+
+    /// Stage relation changes on `T`, as [`Repo::mutate_relations`] would make them.
+    pub fn relations<T>(&mut self, relations: Vec<RelationPlan>) -> Result<(), RepoError>
+    where
+        T: SnugomModel,
+    {
+        let plan = Repo::<T>::new(self.prefix.clone()).prepare_relations(relations)?;
+        self.plan.commands.extend(plan.commands);
+        Ok(())
+    }
+
+    /// The combined plan staged so far.
+    pub fn plan(&self) -> &MutationPlan {
+        &self.plan
+    }
+
+    /// Number of commands staged, counting cascade cleanups and relation scripts.
+    pub fn len(&self) -> usize {
+        self.plan.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plan.is_empty()
+    }
+
+    /// Apply every staged command, or none of them, returning the script responses in order.
+    pub async fn commit(mut self) -> Result<Vec<Value>, RepoError> {
+        let Some(first) = self.plan.commands.first() else {
+            return Ok(Vec::new());
+        };
+        let key = first.key().to_string();
+        let interceptor = self.interceptor.as_ref();
+        let responses = interceptor::observe(interceptor, "plan", &key, execute_atomic(&mut self.conn, &self.plan)).await?;
+        for staged in &self.staged {
+            if let Some((hook, document)) = &staged.after {
+                hook(document);
+            }
+            if let Some(settings) = &self.audit
+                && let Err(_err) = interceptor::observe(
+                    interceptor,
+                    "audit",
+                    &staged.audit_key,
+                    audit::record(&mut self.conn, &staged.audit_key, settings, staged.action, &staged.fields),
+                )
+                .await
+            {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(key = %staged.audit_key, error = %_err, "failed to append audit entry");
+            }
+        }
+        Ok(responses)
+    }
+
+    /// A repository for `T` under this unit of work's prefix, reporting to its interceptor.
+    fn repo<T: SnugomModel>(&self) -> Repo<T> {
+        let repo = Repo::new(self.prefix.clone());
+        match &self.interceptor {
+            Some(interceptor) => repo.with_interceptor(interceptor.clone()),
+            None => repo,
+        }
+    }
+
+    fn stage(&mut self, mut staged: StagedWrite) {
+        self.plan.commands.append(&mut staged.plan.commands);
+        self.staged.push(staged);
+    }
+}
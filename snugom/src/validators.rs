@@ -1,7 +1,55 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{OnceLock, RwLock},
+};
+
 use email_address::EmailAddress;
+use redis::aio::ConnectionManager;
+use regex::Regex;
 use url::Url;
 use uuid::Uuid;
 
+use crate::errors::ValidationResult;
+
+/// A validation that needs to look data up in Redis, e.g. "the referenced template exists and is
+/// published". Implement it on a marker type and name the type in
+/// `#[snugom(validate(async_custom = "Type"))]`: on a field `V` is the field's type (the inner type
+/// of an `Option`, skipped when `None`), on the struct it is the entity.
+///
+/// `Repo::create_with_conn` and `Repo::update_patch_with_conn` run async validators once the
+/// synchronous rules pass, before the mutation plan is built; a patch only runs field validators
+/// for the fields it touches.
+pub trait AsyncValidator<V: ?Sized> {
+    fn validate(conn: &mut ConnectionManager, value: &V) -> impl Future<Output = ValidationResult<()>> + Send;
+}
+
+/// Most patterns [`compiled_regex`] keeps; beyond this, new patterns are compiled on every call.
+const REGEX_CACHE_CAPACITY: usize = 1024;
+
+static REGEX_CACHE: OnceLock<RwLock<HashMap<String, Regex>>> = OnceLock::new();
+
+/// `validate(regex)` pattern compiled once per process and shared by later calls, so checking a
+/// write doesn't recompile its patterns. Invalid patterns are not cached.
+pub fn compiled_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let cache = REGEX_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
+    if let Some(regex) = cache.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(pattern)?;
+    let mut cache = cache.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if cache.len() < REGEX_CACHE_CAPACITY {
+        cache.insert(pattern.to_string(), regex.clone());
+    }
+    Ok(regex)
+}
+
+/// Returns `true` if `value` matches the `validate(regex)` pattern; an invalid pattern matches
+/// nothing, so both the derived `validate()` and the repository's checks reject every value.
+pub fn matches_regex(pattern: &str, value: &str) -> bool {
+    compiled_regex(pattern).is_ok_and(|regex| regex.is_match(value))
+}
+
 /// Returns `true` if the provided string is a syntactically valid email address.
 pub fn is_valid_email(value: &str) -> bool {
     EmailAddress::is_valid(value)
@@ -17,10 +65,72 @@ pub fn is_valid_uuid(value: &str) -> bool {
     Uuid::parse_str(value).is_ok()
 }
 
+/// Returns `true` if the provided string is a phone number: an optional leading `+`, then 7 to 15
+/// digits (the E.164 limit), optionally grouped by spaces, `-`, `.`, or one parenthesized group,
+/// e.g. `+1 (555) 010-0199`.
+pub fn is_valid_phone(value: &str) -> bool {
+    let number = value.strip_prefix('+').unwrap_or(value);
+    let mut digits = 0;
+    let mut in_group = false;
+    for ch in number.chars() {
+        match ch {
+            '0'..='9' => digits += 1,
+            ' ' | '-' | '.' if digits > 0 => {}
+            '(' if !in_group => in_group = true,
+            ')' if in_group => in_group = false,
+            _ => return false,
+        }
+    }
+    !in_group && (7..=15).contains(&digits) && number.ends_with(|ch: char| ch.is_ascii_digit())
+}
+
+/// Returns `true` if the provided string parses as an IPv4 or IPv6 address.
+pub fn is_valid_ip_addr(value: &str) -> bool {
+    value.parse::<IpAddr>().is_ok()
+}
+
+/// Returns `true` if the provided string is an RFC 1123 hostname: dot-separated labels of ASCII
+/// letters, digits, and inner hyphens, each at most 63 characters and 253 in all. A trailing dot
+/// is allowed.
+pub fn is_valid_hostname(value: &str) -> bool {
+    let name = value.strip_suffix('.').unwrap_or(value);
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+}
+
+/// Returns `true` if the provided string is a slug as [`slugify`](crate::slug::slugify) writes
+/// them: lowercase ASCII letters and digits joined by single `-`.
+pub fn is_valid_slug(value: &str) -> bool {
+    value
+        .split('-')
+        .all(|word| !word.is_empty() && word.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn regexes_compile_once() {
+        let first = compiled_regex("^[a-z]+-cache$").unwrap();
+        let second = compiled_regex("^[a-z]+-cache$").unwrap();
+        assert_eq!(first.as_str(), second.as_str());
+        assert!(REGEX_CACHE.get().unwrap().read().unwrap().contains_key("^[a-z]+-cache$"));
+        assert!(matches_regex("^[a-z]+-cache$", "abc-cache"));
+        assert!(!matches_regex("^[a-z]+-cache$", "ABC-cache"));
+
+        assert!(compiled_regex("(unclosed").is_err());
+        assert!(!matches_regex("(unclosed", "(unclosed"));
+        assert!(!REGEX_CACHE.get().unwrap().read().unwrap().contains_key("(unclosed"));
+    }
+
     #[test]
     fn email_validation() {
         assert!(is_valid_email("test@example.com"));
@@ -38,4 +148,45 @@ mod tests {
         assert!(is_valid_uuid("550e8400-e29b-41d4-a716-446655440000"));
         assert!(!is_valid_uuid("not-a-uuid"));
     }
+
+    #[test]
+    fn phone_validation() {
+        assert!(is_valid_phone("+15550100199"));
+        assert!(is_valid_phone("+1 (555) 010-0199"));
+        assert!(is_valid_phone("030.1234.5678"));
+        assert!(!is_valid_phone("12345"));
+        assert!(!is_valid_phone("+1 555 010 0199 0000 00"));
+        assert!(!is_valid_phone("-5550100199"));
+        assert!(!is_valid_phone("(555 0100199"));
+        assert!(!is_valid_phone("555-0100-ext"));
+    }
+
+    #[test]
+    fn ip_addr_validation() {
+        assert!(is_valid_ip_addr("192.168.0.1"));
+        assert!(is_valid_ip_addr("::1"));
+        assert!(!is_valid_ip_addr("256.0.0.1"));
+        assert!(!is_valid_ip_addr("example.com"));
+    }
+
+    #[test]
+    fn hostname_validation() {
+        assert!(is_valid_hostname("example.com"));
+        assert!(is_valid_hostname("api-1.eu.example.com."));
+        assert!(is_valid_hostname("localhost"));
+        assert!(!is_valid_hostname("-bad.example.com"));
+        assert!(!is_valid_hostname("a..b"));
+        assert!(!is_valid_hostname("under_score.com"));
+        assert!(!is_valid_hostname(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn slug_validation() {
+        assert!(is_valid_slug("creme-brulee-2-ways"));
+        assert!(is_valid_slug(&crate::slug::slugify("Crème brûlée, 2 ways!")));
+        assert!(!is_valid_slug(""));
+        assert!(!is_valid_slug("Title"));
+        assert!(!is_valid_slug("double--dash"));
+        assert!(!is_valid_slug("trailing-"));
+    }
 }
@@ -4,3 +4,10 @@ async fn account_and_profile_belongs_to() {
         .await
         .expect("example should succeed");
 }
+
+#[tokio::test]
+async fn rebuild_relations_from_foreign_keys() {
+    snugom::examples::repo::ex14_relation_repair::run()
+        .await
+        .expect("example should succeed");
+}
@@ -176,6 +176,99 @@ async fn client_ex23_batch_workflows() {
         .expect("example should succeed");
 }
 
+#[tokio::test]
+async fn client_ex24_has_many_through() {
+    snugom::examples::client::ex24_has_many_through::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex25_relation_edges() {
+    snugom::examples::client::ex25_relation_edges::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex26_relation_queries() {
+    snugom::examples::client::ex26_relation_queries::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex27_batch_hydration() {
+    snugom::examples::client::ex27_batch_hydration::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex28_relation_counters() {
+    snugom::examples::client::ex28_relation_counters::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex29_view_models() {
+    snugom::examples::client::ex29_view_models::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex30_self_relations() {
+    snugom::examples::client::ex30_self_relations::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn client_ex31_graphql() {
+    snugom::examples::client::ex31_graphql::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex32_hash_storage() {
+    snugom::examples::client::ex32_hash_storage::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn client_ex33_compressed_fields() {
+    snugom::examples::client::ex33_compressed_fields::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex34_binary_fields() {
+    snugom::examples::client::ex34_binary_fields::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex35_atomic_increments() {
+    snugom::examples::client::ex35_atomic_increments::run()
+        .await
+        .expect("example should succeed");
+}
+
+#[tokio::test]
+async fn client_ex36_array_operations() {
+    snugom::examples::client::ex36_array_operations::run()
+        .await
+        .expect("example should succeed");
+}
+
 // ============ Social Network Application ============
 
 // TODO: Social network uses version fields which need rethink
@@ -8,7 +8,7 @@
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use snugom::{Client, CollectionHandle, SnugomClient, SnugomEntity};
+use snugom::{Client, CollectionHandle, SnugomClient, SnugomEntity, audit::AuditAction};
 
 // ============ Test Entities ============
 
@@ -241,6 +241,73 @@ async fn test_client_create_many() {
     cleanup_client(&client).await;
 }
 
+#[tokio::test]
+async fn test_client_create_many_reports_partial_failures() {
+    let client = create_test_client().await;
+    let mut widgets = client.collection::<Widget>();
+
+    let taken_id = uuid::Uuid::new_v4().to_string();
+    widgets
+        .create(
+            Widget::validation_builder()
+                .id(taken_id.clone())
+                .name("Existing".to_string())
+                .category("bulk".to_string())
+                .price(1)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+
+    let mut builders: Vec<_> = (0..7)
+        .map(|i| {
+            Widget::validation_builder()
+                .name(format!("Widget {i}"))
+                .category("bulk".to_string())
+                .price(i)
+                .created_at(Utc::now())
+        })
+        .collect();
+    // Missing required `name`
+    builders.insert(2, Widget::validation_builder().category("bulk".to_string()).price(0).created_at(Utc::now()));
+    builders.insert(
+        5,
+        Widget::validation_builder()
+            .id(taken_id.clone())
+            .name("Duplicate".to_string())
+            .category("bulk".to_string())
+            .price(0)
+            .created_at(Utc::now()),
+    );
+
+    let options = snugom::BulkCreateOptions {
+        chunk_size: 2,
+        parallelism: 2,
+    };
+    let result = widgets.create_many_with(builders, options).await.expect("create_many_with failed");
+    assert_eq!(result.count, 7);
+    assert!(!result.is_complete());
+    assert_eq!(result.failures.len(), 2);
+    assert_eq!(result.failures[0].index, 2);
+    assert_eq!(result.validation_errors().map(|(index, _)| index).collect::<Vec<_>>(), vec![2]);
+    assert_eq!(result.failures[1].index, 5);
+    assert!(matches!(
+        &result.failures[1].error,
+        snugom::RepoError::AlreadyExists { entity_id } if entity_id == &taken_id
+    ));
+
+    // Successful ids come back in input order
+    for (id, i) in result.ids.iter().zip(0..) {
+        let widget = widgets.get_or_error(id).await.expect("get_or_error failed");
+        assert_eq!(widget.price, i);
+    }
+    let existing = widgets.get_or_error(&taken_id).await.expect("get_or_error failed");
+    assert_eq!(existing.name, "Existing");
+    assert_eq!(widgets.count().await.expect("count failed"), 8);
+
+    cleanup_client(&client).await;
+}
+
 #[tokio::test]
 async fn test_client_delete_many_by_ids() {
     let client = create_test_client().await;
@@ -508,6 +575,414 @@ async fn test_client_find_many() {
     let _ = snugom::cleanup_pattern(&mut client.connection(), &pattern).await;
 }
 
+#[tokio::test]
+async fn test_client_reads_from_replica() {
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    // A second connection to the same server stands in for the replica
+    let replica = snugom::ConnectionManager::new(redis::Client::open(redis_url.as_str()).unwrap())
+        .await
+        .expect("Failed to connect to Redis");
+    let client = create_test_client()
+        .await
+        .with_read_replica(replica)
+        .with_read_preference(snugom::ReadPreference::Replica);
+    let mut widgets = client.collection::<Widget>();
+
+    let builder = Widget::validation_builder()
+        .name("Replica".to_string())
+        .category("tools".to_string())
+        .price(10)
+        .created_at(Utc::now());
+    let created = widgets.create(builder).await.expect("create failed");
+    let fetched = widgets.get(&created.id).await.expect("get failed").expect("widget missing");
+    assert_eq!(fetched.name, "Replica");
+    assert!(widgets.exists(&created.id).await.expect("exists failed"));
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_update_and_get_reads_from_primary() {
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    // A replica on another database never sees the write, so only a primary read finds it
+    let mut info = redis::IntoConnectionInfo::into_connection_info(redis_url.as_str()).unwrap();
+    info.redis.db = 15;
+    let replica = snugom::ConnectionManager::new(redis::Client::open(info).unwrap())
+        .await
+        .expect("Failed to connect to Redis");
+    let client = create_test_client()
+        .await
+        .with_read_replica(replica)
+        .with_read_preference(snugom::ReadPreference::Replica);
+    let mut widgets = client.collection::<Widget>();
+
+    let builder = Widget::validation_builder()
+        .name("Lagging".to_string())
+        .category("tools".to_string())
+        .price(10)
+        .created_at(Utc::now());
+    let created = widgets.create(builder).await.expect("create failed");
+    let updated = widgets
+        .update_and_get(&created.id, Widget::patch_builder().entity_id(&created.id).price(11))
+        .await
+        .expect("update_and_get failed");
+    assert_eq!(updated.price, 11);
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_audit_trail() {
+    let client = create_test_client().await.with_actor("user:123");
+    let mut widgets = client.collection::<Widget>();
+
+    let builder = Widget::validation_builder()
+        .name("Audited".to_string())
+        .category("tools".to_string())
+        .price(10)
+        .created_at(Utc::now());
+    let created = widgets.create(builder).await.expect("create failed");
+    let patch = Widget::patch_builder().entity_id(&created.id).name("Renamed".to_string()).price(20);
+    widgets.update(patch).await.expect("update failed");
+    widgets.delete(&created.id).await.expect("delete failed");
+
+    let entries = client.audit::<Widget>(&created.id).list(..).await.expect("audit list failed");
+    let actions: Vec<_> = entries.iter().map(|entry| entry.action).collect();
+    assert_eq!(actions, [AuditAction::Create, AuditAction::Update, AuditAction::Delete]);
+    assert!(entries.iter().all(|entry| entry.actor.as_deref() == Some("user:123")));
+    assert!(entries[1].fields.contains(&"name".to_string()) && entries[1].fields.contains(&"price".to_string()));
+
+    let later = entries[2].at + chrono::Duration::milliseconds(1);
+    assert!(client.audit::<Widget>(&created.id).list(later..).await.expect("audit list failed").is_empty());
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_unit_of_work() {
+    let client = create_test_client().await.with_actor("user:7");
+    let mut widgets = client.collection::<Widget>();
+    let stale = widgets
+        .create(
+            Widget::validation_builder()
+                .name("Stale".to_string())
+                .category("uow".to_string())
+                .price(1)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+
+    let mut work = client.unit_of_work();
+    let widget_id = work
+        .create(
+            Widget::validation_builder()
+                .name("Staged".to_string())
+                .category("uow".to_string())
+                .price(2)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("stage create failed");
+    work.create(
+        Gadget::validation_builder()
+            .name("Part".to_string())
+            .widget_id(widget_id.clone())
+            .created_at(Utc::now()),
+    )
+    .await
+    .expect("stage gadget failed");
+    work.update(Widget::patch_builder().entity_id(&stale.id).price(3))
+        .await
+        .expect("stage update failed");
+    work.delete::<Widget>(&stale.id, None).expect("stage delete failed");
+    assert_eq!(work.len(), 4);
+    assert!(!widgets.exists(&widget_id).await.expect("exists failed"));
+
+    let responses = work.commit().await.expect("commit failed");
+    assert_eq!(responses.len(), 4);
+    assert_eq!(widgets.get(&widget_id).await.expect("get failed").expect("staged widget").name, "Staged");
+    assert!(!widgets.exists(&stale.id).await.expect("exists failed"));
+    let entries = client.audit::<Widget>(&widget_id).list(..).await.expect("audit list failed");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].actor.as_deref(), Some("user:7"));
+
+    let mut work = client.unit_of_work();
+    work.update(Widget::patch_builder().entity_id(&stale.id).price(4))
+        .await
+        .expect_err("deleted widget cannot be staged");
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_touch_and_refresh() {
+    let client = create_test_client().await;
+    let mut widgets = client.collection::<Widget>();
+    let created = widgets
+        .create(
+            Widget::validation_builder()
+                .name("Fresh".to_string())
+                .category("refresh".to_string())
+                .price(1)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+    let mut widget = widgets.get_or_error(&created.id).await.expect("get failed");
+
+    widgets.touch(&created.id).await.expect("touch failed");
+    widgets
+        .update(Widget::patch_builder().entity_id(&created.id).expected_version(2).price(7))
+        .await
+        .expect("touch should have bumped the version to 2");
+
+    widgets.refresh(&mut widget).await.expect("refresh failed");
+    assert_eq!(widget.price, 7);
+
+    widgets.delete(&created.id).await.expect("delete failed");
+    let err = widgets.refresh(&mut widget).await.expect_err("deleted widget");
+    assert!(matches!(err, snugom::errors::RepoError::NotFound { .. }));
+    widgets.touch(&created.id).await.expect_err("deleted widget cannot be touched");
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_interceptor() {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = events.clone();
+    let client = create_test_client().await.with_interceptor(move |event: &snugom::CommandEvent<'_>| {
+        seen.lock().unwrap().push((event.operation, event.target.to_string(), event.outcome.is_ok()));
+    });
+    let mut widgets = client.collection::<Widget>();
+    let created = widgets
+        .create(
+            Widget::validation_builder()
+                .name("Watched".to_string())
+                .category("intercept".to_string())
+                .price(3)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+    widgets.get(&created.id).await.expect("get failed");
+
+    let key = format!("{}:test_client:widgets:{}", client.prefix(), created.id);
+    let seen = std::mem::take(&mut *events.lock().unwrap());
+    assert_eq!(seen, vec![("entity_mutation", key.clone(), true), ("get", key, true)]);
+
+    // Pipelined bulk creates and unit of work commits are reported too
+    let builder = |name: &str| {
+        Widget::validation_builder()
+            .name(name.to_string())
+            .category("intercept".to_string())
+            .price(4)
+            .created_at(Utc::now())
+    };
+    let batch = widgets.create_many(vec![builder("First"), builder("Second")]).await.expect("create_many failed");
+    assert_eq!(batch.count, 2);
+    let key = format!("{}:test_client:widgets:{}", client.prefix(), batch.ids[0]);
+    let seen = std::mem::take(&mut *events.lock().unwrap());
+    assert!(seen.contains(&("entity_mutation", key, true)), "{seen:?}");
+
+    let mut work = client.unit_of_work();
+    let staged_id = work.create(builder("Staged")).await.expect("stage create failed");
+    work.commit().await.expect("commit failed");
+    let key = format!("{}:test_client:widgets:{}", client.prefix(), staged_id);
+    let seen = std::mem::take(&mut *events.lock().unwrap());
+    assert!(seen.contains(&("plan", key, true)), "{seen:?}");
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_run_macro() {
+    let client = create_custom_client().await;
+    let stale = client
+        .widgets()
+        .create(
+            Widget::validation_builder()
+                .name("Stale".to_string())
+                .category("run".to_string())
+                .price(1)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+    let stale_id = stale.id.clone();
+    let version = stale.responses[0]["version"].as_u64().expect("version should exist");
+
+    let (widget_id, gadget_id) = snugom::snugom_run!(client, {
+        let widget_id = create Widget {
+            name: "Run".to_string(),
+            category: "run".to_string(),
+            price: 2,
+            created_at: Utc::now(),
+        };
+        let gadget_id = create Gadget {
+            name: "Part".to_string(),
+            widget_id: widget_id.clone(),
+            created_at: Utc::now(),
+        };
+        update Widget(entity_id = &stale_id) {
+            price: 3,
+        };
+        delete Widget(&stale_id, expected_version = version + 1);
+        (widget_id, gadget_id)
+    })
+    .await
+    .expect("run failed");
+
+    let widget = client.widgets().get(&widget_id).await.expect("get failed").expect("created widget");
+    assert_eq!(widget.name, "Run");
+    let gadget = client.gadgets().get(&gadget_id).await.expect("get failed").expect("created gadget");
+    assert_eq!(gadget.widget_id, widget_id);
+    assert!(!client.widgets().exists(&stale_id).await.expect("exists failed"));
+
+    let pattern = format!("{}:*", client.prefix());
+    let _ = snugom::cleanup_pattern(&mut client.connection(), &pattern).await;
+}
+
+#[tokio::test]
+async fn test_client_copy_to_prefix() {
+    let mut client = create_custom_client().await;
+    let created = client
+        .widgets()
+        .create(
+            Widget::validation_builder()
+                .name("Copied".to_string())
+                .category("copy".to_string())
+                .price(5)
+                .created_at(Utc::now()),
+        )
+        .await
+        .expect("create failed");
+
+    let target = format!("test_client_{}", uuid::Uuid::new_v4());
+    let options = snugom::PrefixCopyOptions {
+        remove_source: true,
+        ..Default::default()
+    };
+    let report = client.copy_to_prefix(&target, options).await.expect("copy failed");
+    assert_eq!(report.copied, report.removed);
+    assert!(!client.widgets().exists(&created.id).await.expect("exists failed"));
+
+    let mut moved = TestClient::new(client.connection(), target.clone());
+    let widget = moved.widgets().get(&created.id).await.expect("get failed").expect("widget moved");
+    assert_eq!(widget.name, "Copied");
+    let report = moved.index_health().await.expect("index_health failed");
+    assert!(report.indexes.iter().all(|status| status.info.is_some()));
+
+    let verification = snugom::verify_prefix_copy(&mut moved.connection(), client.prefix(), &target)
+        .await
+        .expect("verify failed");
+    assert_eq!(verification.source_keys, 0);
+    assert!(verification.target_keys >= 1);
+
+    let _ = snugom::cleanup_pattern(&mut moved.connection(), &format!("{target}:*")).await;
+}
+
+#[tokio::test]
+async fn test_client_slow_query_log() {
+    let client = create_test_client().await.with_slow_query_threshold(std::time::Duration::ZERO);
+    let mut widgets = client.collection::<Widget>();
+    widgets.repo().ensure_search_index(&mut client.connection()).await.expect("ensure_search_index failed");
+    assert!(client.slow_queries().is_empty());
+
+    let query = snugom::search::SearchQuery {
+        filter: vec!["category:eq:slow".to_string()],
+        ..Default::default()
+    };
+    widgets.find_many(query).await.expect("find_many failed");
+
+    let slow = client.slow_queries();
+    assert_eq!(slow.len(), 1);
+    assert!(slow[0].index.ends_with("widgets:idx"), "unexpected index {}", slow[0].index);
+    assert!(slow[0].query.contains("@category:{slow}"), "unexpected query {}", slow[0].query);
+    assert_eq!(slow[0].params.page_size, 25);
+
+    cleanup_client(&client).await;
+}
+
+#[tokio::test]
+async fn test_client_index_health() {
+    let mut client = create_custom_client().await;
+
+    let report = client.index_health().await.expect("index_health failed");
+    assert_eq!(report.indexes.len(), 2);
+    assert!(report.indexes.iter().all(|status| status.drift == vec![snugom::search::IndexDrift::Missing]));
+
+    client.ensure_indexes().await.expect("ensure_indexes failed");
+    let report = client.index_health().await.expect("index_health failed");
+    assert!(report.is_healthy(), "unexpected drift: {:?}", report.unhealthy().collect::<Vec<_>>());
+
+    let info = client
+        .widgets()
+        .repo()
+        .index_info(&mut client.connection())
+        .await
+        .expect("index_info failed");
+    assert_eq!(info.num_docs, 0);
+    assert!(info.fields.iter().any(|field| field.attribute == "price" && field.sortable));
+
+    for status in &report.indexes {
+        let _: () = redis::cmd("FT.DROPINDEX")
+            .arg(&status.index)
+            .query_async(&mut client.connection())
+            .await
+            .unwrap_or(());
+    }
+}
+
+#[tokio::test]
+async fn test_client_ensure_indexes_report() {
+    use snugom::search::IndexBootOutcome;
+
+    let mut client = create_custom_client().await;
+
+    let report = client.ensure_indexes().await.expect("ensure_indexes failed");
+    assert_eq!(report.indexes.len(), 2);
+    assert_eq!(report.created().count(), 2, "expected both indexes created: {report:?}");
+
+    let report = client.ensure_indexes().await.expect("ensure_indexes failed");
+    assert!(report.is_ok());
+    assert!(
+        report.indexes.iter().all(|status| status.outcome == IndexBootOutcome::Existed { drift: Vec::new() }),
+        "expected both indexes up to date: {report:?}"
+    );
+
+    for status in &report.indexes {
+        let _: () = redis::cmd("FT.DROPINDEX")
+            .arg(&status.index)
+            .query_async(&mut client.connection())
+            .await
+            .unwrap_or(());
+    }
+}
+
+#[tokio::test]
+async fn test_ensure_indexes_fails_when_an_index_does() {
+    use snugom::search::{IndexBootOutcome, SearchEntity};
+
+    let client = create_test_client().await;
+    let mut definition = Widget::index_definition(client.prefix());
+    definition.name = format!("{}:broken:idx", client.prefix());
+    definition.language = Some("klingon".to_string());
+    let definitions = std::slice::from_ref(&definition);
+    let mut conn = client.connection();
+
+    let err = snugom::search::ensure_indexes(&mut conn, definitions).await.expect_err("unknown language");
+    assert!(err.to_string().contains(&definition.name), "{err}");
+    let report = snugom::search::boot_indexes(&mut conn, definitions).await.expect("FT._LIST failed");
+    assert!(!report.is_ok());
+    assert!(matches!(report.indexes[0].outcome, IndexBootOutcome::Failed { .. }), "{report:?}");
+
+    cleanup_client(&client).await;
+}
+
 #[tokio::test]
 async fn test_client_count_where() {
     let mut client = create_custom_client().await;
@@ -1104,3 +1579,38 @@ async fn test_client_update_many_by_ids() {
 
     cleanup_client(&client).await;
 }
+
+// ============ Tests: Cleanup ============
+
+#[tokio::test]
+async fn test_cleanup_pattern_reports_unlinked_keys() {
+    let client = create_test_client().await;
+    let mut widgets = client.collection::<Widget>();
+
+    let builders = (0..5)
+        .map(|i| {
+            Widget::validation_builder()
+                .name(format!("Cleanup {i}"))
+                .category("cleanup".to_string())
+                .price(i)
+                .created_at(Utc::now())
+        })
+        .collect();
+    let bulk_result = widgets.create_many(builders).await.expect("create_many failed");
+    assert_eq!(bulk_result.count, 5);
+
+    let pattern = format!("{}:*", client.prefix());
+    let options = snugom::CleanupOptions {
+        chunk_size: 2,
+        parallelism: 2,
+        ..snugom::CleanupOptions::default()
+    };
+    let report = snugom::cleanup_pattern_with(&mut client.connection(), &pattern, options)
+        .await
+        .expect("cleanup failed");
+    assert!(report.deleted >= 5);
+    assert!(report.scanned >= report.deleted);
+
+    let again = snugom::cleanup_pattern(&mut client.connection(), &pattern).await.expect("cleanup failed");
+    assert_eq!(again.deleted, 0);
+}
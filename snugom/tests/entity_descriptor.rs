@@ -1,9 +1,10 @@
 use redis::aio::ConnectionManager;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use snugom::{
     SnugomEntity,
-    repository::Repo,
-    runtime::RedisExecutor,
+    repository::{MutationPayloadBuilder, Repo, plan_document_create},
+    runtime::{RedisExecutor, commands::MutationCommand},
     types::{EntityMetadata, RelationKind, ValidationDescriptor, ValidationRule, ValidationScope},
 };
 use tokio::runtime::Runtime;
@@ -110,6 +111,44 @@ fn validation_reports_issues() {
     assert!(err.issues.iter().any(|issue| issue.field == "summary"));
 }
 
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "events")]
+struct Event {
+    #[snugom(id)]
+    id: String,
+    #[snugom(validate(length(min = 3)))]
+    name: String,
+    #[snugom(datetime)]
+    starts_at: chrono::DateTime<chrono::Utc>,
+    #[snugom(created_at)]
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[test]
+fn document_create_runs_descriptor_normalization() {
+    let descriptor = Event::entity_descriptor();
+    let document = json!({"id": "event-1", "name": "Launch", "starts_at": "2024-05-01T10:00:00Z"});
+    let (entity_id, plan) = plan_document_create(&descriptor, "app", document, true).expect("valid document");
+    assert_eq!(entity_id, "event-1");
+    let MutationCommand::UpsertEntity(mutation) = &plan.commands[0] else {
+        panic!("expected an entity upsert");
+    };
+    assert_eq!(mutation.key, "app:tl:events:event-1");
+    assert!(mutation.if_absent);
+    let starts_at = mutation.datetime_mirrors.iter().find(|mirror| mirror.field == "starts_at").unwrap();
+    assert_eq!(starts_at.value, Some(1_714_557_600_000));
+    let created_at = mutation.datetime_mirrors.iter().find(|mirror| mirror.field == "created_at").unwrap();
+    assert!(created_at.value.is_some());
+
+    let err = plan_document_create(&descriptor, "app", json!({"id": "event-2", "name": "no"}), false)
+        .expect_err("invalid document");
+    let snugom::RepoError::Validation(err) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    assert!(err.issues.iter().any(|issue| issue.field == "name"));
+    assert!(err.issues.iter().any(|issue| issue.field == "starts_at"));
+}
+
 async fn redis_conn() -> ConnectionManager {
     let client = redis::Client::open("redis://127.0.0.1/").expect("redis client");
     client.get_connection_manager().await.expect("connection manager")
@@ -281,3 +320,654 @@ fn cascade_depth_limit_is_enforced() {
         }
     });
 }
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "wallets", lua_check = "tests/lua/non_negative_balance.lua")]
+struct Wallet {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable, sortable)]
+    balance: i64,
+}
+
+#[test]
+fn descriptor_embeds_lua_check_snippet() {
+    let descriptor = Wallet::entity_descriptor();
+    let snippet = descriptor.lua_check.expect("lua_check snippet");
+    assert!(snippet.contains("balance cannot be negative"));
+    assert!(Article::entity_descriptor().lua_check.is_none());
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "players")]
+#[snugom(has_many_through(alias = "teams", through = "memberships", source = "team"))]
+struct Player {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    name: String,
+    #[snugom(relation(target = "memberships"))]
+    memberships: Vec<String>,
+}
+
+#[test]
+fn descriptor_contains_through_relations() {
+    let descriptor = Player::entity_descriptor();
+    assert_eq!(descriptor.through_relations.len(), 1);
+    let teams = &descriptor.through_relations[0];
+    assert_eq!(teams.alias, "teams");
+    assert_eq!(teams.through, "memberships");
+    assert_eq!(teams.source, "team");
+}
+
+/// A stored payment method.
+///
+/// Cards are tokenized before they reach Redis.
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "cards")]
+struct Card {
+    #[snugom(id)]
+    id: String,
+    /// Last four digits shown to the customer
+    #[snugom(filterable(tag))]
+    last4: String,
+    brand: String,
+}
+
+#[test]
+fn descriptor_captures_doc_comments() {
+    let descriptor = Card::entity_descriptor();
+    assert_eq!(
+        descriptor.description.as_deref(),
+        Some("A stored payment method.\n\nCards are tokenized before they reach Redis.")
+    );
+    let field = |name: &str| descriptor.fields.iter().find(|field| field.name == name).expect("field");
+    assert_eq!(field("last4").description.as_deref(), Some("Last four digits shown to the customer"));
+    assert!(field("brand").description.is_none());
+    assert!(Wallet::entity_descriptor().description.is_none());
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "blogs")]
+struct Blog {
+    #[snugom(id)]
+    id: String,
+    post_count: u64,
+    #[snugom(relation(has_many, target = "posts", counter = "post_count"))]
+    posts: Vec<String>,
+}
+
+#[test]
+fn descriptor_records_relation_counter() {
+    let descriptor = Blog::entity_descriptor();
+    let posts = descriptor.relations.iter().find(|relation| relation.alias == "posts").expect("posts relation");
+    assert_eq!(posts.counter.as_deref(), Some("post_count"));
+
+    // The counter is managed, so builders do not require it.
+    let payload = Blog::validation_builder().into_payload().expect("counter defaults to zero");
+    assert_eq!(payload.payload["post_count"], 0);
+}
+
+/// A member profile with a heavy bio.
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "members", view(Summary = [id, handle, nickname], Card = [handle]))]
+struct Member {
+    #[snugom(id)]
+    id: String,
+    /// Public handle
+    #[snugom(filterable(tag))]
+    handle: String,
+    nickname: Option<String>,
+    bio: String,
+}
+
+#[test]
+fn views_generate_partial_structs() {
+    use snugom::ViewModel;
+
+    assert_eq!(<MemberSummary as ViewModel<Member>>::fields(), &["id", "handle", "nickname"]);
+    assert_eq!(<MemberCard as ViewModel<Member>>::fields(), &["handle"]);
+
+    // Optional members default when a projection leaves them out
+    let summary: MemberSummary = serde_json::from_value(serde_json::json!({ "id": "m1", "handle": "ada" })).unwrap();
+    assert_eq!(summary.id, "m1");
+    assert_eq!(summary.handle, "ada");
+    assert!(summary.nickname.is_none());
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "scoreboards")]
+struct Scoreboard {
+    #[snugom(id)]
+    id: String,
+    score: i64,
+    rating: f64,
+}
+
+#[test]
+fn update_dsl_emits_increments() {
+    use snugom::repository::PatchOpKind;
+
+    let delta = 2;
+    let patch = snugom::snug! {
+        Scoreboard(entity_id = "s1".to_string()) {
+            score += 5,
+            rating -= 0.5,
+            score += delta,
+        }
+    }
+    .build_patch()
+    .expect("increments build a patch");
+
+    let increments: Vec<(&str, f64)> = patch
+        .operations
+        .iter()
+        .map(|op| match op.kind {
+            PatchOpKind::Increment(by) => (op.path.as_str(), by),
+            ref other => panic!("expected increment, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(increments, vec![("$.score", 5.0), ("$.rating", -0.5), ("$.score", 2.0)]);
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "bookmarks")]
+struct Bookmark {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    tags: Vec<String>,
+    visits: Vec<u32>,
+}
+
+#[test]
+fn update_dsl_emits_array_operations() {
+    use snugom::repository::PatchOpKind;
+
+    let patch = snugom::snug! {
+        Bookmark(entity_id = "b1".to_string()) {
+            tags: push("rust", "redis"),
+            tags: remove("draft"),
+            visits: push(3u32),
+        }
+    }
+    .build_patch()
+    .expect("array operations build a patch");
+
+    let operations: Vec<(&str, &PatchOpKind)> = patch.operations.iter().map(|op| (op.path.as_str(), &op.kind)).collect();
+    assert_eq!(operations.len(), 4);
+    assert!(matches!(operations[0], ("$.tags", PatchOpKind::ArrayAppend(values)) if values == &[serde_json::json!("rust")]));
+    assert!(matches!(operations[1], ("$.tags", PatchOpKind::ArrayAppend(values)) if values == &[serde_json::json!("redis")]));
+    assert!(matches!(operations[2], ("$.tags", PatchOpKind::ArrayRemove(values)) if values == &[serde_json::json!("draft")]));
+    assert!(matches!(operations[3], ("$.visits", PatchOpKind::ArrayAppend(values)) if values == &[serde_json::json!(3)]));
+}
+
+#[derive(Debug, SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "customers")]
+struct Customer {
+    #[snugom(id(prefix = "cus"))]
+    id: String,
+    name: String,
+}
+
+#[test]
+fn id_prefix_is_generated_and_validated() {
+    let descriptor = Customer::entity_descriptor();
+    assert_eq!(descriptor.id_prefix.as_deref(), Some("cus"));
+
+    let customer = Customer::validation_builder().name("Ada".to_string()).build().expect("generated id");
+    assert!(customer.id.starts_with("cus_"));
+
+    let err = Customer::validation_builder()
+        .id("usr_1".to_string())
+        .name("Ada".to_string())
+        .build()
+        .expect_err("foreign prefix");
+    assert_eq!(err.issues[0].field, "id");
+    assert_eq!(err.issues[0].code, "validation.id_prefix");
+
+    let (entity_id, _) = plan_document_create(&descriptor, "app", json!({"id": "cus_1", "name": "Ada"}), false)
+        .expect("prefixed id");
+    assert_eq!(entity_id, "cus_1");
+    let err = plan_document_create(&descriptor, "app", json!({"id": "1", "name": "Ada"}), false)
+        .expect_err("unprefixed id");
+    assert!(matches!(err, snugom::RepoError::Validation(err) if err.issues[0].code == "validation.id_prefix"));
+}
+
+#[derive(Debug, SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "bookings")]
+#[snugom(validate(custom = "check_booking_nights"))]
+struct Booking {
+    #[snugom(id)]
+    id: String,
+    #[snugom(validate(custom = "check_room"))]
+    room: String,
+    #[snugom(validate(custom = "check_room"))]
+    backup_room: Option<String>,
+    #[snugom(validate(each = "custom(check_guest)"))]
+    guests: Vec<String>,
+    nights: u32,
+}
+
+fn check_room(room: &str) -> snugom::errors::ValidationResult<()> {
+    if room.starts_with('R') {
+        Ok(())
+    } else {
+        Err(snugom::errors::ValidationError::single("room", "validation.custom", "room must start with R"))
+    }
+}
+
+fn check_guest(guest: &str) -> snugom::errors::ValidationResult<()> {
+    if guest.is_empty() {
+        Err(snugom::errors::ValidationError::single("guests", "validation.custom", "guest name is required"))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_booking_nights(booking: &Booking) -> snugom::errors::ValidationResult<()> {
+    if booking.nights > booking.guests.len() as u32 * 7 {
+        Err(snugom::errors::ValidationError::single("nights", "validation.custom", "at most a week per guest"))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_validators_run_against_json() {
+    let descriptor = Booking::entity_descriptor();
+    let room = descriptor.fields.iter().find(|field| field.name == "room").unwrap();
+    let ValidationRule::Custom { path, check } = &room.validations[0].rule else {
+        panic!("expected a custom rule");
+    };
+    assert_eq!(path, "check_room");
+    assert!(check(&json!("R101")).is_ok());
+    assert_eq!(check(&json!("101")).unwrap_err().issues[0].code, "validation.custom");
+    assert_eq!(check(&json!(101)).unwrap_err().issues[0].code, "validation.invalid_type");
+    assert_eq!(descriptor.custom_validators[0].path, "check_booking_nights");
+
+    let document = json!({"id": "b1", "room": "R1", "backup_room": null, "guests": ["Ada"], "nights": 3});
+    plan_document_create(&descriptor, "app", document, false).expect("valid booking");
+
+    let issues = |document| match plan_document_create(&descriptor, "app", document, false) {
+        Err(snugom::RepoError::Validation(err)) => {
+            err.issues.into_iter().map(|issue| issue.field).collect::<Vec<_>>()
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    };
+    let document = json!({"id": "b2", "room": "1", "backup_room": "2", "guests": ["Ada", ""], "nights": 3});
+    assert_eq!(issues(document), ["room", "room", "guests[1]"]);
+    // Entity validators run once the fields are valid
+    let document = json!({"id": "b3", "room": "R1", "guests": ["Ada"], "nights": 8});
+    assert_eq!(issues(document), ["nights"]);
+
+    let booking = Booking {
+        id: "b4".to_string(),
+        room: "R1".to_string(),
+        backup_room: None,
+        guests: Vec::new(),
+        nights: 1,
+    };
+    assert_eq!(booking.validate().unwrap_err().issues[0].field, "nights");
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "invoices")]
+#[snugom(before_update = "recompute_total", after_update = "audit_invoice")]
+struct Invoice {
+    #[snugom(id)]
+    id: String,
+    lines: Vec<u64>,
+    total: u64,
+}
+
+fn recompute_total(invoice: &mut Invoice) {
+    invoice.total = invoice.lines.iter().sum();
+}
+
+fn audit_invoice(_: &Invoice) {}
+
+#[test]
+fn lifecycle_hooks_run_against_json() {
+    let hooks = Invoice::entity_descriptor().hooks;
+    assert!(hooks.before_create.is_none() && hooks.after_create.is_none() && hooks.after_update.is_some());
+    let before_update = hooks.before_update.expect("before_update hook");
+
+    let mut document = json!({"id": "i1", "lines": [250, 100], "total": 0, "metadata": {"version": 2}});
+    before_update(&mut document).expect("hook runs");
+    assert_eq!(document, json!({"id": "i1", "lines": [250, 100], "total": 350, "metadata": {"version": 2}}));
+
+    // Documents that are not yet a valid entity are left for validation to report
+    let mut document = json!({"id": "i2", "total": 0});
+    before_update(&mut document).expect("hook skipped");
+    assert_eq!(document, json!({"id": "i2", "total": 0}));
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "vh", collection = "drafts", versioned(keep = 3))]
+struct Draft {
+    #[snugom(id)]
+    id: String,
+    body: String,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "vh", collection = "notes", versioned)]
+struct Note {
+    #[snugom(id)]
+    id: String,
+}
+
+#[test]
+fn versioned_entities_keep_history() {
+    assert_eq!(Draft::entity_descriptor().keep_versions, Some(3));
+    assert_eq!(Note::entity_descriptor().keep_versions, Some(10));
+    assert_eq!(Invoice::entity_descriptor().keep_versions, None);
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "kl", collection = "shipments", key_separator = "/", key_components = ["eu", "west"])]
+struct Shipment {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    carrier: String,
+}
+
+#[test]
+fn entities_override_their_key_layout() {
+    use snugom::search::SearchEntity;
+
+    let keys = Shipment::entity_descriptor().keys;
+    assert_eq!(keys.separator, Some('/'));
+    assert_eq!(keys.components, vec!["eu".to_string(), "west".to_string()]);
+    assert_eq!(Repo::<Shipment>::new("app").entity_key("s1"), "app/kl/eu/west/shipments/s1");
+
+    let definition = Shipment::index_definition("app");
+    assert_eq!(definition.name, "app/kl/eu/west/shipments/idx");
+    assert_eq!(definition.prefixes, vec!["app/kl/eu/west/shipments/".to_string()]);
+    assert!(Org::entity_descriptor().keys.components.is_empty());
+}
+
+#[test]
+fn versioned_updates_can_be_restored() {
+    let rt = Runtime::new().expect("runtime");
+    rt.block_on(async {
+        let mut conn = redis_conn().await;
+        let drafts: Repo<Draft> = Repo::new("vh");
+        let draft = drafts
+            .create_with_conn(&mut conn, Draft::validation_builder().body("first".to_string()))
+            .await
+            .expect("create draft");
+        for body in ["second", "third", "fourth", "fifth"] {
+            let patch = snugom::snug! { Draft(entity_id = draft.id.clone()) { body: body.to_string() } };
+            drafts.update_patch_with_conn(&mut conn, patch).await.expect("update draft");
+        }
+
+        let history = drafts.history(&mut conn, &draft.id).await.expect("history");
+        let bodies: Vec<_> = history.iter().map(|version| version.entity.body.as_str()).collect();
+        assert_eq!(bodies, ["fourth", "third", "second"]);
+        assert_eq!(history[2].version, 2);
+
+        drafts.restore_version(&mut conn, &draft.id, 2).await.expect("restore");
+        let restored = drafts.get(&mut conn, &draft.id).await.expect("get").expect("draft exists");
+        assert_eq!(restored.body, "second");
+        let history = drafts.history(&mut conn, &draft.id).await.expect("history");
+        assert_eq!(history[0].entity.body, "fifth");
+
+        let err = drafts.restore_version(&mut conn, &draft.id, 1).await.expect_err("trimmed version");
+        assert!(matches!(err, snugom::RepoError::NotFound { .. }));
+    });
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "vh", collection = "binders")]
+struct Binder {
+    #[snugom(id)]
+    id: String,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "vh", collection = "sheets", versioned)]
+struct Sheet {
+    #[snugom(id)]
+    id: String,
+    #[snugom(relation(target = "binders", cascade = "delete"))]
+    binder_id: String,
+    body: String,
+}
+
+#[test]
+fn deletes_drop_version_history() {
+    let rt = Runtime::new().expect("runtime");
+    rt.block_on(async {
+        let mut conn = redis_conn().await;
+        let drafts: Repo<Draft> = Repo::new("vh");
+        let draft = drafts
+            .create_with_conn(&mut conn, Draft::validation_builder().body("first".to_string()))
+            .await
+            .expect("create draft");
+        let patch = snugom::snug! { Draft(entity_id = draft.id.clone()) { body: "second".to_string() } };
+        drafts.update_patch_with_conn(&mut conn, patch).await.expect("update draft");
+        let history_key = drafts.history_key(&draft.id);
+        let exists: bool = redis::cmd("EXISTS").arg(&history_key).query_async(&mut conn).await.expect("exists");
+        assert!(exists);
+
+        drafts.delete_with_conn(&mut conn, &draft.id, None).await.expect("delete draft");
+        let exists: bool = redis::cmd("EXISTS").arg(&history_key).query_async(&mut conn).await.expect("exists");
+        assert!(!exists, "history outlived its draft");
+
+        // Cascade deletes drop the history of the entities they reach
+        let binders: Repo<Binder> = Repo::new("vh");
+        let sheets: Repo<Sheet> = Repo::new("vh");
+        let binder = binders.create_with_conn(&mut conn, Binder::validation_builder()).await.expect("create binder");
+        let sheet = sheets
+            .create_with_conn(
+                &mut conn,
+                Sheet::validation_builder().binder_id(binder.id.clone()).body("draft".to_string()),
+            )
+            .await
+            .expect("create sheet");
+        let patch = snugom::snug! { Sheet(entity_id = sheet.id.clone()) { body: "final".to_string() } };
+        sheets.update_patch_with_conn(&mut conn, patch).await.expect("update sheet");
+        assert_eq!(sheets.history(&mut conn, &sheet.id).await.expect("history").len(), 1);
+
+        binders.delete_with_conn(&mut conn, &binder.id, None).await.expect("delete binder");
+        assert!(sheets.get(&mut conn, &sheet.id).await.expect("get").is_none());
+        let exists: bool = redis::cmd("EXISTS")
+            .arg(sheets.history_key(&sheet.id))
+            .query_async(&mut conn)
+            .await
+            .expect("exists");
+        assert!(!exists, "history outlived its cascaded sheet");
+    });
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "asyncv", collection = "templates")]
+struct Template {
+    #[snugom(id)]
+    id: String,
+    published: bool,
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "asyncv", collection = "pages")]
+#[snugom(validate(async_custom = "PublishedTemplate"))]
+struct Page {
+    #[snugom(id)]
+    id: String,
+    #[snugom(validate(async_custom = "PublishedTemplate"))]
+    template_id: String,
+    title: String,
+}
+
+struct PublishedTemplate;
+
+impl snugom::validators::AsyncValidator<String> for PublishedTemplate {
+    async fn validate(conn: &mut ConnectionManager, template_id: &String) -> snugom::errors::ValidationResult<()> {
+        let template = Repo::<Template>::new("asyncv").get(conn, template_id).await.ok().flatten();
+        match template {
+            Some(template) if template.published => Ok(()),
+            _ => Err(snugom::errors::ValidationError::single(
+                "template_id",
+                "validation.template",
+                "template must exist and be published",
+            )),
+        }
+    }
+}
+
+impl snugom::validators::AsyncValidator<Page> for PublishedTemplate {
+    async fn validate(_conn: &mut ConnectionManager, page: &Page) -> snugom::errors::ValidationResult<()> {
+        if page.title.is_empty() {
+            Err(snugom::errors::ValidationError::single("title", "validation.required", "title is required"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn async_validators_are_described() {
+    let descriptor = Page::entity_descriptor();
+    let validators: Vec<_> = descriptor
+        .async_validators
+        .iter()
+        .map(|validator| (validator.field.as_deref(), validator.path.as_str()))
+        .collect();
+    assert_eq!(validators, [(Some("template_id"), "PublishedTemplate"), (None, "PublishedTemplate")]);
+}
+
+#[test]
+fn async_validators_run_on_create_and_patch() {
+    let rt = Runtime::new().expect("runtime");
+    rt.block_on(async {
+        let mut conn = redis_conn().await;
+        let mut executor = RedisExecutor::new(&mut conn);
+        let templates: Repo<Template> = Repo::new("asyncv");
+        let draft = templates
+            .create(&mut executor, Template::validation_builder().published(false))
+            .await
+            .expect("draft template");
+        let live = templates
+            .create(&mut executor, Template::validation_builder().published(true))
+            .await
+            .expect("published template");
+
+        let pages: Repo<Page> = Repo::new("asyncv");
+        let err = pages
+            .create_with_conn(
+                &mut conn,
+                Page::validation_builder().template_id(draft.id.clone()).title("Home".to_string()),
+            )
+            .await
+            .expect_err("draft template");
+        assert!(matches!(err, snugom::RepoError::Validation(err) if err.issues[0].code == "validation.template"));
+
+        let page = pages
+            .create_with_conn(
+                &mut conn,
+                Page::validation_builder().template_id(live.id.clone()).title("Home".to_string()),
+            )
+            .await
+            .expect("published template");
+
+        let patch = snugom::snug! { Page(entity_id = page.id.clone()) { template_id: draft.id.clone() } };
+        let err = pages.update_patch_with_conn(&mut conn, patch).await.expect_err("draft template");
+        assert!(matches!(err, snugom::RepoError::Validation(err) if err.issues[0].field == "template_id"));
+    });
+}
+
+#[test]
+fn async_validators_run_on_create_many() {
+    let rt = Runtime::new().expect("runtime");
+    rt.block_on(async {
+        let mut conn = redis_conn().await;
+        let mut executor = RedisExecutor::new(&mut conn);
+        let templates: Repo<Template> = Repo::new("asyncv");
+        let draft = templates
+            .create(&mut executor, Template::validation_builder().published(false))
+            .await
+            .expect("draft template");
+        let live = templates
+            .create(&mut executor, Template::validation_builder().published(true))
+            .await
+            .expect("published template");
+
+        let mut pages = snugom::Client::new(conn, "asyncv".to_string()).collection::<Page>();
+        let result = pages
+            .create_many(vec![
+                Page::validation_builder().template_id(live.id.clone()).title("Home".to_string()),
+                Page::validation_builder().template_id(draft.id.clone()).title("About".to_string()),
+            ])
+            .await
+            .expect("create_many");
+        assert_eq!(result.count, 1);
+        let rejected: Vec<_> = result.validation_errors().map(|(index, err)| (index, err.issues[0].code.as_str())).collect();
+        assert_eq!(rejected, [(1, "validation.template")]);
+    });
+}
+
+#[test]
+fn invalid_regex_rejects_every_value() {
+    let descriptor = snugom::types::EntityDescriptor {
+        service: "tl".to_string(),
+        collection: "codes".to_string(),
+        id_field: Some("id".to_string()),
+        fields: vec![snugom::types::FieldDescriptor {
+            name: "code".to_string(),
+            validations: vec![ValidationDescriptor {
+                scope: ValidationScope::Field,
+                rule: ValidationRule::Regex {
+                    pattern: "[A-Z".to_string(),
+                },
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let err = plan_document_create(&descriptor, "app", json!({"id": "c1", "code": "[A-Z"}), false)
+        .expect_err("invalid pattern");
+    assert!(matches!(err, snugom::RepoError::Validation(err) if err.issues[0].code == "validation.regex"));
+    assert!(!snugom::validators::matches_regex("[A-Z", "[A-Z"));
+}
+
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "tl", collection = "orders")]
+struct Order {
+    #[snugom(id)]
+    id: String,
+    discounted: bool,
+    #[snugom(validate(required_if = "*discounted"))]
+    discount_code: Option<String>,
+    #[snugom(validate(forbidden_if = "self.status == \"shipped\""))]
+    cancel_reason: Option<String>,
+    status: String,
+}
+
+#[test]
+fn conditional_rules_run_against_json() {
+    let descriptor = Order::entity_descriptor();
+    let issues = |document| match plan_document_create(&descriptor, "app", document, false) {
+        Ok(_) => Vec::new(),
+        Err(snugom::RepoError::Validation(err)) => {
+            err.issues.into_iter().map(|issue| (issue.field, issue.code)).collect::<Vec<_>>()
+        }
+        Err(other) => panic!("expected validation error, got {other:?}"),
+    };
+
+    let document = json!({"id": "o1", "discounted": false, "status": "open"});
+    assert!(issues(document).is_empty());
+    let document = json!({"id": "o2", "discounted": true, "discount_code": "SPRING", "status": "open"});
+    assert!(issues(document).is_empty());
+
+    let document = json!({"id": "o3", "discounted": true, "discount_code": null, "cancel_reason": "late", "status": "shipped"});
+    assert_eq!(
+        issues(document),
+        [
+            ("discount_code".to_string(), "validation.required_if".to_string()),
+            ("cancel_reason".to_string(), "validation.forbidden_if".to_string()),
+        ]
+    );
+}
@@ -162,6 +162,65 @@ async fn get_or_create_enforces_unique_constraint() {
     assert!(result2.is_err(), "should fail due to unique constraint violation");
 }
 
+/// Test that get_or_create_by returns the entity already holding the unique value.
+#[tokio::test]
+async fn get_or_create_by_resolves_unique_value() {
+    let mut conn = redis_conn().await;
+    let ns = TestNamespace::unique();
+    let repo = ns.settings_repo();
+
+    // Created through the regular path, which claims the unique index
+    let original = repo
+        .create_with_conn(
+            &mut conn,
+            TestSettings::validation_builder()
+                .name("by-name".to_string())
+                .value("original".to_string()),
+        )
+        .await
+        .expect("create should succeed");
+
+    let result = repo
+        .get_or_create_by(
+            &mut conn,
+            &["name"],
+            TestSettings::validation_builder()
+                .id(generate_entity_id())
+                .name("by-name".to_string())
+                .value("ignored".to_string()),
+        )
+        .await
+        .expect("get_or_create_by should succeed");
+    assert!(result.was_found());
+    assert_eq!(result.as_inner().id, original.id);
+    assert_eq!(result.as_inner().value, "original");
+
+    let created = repo
+        .get_or_create_by(
+            &mut conn,
+            &["name"],
+            TestSettings::validation_builder()
+                .name("other-name".to_string())
+                .value("new".to_string()),
+        )
+        .await
+        .expect("get_or_create_by should create");
+    assert!(created.was_created());
+
+    // Resolving by a field without a unique constraint is rejected before anything runs
+    let err = repo
+        .get_or_create_by(
+            &mut conn,
+            &["value"],
+            TestSettings::validation_builder()
+                .name("third".to_string())
+                .value("new".to_string()),
+        )
+        .await
+        .expect_err("value is not unique");
+    assert!(matches!(err, snugom::errors::RepoError::InvalidRequest { .. }));
+}
+
 /// Test that concurrent get_or_create calls don't create duplicate entities.
 #[tokio::test]
 async fn get_or_create_prevents_race_condition() {
@@ -0,0 +1,6 @@
+-- Rejects wallets whose balance would drop below zero.
+local doc, previous = ...
+if doc["balance"] ~= nil and doc["balance"] < 0 then
+    return { field = "balance", message = "balance cannot be negative" }
+end
+return true
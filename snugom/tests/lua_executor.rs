@@ -1,3 +1,5 @@
+#![allow(clippy::drop_non_drop)]
+
 use chrono::{DateTime, Utc};
 use redis::{AsyncCommands, aio::ConnectionManager};
 use serde::{Deserialize, Serialize};
@@ -6,9 +8,10 @@ use snugom::{
     SnugomEntity,
     errors::RepoError,
     repository::{RelationPlan, Repo},
+    search::FilterCondition,
     runtime::{
-        RedisExecutor,
-        commands::{MutationCommand, MutationPlan, build_entity_mutation},
+        MutationExecutor, PlanExecution, RedisExecutor,
+        commands::{MutationCommand, MutationPlan, build_entity_delete, build_entity_mutation},
     },
 };
 
@@ -33,6 +36,15 @@ struct ArrayRecord {
     tags: Vec<String>,
 }
 
+#[derive(SnugomEntity, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "lc", collection = "wallets", lua_check = "tests/lua/non_negative_balance.lua")]
+struct WalletRecord {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable, sortable)]
+    balance: i64,
+}
+
 async fn redis_connection() -> ConnectionManager {
     let client = redis::Client::open("redis://127.0.0.1/").expect("redis client");
     client.get_connection_manager().await.expect("connection manager")
@@ -204,6 +216,7 @@ async fn version_conflict_returns_error() {
     let mutation = build_entity_mutation(
         repo.descriptor(),
         key.clone(),
+        repo.key_context().layout(),
         payload.payload,
         payload.mirrors,
         Some(41),
@@ -315,7 +328,7 @@ async fn empty_arrays_survive_assign_and_merge() {
     let stored: Value = serde_json::from_str(&stored_raw).expect("parse stored json");
     let tags = stored
         .as_array()
-        .and_then(|a| a.get(0))
+        .and_then(|a| a.first())
         .and_then(|o| o.get("tags"))
         .and_then(|t| t.as_array())
         .expect("tags array after create");
@@ -344,9 +357,164 @@ async fn empty_arrays_survive_assign_and_merge() {
     let patched: Value = serde_json::from_str(&patched_raw).expect("parse patched json");
     let tags_after = patched
         .as_array()
-        .and_then(|a| a.get(0))
+        .and_then(|a| a.first())
         .and_then(|o| o.get("tags"))
         .and_then(|t| t.as_array())
         .expect("tags array after patch");
     assert!(tags_after.is_empty(), "tags should remain empty array after assign patch");
 }
+
+#[tokio::test]
+async fn lua_check_rejects_create_and_rolls_back_patch() {
+    let mut conn = redis_connection().await;
+    let repo: Repo<WalletRecord> = Repo::new("snug");
+    let key = repo.entity_key("wallet-1");
+    let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+
+    {
+        let mut executor = RedisExecutor::new(&mut conn);
+        let builder = WalletRecord::validation_builder().id(String::from("wallet-1")).balance(-5);
+        let err = repo.create(&mut executor, builder).await.expect_err("negative balance rejected");
+        match err {
+            RepoError::Validation(validation) => {
+                assert_eq!(validation.issues[0].field, "balance");
+                assert_eq!(validation.issues[0].code, "validation.lua_check");
+            }
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+    let exists: bool = conn.exists(&key).await.unwrap();
+    assert!(!exists, "rejected create must not write the document");
+
+    {
+        let mut executor = RedisExecutor::new(&mut conn);
+        let builder = WalletRecord::validation_builder().id(String::from("wallet-1")).balance(10);
+        repo.create(&mut executor, builder).await.expect("create wallet");
+    }
+
+    {
+        let mut executor = RedisExecutor::new(&mut conn);
+        let patch = snugom::snug! {
+            WalletRecord(entity_id = "wallet-1".to_string()) {
+                balance: -1,
+            }
+        };
+        let err = repo.update_patch(&mut executor, patch).await.expect_err("patch rejected");
+        assert!(matches!(err, RepoError::Validation(_)));
+    }
+
+    let balance_raw: String = redis::cmd("JSON.GET")
+        .arg(&key)
+        .arg("$.balance")
+        .query_async(&mut conn)
+        .await
+        .expect("fetch balance");
+    assert_eq!(balance_raw, "[10]", "rejected patch must be rolled back");
+}
+
+#[tokio::test]
+async fn create_if_absent_rejects_existing_key() {
+    let mut conn = redis_connection().await;
+    let repo: Repo<WalletRecord> = Repo::new("strict_create");
+    let key = repo.entity_key("w1");
+    let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+
+    let wallet = |balance: i64| WalletRecord::validation_builder().id(String::from("w1")).balance(balance);
+    let mut executor = RedisExecutor::new(&mut conn);
+    repo.create_if_absent(&mut executor, wallet(10)).await.expect("first create");
+
+    let err = repo.create_if_absent(&mut executor, wallet(20)).await.expect_err("key exists");
+    assert!(matches!(err, RepoError::AlreadyExists { ref entity_id } if entity_id == "w1"));
+
+    // Plain create keeps its overwrite semantics
+    repo.create(&mut executor, wallet(30)).await.expect("overwrite");
+    drop(executor);
+    let balance: String = redis::cmd("JSON.GET").arg(&key).arg("$.balance").query_async(&mut conn).await.unwrap();
+    assert_eq!(balance, "[30]");
+}
+
+#[tokio::test]
+async fn update_patch_if_checks_the_stored_document() {
+    let mut conn = redis_connection().await;
+    let repo: Repo<WalletRecord> = Repo::new("conditional");
+    let key = repo.entity_key("w1");
+    let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+    let builder = WalletRecord::validation_builder().id(String::from("w1")).balance(10);
+    repo.create_with_conn(&mut conn, builder).await.expect("create wallet");
+
+    let withdraw = |balance: i64| snugom::snug! { WalletRecord(entity_id = "w1".to_string()) { balance: balance } };
+    let funded = || FilterCondition::numeric_range("balance", Some(8.0), None);
+    repo.update_patch_if(&mut conn, funded(), withdraw(2)).await.expect("balance is 10");
+
+    let err = repo.update_patch_if(&mut conn, funded(), withdraw(0)).await.expect_err("balance is 2");
+    assert!(matches!(err, RepoError::PreconditionFailed { ref entity_id } if entity_id == "w1"));
+    let balance: String = redis::cmd("JSON.GET").arg(&key).arg("$.balance").query_async(&mut conn).await.unwrap();
+    assert_eq!(balance, "[2]");
+}
+
+#[tokio::test]
+async fn script_registry_loads_every_script() {
+    let mut conn = redis_connection().await;
+    snugom::runtime::scripts::ScriptRegistry::load(&mut conn).await.expect("load scripts");
+    let missing = snugom::runtime::scripts::ScriptRegistry::missing(&mut conn).await.expect("script exists");
+    assert!(missing.is_empty(), "{missing:?}");
+}
+
+#[tokio::test]
+async fn pipelined_and_atomic_plans_return_every_response() {
+    let mut conn = redis_connection().await;
+    let repo: Repo<ArrayRecord> = Repo::new("plan_modes");
+    for mode in [PlanExecution::Pipelined, PlanExecution::Atomic] {
+        let mut plan = MutationPlan::new();
+        for id in ["first", "second", "third"] {
+            let _: () = redis::cmd("DEL").arg(repo.entity_key(id)).query_async(&mut conn).await.unwrap();
+            let builder = ArrayRecord::validation_builder().id(id.to_string()).tags(vec![format!("{mode:?}")]);
+            plan.commands.extend(repo.plan_create(builder).expect("plan create").commands);
+        }
+
+        let mut executor = RedisExecutor::new(&mut conn).with_mode(mode);
+        let responses = executor.execute(plan).await.expect("execute plan");
+        drop(executor);
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|response| response["ok"] == Value::Bool(true)), "{mode:?}");
+        for id in ["first", "second", "third"] {
+            let exists: bool = conn.exists(repo.entity_key(id)).await.expect("exists");
+            assert!(exists, "{mode:?} {id}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn atomic_plans_undo_earlier_writes_when_a_command_fails() {
+    let mut conn = redis_connection().await;
+    let repo: Repo<ArrayRecord> = Repo::new("plan_rollback");
+    for id in ["kept", "staged"] {
+        let _: () = redis::cmd("DEL").arg(repo.entity_key(id)).query_async(&mut conn).await.unwrap();
+    }
+    let kept = ArrayRecord::validation_builder().id("kept".to_string()).tags(vec!["before".to_string()]);
+    repo.create_with_conn(&mut conn, kept).await.expect("create kept");
+
+    let mut plan = MutationPlan::new();
+    let staged = ArrayRecord::validation_builder().id("staged".to_string()).tags(vec!["new".to_string()]);
+    plan.commands.extend(repo.plan_create(staged).expect("plan create").commands);
+    let overwrite = ArrayRecord::validation_builder().id("kept".to_string()).tags(vec!["after".to_string()]);
+    plan.commands.extend(repo.plan_create(overwrite).expect("plan overwrite").commands);
+    let layout = plan.commands[0].layout().clone();
+    plan.commands.push(MutationCommand::DeleteEntity(build_entity_delete(
+        repo.entity_key("kept"),
+        layout,
+        Some(99),
+        Vec::new(),
+        Vec::new(),
+    )));
+
+    let mut executor = RedisExecutor::new(&mut conn).with_mode(PlanExecution::Atomic);
+    let err = executor.execute(plan).await.expect_err("stale version");
+    drop(executor);
+    assert!(matches!(err, RepoError::VersionConflict { expected: Some(99), .. }), "{err:?}");
+
+    let exists: bool = conn.exists(repo.entity_key("staged")).await.expect("exists");
+    assert!(!exists, "the create before the failure is undone");
+    let tags: String = redis::cmd("JSON.GET").arg(repo.entity_key("kept")).arg("$.tags").query_async(&mut conn).await.unwrap();
+    assert_eq!(tags, r#"[["before"]]"#);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "utoipa")]
+
+use serde::{Deserialize, Serialize};
+use snugom::{
+    SearchQuery, SnugomEntity, ValidationError,
+    search::PaginatedResponse,
+};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+#[derive(SnugomEntity, ToSchema, Serialize, Deserialize)]
+#[snugom(schema = 1, service = "docs", collection = "widgets")]
+struct Widget {
+    #[snugom(id)]
+    id: String,
+    #[snugom(filterable(tag))]
+    status: String,
+    #[snugom(filterable, sortable)]
+    unit_price: i64,
+}
+
+#[derive(OpenApi)]
+#[openapi(components(schemas(PaginatedResponse<Widget>, SearchQuery, WidgetFilterField, ValidationError)))]
+struct ApiDoc;
+
+#[test]
+fn openapi_includes_search_and_error_schemas() {
+    let json = ApiDoc::openapi().to_json().expect("openapi json");
+    let doc: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+    let schemas = &doc["components"]["schemas"];
+
+    let page = schemas.as_object().unwrap().keys().find(|name| name.starts_with("PaginatedResponse"));
+    assert!(page.is_some(), "{json}");
+    assert!(schemas["SearchQuery"]["properties"]["filter"].is_object(), "{json}");
+    assert!(schemas["ValidationError"]["properties"]["issues"].is_object(), "{json}");
+    assert_eq!(schemas["WidgetFilterField"]["enum"], serde_json::json!(["status", "unit_price"]));
+    assert_eq!(WidgetFilterField::UnitPrice.as_str(), "unit_price");
+}
+
+#[test]
+fn search_query_documents_query_parameters() {
+    let names: Vec<String> = <SearchQuery as IntoParams>::into_params(|| None).into_iter().map(|param| param.name).collect();
+    for expected in ["page", "page_size", "sort_by", "sort_order", "q", "filter"] {
+        assert!(names.iter().any(|name| name == expected), "missing {expected} in {names:?}");
+    }
+}
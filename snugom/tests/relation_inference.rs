@@ -4,7 +4,7 @@
 //! field attributes instead of container-level attributes.
 
 use serde::{Deserialize, Serialize};
-use snugom::{types::EntityMetadata, SnugomEntity};
+use snugom::{types::EntityMetadata, SnugomClient, SnugomEntity};
 
 /// Example using belongs_to inference from {entity}_id fields
 mod belongs_to_inference {
@@ -104,6 +104,53 @@ mod explicit_target {
     }
 }
 
+/// Example with relations into another service's collection
+mod cross_service {
+    use super::*;
+
+    pub mod billing {
+        use super::*;
+
+        #[derive(SnugomEntity, Serialize, Deserialize)]
+        #[snugom(schema = 1, service = "billing", collection = "invoices")]
+        pub struct Invoice {
+            #[snugom(id)]
+            pub id: String,
+            #[snugom(filterable(tag))]
+            pub status: String,
+        }
+    }
+
+    #[derive(SnugomEntity, Serialize, Deserialize)]
+    #[snugom(schema = 1, service = "shop", collection = "orders")]
+    pub struct Order {
+        #[snugom(id)]
+        pub id: String,
+        #[snugom(filterable(tag))]
+        pub status: String,
+
+        #[snugom(relation(target = "invoices", target_service = "billing", cascade = "delete", model = "billing::Invoice"))]
+        pub invoices: Vec<String>,
+    }
+
+    // Fails to compile unless the generated accessor returns the relation's `model`
+    #[expect(dead_code, reason = "only checked to compile")]
+    async fn order_invoices(
+        orders: &mut snugom::CollectionHandle<Order>,
+    ) -> Result<Vec<billing::Invoice>, snugom::RepoError> {
+        Ok(orders.invoices("o1", snugom::types::RelationQueryOptions::new()).await?.items)
+    }
+
+    // Fails to compile unless Invoice is listed here or in `entities`
+    #[expect(dead_code, reason = "only checked to compile")]
+    #[derive(SnugomClient)]
+    #[snugom_client(entities = [Order], shared = [billing::Invoice])]
+    pub struct ShopClient {
+        conn: snugom::ConnectionManager,
+        prefix: String,
+    }
+}
+
 #[test]
 fn test_belongs_to_inferred_from_field_name() {
     let descriptor = belongs_to_inference::Team::entity_descriptor();
@@ -173,3 +220,21 @@ fn test_default_cascade_is_none() {
     // Default cascade should be None
     assert!(matches!(org_rel.cascade, snugom::types::CascadePolicy::None));
 }
+
+#[test]
+fn test_target_service_on_relation() {
+    let order_desc = cross_service::Order::entity_descriptor();
+
+    let invoices_rel = order_desc.relations.iter()
+        .find(|r| r.alias == "invoices")
+        .expect("should have invoices relation");
+
+    assert_eq!(invoices_rel.target, "invoices");
+    assert_eq!(invoices_rel.target_service.as_deref(), Some("billing"));
+    assert_eq!(cross_service::Order::SERVICE_RELATION_TARGETS, &[("billing", "invoices")]);
+    assert!(cross_service::Order::RELATION_TARGETS.is_empty());
+
+    // Same-service relations leave the service unset
+    let book_desc = explicit_target::Book::entity_descriptor();
+    assert!(book_desc.relations.iter().all(|r| r.target_service.is_none()));
+}
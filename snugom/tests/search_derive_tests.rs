@@ -356,6 +356,16 @@ pub struct AscendingSortEntity {
     pub name: String,
 }
 
+/// Entity whose time-ordered id sorts chronologically
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "ulid_items", default_sort = "id")]
+pub struct UlidEntity {
+    #[snugom(id(prefix = "itm", strategy = "ulid"))]
+    pub id: String,
+
+    pub name: String,
+}
+
 
 // =============================================================================
 // UNIT TESTS - Numeric Fields
@@ -605,6 +615,15 @@ mod enum_tests {
         assert!(matches!(field.field_type, IndexFieldType::Tag));
     }
 
+    #[test]
+    fn test_optional_filterable_indexes_missing_values() {
+        let def = EnumEntity::index_definition("test");
+        let index_missing = |name: &str| def.schema.iter().find(|f| f.field_name == name).unwrap().index_missing;
+        assert!(index_missing("__role_tag"), "optional filterable fields are indexed with INDEXMISSING");
+        assert!(!index_missing("__status_tag"));
+        assert!(!index_missing("internal_status"));
+    }
+
     #[test]
     fn test_enum_sortable_generates_tag_sortable() {
         let def = EnumEntity::index_definition("test");
@@ -689,6 +708,21 @@ mod text_string_tests {
 mod tag_string_tests {
     use super::*;
 
+    #[test]
+    fn test_time_ordered_id_generates_tag_sortable() {
+        let def = UlidEntity::index_definition("test");
+        let id_field = def.schema.iter().find(|f| f.field_name == "id").expect("id should be in schema");
+        assert!(matches!(id_field.field_type, IndexFieldType::Tag));
+        assert!(id_field.sortable, "id should be sortable");
+        assert!(UlidEntity::allowed_sorts().iter().any(|s| s.name == "id"));
+
+        let ids: Vec<String> = (0..5)
+            .map(|_| UlidEntity::validation_builder().name("x".to_string()).build().expect("generated id").id)
+            .collect();
+        assert!(ids.iter().all(|id| id.starts_with("itm_") && id.len() == "itm_".len() + 26));
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
     #[test]
     fn test_filterable_tag_generates_tag_index() {
         let def = TagStringEntity::index_definition("test");
@@ -1065,6 +1099,386 @@ mod error_tests {
     }
 }
 
+// =============================================================================
+// UNIT TESTS - Serde Renames
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[serde(rename_all = "camelCase")]
+#[snugom(schema = 1, service = "test", collection = "renamed_items", default_sort = "-display_name")]
+pub struct RenamedEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(filterable(tag), sortable)]
+    pub display_name: String,
+
+    #[serde(rename = "lvl")]
+    #[snugom(filterable, sortable, validate(range(min = 1, max = 99)))]
+    pub skill_level: u32,
+
+    #[snugom(datetime, filterable, sortable)]
+    pub last_seen_at: DateTime<Utc>,
+
+    #[snugom(filterable)]
+    pub current_status: TestStatus,
+}
+
+mod serde_rename_tests {
+    use super::*;
+    use snugom::types::EntityMetadata;
+
+    fn index_paths() -> Vec<(&'static str, &'static str)> {
+        let def = RenamedEntity::index_definition("test");
+        def.schema.iter().map(|f| (f.field_name, f.path)).collect()
+    }
+
+    #[test]
+    fn test_index_paths_use_serialized_names() {
+        let paths = index_paths();
+        assert!(paths.contains(&("displayName", "$.displayName")));
+        assert!(paths.contains(&("lvl", "$.lvl")));
+        assert!(paths.contains(&("lastSeenAt_ts", "$.lastSeenAt_ts")));
+        assert!(paths.contains(&("__currentStatus_tag", "$.__currentStatus_tag")));
+    }
+
+    #[test]
+    fn test_filters_use_serialized_names() {
+        for (field, values) in [("displayName", vec!["x"]), ("lvl", vec!["1", "5"]), ("currentStatus", vec!["active"])] {
+            let descriptor = snugom::search::FilterDescriptor {
+                field: field.to_string(),
+                operator: if field == "lvl" {
+                    snugom::search::FilterOperator::Range
+                } else {
+                    snugom::search::FilterOperator::Eq
+                },
+                values: values.into_iter().map(str::to_string).collect(),
+            };
+            assert!(RenamedEntity::map_filter(descriptor).is_ok(), "{field} should be filterable");
+        }
+
+        let descriptor = snugom::search::FilterDescriptor {
+            field: "skill_level".to_string(),
+            operator: snugom::search::FilterOperator::Eq,
+            values: vec!["1".to_string()],
+        };
+        assert!(RenamedEntity::map_filter(descriptor).is_err(), "Rust names are not filter names");
+    }
+
+    #[test]
+    fn test_sorts_use_serialized_names() {
+        let names: Vec<_> = RenamedEntity::allowed_sorts().iter().map(|sort| (sort.name, sort.path)).collect();
+        assert!(names.contains(&("displayName", "displayName")));
+        assert!(names.contains(&("lastSeenAt", "lastSeenAt_ts")));
+
+        // default_sort names the Rust field
+        let default = RenamedEntity::default_sort();
+        assert_eq!(default.name, "displayName");
+        assert_eq!(default.default_order, SortOrder::Desc);
+    }
+
+    #[test]
+    fn test_descriptor_and_validation_use_serialized_names() {
+        let descriptor = RenamedEntity::entity_descriptor();
+        let names: Vec<_> = descriptor.fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, ["id", "displayName", "lvl", "lastSeenAt", "currentStatus"]);
+        let mirror = descriptor.fields.iter().find(|field| field.name == "lastSeenAt").unwrap();
+        assert_eq!(mirror.datetime_mirror.as_deref(), Some("lastSeenAt_ts"));
+
+        let entity = RenamedEntity {
+            id: "r1".to_string(),
+            display_name: "Renamed".to_string(),
+            skill_level: 0,
+            last_seen_at: Utc::now(),
+            current_status: TestStatus::Active,
+        };
+        let err = entity.validate().expect_err("level 0 is out of range");
+        assert!(err.issues.iter().any(|issue| issue.field == "lvl"));
+
+        // The stored document uses the same names
+        let json = serde_json::to_value(&entity).unwrap();
+        assert!(json.get("displayName").is_some() && json.get("lvl").is_some());
+    }
+}
+
+// =============================================================================
+// UNIT TESTS - Nested Fields
+// =============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestAddress {
+    pub city: String,
+    pub zip: String,
+    pub floor: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestContact {
+    pub phone: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "nested_items", default_sort = "address.floor")]
+pub struct NestedEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(filterable(tag))]
+    pub name: String,
+
+    #[snugom(flatten(
+        city(filterable(tag), sortable),
+        zip(filterable(tag), alias = "postcode"),
+        floor(filterable(numeric), sortable)
+    ))]
+    pub address: TestAddress,
+
+    #[snugom(flatten(city(searchable)))]
+    pub billing_address: Option<TestAddress>,
+
+    #[serde(flatten)]
+    #[snugom(flatten(phone(indexed(tag))))]
+    pub contact: TestContact,
+}
+
+mod nested_field_tests {
+    use super::*;
+
+    #[test]
+    fn test_index_uses_dotted_paths() {
+        let def = NestedEntity::index_definition("test");
+        let paths: Vec<_> = def.schema.iter().map(|f| (f.field_name, f.path, f.sortable)).collect();
+        assert!(paths.contains(&("address_city", "$.address.city", true)));
+        assert!(paths.contains(&("address_zip", "$.address.zip", false)));
+        assert!(paths.contains(&("address_floor", "$.address.floor", true)));
+        assert!(paths.contains(&("billing_address_city", "$.billing_address.city", false)));
+        // serde(flatten) stores the embedded fields at the document root
+        assert!(paths.contains(&("phone", "$.phone", false)));
+
+        let floor = def.schema.iter().find(|f| f.field_name == "address_floor").unwrap();
+        assert!(matches!(floor.field_type, IndexFieldType::Numeric));
+        assert_eq!(NestedEntity::text_search_fields(), &["billing_address_city"]);
+    }
+
+    #[test]
+    fn test_nested_filters() {
+        let descriptor = snugom::search::FilterDescriptor {
+            field: "address.city".to_string(),
+            operator: snugom::search::FilterOperator::Eq,
+            values: vec!["Lisbon".to_string()],
+        };
+        match NestedEntity::map_filter(descriptor).unwrap() {
+            snugom::search::FilterCondition::TagEquals { field, values } => {
+                assert_eq!(field, "address_city");
+                assert_eq!(values, vec!["Lisbon".to_string()]);
+            }
+            other => panic!("expected a tag filter, got {other:?}"),
+        }
+
+        let descriptor = snugom::search::FilterDescriptor {
+            field: "postcode".to_string(),
+            operator: snugom::search::FilterOperator::Eq,
+            values: vec!["1000".to_string()],
+        };
+        assert!(NestedEntity::map_filter(descriptor).is_ok(), "alias names the nested filter");
+
+        let descriptor = snugom::search::FilterDescriptor {
+            field: "address.floor".to_string(),
+            operator: snugom::search::FilterOperator::Range,
+            values: vec!["1".to_string(), "3".to_string()],
+        };
+        assert!(NestedEntity::map_filter(descriptor).is_ok());
+    }
+
+    #[test]
+    fn test_nested_sorts() {
+        let sorts: Vec<_> = NestedEntity::allowed_sorts().iter().map(|sort| (sort.name, sort.path)).collect();
+        assert_eq!(sorts, vec![("address.city", "address_city"), ("address.floor", "address_floor")]);
+
+        let default = NestedEntity::default_sort();
+        assert_eq!(default.name, "address.floor");
+        assert_eq!(default.default_order, SortOrder::Desc);
+    }
+}
+
+// =============================================================================
+// UNIT TESTS - Casefold Tags
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "casefold_items")]
+pub struct CasefoldEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(filterable(tag, casefold), sortable)]
+    pub email: String,
+
+    #[snugom(filterable(tag, casefold))]
+    pub nickname: Option<String>,
+
+    #[snugom(filterable(tag))]
+    pub code: String,
+}
+
+mod casefold_tests {
+    use super::*;
+    use snugom::types::EntityMetadata;
+
+    fn tag_values(field: &str, values: &[&str]) -> (String, Vec<String>) {
+        let descriptor = snugom::search::FilterDescriptor {
+            field: field.to_string(),
+            operator: snugom::search::FilterOperator::Eq,
+            values: values.iter().map(|value| value.to_string()).collect(),
+        };
+        match CasefoldEntity::map_filter(descriptor).unwrap() {
+            snugom::search::FilterCondition::TagEquals { field, values } => (field, values),
+            other => panic!("expected a tag filter, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_casefold_index_uses_shadow() {
+        let def = CasefoldEntity::index_definition("test");
+        let paths: Vec<_> = def.schema.iter().map(|f| (f.field_name, f.path)).collect();
+        assert!(paths.contains(&("__email_lc", "$.__email_lc")));
+        assert!(paths.contains(&("__nickname_lc", "$.__nickname_lc")));
+        assert!(paths.contains(&("code", "$.code")));
+
+        let sorts: Vec<_> = CasefoldEntity::allowed_sorts().iter().map(|sort| (sort.name, sort.path)).collect();
+        assert_eq!(sorts, vec![("email", "__email_lc")]);
+    }
+
+    #[test]
+    fn test_casefold_filter_lowercases_values() {
+        assert_eq!(
+            tag_values("email", &["Ann@Example.com", "BOB@example.com"]),
+            ("__email_lc".to_string(), vec!["ann@example.com".to_string(), "bob@example.com".to_string()])
+        );
+        // Plain tags keep their values as given
+        assert_eq!(tag_values("code", &["AbC"]), ("code".to_string(), vec!["AbC".to_string()]));
+    }
+
+    #[test]
+    fn test_casefold_descriptor() {
+        let descriptor = CasefoldEntity::entity_descriptor();
+        let casefold: Vec<_> = descriptor
+            .fields
+            .iter()
+            .filter(|field| field.casefold_tag)
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(casefold, ["email", "nickname"]);
+    }
+}
+
+// =============================================================================
+// UNIT TESTS - Ranked Enums
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "ranked_items")]
+pub struct RankedEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(filterable(tag), sortable, enum_order = ["low", "medium", "high", "critical"])]
+    pub priority: Priority,
+}
+
+mod enum_order_tests {
+    use super::*;
+    use snugom::types::EntityMetadata;
+
+    #[test]
+    fn test_enum_order_sorts_through_numeric_shadow() {
+        let def = RankedEntity::index_definition("test");
+        let fields: Vec<_> = def
+            .schema
+            .iter()
+            .map(|f| (f.field_name, f.field_type.as_str(), f.sortable))
+            .collect();
+        assert_eq!(fields, vec![("__priority_tag", "TAG", false), ("__priority_ord", "NUMERIC", true)]);
+
+        let sorts: Vec<_> = RankedEntity::allowed_sorts()
+            .iter()
+            .map(|sort| (sort.name, sort.path, sort.default_order))
+            .collect();
+        assert_eq!(sorts, vec![("priority", "__priority_ord", snugom::search::SortOrder::Asc)]);
+
+        let descriptor = snugom::search::FilterDescriptor {
+            field: "priority".to_string(),
+            operator: snugom::search::FilterOperator::Eq,
+            values: vec!["high".to_string()],
+        };
+        assert!(matches!(
+            RankedEntity::map_filter(descriptor).unwrap(),
+            snugom::search::FilterCondition::TagEquals { field, .. } if field == "__priority_tag"
+        ));
+        assert_eq!(RankedEntity::entity_descriptor().fields[1].enum_order, ["low", "medium", "high", "critical"]);
+    }
+}
+
+// =============================================================================
+// UNIT TESTS - Index Language
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[serde(rename_all = "camelCase")]
+#[snugom(schema = 1, service = "test", collection = "articles", language = "german", language_field = "content_language")]
+pub struct GermanArticle {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(searchable)]
+    pub body: String,
+
+    pub content_language: String,
+}
+
+mod language_tests {
+    use super::*;
+
+    #[test]
+    fn test_language_and_language_field_reach_the_index_definition() {
+        let def = GermanArticle::index_definition("test");
+        assert_eq!(def.language.as_deref(), Some("german"));
+        assert_eq!(def.language_field.as_deref(), Some("contentLanguage"));
+
+        let def = NumericEntity::index_definition("test");
+        assert_eq!(def.language, None);
+        assert_eq!(def.language_field, None);
+    }
+}
+
+// =============================================================================
+// UNIT TESTS - Index Name
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "regional_orders", index_name = "orders_eu:v2")]
+pub struct RegionalOrder {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(filterable(tag))]
+    pub status: String,
+}
+
+mod index_name_tests {
+    use super::*;
+
+    #[test]
+    fn test_index_name_override_keeps_prefix_and_key_layout() {
+        let def = RegionalOrder::index_definition("test");
+        assert_eq!(def.name, "test:orders_eu:v2");
+        assert_eq!(def.prefixes, vec!["test:test:regional_orders:".to_string()]);
+
+        let def = NumericEntity::index_definition("test");
+        assert!(def.name.ends_with(":idx"));
+    }
+}
+
 // =============================================================================
 // INTEGRATION TESTS - Require Redis
 // =============================================================================
@@ -1176,7 +1590,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1238,7 +1652,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1298,7 +1712,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1311,6 +1725,50 @@ mod integration_tests {
         drop_index_if_exists(&mut conn, &format!("{prefix}:itest:items:idx")).await;
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+    #[snugom(schema = 1, service = "itest", collection = "stories", language = "german")]
+    pub struct GermanStory {
+        #[snugom(id)]
+        pub id: String,
+
+        #[snugom(searchable)]
+        pub body: String,
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_integration_search_stems_queries_in_the_index_language() {
+        let mut conn = get_redis_connection().await;
+        let prefix = "search_test_lang";
+
+        cleanup_keys(&mut conn, &format!("{prefix}:itest:stories:*")).await;
+        drop_index_if_exists(&mut conn, &format!("{prefix}:itest:stories:idx")).await;
+
+        let repo: Repo<GermanStory> = Repo::new(prefix.to_string());
+        repo.ensure_search_index(&mut conn).await.expect("Failed to create index");
+
+        for (i, body) in [(1, "Die Kinder spielen"), (2, "Der Hund schläft")].iter() {
+            let builder = GermanStory::validation_builder().id(format!("story-{i}")).body(body.to_string());
+            repo.create_with_conn(&mut conn, builder)
+                .await
+                .expect("create should succeed");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        // German stems both "Kindern" and "Kinder" to "kind"; the English stemmer leaves "kindern" alone
+        let text = snugom::search::build_text_query(Some("Kindern".to_string()), GermanStory::text_search_fields())
+            .expect("text query");
+        let params = snugom::search::SearchParams::new().with_text_query(text);
+        let result = repo.search(&mut conn, params).await.expect("search should succeed");
+
+        let ids: Vec<&str> = result.items.iter().map(|story| story.id.as_str()).collect();
+        assert_eq!(ids, ["story-1"], "the query should be stemmed in German");
+
+        cleanup_keys(&mut conn, &format!("{prefix}:itest:stories:*")).await;
+        drop_index_if_exists(&mut conn, &format!("{prefix}:itest:stories:idx")).await;
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_integration_search_with_sorting() {
@@ -1357,7 +1815,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1420,7 +1878,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1487,7 +1945,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1547,7 +2005,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1572,7 +2030,7 @@ mod integration_tests {
             .with_text_query(
                 IntegrationTestEntity::allowed_sorts(),
                 IntegrationTestEntity::default_sort(),
-                |d| IntegrationTestEntity::map_filter(d),
+                IntegrationTestEntity::map_filter,
                 IntegrationTestEntity::text_search_fields(),
             )
             .expect("valid params");
@@ -1586,6 +2044,53 @@ mod integration_tests {
         drop_index_if_exists(&mut conn, &format!("{prefix}:itest:items:idx")).await;
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_integration_search_all_walks_every_page() {
+        use futures_util::TryStreamExt;
+        use snugom::search::{SearchParams, SearchSort};
+
+        let mut conn = get_redis_connection().await;
+        let prefix = "search_test8";
+
+        cleanup_keys(&mut conn, &format!("{prefix}:itest:items:*")).await;
+        drop_index_if_exists(&mut conn, &format!("{prefix}:itest:items:idx")).await;
+
+        let repo: Repo<IntegrationTestEntity> = Repo::new(prefix.to_string());
+        repo.ensure_search_index(&mut conn).await.expect("Failed to create index");
+
+        let now = chrono::Utc::now();
+        for i in 1..=7 {
+            let builder = IntegrationTestEntity::validation_builder()
+                .id(format!("item-{i:02}"))
+                .name(format!("Item {i:02}"))
+                .score(i * 10)
+                .category("test".to_string())
+                .active(true)
+                .created_at(now);
+            repo.create_with_conn(&mut conn, builder)
+                .await
+                .expect("create should succeed");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        // Pages of 3 take three requests, the last one partial
+        let params = SearchParams::new()
+            .with_sort(Some(SearchSort {
+                field: "score".to_string(),
+                order: SortOrder::Asc,
+            }))
+            .with_page(1, 3);
+        let items: Vec<IntegrationTestEntity> =
+            repo.search_all(&mut conn, params).try_collect().await.expect("search_all should succeed");
+        let scores: Vec<u32> = items.iter().map(|item| item.score).collect();
+        assert_eq!(scores, [10, 20, 30, 40, 50, 60, 70]);
+
+        cleanup_keys(&mut conn, &format!("{prefix}:itest:items:*")).await;
+        drop_index_if_exists(&mut conn, &format!("{prefix}:itest:items:idx")).await;
+    }
+
     // =========================================================================
     // INTEGRATION TESTS - FilterCondition::Or
     // =========================================================================
@@ -1875,6 +2380,8 @@ mod integration_tests {
                 field: "score".to_string(),
                 min: Some(30.0),
                 max: Some(60.0),
+                min_exclusive: false,
+                max_exclusive: false,
             },
         ]);
 
@@ -2045,6 +2552,8 @@ mod integration_tests {
                 field: "score".to_string(),
                 min: Some(80.0),
                 max: None,
+                min_exclusive: false,
+                max_exclusive: false,
             },
         ]);
 
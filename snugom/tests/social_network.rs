@@ -1,3 +1,5 @@
+#![allow(clippy::drop_non_drop)]
+
 #[path = "social_network/cascade_tests.rs"]
 mod cascade_tests;
 #[path = "social_network/create_tests.rs"]
@@ -77,3 +77,38 @@ async fn macro_idempotency_prevents_duplicate_mutation() {
     let values: Vec<String> = serde_json::from_str(&json_str).expect("parse values");
     assert_eq!(values[0], "Idempotent Macro", "display name should remain from first mutation");
 }
+
+#[tokio::test]
+async fn idempotent_replay_returns_original_create_result() {
+    let mut conn = redis_conn().await;
+    let ns = TestNamespace::unique();
+    let users: Repo<UserRecord> = ns.user_repo();
+
+    let status = users.idempotency_status(&mut conn, "user-create-explicit").await.expect("status");
+    assert!(!status.consumed);
+    assert!(status.response.is_none());
+
+    let now = Utc::now();
+    let builder = UserRecord::validation_builder()
+        .id(String::from("explicit-user"))
+        .display_name(String::from("Original"))
+        .created_at(now)
+        .idempotency_key("user-create-explicit");
+    let first = users.create_with_conn(&mut conn, builder).await.expect("first create");
+    assert!(!first.replayed);
+
+    let status = users.idempotency_status(&mut conn, "user-create-explicit").await.expect("status");
+    assert!(status.consumed);
+    assert!(status.expires_at.is_some_and(|expires_at| expires_at > now));
+    assert_eq!(status.response.as_ref().and_then(|response| response["entity_id"].as_str()), Some("explicit-user"));
+
+    // The entity already exists, but the key marks this as a retry rather than a conflict
+    let builder = UserRecord::validation_builder()
+        .id(String::from("explicit-user"))
+        .display_name(String::from("Retried"))
+        .created_at(now)
+        .idempotency_key("user-create-explicit");
+    let second = users.create_with_conn(&mut conn, builder).await.expect("replayed create");
+    assert!(second.replayed);
+    assert_eq!(second.id, first.id);
+}
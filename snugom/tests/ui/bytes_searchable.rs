@@ -0,0 +1,18 @@
+//! Compile-fail test: searchable on a Vec<u8> byte field.
+//! Byte fields are stored as base64, which full-text search cannot use.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: byte fields cannot be searchable
+    #[snugom(searchable)]
+    pub thumbnail: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+//! Compile-fail test: casefold on a non-string field.
+//! Only strings have a lowercase form to shadow.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: casefold requires a String field
+    #[snugom(filterable(tag, casefold))]
+    pub level: u32,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+//! Compile-fail test: a relation into another service must name an entity the client knows.
+
+use serde::{Deserialize, Serialize};
+use snugom::{SnugomClient, SnugomEntity};
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "shop", collection = "orders")]
+pub struct Order {
+    #[snugom(id)]
+    pub id: String,
+    #[snugom(filterable(tag))]
+    pub status: String,
+    #[snugom(relation(target = "invoices", target_service = "billing"))]
+    pub invoices: Vec<String>,
+}
+
+#[derive(SnugomClient)]
+#[snugom_client(entities = [Order])]
+pub struct ShopClient {
+    conn: snugom::ConnectionManager,
+    prefix: String,
+}
+
+fn main() {}
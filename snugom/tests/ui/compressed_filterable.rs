@@ -0,0 +1,18 @@
+//! Compile-fail test: compressed field marked filterable.
+//! Compressed values are opaque blobs, so RediSearch cannot index them.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: compressed fields cannot be indexed
+    #[snugom(compressed, filterable(text))]
+    pub body: String,
+}
+
+fn main() {}
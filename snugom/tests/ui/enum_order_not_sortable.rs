@@ -0,0 +1,24 @@
+//! Compile-fail test: enum_order only affects sorting, so the field must be sortable.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: enum_order without sortable
+    #[snugom(filterable, enum_order = ["low", "high"])]
+    pub priority: Priority,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+//! Compile-fail test: filterable on a nested field without an explicit type.
+//! The derive cannot see the embedded struct's field types to infer one.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Address {
+    pub floor: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: nested fields need filterable(numeric)
+    #[snugom(flatten(floor(filterable)))]
+    pub address: Address,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+//! Compile-fail test: #[snugom(flatten(...))] naming a field the embedded struct doesn't have.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Address {
+    pub city: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: Address has no `town` field
+    #[snugom(flatten(town(filterable(tag))))]
+    pub address: Address,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+//! Compile-fail test: has_many_through must reference a relation declared on the entity.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "players")]
+#[snugom(has_many_through(alias = "teams", through = "memberships", source = "team"))]
+pub struct Player {
+    #[snugom(id)]
+    pub id: String,
+}
+
+fn main() {}
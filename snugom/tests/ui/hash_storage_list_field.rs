@@ -0,0 +1,17 @@
+//! Compile-fail test: list field on a hash-stored entity.
+//! Hash storage keeps one hash field per entity field, so only flat entities qualify.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, storage = "hash")]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: lists cannot be stored as a single hash field
+    pub tags: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+//! Compile-fail test: an id prefix with a separator in it.
+//! The `_` separator is added by the derive, so prefixes are plain alphanumerics.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    // ERROR: prefix must be ASCII alphanumeric
+    #[snugom(id(prefix = "usr_"))]
+    pub id: String,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+//! Compile-fail test: an immutable field that is also refreshed on every patch.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: updated_at changes on every patch
+    #[snugom(updated_at, immutable)]
+    pub updated_at: DateTime<Utc>,
+}
+
+fn main() {}
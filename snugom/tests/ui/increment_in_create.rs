@@ -0,0 +1,22 @@
+//! Compile-fail test: `+=` inside a create.
+//! Increments only make sense against an existing document.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct Counter {
+    #[snugom(id)]
+    pub id: String,
+    pub hits: i64,
+}
+
+fn main() {
+    // ERROR: increments are only supported in updates
+    let _ = snugom::snug! {
+        Counter {
+            hits += 1,
+        }
+    };
+}
@@ -0,0 +1,17 @@
+//! Compile-fail test: a redacted field that cannot deserialize when search leaves it out.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: needs Option<String> or #[serde(default)]
+    #[snugom(redact)]
+    pub password_hash: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+//! Compile-fail test: a relation counter must name a numeric field on the entity.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "test", collection = "authors")]
+pub struct Author {
+    #[snugom(id)]
+    pub id: String,
+    #[snugom(relation(has_many, target = "posts", counter = "post_count"))]
+    pub posts: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+//! Compile-fail test: model on a belongs_to relation.
+//! Only has_many and many_to_many relations get a generated accessor to type.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    // ERROR: model requires a has_many or many_to_many relation
+    #[snugom(relation(model = "Team"))]
+    pub team_id: String,
+}
+
+fn main() {}
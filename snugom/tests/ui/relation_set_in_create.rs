@@ -0,0 +1,24 @@
+//! Compile-fail test: `set` on a relation inside a create.
+//! Only an existing entity has a relation to replace; a create uses `connect`.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, service = "ui", collection = "teams")]
+pub struct Team {
+    #[snugom(id)]
+    pub id: String,
+    #[serde(default)]
+    #[snugom(relation(target = "teams"))]
+    pub members: Vec<String>,
+}
+
+fn main() {
+    // ERROR: `set` is only supported in updates
+    let _ = snugom::snug! {
+        Team {
+            members: [set ["u1".to_string()]],
+        }
+    };
+}
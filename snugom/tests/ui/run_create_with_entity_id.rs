@@ -0,0 +1,27 @@
+//! Compile-fail test: a `create` step in snugom_run! given an entity id.
+//! Patching an existing entity is an `update` step.
+
+use serde::{Deserialize, Serialize};
+use snugom::{Client, SnugomEntity};
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct Counter {
+    #[snugom(id)]
+    pub id: String,
+    pub hits: i64,
+}
+
+async fn stage(client: &Client, id: &str) {
+    // ERROR: `create` takes a plain `Counter { ... }`
+    let _ = snugom::snugom_run!(client, {
+        create Counter(entity_id = id) {
+            hits: 1,
+        };
+    })
+    .await;
+}
+
+fn main() {
+    let _ = stage;
+}
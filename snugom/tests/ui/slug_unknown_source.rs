@@ -0,0 +1,19 @@
+//! Compile-fail test: a slug generated from a field that does not exist.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1)]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    pub title: String,
+
+    // ERROR: there is no `headline` field
+    #[snugom(slug(from = "headline"))]
+    pub slug: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+//! Compile-fail test: the index language must be one RediSearch can stem.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+// ERROR: klingon is not a RediSearch language
+#[snugom(schema = 1, language = "klingon")]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    #[snugom(searchable)]
+    pub body: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+//! Compile-fail test: versioned hash-stored entity.
+//! Previous versions are copied as JSON documents, which hash storage doesn't keep.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+// ERROR: versioned requires JSON storage
+#[snugom(schema = 1, storage = "hash", versioned(keep = 5))]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+//! Compile-fail test: view naming a field the entity does not have.
+
+use serde::{Deserialize, Serialize};
+use snugom::SnugomEntity;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SnugomEntity)]
+#[snugom(schema = 1, view(Summary = [id, title]))]
+pub struct InvalidEntity {
+    #[snugom(id)]
+    pub id: String,
+
+    pub name: String,
+}
+
+fn main() {}
@@ -65,6 +65,31 @@ struct UpsertChildEntity {
     name: String,
 }
 
+static HOOKED_CREATES: AtomicUsize = AtomicUsize::new(0);
+static HOOKED_UPDATES: AtomicUsize = AtomicUsize::new(0);
+
+/// Entity with lifecycle hooks, to check which ones each upsert branch runs.
+#[derive(SnugomEntity, Serialize, Deserialize, Debug, Clone)]
+#[snugom(schema = 1, service = "upsert_test", collection = "hooked")]
+#[snugom(after_create = "count_create", before_update = "trim_status", after_update = "count_update")]
+struct HookedEntity {
+    #[snugom(id)]
+    id: String,
+    status: String,
+}
+
+fn count_create(_: &HookedEntity) {
+    HOOKED_CREATES.fetch_add(1, Ordering::SeqCst);
+}
+
+fn trim_status(entity: &mut HookedEntity) {
+    entity.status = entity.status.trim().to_string();
+}
+
+fn count_update(_: &HookedEntity) {
+    HOOKED_UPDATES.fetch_add(1, Ordering::SeqCst);
+}
+
 // ============================================================================
 // Test Utilities
 // ============================================================================
@@ -691,3 +716,32 @@ async fn upsert_sets_version_on_create() {
     let json: Value = serde_json::from_str(&raw).expect("parse");
     assert_eq!(json[0]["metadata"]["version"], 1);
 }
+
+/// Test that each branch runs the hooks of the write it performed.
+#[tokio::test]
+async fn upsert_runs_the_hooks_of_the_branch_taken() {
+    let mut conn = redis_conn().await;
+    let ns = TestNamespace::unique();
+    let repo: Repo<HookedEntity> = Repo::new(ns.prefix.clone());
+    let id = generate_entity_id();
+    let upsert = |status: &str| {
+        (
+            HookedEntity::validation_builder().id(id.clone()).status(status.to_string()),
+            snugom::snug! { HookedEntity(entity_id = id.clone()) { status: status.to_string() } },
+        )
+    };
+
+    let (create, update) = upsert("new");
+    let result = repo.upsert(&mut conn, create, update).await.expect("upsert creates");
+    assert!(matches!(result, UpsertResult::Created(_)));
+    assert_eq!(HOOKED_CREATES.load(Ordering::SeqCst), 1);
+    assert_eq!(HOOKED_UPDATES.load(Ordering::SeqCst), 0);
+
+    let (create, update) = upsert("  changed  ");
+    let result = repo.upsert(&mut conn, create, update).await.expect("upsert updates");
+    assert!(matches!(result, UpsertResult::Updated(_)));
+    assert_eq!(HOOKED_CREATES.load(Ordering::SeqCst), 1);
+    assert_eq!(HOOKED_UPDATES.load(Ordering::SeqCst), 1);
+    let stored = repo.get(&mut conn, &id).await.expect("fetch").expect("entity exists");
+    assert_eq!(stored.status, "changed", "before_update ran on the update branch");
+}